@@ -0,0 +1,380 @@
+//! Filesystem work kept off the UI thread with `background_io = true`, for a
+//! notes folder on a slow or network filesystem: the scan that reads every
+//! note, the renames and deletes made from the sidebar along with the scan
+//! that follows them, and saving a note. Each job runs on a thread of its own
+//! and is polled from the main loop, as the search index build is. Saves of
+//! the same note are written in the order they were made.
+
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use super::line_ending;
+use super::snapshots;
+use super::state::{FileOpsThen, FileTreeItem, Note};
+
+/// The notes found under the notes folder, indexed by the tree, and the
+/// ones that couldn't be read.
+#[derive(Debug)]
+pub struct Scan {
+    pub tree: Vec<FileTreeItem>,
+    pub notes: Vec<Note>,
+    pub errors: Vec<ScanError>,
+}
+
+/// A note, or a folder of them, the scan couldn't read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl ScanError {
+    fn new(path: PathBuf, error: &io::Error) -> Self {
+        let dangling = fs::symlink_metadata(&path).is_ok_and(|m| m.file_type().is_symlink()) && !path.exists();
+        let message = if dangling { "broken symlink".to_string() } else { error.to_string() };
+        Self { path, message }
+    }
+}
+
+/// Walk `dir` for `.md` notes, reading each onto the end of `notes` and
+/// what can't be read onto `errors`. Hidden folders are left out, and
+/// folders without notes unless `show_empty_dir`. `expanded` has the
+/// folders open in the sidebar. `progress` counts the notes read. Gives up
+/// once `cancel` is set, with what it found so far.
+pub fn scan_dir(
+    dir: &Path,
+    show_empty_dir: bool,
+    expanded: &HashMap<PathBuf, bool>,
+    notes: &mut Vec<Note>,
+    errors: &mut Vec<ScanError>,
+    progress: &AtomicUsize,
+    cancel: &AtomicBool,
+) -> Vec<FileTreeItem> {
+    let mut items = Vec::new();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            errors.push(ScanError::new(dir.to_path_buf(), &e));
+            return items;
+        }
+    };
+    for entry in entries.flatten() {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            if path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.')) {
+                continue;
+            }
+            let children = scan_dir(&path, show_empty_dir, expanded, notes, errors, progress, cancel);
+            if show_empty_dir || has_notes(&children) {
+                let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                let expanded = expanded.get(&path).copied().unwrap_or(false);
+                items.push(FileTreeItem::Folder { name, path, expanded, children });
+            }
+        } else if path.extension().is_some_and(|e| e == "md") {
+            match Note::read(&path) {
+                Ok(note) => {
+                    items.push(FileTreeItem::Note { note_index: notes.len() });
+                    notes.push(note);
+                    progress.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(e) => errors.push(ScanError::new(path, &e)),
+            }
+        }
+    }
+    items
+}
+
+fn has_notes(items: &[FileTreeItem]) -> bool {
+    items.iter().any(|item| match item {
+        FileTreeItem::Note { .. } => true,
+        FileTreeItem::Folder { children, .. } => has_notes(children),
+    })
+}
+
+/// A rename or delete made before the notes folder is read again, see
+/// [`ScanJob::after`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileOp {
+    Rename { from: PathBuf, to: PathBuf },
+    /// A note, or a folder and everything in it
+    Remove(PathBuf),
+}
+
+impl FileOp {
+    pub fn run(&self) -> io::Result<()> {
+        match self {
+            FileOp::Rename { from, to } => fs::rename(from, to),
+            FileOp::Remove(path) if path.is_dir() => fs::remove_dir_all(path),
+            FileOp::Remove(path) => fs::remove_file(path),
+        }
+    }
+}
+
+/// Run `ops` in order, giving how each went. With `stop_at_failure` the first
+/// that fails is the last one tried.
+pub fn run_ops(ops: &[FileOp], stop_at_failure: bool) -> Vec<io::Result<()>> {
+    let mut results = Vec::with_capacity(ops.len());
+    for op in ops {
+        let result = op.run();
+        let failed = result.is_err();
+        if let Err(e) = &result {
+            log::warn!("{:?} failed: {}", op, e);
+        }
+        results.push(result);
+        if failed && stop_at_failure {
+            break;
+        }
+    }
+    results
+}
+
+/// A scan of the notes folder running on its own thread.
+pub struct ScanJob {
+    receiver: Receiver<Scan>,
+    ops_receiver: Receiver<Vec<io::Result<()>>>,
+    /// Notes read so far
+    progress: Arc<AtomicUsize>,
+    cancel: Arc<AtomicBool>,
+    /// The note to select once the scan is in, as at startup
+    pub select: Option<PathBuf>,
+    /// The renames and deletes made before the scan
+    pub ops: Vec<FileOp>,
+    /// What's left to do once the scan is in, after `ops`
+    pub then: Option<FileOpsThen>,
+}
+
+impl ScanJob {
+    pub fn start(root: PathBuf, show_empty_dir: bool, expanded: HashMap<PathBuf, bool>) -> Self {
+        Self::after(Vec::new(), false, root, show_empty_dir, expanded)
+    }
+
+    /// Make the renames and deletes in `ops` as [`run_ops`] does, then scan
+    /// as [`ScanJob::start`] does.
+    pub fn after(
+        ops: Vec<FileOp>,
+        stop_at_failure: bool,
+        root: PathBuf,
+        show_empty_dir: bool,
+        expanded: HashMap<PathBuf, bool>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let (ops_sender, ops_receiver) = mpsc::channel();
+        let progress = Arc::new(AtomicUsize::new(0));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (thread_progress, thread_cancel) = (Arc::clone(&progress), Arc::clone(&cancel));
+        let thread_ops = ops.clone();
+        std::thread::spawn(move || {
+            if !thread_ops.is_empty() {
+                let _span = crate::logging::span("file ops");
+                let _ = ops_sender.send(run_ops(&thread_ops, stop_at_failure));
+            }
+            let _span = crate::logging::span("notes scan");
+            let (mut notes, mut errors) = (Vec::new(), Vec::new());
+            let tree =
+                scan_dir(&root, show_empty_dir, &expanded, &mut notes, &mut errors, &thread_progress, &thread_cancel);
+            log::debug!("scanned {} notes under {}, {} unreadable", notes.len(), root.display(), errors.len());
+            if !thread_cancel.load(Ordering::Relaxed) {
+                let _ = sender.send(Scan { tree, notes, errors });
+            }
+        });
+        Self { receiver, ops_receiver, progress, cancel, select: None, ops, then: None }
+    }
+
+    pub fn notes_read(&self) -> usize {
+        self.progress.load(Ordering::Relaxed)
+    }
+
+    /// Stop the scan; [`ScanJob::poll`] then gives `Some(None)`.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// `None` while scanning, then what the scan found, or `Some(None)` when
+    /// it was cancelled or its thread died.
+    pub fn poll(&self) -> Option<Option<Scan>> {
+        match self.receiver.try_recv() {
+            Ok(scan) => Some(Some(scan)),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(None),
+        }
+    }
+
+    /// How `ops` went, once [`ScanJob::poll`] has given something. Empty if
+    /// the thread died before they were made.
+    pub fn ops_done(&self) -> Vec<io::Result<()>> {
+        self.ops_receiver.try_recv().unwrap_or_default()
+    }
+}
+
+/// A snapshot to take before a save overwrites the note, see [`snapshots::take`].
+pub struct SnapshotRequest {
+    pub root: PathBuf,
+    pub now: DateTime<Local>,
+    pub keep: usize,
+    /// Snapshots are taken of every save, not only ones that change the text
+    pub always: bool,
+}
+
+/// How a background save went.
+pub struct Saved {
+    /// The file's new modified time
+    pub written: io::Result<Option<SystemTime>>,
+    pub snapshot: io::Result<()>,
+}
+
+/// A note being written on its own thread.
+pub struct SaveJob {
+    receiver: Receiver<Saved>,
+    pub path: PathBuf,
+    /// The note's text before, put back if the write fails
+    pub previous: String,
+    /// The text saved
+    pub content: String,
+    /// Disconnects when the write is done, for the next save of the same
+    /// path to wait on. Taken by that save.
+    done: Option<Receiver<()>>,
+}
+
+impl SaveJob {
+    /// Write `bytes` to `path`, after a snapshot of what's there if asked
+    /// for. `content` is the note's text as `bytes` encode it, `previous`
+    /// what it was before the edit. Nothing is written until `after`, an
+    /// earlier save's [`SaveJob::take_done`], disconnects.
+    pub fn start(
+        path: PathBuf,
+        previous: String,
+        content: String,
+        bytes: Vec<u8>,
+        snapshot: Option<SnapshotRequest>,
+        after: Option<Receiver<()>>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let (done_sender, done) = mpsc::channel::<()>();
+        let thread_path = path.clone();
+        let thread_content = content.clone();
+        std::thread::spawn(move || {
+            let _span = crate::logging::span("save");
+            let _done = done_sender;
+            if let Some(after) = after {
+                let _ = after.recv();
+            }
+            let path = thread_path;
+            let snapshot = match snapshot {
+                Some(request) => {
+                    let changed = request.always
+                        || fs::read_to_string(&path).is_ok_and(|disk| line_ending::normalize(disk) != thread_content);
+                    if changed {
+                        snapshots::take(&request.root, &path, request.now, request.keep).map(|_| ())
+                    } else {
+                        Ok(())
+                    }
+                }
+                None => Ok(()),
+            };
+            let written = fs::write(&path, bytes).map(|()| fs::metadata(&path).ok().and_then(|m| m.modified().ok()));
+            if let Err(e) = &written {
+                log::warn!("saving {} failed: {}", path.display(), e);
+            }
+            let _ = sender.send(Saved { written, snapshot });
+        });
+        Self { receiver, path, previous, content, done: Some(done) }
+    }
+
+    /// What a later save of the same path waits on, if no save has taken it yet.
+    pub fn take_done(&mut self) -> Option<Receiver<()>> {
+        self.done.take()
+    }
+
+    /// `None` while writing, then how it went.
+    pub fn poll(&self) -> Option<Saved> {
+        match self.receiver.try_recv() {
+            Ok(saved) => Some(saved),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Saved {
+                written: Err(io::Error::other("the save was interrupted")),
+                snapshot: Ok(()),
+            }),
+        }
+    }
+
+    /// Block until the write is done, for quitting.
+    pub fn wait(self) -> Saved {
+        self.receiver.recv().unwrap_or_else(|_| Saved {
+            written: Err(io::Error::other("the save was interrupted")),
+            snapshot: Ok(()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_support::TempDir;
+
+    #[test]
+    fn test_scan_and_save() {
+        let dir = TempDir::new("background-io");
+        for (file, text) in [("a.md", "a"), ("sub/b.md", "b"), (".hidden/c.md", "c"), ("empty/d.txt", "d")] {
+            fs::create_dir_all(dir.join(file).parent().unwrap()).unwrap();
+            fs::write(dir.join(file), text).unwrap();
+        }
+
+        let job = ScanJob::start(dir.to_path_buf(), false, HashMap::new());
+        let scan = loop {
+            if let Some(scan) = job.poll() {
+                break scan.unwrap();
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        };
+        let mut titles: Vec<&str> = scan.notes.iter().map(|n| n.title.as_str()).collect();
+        titles.sort();
+        assert_eq!(titles, ["a", "b"]);
+        assert_eq!(scan.tree.len(), 2);
+        assert_eq!(job.notes_read(), 2);
+
+        let (mut notes, mut errors) = (Vec::new(), Vec::new());
+        let (progress, cancel) = (AtomicUsize::new(0), AtomicBool::new(true));
+        assert!(scan_dir(&dir, true, &HashMap::new(), &mut notes, &mut errors, &progress, &cancel).is_empty());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.join("gone.md"), dir.join("link.md")).unwrap();
+            let cancel = AtomicBool::new(false);
+            scan_dir(&dir, false, &HashMap::new(), &mut notes, &mut errors, &progress, &cancel);
+            assert_eq!(errors, [ScanError { path: dir.join("link.md"), message: "broken symlink".to_string() }]);
+        }
+
+        let saved = SaveJob::start(dir.join("a.md"), "a".into(), "z".into(), b"z".to_vec(), None, None).wait();
+        assert!(saved.written.unwrap().is_some());
+        assert_eq!(fs::read_to_string(dir.join("a.md")).unwrap(), "z");
+    }
+
+    #[test]
+    fn a_second_save_waits_for_the_first() {
+        let dir = TempDir::new("background-io-order");
+        let path = dir.join("a.md");
+        // Holds the first save back until it's dropped
+        let (hold, held) = mpsc::channel::<()>();
+        let mut first = SaveJob::start(path.clone(), "".into(), "1".into(), b"1".to_vec(), None, Some(held));
+        let second = SaveJob::start(path.clone(), "1".into(), "2".into(), b"2".to_vec(), None, first.take_done());
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        assert!(first.poll().is_none());
+        assert!(second.poll().is_none());
+        assert!(!path.exists());
+
+        drop(hold);
+        assert!(second.wait().written.is_ok());
+        assert!(first.wait().written.is_ok());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "2");
+    }
+}
@@ -0,0 +1,168 @@
+//! `:bulk-rename /pat/rep/`: one substitution run over the names of the
+//! notes marked in the sidebar, or of the ones its filter matches.
+
+use std::path::{Path, PathBuf};
+
+use super::paths;
+use crate::vim::command::SubstituteFlags;
+use crate::vim::substitute::substitute_text;
+
+/// A `:bulk-rename` waiting for confirmation: every note it was run over,
+/// with the name it would get.
+#[derive(Debug, Clone, Default)]
+pub struct BulkRename {
+    pub pattern: String,
+    pub replacement: String,
+    pub renames: Vec<PlannedRename>,
+    /// First row of the preview table on screen
+    pub scroll: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedRename {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub old_name: String,
+    pub new_name: String,
+    /// Why the note keeps its name
+    pub problem: Option<RenameProblem>,
+    /// Wiki links to the note from the vault, updated along with it
+    pub links: usize,
+    /// The names those links reach it by
+    pub link_names: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenameProblem {
+    Unchanged,
+    /// Another note in the batch would get the same name
+    Duplicate,
+    /// A file by the new name is already there
+    Exists,
+    Unsaved,
+    Invalid(String),
+}
+
+impl RenameProblem {
+    pub fn describe(&self) -> &str {
+        match self {
+            RenameProblem::Unchanged => "unchanged",
+            RenameProblem::Duplicate => "duplicate name",
+            RenameProblem::Exists => "already exists",
+            RenameProblem::Unsaved => "unsaved changes",
+            RenameProblem::Invalid(reason) => reason,
+        }
+    }
+}
+
+impl BulkRename {
+    /// The renames that will go ahead.
+    pub fn ready(&self) -> impl Iterator<Item = &PlannedRename> {
+        self.renames.iter().filter(|rename| rename.problem.is_none())
+    }
+
+    /// Links the renames that go ahead will update.
+    pub fn links(&self) -> usize {
+        self.ready().map(|rename| rename.links).sum()
+    }
+
+    /// Renames held back by a clash or a bad name, not just left unchanged.
+    pub fn conflicts(&self) -> usize {
+        self.renames
+            .iter()
+            .filter(|rename| rename.problem.as_ref().is_some_and(|p| *p != RenameProblem::Unchanged))
+            .count()
+    }
+}
+
+/// New names for `notes`, each a path and its note's title, with what keeps
+/// any of them from being renamed. `check_name` vets a new name; `unsaved` is the
+/// note with changes in the editor.
+pub fn plan(
+    notes: &[(PathBuf, String)],
+    pattern: &str,
+    replacement: &str,
+    flags: &SubstituteFlags,
+    unsaved: Option<&Path>,
+    check_name: impl Fn(&str) -> Result<(), String>,
+) -> Vec<PlannedRename> {
+    let mut renames: Vec<PlannedRename> = notes
+        .iter()
+        .map(|(from, old_name)| {
+            let (new_name, _) = substitute_text(old_name, pattern, replacement, flags);
+            let new_name = new_name.trim().to_string();
+            let to = from.with_file_name(format!("{}.md", new_name));
+            let problem = if new_name == *old_name {
+                Some(RenameProblem::Unchanged)
+            } else if let Err(reason) = check_name(&new_name) {
+                Some(RenameProblem::Invalid(reason))
+            } else if unsaved == Some(from.as_path()) {
+                Some(RenameProblem::Unsaved)
+            } else if to.exists() && !paths::same_path(from, &to) {
+                Some(RenameProblem::Exists)
+            } else {
+                None
+            };
+            PlannedRename {
+                from: from.clone(),
+                to,
+                old_name: old_name.clone(),
+                new_name,
+                problem,
+                links: 0,
+                link_names: Vec::new(),
+            }
+        })
+        .collect();
+
+    // Two notes can't both take a name, where case is ignored or not
+    let targets: Vec<String> = renames.iter().map(|rename| rename.to.to_string_lossy().to_lowercase()).collect();
+    for (i, rename) in renames.iter_mut().enumerate() {
+        let clashes = targets.iter().enumerate().any(|(j, other)| j != i && *other == targets[i]);
+        if clashes && rename.problem != Some(RenameProblem::Unchanged) {
+            rename.problem = Some(RenameProblem::Duplicate);
+        }
+    }
+    renames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_support::TempDir;
+    use std::fs;
+
+    #[test]
+    fn test_plan_flags_what_cannot_be_renamed() {
+        let dir = TempDir::new("bulk-rename-plan");
+        let note = |name: &str| {
+            let path = dir.join(format!("{}.md", name));
+            fs::write(&path, "").unwrap();
+            (path, name.to_string())
+        };
+        let notes = [note("draft plan"), note("draft notes"), note("notes"), note("plan"), note("other"), note("draft ideas")];
+        fs::write(dir.join("final ideas.md"), "").unwrap();
+        let check = |name: &str| if name.contains('?') { Err("bad".to_string()) } else { Ok(()) };
+
+        let renames = plan(&notes, "draft ", "", &SubstituteFlags::default(), Some(&notes[5].0), check);
+        let problems: Vec<Option<RenameProblem>> = renames.iter().map(|r| r.problem.clone()).collect();
+        assert_eq!(
+            problems,
+            [
+                Some(RenameProblem::Duplicate),
+                Some(RenameProblem::Duplicate),
+                Some(RenameProblem::Unchanged),
+                Some(RenameProblem::Unchanged),
+                Some(RenameProblem::Unchanged),
+                Some(RenameProblem::Unsaved),
+            ]
+        );
+
+        let renames = plan(&notes[5..], "draft", "final", &SubstituteFlags::default(), None, check);
+        assert_eq!(renames[0].problem, Some(RenameProblem::Exists));
+        let renames = plan(&notes[4..5], "other", "what?", &SubstituteFlags::default(), None, check);
+        assert_eq!(renames[0].problem, Some(RenameProblem::Invalid("bad".to_string())));
+        let renames = plan(&notes[4..5], "o", "O", &SubstituteFlags::default(), None, check);
+        assert_eq!((renames[0].new_name.as_str(), renames[0].problem.clone()), ("Other", None));
+    }
+}
@@ -0,0 +1,139 @@
+//! GitHub and Obsidian style callouts: a blockquote whose first line is
+//! `> [!NOTE]`, `> [!warning] Custom title`, or with `-`/`+` after the
+//! marker, `> [!tip]-`, one that folds like a details block (folded or open
+//! to begin with). Types other than the five GitHub ones and their common
+//! Obsidian names are shown in a generic style.
+
+use ratatui::style::Color;
+
+use crate::config::CalloutTheme;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CalloutKind {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
+    Other,
+}
+
+impl CalloutKind {
+    pub fn from_name(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "note" | "info" | "abstract" | "summary" | "tldr" => Self::Note,
+            "tip" | "hint" | "success" | "check" | "done" => Self::Tip,
+            "important" | "question" | "help" | "faq" => Self::Important,
+            "warning" | "attention" => Self::Warning,
+            "caution" | "danger" | "error" | "bug" | "failure" => Self::Caution,
+            _ => Self::Other,
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Note => "ℹ",
+            Self::Tip => "✦",
+            Self::Important => "❢",
+            Self::Warning => "▲",
+            Self::Caution => "✖",
+            Self::Other => "●",
+        }
+    }
+
+    pub fn color(self, theme: &CalloutTheme) -> Color {
+        match self {
+            Self::Note => theme.note,
+            Self::Tip => theme.tip,
+            Self::Important => theme.important,
+            Self::Warning => theme.warning,
+            Self::Caution => theme.caution,
+            Self::Other => theme.other,
+        }
+    }
+}
+
+/// The first line of a callout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalloutHeader {
+    pub kind: CalloutKind,
+    /// The title after the marker, or else the type as written, capitalized
+    pub title: String,
+    /// For a foldable callout, whether it starts open (`+`) or folded (`-`)
+    pub fold: Option<bool>,
+}
+
+/// The type of a callout's first line and the char column its `[!type]`
+/// marker starts at, for the editor to highlight the marker and title.
+pub fn marker_start(line: &str) -> Option<(CalloutKind, usize)> {
+    let (start, _, name, _) = split_marker(line)?;
+    Some((CalloutKind::from_name(name), line[..start].chars().count()))
+}
+
+/// Byte range of the marker with its fold sign, the type and the fold sign.
+fn split_marker(line: &str) -> Option<(usize, usize, &str, Option<bool>)> {
+    let quoted = line.trim_start().strip_prefix('>')?;
+    let rest = quoted.trim_start();
+    let start = line.len() - rest.len();
+    let name = rest.strip_prefix("[!")?;
+    let close = name.find(']')?;
+    let name_str = &name[..close];
+    if name_str.is_empty() || !name_str.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        return None;
+    }
+    let after = &name[close + 1..];
+    let fold = match after.chars().next() {
+        Some('-') => Some(false),
+        Some('+') => Some(true),
+        _ => None,
+    };
+    let end = start + 2 + close + 1 + usize::from(fold.is_some());
+    Some((start, end, name_str, fold))
+}
+
+pub fn parse_header(line: &str) -> Option<CalloutHeader> {
+    let (_, end, name, fold) = split_marker(line)?;
+    let title = match line[end..].trim() {
+        "" => {
+            let mut chars = name.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect()
+            })
+        }
+        title => title.to_string(),
+    };
+    Some(CalloutHeader { kind: CalloutKind::from_name(name), title, fold })
+}
+
+/// A line inside a quote without its `>` and the space after it, or None
+/// for a line that ends the quote.
+pub fn quoted_text(line: &str) -> Option<&str> {
+    let rest = line.trim_start().strip_prefix('>')?;
+    Some(rest.strip_prefix(' ').unwrap_or(rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_header() {
+        assert_eq!(
+            parse_header("> [!NOTE]"),
+            Some(CalloutHeader { kind: CalloutKind::Note, title: "Note".to_string(), fold: None })
+        );
+        assert_eq!(
+            parse_header(">[!warning]- Mind the gap "),
+            Some(CalloutHeader { kind: CalloutKind::Warning, title: "Mind the gap".to_string(), fold: Some(false) })
+        );
+        let custom = parse_header("> [!recipe]+").unwrap();
+        assert_eq!((custom.kind, custom.title.as_str(), custom.fold), (CalloutKind::Other, "Recipe", Some(true)));
+        assert_eq!(parse_header("> [!] empty"), None);
+        assert_eq!(parse_header("> [link](x)"), None);
+        assert_eq!(parse_header("[!NOTE] not quoted"), None);
+
+        assert_eq!(marker_start("  > [!tip]- Title"), Some((CalloutKind::Tip, 4)));
+        assert_eq!(quoted_text(">  indented"), Some(" indented"));
+        assert_eq!(quoted_text("after"), None);
+    }
+}
@@ -0,0 +1,123 @@
+//! What the terminal turned out to support, so hints only offer keys and
+//! mouse actions that work in it. Without the kitty keyboard protocol a
+//! terminal sends Ctrl+Shift+R as Ctrl+R and Ctrl+M as Enter, so those
+//! chords are shown as the leader sequence doing the same. Ctrl+S and Ctrl+Q
+//! always arrive: raw mode turns the terminal's flow control off.
+
+use super::leader::{LeaderAction, LeaderMap};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Capabilities {
+    /// Keys come with all their modifiers (the kitty keyboard protocol)
+    pub keyboard_enhancement: bool,
+    /// Mouse capture was turned on
+    pub mouse: bool,
+    /// Pastes arrive whole rather than as typed keys
+    pub bracketed_paste: bool,
+    /// The terminal reports focus changes, for reloading on focus
+    pub focus_events: bool,
+    /// An image protocol answered the startup query
+    pub images: bool,
+}
+
+/// A built-in chord only the kitty keyboard protocol can send.
+struct EnhancedChord {
+    keys: &'static str,
+    /// What a terminal without the protocol sends instead
+    sent_as: &'static str,
+    action: LeaderAction,
+}
+
+const ENHANCED_CHORDS: [EnhancedChord; 2] = [
+    EnhancedChord { keys: "Ctrl+Sh+R", sent_as: "Ctrl+R", action: LeaderAction::ReloadConfig },
+    EnhancedChord { keys: "Ctrl+m", sent_as: "Enter", action: LeaderAction::Frontmatter },
+];
+
+impl Capabilities {
+    fn missing_chords(&self) -> impl Iterator<Item = &'static EnhancedChord> {
+        let enhanced = self.keyboard_enhancement;
+        ENHANCED_CHORDS.iter().filter(move |_| !enhanced)
+    }
+
+    /// How to press `keys` here: the chord itself, or the leader sequence for
+    /// the same action when the terminal can't send it and one is bound.
+    pub fn key_label(&self, keys: &str, leader_key: Option<char>, leader: &LeaderMap) -> String {
+        self.missing_chords()
+            .find(|chord| chord.keys == keys)
+            .and_then(|chord| leader_sequence(chord.action, leader_key, leader))
+            .unwrap_or_else(|| keys.to_string())
+    }
+
+    /// A warning about chords the terminal can't send that no leader
+    /// sequence stands in for, to show once at startup.
+    pub fn unreachable_warning(&self, leader_key: Option<char>, leader: &LeaderMap) -> Option<String> {
+        let unreachable: Vec<String> = self
+            .missing_chords()
+            .filter(|chord| leader_sequence(chord.action, leader_key, leader).is_none())
+            .map(|chord| format!("{} arrives as {}, bind {} under [leader]", chord.keys, chord.sent_as, chord.action.name()))
+            .collect();
+        (!unreachable.is_empty()).then(|| format!("This terminal can't send: {}", unreachable.join("; ")))
+    }
+
+    /// The terminal features that couldn't be turned on, to mention once at
+    /// startup.
+    pub fn degraded_warning(&self) -> Option<String> {
+        let missing: Vec<&str> = [
+            (self.mouse, "mouse capture"),
+            (self.bracketed_paste, "bracketed paste"),
+            (self.focus_events, "focus events"),
+        ]
+        .into_iter()
+        .filter(|&(enabled, _)| !enabled)
+        .map(|(_, name)| name)
+        .collect();
+        (!missing.is_empty()).then(|| format!("Running without {}", missing.join(", ")))
+    }
+}
+
+/// `<leader> keys` for `action`, e.g. `Space rc`.
+fn leader_sequence(action: LeaderAction, leader_key: Option<char>, leader: &LeaderMap) -> Option<String> {
+    let key = match leader_key? {
+        ' ' => "Space".to_string(),
+        c => c.to_string(),
+    };
+    Some(format!("{} {}", key, leader.keys_for(action)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn chords_fall_back_to_leader_sequences() {
+        let defaults = LeaderMap::from_config(&crate::config::Config::default().leader);
+        let legacy = Capabilities { mouse: true, bracketed_paste: true, focus_events: true, ..Capabilities::default() };
+        assert_eq!(legacy.key_label("Ctrl+Sh+R", Some(' '), &defaults), "Space rc");
+        assert_eq!(legacy.key_label("Ctrl+m", Some(','), &defaults), ", fm");
+        assert_eq!(legacy.key_label("Ctrl+k", Some(' '), &defaults), "Ctrl+k");
+        assert_eq!(legacy.unreachable_warning(Some(' '), &defaults), None);
+
+        let kitty = Capabilities { keyboard_enhancement: true, ..legacy };
+        assert_eq!(kitty.key_label("Ctrl+Sh+R", Some(' '), &defaults), "Ctrl+Sh+R");
+
+        let only_save = LeaderMap::from_config(&BTreeMap::from([("w".to_string(), "save".to_string())]));
+        assert_eq!(legacy.key_label("Ctrl+m", Some(' '), &only_save), "Ctrl+m");
+        assert_eq!(
+            legacy.unreachable_warning(Some(' '), &only_save).as_deref(),
+            Some(
+                "This terminal can't send: Ctrl+Sh+R arrives as Ctrl+R, bind reload_config under [leader]; \
+                 Ctrl+m arrives as Enter, bind frontmatter under [leader]"
+            )
+        );
+        assert_eq!(kitty.unreachable_warning(Some(' '), &only_save), None);
+    }
+
+    #[test]
+    fn degraded_features_are_listed() {
+        let full = Capabilities { mouse: true, bracketed_paste: true, focus_events: true, ..Capabilities::default() };
+        assert_eq!(full.degraded_warning(), None);
+        let plain = Capabilities { focus_events: true, ..Capabilities::default() };
+        assert_eq!(plain.degraded_warning().as_deref(), Some("Running without mouse capture, bracketed paste"));
+    }
+}
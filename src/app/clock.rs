@@ -0,0 +1,81 @@
+//! Where the app reads the time from: the system clock, or in tests a
+//! manual one that only moves when told to, so timeouts can be checked
+//! without sleeping.
+
+#[cfg(test)]
+use std::sync::{Arc, Mutex};
+#[cfg(test)]
+use std::time::Duration;
+use std::time::Instant;
+
+use chrono::{DateTime, Local};
+
+#[derive(Debug, Clone, Default)]
+pub enum Clock {
+    #[default]
+    System,
+    /// Shared by its clones, so a test keeps a handle to move the app's clock
+    #[cfg(test)]
+    Manual(Arc<Mutex<ManualTime>>),
+}
+
+#[cfg(test)]
+#[derive(Debug, Clone, Copy)]
+pub struct ManualTime {
+    instant: Instant,
+    local: DateTime<Local>,
+}
+
+impl Clock {
+    /// A clock stopped at `local` until [`Clock::advance`] moves it.
+    #[cfg(test)]
+    pub fn manual(local: DateTime<Local>) -> Self {
+        Self::Manual(Arc::new(Mutex::new(ManualTime { instant: Instant::now(), local })))
+    }
+
+    pub fn now(&self) -> Instant {
+        match self {
+            Self::System => Instant::now(),
+            #[cfg(test)]
+            Self::Manual(time) => time.lock().map_or_else(|e| e.into_inner().instant, |t| t.instant),
+        }
+    }
+
+    /// The wall-clock time, for dates and times written into notes.
+    pub fn local_now(&self) -> DateTime<Local> {
+        match self {
+            Self::System => Local::now(),
+            #[cfg(test)]
+            Self::Manual(time) => time.lock().map_or_else(|e| e.into_inner().local, |t| t.local),
+        }
+    }
+
+    /// Moves a manual clock forward; the system clock moves by itself.
+    #[cfg(test)]
+    pub fn advance(&self, by: Duration) {
+        if let Self::Manual(time) = self {
+            let mut time = time.lock().unwrap_or_else(|e| e.into_inner());
+            time.instant += by;
+            time.local += by;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_manual_clock_moves_only_when_advanced() {
+        let start = Local.with_ymd_and_hms(2024, 5, 1, 9, 30, 0).unwrap();
+        let clock = Clock::manual(start);
+        let handle = clock.clone();
+        let before = clock.now();
+        assert_eq!(clock.now(), before);
+
+        handle.advance(Duration::from_secs(90));
+        assert_eq!(clock.now() - before, Duration::from_secs(90));
+        assert_eq!(clock.local_now().format("%H:%M").to_string(), "09:31");
+    }
+}
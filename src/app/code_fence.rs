@@ -0,0 +1,24 @@
+//! Fenced code blocks, read the way the content view renders them: a line
+//! starting with three backticks is a fence, and each one switches between
+//! text and code. Anything that has to agree with the rendered note about
+//! what is code goes through here.
+
+pub fn is_fence(line: &str) -> bool {
+    line.starts_with("```")
+}
+
+/// The code blocks in `lines` as `(opening row, closing row)`. The last one
+/// has no closing row if its fence is never closed.
+pub fn code_blocks<S: AsRef<str>>(lines: &[S]) -> Vec<(usize, Option<usize>)> {
+    let mut blocks: Vec<(usize, Option<usize>)> = Vec::new();
+    for (row, line) in lines.iter().enumerate() {
+        if !is_fence(line.as_ref()) {
+            continue;
+        }
+        match blocks.last_mut() {
+            Some((_, close @ None)) => *close = Some(row),
+            _ => blocks.push((row, None)),
+        }
+    }
+    blocks
+}
@@ -0,0 +1,103 @@
+//! Paragraph (`{`/`}`) and screen (`H`/`M`/`L`) motions over the content view.
+
+use ratatui::layout::Rect;
+
+/// Screen line targeted by `H`, `M` or `L`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenLine {
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// Position `}` moves to from `from`, where `boundaries[i]` tells whether the
+/// i-th visible item is a blank line or heading: the boundary after the next
+/// paragraph, or the last item.
+pub fn next_paragraph(boundaries: &[bool], from: usize) -> usize {
+    let after = &boundaries[from.min(boundaries.len())..];
+    let skip_boundaries = after.iter().take_while(|&&b| b).count();
+    let skip_paragraph = after
+        .iter()
+        .skip(skip_boundaries)
+        .take_while(|&&b| !b)
+        .count();
+    (from + skip_boundaries + skip_paragraph).min(boundaries.len().saturating_sub(1))
+}
+
+/// Position `{` moves to from `from`: the boundary before the previous
+/// paragraph, or the first item.
+pub fn prev_paragraph(boundaries: &[bool], from: usize) -> usize {
+    let before = &boundaries[..(from + 1).min(boundaries.len())];
+    let skip_boundaries = before.iter().rev().take_while(|&&b| b).count();
+    let skip_paragraph = before
+        .iter()
+        .rev()
+        .skip(skip_boundaries)
+        .take_while(|&&b| !b)
+        .count();
+    from.saturating_sub(skip_boundaries + skip_paragraph)
+}
+
+/// Content item drawn at `line` of the screen, given the item rects from the
+/// last frame. `offset` counts items from the top (`H`) or bottom (`L`), as in
+/// `3H`; it is ignored for `M`.
+pub fn screen_item(rects: &[(usize, Rect)], line: ScreenLine, offset: usize) -> Option<usize> {
+    let last = rects.len().checked_sub(1)?;
+    let pos = match line {
+        ScreenLine::Top => offset.min(last),
+        ScreenLine::Bottom => last - offset.min(last),
+        ScreenLine::Middle => {
+            let top = rects[0].1.y;
+            let bottom = rects[last].1.bottom();
+            let mid = top + (bottom - top) / 2;
+            rects
+                .iter()
+                .position(|(_, r)| mid < r.bottom())
+                .unwrap_or(last)
+        }
+    };
+    Some(rects[pos].0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // text, blank, text, text, blank, blank, text
+    const DOC: [bool; 7] = [false, true, false, false, true, true, false];
+
+    #[test]
+    fn test_next_paragraph() {
+        assert_eq!(next_paragraph(&DOC, 0), 1);
+        assert_eq!(next_paragraph(&DOC, 1), 4);
+        assert_eq!(next_paragraph(&DOC, 4), 6);
+        assert_eq!(next_paragraph(&DOC, 6), 6);
+        assert_eq!(next_paragraph(&[], 0), 0);
+    }
+
+    #[test]
+    fn test_prev_paragraph() {
+        assert_eq!(prev_paragraph(&DOC, 6), 5);
+        assert_eq!(prev_paragraph(&DOC, 5), 1);
+        assert_eq!(prev_paragraph(&DOC, 3), 1);
+        assert_eq!(prev_paragraph(&DOC, 1), 0);
+        assert_eq!(prev_paragraph(&DOC, 0), 0);
+    }
+
+    #[test]
+    fn test_screen_item_uses_rendered_heights() {
+        // Items 10..13 on screen; item 11 is an image 8 rows tall
+        let rects = [
+            (10, Rect::new(0, 1, 40, 1)),
+            (11, Rect::new(0, 2, 40, 8)),
+            (12, Rect::new(0, 10, 40, 1)),
+            (13, Rect::new(0, 11, 40, 1)),
+        ];
+        assert_eq!(screen_item(&rects, ScreenLine::Top, 0), Some(10));
+        assert_eq!(screen_item(&rects, ScreenLine::Top, 2), Some(12));
+        assert_eq!(screen_item(&rects, ScreenLine::Middle, 0), Some(11));
+        assert_eq!(screen_item(&rects, ScreenLine::Bottom, 0), Some(13));
+        assert_eq!(screen_item(&rects, ScreenLine::Bottom, 9), Some(10));
+        assert_eq!(screen_item(&[], ScreenLine::Top, 0), None);
+    }
+}
@@ -0,0 +1,90 @@
+//! What deleting a folder takes with it, shown before the delete is confirmed.
+
+use std::fs;
+use std::path::Path;
+
+/// Folders with more notes than this have to have their name typed to be
+/// deleted, rather than just `y`.
+const TYPE_NAME_ABOVE_NOTES: usize = 10;
+
+/// Everything under a folder, at any depth.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FolderContents {
+    pub notes: usize,
+    pub folders: usize,
+    /// Files that aren't notes, like images and attachments
+    pub other_files: usize,
+}
+
+impl FolderContents {
+    pub fn count(folder: &Path) -> Self {
+        let mut contents = Self::default();
+        contents.add(folder);
+        contents
+    }
+
+    fn add(&mut self, dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // Don't follow symlinked folders; deleting removes only the link
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                self.folders += 1;
+                self.add(&path);
+            } else if path.extension().is_some_and(|ext| ext == "md") {
+                self.notes += 1;
+            } else {
+                self.other_files += 1;
+            }
+        }
+    }
+
+    pub fn needs_typed_name(&self) -> bool {
+        self.notes > TYPE_NAME_ABOVE_NOTES
+    }
+
+    /// `12 notes, 3 subfolders and 2 other files`, leaving out zero counts
+    /// other than notes.
+    pub fn summary(&self) -> String {
+        let plural = |n: usize, one: &str, many: &str| format!("{} {}", n, if n == 1 { one } else { many });
+        let mut parts = vec![plural(self.notes, "note", "notes")];
+        if self.folders > 0 {
+            parts.push(plural(self.folders, "subfolder", "subfolders"));
+        }
+        if self.other_files > 0 {
+            parts.push(plural(self.other_files, "other file", "other files"));
+        }
+        match parts.split_last() {
+            Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+            _ => parts.join(""),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_nested_folder() {
+        let root = std::env::temp_dir().join(format!("ekphos-delete-{}", std::process::id()));
+        fs::create_dir_all(root.join("a/b/c")).unwrap();
+        fs::create_dir_all(root.join("empty")).unwrap();
+        for note in ["top.md", "a/one.md", "a/b/two.md", "a/b/c/three.md", "a/b/c/four.md"] {
+            fs::write(root.join(note), "# note").unwrap();
+        }
+        fs::write(root.join("a/b/diagram.png"), [0u8]).unwrap();
+        fs::write(root.join(".hidden"), "").unwrap();
+
+        let contents = FolderContents::count(&root);
+        assert_eq!(contents, FolderContents { notes: 5, folders: 4, other_files: 2 });
+        assert_eq!(contents.summary(), "5 notes, 4 subfolders and 2 other files");
+        assert!(!contents.needs_typed_name());
+        assert_eq!(FolderContents::count(&root.join("empty")).summary(), "0 notes");
+        assert_eq!(FolderContents::count(&root.join("a/b/c")).summary(), "2 notes");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,197 @@
+//! Passphrase-encrypted notes. An encrypted note keeps its `.md` name and is
+//! stored as a header line followed by base64 of the salt, a key check, the
+//! nonce and the ChaCha20-Poly1305 ciphertext. The key comes from the
+//! passphrase through Argon2id; plaintext only ever lives in memory.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use zeroize::Zeroizing;
+
+/// First line of every encrypted note.
+pub const HEADER: &str = "ekphos-encrypted v1";
+
+const SALT_LEN: usize = 16;
+const CHECK_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// A passphrase, wiped from memory when dropped and never printed.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Passphrase(Zeroizing<String>);
+
+impl Passphrase {
+    pub fn new(passphrase: String) -> Self {
+        Self(Zeroizing::new(passphrase))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Passphrase(..)")
+    }
+}
+
+/// Whether a note is encrypted on disk, and if so whether it's readable now.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum NoteLock {
+    #[default]
+    Plain,
+    /// Encrypted and not unlocked; the note's content is empty
+    Locked,
+    /// Decrypted in memory; saves encrypt again with this passphrase
+    Unlocked(Passphrase),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecryptError {
+    WrongPassphrase,
+    /// Not a readable encrypted note: truncated, edited or damaged
+    Corrupted,
+}
+
+impl fmt::Display for DecryptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecryptError::WrongPassphrase => f.write_str("Wrong passphrase"),
+            DecryptError::Corrupted => f.write_str("The encrypted note is damaged and can't be read"),
+        }
+    }
+}
+
+/// Whether `text` as read from disk is an encrypted note.
+pub fn is_encrypted(text: &str) -> bool {
+    text.lines().next().is_some_and(|line| line.trim_end() == HEADER)
+}
+
+/// The encryption key and the check value stored next to it, which tells a
+/// wrong passphrase apart from damaged ciphertext.
+fn derive(passphrase: &Passphrase, salt: &[u8]) -> Option<Zeroizing<[u8; 32 + CHECK_LEN]>> {
+    let mut out = Zeroizing::new([0u8; 32 + CHECK_LEN]);
+    Argon2::default()
+        .hash_password_into(passphrase.as_str().as_bytes(), salt, out.as_mut())
+        .ok()?;
+    Some(out)
+}
+
+/// `plaintext` encrypted under `passphrase`, as written to disk.
+pub fn encrypt(plaintext: &str, passphrase: &Passphrase) -> Result<String, String> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let derived = derive(passphrase, &salt).ok_or("Failed to derive the encryption key")?;
+    let (key, check) = derived.split_at(32);
+
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|_| "Failed to encrypt the note".to_string())?;
+
+    let mut payload = Vec::with_capacity(SALT_LEN + CHECK_LEN + NONCE_LEN + ciphertext.len());
+    payload.extend_from_slice(&salt);
+    payload.extend_from_slice(check);
+    payload.extend_from_slice(&nonce);
+    payload.extend_from_slice(&ciphertext);
+    Ok(format!("{}\n{}\n", HEADER, STANDARD.encode(payload)))
+}
+
+/// The plaintext of an encrypted note read from disk.
+pub fn decrypt(text: &str, passphrase: &Passphrase) -> Result<String, DecryptError> {
+    let mut lines = text.lines();
+    if lines.next().map(str::trim_end) != Some(HEADER) {
+        return Err(DecryptError::Corrupted);
+    }
+    let encoded: String = lines.flat_map(|line| line.trim().chars()).collect();
+    let payload = STANDARD.decode(encoded).map_err(|_| DecryptError::Corrupted)?;
+    if payload.len() < SALT_LEN + CHECK_LEN + NONCE_LEN {
+        return Err(DecryptError::Corrupted);
+    }
+    let (salt, rest) = payload.split_at(SALT_LEN);
+    let (check, rest) = rest.split_at(CHECK_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let derived = derive(passphrase, salt).ok_or(DecryptError::Corrupted)?;
+    let (key, expected_check) = derived.split_at(32);
+    if check != expected_check {
+        return Err(DecryptError::WrongPassphrase);
+    }
+    let plaintext = ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| DecryptError::Corrupted)?;
+    String::from_utf8(plaintext).map_err(|_| DecryptError::Corrupted)
+}
+
+/// The last passphrase that unlocked a note, tried first on the next one
+/// until it goes unused for the configured timeout.
+#[derive(Debug, Clone)]
+pub struct PassphraseCache {
+    passphrase: Passphrase,
+    last_used: Instant,
+}
+
+impl PassphraseCache {
+    pub fn new(passphrase: Passphrase, now: Instant) -> Self {
+        Self { passphrase, last_used: now }
+    }
+
+    /// The cached passphrase, marking it used at `now`.
+    pub fn take_use(&mut self, now: Instant) -> Passphrase {
+        self.last_used = now;
+        self.passphrase.clone()
+    }
+
+    /// When the cache runs out, `timeout` after its last use.
+    pub fn expires_at(&self, timeout: Duration) -> Instant {
+        self.last_used + timeout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pass(s: &str) -> Passphrase {
+        Passphrase::new(s.to_string())
+    }
+
+    #[test]
+    fn test_round_trip_keeps_unicode() {
+        let text = "# Tagebuch 📓\n\nÜber naïve café — 日本語のメモ\n- [ ] task\n";
+        let disk = encrypt(text, &pass("correct horse ✓")).unwrap();
+        assert!(is_encrypted(&disk));
+        assert!(!disk.contains("café"));
+        assert_eq!(decrypt(&disk, &pass("correct horse ✓")).unwrap(), text);
+        // A CRLF copy of the file still reads
+        assert_eq!(decrypt(&disk.replace('\n', "\r\n"), &pass("correct horse ✓")).unwrap(), text);
+        assert!(!is_encrypted(text));
+    }
+
+    #[test]
+    fn test_wrong_passphrase_and_damage_are_told_apart() {
+        let disk = encrypt("secret", &pass("right")).unwrap();
+        assert_eq!(decrypt(&disk, &pass("wrong")), Err(DecryptError::WrongPassphrase));
+
+        // Flip a bit of the ciphertext, past the salt and check
+        let mut payload = STANDARD.decode(disk.lines().nth(1).unwrap()).unwrap();
+        *payload.last_mut().unwrap() ^= 1;
+        let damaged = format!("{}\n{}\n", HEADER, STANDARD.encode(payload));
+        assert_eq!(decrypt(&damaged, &pass("right")), Err(DecryptError::Corrupted));
+
+        let truncated = format!("{}\nAAAA\n", HEADER);
+        assert_eq!(decrypt(&truncated, &pass("right")), Err(DecryptError::Corrupted));
+        assert_eq!(decrypt("# plain note", &pass("right")), Err(DecryptError::Corrupted));
+    }
+
+    #[test]
+    fn test_passphrase_debug_hides_it() {
+        assert_eq!(format!("{:?}", NoteLock::Unlocked(pass("hunter2"))), "Unlocked(Passphrase(..))");
+    }
+}
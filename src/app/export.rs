@@ -0,0 +1,464 @@
+//! `:export html`/`:export pdf`: the current note as a standalone HTML page,
+//! and through it a PDF made by the converter set as `[export] pdf_command`.
+//! `:copy html` puts the same HTML on the clipboard.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+
+use super::wiki_link::WikiLink;
+
+const STYLE: &str = "body { max-width: 46em; margin: 2em auto; padding: 0 1em; font-family: sans-serif; line-height: 1.5; }
+pre { background: #f4f4f4; padding: 0.75em; overflow-x: auto; }
+code { font-family: monospace; }
+blockquote { border-left: 3px solid #ccc; margin-left: 0; padding-left: 1em; color: #555; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.5em; }
+.wikilink { color: #2a6; }
+li.task { list-style: none; }";
+
+/// A PDF conversion running in the background.
+#[derive(Debug)]
+pub struct PdfExport {
+    child: Child,
+    /// Intermediate HTML, removed once the converter exits.
+    html: PathBuf,
+    pub output: PathBuf,
+}
+
+impl PdfExport {
+    /// Write `html` next to `output` and start the converter on it.
+    pub fn start(template: &str, html: &str, output: PathBuf) -> Result<Self, String> {
+        let html_path = output.with_file_name(format!(
+            ".{}.export.html",
+            output.file_stem().unwrap_or_default().to_string_lossy()
+        ));
+        let args = pdf_command_args(template, &html_path, &output)?;
+        fs::write(&html_path, html).map_err(|e| format!("Failed to write HTML: {}", e))?;
+
+        let child = Command::new(&args[0])
+            .args(&args[1..])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        match child {
+            Ok(child) => Ok(Self { child, html: html_path, output }),
+            Err(e) => {
+                let _ = fs::remove_file(&html_path);
+                Err(format!("Failed to run {}: {}", args[0], e))
+            }
+        }
+    }
+
+    /// `None` while the converter runs, then whether it made the PDF.
+    pub fn poll(&mut self) -> Option<Result<(), String>> {
+        let status = match self.child.try_wait() {
+            Ok(None) => return None,
+            Ok(Some(status)) => status,
+            Err(e) => return Some(Err(format!("PDF export failed: {}", e))),
+        };
+        let _ = fs::remove_file(&self.html);
+        Some(if !status.success() {
+            Err(format!("PDF export failed: pdf_command exited with {}", status))
+        } else if !self.output.exists() {
+            Err(format!("PDF export failed: pdf_command didn't write {}", self.output.display()))
+        } else {
+            Ok(())
+        })
+    }
+}
+
+/// Split the `pdf_command` template into arguments, honoring single and double
+/// quotes, and fill `{input}` and `{output}` into each.
+pub fn pdf_command_args(template: &str, input: &Path, output: &Path) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut arg = String::new();
+    let mut in_arg = false;
+    let mut quote = None;
+    for c in template.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => arg.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut arg));
+                    in_arg = false;
+                }
+            }
+            (None, c) => {
+                arg.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err("pdf_command has an unterminated quote".to_string());
+    }
+    if in_arg {
+        args.push(arg);
+    }
+    if args.is_empty() {
+        return Err("pdf_command is empty".to_string());
+    }
+
+    let input = input.to_string_lossy();
+    let output = output.to_string_lossy();
+    Ok(args
+        .into_iter()
+        .map(|arg| arg.replace("{input}", &input).replace("{output}", &output))
+        .collect())
+}
+
+/// Render a note's markdown as a complete HTML page.
+pub fn note_to_html(title: &str, markdown: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        markdown_to_html(markdown)
+    )
+}
+
+/// Render markdown as the HTML that goes inside a page's `<body>`.
+pub fn markdown_to_html(markdown: &str) -> String {
+    let mut body = String::new();
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut quote: Vec<String> = Vec::new();
+    let mut list: Option<&str> = None;
+    let mut table: Vec<Vec<String>> = Vec::new();
+    let mut code: Option<String> = None;
+
+    let mut lines = markdown.lines().peekable();
+    // Frontmatter isn't part of the page
+    if lines.peek().map(|l| l.trim_end()) == Some("---") {
+        lines.next();
+        for line in lines.by_ref() {
+            if line.trim_end() == "---" {
+                break;
+            }
+        }
+    }
+
+    for line in lines {
+        if let Some(block) = code.as_mut() {
+            if line.trim_start().starts_with("```") {
+                body.push_str(block);
+                body.push_str("</code></pre>\n");
+                code = None;
+            } else {
+                block.push_str(&escape(line));
+                block.push('\n');
+            }
+            continue;
+        }
+
+        let trimmed = line.trim();
+        if !trimmed.starts_with('|') {
+            close_table(&mut body, &mut table);
+        }
+        if !trimmed.starts_with('>') {
+            close_quote(&mut body, &mut quote);
+        }
+        let item = list_item(trimmed);
+        if item.is_none() {
+            close_list(&mut body, &mut list);
+        }
+        if trimmed.is_empty()
+            || trimmed.starts_with("```")
+            || heading(trimmed).is_some()
+            || trimmed.starts_with('|')
+            || trimmed.starts_with('>')
+            || item.is_some()
+            || is_rule(trimmed)
+        {
+            close_paragraph(&mut body, &mut paragraph);
+        }
+
+        if let Some(lang) = trimmed.strip_prefix("```") {
+            let class = lang.trim();
+            code = Some(if class.is_empty() {
+                "<pre><code>".to_string()
+            } else {
+                format!("<pre><code class=\"language-{}\">", escape(class))
+            });
+        } else if let Some((level, text)) = heading(trimmed) {
+            body.push_str(&format!("<h{0}>{1}</h{0}>\n", level, inline(text)));
+        } else if is_rule(trimmed) {
+            body.push_str("<hr>\n");
+        } else if let Some(text) = trimmed.strip_prefix('>') {
+            quote.push(inline(text.trim_start()));
+        } else if trimmed.starts_with('|') {
+            table.push(table_cells(trimmed));
+        } else if let Some((tag, text)) = item {
+            if list != Some(tag) {
+                close_list(&mut body, &mut list);
+                body.push_str(&format!("<{}>\n", tag));
+                list = Some(tag);
+            }
+            body.push_str(&task_item(text));
+        } else if !trimmed.is_empty() {
+            paragraph.push(inline(trimmed));
+        }
+    }
+    if let Some(block) = code {
+        body.push_str(&block);
+        body.push_str("</code></pre>\n");
+    }
+    close_paragraph(&mut body, &mut paragraph);
+    close_quote(&mut body, &mut quote);
+    close_list(&mut body, &mut list);
+    close_table(&mut body, &mut table);
+    body
+}
+
+/// `html` with its images pointed at `file://` URLs, relative paths resolved
+/// from `dir`, so they still show once the HTML is pasted somewhere else.
+pub fn with_file_images(html: &str, dir: &Path) -> String {
+    const SRC: &str = "<img src=\"";
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find(SRC) {
+        out.push_str(&rest[..start + SRC.len()]);
+        rest = &rest[start + SRC.len()..];
+        let end = rest.find('"').unwrap_or(rest.len());
+        let src = &rest[..end];
+        if src.contains("://") || src.starts_with("data:") {
+            out.push_str(src);
+        } else {
+            let path = dir.join(src.replace("&quot;", "\"").replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&"));
+            out.push_str("file://");
+            out.push_str(&escape(&path.to_string_lossy()).replace(' ', "%20"));
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn close_paragraph(body: &mut String, paragraph: &mut Vec<String>) {
+    if !paragraph.is_empty() {
+        body.push_str(&format!("<p>{}</p>\n", paragraph.join("\n")));
+        paragraph.clear();
+    }
+}
+
+fn close_quote(body: &mut String, quote: &mut Vec<String>) {
+    if !quote.is_empty() {
+        body.push_str(&format!("<blockquote><p>{}</p></blockquote>\n", quote.join("\n")));
+        quote.clear();
+    }
+}
+
+fn close_list(body: &mut String, list: &mut Option<&str>) {
+    if let Some(tag) = list.take() {
+        body.push_str(&format!("</{}>\n", tag));
+    }
+}
+
+/// Tables with a `|---|` separator row get their first row as the header.
+fn close_table(body: &mut String, table: &mut Vec<Vec<String>>) {
+    if table.is_empty() {
+        return;
+    }
+    let is_separator = |row: &Vec<String>| {
+        row.iter().all(|cell| !cell.is_empty() && cell.chars().all(|c| matches!(c, '-' | ':' | ' ')))
+    };
+    let has_header = table.len() > 1 && is_separator(&table[1]);
+
+    body.push_str("<table>\n");
+    for (i, row) in table.iter().enumerate() {
+        if has_header && i == 1 {
+            continue;
+        }
+        let tag = if has_header && i == 0 { "th" } else { "td" };
+        body.push_str("<tr>");
+        for cell in row {
+            body.push_str(&format!("<{0}>{1}</{0}>", tag, inline(cell)));
+        }
+        body.push_str("</tr>\n");
+    }
+    body.push_str("</table>\n");
+    table.clear();
+}
+
+fn table_cells(line: &str) -> Vec<String> {
+    let inner = line.trim_start_matches('|').trim_end_matches('|');
+    inner.split('|').map(|cell| cell.trim().to_string()).collect()
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let text = line[level..].strip_prefix(' ')?;
+    (1..=6).contains(&level).then_some((level, text.trim()))
+}
+
+/// `---`, `***` or `___`, spaces allowed between.
+fn is_rule(line: &str) -> bool {
+    let mut marks = line.chars().filter(|&c| c != ' ');
+    let Some(first @ ('-' | '*' | '_')) = marks.next() else {
+        return false;
+    };
+    let rest: Vec<char> = marks.collect();
+    rest.len() >= 2 && rest.iter().all(|&c| c == first)
+}
+
+/// List tag and item text of a `- item` or `1. item` line.
+fn list_item(line: &str) -> Option<(&'static str, &str)> {
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(text) = line.strip_prefix(marker) {
+            return Some(("ul", text));
+        }
+    }
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        if let Some(text) = line[digits..].strip_prefix(". ") {
+            return Some(("ol", text));
+        }
+    }
+    None
+}
+
+fn task_item(text: &str) -> String {
+    for (marker, checked) in [("[ ] ", ""), ("[x] ", " checked"), ("[X] ", " checked")] {
+        if let Some(text) = text.strip_prefix(marker) {
+            return format!(
+                "<li class=\"task\"><input type=\"checkbox\" disabled{}> {}</li>\n",
+                checked,
+                inline(text)
+            );
+        }
+    }
+    format!("<li>{}</li>\n", inline(text))
+}
+
+/// Inline markdown: code spans, emphasis, links, images and wiki links.
+fn inline(text: &str) -> String {
+    let mut html = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                html.push_str(&format!("<code>{}</code>", escape(&rest[1..1 + end])));
+                rest = &rest[end + 2..];
+                continue;
+            }
+        } else if let Some(after) = rest.strip_prefix("**") {
+            if let Some(end) = after.find("**").filter(|&end| end > 0) {
+                html.push_str(&format!("<strong>{}</strong>", inline(&after[..end])));
+                rest = &after[end + 2..];
+                continue;
+            }
+        } else if c == '*' || c == '_' {
+            if let Some(end) = rest[1..].find(c).filter(|&end| end > 0) {
+                html.push_str(&format!("<em>{}</em>", inline(&rest[1..1 + end])));
+                rest = &rest[end + 2..];
+                continue;
+            }
+        } else if let Some(link) = WikiLink::parse(rest) {
+            html.push_str(&format!("<span class=\"wikilink\">{}</span>", escape(&link.shown())));
+            rest = &rest[link.len..];
+            continue;
+        } else if c == '!' || c == '[' {
+            let image = c == '!';
+            let start = if image { 1 } else { 0 };
+            if let Some((label, url, len)) = link(&rest[start..]) {
+                if image {
+                    html.push_str(&format!("<img src=\"{}\" alt=\"{}\">", escape(url), escape(label)));
+                } else {
+                    html.push_str(&format!("<a href=\"{}\">{}</a>", escape(url), inline(label)));
+                }
+                rest = &rest[start + len..];
+                continue;
+            }
+        }
+        html.push_str(&escape(&rest[..c.len_utf8()]));
+        rest = &rest[c.len_utf8()..];
+    }
+    html
+}
+
+/// Label, URL and byte length of a `[label](url)` at the start of `text`.
+fn link(text: &str) -> Option<(&str, &str, usize)> {
+    let after = text.strip_prefix('[')?;
+    let label_end = after.find("](")?;
+    let url_start = label_end + 2;
+    let url_end = after[url_start..].find(')')? + url_start;
+    Some((&after[..label_end], &after[url_start..url_end], url_end + 2))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pdf_command_args_substitutes_paths() {
+        let input = Path::new("/notes/My Notes/.plan.export.html");
+        let output = Path::new("/notes/My Notes/plan.pdf");
+
+        let args = pdf_command_args("wkhtmltopdf --quiet {input} {output}", input, output).unwrap();
+        assert_eq!(
+            args,
+            vec!["wkhtmltopdf", "--quiet", "/notes/My Notes/.plan.export.html", "/notes/My Notes/plan.pdf"]
+        );
+
+        let args = pdf_command_args(
+            "chromium --headless '--print-to-pdf={output}' \"file://{input}\"",
+            input,
+            output,
+        )
+        .unwrap();
+        assert_eq!(
+            args,
+            vec![
+                "chromium",
+                "--headless",
+                "--print-to-pdf=/notes/My Notes/plan.pdf",
+                "file:///notes/My Notes/.plan.export.html",
+            ]
+        );
+
+        assert!(pdf_command_args("  ", input, output).is_err());
+        assert!(pdf_command_args("convert 'x {input}", input, output).is_err());
+    }
+
+    #[test]
+    fn test_with_file_images() {
+        let html = markdown_to_html("![diagram](img/flow chart.png) ![web](https://x.y/a.png) ![abs](/tmp/b.png)");
+        assert_eq!(
+            with_file_images(&html, Path::new("/notes/Work")),
+            "<p><img src=\"file:///notes/Work/img/flow%20chart.png\" alt=\"diagram\"> \
+             <img src=\"https://x.y/a.png\" alt=\"web\"> \
+             <img src=\"file:///tmp/b.png\" alt=\"abs\"></p>\n"
+        );
+    }
+
+    #[test]
+    fn test_note_to_html() {
+        let markdown = "---\ntags: [a]\n---\n# Plan <draft>\n\nSome **bold** and `co*de`\nsee [[Other|the other note]].\n\n- [x] done\n- [link](https://x.y)\n\n```rust\nlet a = 1 < 2;\n```\n| A | B |\n|---|---|\n| 1 | 2 |\n";
+        let html = note_to_html("Plan", markdown);
+        let body = &html[html.find("<body>").unwrap()..];
+        assert_eq!(
+            body,
+            "<body>\n<h1>Plan &lt;draft&gt;</h1>\n\
+             <p>Some <strong>bold</strong> and <code>co*de</code>\nsee <span class=\"wikilink\">the other note</span>.</p>\n\
+             <ul>\n<li class=\"task\"><input type=\"checkbox\" disabled checked> done</li>\n\
+             <li><a href=\"https://x.y\">link</a></li>\n</ul>\n\
+             <pre><code class=\"language-rust\">let a = 1 &lt; 2;\n</code></pre>\n\
+             <table>\n<tr><th>A</th><th>B</th></tr>\n<tr><td>1</td><td>2</td></tr>\n</table>\n\
+             </body>\n</html>\n"
+        );
+        assert!(html.contains("<title>Plan</title>"));
+    }
+}
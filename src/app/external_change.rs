@@ -0,0 +1,53 @@
+//! What to do when the viewed note changed on disk, per the `external_change`
+//! policy.
+
+use crate::config::ExternalChange;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExternalChangeAction {
+    /// Load the version on disk
+    Reload,
+    /// Ask with the ExternalChange dialog
+    Prompt,
+    /// Keep the version on screen
+    Keep,
+}
+
+/// The one place the reload decision is made. Unsaved edits always get the
+/// prompt: reloading would throw them away, ignoring would clobber the file
+/// on the next save without anyone having looked.
+pub fn external_change_action(policy: ExternalChange, local_edits: bool) -> ExternalChangeAction {
+    match (policy, local_edits) {
+        (_, true) => ExternalChangeAction::Prompt,
+        (ExternalChange::Reload, false) => ExternalChangeAction::Reload,
+        (ExternalChange::Prompt, false) => ExternalChangeAction::Prompt,
+        (ExternalChange::Ignore, false) => ExternalChangeAction::Keep,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_external_change_decision_matrix() {
+        use ExternalChangeAction::*;
+        let cases = [
+            (ExternalChange::Reload, false, Reload),
+            (ExternalChange::Prompt, false, Prompt),
+            (ExternalChange::Ignore, false, Keep),
+            (ExternalChange::Reload, true, Prompt),
+            (ExternalChange::Prompt, true, Prompt),
+            (ExternalChange::Ignore, true, Prompt),
+        ];
+        for (policy, local_edits, expected) in cases {
+            assert_eq!(
+                external_change_action(policy, local_edits),
+                expected,
+                "{:?} with local edits: {}",
+                policy,
+                local_edits
+            );
+        }
+    }
+}
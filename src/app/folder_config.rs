@@ -0,0 +1,192 @@
+//! Per-folder settings: a `.ekphos.toml` in any folder of the vault changes
+//! some settings for the notes under it, e.g. a different theme or snippets
+//! for work notes. Files nearer the note win. Only the keys in
+//! [`FolderConfig`] can be set this way; anything else, like `notes_dir` or
+//! `ipc`, is refused, since these files come with the vault rather than
+//! from the user's own config.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, LineEnding};
+
+pub const FILE_NAME: &str = ".ekphos.toml";
+
+/// The settings a folder can change.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FolderConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frontmatter_hidden: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_tags: Option<bool>,
+    #[serde(default, skip_serializing_if = "FolderEditorConfig::is_empty")]
+    pub editor: FolderEditorConfig,
+    /// Added to the snippets from `config.toml`, replacing ones of the same
+    /// name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub snippets: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FolderEditorConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_wrap: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tab_width: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conceal: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_cleared_tasks: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paste_url_as_link: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_ending: Option<LineEnding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ensure_final_newline: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub word_chars: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet_prefix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_format: Option<String>,
+}
+
+impl FolderEditorConfig {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl FolderConfig {
+    fn apply(&self, config: &mut Config) {
+        fn set<T: Clone>(target: &mut T, value: &Option<T>) {
+            if let Some(value) = value {
+                *target = value.clone();
+            }
+        }
+        set(&mut config.theme, &self.theme);
+        set(&mut config.frontmatter_hidden, &self.frontmatter_hidden);
+        set(&mut config.show_tags, &self.show_tags);
+
+        let (editor, ours) = (&mut config.editor, &self.editor);
+        set(&mut editor.line_wrap, &ours.line_wrap);
+        set(&mut editor.tab_width, &ours.tab_width);
+        set(&mut editor.conceal, &ours.conceal);
+        set(&mut editor.archive_cleared_tasks, &ours.archive_cleared_tasks);
+        set(&mut editor.paste_url_as_link, &ours.paste_url_as_link);
+        set(&mut editor.line_ending, &ours.line_ending);
+        set(&mut editor.ensure_final_newline, &ours.ensure_final_newline);
+        set(&mut editor.word_chars, &ours.word_chars);
+        set(&mut editor.snippet_prefix, &ours.snippet_prefix);
+        set(&mut editor.date_format, &ours.date_format);
+        set(&mut editor.time_format, &ours.time_format);
+
+        config.snippets.extend(self.snippets.clone());
+    }
+
+    /// The settings this file makes, as `key = value` lines.
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut section = String::new();
+        for line in toml::to_string(self).unwrap_or_default().lines().map(str::trim) {
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = format!("{}.", name);
+            } else if !line.is_empty() {
+                lines.push(format!("{}{}", section, line));
+            }
+        }
+        lines
+    }
+}
+
+/// One `.ekphos.toml` that applies to a note, or why it can't be used.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FolderConfigLayer {
+    pub path: PathBuf,
+    pub config: Result<FolderConfig, String>,
+}
+
+/// The `.ekphos.toml` files from `notes_dir` down to the folder of `note`,
+/// farthest first. None for a note outside `notes_dir`.
+pub fn layers_for(notes_dir: &Path, note: &Path) -> Vec<FolderConfigLayer> {
+    let Ok(relative) = note.strip_prefix(notes_dir) else {
+        return Vec::new();
+    };
+    let mut dir = notes_dir.to_path_buf();
+    let mut dirs = vec![dir.clone()];
+    if let Some(parent) = relative.parent() {
+        for part in parent.components() {
+            dir.push(part);
+            dirs.push(dir.clone());
+        }
+    }
+    dirs.into_iter()
+        .map(|dir| dir.join(FILE_NAME))
+        .filter(|path| path.is_file())
+        .map(|path| {
+            let config = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| toml::from_str(&content).map_err(|e| e.message().to_string()));
+            FolderConfigLayer { path, config }
+        })
+        .collect()
+}
+
+/// `base` with the usable `layers` applied in order.
+pub fn layered(base: &Config, layers: &[FolderConfigLayer]) -> Config {
+    let mut config = base.clone();
+    for layer in layers {
+        if let Ok(folder) = &layer.config {
+            folder.apply(&mut config);
+        }
+    }
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers() {
+        let dir = std::env::temp_dir().join(format!("ekphos-folder-config-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let work = dir.join("work");
+        fs::create_dir_all(work.join("meetings")).unwrap();
+        fs::write(dir.join(FILE_NAME), "show_tags = false\n[editor]\ntab_width = 2\n").unwrap();
+        fs::write(work.join(FILE_NAME), "theme = \"work\"\n[editor]\ntab_width = 8\n[snippets]\nsig = \"Ana\"\n")
+            .unwrap();
+        fs::write(work.join("meetings").join(FILE_NAME), "notes_dir = \"/tmp\"\n").unwrap();
+
+        let layers = layers_for(&dir, &work.join("meetings/Standup.md"));
+        assert_eq!(layers.len(), 3);
+        assert!(layers[2].config.as_ref().is_err_and(|e| e.contains("notes_dir")));
+
+        let base = Config::default();
+        let config = layered(&base, &layers);
+        assert_eq!(config.theme, "work");
+        assert!(!config.show_tags);
+        assert_eq!(config.editor.tab_width, 8);
+        assert_eq!(config.notes_dir, base.notes_dir);
+        assert_eq!(config.snippets.get("sig").map(String::as_str), Some("Ana"));
+        assert_eq!(config.snippets.len(), base.snippets.len() + 1);
+        assert_eq!(layers[1].config.as_ref().unwrap().describe(), [
+            "theme = \"work\"",
+            "editor.tab_width = 8",
+            "snippets.sig = \"Ana\""
+        ]);
+
+        // Only the root file for a note at the top, none outside the vault
+        assert_eq!(layered(&base, &layers_for(&dir, &dir.join("Inbox.md"))).editor.tab_width, 2);
+        assert!(layers_for(&dir, Path::new("/elsewhere/Note.md")).is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,201 @@
+//! Folder notes: what the content view shows for a folder selected in the
+//! sidebar, either its index note or a listing of what it contains.
+
+use std::path::{Path, PathBuf};
+
+use super::state::{ContentItem, FileTreeItem, Note};
+
+/// Stands for the folder's own name in `display.folder_index_notes`.
+const FOLDER_NAME: &str = "{folder}";
+
+/// Note names tried as a folder's index note, most preferred first.
+fn index_names(folder: &Path, precedence: &[String]) -> Vec<String> {
+    let folder_name = folder
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    precedence
+        .iter()
+        .map(|name| name.replace(FOLDER_NAME, &folder_name))
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// The index note of `folder`: the first name in `precedence` matching a note
+/// directly inside it, ignoring case.
+pub fn find_index_note(folder: &Path, precedence: &[String], notes: &[Note]) -> Option<usize> {
+    index_names(folder, precedence).iter().find_map(|name| {
+        notes.iter().position(|note| {
+            note.file_path
+                .as_deref()
+                .is_some_and(|p| p.parent() == Some(folder))
+                && note.title.eq_ignore_ascii_case(name)
+        })
+    })
+}
+
+/// Where a new index note for `folder` goes, named after the first entry of
+/// `precedence`.
+pub fn new_index_note_path(folder: &Path, precedence: &[String]) -> Option<PathBuf> {
+    let name = index_names(folder, precedence).into_iter().next()?;
+    Some(folder.join(format!("{}.md", name)))
+}
+
+/// Content items listing `folder` in sidebar order: subfolders with their note
+/// counts, then notes with a preview of their first line.
+pub fn folder_listing(tree: &[FileTreeItem], root: &Path, folder: &Path, notes: &[Note]) -> Vec<ContentItem> {
+    let children = if folder == root {
+        Some(tree)
+    } else {
+        find_children(tree, folder)
+    };
+    let Some(children) = children else {
+        return Vec::new();
+    };
+
+    let mut folders = Vec::new();
+    let mut files = Vec::new();
+    for child in children {
+        match child {
+            FileTreeItem::Folder { name, path, children, .. } => {
+                let count = count_notes(children);
+                folders.push(ContentItem::FolderEntry {
+                    name: name.clone(),
+                    detail: format!("{} note{}", count, if count == 1 { "" } else { "s" }),
+                    path: path.clone(),
+                    is_folder: true,
+                });
+            }
+            FileTreeItem::Note { note_index, .. } => {
+                let Some(note) = notes.get(*note_index) else {
+                    continue;
+                };
+                let Some(path) = note.file_path.clone() else {
+                    continue;
+                };
+                files.push(ContentItem::FolderEntry {
+                    name: note.title.clone(),
+                    detail: note_preview(note),
+                    path,
+                    is_folder: false,
+                });
+            }
+        }
+    }
+    folders.extend(files);
+    folders
+}
+
+fn find_children<'a>(items: &'a [FileTreeItem], folder: &Path) -> Option<&'a [FileTreeItem]> {
+    items.iter().find_map(|item| match item {
+        FileTreeItem::Folder { path, children, .. } if path == folder => Some(children.as_slice()),
+        FileTreeItem::Folder { children, .. } => find_children(children, folder),
+        FileTreeItem::Note { .. } => None,
+    })
+}
+
+fn count_notes(items: &[FileTreeItem]) -> usize {
+    items
+        .iter()
+        .map(|item| match item {
+            FileTreeItem::Folder { children, .. } => count_notes(children),
+            FileTreeItem::Note { .. } => 1,
+        })
+        .sum()
+}
+
+/// First non-blank line of the note body, skipping a heading that only
+/// repeats the title.
+fn note_preview(note: &Note) -> String {
+    note.content
+        .lines()
+        .skip(note.content_start_line)
+        .map(|line| line.trim().trim_start_matches('#').trim())
+        .find(|line| !line.is_empty() && !line.eq_ignore_ascii_case(&note.title))
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(path: &str, content: &str) -> Note {
+        let path = PathBuf::from(path);
+        Note {
+            title: path.file_stem().unwrap().to_string_lossy().to_string(),
+            content: content.to_string(),
+            file_path: Some(path),
+            modified_time: None,
+            created_time: None,
+            frontmatter: None,
+            content_start_line: 0,
+            newline: Default::default(),
+            lock: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_find_index_note_follows_precedence() {
+        let precedence = vec!["{folder}".to_string(), "index".to_string(), "README".to_string()];
+        let notes = vec![
+            note("/vault/projects/readme.md", ""),
+            note("/vault/projects/Index.md", ""),
+            note("/vault/projects/sub/projects.md", ""),
+        ];
+        let folder = Path::new("/vault/projects");
+        assert_eq!(find_index_note(folder, &precedence, &notes), Some(1));
+        assert_eq!(find_index_note(folder, &precedence[2..], &notes), Some(0));
+        assert_eq!(find_index_note(Path::new("/vault/other"), &precedence, &notes), None);
+        assert_eq!(
+            new_index_note_path(folder, &precedence),
+            Some(PathBuf::from("/vault/projects/projects.md"))
+        );
+    }
+
+    #[test]
+    fn test_folder_listing_counts_and_previews() {
+        let notes = vec![
+            note("/vault/a/todo.md", "# todo\n\nBuy milk\n"),
+            note("/vault/a/b/deep.md", ""),
+            note("/vault/a/b/c/deeper.md", ""),
+        ];
+        let tree = vec![FileTreeItem::Folder {
+            name: "a".to_string(),
+            path: PathBuf::from("/vault/a"),
+            expanded: false,
+            children: vec![
+                FileTreeItem::Note { note_index: 0 },
+                FileTreeItem::Folder {
+                    name: "b".to_string(),
+                    path: PathBuf::from("/vault/a/b"),
+                    expanded: false,
+                    children: vec![
+                        FileTreeItem::Note { note_index: 1 },
+                        FileTreeItem::Folder {
+                            name: "c".to_string(),
+                            path: PathBuf::from("/vault/a/b/c"),
+                            expanded: false,
+                            children: vec![FileTreeItem::Note { note_index: 2 }],
+                        },
+                    ],
+                },
+            ],
+        }];
+
+        let listing = folder_listing(&tree, Path::new("/vault"), Path::new("/vault/a"), &notes);
+        let entries: Vec<(&str, &str, bool)> = listing
+            .iter()
+            .filter_map(|item| match item {
+                ContentItem::FolderEntry { name, detail, is_folder, .. } => {
+                    Some((name.as_str(), detail.as_str(), *is_folder))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(entries, vec![("b", "2 notes", true), ("todo", "Buy milk", false)]);
+
+        let root = folder_listing(&tree, Path::new("/vault"), Path::new("/vault"), &notes);
+        assert_eq!(root.len(), 1);
+    }
+}
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use serde::Deserialize;
 
+use crate::config::InlineImages;
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, Default, Deserialize)]
 pub struct Frontmatter {
@@ -42,6 +44,23 @@ impl Frontmatter {
 
         (frontmatter, content_start_line)
     }
+
+    /// The note's own `images: auto | off`, over `images.inline`. A YAML
+    /// boolean works too.
+    pub fn inline_images(&self) -> Option<InlineImages> {
+        match self.extra.get("images")? {
+            serde_yaml::Value::Bool(true) => Some(InlineImages::Auto),
+            serde_yaml::Value::Bool(false) => Some(InlineImages::Off),
+            serde_yaml::Value::String(name) => InlineImages::from_name(&name.to_lowercase()),
+            _ => None,
+        }
+    }
+
+    /// The note's own `join_paragraph_lines: true | false`, over
+    /// `reading.join_paragraph_lines`.
+    pub fn join_paragraph_lines(&self) -> Option<bool> {
+        self.extra.get("join_paragraph_lines")?.as_bool()
+    }
 }
 
 #[cfg(test)]
@@ -107,4 +126,18 @@ Content"#;
         assert_eq!(fm.tags, vec!["rust", "cli", "tui"]);
         assert_eq!(start, 6);
     }
+
+    #[test]
+    fn test_inline_images_setting() {
+        let setting = |content: &str| Frontmatter::parse(content).0.and_then(|fm| fm.inline_images());
+        assert_eq!(setting("---\nimages: off\n---\n"), Some(InlineImages::Off));
+        assert_eq!(setting("---\nimages: Auto\n---\n"), Some(InlineImages::Auto));
+        assert_eq!(setting("---\nimages: false\n---\n"), Some(InlineImages::Off));
+        assert_eq!(setting("---\nimages: [a.png]\n---\n"), None);
+        assert_eq!(setting("---\ntitle: x\n---\n"), None);
+
+        let join = |content: &str| Frontmatter::parse(content).0.and_then(|fm| fm.join_paragraph_lines());
+        assert_eq!(join("---\njoin_paragraph_lines: true\n---\n"), Some(true));
+        assert_eq!(join("---\ntitle: x\n---\n"), None);
+    }
 }
@@ -0,0 +1,127 @@
+//! `:gsub`: one substitution run over every note in the vault.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::encryption::is_encrypted;
+use crate::vim::command::SubstituteFlags;
+use crate::vim::substitute::substitute_text;
+
+/// A `:gsub` waiting for confirmation, with the counts from its dry run.
+#[derive(Debug, Clone)]
+pub struct GlobalSubstitute {
+    pub pattern: String,
+    pub replacement: String,
+    pub flags: SubstituteFlags,
+    pub preview: VaultSubstitution,
+}
+
+/// Notes a substitution changed (or would change), with their replacement counts.
+#[derive(Debug, Clone, Default)]
+pub struct VaultSubstitution {
+    pub files: Vec<(PathBuf, usize)>,
+    /// Notes left alone: unsaved in the editor, encrypted, or unreadable/unwritable.
+    pub skipped: Vec<PathBuf>,
+}
+
+impl VaultSubstitution {
+    pub fn occurrences(&self) -> usize {
+        self.files.iter().map(|(_, count)| count).sum()
+    }
+}
+
+/// Substitute in each of the notes at `paths` except `skip`. Files are only
+/// rewritten when `write` is set; otherwise the matches are just counted.
+pub fn substitute_notes(
+    paths: &[PathBuf],
+    pattern: &str,
+    replacement: &str,
+    flags: &SubstituteFlags,
+    skip: Option<&Path>,
+    write: bool,
+) -> VaultSubstitution {
+    let mut result = VaultSubstitution::default();
+    for path in paths {
+        if skip == Some(path.as_path()) {
+            result.skipped.push(path.clone());
+            continue;
+        }
+        let text = match fs::read_to_string(path) {
+            Ok(text) if !is_encrypted(&text) => text,
+            _ => {
+                result.skipped.push(path.clone());
+                continue;
+            }
+        };
+
+        let (new_text, count) = substitute_text(&text, pattern, replacement, flags);
+        if count == 0 {
+            continue;
+        }
+        if write && fs::write(path, new_text).is_err() {
+            result.skipped.push(path.clone());
+            continue;
+        }
+        result.files.push((path.clone(), count));
+    }
+    result
+}
+
+/// File names of `paths` without extension, comma separated.
+pub fn note_names(paths: &[PathBuf]) -> String {
+    let names: Vec<String> = paths
+        .iter()
+        .map(|path| path.file_stem().unwrap_or_default().to_string_lossy().to_string())
+        .collect();
+    names.join(", ")
+}
+
+/// `", skipped a, b"` naming the notes a substitution left alone, or nothing.
+pub fn skipped_notes_suffix(skipped: &[PathBuf]) -> String {
+    if skipped.is_empty() {
+        String::new()
+    } else {
+        format!(", skipped {}", note_names(skipped))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitute_notes_over_vault() {
+        let vault = std::env::temp_dir().join(format!("ekphos-gsub-{}", std::process::id()));
+        fs::create_dir_all(vault.join("sub")).unwrap();
+        let a = vault.join("a.md");
+        let b = vault.join("sub/b.md");
+        let c = vault.join("c.md");
+        let open = vault.join("open.md");
+        fs::write(&a, "old and old\nold\n").unwrap();
+        fs::write(&b, "nothing here\n").unwrap();
+        fs::write(&c, "Old\n").unwrap();
+        fs::write(&open, "old\n").unwrap();
+        let paths = vec![a.clone(), b.clone(), c.clone(), open.clone()];
+        let flags = SubstituteFlags::parse("g");
+
+        // Dry run counts without touching any file
+        let dry = substitute_notes(&paths, "old", "new", &flags, Some(&open), false);
+        assert_eq!(dry.files, vec![(a.clone(), 3)]);
+        assert_eq!(dry.skipped, vec![open.clone()]);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "old and old\nold\n");
+
+        let done = substitute_notes(&paths, "old", "new", &flags, Some(&open), true);
+        assert_eq!(done.occurrences(), 3);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "new and new\nnew\n");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "nothing here\n");
+        assert_eq!(fs::read_to_string(&c).unwrap(), "Old\n");
+        assert_eq!(fs::read_to_string(&open).unwrap(), "old\n");
+
+        let flags = SubstituteFlags::parse("gi");
+        let done = substitute_notes(&paths, "old", "new", &flags, None, true);
+        assert_eq!(done.files, vec![(c.clone(), 1), (open.clone(), 1)]);
+        assert_eq!(fs::read_to_string(&c).unwrap(), "new\n");
+
+        let _ = fs::remove_dir_all(&vault);
+    }
+}
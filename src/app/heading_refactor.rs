@@ -0,0 +1,207 @@
+//! `:rename-heading`, `:promote` and `:demote`: changing a heading together
+//! with what depends on it. A renamed heading takes the links to it along,
+//! and a heading changes level together with its subheadings.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::encryption::is_encrypted;
+use super::global_substitute::VaultSubstitution;
+use super::state::{percent_decode, slugify_heading};
+use super::wiki_link::{escape, split_unescaped, WikiLink};
+
+/// Where a link points, for telling whether it's to the renamed heading's note.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LinkTarget<'a> {
+    /// `[[target#Heading]]`, empty for `[[#Heading]]`
+    Wiki(&'a str),
+    /// `[text](path#heading)`, empty for `[text](#heading)`
+    Path(&'a str),
+}
+
+/// Level and title of a `#` heading line.
+pub fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim_end()))
+}
+
+/// `text` with the links to the heading slugged `old_slug` pointed at
+/// `new_title`, and how many were changed. `links_here` says whether a link's
+/// target is the heading's note. Fenced code is left alone.
+pub fn rename_heading_links(
+    text: &str,
+    old_slug: &str,
+    new_title: &str,
+    links_here: impl Fn(LinkTarget) -> bool,
+) -> (String, usize) {
+    let new_slug = slugify_heading(new_title);
+    let mut out = String::with_capacity(text.len());
+    let mut count = 0;
+    let mut in_code = false;
+    for line in text.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+        }
+        if in_code || line.trim_start().starts_with("```") {
+            out.push_str(line);
+            continue;
+        }
+        let mut rest = line;
+        loop {
+            let wiki = rest.find("[[");
+            let markdown = rest.find("](");
+            match (wiki, markdown) {
+                (Some(start), _) if markdown.is_none_or(|md| start < md) => {
+                    out.push_str(&rest[..start]);
+                    rest = &rest[start..];
+                    let Some(link) = WikiLink::parse(rest) else {
+                        out.push_str("[[");
+                        rest = &rest[2..];
+                        continue;
+                    };
+                    let inner = &rest[2..link.len - 2];
+                    let (destination, alias) =
+                        inner.split_at(split_unescaped(inner, '|').map_or(inner.len(), |(destination, _)| destination.len()));
+                    match (split_unescaped(destination, '#'), &link.heading) {
+                        (Some((target, _)), Some(heading))
+                            if slugify_heading(heading) == old_slug && links_here(LinkTarget::Wiki(link.target.trim())) =>
+                        {
+                            out.push_str(&format!("[[{}#{}{}]]", target, escape(new_title), alias));
+                            count += 1;
+                        }
+                        _ => out.push_str(&rest[..link.len]),
+                    }
+                    rest = &rest[link.len..];
+                }
+                (_, Some(start)) => {
+                    out.push_str(&rest[..start + 2]);
+                    rest = &rest[start + 2..];
+                    let Some(end) = rest.find(')') else {
+                        break;
+                    };
+                    let destination = &rest[..end];
+                    match destination.split_once('#') {
+                        Some((path, fragment))
+                            if slugify_heading(&percent_decode(fragment)) == old_slug
+                                && links_here(LinkTarget::Path(path)) =>
+                        {
+                            out.push_str(&format!("{}#{}", path, new_slug));
+                            count += 1;
+                        }
+                        _ => out.push_str(destination),
+                    }
+                    rest = &rest[end..];
+                }
+                _ => break,
+            }
+        }
+        out.push_str(rest);
+    }
+    (out, count)
+}
+
+/// Rename the links to a heading in each of the notes at `paths`. Files are
+/// only rewritten when `write` is set; otherwise the links are just counted.
+pub fn rename_heading_in_notes(
+    paths: &[PathBuf],
+    old_slug: &str,
+    new_title: &str,
+    links_here: impl Fn(&Path, LinkTarget) -> bool,
+    write: bool,
+) -> VaultSubstitution {
+    let mut result = VaultSubstitution::default();
+    for path in paths {
+        let text = match fs::read_to_string(path) {
+            Ok(text) if !is_encrypted(&text) => text,
+            _ => {
+                result.skipped.push(path.clone());
+                continue;
+            }
+        };
+        let (new_text, count) = rename_heading_links(&text, old_slug, new_title, |target| links_here(path, target));
+        if count == 0 {
+            continue;
+        }
+        if write && fs::write(path, new_text).is_err() {
+            result.skipped.push(path.clone());
+            continue;
+        }
+        result.files.push((path.clone(), count));
+    }
+    result
+}
+
+/// The line changes moving the heading at `row` and the subheadings under it
+/// `delta` levels, as `(row, new_line)`.
+pub fn shift_section(lines: &[&str], row: usize, delta: isize) -> Result<Vec<(usize, String)>, String> {
+    let (level, _) = lines.get(row).and_then(|line| parse_heading(line)).ok_or("Not on a heading")?;
+    let mut changes = Vec::new();
+    let mut in_code = false;
+    for (i, line) in lines.iter().enumerate().skip(row) {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        let Some((sub_level, _)) = parse_heading(line).filter(|_| !in_code) else {
+            continue;
+        };
+        if i > row && sub_level <= level {
+            break;
+        }
+        let new_level = sub_level as isize + delta;
+        if !(1..=6).contains(&new_level) {
+            return Err(if delta < 0 {
+                "Can't promote above #".to_string()
+            } else {
+                "Can't demote below ######".to_string()
+            });
+        }
+        changes.push((i, format!("{}{}", "#".repeat(new_level as usize), &line[sub_level..])));
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename_heading_links() {
+        let text = "See [setup](#getting-started) and [[#Getting Started|setup]].\n\
+            Also [[Guide#getting started]], [[Other#Getting Started]] and [x](guide.md#Getting%20Started).\n\
+            ```\n[[#Getting Started]]\n```\n\
+            [other](#getting-startedx) [img](#getting-started\n";
+        let (renamed, count) = rename_heading_links(text, "getting-started", "Install", |target| match target {
+            LinkTarget::Wiki(note) => note.is_empty() || note == "Guide",
+            LinkTarget::Path(path) => path.is_empty() || path == "guide.md",
+        });
+        assert_eq!(count, 4);
+        assert_eq!(
+            renamed,
+            "See [setup](#install) and [[#Install|setup]].\n\
+            Also [[Guide#Install]], [[Other#Getting Started]] and [x](guide.md#install).\n\
+            ```\n[[#Getting Started]]\n```\n\
+            [other](#getting-startedx) [img](#getting-started\n"
+        );
+    }
+
+    #[test]
+    fn test_shift_section_with_subheadings() {
+        let lines = ["# Top", "## Plan", "text", "### Detail", "```", "## not a heading", "```", "## Next"];
+        assert_eq!(
+            shift_section(&lines, 1, 1).unwrap(),
+            [(1, "### Plan".to_string()), (3, "#### Detail".to_string())]
+        );
+        assert_eq!(
+            shift_section(&lines, 1, -1).unwrap(),
+            [(1, "# Plan".to_string()), (3, "## Detail".to_string())]
+        );
+        assert!(shift_section(&lines, 0, -1).is_err());
+        assert!(shift_section(&lines, 2, 1).is_err());
+        assert!(shift_section(&["##### A", "###### B"], 0, 1).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+//! The line drawn above each image on a line of its own: its file name,
+//! pixel size, file size and whether it's loading, failed or shown. With
+//! `images.inline = "off"`, or `images: off` in a note's frontmatter, the
+//! line stands in for the image.
+
+/// Where an image is in being read or fetched.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum ImageLoad {
+    #[default]
+    Loading,
+    Failed(String),
+    Loaded,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ImageInfo {
+    pub load: ImageLoad,
+    /// Bytes in the file, or in the download for a remote image
+    pub bytes: Option<u64>,
+    /// Pixel size of the original, before any resizing for the cache
+    pub dimensions: Option<(u32, u32)>,
+}
+
+impl ImageInfo {
+    /// The placeholder text for the image at `path`. `inline` is whether the
+    /// image itself is drawn under it.
+    pub fn describe(&self, path: &str, inline: bool) -> String {
+        let mut parts = vec![file_name(path).to_string()];
+        if let Some((width, height)) = self.dimensions {
+            parts.push(format!("{}×{}", width, height));
+        }
+        if let Some(bytes) = self.bytes {
+            parts.push(format_size(bytes));
+        }
+        parts.push(match (&self.load, inline) {
+            (ImageLoad::Failed(reason), _) => format!("failed: {}", reason),
+            (_, false) => "preview off".to_string(),
+            (ImageLoad::Loading, true) => "loading".to_string(),
+            (ImageLoad::Loaded, true) => "shown".to_string(),
+        });
+        parts.join(" · ")
+    }
+}
+
+/// The last segment of a path or URL, without any query string.
+pub fn file_name(path: &str) -> &str {
+    let path = path.split(['?', '#']).next().unwrap_or(path).trim_end_matches('/');
+    path.rsplit(['/', '\\']).next().filter(|name| !name.is_empty()).unwrap_or(path)
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KB", "MB", "GB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if size < 10.0 {
+        format!("{:.1} {}", size, UNITS[unit])
+    } else {
+        format!("{:.0} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let loaded = ImageInfo { load: ImageLoad::Loaded, bytes: Some(245_760), dimensions: Some((1920, 1080)) };
+        assert_eq!(loaded.describe("assets/diagram.png", true), "diagram.png · 1920×1080 · 240 KB · shown");
+        assert_eq!(loaded.describe("assets/diagram.png", false), "diagram.png · 1920×1080 · 240 KB · preview off");
+
+        let failed = ImageInfo { load: ImageLoad::Failed("HTTP 404".to_string()), ..ImageInfo::default() };
+        assert_eq!(failed.describe("https://example.com/a/cat.jpg?w=200", false), "cat.jpg · failed: HTTP 404");
+        assert_eq!(ImageInfo::default().describe("https://example.com/", true), "example.com · loading");
+
+        assert_eq!(format_size(812), "812 B");
+        assert_eq!(format_size(3 * 1024 * 1024 + 200_000), "3.2 MB");
+    }
+}
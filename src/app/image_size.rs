@@ -0,0 +1,178 @@
+//! How big an image on a line of its own is drawn in the content view. A
+//! size can be asked for in the markdown, Obsidian style in pixels with
+//! `![diagram|300](path)` or `![diagram|300x200](path)`, or in terminal
+//! cells with a title like `![diagram](path "height=10 width=40")`.
+//! Otherwise the image is drawn at its own size, no taller than
+//! `images.max_height_rows`.
+
+/// Rows an image takes while its size isn't known yet, e.g. while a remote
+/// one is loading.
+const UNKNOWN_IMAGE_ROWS: u16 = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    Pixels(u32),
+    Cells(u16),
+}
+
+impl Length {
+    fn pixels(self, cell: u16) -> u32 {
+        match self {
+            Length::Pixels(px) => px,
+            Length::Cells(cells) => u32::from(cells) * u32::from(cell),
+        }
+    }
+}
+
+/// The size asked for in the markdown, if any.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ImageSize {
+    pub width: Option<Length>,
+    pub height: Option<Length>,
+}
+
+impl ImageSize {
+    fn is_set(self) -> bool {
+        self.width.is_some() || self.height.is_some()
+    }
+}
+
+/// The target and requested size of an image that is a line of its own,
+/// like `![alt|300](path "height=10")`.
+pub fn parse_image_line(line: &str) -> Option<(String, ImageSize)> {
+    if !line.starts_with("![") {
+        return None;
+    }
+    let start = line.find("](")?;
+    let end = start + line[start..].find(')')?;
+    let (alt, inner) = (&line[2..start], &line[start + 2..end]);
+
+    let (path, title) = match inner.find(" \"") {
+        Some(quote) if inner.len() > quote + 2 && inner.ends_with('"') => {
+            (inner[..quote].trim(), Some(&inner[quote + 2..inner.len() - 1]))
+        }
+        _ => (inner, None),
+    };
+    if path.is_empty() {
+        return None;
+    }
+
+    let mut size = ImageSize::default();
+    if let Some((_, dims)) = alt.rsplit_once('|') {
+        let (width, height) = match dims.trim().split_once('x') {
+            Some((w, h)) => (w.parse().ok(), h.parse().ok()),
+            None => (dims.trim().parse().ok(), None),
+        };
+        size.width = width.filter(|&w| w > 0).map(Length::Pixels);
+        size.height = height.filter(|&h| h > 0).map(Length::Pixels);
+    }
+    for attr in title.unwrap_or_default().split([' ', ',']) {
+        let Some((key, value)) = attr.split_once('=') else {
+            continue;
+        };
+        let Some(cells) = value.trim().parse().ok().filter(|&c: &u16| c > 0).map(Length::Cells) else {
+            continue;
+        };
+        match key.trim() {
+            "width" => size.width = Some(cells),
+            "height" => size.height = Some(cells),
+            _ => {}
+        }
+    }
+    Some((path.to_string(), size))
+}
+
+/// The columns and rows an image of `pixels` is drawn in, inside its border,
+/// with cells of `font` pixels. It keeps its aspect ratio and fits in
+/// `max_cols` by `max_rows`; `max_height_rows` only caps images whose size
+/// wasn't asked for. An image that isn't loaded yet gets a placeholder box.
+pub fn fit_image(
+    pixels: Option<(u32, u32)>,
+    font: (u16, u16),
+    size: ImageSize,
+    max_height_rows: u16,
+    max_cols: u16,
+    max_rows: u16,
+) -> (u16, u16) {
+    let max_rows = if size.is_set() { max_rows } else { max_rows.min(max_height_rows) }.max(1);
+    let max_cols = max_cols.max(1);
+    let (font_w, font_h) = (font.0.max(1), font.1.max(1));
+    let Some((image_w, image_h)) = pixels.filter(|&(w, h)| w > 0 && h > 0) else {
+        let rows = size.height.map_or(UNKNOWN_IMAGE_ROWS, |h| {
+            h.pixels(font_h).div_ceil(u32::from(font_h)).min(u32::from(u16::MAX)) as u16
+        });
+        return (max_cols, rows.clamp(1, max_rows));
+    };
+
+    let (image_w, image_h) = (image_w as f64, image_h as f64);
+    let (width, height) = match (size.width, size.height) {
+        (Some(w), Some(h)) => (w.pixels(font_w) as f64, h.pixels(font_h) as f64),
+        (Some(w), None) => {
+            let w = w.pixels(font_w) as f64;
+            (w, w * image_h / image_w)
+        }
+        (None, Some(h)) => {
+            let h = h.pixels(font_h) as f64;
+            (h * image_w / image_h, h)
+        }
+        (None, None) => (image_w, image_h),
+    };
+    let cols = width / f64::from(font_w);
+    let rows = height / f64::from(font_h);
+    let scale = (f64::from(max_cols) / cols).min(f64::from(max_rows) / rows).min(1.0);
+    let fit = |cells: f64, max: u16| ((cells * scale).ceil() as u16).clamp(1, max);
+    (fit(cols, max_cols), fit(rows, max_rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_image_line() {
+        let parse = |line: &str| parse_image_line(line).unwrap();
+        assert_eq!(parse("![diagram](img/a.png)"), ("img/a.png".to_string(), ImageSize::default()));
+        assert_eq!(
+            parse("![diagram|300](img/a.png)").1,
+            ImageSize { width: Some(Length::Pixels(300)), height: None }
+        );
+        assert_eq!(
+            parse("![diagram|300x200](img/a.png)").1,
+            ImageSize { width: Some(Length::Pixels(300)), height: Some(Length::Pixels(200)) }
+        );
+        assert_eq!(
+            parse(r#"![diagram](img/a.png "height=10 width=40")"#),
+            (
+                "img/a.png".to_string(),
+                ImageSize { width: Some(Length::Cells(40)), height: Some(Length::Cells(10)) }
+            )
+        );
+        // A title that isn't a size, and an alt with a pipe that isn't one
+        assert_eq!(parse(r#"![a|b](x.png "A diagram")"#), ("x.png".to_string(), ImageSize::default()));
+        assert_eq!(parse_image_line("![empty]()"), None);
+        assert_eq!(parse_image_line("text ![a](x.png)"), None);
+    }
+
+    #[test]
+    fn test_fit_image() {
+        let font = (8, 16);
+        let none = ImageSize::default();
+        // 800x400 px is 100x25 cells; capped at 15 rows, keeping the ratio
+        assert_eq!(fit_image(Some((800, 400)), font, none, 15, 200, 40), (60, 15));
+        // Narrower than the view
+        assert_eq!(fit_image(Some((800, 400)), font, none, 50, 50, 40), (50, 13));
+        // A small image stays small
+        assert_eq!(fit_image(Some((80, 32)), font, none, 15, 200, 40), (10, 2));
+
+        // Asked-for sizes may go past max_height_rows, not past the view
+        let rows = ImageSize { width: None, height: Some(Length::Cells(20)) };
+        assert_eq!(fit_image(Some((800, 400)), font, rows, 15, 200, 40), (80, 20));
+        let px = ImageSize { width: Some(Length::Pixels(160)), height: None };
+        assert_eq!(fit_image(Some((800, 400)), font, px, 15, 200, 40), (20, 5));
+        assert_eq!(fit_image(Some((800, 400)), font, rows, 15, 200, 8), (32, 8));
+
+        // Not loaded yet
+        assert_eq!(fit_image(None, font, none, 15, 70, 40), (70, 6));
+        assert_eq!(fit_image(None, font, rows, 15, 70, 40), (70, 20));
+    }
+}
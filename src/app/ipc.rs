@@ -0,0 +1,368 @@
+//! Scripting ekphos from other tools. With `ipc = true` a Unix socket in the
+//! cache folder takes one JSON command per line, e.g.
+//! `{"command": "open-note", "note": "projects/plan"}`, and answers each with
+//! a line like `{"success": true, "result": ...}` or
+//! `{"success": false, "error": "..."}`.
+//!
+//! Connections are served on their own threads, which only parse commands
+//! and pass them to the main loop; [`App::poll_ipc`](super::App::poll_ipc)
+//! runs them there and sends the answer back.
+
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::Note;
+
+/// Longest command line read; a longer one gets an error and the
+/// connection is closed.
+const MAX_LINE: u64 = 1024 * 1024;
+/// Commands taken per [`RATE_WINDOW`], over all connections.
+const RATE_LIMIT: usize = 50;
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+/// How long a connection waits for the main loop to run its command.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Most matches a `search` returns.
+const MAX_SEARCH_RESULTS: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum IpcCommand {
+    /// `note` is a path, absolute or relative to the notes folder, or a title
+    OpenNote { note: String },
+    GetCurrentNote,
+    /// Insert at the editor cursor, opening the current note for editing
+    InsertText { text: String },
+    Search { query: String },
+    ListNotes,
+    Save,
+}
+
+impl IpcCommand {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        serde_json::from_str(line).map_err(|e| format!("Invalid command: {}", e))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IpcResponse {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl IpcResponse {
+    pub fn ok(result: Value) -> Self {
+        Self { success: true, result: Some(result), error: None }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self { success: false, result: None, error: Some(message.into()) }
+    }
+
+    fn to_line(&self) -> String {
+        let mut line = serde_json::to_string(self).unwrap_or_default();
+        line.push('\n');
+        line
+    }
+}
+
+/// A command waiting for the main loop, with the way back to its connection.
+pub struct IpcRequest {
+    pub command: IpcCommand,
+    reply: Sender<IpcResponse>,
+}
+
+impl IpcRequest {
+    pub fn respond(self, response: IpcResponse) {
+        // The connection may have timed out or gone away meanwhile
+        let _ = self.reply.send(response);
+    }
+}
+
+/// Refuses commands past [`RATE_LIMIT`] in any [`RATE_WINDOW`].
+#[derive(Debug, Default)]
+struct RateLimiter {
+    recent: VecDeque<Instant>,
+}
+
+impl RateLimiter {
+    fn allow(&mut self, now: Instant) -> bool {
+        while self.recent.front().is_some_and(|at| now.duration_since(*at) >= RATE_WINDOW) {
+            self.recent.pop_front();
+        }
+        if self.recent.len() >= RATE_LIMIT {
+            return false;
+        }
+        self.recent.push_back(now);
+        true
+    }
+}
+
+/// The listening socket, removed again when this is dropped.
+pub struct IpcServer {
+    path: PathBuf,
+    requests: Receiver<IpcRequest>,
+}
+
+impl IpcServer {
+    /// Listen on `path`. A socket left behind by an ekphos that didn't exit
+    /// cleanly is replaced; one another ekphos is still listening on isn't.
+    #[cfg(unix)]
+    pub fn start(path: &Path) -> io::Result<Self> {
+        use std::os::unix::fs::PermissionsExt;
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    format!("{} is in use by another ekphos", path.display()),
+                ));
+            }
+            std::fs::remove_file(path)?;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        // Only the user running ekphos may drive it
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+
+        let (sender, requests) = mpsc::channel();
+        let limiter = Arc::new(Mutex::new(RateLimiter::default()));
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (sender, limiter) = (sender.clone(), Arc::clone(&limiter));
+                std::thread::spawn(move || serve(stream, &sender, &limiter));
+            }
+        });
+        Ok(Self { path: path.to_path_buf(), requests })
+    }
+
+    #[cfg(not(unix))]
+    pub fn start(_path: &Path) -> io::Result<Self> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "IPC needs Unix domain sockets"))
+    }
+
+    /// The next command waiting to run, if any.
+    pub fn try_recv(&self) -> Option<IpcRequest> {
+        self.requests.try_recv().ok()
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Answer the commands on one connection until it closes.
+fn serve<S: Read + Write>(stream: S, sender: &Sender<IpcRequest>, limiter: &Mutex<RateLimiter>) {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut line = String::new();
+        match reader.by_ref().take(MAX_LINE + 1).read_line(&mut line) {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {}
+        }
+        let too_long = line.len() as u64 > MAX_LINE;
+        let response = if too_long {
+            IpcResponse::error(format!("Command longer than {} bytes", MAX_LINE))
+        } else if line.trim().is_empty() {
+            continue;
+        } else if !limiter.lock().is_ok_and(|mut limiter| limiter.allow(Instant::now())) {
+            IpcResponse::error("Too many commands, try again shortly")
+        } else {
+            match IpcCommand::parse(line.trim()) {
+                Ok(command) => run_on_main_loop(command, sender),
+                Err(e) => IpcResponse::error(e),
+            }
+        };
+        let stream = reader.get_mut();
+        if stream.write_all(response.to_line().as_bytes()).and_then(|_| stream.flush()).is_err() || too_long {
+            return;
+        }
+    }
+}
+
+fn run_on_main_loop(command: IpcCommand, sender: &Sender<IpcRequest>) -> IpcResponse {
+    let (reply, response) = mpsc::channel();
+    if sender.send(IpcRequest { command, reply }).is_err() {
+        return IpcResponse::error("ekphos is shutting down");
+    }
+    response
+        .recv_timeout(REPLY_TIMEOUT)
+        .unwrap_or_else(|_| IpcResponse::error("ekphos didn't answer in time"))
+}
+
+/// Send one command line to the ekphos listening on `path` and return its
+/// answer, for `ekphos --ipc-send`.
+#[cfg(unix)]
+pub fn send(path: &Path, command: &str) -> io::Result<String> {
+    let mut stream = std::os::unix::net::UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(REPLY_TIMEOUT + Duration::from_secs(1)))?;
+    writeln!(stream, "{}", command.trim())?;
+    let mut response = String::new();
+    BufReader::new(stream).read_line(&mut response)?;
+    Ok(response.trim_end().to_string())
+}
+
+#[cfg(not(unix))]
+pub fn send(_path: &Path, _command: &str) -> io::Result<String> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "IPC needs Unix domain sockets"))
+}
+
+/// The note `name` refers to: an absolute path, a path relative to
+/// `notes_path` with or without `.md`, or else a title.
+pub fn find_note(notes: &[Note], notes_path: &Path, name: &str) -> Option<usize> {
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    let path = notes_path.join(name);
+    let candidates = [path.clone(), path.with_extension("md"), notes_path.join(format!("{}.md", name))];
+    notes
+        .iter()
+        .position(|note| note.file_path.as_ref().is_some_and(|p| candidates.contains(p)))
+        .or_else(|| notes.iter().position(|note| note.title.eq_ignore_ascii_case(name)))
+}
+
+pub fn note_summary(note: &Note) -> Value {
+    json!({ "title": note.title, "path": note_path(note) })
+}
+
+fn note_path(note: &Note) -> Option<String> {
+    note.file_path.as_ref().map(|path| path.to_string_lossy().to_string())
+}
+
+/// Lines containing `query`, ignoring case, as `{path, title, line, text}`
+/// with 1-based line numbers. Locked notes aren't searched.
+pub fn search_notes(notes: &[Note], query: &str) -> Result<Value, String> {
+    let query = query.to_lowercase();
+    if query.trim().is_empty() {
+        return Err("Search query cannot be empty".to_string());
+    }
+    let matches: Vec<Value> = notes
+        .iter()
+        .flat_map(|note| {
+            note.content
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| line.to_lowercase().contains(&query))
+                .map(move |(idx, line)| {
+                    json!({ "path": note_path(note), "title": note.title, "line": idx + 1, "text": line })
+                })
+        })
+        .take(MAX_SEARCH_RESULTS)
+        .collect();
+    Ok(Value::Array(matches))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn vault(name: &str) -> (PathBuf, Vec<Note>) {
+        let dir = std::env::temp_dir().join(format!("ekphos-ipc-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("projects")).unwrap();
+        fs::write(dir.join("Inbox.md"), "# Inbox\n\n- call the plumber\n").unwrap();
+        fs::write(dir.join("projects/Plan.md"), "# Plan\n\nPlumbing first, then paint\n").unwrap();
+        let notes = ["Inbox.md", "projects/Plan.md"].map(|p| Note::read(&dir.join(p)).unwrap()).to_vec();
+        (dir, notes)
+    }
+
+    #[test]
+    fn test_parse_each_command() {
+        let parse = |line: &str| IpcCommand::parse(line).unwrap();
+        assert_eq!(
+            parse(r#"{"command": "open-note", "note": "projects/Plan"}"#),
+            IpcCommand::OpenNote { note: "projects/Plan".to_string() }
+        );
+        assert_eq!(parse(r#"{"command": "get-current-note"}"#), IpcCommand::GetCurrentNote);
+        assert_eq!(
+            parse(r#"{"command": "insert-text", "text": "- [ ] milk\n"}"#),
+            IpcCommand::InsertText { text: "- [ ] milk\n".to_string() }
+        );
+        assert_eq!(
+            parse(r#"{"command": "search", "query": "plumb"}"#),
+            IpcCommand::Search { query: "plumb".to_string() }
+        );
+        assert_eq!(parse(r#"{"command": "list-notes"}"#), IpcCommand::ListNotes);
+        assert_eq!(parse(r#"{"command": "save"}"#), IpcCommand::Save);
+
+        assert!(IpcCommand::parse(r#"{"command": "format-disk"}"#).is_err());
+        assert!(IpcCommand::parse(r#"{"command": "open-note"}"#).is_err());
+        assert!(IpcCommand::parse("open-note Plan").is_err());
+    }
+
+    #[test]
+    fn test_find_list_and_search() {
+        let (dir, notes) = vault("find");
+        assert_eq!(find_note(&notes, &dir, "projects/Plan"), Some(1));
+        assert_eq!(find_note(&notes, &dir, "projects/Plan.md"), Some(1));
+        assert_eq!(find_note(&notes, &dir, &dir.join("Inbox.md").to_string_lossy()), Some(0));
+        assert_eq!(find_note(&notes, &dir, "inbox"), Some(0));
+        assert_eq!(find_note(&notes, &dir, "Someday"), None);
+
+        assert_eq!(note_summary(&notes[0])["title"], "Inbox");
+
+        let found = search_notes(&notes, "PLUMB").unwrap();
+        let found: Vec<(&str, u64)> =
+            found.as_array().unwrap().iter().map(|m| (m["title"].as_str().unwrap(), m["line"].as_u64().unwrap())).collect();
+        assert_eq!(found, [("Inbox", 3), ("Plan", 3)]);
+        assert!(search_notes(&notes, " ").is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let mut limiter = RateLimiter::default();
+        let start = Instant::now();
+        assert!((0..RATE_LIMIT).all(|_| limiter.allow(start)));
+        assert!(!limiter.allow(start));
+        assert!(limiter.allow(start + RATE_WINDOW));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_round_trip_over_socket() {
+        let dir = std::env::temp_dir().join(format!("ekphos-ipc-socket-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("ipc.sock");
+        let server = IpcServer::start(&path).unwrap();
+        assert!(IpcServer::start(&path).is_err());
+
+        // Stand in for the main loop
+        let main_loop = std::thread::spawn(move || {
+            let request = loop {
+                if let Some(request) = server.try_recv() {
+                    break request;
+                }
+                std::thread::sleep(Duration::from_millis(5));
+            };
+            assert_eq!(request.command, IpcCommand::ListNotes);
+            request.respond(IpcResponse::ok(json!(["Inbox"])));
+            server
+        });
+
+        let answer = send(&path, r#"{"command": "list-notes"}"#).unwrap();
+        assert_eq!(answer, r#"{"success":true,"result":["Inbox"]}"#);
+        let answer: Value = serde_json::from_str(&send(&path, "{").unwrap()).unwrap();
+        assert_eq!(answer["success"], false);
+
+        drop(main_loop.join().unwrap());
+        assert!(!path.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
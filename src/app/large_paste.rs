@@ -0,0 +1,86 @@
+//! Pastes too big to insert in one go. Past `editor.large_paste_kb` or
+//! `editor.large_paste_lines` they're confirmed first, then go in a chunk per
+//! turn of the event loop, so the screen keeps up and Esc can stop them.
+
+use crate::editor::BulkInsert;
+
+/// How much goes in per turn of the event loop
+const CHUNK_BYTES: usize = 64 * 1024;
+
+/// Whether `text` needs confirming before it's pasted. A limit of 0 is off.
+pub fn is_large(text: &str, max_kb: usize, max_lines: usize) -> bool {
+    (max_kb > 0 && text.len() > max_kb * 1024) || (max_lines > 0 && text.lines().count() > max_lines)
+}
+
+/// Where the piece of `text` starting at byte `from` ends: at most `max`
+/// bytes on, after the last newline in them when there is one, and never
+/// inside a character.
+fn chunk_end(text: &str, from: usize, max: usize) -> usize {
+    let limit = from + max;
+    if limit >= text.len() {
+        return text.len();
+    }
+    if let Some(newline) = text.as_bytes()[from..limit].iter().rposition(|&b| b == b'\n') {
+        return from + newline + 1;
+    }
+    let mut end = limit;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    end
+}
+
+/// A paste going into the editor a chunk at a time.
+#[derive(Debug)]
+pub struct PasteJob {
+    text: String,
+    done: usize,
+    bulk: BulkInsert,
+}
+
+impl PasteJob {
+    pub fn new(text: String, bulk: BulkInsert) -> Self {
+        Self { text, done: 0, bulk }
+    }
+
+    /// The next chunk to insert, or `None` once it's all in.
+    pub fn next_chunk(&mut self) -> Option<&str> {
+        let start = self.done;
+        if start >= self.text.len() {
+            return None;
+        }
+        self.done = chunk_end(&self.text, start, CHUNK_BYTES);
+        Some(&self.text[start..self.done])
+    }
+
+    pub fn percent_done(&self) -> usize {
+        (self.done * 100).checked_div(self.text.len()).unwrap_or(100)
+    }
+
+    /// What to hand [`crate::editor::Editor::end_bulk_insert`]: the text that
+    /// went in, all of it unless the paste was stopped.
+    pub fn finish(mut self) -> (BulkInsert, String) {
+        self.text.truncate(self.done);
+        (self.bulk, self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_end_at_lines_and_characters() {
+        let text = "ab\ncd\néé";
+        assert_eq!(chunk_end(text, 0, 4), 3);
+        assert_eq!(chunk_end(text, 3, 5), 6);
+        // No newline in reach: stop before the character the limit splits
+        assert_eq!(chunk_end(text, 6, 3), 8);
+        assert_eq!(chunk_end(text, 8, 4), text.len());
+
+        assert!(is_large(&"x".repeat(2048), 1, 0));
+        assert!(!is_large(&"x".repeat(1024), 1, 0));
+        assert!(is_large("a\nb\nc", 0, 2));
+        assert!(!is_large(&"x\n".repeat(5000), 0, 0));
+    }
+}
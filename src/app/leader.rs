@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// How long a partial leader sequence waits for its next key.
+const LEADER_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Named actions that `[leader]` config entries can map to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaderAction {
+    Save,
+    Quit,
+    FileFinder,
+    ContentSearch,
+    BufferSearch,
+    Graph,
+    Help,
+    ToggleSidebar,
+    ToggleOutline,
+    Zen,
+    ThemeSelector,
+    Journal,
+    Quickfix,
+    Scaffold,
+    Vault,
+    ReloadConfig,
+    Frontmatter,
+}
+
+/// Config names of the actions.
+const ACTION_NAMES: [(&str, LeaderAction); 17] = [
+    ("save", LeaderAction::Save),
+    ("quit", LeaderAction::Quit),
+    ("file_finder", LeaderAction::FileFinder),
+    ("content_search", LeaderAction::ContentSearch),
+    ("buffer_search", LeaderAction::BufferSearch),
+    ("graph", LeaderAction::Graph),
+    ("help", LeaderAction::Help),
+    ("toggle_sidebar", LeaderAction::ToggleSidebar),
+    ("toggle_outline", LeaderAction::ToggleOutline),
+    ("zen", LeaderAction::Zen),
+    ("theme", LeaderAction::ThemeSelector),
+    ("journal", LeaderAction::Journal),
+    ("quickfix", LeaderAction::Quickfix),
+    ("scaffold", LeaderAction::Scaffold),
+    ("vault", LeaderAction::Vault),
+    ("reload_config", LeaderAction::ReloadConfig),
+    ("frontmatter", LeaderAction::Frontmatter),
+];
+
+impl LeaderAction {
+    pub fn from_name(name: &str) -> Option<Self> {
+        ACTION_NAMES.iter().find(|(n, _)| *n == name).map(|&(_, action)| action)
+    }
+
+    pub fn name(self) -> &'static str {
+        ACTION_NAMES.iter().find(|(_, a)| *a == self).map_or("", |(name, _)| name)
+    }
+}
+
+/// Parse the `leader_key` config value: `"space"` or a single character.
+pub fn parse_leader_key(value: &str) -> Option<char> {
+    if value.eq_ignore_ascii_case("space") {
+        return Some(' ');
+    }
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+/// Leader key sequences resolved from the `[leader]` config table. Entries
+/// naming an unknown action are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct LeaderMap {
+    bindings: BTreeMap<String, LeaderAction>,
+}
+
+impl LeaderMap {
+    pub fn from_config(entries: &BTreeMap<String, String>) -> Self {
+        let bindings = entries
+            .iter()
+            .filter(|(keys, _)| !keys.is_empty())
+            .filter_map(|(keys, name)| Some((keys.clone(), LeaderAction::from_name(name)?)))
+            .collect();
+        Self { bindings }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    fn get(&self, keys: &str) -> Option<LeaderAction> {
+        self.bindings.get(keys).copied()
+    }
+
+    /// The shortest sequence bound to `action`.
+    pub fn keys_for(&self, action: LeaderAction) -> Option<&str> {
+        self.bindings
+            .iter()
+            .filter(|(_, a)| **a == action)
+            .map(|(keys, _)| keys.as_str())
+            .min_by_key(|keys| keys.len())
+    }
+
+    fn has_longer_match(&self, keys: &str) -> bool {
+        self.bindings
+            .keys()
+            .any(|seq| seq.len() > keys.len() && seq.starts_with(keys))
+    }
+}
+
+/// Keys typed since the leader key, while a sequence is pending.
+#[derive(Debug, Clone, Default)]
+pub struct LeaderState {
+    keys: Option<String>,
+    started_at: Option<Instant>,
+}
+
+impl LeaderState {
+    /// Begin a sequence at `now`, the time by the app's clock.
+    pub fn start(&mut self, now: Instant) {
+        self.keys = Some(String::new());
+        self.started_at = Some(now);
+    }
+
+    pub fn cancel(&mut self) {
+        self.keys = None;
+        self.started_at = None;
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.keys.is_some()
+    }
+
+    /// The keys typed so far, for the status bar.
+    pub fn keys(&self) -> Option<&str> {
+        self.keys.as_deref()
+    }
+
+    pub fn is_expired(&self, now: Instant) -> bool {
+        self.started_at
+            .is_some_and(|started| now.saturating_duration_since(started) >= LEADER_TIMEOUT)
+    }
+
+    /// Add a key to the pending sequence. Returns the action once the sequence
+    /// matches a binding; the state stays pending only while the keys are a
+    /// prefix of some longer binding, and is cleared otherwise.
+    pub fn feed(&mut self, c: char, map: &LeaderMap, now: Instant) -> Option<LeaderAction> {
+        let keys = self.keys.as_mut()?;
+        keys.push(c);
+
+        let exact = map.get(keys);
+        if exact.is_none() && map.has_longer_match(keys) {
+            self.started_at = Some(now);
+            return None;
+        }
+        self.cancel();
+        exact
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_map() -> LeaderMap {
+        LeaderMap::from_config(&crate::config::Config::default().leader)
+    }
+
+    #[test]
+    fn test_parse_leader_key() {
+        assert_eq!(parse_leader_key("space"), Some(' '));
+        assert_eq!(parse_leader_key(","), Some(','));
+        assert_eq!(parse_leader_key("ab"), None);
+        assert_eq!(parse_leader_key(""), None);
+    }
+
+    #[test]
+    fn test_leader_w_triggers_save() {
+        let map = default_map();
+        let mut state = LeaderState::default();
+        state.start(Instant::now());
+        assert_eq!(state.feed('w', &map, Instant::now()), Some(LeaderAction::Save));
+        assert!(!state.is_pending());
+    }
+
+    #[test]
+    fn test_unmapped_leader_key_is_noop_and_clears_pending() {
+        let map = default_map();
+        let mut state = LeaderState::default();
+        state.start(Instant::now());
+        assert_eq!(state.feed('x', &map, Instant::now()), None);
+        assert!(!state.is_pending());
+        assert_eq!(state.keys(), None);
+    }
+
+    #[test]
+    fn test_multi_key_sequence_waits_for_next_key() {
+        let map = default_map();
+        let mut state = LeaderState::default();
+        state.start(Instant::now());
+        assert_eq!(state.feed('f', &map, Instant::now()), None);
+        assert_eq!(state.keys(), Some("f"));
+        assert_eq!(state.feed('f', &map, Instant::now()), Some(LeaderAction::FileFinder));
+        assert!(!state.is_pending());
+    }
+
+    #[test]
+    fn test_sequence_expires_a_timeout_after_its_last_key() {
+        let map = default_map();
+        let mut state = LeaderState::default();
+        let start = Instant::now();
+        state.start(start);
+        assert!(!state.is_expired(start + LEADER_TIMEOUT / 2));
+        // Each key that keeps the sequence going restarts the wait
+        assert_eq!(state.feed('f', &map, start + LEADER_TIMEOUT / 2), None);
+        assert!(!state.is_expired(start + LEADER_TIMEOUT));
+        assert!(state.is_expired(start + LEADER_TIMEOUT * 3 / 2));
+    }
+
+    #[test]
+    fn test_unknown_action_names_are_ignored() {
+        let mut entries = BTreeMap::new();
+        entries.insert("x".to_string(), "does_not_exist".to_string());
+        let map = LeaderMap::from_config(&entries);
+        let mut state = LeaderState::default();
+        state.start(Instant::now());
+        assert_eq!(state.feed('x', &map, Instant::now()), None);
+    }
+}
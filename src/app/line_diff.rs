@@ -0,0 +1,98 @@
+//! Line diff of a note's text before and after it was reloaded from disk, to
+//! highlight what changed outside ekphos.
+
+/// Largest old × new line count diffed exactly; past it a changed region is
+/// taken as replaced wholesale.
+const MAX_DIFF_CELLS: usize = 1_000_000;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LineDiff {
+    /// Lines of the new text that were added or changed.
+    pub changed: Vec<usize>,
+    /// Lines changed, counting a line edited in place once.
+    pub count: usize,
+}
+
+/// Diff `old` against `new` line by line.
+pub fn diff_lines(old: &str, new: &str) -> LineDiff {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut diff = LineDiff::default();
+    if old_mid.len() * new_mid.len() > MAX_DIFF_CELLS {
+        diff.changed.extend(prefix..prefix + new_mid.len());
+        diff.count = old_mid.len().max(new_mid.len());
+        return diff;
+    }
+
+    // lcs[i][j]: longest common subsequence of old_mid[i..] and new_mid[j..]
+    let (m, n) = (old_mid.len(), new_mid.len());
+    let mut lcs = vec![vec![0u32; n + 1]; m + 1];
+    for i in (0..m).rev() {
+        for j in (0..n).rev() {
+            lcs[i][j] = if old_mid[i] == new_mid[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    // Walk the table, grouping runs of removed and added lines into hunks
+    let (mut i, mut j) = (0, 0);
+    let (mut removed, mut added) = (0, 0);
+    while i < m || j < n {
+        if i < m && j < n && old_mid[i] == new_mid[j] {
+            diff.count += removed.max(added);
+            removed = 0;
+            added = 0;
+            i += 1;
+            j += 1;
+        } else if j < n && (i == m || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            diff.changed.push(prefix + j);
+            added += 1;
+            j += 1;
+        } else {
+            removed += 1;
+            i += 1;
+        }
+    }
+    diff.count += removed.max(added);
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines_edits_and_insertions() {
+        let old = "a\nb\nc\nd\n";
+        let new = "a\nB\nc\nnew\nd\n";
+        assert_eq!(
+            diff_lines(old, new),
+            LineDiff {
+                changed: vec![1, 3],
+                count: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_diff_lines_removals_count_but_highlight_nothing() {
+        let diff = diff_lines("a\nb\nc\n", "a\nc\n");
+        assert!(diff.changed.is_empty());
+        assert_eq!(diff.count, 1);
+        assert_eq!(diff_lines("same\n", "same"), LineDiff::default());
+    }
+}
@@ -0,0 +1,150 @@
+//! Line endings of notes on disk. Notes are held with `\n` only and get their
+//! file's ending back when saved, so a CRLF note doesn't turn into a
+//! whole-file diff on its first save. The same goes for the final newline.
+
+use std::borrow::Cow;
+
+use crate::config::LineEnding;
+
+/// The line ending a file was written with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Newline {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+impl Newline {
+    /// The ending most of `text`'s lines use; LF on a tie or without any.
+    pub fn detect(text: &str) -> Self {
+        let crlf = text.matches("\r\n").count();
+        let lf = text.matches('\n').count() - crlf;
+        if crlf > lf {
+            Newline::Crlf
+        } else {
+            Newline::Lf
+        }
+    }
+
+    /// The ending to save with under `policy`, for a file loaded with `self`.
+    pub fn for_saving(self, policy: LineEnding) -> Self {
+        match policy {
+            LineEnding::Preserve => self,
+            LineEnding::Lf => Newline::Lf,
+            LineEnding::Crlf => Newline::Crlf,
+        }
+    }
+}
+
+/// `text` with every CRLF turned into LF, for the in-memory copy.
+pub fn normalize(text: String) -> String {
+    if text.contains("\r\n") {
+        text.replace("\r\n", "\n")
+    } else {
+        text
+    }
+}
+
+/// LF-only `text` with `newline` endings, as written to disk.
+pub fn with_newline(text: &str, newline: Newline) -> Cow<'_, str> {
+    match newline {
+        Newline::Lf => Cow::Borrowed(text),
+        Newline::Crlf => Cow::Owned(text.replace('\n', "\r\n")),
+    }
+}
+
+/// Note text for the editor's `lines`. It ends with a newline if `previous`,
+/// the note's text before the edit, did, or with `ensure` unless it's empty.
+pub fn join_lines(lines: &[&str], previous: &str, ensure: bool) -> String {
+    let mut text = lines.join("\n");
+    let empty = lines.len() <= 1 && text.is_empty();
+    if previous.ends_with('\n') || (ensure && !empty) {
+        text.push('\n');
+    }
+    text
+}
+
+/// Whether the editor's `lines` hold the note text `content`. One final
+/// newline and CRLF vs LF don't count as differences, since neither survives
+/// the split into lines.
+pub fn same_text(lines: &[&str], content: &str) -> bool {
+    let content = content.strip_suffix('\n').unwrap_or(content);
+    let content = content.strip_suffix('\r').unwrap_or(content);
+    let mut content_lines = content.split('\n').map(|line| line.strip_suffix('\r').unwrap_or(line));
+    let mut lines = lines.iter().map(|line| line.strip_suffix('\r').unwrap_or(line));
+    loop {
+        match (lines.next(), content_lines.next()) {
+            (Some(a), Some(b)) if a == b => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_dominant_newline() {
+        assert_eq!(Newline::detect("a\r\nb\r\nc\n"), Newline::Crlf);
+        assert_eq!(Newline::detect("a\nb\r\nc\n"), Newline::Lf);
+        assert_eq!(Newline::detect("a\r\nb\n"), Newline::Lf);
+        assert_eq!(Newline::detect("no newline"), Newline::Lf);
+    }
+
+    #[test]
+    fn test_crlf_round_trip() {
+        let disk = "# Title\r\n\r\n- item\r\n".to_string();
+        let newline = Newline::detect(&disk);
+        let content = normalize(disk.clone());
+        assert_eq!(content, "# Title\n\n- item\n");
+
+        let preserved = newline.for_saving(LineEnding::Preserve);
+        assert_eq!(with_newline(&content, preserved), disk);
+        let converted = newline.for_saving(LineEnding::Lf);
+        assert_eq!(with_newline(&content, converted), "# Title\n\n- item\n");
+        assert_eq!(with_newline("a\nb", Newline::Lf.for_saving(LineEnding::Crlf)), "a\r\nb");
+    }
+
+    #[test]
+    fn test_final_newline_round_trip() {
+        // What the editor holds for a note's text
+        let round_trip = |text: &str, ensure: bool| {
+            let lines: Vec<&str> = text.lines().collect();
+            let lines = if lines.is_empty() { vec![""] } else { lines };
+            join_lines(&lines, text, ensure)
+        };
+        assert_eq!(round_trip("# Note\nlast line", false), "# Note\nlast line");
+        assert_eq!(round_trip("# Note\nlast line\n", false), "# Note\nlast line\n");
+        assert_eq!(round_trip("# Note\n\n", false), "# Note\n\n");
+        assert_eq!(round_trip("", false), "");
+        assert_eq!(round_trip("\n", false), "\n");
+
+        assert_eq!(round_trip("# Note\nlast line", true), "# Note\nlast line\n");
+        assert_eq!(round_trip("", true), "");
+    }
+
+    #[test]
+    fn test_unchanged_buffer_is_same_text() {
+        // The buffer `enter_edit_mode` builds from a file's text
+        let opened = |disk: &str| {
+            let content = normalize(disk.to_string());
+            let lines: Vec<String> = content.lines().map(String::from).collect();
+            let lines = if lines.is_empty() { vec![String::new()] } else { lines };
+            (content, lines)
+        };
+        for disk in ["# Note\nlast line\n", "# Note\nlast line", "# Note\r\nlast line\r\n", "# Note\r\nlast", "", "\n", "a\n\n"] {
+            let (content, lines) = opened(disk);
+            let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+            assert!(same_text(&lines, &content), "{:?} opened as changed", disk);
+            // Not yet normalized text compares the same
+            assert!(same_text(&lines, disk), "{:?} opened as changed", disk);
+        }
+
+        assert!(!same_text(&["# Note", "last line", ""], "# Note\nlast line\n"));
+        assert!(!same_text(&["# Note"], "# Note\nlast line"));
+        assert!(!same_text(&["# Note", "last line!"], "# Note\r\nlast line\r\n"));
+        assert!(!same_text(&["x"], ""));
+    }
+}
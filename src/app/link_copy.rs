@@ -0,0 +1,60 @@
+//! `y` on a link in the content view: what goes on the clipboard for it.
+
+use std::path::Path;
+
+use crate::config::CopyWikiLink;
+
+use super::wiki_link::wiki_link_text;
+
+/// The link or image under the cursor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CopiedLink<'a> {
+    /// `[text](url)` or an inline `![alt](url)`
+    Url(&'a str),
+    Wiki { target: &'a str, heading: Option<&'a str> },
+    /// An image on a line of its own, by the path written in the note
+    Image(&'a str),
+}
+
+/// The text copied for `link`. `resolved` is the file a wiki link or image
+/// points at, when it was found; a wiki link nothing resolves to is copied
+/// as `[[target]]` either way.
+pub fn copy_text(link: CopiedLink, wiki: CopyWikiLink, resolved: Option<&Path>) -> String {
+    match link {
+        CopiedLink::Url(url) => url.to_string(),
+        CopiedLink::Image(path) => resolved.map_or_else(|| path.to_string(), |p| p.display().to_string()),
+        CopiedLink::Wiki { target, heading } => match (wiki, resolved) {
+            (CopyWikiLink::Path, Some(path)) => path.display().to_string(),
+            _ => wiki_link_text(target, heading, None),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_markdown_link_and_image() {
+        let url = CopiedLink::Url("https://example.com/paper.pdf");
+        assert_eq!(copy_text(url, CopyWikiLink::Path, None), "https://example.com/paper.pdf");
+
+        let image = CopiedLink::Image("img/chart.png");
+        assert_eq!(
+            copy_text(image, CopyWikiLink::Path, Some(Path::new("/notes/img/chart.png"))),
+            "/notes/img/chart.png"
+        );
+        assert_eq!(copy_text(image, CopyWikiLink::Path, None), "img/chart.png");
+    }
+
+    #[test]
+    fn test_copy_wiki_link() {
+        let link = CopiedLink::Wiki { target: "Reading List", heading: Some("Papers") };
+        let path = Path::new("/notes/research/Reading List.md");
+        assert_eq!(copy_text(link, CopyWikiLink::Path, Some(path)), "/notes/research/Reading List.md");
+        assert_eq!(copy_text(link, CopyWikiLink::Link, Some(path)), "[[Reading List#Papers]]");
+
+        let missing = CopiedLink::Wiki { target: "Not Yet", heading: None };
+        assert_eq!(copy_text(missing, CopyWikiLink::Path, None), "[[Not Yet]]");
+    }
+}
@@ -0,0 +1,283 @@
+//! The wiki links between notes, as the graph view draws them and as
+//! `:graph export` and `ekphos --export-graph` write them out, in Graphviz
+//! DOT or JSON.
+
+use std::fs;
+use std::path::Path;
+
+use super::encryption::NoteLock;
+use super::paths;
+use super::state::Note;
+use super::wiki_link::links_in;
+
+/// A resolved wiki link, by index into the notes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoteLink {
+    pub from: usize,
+    pub to: usize,
+    /// 1-based line of the file the link is written on
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphFormat {
+    Dot,
+    Json,
+}
+
+impl GraphFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "dot" => Some(GraphFormat::Dot),
+            "json" => Some(GraphFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            GraphFormat::Dot => "dot",
+            GraphFormat::Json => "json",
+        }
+    }
+}
+
+/// Every `.md` file under `notes_path`, skipping hidden folders the way the
+/// sidebar does.
+pub fn read_vault(notes_path: &Path) -> Vec<Note> {
+    let mut notes = Vec::new();
+    collect_notes(notes_path, &mut notes);
+    notes
+}
+
+fn collect_notes(dir: &Path, notes: &mut Vec<Note>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<_> = entries.flatten().map(|entry| entry.path()).collect();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            if !path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.')) {
+                collect_notes(&path, notes);
+            }
+        } else if path.extension().is_some_and(|e| e == "md") {
+            if let Ok(note) = Note::read(&path) {
+                notes.push(note);
+            }
+        }
+    }
+}
+
+/// The note a wiki link target points at. `folder/note` is looked up by path
+/// under `notes_path`; a bare title prefers a note in the root folder, then
+/// matches one anywhere.
+pub fn resolve_wiki_target(notes: &[Note], notes_path: &Path, target: &str) -> Option<usize> {
+    if target.is_empty() {
+        return None;
+    }
+    if target.contains('/') {
+        let expected = notes_path.join(format!("{}.md", target));
+        return notes.iter().position(|note| {
+            note.file_path.as_ref().is_some_and(|path| paths::same_path(path, &expected))
+        });
+    }
+    notes
+        .iter()
+        .position(|note| {
+            note.title.eq_ignore_ascii_case(target)
+                && note.file_path.as_ref().is_some_and(|path| path.parent() == Some(notes_path))
+        })
+        .or_else(|| notes.iter().position(|note| note.title.eq_ignore_ascii_case(target)))
+}
+
+/// The targets of the wiki links on `line`, leaving out those in inline code
+/// and dropping any `#heading` or `|alias`.
+pub fn wiki_targets(line: &str) -> Vec<String> {
+    links_in(line).into_iter().filter_map(|(_, link)| link.ok()).map(|link| link.target).collect()
+}
+
+/// Every wiki link between notes that can be read, in note and line order.
+/// Locked notes are left out at either end, and links to nothing are dropped.
+pub fn note_links(notes: &[Note], notes_path: &Path) -> Vec<NoteLink> {
+    let mut links = Vec::new();
+    for (from, note) in notes.iter().enumerate() {
+        if note.lock == NoteLock::Locked {
+            continue;
+        }
+        for (row, line) in note.content.lines().enumerate() {
+            for target in wiki_targets(line) {
+                if let Some(to) = resolve_wiki_target(notes, notes_path, &target)
+                    .filter(|&to| notes[to].lock != NoteLock::Locked)
+                {
+                    links.push(NoteLink { from, to, line: row + 1 });
+                }
+            }
+        }
+    }
+    links
+}
+
+/// The link graph of `notes` in `format`. Every readable note is a node,
+/// including those with no links, and every link is an edge, so a note that
+/// links to another twice has two.
+///
+/// The JSON is an object with `nodes` and `edges`. A node has an `id`, the
+/// note's `path` relative to `notes_path`, its `title` and its frontmatter
+/// `tags`; an edge has the `source` and `target` node ids and the `line` of
+/// the source note the link is on:
+///
+/// ```json
+/// {"nodes": [{"id": 0, "path": "work/Plan.md", "title": "Plan", "tags": ["q3"]}],
+///  "edges": [{"source": 0, "target": 0, "line": 12}]}
+/// ```
+///
+/// In DOT a node is `n<id>` labelled with its title, with `path` and `tags`
+/// attributes, and an edge carries a `line` attribute.
+pub fn export_graph(notes: &[Note], notes_path: &Path, format: GraphFormat) -> String {
+    let mut ids = vec![None; notes.len()];
+    let mut nodes = Vec::new();
+    for (idx, note) in notes.iter().enumerate() {
+        if note.lock == NoteLock::Locked {
+            continue;
+        }
+        ids[idx] = Some(nodes.len());
+        let path = note
+            .file_path
+            .as_ref()
+            .map(|path| path.strip_prefix(notes_path).unwrap_or(path).to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default();
+        let tags = note.frontmatter.as_ref().map(|fm| fm.tags.clone()).unwrap_or_default();
+        nodes.push((path, note.title.as_str(), tags));
+    }
+    let edges: Vec<(usize, usize, usize)> = note_links(notes, notes_path)
+        .into_iter()
+        .filter_map(|link| Some((ids[link.from]?, ids[link.to]?, link.line)))
+        .collect();
+
+    let mut out = String::new();
+    match format {
+        GraphFormat::Dot => {
+            out.push_str("digraph notes {\n");
+            for (id, (path, title, tags)) in nodes.iter().enumerate() {
+                out.push_str(&format!(
+                    "  n{} [label={}, path={}, tags={}];\n",
+                    id,
+                    dot_string(title),
+                    dot_string(path),
+                    dot_string(&tags.join(","))
+                ));
+            }
+            for (source, target, line) in edges {
+                out.push_str(&format!("  n{} -> n{} [line={}];\n", source, target, line));
+            }
+            out.push_str("}\n");
+        }
+        GraphFormat::Json => {
+            out.push_str("{\n  \"nodes\": [");
+            for (id, (path, title, tags)) in nodes.iter().enumerate() {
+                let tags: Vec<String> = tags.iter().map(|tag| json_string(tag)).collect();
+                out.push_str(if id == 0 { "\n    " } else { ",\n    " });
+                out.push_str(&format!(
+                    "{{\"id\": {}, \"path\": {}, \"title\": {}, \"tags\": [{}]}}",
+                    id,
+                    json_string(path),
+                    json_string(title),
+                    tags.join(", ")
+                ));
+            }
+            out.push_str(if nodes.is_empty() { "],\n  \"edges\": [" } else { "\n  ],\n  \"edges\": [" });
+            for (i, (source, target, line)) in edges.iter().enumerate() {
+                out.push_str(if i == 0 { "\n    " } else { ",\n    " });
+                out.push_str(&format!("{{\"source\": {}, \"target\": {}, \"line\": {}}}", source, target, line));
+            }
+            out.push_str(if edges.is_empty() { "]\n}\n" } else { "\n  ]\n}\n" });
+        }
+    }
+    out
+}
+
+fn dot_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn json_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn vault() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ekphos-link-graph-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("work")).unwrap();
+        fs::create_dir_all(dir.join(".trash")).unwrap();
+        fs::write(dir.join("Home.md"), "# Home\nSee [[work/Plan]] and [[Ideas#Later|ideas]].\n`[[Code]]` [[Plan]]\n").unwrap();
+        fs::write(dir.join("work/Plan.md"), "---\ntags: [q3, \"big \\\"one\\\"\"]\n---\nBack to [[home]].\n").unwrap();
+        fs::write(dir.join("Orphan.md"), "Nothing links here, [[Missing]] links nowhere.\n").unwrap();
+        fs::write(dir.join(".trash/Old.md"), "[[Home]]\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_wiki_targets() {
+        assert_eq!(wiki_targets("[[A]] `[[B]]` [[C#h|alias]] [[]] [[D"), ["A", "C"]);
+        assert_eq!(wiki_targets("`open [[E]]"), Vec::<String>::new());
+        assert_eq!(wiki_targets("[[C++ \\[advanced\\]|C++]]"), ["C++ [advanced]"]);
+    }
+
+    #[test]
+    fn test_export_graph() {
+        let dir = vault();
+        let notes = read_vault(&dir);
+        let titles: Vec<&str> = notes.iter().map(|note| note.title.as_str()).collect();
+        assert_eq!(titles, ["Home", "Orphan", "Plan"]);
+
+        let links = note_links(&notes, &dir);
+        assert_eq!(
+            links,
+            [
+                NoteLink { from: 0, to: 2, line: 2 },
+                NoteLink { from: 0, to: 2, line: 3 },
+                NoteLink { from: 2, to: 0, line: 4 },
+            ]
+        );
+
+        assert_eq!(
+            export_graph(&notes, &dir, GraphFormat::Json),
+            "{\n  \"nodes\": [\n    \
+            {\"id\": 0, \"path\": \"Home.md\", \"title\": \"Home\", \"tags\": []},\n    \
+            {\"id\": 1, \"path\": \"Orphan.md\", \"title\": \"Orphan\", \"tags\": []},\n    \
+            {\"id\": 2, \"path\": \"work/Plan.md\", \"title\": \"Plan\", \"tags\": [\"q3\", \"big \\\"one\\\"\"]}\n  ],\n  \
+            \"edges\": [\n    \
+            {\"source\": 0, \"target\": 2, \"line\": 2},\n    \
+            {\"source\": 0, \"target\": 2, \"line\": 3},\n    \
+            {\"source\": 2, \"target\": 0, \"line\": 4}\n  ]\n}\n"
+        );
+        assert_eq!(
+            export_graph(&notes, &dir, GraphFormat::Dot),
+            "digraph notes {\n  \
+            n0 [label=\"Home\", path=\"Home.md\", tags=\"\"];\n  \
+            n1 [label=\"Orphan\", path=\"Orphan.md\", tags=\"\"];\n  \
+            n2 [label=\"Plan\", path=\"work/Plan.md\", tags=\"q3,big \\\"one\\\"\"];\n  \
+            n0 -> n2 [line=2];\n  n0 -> n2 [line=3];\n  n2 -> n0 [line=4];\n}\n"
+        );
+        assert_eq!(export_graph(&[], &dir, GraphFormat::Json), "{\n  \"nodes\": [],\n  \"edges\": []\n}\n");
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,91 @@
+//! Files in the vault offered as markdown link targets (`](`), and the relative
+//! paths inserted for them.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Stop walking the vault after this many files so huge trees stay responsive.
+const MAX_LINK_TARGETS: usize = 5000;
+
+/// Every non-hidden file under `root`, depth first.
+pub fn vault_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    collect_files(root, &mut files);
+    files.sort();
+    files
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if files.len() >= MAX_LINK_TARGETS {
+            return;
+        }
+        let path = entry.path();
+        if path
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+        {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+/// Link text for `target` as seen from a note in `from_dir`: a relative path
+/// with `/` separators and spaces and parentheses percent-encoded.
+pub fn relative_link(from_dir: &Path, target: &Path) -> String {
+    let from: Vec<Component> = from_dir.components().collect();
+    let to: Vec<Component> = target.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().to_string()),
+    );
+    percent_encode_link(&parts.join("/"))
+}
+
+fn percent_encode_link(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for c in path.chars() {
+        match c {
+            ' ' => out.push_str("%20"),
+            '(' => out.push_str("%28"),
+            ')' => out.push_str("%29"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_link_same_dir() {
+        let link = relative_link(Path::new("/vault"), Path::new("/vault/My Note.md"));
+        assert_eq!(link, "My%20Note.md");
+    }
+
+    #[test]
+    fn test_relative_link_across_folders() {
+        let link = relative_link(
+            Path::new("/vault/projects/2024"),
+            Path::new("/vault/assets/diagram (v2).png"),
+        );
+        assert_eq!(link, "../../assets/diagram%20%28v2%29.png");
+
+        let link = relative_link(Path::new("/vault"), Path::new("/vault/assets/a.png"));
+        assert_eq!(link, "assets/a.png");
+    }
+}
@@ -0,0 +1,170 @@
+//! `[Page Title](url)` for a bare web URL pasted in insert mode, with
+//! `network.fetch_link_titles`. The URL goes in as pasted straight away; the
+//! page's `<title>` is fetched in the background and swapped in only if the
+//! URL is still where it was, untouched.
+
+use std::io::Read;
+use std::path::PathBuf;
+
+/// Most of a page read looking for its title.
+const MAX_PAGE_BYTES: u64 = 512 * 1024;
+
+/// A pasted URL waiting for its page title.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PastedLink {
+    /// The note it was pasted in
+    pub note: Option<PathBuf>,
+    pub row: usize,
+    pub col: usize,
+    pub url: String,
+    /// The char just before the URL when it was pasted
+    pub before: Option<char>,
+}
+
+impl PastedLink {
+    /// Whether `line` still has the URL where it was pasted, with nothing
+    /// typed onto either end of it.
+    pub fn is_intact(&self, line: &str) -> bool {
+        let chars: Vec<char> = line.chars().collect();
+        let end = self.col + self.url.chars().count();
+        if end > chars.len() || !chars[self.col..end].iter().copied().eq(self.url.chars()) {
+            return false;
+        }
+        let before = self.col.checked_sub(1).map(|i| chars[i]);
+        let continues = |c: &char| !c.is_whitespace() && !matches!(c, '.' | ',' | ';' | ':' | '!' | '?');
+        before == self.before && !chars.get(end).is_some_and(continues)
+    }
+
+    /// The markdown link replacing the URL.
+    pub fn markdown(&self, title: &str) -> String {
+        let mut text = String::with_capacity(title.len());
+        for c in title.chars() {
+            if matches!(c, '[' | ']' | '\\') {
+                text.push('\\');
+            }
+            text.push(c);
+        }
+        if self.url.matches('(').count() == self.url.matches(')').count() {
+            format!("[{}]({})", text, self.url)
+        } else {
+            format!("[{}](<{}>)", text, self.url)
+        }
+    }
+}
+
+/// Whether a title is worth fetching for `url`.
+pub fn is_web_url(url: &str) -> bool {
+    let lower = url.to_ascii_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+pub fn fetch_title_blocking(url: &str) -> Option<String> {
+    let response = ureq::get(url)
+        .set("User-Agent", "ekphos/0.4")
+        .call()
+        .ok()?;
+
+    let content_type = response
+        .header("Content-Type")
+        .unwrap_or("")
+        .to_lowercase();
+
+    if !content_type.starts_with("text/html") && !content_type.starts_with("application/xhtml") {
+        return None;
+    }
+
+    let mut bytes = Vec::new();
+    response.into_reader().take(MAX_PAGE_BYTES).read_to_end(&mut bytes).ok()?;
+
+    page_title(&String::from_utf8_lossy(&bytes))
+}
+
+/// The text of the first `<title>` in `html`, on one line.
+pub fn page_title(html: &str) -> Option<String> {
+    // ASCII lowercasing keeps byte offsets the same
+    let lower = html.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    let title = decode_entities(&html[start..end]);
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!title.is_empty()).then_some(title)
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').filter(|&semi| semi <= 10).and_then(|semi| {
+            let c = match &rest[1..semi] {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => ' ',
+                entity => {
+                    let code = match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => entity.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, semi))
+        });
+        match decoded {
+            Some((c, semi)) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_title() {
+        let html = "<html><head>\n<TITLE data-x=\"1\">\n  Rust &amp; Ratatui &#8211; a\n  guide </title></head></html>";
+        assert_eq!(page_title(html), Some("Rust & Ratatui – a guide".to_string()));
+        assert_eq!(page_title("<title>Fish &chips; &#x41;</title>"), Some("Fish &chips; A".to_string()));
+        assert_eq!(page_title("<title>  </title>"), None);
+        assert_eq!(page_title("<h1>No title</h1>"), None);
+    }
+
+    #[test]
+    fn test_is_intact() {
+        let link = PastedLink {
+            note: None,
+            row: 0,
+            col: 4,
+            url: "https://a.io/x".to_string(),
+            before: Some(' '),
+        };
+        assert!(link.is_intact("see https://a.io/x"));
+        assert!(link.is_intact("see https://a.io/x. Then"));
+        assert!(!link.is_intact("see https://a.io/xy"));
+        assert!(!link.is_intact("see https://a.io/"));
+        assert!(!link.is_intact("see(https://a.io/x"));
+        assert!(!link.is_intact("https://a.io/x"));
+    }
+
+    #[test]
+    fn test_markdown() {
+        let link = |url: &str| PastedLink { note: None, row: 0, col: 0, url: url.to_string(), before: None };
+        assert_eq!(link("https://a.io").markdown("A [draft]"), "[A \\[draft\\]](https://a.io)");
+        assert_eq!(link("https://w.org/A_(b)").markdown("A"), "[A](https://w.org/A_(b))");
+        assert_eq!(link("https://w.org/A_b)").markdown("A"), "[A](<https://w.org/A_b)>)");
+    }
+}
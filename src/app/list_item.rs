@@ -0,0 +1,118 @@
+//! List item markers and the continuation lines that belong to an item, for
+//! grouping indented lines under their bullet in the content view.
+
+/// A `- item`, `* item`, `+ item`, `1. item` or `1) item` line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ListMarker<'a> {
+    /// Columns of leading whitespace.
+    pub indent: usize,
+    pub marker: &'a str,
+    pub text: &'a str,
+}
+
+impl ListMarker<'_> {
+    /// Column the item's text starts at, which continuation lines line up with.
+    pub fn content_column(&self) -> usize {
+        self.indent + self.marker.len() + 1
+    }
+}
+
+pub fn parse_list_marker(line: &str) -> Option<ListMarker<'_>> {
+    let rest = line.trim_start();
+    let indent = leading_columns(line);
+    let marker_len = match rest.chars().next()? {
+        '-' | '*' | '+' => 1,
+        c if c.is_ascii_digit() => {
+            let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+            match rest[digits..].chars().next() {
+                Some('.' | ')') if digits <= 9 => digits + 1,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    let text = rest[marker_len..].strip_prefix(' ')?;
+    Some(ListMarker {
+        indent,
+        marker: &rest[..marker_len],
+        text,
+    })
+}
+
+/// Width of `line`'s leading whitespace, with tabs as four columns.
+pub fn leading_columns(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// Indent guides of `line`: the columns of the levels it's nested under, a
+/// level every `tab_width` columns of leading whitespace, where a tab runs to
+/// the next level. Columns are as drawn with tabs `drawn_tab` wide. There is
+/// a guide per level, so the count is the line's nesting depth.
+pub fn indent_guides(line: &str, tab_width: usize, drawn_tab: usize) -> Vec<usize> {
+    let tab_width = tab_width.max(1);
+    let mut guides = Vec::new();
+    let (mut width, mut drawn) = (0, 0);
+    for c in line.chars().take_while(|c| c.is_whitespace()) {
+        if width % tab_width == 0 {
+            guides.push(drawn);
+        }
+        if c == '\t' {
+            width = (width / tab_width + 1) * tab_width;
+            drawn += drawn_tab;
+        } else {
+            width += 1;
+            drawn += 1;
+        }
+    }
+    guides
+}
+
+/// Text of a definition line (`: definition`) following a term.
+pub fn definition_text(line: &str) -> Option<&str> {
+    line.strip_prefix(": ").map(str::trim_start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_list_marker() {
+        let item = parse_list_marker("- milk").unwrap();
+        assert_eq!((item.indent, item.marker, item.text), (0, "-", "milk"));
+        assert_eq!(item.content_column(), 2);
+
+        let item = parse_list_marker("    12. step").unwrap();
+        assert_eq!((item.indent, item.marker, item.text), (4, "12.", "step"));
+        assert_eq!(item.content_column(), 8);
+
+        assert_eq!(parse_list_marker("\t+ x").unwrap().content_column(), 6);
+        assert_eq!(parse_list_marker("3) x").unwrap().marker, "3)");
+        assert_eq!(parse_list_marker("-not a list"), None);
+        assert_eq!(parse_list_marker("2024. was a year").map(|m| m.marker), Some("2024."));
+        assert_eq!(parse_list_marker("1.5 cups"), None);
+        assert_eq!(parse_list_marker("plain"), None);
+    }
+
+    #[test]
+    fn test_indent_guides() {
+        assert!(indent_guides("- top", 4, 4).is_empty());
+        assert_eq!(indent_guides("  - two spaces", 4, 4), [0]);
+        assert_eq!(indent_guides("        - eight", 4, 4), [0, 4]);
+        assert_eq!(indent_guides("    - four", 2, 2), [0, 2]);
+        // A tab after part of a level finishes it, whatever it's drawn as
+        assert_eq!(indent_guides("\t\t- tabs", 2, 2), [0, 2]);
+        assert_eq!(indent_guides("\t\t- tabs", 2, 4), [0, 4]);
+        assert_eq!(indent_guides("  \t  - mixed", 4, 4), [0, 6]);
+    }
+
+    #[test]
+    fn test_definition_text() {
+        assert_eq!(definition_text(": a fruit"), Some("a fruit"));
+        assert_eq!(definition_text(":  spaced"), Some("spaced"));
+        assert_eq!(definition_text("Term: not one"), None);
+    }
+}
@@ -0,0 +1,173 @@
+//! Turning lines into another kind of list for `:listify` and `gl`: plain
+//! lines, `- bullets`, `1. numbered` items or `- [ ] tasks`. Indentation is
+//! kept as it is, so nested items stay nested.
+
+use super::list_item::{leading_columns, parse_list_marker};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListStyle {
+    Plain,
+    Bullet,
+    Ordered,
+    Task,
+}
+
+impl ListStyle {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Self::Plain),
+            "bullet" => Some(Self::Bullet),
+            "ordered" => Some(Self::Ordered),
+            "task" => Some(Self::Task),
+            _ => None,
+        }
+    }
+
+    /// The style `gl` turns this one into.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Plain => Self::Bullet,
+            Self::Bullet => Self::Ordered,
+            Self::Ordered => Self::Task,
+            Self::Task => Self::Plain,
+        }
+    }
+
+    pub fn of(line: &str) -> Self {
+        Item::parse(line).style
+    }
+}
+
+/// A line split into its indent, list marker and text.
+struct Item<'a> {
+    indent: &'a str,
+    style: ListStyle,
+    marker: &'a str,
+    text: &'a str,
+}
+
+impl<'a> Item<'a> {
+    fn parse(line: &'a str) -> Self {
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let Some(item) = parse_list_marker(line) else {
+            return Self { indent, style: ListStyle::Plain, marker: "", text: line.trim_start() };
+        };
+        let checked = ["[ ]", "[x]", "[X]"].into_iter().find_map(|checkbox| {
+            let rest = item.text.strip_prefix(checkbox)?;
+            (rest.is_empty() || rest.starts_with(' ')).then(|| rest.strip_prefix(' ').unwrap_or(rest))
+        });
+        let style = match checked {
+            Some(_) => ListStyle::Task,
+            None if item.marker.starts_with(|c: char| c.is_ascii_digit()) => ListStyle::Ordered,
+            None => ListStyle::Bullet,
+        };
+        Self { indent, style, marker: item.marker, text: checked.unwrap_or(item.text) }
+    }
+
+    fn bullet(&self) -> &str {
+        match self.marker {
+            "*" | "+" => self.marker,
+            _ => "-",
+        }
+    }
+}
+
+/// Chars before the text of `line`: its indent, marker and checkbox.
+pub fn prefix_len(line: &str) -> usize {
+    line.chars().count() - Item::parse(line).text.chars().count()
+}
+
+/// `lines` as `style` items. Numbered items count up from 1 in each run of
+/// lines without a blank one, separately at each indent. With `nested` off,
+/// only the least indented lines are changed and the ones nested under them
+/// are left alone.
+pub fn convert(lines: &[&str], style: ListStyle, nested: bool) -> Vec<String> {
+    let top = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| leading_columns(line))
+        .min()
+        .unwrap_or(0);
+    // Indent and last number of each level of the run so far
+    let mut numbers: Vec<(usize, usize)> = Vec::new();
+
+    lines
+        .iter()
+        .map(|&line| {
+            if line.trim().is_empty() {
+                numbers.clear();
+                return line.to_string();
+            }
+            let depth = leading_columns(line);
+            while numbers.last().is_some_and(|&(indent, _)| indent > depth) {
+                numbers.pop();
+            }
+            if !nested && depth > top {
+                return line.to_string();
+            }
+            let number = match numbers.last_mut() {
+                Some((indent, number)) if *indent == depth => {
+                    *number += 1;
+                    *number
+                }
+                _ => {
+                    numbers.push((depth, 1));
+                    1
+                }
+            };
+
+            let item = Item::parse(line);
+            match style {
+                ListStyle::Plain => format!("{}{}", item.indent, item.text),
+                ListStyle::Bullet => format!("{}{} {}", item.indent, item.bullet(), item.text),
+                ListStyle::Ordered => {
+                    let delimiter = if item.marker.ends_with(')') { ')' } else { '.' };
+                    format!("{}{}{} {}", item.indent, number, delimiter, item.text)
+                }
+                ListStyle::Task if item.style == ListStyle::Task => line.to_string(),
+                ListStyle::Task => format!("{}{} [ ] {}", item.indent, item.bullet(), item.text),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIST: [&str; 6] = ["Milk", "  * Oat", "  3) Soy", "", "- [x] Eggs", "Bread"];
+
+    #[test]
+    fn test_convert() {
+        assert_eq!(
+            convert(&LIST, ListStyle::Bullet, true),
+            ["- Milk", "  * Oat", "  - Soy", "", "- Eggs", "- Bread"]
+        );
+        assert_eq!(
+            convert(&LIST, ListStyle::Ordered, true),
+            ["1. Milk", "  1. Oat", "  2) Soy", "", "1. Eggs", "2. Bread"]
+        );
+        assert_eq!(
+            convert(&LIST, ListStyle::Task, true),
+            ["- [ ] Milk", "  * [ ] Oat", "  - [ ] Soy", "", "- [x] Eggs", "- [ ] Bread"]
+        );
+        assert_eq!(convert(&LIST, ListStyle::Plain, true), ["Milk", "  Oat", "  Soy", "", "Eggs", "Bread"]);
+
+        // Nested items left alone, still counted around
+        let list = ["- a", "  - b", "- c"];
+        assert_eq!(convert(&list, ListStyle::Ordered, false), ["1. a", "  - b", "2. c"]);
+    }
+
+    #[test]
+    fn test_style_and_prefix() {
+        assert_eq!(ListStyle::of("  12. x"), ListStyle::Ordered);
+        assert_eq!(ListStyle::of("- [X]"), ListStyle::Task);
+        assert_eq!(ListStyle::of("- [link](x)"), ListStyle::Bullet);
+        assert_eq!(ListStyle::of("-x"), ListStyle::Plain);
+        assert_eq!(ListStyle::Task.next(), ListStyle::Plain);
+
+        assert_eq!(prefix_len("  - [ ] buy"), 8);
+        assert_eq!(prefix_len("10. ten"), 4);
+        assert_eq!(prefix_len("  plain"), 2);
+    }
+}
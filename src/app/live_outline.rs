@@ -0,0 +1,194 @@
+//! The outline while a note is edited. Its entries point at buffer rows
+//! rather than content items, and most edits patch it instead of parsing the
+//! whole note again: a heading's title is updated where it is and the rows
+//! of the entries below are shifted. Edits that can change what counts as a
+//! heading elsewhere, like a code fence or a heading added or removed,
+//! rebuild it.
+
+use crate::config::DisplayConfig;
+
+use super::code_fence::is_fence;
+use super::frontmatter::Frontmatter;
+use super::state::{outline_items, ContentItem, OutlineItem, OutlineKind};
+
+/// The buffer lines the outline was last brought up to date with.
+#[derive(Debug, Default)]
+pub struct LiveOutline {
+    lines: Vec<String>,
+    body_start: usize,
+}
+
+impl LiveOutline {
+    /// The outline of `lines`, with the state to patch it from.
+    pub fn build(lines: &[&str], display: &DisplayConfig) -> (Self, Vec<OutlineItem>) {
+        let mut live = LiveOutline::default();
+        let outline = live.rebuild(lines, display);
+        (live, outline)
+    }
+
+    fn rebuild(&mut self, lines: &[&str], display: &DisplayConfig) -> Vec<OutlineItem> {
+        let (_, body_start) = Frontmatter::parse(&lines.join("\n"));
+        self.body_start = body_start;
+        self.lines = lines.iter().map(|line| line.to_string()).collect();
+        let mut items = vec![ContentItem::TextLine(String::new()); body_start.min(lines.len())];
+        items.extend(line_items(&lines[items.len()..], false));
+        outline_items(&items, display)
+    }
+
+    /// Bring `outline` from the last lines to `lines`. Returns false when it
+    /// had to be rebuilt rather than patched.
+    pub fn update(&mut self, outline: &mut Vec<OutlineItem>, lines: &[&str], display: &DisplayConfig) -> bool {
+        let old = &self.lines;
+        let prefix = old.iter().zip(lines).take_while(|(a, b)| a == *b).count();
+        let max_suffix = old.len().min(lines.len()) - prefix;
+        let suffix = old
+            .iter()
+            .rev()
+            .zip(lines.iter().rev())
+            .take(max_suffix)
+            .take_while(|(a, b)| a == *b)
+            .count();
+        let old_changed = &old[prefix..old.len() - suffix];
+        let new_changed = &lines[prefix..lines.len() - suffix];
+        if old_changed.is_empty() && new_changed.is_empty() {
+            return true;
+        }
+
+        let structural = |line: &str| is_fence(line) || line.trim_end() == "---";
+        if prefix < self.body_start
+            || old_changed.iter().any(|line| structural(line))
+            || new_changed.iter().any(|line| structural(line))
+        {
+            *outline = self.rebuild(lines, display);
+            return false;
+        }
+
+        let in_code = lines[self.body_start..prefix].iter().filter(|line| is_fence(line)).count() % 2 == 1;
+        // A code block's entry is titled with its first line
+        if in_code && display.outline_code_blocks && is_fence(lines[prefix - 1]) {
+            *outline = self.rebuild(lines, display);
+            return false;
+        }
+        let old_entries = outline_items(&line_items(old_changed, in_code), display);
+        let new_entries = outline_items(&line_items(new_changed, in_code), display);
+        let same_shape = old_entries.len() == new_entries.len()
+            && old_entries.iter().zip(&new_entries).all(|(a, b)| {
+                a.kind == b.kind && (a.kind != OutlineKind::Heading || a.level == b.level)
+            });
+        if !same_shape {
+            *outline = self.rebuild(lines, display);
+            return false;
+        }
+
+        let old_end = prefix + old_changed.len();
+        let mut new_entries = new_entries.into_iter();
+        for item in outline.iter_mut() {
+            if item.line >= old_end {
+                item.line = item.line + new_changed.len() - old_changed.len();
+            } else if item.line >= prefix {
+                if let Some(entry) = new_entries.next() {
+                    item.title = entry.title;
+                    item.line = prefix + entry.line;
+                }
+            }
+        }
+        self.lines.splice(prefix..old_end, new_changed.iter().map(|line| line.to_string()));
+        true
+    }
+}
+
+/// Content items for buffer lines, one per line, the way the content view
+/// reads fences.
+fn line_items<S: AsRef<str>>(lines: &[S], mut in_code: bool) -> Vec<ContentItem> {
+    lines
+        .iter()
+        .map(|line| {
+            let line = line.as_ref();
+            if is_fence(line) {
+                in_code = !in_code;
+                ContentItem::CodeFence(line.trim_start_matches('`').to_string())
+            } else if in_code {
+                ContentItem::CodeLine(line.to_string())
+            } else {
+                ContentItem::TextLine(line.to_string())
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "# Plan\nIntro text\n## Goals\nmore text\n## Risks\n";
+
+    fn summary(outline: &[OutlineItem]) -> Vec<(usize, &str, usize)> {
+        outline.iter().map(|item| (item.level, item.title.as_str(), item.line)).collect()
+    }
+
+    fn edited(edit: impl FnOnce(&mut Vec<&'static str>)) -> Vec<&'static str> {
+        let mut lines: Vec<&str> = NOTE.lines().collect();
+        edit(&mut lines);
+        lines
+    }
+
+    #[test]
+    fn test_edit_text_leaves_outline_alone() {
+        let display = DisplayConfig::default();
+        let (mut live, mut outline) = LiveOutline::build(&NOTE.lines().collect::<Vec<_>>(), &display);
+        let before = outline.clone();
+
+        assert!(live.update(&mut outline, &edited(|lines| lines[1] = "Intro text, longer"), &display));
+        assert_eq!(summary(&outline), summary(&before));
+    }
+
+    #[test]
+    fn test_edit_heading_title_in_place() {
+        let display = DisplayConfig::default();
+        let (mut live, mut outline) = LiveOutline::build(&NOTE.lines().collect::<Vec<_>>(), &display);
+
+        assert!(live.update(&mut outline, &edited(|lines| lines[2] = "## Goals for Q3"), &display));
+        assert_eq!(summary(&outline), [(1, "Plan", 0), (2, "Goals for Q3", 2), (2, "Risks", 4)]);
+
+        // Changing the level is a rebuild, and still comes out right
+        assert!(!live.update(&mut outline, &edited(|lines| lines[2] = "### Goals for Q3"), &display));
+        assert_eq!(summary(&outline), [(1, "Plan", 0), (3, "Goals for Q3", 2), (2, "Risks", 4)]);
+    }
+
+    #[test]
+    fn test_inserted_lines_shift_later_entries() {
+        let display = DisplayConfig::default();
+        let (mut live, mut outline) = LiveOutline::build(&NOTE.lines().collect::<Vec<_>>(), &display);
+
+        let inserted = edited(|lines| {
+            for line in ["another", "", "new line"] {
+                lines.insert(2, line);
+            }
+        });
+        assert!(live.update(&mut outline, &inserted, &display));
+        assert_eq!(summary(&outline), [(1, "Plan", 0), (2, "Goals", 5), (2, "Risks", 7)]);
+
+        let deleted = edited(|lines| {
+            lines.remove(3);
+        });
+        assert!(live.update(&mut outline, &deleted, &display));
+        assert_eq!(summary(&outline), [(1, "Plan", 0), (2, "Goals", 2), (2, "Risks", 3)]);
+    }
+
+    #[test]
+    fn test_code_fence_rebuilds() {
+        let display = DisplayConfig::default();
+        let (mut live, mut outline) = LiveOutline::build(&NOTE.lines().collect::<Vec<_>>(), &display);
+
+        // Opening a fence turns the headings below it into code
+        let fenced = edited(|lines| lines[1] = "```");
+        assert!(!live.update(&mut outline, &fenced, &display));
+        assert_eq!(summary(&outline), [(1, "Plan", 0)]);
+
+        // A heading typed inside the block isn't one
+        let mut in_code = fenced.clone();
+        in_code[3] = "# not a heading";
+        assert!(live.update(&mut outline, &in_code, &display));
+        assert_eq!(summary(&outline), [(1, "Plan", 0)]);
+    }
+}
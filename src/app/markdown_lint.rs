@@ -0,0 +1,195 @@
+//! Markdown mistakes that render oddly rather than failing, flagged in the
+//! editor: an unclosed code fence, `[[` without `]]`, unbalanced `**` and
+//! table rows with the wrong number of cells.
+
+use crate::config::LintConfig;
+
+use super::code_fence;
+use super::frontmatter::Frontmatter;
+
+/// How much a diagnostic matters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    /// A markdown mistake
+    Warning,
+    /// A writing-style suggestion from the prose lint
+    Hint,
+}
+
+/// A flagged range of one buffer line, in chars.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub row: usize,
+    pub start_col: usize,
+    pub end_col: usize,
+    pub message: String,
+    pub severity: Severity,
+}
+
+impl Diagnostic {
+    fn new(row: usize, start_col: usize, end_col: usize, message: impl Into<String>) -> Self {
+        Self { row, start_col, end_col, message: message.into(), severity: Severity::Warning }
+    }
+
+    /// Whether the cursor at `row`, `col` is on this range. An empty range
+    /// counts its first column.
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        self.row == row && col >= self.start_col && col < self.end_col.max(self.start_col + 1)
+    }
+}
+
+/// Diagnostics for the buffer `lines` under the rules enabled in `config`,
+/// in row order. Frontmatter and fenced code are skipped.
+pub fn lint(lines: &[&str], config: &LintConfig) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let (_, body_start) = Frontmatter::parse(&lines.join("\n"));
+    let body = &lines[body_start.min(lines.len())..];
+
+    let blocks = code_fence::code_blocks(body);
+    let in_code = |row: usize| {
+        blocks
+            .iter()
+            .any(|&(open, close)| row >= open && close.is_none_or(|close| row <= close))
+    };
+    if config.unclosed_fence {
+        if let Some(&(open, None)) = blocks.last() {
+            let len = body[open].chars().count();
+            diagnostics.push(Diagnostic::new(body_start + open, 0, len, "Code fence is never closed"));
+        }
+    }
+
+    let mut table_header: Option<usize> = None;
+    for (i, line) in body.iter().enumerate() {
+        let row = body_start + i;
+        if in_code(i) {
+            table_header = None;
+            continue;
+        }
+        if config.unclosed_wiki_link {
+            diagnostics.extend(unclosed_wiki_link(row, line));
+        }
+        if config.unbalanced_bold {
+            diagnostics.extend(unbalanced_bold(row, line));
+        }
+
+        let trimmed = line.trim();
+        if trimmed.len() >= 2 && trimmed.starts_with('|') && trimmed.ends_with('|') {
+            let cells = trimmed[1..trimmed.len() - 1].split('|').count();
+            match table_header {
+                Some(expected) if config.table_cells && cells != expected => {
+                    diagnostics.push(Diagnostic::new(
+                        row,
+                        0,
+                        line.chars().count(),
+                        format!("Table row has {} cells, the header has {}", cells, expected),
+                    ));
+                }
+                Some(_) => {}
+                None => table_header = Some(cells),
+            }
+        } else {
+            table_header = None;
+        }
+    }
+    diagnostics.sort_by_key(|d| (d.row, d.start_col));
+    diagnostics
+}
+
+/// Char columns of `line` outside inline code spans.
+fn outside_inline_code(line: &str) -> Vec<bool> {
+    let mut in_code = false;
+    line.chars()
+        .map(|c| {
+            if c == '`' {
+                in_code = !in_code;
+                false
+            } else {
+                !in_code
+            }
+        })
+        .collect()
+}
+
+/// `[[` with no `]]` after it on the line, from the last such `[[` to the end.
+fn unclosed_wiki_link(row: usize, line: &str) -> Option<Diagnostic> {
+    let chars: Vec<char> = line.chars().collect();
+    let outside = outside_inline_code(line);
+    let mut open: Option<usize> = None;
+    let mut col = 0;
+    while col + 1 < chars.len() {
+        match (chars[col], chars[col + 1]) {
+            ('[', '[') if outside[col] && open.is_none() => {
+                open = Some(col);
+                col += 2;
+            }
+            (']', ']') if outside[col] && open.is_some() => {
+                open = None;
+                col += 2;
+            }
+            _ => col += 1,
+        }
+    }
+    open.map(|start| Diagnostic::new(row, start, chars.len(), "Wiki link is missing its closing ]]"))
+}
+
+/// The last `**` on a line that has an odd number of them. Horizontal rules
+/// like `***` aren't emphasis.
+fn unbalanced_bold(row: usize, line: &str) -> Option<Diagnostic> {
+    let trimmed = line.trim();
+    if trimmed.chars().all(|c| c == '*' || c == ' ') {
+        return None;
+    }
+    let chars: Vec<char> = line.chars().collect();
+    let outside = outside_inline_code(line);
+    let mut markers = Vec::new();
+    let mut col = 0;
+    while col + 1 < chars.len() {
+        if chars[col] == '*' && chars[col + 1] == '*' && outside[col] {
+            markers.push(col);
+            col += 2;
+        } else {
+            col += 1;
+        }
+    }
+    (markers.len() % 2 == 1)
+        .then(|| markers.last().map(|&start| Diagnostic::new(row, start, start + 2, "Unbalanced ** bold marker")))
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(text: &str, config: &LintConfig) -> Vec<(usize, usize, usize, String)> {
+        let lines: Vec<&str> = text.lines().collect();
+        lint(&lines, config)
+            .into_iter()
+            .map(|d| (d.row, d.start_col, d.end_col, d.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_rules() {
+        let note = "---\ntitle: [[not a link\n---\n\
+            See [[Other Note]] and [[Broken here.\n\
+            Some **bold** and **half bold.\n\
+            ***\n\
+            `[[code` and `**` are fine\n\
+            | a | b |\n|---|---|\n| 1 | 2 | 3 |\n\n\
+            ```\n[[inside code\n```\n\
+            ```rust\nfn main() {}\n";
+        let found = messages(note, &LintConfig::default());
+        assert_eq!(
+            found,
+            [
+                (3, 23, 37, "Wiki link is missing its closing ]]".to_string()),
+                (4, 18, 20, "Unbalanced ** bold marker".to_string()),
+                (9, 0, 13, "Table row has 3 cells, the header has 2".to_string()),
+                (14, 0, 7, "Code fence is never closed".to_string()),
+            ]
+        );
+
+        let config = LintConfig { unclosed_fence: false, table_cells: false, ..LintConfig::default() };
+        assert_eq!(messages(note, &config).len(), 2);
+    }
+}
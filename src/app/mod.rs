@@ -1,5 +1,77 @@
+mod background_io;
+mod bulk_rename;
+mod callout;
+mod capabilities;
+mod clock;
+mod code_fence;
+mod content_motion;
+mod delete_confirm;
+mod encryption;
+mod export;
+mod external_change;
+mod folder_config;
+mod folder_view;
 mod frontmatter;
+mod global_substitute;
+mod heading_refactor;
+mod image_info;
+mod image_size;
+mod large_paste;
+mod ipc;
+mod leader;
+mod line_diff;
+mod line_ending;
+mod link_copy;
+mod link_graph;
+mod link_titles;
+mod link_targets;
+mod list_item;
+mod list_style;
+mod markdown_lint;
+mod live_outline;
+mod note_merge;
+mod note_stats;
+mod opener;
+mod path_input;
+mod paths;
+mod pins;
+mod prose_lint;
+mod quickfix;
+mod reading_positions;
+mod reference_links;
+mod refile;
+mod render_cache;
+mod scaffold;
+mod search_history;
+mod section_tasks;
+mod sidebar_marks;
+mod snapshots;
+mod smooth_scroll;
+mod snippets;
 mod state;
+#[cfg(test)]
+pub mod test_support;
+mod toc;
 mod welcome_notes;
+mod wiki_link;
 
+pub use bulk_rename::RenameProblem;
+pub use callout::{marker_start as callout_marker_start, CalloutKind};
+pub use capabilities::Capabilities;
+pub use content_motion::ScreenLine;
+pub use encryption::NoteLock;
+pub use global_substitute::note_names;
+pub use image_info::{format_size, ImageLoad};
+pub use image_size::{fit_image, ImageSize};
+pub use ipc::send as ipc_send;
+pub use leader::LeaderAction;
+pub use link_graph::{export_graph, read_vault, GraphFormat};
+pub use list_item::{indent_guides, parse_list_marker};
+pub use list_style::ListStyle;
+pub use paths::canonicalize as canonicalize_path;
+pub use reference_links::{parse_definition, reference_link_at};
+pub use render_cache::fingerprint as render_fingerprint;
+pub use section_tasks::ARCHIVE_HEADING;
+pub use smooth_scroll::FRAME_INTERVAL as SMOOTH_SCROLL_FRAME;
 pub use state::*;
+pub use wiki_link::{split_unescaped as split_wiki_unescaped, WikiLink};
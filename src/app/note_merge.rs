@@ -0,0 +1,175 @@
+//! `:merge`: fold a duplicate note into the current one. The other note's
+//! text is added at the end, the links to it across the vault are pointed
+//! at the current note, and its file goes to `.trash` in the notes folder.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::MergeStyle;
+
+use super::encryption::is_encrypted;
+use super::frontmatter::Frontmatter;
+use super::global_substitute::VaultSubstitution;
+use super::wiki_link::{escape, split_unescaped, WikiLink};
+
+/// Where merged-away notes go, under the notes folder. Hidden, so the
+/// sidebar and the link graph leave it out.
+pub const TRASH_DIR: &str = ".trash";
+
+/// A `:merge` waiting for confirmation, with the notes whose links would change.
+#[derive(Debug, Clone)]
+pub struct NoteMerge {
+    /// The note merged away
+    pub source: usize,
+    /// The note it's merged into
+    pub target: usize,
+    /// How a wiki link names the target note
+    pub link_target: String,
+    pub preview: VaultSubstitution,
+}
+
+/// `current` with `other`'s text after it, without `other`'s frontmatter.
+/// With [`MergeStyle::Heading`] it goes under a `## title` heading.
+pub fn merged_text(current: &str, title: &str, other: &str, style: MergeStyle) -> String {
+    let (_, body_start) = Frontmatter::parse(other);
+    let body: Vec<&str> = other.lines().skip(body_start).collect();
+    let body = body.join("\n");
+    let body = body.trim_matches('\n');
+
+    let mut merged = current.trim_end().to_string();
+    if !merged.is_empty() {
+        merged.push_str("\n\n");
+    }
+    if style == MergeStyle::Heading {
+        merged.push_str(&format!("## {}\n\n", title));
+    }
+    merged.push_str(body);
+    merged.push('\n');
+    merged
+}
+
+/// `text` with the wiki links `links_here` says are to the merged-away note
+/// pointed at `new_target`, and how many were changed. Headings and aliases
+/// are kept; fenced code is left alone.
+pub fn relink(text: &str, new_target: &str, links_here: impl Fn(&str) -> bool) -> (String, usize) {
+    let mut out = String::with_capacity(text.len());
+    let mut count = 0;
+    let mut in_code = false;
+    for line in text.split_inclusive('\n') {
+        let fence = line.trim_start().starts_with("```");
+        if fence {
+            in_code = !in_code;
+        }
+        if in_code || fence {
+            out.push_str(line);
+            continue;
+        }
+        let mut rest = line;
+        while let Some(start) = rest.find("[[") {
+            out.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let Some(link) = WikiLink::parse(rest) else {
+                out.push_str("[[");
+                rest = &rest[2..];
+                continue;
+            };
+            let inner = &rest[2..link.len - 2];
+            let destination = split_unescaped(inner, '|').map_or(inner, |(destination, _)| destination);
+            let target_len = split_unescaped(destination, '#').map_or(destination.len(), |(target, _)| target.len());
+            if target_len > 0 && links_here(link.target.trim()) {
+                out.push_str(&format!("[[{}{}]]", escape(new_target), &inner[target_len..]));
+                count += 1;
+            } else {
+                out.push_str(&rest[..link.len]);
+            }
+            rest = &rest[link.len..];
+        }
+        out.push_str(rest);
+    }
+    (out, count)
+}
+
+/// Relink each of the notes at `paths`, see [`relink`]. Files are only
+/// rewritten when `write` is set; otherwise the links are just counted.
+pub fn relink_notes(
+    paths: &[PathBuf],
+    new_target: &str,
+    links_here: impl Fn(&str) -> bool,
+    write: bool,
+) -> VaultSubstitution {
+    let mut result = VaultSubstitution::default();
+    for path in paths {
+        let text = match fs::read_to_string(path) {
+            Ok(text) if !is_encrypted(&text) => text,
+            _ => {
+                result.skipped.push(path.clone());
+                continue;
+            }
+        };
+        let (new_text, count) = relink(&text, new_target, &links_here);
+        if count == 0 {
+            continue;
+        }
+        if write && fs::write(path, new_text).is_err() {
+            result.skipped.push(path.clone());
+            continue;
+        }
+        result.files.push((path.clone(), count));
+    }
+    result
+}
+
+/// Move `path` into [`TRASH_DIR`] under `notes_root`, keeping the folders
+/// it was in. A note already there with the name gets a number after it.
+pub fn move_to_trash(notes_root: &Path, path: &Path) -> io::Result<PathBuf> {
+    let relative = path.strip_prefix(notes_root).unwrap_or(path);
+    let relative = relative.strip_prefix("/").unwrap_or(relative);
+    let mut trashed = notes_root.join(TRASH_DIR).join(relative);
+    if let Some(parent) = trashed.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let stem = trashed.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let mut n = 2;
+    while trashed.exists() {
+        trashed.set_file_name(format!("{} {}.md", stem, n));
+        n += 1;
+    }
+    fs::rename(path, &trashed)?;
+    Ok(trashed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_support::TempDir;
+
+    #[test]
+    fn test_merged_text() {
+        let other = "---\ntags: [a]\n---\n\nOld body\n";
+        assert_eq!(merged_text("# New\nText\n\n", "Old", other, MergeStyle::Heading), "# New\nText\n\n## Old\n\nOld body\n");
+        assert_eq!(merged_text("# New\n", "Old", "Old body", MergeStyle::Verbatim), "# New\n\nOld body\n");
+        assert_eq!(merged_text("", "Old", "Old body", MergeStyle::Verbatim), "Old body\n");
+    }
+
+    #[test]
+    fn test_relink() {
+        let text = "[[Old]] [[old#Part|part]] [[Other]] [[#Local]]\n```\n[[Old]]\n```\n";
+        let (relinked, count) = relink(text, "New", |target| target.eq_ignore_ascii_case("old"));
+        assert_eq!(relinked, "[[New]] [[New#Part|part]] [[Other]] [[#Local]]\n```\n[[Old]]\n```\n");
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_move_to_trash() {
+        let dir = TempDir::new("merge");
+        fs::create_dir_all(dir.join("work")).unwrap();
+        for _ in 0..2 {
+            fs::write(dir.join("work/Old.md"), "old").unwrap();
+            move_to_trash(&dir, &dir.join("work/Old.md")).unwrap();
+        }
+        assert!(!dir.join("work/Old.md").exists());
+        assert!(dir.join(".trash/work/Old.md").exists());
+        assert!(dir.join(".trash/work/Old 2.md").exists());
+    }
+}
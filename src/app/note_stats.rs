@@ -0,0 +1,170 @@
+//! `:stats`: counts summarizing a note's text and structure.
+
+use super::frontmatter::Frontmatter;
+use super::wiki_link::WikiLink;
+
+/// Reading speed the estimated reading time assumes.
+const WORDS_PER_MINUTE: usize = 200;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NoteAnalysis {
+    pub words: usize,
+    /// Characters, not counting line breaks
+    pub chars: usize,
+    pub lines: usize,
+    pub paragraphs: usize,
+    /// Headings of each level, `#` through `######`
+    pub headings: [usize; 6],
+    pub links: usize,
+    pub wiki_links: usize,
+    pub images: usize,
+    pub tasks_done: usize,
+    pub tasks_pending: usize,
+}
+
+impl NoteAnalysis {
+    /// Minutes to read the note, rounded up.
+    pub fn reading_minutes(&self) -> usize {
+        self.words.div_ceil(WORDS_PER_MINUTE)
+    }
+}
+
+/// Counts for `content`, leaving out its frontmatter. Headings, links and
+/// tasks inside fenced code blocks or inline code aren't counted.
+pub fn analyze_note(content: &str) -> NoteAnalysis {
+    let (_, body_start) = Frontmatter::parse(content);
+    let mut analysis = NoteAnalysis::default();
+    let mut in_code_block = false;
+    let mut in_paragraph = false;
+
+    for line in content.lines().skip(body_start) {
+        analysis.lines += 1;
+        analysis.chars += line.chars().count();
+        analysis.words += line
+            .split_whitespace()
+            .filter(|word| word.chars().any(|c| c.is_alphanumeric()))
+            .count();
+
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+            in_paragraph = false;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        if let Some(level) = heading_level(trimmed) {
+            analysis.headings[level - 1] += 1;
+            in_paragraph = false;
+            continue;
+        }
+        if trimmed.is_empty() {
+            in_paragraph = false;
+            continue;
+        }
+        if !in_paragraph {
+            analysis.paragraphs += 1;
+            in_paragraph = true;
+        }
+
+        if trimmed.starts_with("- [x] ") || trimmed.starts_with("- [X] ") {
+            analysis.tasks_done += 1;
+        } else if trimmed.starts_with("- [ ] ") {
+            analysis.tasks_pending += 1;
+        }
+        count_links(line, &mut analysis);
+    }
+    analysis
+}
+
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    ((1..=6).contains(&level) && line[level..].starts_with(' ')).then_some(level)
+}
+
+/// `[text](url)`, `![alt](src)` and `[[note]]` on `line`, outside inline code.
+fn count_links(line: &str, analysis: &mut NoteAnalysis) {
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'`' => match line[i + 1..].find('`') {
+                Some(close) => i += close + 2,
+                None => return,
+            },
+            b'[' if bytes.get(i + 1) == Some(&b'[') => match WikiLink::parse(&line[i..]) {
+                Some(link) => {
+                    analysis.wiki_links += 1;
+                    i += link.len;
+                }
+                None => i += 2,
+            },
+            b'!' if bytes.get(i + 1) == Some(&b'[') => match link_end(line, i + 1) {
+                Some(end) => {
+                    analysis.images += 1;
+                    i = end;
+                }
+                None => i += 2,
+            },
+            b'[' => match link_end(line, i) {
+                Some(end) => {
+                    analysis.links += 1;
+                    i = end;
+                }
+                None => i += 1,
+            },
+            _ => i += 1,
+        }
+    }
+}
+
+/// For `[text](target)` starting at `start`, the byte just past the `)`.
+fn link_end(line: &str, start: usize) -> Option<usize> {
+    let text_end = start + line[start..].find("](")?;
+    if line[start + 1..text_end].contains('[') {
+        return None;
+    }
+    let target_end = text_end + 2 + line[text_end + 2..].find(')')?;
+    Some(target_end + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_sample_note() {
+        let note = "---\ntags: [research]\n---\n# Paper notes\n\n\
+            Intro with a [source](https://example.com) and [[Other Note]].\n\
+            Second line of the same paragraph.\n\n\
+            ## Methods\n\n\
+            - [x] read the abstract\n\
+            - [ ] check the data\n\
+            - [ ] email the [author](mailto:a@b.c)\n\n\
+            ![figure](fig1.png)\n\n\
+            ## Results\n\n\
+            ```md\n# not a heading [x](y)\n```\n\
+            Use `[not](a link)` here.\n";
+        let analysis = analyze_note(note);
+
+        assert_eq!(analysis.headings, [1, 2, 0, 0, 0, 0]);
+        assert_eq!((analysis.tasks_done, analysis.tasks_pending), (1, 2));
+        assert_eq!((analysis.links, analysis.wiki_links, analysis.images), (2, 1, 1));
+        // Intro, the task list, the figure and the last line
+        assert_eq!(analysis.paragraphs, 4);
+        assert_eq!(analysis.lines, 19);
+        assert_eq!(analysis.reading_minutes(), 1);
+    }
+
+    #[test]
+    fn test_word_and_char_counts() {
+        let analysis = analyze_note("Hello, wörld — again!\n\n- [ ] x");
+        // The dash and the task's box aren't words
+        assert_eq!(analysis.words, 4);
+        assert_eq!(analysis.chars, 28);
+        assert_eq!(analyze_note("").reading_minutes(), 0);
+        assert_eq!(analyze_note(&"word ".repeat(401)).reading_minutes(), 3);
+    }
+}
@@ -0,0 +1,176 @@
+//! Handing link and image targets to the system opener, and notes to the
+//! file manager. Targets come from note content, so with
+//! `security.confirm_open` on they wait behind a dialog showing exactly what
+//! would be opened.
+
+use std::io;
+use std::path::Path;
+#[cfg(not(test))]
+use std::process::{Command, Stdio};
+
+/// A target held until the open confirmation dialog is answered.
+#[derive(Debug, Default)]
+pub struct PendingOpen {
+    target: Option<String>,
+}
+
+impl PendingOpen {
+    /// Open `target` now, or hold it when `confirm` is set. Returns whether
+    /// it was held, i.e. whether the dialog should be shown.
+    pub fn request(&mut self, target: String, confirm: bool) -> bool {
+        if confirm {
+            self.target = Some(target);
+            true
+        } else {
+            spawn_opener(&target);
+            false
+        }
+    }
+
+    /// The held target, for the dialog.
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    /// Answer the dialog: the held target is opened if `confirmed` and
+    /// dropped either way.
+    pub fn answer(&mut self, confirmed: bool) {
+        if let Some(target) = self.target.take().filter(|_| confirmed) {
+            spawn_opener(&target);
+        }
+    }
+}
+
+/// Open `target` with the system's default handler: `open` on macOS, the
+/// shell on Windows and `xdg-open` on Linux and the BSDs.
+#[cfg(not(test))]
+fn spawn_opener(target: &str) {
+    #[cfg(target_os = "macos")]
+    let _ = spawn(Command::new("open").arg(target));
+    #[cfg(not(any(target_os = "macos", windows)))]
+    let _ = spawn(Command::new("xdg-open").arg(target));
+    #[cfg(windows)]
+    shell_execute(target);
+}
+
+/// Show `path` in the system file manager, selected where it can be.
+#[cfg(not(test))]
+pub fn reveal(path: &Path) -> io::Result<()> {
+    spawn(&mut reveal_command(path))
+}
+
+#[cfg(all(not(test), target_os = "macos"))]
+fn reveal_command(path: &Path) -> Command {
+    let mut command = Command::new("open");
+    command.arg("-R").arg(path);
+    command
+}
+
+/// Explorer parses its own command line and wants the path after
+/// `/select,` quoted, which the usual argument quoting doesn't do.
+#[cfg(all(not(test), windows))]
+fn reveal_command(path: &Path) -> Command {
+    use std::os::windows::process::CommandExt;
+    let mut command = Command::new("explorer");
+    command.raw_arg(format!("/select,\"{}\"", path.display()));
+    command
+}
+
+/// No file manager selects a file through `xdg-open`, so the folder it's
+/// in is opened.
+#[cfg(all(not(test), not(any(target_os = "macos", windows))))]
+fn reveal_command(path: &Path) -> Command {
+    let mut command = Command::new("xdg-open");
+    command.arg(path.parent().unwrap_or(path));
+    command
+}
+
+/// Start `command` without waiting for it or letting it write over the UI.
+#[cfg(not(test))]
+fn spawn(command: &mut Command) -> io::Result<()> {
+    command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).spawn().map(drop)
+}
+
+/// Windows: hand `target` to the shell as Explorer would, so it never goes
+/// through `cmd`, which reads a URL's `&` as the end of the command.
+#[cfg(all(not(test), windows))]
+fn shell_execute(target: &str) {
+    use std::ffi::{c_void, OsStr};
+    use std::os::windows::ffi::OsStrExt;
+
+    #[link(name = "shell32")]
+    extern "system" {
+        fn ShellExecuteW(
+            hwnd: *mut c_void,
+            operation: *const u16,
+            file: *const u16,
+            parameters: *const u16,
+            directory: *const u16,
+            show: i32,
+        ) -> *mut c_void;
+    }
+    const SW_SHOWNORMAL: i32 = 1;
+
+    let wide = |s: &str| OsStr::new(s).encode_wide().chain(Some(0)).collect::<Vec<u16>>();
+    let (operation, file) = (wide("open"), wide(target));
+    // SAFETY: both strings are NUL-terminated and outlive the call, and the
+    // null pointers are the documented defaults
+    unsafe {
+        ShellExecuteW(
+            std::ptr::null_mut(),
+            operation.as_ptr(),
+            file.as_ptr(),
+            std::ptr::null(),
+            std::ptr::null(),
+            SW_SHOWNORMAL,
+        );
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    /// What tests would have opened, in place of spawning the opener
+    static OPENED: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+#[cfg(test)]
+fn spawn_opener(target: &str) {
+    OPENED.with_borrow_mut(|opened| opened.push(target.to_string()));
+}
+
+#[cfg(test)]
+pub fn reveal(path: &Path) -> io::Result<()> {
+    OPENED.with_borrow_mut(|opened| opened.push(format!("reveal {}", path.display())));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opened() -> Vec<String> {
+        OPENED.with_borrow_mut(std::mem::take)
+    }
+
+    #[test]
+    fn test_open_waits_for_confirmation() {
+        let mut pending = PendingOpen::default();
+        assert!(!pending.request("https://example.com".to_string(), false));
+        assert_eq!(opened(), ["https://example.com"]);
+
+        assert!(pending.request("file:///etc/passwd".to_string(), true));
+        assert_eq!(pending.target(), Some("file:///etc/passwd"));
+        assert!(opened().is_empty());
+        pending.answer(false);
+        assert_eq!(pending.target(), None);
+        assert!(opened().is_empty());
+
+        assert!(pending.request("vscode://open?x".to_string(), true));
+        pending.answer(true);
+        assert_eq!(opened(), ["vscode://open?x"]);
+        assert_eq!(pending.target(), None);
+
+        reveal(Path::new("/notes/a.md")).unwrap();
+        assert_eq!(opened(), ["reveal /notes/a.md"]);
+    }
+}
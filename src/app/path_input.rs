@@ -0,0 +1,73 @@
+//! Folder paths typed into `:cd` and the notes folder dialogs: what they
+//! point at, and Tab completing them. `~` is the home folder and relative
+//! paths are under the folder given, the notes folder for `:cd`.
+
+use std::fs;
+use std::path::{is_separator, Path, PathBuf};
+
+use super::paths;
+
+/// The folder `input` names, made absolute. Existing folders are
+/// canonicalized, so `..` and links are resolved.
+pub fn resolve(input: &str, base: &Path) -> PathBuf {
+    let expanded = PathBuf::from(shellexpand::tilde(input.trim()).as_ref());
+    let path = if expanded.is_absolute() { expanded } else { base.join(expanded) };
+    paths::canonicalize(&path).unwrap_or(path)
+}
+
+/// `input` completed as far as the folders it could mean agree, with a `/`
+/// once it names just one. Hidden folders are only offered for a name
+/// starting with `.`. `None` when no folder starts that way.
+pub fn complete(input: &str, base: &Path) -> Option<String> {
+    if input == "~" {
+        return Some("~/".to_string());
+    }
+    let split = input.rfind(is_separator).map_or(0, |i| i + 1);
+    let (dir, prefix) = input.split_at(split);
+    let parent = if dir.is_empty() { base.to_path_buf() } else { resolve(dir, base) };
+
+    let mut names: Vec<String> = fs::read_dir(parent)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with(prefix) && (prefix.starts_with('.') || !name.starts_with('.')))
+        .collect();
+    names.sort();
+    let first = names.first()?;
+    let shared = names.iter().fold(first.len(), |len, name| {
+        first[..len].char_indices().zip(name.chars()).find(|((_, a), b)| a != b).map_or(len.min(name.len()), |((i, _), _)| i)
+    });
+
+    let mut completed = format!("{}{}", dir, &first[..shared]);
+    if names.len() == 1 {
+        completed.push('/');
+    }
+    Some(completed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_support::TempDir;
+
+    #[test]
+    fn test_complete() {
+        let dir = TempDir::new("path-input");
+        for folder in ["notes", "notebook/inner", "news", ".hidden"] {
+            fs::create_dir_all(dir.join(folder)).unwrap();
+        }
+        fs::write(dir.join("notes.md"), "").unwrap();
+
+        assert_eq!(complete("no", &dir).as_deref(), Some("note"));
+        assert_eq!(complete("notes", &dir).as_deref(), Some("notes/"));
+        assert_eq!(complete("notebook/i", &dir).as_deref(), Some("notebook/inner/"));
+        assert_eq!(complete("n", &dir).as_deref(), Some("n"));
+        assert_eq!(complete(".h", &dir).as_deref(), Some(".hidden/"));
+        assert_eq!(complete("x", &dir), None);
+
+        let absolute = format!("{}/new", dir.display());
+        assert_eq!(complete(&absolute, Path::new("/")), Some(format!("{}/news/", dir.display())));
+        assert_eq!(resolve("notebook/inner/..", &dir), resolve("notebook", &dir));
+    }
+}
@@ -0,0 +1,143 @@
+//! Paths as each platform has them. On Windows `fs::canonicalize` gives
+//! verbatim `\\?\C:\...` paths that look wrong anywhere they're shown, and
+//! both `/` and `\` separate components. Windows and macOS filesystems
+//! ignore case by default, so two spellings can name the same file.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Whether this platform's filesystems ignore case by default.
+pub const CASE_INSENSITIVE: bool = cfg!(any(windows, target_os = "macos"));
+
+/// `fs::canonicalize`, without the verbatim prefix on Windows where the
+/// path reads the same without it.
+pub fn canonicalize(path: &Path) -> io::Result<PathBuf> {
+    fs::canonicalize(path).map(simplify)
+}
+
+#[cfg(windows)]
+fn simplify(path: PathBuf) -> PathBuf {
+    match path.to_str().and_then(strip_verbatim) {
+        Some(plain) => PathBuf::from(plain),
+        None => path,
+    }
+}
+
+#[cfg(not(windows))]
+fn simplify(path: PathBuf) -> PathBuf {
+    path
+}
+
+/// `\\?\C:\dir` as `C:\dir` and `\\?\UNC\server\share` as
+/// `\\server\share`. `None` when the path has no verbatim prefix or needs
+/// it: too long for the usual limit, or with a component Windows would
+/// otherwise read differently, like `nul` or `name.`.
+#[cfg(windows)]
+fn strip_verbatim(path: &str) -> Option<String> {
+    const MAX_PATH: usize = 260;
+    const RESERVED: &[&str] = &["con", "prn", "aux", "nul", "conin$", "conout$"];
+
+    let (plain, components) = if let Some(rest) = path.strip_prefix(r"\\?\UNC\") {
+        (format!(r"\\{}", rest), rest)
+    } else {
+        let rest = path.strip_prefix(r"\\?\")?;
+        let drive = rest.as_bytes();
+        if drive.len() < 3 || !drive[0].is_ascii_alphabetic() || &drive[1..3] != br":\" {
+            return None;
+        }
+        (rest.to_string(), &rest[3..])
+    };
+    let needs_prefix = |name: &str| {
+        let stem = name.split('.').next().unwrap_or(name).trim_end().to_ascii_lowercase();
+        let numbered = stem.len() == 4
+            && (stem.starts_with("com") || stem.starts_with("lpt"))
+            && stem.as_bytes()[3].is_ascii_digit();
+        name.is_empty()
+            || name == "."
+            || name == ".."
+            || name.ends_with(['.', ' '])
+            || name.contains('/')
+            || RESERVED.contains(&stem.as_str())
+            || numbered
+    };
+    let components = components.strip_suffix('\\').unwrap_or(components);
+    if plain.len() >= MAX_PATH || (!components.is_empty() && components.split('\\').any(needs_prefix)) {
+        return None;
+    }
+    Some(plain)
+}
+
+/// Whether `a` and `b` name the same file as far as their text tells, with
+/// case ignored where the filesystem ignores it. Components are compared,
+/// so separators, `.` and trailing slashes don't matter.
+pub fn same_path(a: &Path, b: &Path) -> bool {
+    paths_equal(a, b, CASE_INSENSITIVE)
+}
+
+fn paths_equal(a: &Path, b: &Path, ignore_case: bool) -> bool {
+    let (mut a, mut b) = (a.components(), b.components());
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return true,
+            (Some(x), Some(y)) => {
+                let (x, y) = (x.as_os_str().to_string_lossy(), y.as_os_str().to_string_lossy());
+                if x != y && !(ignore_case && x.to_lowercase() == y.to_lowercase()) {
+                    return false;
+                }
+            }
+            _ => return false,
+        }
+    }
+}
+
+/// `path` with `/` between its components whatever the platform, as a wiki
+/// link target writes it.
+pub fn slash_path(path: &Path) -> String {
+    path.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_equal() {
+        let (upper, lower) = (Path::new("/Notes/Work/Plan.md"), Path::new("/notes/work/plan.md"));
+        assert!(paths_equal(upper, lower, true));
+        assert!(!paths_equal(upper, lower, false));
+        assert!(paths_equal(Path::new("/notes/./a.md"), Path::new("/notes/a.md"), false));
+        assert!(paths_equal(Path::new("/notes/dir/"), Path::new("/notes/dir"), false));
+        assert!(!paths_equal(Path::new("/notes/a.md"), Path::new("/notes/a.md/b"), true));
+        assert!(same_path(upper, upper));
+    }
+
+    #[test]
+    fn test_slash_path() {
+        assert_eq!(slash_path(Path::new("projects/ideas")), "projects/ideas");
+        assert_eq!(slash_path(&Path::new("a").join("b").join("c")), "a/b/c");
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let dir = std::env::temp_dir();
+        let canonical = canonicalize(&dir.join(".")).unwrap();
+        assert!(!canonical.to_string_lossy().starts_with(r"\\?\"));
+        assert!(same_path(&canonical, &canonicalize(&dir).unwrap()));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_strip_verbatim() {
+        assert_eq!(strip_verbatim(r"\\?\C:\notes\a.md").as_deref(), Some(r"C:\notes\a.md"));
+        assert_eq!(strip_verbatim(r"\\?\C:\").as_deref(), Some(r"C:\"));
+        assert_eq!(strip_verbatim(r"\\?\UNC\server\share\a.md").as_deref(), Some(r"\\server\share\a.md"));
+        assert_eq!(strip_verbatim(r"C:\notes"), None);
+        assert_eq!(strip_verbatim(r"\\?\Volume{1234}\notes"), None);
+        assert_eq!(strip_verbatim(r"\\?\C:\notes\nul.md"), None);
+        assert_eq!(strip_verbatim(r"\\?\C:\notes\COM1"), None);
+        assert_eq!(strip_verbatim(r"\\?\C:\notes\trailing."), None);
+        assert_eq!(strip_verbatim(&format!(r"\\?\C:\{}", "a".repeat(300))), None);
+        assert_eq!(simplify(PathBuf::from(r"\\?\D:\x")), PathBuf::from(r"D:\x"));
+    }
+}
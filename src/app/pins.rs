@@ -0,0 +1,169 @@
+//! Notes and folders pinned to the top of the sidebar. Each vault's pins are
+//! kept by their path relative to the notes folder, so they follow a rename
+//! or move made in ekphos and are dropped once the file is gone.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// On-disk form, e.g. `[vaults] "/home/me/notes" = ["Inbox.md", "projects"]`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PinFile {
+    #[serde(default)]
+    vaults: BTreeMap<String, Vec<PathBuf>>,
+}
+
+impl PinFile {
+    fn read(file: &Path) -> Self {
+        fs::read_to_string(file)
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// The pins of one vault, in the order they were pinned.
+#[derive(Debug, Clone, Default)]
+pub struct Pins {
+    root: PathBuf,
+    /// Relative to `root`
+    paths: Vec<PathBuf>,
+}
+
+impl Pins {
+    /// The pins stored in `file` for the vault at `root`.
+    pub fn load(file: &Path, root: &Path) -> Self {
+        let paths = PinFile::read(file)
+            .vaults
+            .remove(&root.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Self { root: root.to_path_buf(), paths }
+    }
+
+    /// Write these pins to `file`, leaving other vaults' pins as they are.
+    pub fn save(&self, file: &Path) -> std::io::Result<()> {
+        let mut pin_file = PinFile::read(file);
+        let key = self.root.to_string_lossy().to_string();
+        if self.paths.is_empty() {
+            pin_file.vaults.remove(&key);
+        } else {
+            pin_file.vaults.insert(key, self.paths.clone());
+        }
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(file, toml::to_string_pretty(&pin_file).unwrap_or_default())
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Full paths of the pinned notes and folders, in pin order.
+    pub fn paths(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        self.paths.iter().map(|path| self.root.join(path))
+    }
+
+    pub fn is_pinned(&self, path: &Path) -> bool {
+        path.strip_prefix(&self.root).is_ok_and(|rel| self.paths.iter().any(|p| p == rel))
+    }
+
+    /// Pin `path`, or unpin it if it was pinned. Returns whether it's pinned
+    /// now; paths outside the vault can't be.
+    pub fn toggle(&mut self, path: &Path) -> bool {
+        let Ok(rel) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        match self.paths.iter().position(|p| p == rel) {
+            Some(idx) => {
+                self.paths.remove(idx);
+                false
+            }
+            None => {
+                self.paths.push(rel.to_path_buf());
+                true
+            }
+        }
+    }
+
+    /// Follow a rename or move of `from` to `to`, which may be a folder with
+    /// pins inside it. Returns whether any pin changed.
+    pub fn moved(&mut self, from: &Path, to: &Path) -> bool {
+        let (Ok(from), Ok(to)) = (from.strip_prefix(&self.root), to.strip_prefix(&self.root)) else {
+            return false;
+        };
+        let mut changed = false;
+        for path in &mut self.paths {
+            if let Ok(rest) = path.strip_prefix(from) {
+                *path = if rest.as_os_str().is_empty() { to.to_path_buf() } else { to.join(rest) };
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Drop the pins whose file or folder no longer exists. Returns whether
+    /// any were dropped.
+    pub fn prune(&mut self) -> bool {
+        let before = self.paths.len();
+        let root = &self.root;
+        self.paths.retain(|path| root.join(path).exists());
+        self.paths.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_and_move() {
+        let root = Path::new("/notes");
+        let mut pins = Pins { root: root.to_path_buf(), paths: Vec::new() };
+        assert!(pins.toggle(&root.join("Inbox.md")));
+        assert!(pins.toggle(&root.join("projects")));
+        assert!(pins.toggle(&root.join("projects/Plan.md")));
+        assert!(!pins.toggle(Path::new("/elsewhere/Outside.md")));
+        assert!(pins.is_pinned(&root.join("Inbox.md")));
+
+        assert!(pins.moved(&root.join("projects"), &root.join("archive/projects")));
+        assert!(pins.moved(&root.join("Inbox.md"), &root.join("Today.md")));
+        assert!(!pins.moved(&root.join("projects-old"), &root.join("x")));
+        let paths: Vec<PathBuf> = pins.paths().collect();
+        assert_eq!(
+            paths,
+            [root.join("Today.md"), root.join("archive/projects"), root.join("archive/projects/Plan.md")]
+        );
+
+        assert!(!pins.toggle(&root.join("Today.md")));
+        assert!(!pins.is_pinned(&root.join("Today.md")));
+    }
+
+    #[test]
+    fn test_save_keeps_other_vaults_and_prunes() {
+        let dir = std::env::temp_dir().join(format!("ekphos-pins-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let (work, home) = (dir.join("work"), dir.join("home"));
+        fs::create_dir_all(&work).unwrap();
+        fs::create_dir_all(&home).unwrap();
+        fs::write(work.join("Plan.md"), "").unwrap();
+        fs::write(home.join("Garden.md"), "").unwrap();
+        let file = dir.join("cache/pins.toml");
+
+        let mut pins = Pins::load(&file, &work);
+        pins.toggle(&work.join("Plan.md"));
+        pins.toggle(&work.join("Gone.md"));
+        pins.save(&file).unwrap();
+        let mut other = Pins::load(&file, &home);
+        other.toggle(&home.join("Garden.md"));
+        other.save(&file).unwrap();
+
+        let mut pins = Pins::load(&file, &work);
+        assert_eq!(pins.paths().count(), 2);
+        assert!(pins.prune());
+        assert_eq!(pins.paths().collect::<Vec<_>>(), [work.join("Plan.md")]);
+        assert!(Pins::load(&file, &home).is_pinned(&home.join("Garden.md")));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,303 @@
+//! Writing-style hints, flagged with `lint.prose = true` and listed by
+//! `:prose`: vague words like "very", likely passive voice, a word written
+//! twice in a row and overlong sentences. A sentence runs to `.`, `!` or `?`
+//! or the end of its line. Frontmatter, fenced and inline code, and link
+//! URLs are skipped.
+//!
+//! `prose_words.toml` in the config folder adds to the built-in lists:
+//!
+//! ```toml
+//! weasel_words = ["basically"]   # flagged as well as the built-in ones
+//! participles = ["thrown"]       # irregular ones, for passive voice
+//! allowed = ["quite"]            # never flagged
+//! ```
+
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::config::ProseRules;
+
+use super::code_fence;
+use super::frontmatter::Frontmatter;
+use super::markdown_lint::{Diagnostic, Severity};
+
+const WEASEL_WORDS: &[&str] = &[
+    "very", "quite", "really", "fairly", "rather", "somewhat", "extremely", "various", "several", "mostly",
+    "largely", "clearly", "obviously", "basically", "actually", "simply", "arguably", "surprisingly",
+    "remarkably", "significantly", "relatively", "virtually", "totally", "completely", "literally",
+];
+
+/// Past participles not ending in `-ed`.
+const PARTICIPLES: &[&str] = &[
+    "been", "begun", "bought", "broken", "brought", "built", "caught", "chosen", "done", "drawn", "driven",
+    "eaten", "fallen", "felt", "forgotten", "found", "given", "gotten", "grown", "held", "hidden", "kept",
+    "known", "laid", "led", "left", "lost", "made", "meant", "paid", "put", "read", "run", "said", "seen",
+    "sent", "set", "shown", "sold", "spent", "spoken", "stolen", "taken", "taught", "thought", "told",
+    "understood", "won", "worn", "written",
+];
+
+const BE_VERBS: &[&str] = &["am", "is", "are", "was", "were", "be", "been", "being"];
+
+/// Lines above this many distinct texts are forgotten rather than cached.
+const CACHE_LIMIT: usize = 10_000;
+
+/// `prose_words.toml`.
+#[derive(Debug, Default, Deserialize)]
+struct WordFile {
+    #[serde(default)]
+    weasel_words: Vec<String>,
+    #[serde(default)]
+    participles: Vec<String>,
+    #[serde(default)]
+    allowed: Vec<String>,
+}
+
+/// A hint on one line: its char range and message.
+type Finding = (usize, usize, String);
+
+#[derive(Debug, Default)]
+pub struct ProseLinter {
+    rules: ProseRules,
+    weasel_words: HashSet<String>,
+    participles: HashSet<String>,
+    /// Findings by line text, so an edit only relints the lines it changed
+    cache: HashMap<String, Vec<Finding>>,
+}
+
+impl ProseLinter {
+    /// A linter for `rules`, with the words from `word_file` if it exists.
+    pub fn new(rules: &ProseRules, word_file: &Path) -> Self {
+        let words = fs::read_to_string(word_file)
+            .ok()
+            .and_then(|content| toml::from_str::<WordFile>(&content).ok())
+            .unwrap_or_default();
+        let lower = |words: Vec<String>| words.into_iter().map(|w| w.to_lowercase());
+        let allowed: HashSet<String> = lower(words.allowed).collect();
+        let list = |built_in: &[&str], extra: Vec<String>| -> HashSet<String> {
+            built_in.iter().map(|w| w.to_string()).chain(lower(extra)).filter(|w| !allowed.contains(w)).collect()
+        };
+        Self {
+            rules: rules.clone(),
+            weasel_words: list(WEASEL_WORDS, words.weasel_words),
+            participles: list(PARTICIPLES, words.participles),
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn rules(&self) -> &ProseRules {
+        &self.rules
+    }
+
+    /// Hints for the buffer `lines`, in row order.
+    pub fn lint(&mut self, lines: &[&str]) -> Vec<Diagnostic> {
+        let (_, body_start) = Frontmatter::parse(&lines.join("\n"));
+        let body_start = body_start.min(lines.len());
+        let mut in_code = vec![false; lines.len()];
+        for (open, close) in code_fence::code_blocks(&lines[body_start..]) {
+            let end = close.map_or(lines.len(), |close| body_start + close + 1);
+            in_code[body_start + open..end].fill(true);
+        }
+
+        if self.cache.len() > CACHE_LIMIT {
+            self.cache.clear();
+        }
+        let mut diagnostics = Vec::new();
+        for (row, line) in lines.iter().enumerate().skip(body_start) {
+            if in_code[row] || line.trim().is_empty() {
+                continue;
+            }
+            if !self.cache.contains_key(*line) {
+                let findings = self.lint_line(line);
+                self.cache.insert(line.to_string(), findings);
+            }
+            diagnostics.extend(self.cache[*line].iter().map(|(start, end, message)| Diagnostic {
+                row,
+                start_col: *start,
+                end_col: *end,
+                message: message.clone(),
+                severity: Severity::Hint,
+            }));
+        }
+        diagnostics
+    }
+
+    fn lint_line(&self, line: &str) -> Vec<Finding> {
+        let chars: Vec<char> = line.chars().collect();
+        let words = words(&chars);
+        let mut findings = Vec::new();
+
+        for (i, word) in words.iter().enumerate() {
+            if self.rules.weasel_words && self.weasel_words.contains(&word.lower) {
+                findings.push((word.start, word.end, format!("weasel-word: \"{}\" is vague", word.text(&chars))));
+            }
+            if self.rules.repeated_words && i > 0 {
+                let prev = &words[i - 1];
+                let gap_is_space = chars[prev.end..word.start].iter().all(|c| c.is_whitespace());
+                if prev.lower == word.lower && gap_is_space && !word.lower.chars().all(|c| c.is_ascii_digit()) {
+                    findings.push((prev.start, word.end, format!("repeated-word: \"{}\" twice", word.text(&chars))));
+                }
+            }
+            if self.rules.passive_voice && BE_VERBS.contains(&word.lower.as_str()) {
+                // `was written`, or `was quickly written`
+                let next = words.get(i + 1).filter(|w| !w.lower.ends_with("ly")).or_else(|| words.get(i + 2));
+                if let Some(participle) = next.filter(|w| self.is_participle(&w.lower)) {
+                    let text: String = chars[word.start..participle.end].iter().collect();
+                    findings.push((word.start, participle.end, format!("passive-voice: \"{}\" may be passive", text)));
+                }
+            }
+        }
+
+        if self.rules.long_sentences {
+            let max = self.rules.max_sentence_words;
+            for (start, end, count) in sentences(&chars, &words) {
+                if count > max {
+                    findings.push((start, end, format!("long-sentence: {} words, over {}", count, max)));
+                }
+            }
+        }
+        findings.sort_by_key(|&(start, end, _)| (start, end));
+        findings
+    }
+
+    fn is_participle(&self, word: &str) -> bool {
+        (word.len() > 3 && word.ends_with("ed") && !BE_VERBS.contains(&word)) || self.participles.contains(word)
+    }
+}
+
+struct Word {
+    start: usize,
+    end: usize,
+    lower: String,
+}
+
+impl Word {
+    fn text(&self, chars: &[char]) -> String {
+        chars[self.start..self.end].iter().collect()
+    }
+}
+
+/// The words of a line outside inline code and link URLs.
+fn words(chars: &[char]) -> Vec<Word> {
+    let skipped = skipped_columns(chars);
+    let mut words = Vec::new();
+    let mut col = 0;
+    while col < chars.len() {
+        if skipped[col] || !chars[col].is_alphanumeric() {
+            col += 1;
+            continue;
+        }
+        let start = col;
+        // An apostrophe inside a word, as in "don't", is part of it
+        while col < chars.len()
+            && !skipped[col]
+            && (chars[col].is_alphanumeric()
+                || (matches!(chars[col], '\'' | '’') && chars.get(col + 1).is_some_and(|c| c.is_alphanumeric())))
+        {
+            col += 1;
+        }
+        let lower = chars[start..col].iter().collect::<String>().to_lowercase();
+        words.push(Word { start, end: col, lower });
+    }
+    words
+}
+
+/// Columns of inline code, the `(url)` of a `[text](url)` link, `<url>`
+/// autolinks and bare `http://` or `https://` URLs.
+fn skipped_columns(chars: &[char]) -> Vec<bool> {
+    let mut skipped = vec![false; chars.len()];
+    let mut col = 0;
+    while col < chars.len() {
+        let rest: String = chars[col..chars.len().min(col + 8)].iter().collect();
+        let end = if chars[col] == '`' {
+            chars[col + 1..].iter().position(|&c| c == '`').map(|i| col + i + 2)
+        } else if chars[col] == '(' && col > 0 && chars[col - 1] == ']' {
+            chars[col..].iter().position(|&c| c == ')').map(|i| col + i + 1)
+        } else if chars[col] == '<' && (rest.starts_with("<http") || rest.starts_with("<mailto")) {
+            chars[col..].iter().position(|&c| c == '>').map(|i| col + i + 1)
+        } else if rest.starts_with("http://") || rest.starts_with("https://") {
+            Some(chars[col..].iter().position(|c| c.is_whitespace()).map_or(chars.len(), |i| col + i))
+        } else {
+            None
+        };
+        match end {
+            Some(end) => {
+                skipped[col..end].fill(true);
+                col = end;
+            }
+            None => col += 1,
+        }
+    }
+    skipped
+}
+
+/// Each sentence's char range, from its first word, and how many words it has.
+fn sentences(chars: &[char], words: &[Word]) -> Vec<(usize, usize, usize)> {
+    let ends_sentence = |col: usize| {
+        matches!(chars[col], '.' | '!' | '?') && chars.get(col + 1).is_none_or(|c| c.is_whitespace())
+    };
+    let mut sentences = Vec::new();
+    let mut current: Option<(usize, usize)> = None;
+    for (i, word) in words.iter().enumerate() {
+        let (start, count) = current.unwrap_or((word.start, 0));
+        let next_start = words.get(i + 1).map_or(chars.len(), |w| w.start);
+        match (word.end..next_start).find(|&col| ends_sentence(col)) {
+            Some(end) => {
+                sentences.push((start, end + 1, count + 1));
+                current = None;
+            }
+            None => current = Some((start, count + 1)),
+        }
+    }
+    if let Some((start, count)) = current {
+        let end = words.last().map_or(chars.len(), |w| w.end);
+        sentences.push((start, end, count));
+    }
+    sentences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_support::TempDir;
+
+    fn hints(text: &str, rules: &ProseRules) -> Vec<(usize, usize, usize, String)> {
+        let mut linter = ProseLinter::new(rules, Path::new("/nonexistent/prose_words.toml"));
+        let lines: Vec<&str> = text.lines().collect();
+        linter.lint(&lines).into_iter().map(|d| (d.row, d.start_col, d.end_col, d.message)).collect()
+    }
+
+    #[test]
+    fn test_prose_rules() {
+        let note = "---\ntitle: A very long title\n---\n\
+            It is very good and the the result was written quickly.\n\
+            See [docs](https://example.com/very/the/the) and `very very`.\n\
+            ```\nvery\n```\n\
+            The paper was carefully reviewed. Don't don't stop!";
+        assert_eq!(
+            hints(note, &ProseRules::default()),
+            [
+                (3, 6, 10, "weasel-word: \"very\" is vague".to_string()),
+                (3, 20, 27, "repeated-word: \"the\" twice".to_string()),
+                (3, 35, 46, "passive-voice: \"was written\" may be passive".to_string()),
+                (8, 10, 32, "passive-voice: \"was carefully reviewed\" may be passive".to_string()),
+                (8, 34, 45, "repeated-word: \"don't\" twice".to_string()),
+            ]
+        );
+
+        let rules = ProseRules { max_sentence_words: 5, weasel_words: false, ..ProseRules::default() };
+        let found = hints("One two three four five six. Short one here.", &rules);
+        assert_eq!(found, [(0, 0, 28, "long-sentence: 6 words, over 5".to_string())]);
+    }
+
+    #[test]
+    fn test_word_file() {
+        let dir = TempDir::new("prose");
+        let file = dir.join("prose_words.toml");
+        fs::write(&file, "weasel_words = [\"Basically\", \"kinda\"]\nallowed = [\"very\"]\n").unwrap();
+        let mut linter = ProseLinter::new(&ProseRules::default(), &file);
+        let found: Vec<String> = linter.lint(&["very kinda basically"]).into_iter().map(|d| d.message).collect();
+        assert_eq!(found, ["weasel-word: \"kinda\" is vague", "weasel-word: \"basically\" is vague"]);
+    }
+}
@@ -0,0 +1,127 @@
+//! Quickfix list: locations found by a search or check across notes, stepped
+//! through with `:cnext`/`:cprev` as in vim.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickfixEntry {
+    pub note: usize,
+    /// Line in the note's text, from 0.
+    pub line: usize,
+    /// Char column in the line.
+    pub col: usize,
+    pub text: String,
+}
+
+/// Where jumping to an entry takes the view.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuickfixJump {
+    SameNote { line: usize, col: usize },
+    OtherNote { note: usize, line: usize, col: usize },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct QuickfixList {
+    /// What filled the list, shown as the quickfix window title.
+    pub title: String,
+    pub entries: Vec<QuickfixEntry>,
+    /// Entry last jumped to.
+    pub current: Option<usize>,
+}
+
+impl QuickfixList {
+    pub fn new(title: impl Into<String>, entries: Vec<QuickfixEntry>) -> Self {
+        Self {
+            title: title.into(),
+            entries,
+            current: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Move to the next or previous entry, starting from the first. `None` at
+    /// either end of the list, where the current entry stays put.
+    pub fn step(&mut self, forward: bool) -> Option<usize> {
+        let next = match self.current {
+            None if !self.entries.is_empty() => 0,
+            Some(i) if forward && i + 1 < self.entries.len() => i + 1,
+            Some(i) if !forward && i > 0 => i - 1,
+            _ => return None,
+        };
+        self.current = Some(next);
+        Some(next)
+    }
+
+    /// Make `index` the current entry, if there is one.
+    pub fn select(&mut self, index: usize) -> Option<usize> {
+        (index < self.entries.len()).then(|| {
+            self.current = Some(index);
+            index
+        })
+    }
+
+    /// Where jumping to entry `index` goes, with `current_note` selected.
+    pub fn jump(&self, index: usize, current_note: usize) -> Option<QuickfixJump> {
+        let entry = self.entries.get(index)?;
+        let (line, col) = (entry.line, entry.col);
+        Some(if entry.note == current_note {
+            QuickfixJump::SameNote { line, col }
+        } else {
+            QuickfixJump::OtherNote {
+                note: entry.note,
+                line,
+                col,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(note: usize, line: usize) -> QuickfixEntry {
+        QuickfixEntry {
+            note,
+            line,
+            col: 2,
+            text: format!("line {}", line),
+        }
+    }
+
+    #[test]
+    fn test_step_through_entries() {
+        let mut list = QuickfixList::new("Search", vec![entry(0, 1), entry(0, 5), entry(3, 0)]);
+        assert_eq!(list.step(false), Some(0));
+        assert_eq!(list.step(true), Some(1));
+        assert_eq!(list.step(true), Some(2));
+        assert_eq!(list.step(true), None);
+        assert_eq!(list.current, Some(2));
+        assert_eq!(list.step(false), Some(1));
+
+        assert_eq!(list.select(7), None);
+        assert_eq!(list.select(0), Some(0));
+        assert_eq!(list.step(false), None);
+
+        let mut empty = QuickfixList::default();
+        assert!(empty.is_empty());
+        assert_eq!(empty.step(true), None);
+    }
+
+    #[test]
+    fn test_jump_opens_other_note() {
+        let mut list = QuickfixList::new("Links", vec![entry(0, 4), entry(3, 7)]);
+        let first = list.step(true).unwrap();
+        assert_eq!(list.jump(first, 0), Some(QuickfixJump::SameNote { line: 4, col: 2 }));
+
+        // The second entry is in note 3, so jumping there opens it
+        let second = list.step(true).unwrap();
+        assert_eq!(
+            list.jump(second, 0),
+            Some(QuickfixJump::OtherNote { note: 3, line: 7, col: 2 })
+        );
+        assert_eq!(list.jump(second, 3), Some(QuickfixJump::SameNote { line: 7, col: 2 }));
+        assert_eq!(list.jump(9, 0), None);
+    }
+}
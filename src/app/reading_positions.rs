@@ -0,0 +1,105 @@
+//! Where each note was left in the content view, so going back to it picks
+//! up there. Kept for the session, and across restarts in `positions.toml`
+//! with `remember_positions = true`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The content cursor and scroll offset, in content items.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReadingPosition {
+    pub cursor: usize,
+    pub scroll: usize,
+}
+
+impl ReadingPosition {
+    /// This position in a note of `len` content items, which may be fewer
+    /// than when it was left.
+    pub fn clamped(self, len: usize) -> Self {
+        let last = len.saturating_sub(1);
+        Self { cursor: self.cursor.min(last), scroll: self.scroll.min(last) }
+    }
+}
+
+/// On-disk form, e.g. `"/home/me/notes/Book.md" = { cursor = 120, scroll = 104 }`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PositionFile {
+    #[serde(default)]
+    notes: BTreeMap<String, ReadingPosition>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReadingPositions {
+    positions: HashMap<PathBuf, ReadingPosition>,
+}
+
+impl ReadingPositions {
+    pub fn load(file: &Path) -> Self {
+        let positions = fs::read_to_string(file)
+            .ok()
+            .and_then(|content| toml::from_str::<PositionFile>(&content).ok())
+            .unwrap_or_default()
+            .notes
+            .into_iter()
+            .map(|(path, position)| (PathBuf::from(path), position))
+            .collect();
+        Self { positions }
+    }
+
+    /// Write the positions out, leaving out notes that no longer exist.
+    pub fn save(&self, file: &Path) -> std::io::Result<()> {
+        let notes = self
+            .positions
+            .iter()
+            .filter(|(path, _)| path.exists())
+            .map(|(path, position)| (path.to_string_lossy().to_string(), *position))
+            .collect();
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(file, toml::to_string_pretty(&PositionFile { notes }).unwrap_or_default())
+    }
+
+    pub fn get(&self, path: &Path) -> Option<ReadingPosition> {
+        self.positions.get(path).copied()
+    }
+
+    pub fn remember(&mut self, path: PathBuf, position: ReadingPosition) {
+        self.positions.insert(path, position);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamped() {
+        let position = ReadingPosition { cursor: 40, scroll: 30 };
+        assert_eq!(position.clamped(100), position);
+        assert_eq!(position.clamped(35), ReadingPosition { cursor: 34, scroll: 30 });
+        assert_eq!(position.clamped(0), ReadingPosition::default());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("ekphos-positions-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let (book, gone) = (dir.join("Book.md"), dir.join("Gone.md"));
+        fs::write(&book, "").unwrap();
+        let file = dir.join("cache/positions.toml");
+
+        let mut positions = ReadingPositions::default();
+        positions.remember(book.clone(), ReadingPosition { cursor: 120, scroll: 104 });
+        positions.remember(gone.clone(), ReadingPosition { cursor: 3, scroll: 0 });
+        positions.save(&file).unwrap();
+
+        let loaded = ReadingPositions::load(&file);
+        assert_eq!(loaded.get(&book), Some(ReadingPosition { cursor: 120, scroll: 104 }));
+        assert_eq!(loaded.get(&gone), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,188 @@
+//! Reference-style links: `[text][ref]`, or collapsed `[text][]`, pointing at
+//! a `[ref]: url` definition anywhere in the note. Labels match ignoring case
+//! and runs of whitespace. The content view shows resolved references as
+//! ordinary inline links; ones without a definition are shown as broken.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use super::code_fence;
+
+/// Definition URLs by [`normalize_label`]ed label.
+pub type Definitions = HashMap<String, String>;
+
+/// A `[text][ref]` in a line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReferenceLink<'a> {
+    pub text: &'a str,
+    /// The label, which is the text for a collapsed `[text][]`
+    pub label: &'a str,
+    /// Byte offset just past the closing `]`
+    pub end: usize,
+}
+
+pub fn normalize_label(label: &str) -> String {
+    label.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// The label and URL of a `[ref]: url "title"` definition line. Footnote
+/// definitions (`[^1]:`) aren't links.
+pub fn parse_definition(line: &str) -> Option<(&str, &str)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let rest = trimmed.strip_prefix('[')?;
+    let close = rest.find(']')?;
+    let label = &rest[..close];
+    if label.trim().is_empty() || label.starts_with('^') || label.contains('[') {
+        return None;
+    }
+    let destination = rest[close + 1..].strip_prefix(':')?.trim_start();
+    let url = match destination.strip_prefix('<') {
+        Some(bracketed) => &bracketed[..bracketed.find('>')?],
+        None => destination.split_whitespace().next()?,
+    };
+    (!url.is_empty()).then_some((label, url))
+}
+
+/// Every definition in `content` outside code blocks. The first definition
+/// of a label wins, as in CommonMark.
+pub fn collect_definitions(content: &str) -> Definitions {
+    let mut definitions = Definitions::new();
+    let mut in_code_block = false;
+    for line in content.lines() {
+        if code_fence::is_fence(line) {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        if let Some((label, url)) = parse_definition(line) {
+            definitions.entry(normalize_label(label)).or_insert_with(|| url.to_string());
+        }
+    }
+    definitions
+}
+
+/// The reference link starting at byte `at` of `text`, if there is one.
+/// Wiki links, inline links and images don't count.
+pub fn reference_link_at(text: &str, at: usize) -> Option<ReferenceLink<'_>> {
+    let rest = text.get(at..)?;
+    if !rest.starts_with('[') || rest.starts_with("[[") || text[..at].ends_with('!') {
+        return None;
+    }
+    let text_end = rest[1..].find([']', '['])? + 1;
+    let after = rest[text_end..].strip_prefix("][")?;
+    let label_end = after.find([']', '['])?;
+    if !after[label_end..].starts_with(']') {
+        return None;
+    }
+    let link_text = &rest[1..text_end];
+    if link_text.trim().is_empty() {
+        return None;
+    }
+    let label = match &after[..label_end] {
+        "" => link_text,
+        label => label,
+    };
+    Some(ReferenceLink { text: link_text, label, end: at + text_end + 2 + label_end + 1 })
+}
+
+/// `line` with each reference link that has a definition written as an
+/// inline `[text](url)` link. Dangling references and any in inline code
+/// are left as they are.
+pub fn resolve_references<'a>(line: &'a str, definitions: &Definitions) -> Cow<'a, str> {
+    if definitions.is_empty() || !line.contains("][") {
+        return Cow::Borrowed(line);
+    }
+    let mut resolved = String::with_capacity(line.len());
+    let mut copied = 0;
+    let mut in_code = false;
+    for (at, c) in line.char_indices() {
+        if at < copied {
+            continue;
+        }
+        if c == '`' {
+            in_code = !in_code;
+        }
+        if in_code || c != '[' {
+            continue;
+        }
+        let Some(link) = reference_link_at(line, at) else {
+            continue;
+        };
+        if let Some(url) = definitions.get(&normalize_label(link.label)) {
+            resolved.push_str(&line[copied..at]);
+            resolved.push_str(&format!("[{}]({})", link.text, url));
+            copied = link.end;
+        }
+    }
+    if copied == 0 {
+        return Cow::Borrowed(line);
+    }
+    resolved.push_str(&line[copied..]);
+    Cow::Owned(resolved)
+}
+
+/// The references in `line` without a definition, as char columns and
+/// labels, for `:checklinks`.
+pub fn dangling_references(line: &str, definitions: &Definitions) -> Vec<(usize, String)> {
+    let mut dangling = Vec::new();
+    let mut skip_to = 0;
+    for (col, (at, _)) in line.char_indices().enumerate() {
+        if at < skip_to {
+            continue;
+        }
+        if let Some(link) = reference_link_at(line, at) {
+            if !definitions.contains_key(&normalize_label(link.label)) {
+                dangling.push((col, link.label.to_string()));
+            }
+            skip_to = link.end;
+        }
+    }
+    dangling
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "See [the docs][Docs] and [Rust][].\n\
+        Also [this][missing], ![img][docs] and [inline](x).\n\
+        \n\
+        [docs]: <https://docs.rs/a b> \"Docs\"\n\
+        [  RUST ]: https://rust-lang.org\n\
+        [docs]: https://ignored.example\n\
+        ```\n\
+        [code]: https://not.a.definition\n\
+        ```\n";
+
+    #[test]
+    fn test_definitions() {
+        let definitions = collect_definitions(NOTE);
+        assert_eq!(definitions.len(), 2);
+        assert_eq!(definitions["docs"], "https://docs.rs/a b");
+        assert_eq!(definitions["rust"], "https://rust-lang.org");
+        assert_eq!(parse_definition("[^1]: A footnote"), None);
+        assert_eq!(parse_definition("    [x]: https://indented.code"), None);
+        assert_eq!(parse_definition("[x]:"), None);
+    }
+
+    #[test]
+    fn test_resolve_references() {
+        let definitions = collect_definitions(NOTE);
+        let lines: Vec<&str> = NOTE.lines().collect();
+        assert_eq!(
+            resolve_references(lines[0], &definitions),
+            "See [the docs](https://docs.rs/a b) and [Rust](https://rust-lang.org)."
+        );
+        // Dangling and image references are left alone
+        assert_eq!(resolve_references(lines[1], &definitions), lines[1]);
+        assert_eq!(resolve_references("`[Rust][]` [Rust][]", &definitions), "`[Rust][]` [Rust](https://rust-lang.org)");
+        assert_eq!(dangling_references(lines[1], &definitions), [(5, "missing".to_string())]);
+        assert_eq!(reference_link_at("[[wiki]][x]", 0), None);
+        assert_eq!(reference_link_at("[a [b][c]", 0), None);
+    }
+}
@@ -0,0 +1,303 @@
+//! Refiling: moving a list item, with the lines nested under it, out of one
+//! note and under a heading of another, for an inbox note whose bullets get
+//! sorted into their proper notes later. A heading's whole section can be
+//! moved or copied the same way.
+
+use std::ops::Range;
+
+use super::code_fence;
+use super::heading_refactor::parse_heading;
+use super::list_item::{leading_columns, parse_list_marker};
+
+/// What's refiled.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RefileKind {
+    /// A list item and what's nested under it
+    #[default]
+    Item,
+    /// A heading and its section, subheadings included
+    Section { copy: bool },
+}
+
+impl RefileKind {
+    /// What the pickers say is done, as in "Refile to".
+    pub fn verb(self) -> &'static str {
+        match self {
+            RefileKind::Item => "Refile",
+            RefileKind::Section { copy: false } => "Move section",
+            RefileKind::Section { copy: true } => "Copy section",
+        }
+    }
+}
+
+/// Where in the target note the item goes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RefileTarget {
+    /// At the end of the section under the heading on this row
+    Heading(usize),
+    /// Under a new `## title` heading at the end of the note
+    NewHeading(String),
+    /// At the end of the note
+    End,
+}
+
+/// A list item taken out of its note.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TakenItem {
+    /// The rows it was on
+    pub rows: Range<usize>,
+    /// The note's lines without it
+    pub remaining: Vec<String>,
+    /// Its lines, moved to the top level
+    pub block: Vec<String>,
+}
+
+/// Which of `lines` are frontmatter or inside a code block, fences included.
+fn not_text(lines: &[&str]) -> Vec<bool> {
+    let mut skip = vec![false; lines.len()];
+    if lines.first().is_some_and(|l| l.trim_end() == "---") {
+        let end = lines.iter().skip(1).position(|l| l.trim_end() == "---").map_or(lines.len(), |i| i + 2);
+        skip[..end].fill(true);
+    }
+    for (open, close) in code_fence::code_blocks(lines) {
+        let end = close.map_or(lines.len(), |close| close + 1);
+        skip[open..end].fill(true);
+    }
+    skip
+}
+
+/// The list item on `row` and everything nested under it, up to a blank line.
+pub fn take_item(lines: &[&str], row: usize) -> Result<TakenItem, &'static str> {
+    let line = lines.get(row).copied().unwrap_or_default();
+    if not_text(lines).get(row) == Some(&true) && parse_list_marker(line).is_some() {
+        return Err("Can't refile from a code block or frontmatter");
+    }
+    if parse_list_marker(line).is_none() {
+        return Err("Not on a list item");
+    }
+    let indent = leading_columns(line);
+    let end = (row + 1..lines.len())
+        .find(|&i| lines[i].trim().is_empty() || leading_columns(lines[i]) <= indent)
+        .unwrap_or(lines.len());
+
+    let block = lines[row..end].iter().map(|l| dedent(l, indent)).collect();
+    let remaining = lines[..row].iter().chain(&lines[end..]).map(|l| l.to_string()).collect();
+    Ok(TakenItem { rows: row..end, remaining, block })
+}
+
+/// The heading on `row` and its section, up to the next heading of the same
+/// or a higher level. Trailing blank lines stay where they were.
+pub fn take_section(lines: &[&str], row: usize) -> Result<TakenItem, &'static str> {
+    let skip = not_text(lines);
+    let level = match lines.get(row).and_then(|line| parse_heading(line)) {
+        Some((level, _)) if !skip[row] => level,
+        _ => return Err("Not on a heading"),
+    };
+    let end = (row + 1..lines.len())
+        .find(|&i| !skip[i] && parse_heading(lines[i]).is_some_and(|(l, _)| l <= level))
+        .unwrap_or(lines.len());
+    let end = (row + 1..end).rev().find(|&i| !lines[i].trim().is_empty()).map_or(row + 1, |i| i + 1);
+
+    let block = lines[row..end].iter().map(|l| l.to_string()).collect();
+    let remaining = lines[..row].iter().chain(&lines[end..]).map(|l| l.to_string()).collect();
+    Ok(TakenItem { rows: row..end, remaining, block })
+}
+
+/// What `kind` takes from the row: a copied section leaves the note as it was.
+pub fn take(kind: RefileKind, lines: &[&str], row: usize) -> Result<TakenItem, &'static str> {
+    match kind {
+        RefileKind::Item => take_item(lines, row),
+        RefileKind::Section { copy } => {
+            let mut taken = take_section(lines, row)?;
+            if copy {
+                taken.remaining = lines.iter().map(|l| l.to_string()).collect();
+            }
+            Ok(taken)
+        }
+    }
+}
+
+/// The section `block` with its headings shifted so the first is at
+/// `level`, kept within `#` to `######`. Code blocks are left alone.
+pub fn relevel(block: &[String], level: usize) -> Vec<String> {
+    let refs: Vec<&str> = block.iter().map(String::as_str).collect();
+    let skip = not_text(&refs);
+    let Some((top, _)) = block.first().and_then(|line| parse_heading(line)) else {
+        return block.to_vec();
+    };
+    block
+        .iter()
+        .enumerate()
+        .map(|(row, line)| match parse_heading(line) {
+            Some((old, _)) if !skip[row] => {
+                let new = (old + level).saturating_sub(top).clamp(1, 6);
+                format!("{}{}", "#".repeat(new), &line[old..])
+            }
+            _ => line.clone(),
+        })
+        .collect()
+}
+
+/// The level a section put at `target` in `lines` starts at: one below the
+/// heading it goes under, or `None` at the end of the note, where it keeps
+/// its own.
+pub fn section_level(lines: &[String], target: &RefileTarget) -> Option<usize> {
+    match target {
+        RefileTarget::End => None,
+        RefileTarget::Heading(row) => lines.get(*row).and_then(|line| parse_heading(line)).map(|(level, _)| level + 1),
+        RefileTarget::NewHeading(_) => Some(3),
+    }
+}
+
+/// `line` with up to `columns` of its leading whitespace taken off.
+fn dedent(line: &str, columns: usize) -> String {
+    let mut taken = 0;
+    line.trim_start_matches(|c: char| {
+        let width = if c == '\t' { 4 } else { 1 };
+        let take = c.is_whitespace() && taken + width <= columns;
+        if take {
+            taken += width;
+        }
+        take
+    })
+    .to_string()
+}
+
+/// The headings of a note, as rows and their text with the `#`s.
+pub fn headings(lines: &[&str]) -> Vec<(usize, String)> {
+    let skip = not_text(lines);
+    lines
+        .iter()
+        .enumerate()
+        .filter(|&(row, line)| !skip[row] && parse_heading(line).is_some())
+        .map(|(row, line)| (row, line.trim().to_string()))
+        .collect()
+}
+
+/// `lines` with `block` added at `target`: after the last line of the
+/// heading's section, so it joins a list there, or at the end of the note.
+pub fn insert(lines: &[String], target: &RefileTarget, block: &[String]) -> Vec<String> {
+    let mut lines = lines.to_vec();
+    let last_text = |lines: &[String], rows: Range<usize>| rows.rev().find(|&i| !lines[i].trim().is_empty());
+
+    let at = match target {
+        RefileTarget::Heading(row) if *row < lines.len() => {
+            let refs: Vec<&str> = lines.iter().map(String::as_str).collect();
+            let skip = not_text(&refs);
+            let level = parse_heading(&lines[*row]).map_or(6, |(level, _)| level);
+            let end = (row + 1..lines.len())
+                .find(|&i| !skip[i] && parse_heading(&lines[i]).is_some_and(|(l, _)| l <= level))
+                .unwrap_or(lines.len());
+            last_text(&lines, row + 1..end).unwrap_or(*row)
+        }
+        RefileTarget::NewHeading(title) => {
+            lines.truncate(last_text(&lines, 0..lines.len()).map_or(0, |i| i + 1));
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(format!("## {}", title.trim()));
+            lines.len() - 1
+        }
+        RefileTarget::Heading(_) | RefileTarget::End => {
+            lines.truncate(last_text(&lines, 0..lines.len()).map_or(0, |i| i + 1));
+            lines.len().saturating_sub(1)
+        }
+    };
+
+    // Straight after a list item it joins the list; anything else gets a gap
+    let mut added = Vec::with_capacity(block.len() + 2);
+    let joins_list = lines.get(at).is_some_and(|l| parse_list_marker(l).is_some())
+        && block.first().is_some_and(|l| parse_list_marker(l).is_some());
+    if !lines.is_empty() && !joins_list {
+        added.push(String::new());
+    }
+    added.extend_from_slice(block);
+    if lines.get(at + 1).is_some_and(|l| !l.trim().is_empty()) {
+        added.push(String::new());
+    }
+    let at = if lines.is_empty() { 0 } else { at + 1 };
+    lines.splice(at..at, added);
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INBOX: &str = "# Inbox\n- buy milk\n- read paper\n  - section 3\n  notes\n- call Sam\n\n```\n- code\n```";
+
+    fn strings(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_take_item() {
+        let lines: Vec<&str> = INBOX.lines().collect();
+        let taken = take_item(&lines, 2).unwrap();
+        assert_eq!(taken.rows, 2..5);
+        assert_eq!(taken.block, ["- read paper", "  - section 3", "  notes"]);
+        assert_eq!(taken.remaining[..3], ["# Inbox", "- buy milk", "- call Sam"]);
+
+        // A nested item leaves its parent
+        assert_eq!(take_item(&lines, 3).unwrap().block, ["- section 3"]);
+        assert_eq!(take_item(&lines, 0), Err("Not on a list item"));
+        assert_eq!(take_item(&lines, 8), Err("Can't refile from a code block or frontmatter"));
+        let front = ["---", "tags:", "  - a", "---", "- b"];
+        assert!(take_item(&front, 2).is_err());
+        assert_eq!(take_item(&front, 4).unwrap().rows, 4..5);
+    }
+
+    #[test]
+    fn test_insert() {
+        let note = strings("# Work\n\n## Ideas\n- first\n\n## Later\nText");
+        let block = strings("- new\n  - child");
+        let headings = headings(&note.iter().map(String::as_str).collect::<Vec<_>>());
+        assert_eq!(headings, [(0, "# Work".to_string()), (2, "## Ideas".to_string()), (5, "## Later".to_string())]);
+
+        // Joins the list under its heading
+        assert_eq!(
+            insert(&note, &RefileTarget::Heading(2), &block),
+            strings("# Work\n\n## Ideas\n- first\n- new\n  - child\n\n## Later\nText")
+        );
+        // After text, with a gap, at the end of a section holding subsections
+        assert_eq!(
+            insert(&note, &RefileTarget::Heading(0), &block)[6..],
+            strings("Text\n\n- new\n  - child")
+        );
+        assert_eq!(
+            insert(&strings("## Empty\n## Next"), &RefileTarget::Heading(0), &block),
+            strings("## Empty\n\n- new\n  - child\n\n## Next")
+        );
+        assert_eq!(
+            insert(&strings("Intro\n\n"), &RefileTarget::NewHeading("Refiled".into()), &block),
+            strings("Intro\n\n## Refiled\n\n- new\n  - child")
+        );
+        assert_eq!(insert(&strings("- a\n\n"), &RefileTarget::End, &block), strings("- a\n- new\n  - child"));
+        assert_eq!(insert(&[], &RefileTarget::End, &block), block);
+    }
+
+    #[test]
+    fn test_take_section() {
+        let note = "# Plan\n## Ideas\n- one\n```\n# not a heading\n```\n### Detail\ntext\n\n## Later\nend";
+        let lines: Vec<&str> = note.lines().collect();
+        let taken = take_section(&lines, 1).unwrap();
+        assert_eq!(taken.rows, 1..8);
+        assert_eq!(taken.block[..3], ["## Ideas", "- one", "```"]);
+        assert_eq!(taken.remaining, ["# Plan", "", "## Later", "end"]);
+        assert_eq!(take_section(&lines, 0).unwrap().rows, 0..11);
+        assert_eq!(take_section(&lines, 2), Err("Not on a heading"));
+        assert_eq!(take_section(&lines, 4), Err("Not on a heading"));
+        assert_eq!(take(RefileKind::Section { copy: true }, &lines, 1).unwrap().remaining.len(), lines.len());
+
+        assert_eq!(
+            relevel(&taken.block, 3),
+            ["### Ideas", "- one", "```", "# not a heading", "```", "#### Detail", "text"]
+        );
+        assert_eq!(relevel(&strings("### A\n###### B"), 2), ["## A", "##### B"]);
+        assert_eq!(relevel(&strings("## A\n##### B"), 5)[1], "###### B");
+
+        // A section never joins the list it's put after
+        let list = strings("## Ideas\n- first");
+        assert_eq!(insert(&list, &RefileTarget::Heading(0), &strings("### New")), strings("## Ideas\n- first\n\n### New"));
+    }
+}
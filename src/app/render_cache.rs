@@ -0,0 +1,151 @@
+//! View mode content items as they were last drawn, and how tall they were,
+//! so a frame of a note that isn't changing only draws the items that have
+//! just scrolled into view and copies the rest.
+//!
+//! An item is looked up by its index and a key made of everything it's drawn
+//! from, its size on screen and the theme generation. Anything that changes
+//! the item, its width or its neighbours' decoration changes the key; a theme
+//! or config reload bumps the generation. Search matches and the reload fade
+//! are painted over the copied cells, so they never cost a redraw.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Write};
+use std::hash::{DefaultHasher, Hasher};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+
+/// What an item's cached cells or height were worked out from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItemKey {
+    fingerprint: u64,
+    width: u16,
+    height: u16,
+    generation: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct RenderCache {
+    generation: u64,
+    cells: RefCell<HashMap<usize, (ItemKey, Buffer)>>,
+    heights: RefCell<HashMap<usize, (ItemKey, u16)>>,
+    hits: Cell<usize>,
+    misses: Cell<usize>,
+}
+
+/// Hashes what's written to it, so a `Debug` value can be fingerprinted
+/// without building the string.
+struct HashWriter(DefaultHasher);
+
+impl Write for HashWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// One hash of everything in `parts`, by their `Debug` output.
+pub fn fingerprint(parts: &[&dyn Debug]) -> u64 {
+    let mut writer = HashWriter(DefaultHasher::new());
+    for part in parts {
+        let _ = write!(writer, "{:?}\u{1f}", part);
+    }
+    writer.0.finish()
+}
+
+impl RenderCache {
+    /// Forget everything drawn so far, after the theme or config changed.
+    pub fn invalidate(&mut self) {
+        self.generation += 1;
+        self.clear();
+    }
+
+    /// Drop the cached items, for a different note or folder coming into view.
+    pub fn clear(&mut self) {
+        self.cells.get_mut().clear();
+        self.heights.get_mut().clear();
+    }
+
+    pub fn key(&self, fingerprint: u64, width: u16, height: u16) -> ItemKey {
+        ItemKey {
+            fingerprint,
+            width,
+            height,
+            generation: self.generation,
+        }
+    }
+
+    /// The height item `index` had under `key`, measuring it again otherwise.
+    pub fn height(&self, index: usize, key: ItemKey, measure: impl FnOnce() -> u16) -> u16 {
+        if let Some(&(cached, height)) = self.heights.borrow().get(&index) {
+            if cached == key {
+                return height;
+            }
+        }
+        let height = measure();
+        self.heights.borrow_mut().insert(index, (key, height));
+        height
+    }
+
+    /// Copy item `index` into `area` of `buf` if it was drawn under `key`.
+    pub fn restore(&self, index: usize, key: ItemKey, buf: &mut Buffer, area: Rect) -> bool {
+        let cells = self.cells.borrow();
+        let Some((_, cached)) = cells.get(&index).filter(|(cached, _)| *cached == key) else {
+            self.misses.set(self.misses.get() + 1);
+            return false;
+        };
+        self.hits.set(self.hits.get() + 1);
+        let area = area.intersection(buf.area);
+        for y in 0..area.height {
+            for x in 0..area.width {
+                buf[(area.x + x, area.y + y)] = cached[(x, y)].clone();
+            }
+        }
+        true
+    }
+
+    /// Keep what was just drawn into `area` of `buf` for item `index`.
+    pub fn store(&self, index: usize, key: ItemKey, buf: &Buffer, area: Rect) {
+        let area = area.intersection(buf.area);
+        let mut cached = Buffer::empty(Rect::new(0, 0, area.width, area.height));
+        for y in 0..area.height {
+            for x in 0..area.width {
+                cached[(x, y)] = buf[(area.x + x, area.y + y)].clone();
+            }
+        }
+        self.cells.borrow_mut().insert(index, (key, cached));
+    }
+
+    /// Items copied from the cache and items drawn afresh, since startup.
+    pub fn stats(&self) -> (usize, usize) {
+        (self.hits.get(), self.misses.get())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Color, Style};
+
+    #[test]
+    fn test_cells_come_back_where_they_are_restored() {
+        let mut cache = RenderCache::default();
+        let mut screen = Buffer::empty(Rect::new(0, 0, 10, 6));
+        screen.set_string(2, 1, "item", Style::default().fg(Color::Red));
+
+        let key = cache.key(fingerprint(&[&"item", &0]), 8, 1);
+        cache.store(3, key, &screen, Rect::new(2, 1, 8, 1));
+        assert!(!cache.restore(3, cache.key(fingerprint(&[&"item", &1]), 8, 1), &mut screen, Rect::new(2, 4, 8, 1)));
+        assert!(cache.restore(3, key, &mut screen, Rect::new(2, 4, 8, 1)));
+        assert_eq!(screen[(2, 4)].symbol(), "i");
+        assert_eq!(screen[(5, 4)].fg, Color::Red);
+        assert_eq!(cache.stats(), (1, 1));
+
+        assert_eq!(cache.height(3, key, || 2), 2);
+        assert_eq!(cache.height(3, key, || unreachable!()), 2);
+        cache.invalidate();
+        assert!(!cache.restore(3, key, &mut screen, Rect::new(2, 4, 8, 1)));
+        assert_eq!(cache.height(3, cache.key(fingerprint(&[&"item", &0]), 8, 1), || 5), 5);
+    }
+}
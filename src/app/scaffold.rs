@@ -0,0 +1,241 @@
+//! `:scaffold`: a set of folders and notes created at once, from a template
+//! folder or from an indented outline.
+//!
+//! Template folders live in `~/.config/ekphos/templates/<name>/` and hold
+//! `.md` files in any layout. `{{project}}` in file names and contents is
+//! replaced with a name asked for when the scaffold is created.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::list_item::{leading_columns, parse_list_marker};
+use super::wiki_link::{self, WikiLink};
+
+pub const PROJECT_PLACEHOLDER: &str = "{{project}}";
+
+/// A folder or note to create, relative to the folder the scaffold goes in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScaffoldEntry {
+    Folder(PathBuf),
+    Note { path: PathBuf, content: String },
+}
+
+/// What creating a scaffold did, as absolute paths.
+#[derive(Debug, Default)]
+pub struct ScaffoldReport {
+    pub created: Vec<PathBuf>,
+    /// Notes left alone because a file was already there
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Entries for an outline with one name per line, nested by indentation.
+/// A name ending in `/` or with lines nested under it is a folder; the rest
+/// are notes. List markers in front of names are ignored.
+pub fn parse_outline(outline: &str) -> Vec<ScaffoldEntry> {
+    let lines: Vec<(usize, &str)> = outline
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let name = parse_list_marker(line).map_or(line.trim(), |item| item.text.trim());
+            (leading_columns(line), name)
+        })
+        .collect();
+
+    let mut entries = Vec::new();
+    let mut parents: Vec<(usize, PathBuf)> = Vec::new();
+    for (i, &(indent, name)) in lines.iter().enumerate() {
+        while parents.last().is_some_and(|(parent_indent, _)| *parent_indent >= indent) {
+            parents.pop();
+        }
+        let dir = parents.last().map(|(_, path)| path.clone()).unwrap_or_default();
+        let has_children = lines.get(i + 1).is_some_and(|(next, _)| *next > indent);
+
+        if let Some(folder) = name.strip_suffix('/').or(has_children.then_some(name)) {
+            let path = dir.join(folder.trim());
+            entries.push(ScaffoldEntry::Folder(path.clone()));
+            parents.push((indent, path));
+        } else {
+            let title = name.strip_suffix(".md").unwrap_or(name);
+            entries.push(ScaffoldEntry::Note {
+                path: dir.join(format!("{}.md", title)),
+                content: format!("# {}\n\n", title),
+            });
+        }
+    }
+    entries
+}
+
+/// Entries for the folders and `.md` files under `dir`, parents first.
+pub fn read_template(dir: &Path) -> std::io::Result<Vec<ScaffoldEntry>> {
+    let mut entries = Vec::new();
+    read_template_dir(dir, Path::new(""), &mut entries)?;
+    Ok(entries)
+}
+
+fn read_template_dir(dir: &Path, relative: &Path, entries: &mut Vec<ScaffoldEntry>) -> std::io::Result<()> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?.flatten().map(|entry| entry.path()).collect();
+    paths.sort();
+    for path in paths {
+        let name = path.file_name().unwrap_or_default();
+        if name.to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let relative = relative.join(name);
+        if path.is_dir() {
+            entries.push(ScaffoldEntry::Folder(relative.clone()));
+            read_template_dir(&path, &relative, entries)?;
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            entries.push(ScaffoldEntry::Note { path: relative, content: fs::read_to_string(&path)? });
+        }
+    }
+    Ok(())
+}
+
+/// Whether any name or note in `entries` needs a project name.
+pub fn uses_project(entries: &[ScaffoldEntry]) -> bool {
+    entries.iter().any(|entry| match entry {
+        ScaffoldEntry::Folder(path) => path.to_string_lossy().contains(PROJECT_PLACEHOLDER),
+        ScaffoldEntry::Note { path, content } => {
+            path.to_string_lossy().contains(PROJECT_PLACEHOLDER) || content.contains(PROJECT_PLACEHOLDER)
+        }
+    })
+}
+
+/// Create `entries` under `root`, with `project` filled in. `link_prefix` is
+/// `root` relative to the notes directory; `[[folder/note]]` links between
+/// the new notes get it prepended so they resolve from there.
+pub fn create_scaffold(
+    entries: &[ScaffoldEntry],
+    root: &Path,
+    project: &str,
+    link_prefix: &str,
+) -> Result<ScaffoldReport, String> {
+    let fill = |path: &Path| PathBuf::from(path.to_string_lossy().replace(PROJECT_PLACEHOLDER, project));
+    let note_targets: HashSet<String> = entries
+        .iter()
+        .filter_map(|entry| match entry {
+            ScaffoldEntry::Note { path, .. } => Some(link_target(&fill(path))),
+            ScaffoldEntry::Folder(_) => None,
+        })
+        .collect();
+
+    let mut report = ScaffoldReport::default();
+    for entry in entries {
+        match entry {
+            ScaffoldEntry::Folder(path) => {
+                let path = root.join(fill(path));
+                if !path.is_dir() {
+                    fs::create_dir_all(&path)
+                        .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+                    report.created.push(path);
+                }
+            }
+            ScaffoldEntry::Note { path, content } => {
+                let path = root.join(fill(path));
+                if path.exists() {
+                    report.skipped.push(path);
+                    continue;
+                }
+                let content = content.replace(PROJECT_PLACEHOLDER, project);
+                let content = prefix_wiki_links(&content, &note_targets, link_prefix);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+                }
+                fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+                report.created.push(path);
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// `notes/Plan.md` as a wiki link target: `notes/Plan`.
+fn link_target(path: &Path) -> String {
+    path.with_extension("")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// `content` with `prefix/` put in front of `[[folder/note]]` targets that
+/// are among `targets`.
+fn prefix_wiki_links(content: &str, targets: &HashSet<String>, prefix: &str) -> String {
+    if prefix.is_empty() {
+        return content.to_string();
+    }
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find("[[") {
+        out.push_str(&rest[..start + 2]);
+        let link = WikiLink::parse(&rest[start..]);
+        rest = &rest[start + 2..];
+        if link.is_some_and(|link| link.target.contains('/') && targets.contains(&link.target)) {
+            out.push_str(&wiki_link::escape(prefix));
+            out.push('/');
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(path: &str) -> ScaffoldEntry {
+        let title = Path::new(path).file_stem().unwrap().to_string_lossy().to_string();
+        ScaffoldEntry::Note { path: PathBuf::from(path), content: format!("# {}\n\n", title) }
+    }
+
+    #[test]
+    fn test_parse_outline() {
+        let outline = "{{project}}\n  - Overview\n  Meetings/\n    Kickoff.md\n  Research\n    Sources\nInbox\n";
+        assert_eq!(
+            parse_outline(outline),
+            [
+                ScaffoldEntry::Folder(PathBuf::from("{{project}}")),
+                note("{{project}}/Overview.md"),
+                ScaffoldEntry::Folder(PathBuf::from("{{project}}/Meetings")),
+                note("{{project}}/Meetings/Kickoff.md"),
+                ScaffoldEntry::Folder(PathBuf::from("{{project}}/Research")),
+                note("{{project}}/Research/Sources.md"),
+                note("Inbox.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_create_from_template() {
+        let base = std::env::temp_dir().join(format!("ekphos-scaffold-{}", std::process::id()));
+        let template = base.join("template");
+        let notes = base.join("notes");
+        fs::create_dir_all(template.join("{{project}}/Meetings")).unwrap();
+        fs::write(
+            template.join("{{project}}/{{project}} Overview.md"),
+            "# {{project}}\n\nSee [[{{project}}/Meetings/Kickoff|kickoff]] and [[Elsewhere/Note]].\n",
+        )
+        .unwrap();
+        fs::write(template.join("{{project}}/Meetings/Kickoff.md"), "# Kickoff\n").unwrap();
+        fs::write(template.join("{{project}}/.hidden.md"), "").unwrap();
+        fs::create_dir_all(notes.join("work/Acme/Meetings")).unwrap();
+        fs::write(notes.join("work/Acme/Meetings/Kickoff.md"), "mine").unwrap();
+
+        let entries = read_template(&template).unwrap();
+        assert!(uses_project(&entries));
+        let report = create_scaffold(&entries, &notes.join("work"), "Acme", "work").unwrap();
+
+        assert_eq!(report.skipped, [notes.join("work/Acme/Meetings/Kickoff.md")]);
+        assert_eq!(fs::read_to_string(notes.join("work/Acme/Meetings/Kickoff.md")).unwrap(), "mine");
+        assert_eq!(report.created, [notes.join("work/Acme/Acme Overview.md")]);
+        assert!(!notes.join("work/Acme/.hidden.md").exists());
+        // Links between the new notes resolve from the notes directory
+        assert_eq!(
+            fs::read_to_string(notes.join("work/Acme/Acme Overview.md")).unwrap(),
+            "# Acme\n\nSee [[work/Acme/Meetings/Kickoff|kickoff]] and [[Elsewhere/Note]].\n"
+        );
+        fs::remove_dir_all(&base).unwrap();
+    }
+}
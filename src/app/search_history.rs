@@ -0,0 +1,146 @@
+//! Patterns searched for in a note, shared by the view mode search prompt,
+//! the editor's and vim's `/`. Kept for the session, and across restarts in
+//! `search_history.toml` with `remember_search_history = true`.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Most patterns kept; the oldest go first.
+const LIMIT: usize = 100;
+
+/// On-disk form, oldest first.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HistoryFile {
+    #[serde(default)]
+    patterns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchHistory {
+    /// Oldest first, without repeats
+    patterns: Vec<String>,
+    /// The pattern Up/Down is on, counted back from the newest
+    browsing: Option<usize>,
+    /// What was typed before Up was first pressed, given back past the newest
+    draft: String,
+}
+
+impl SearchHistory {
+    pub fn load(file: &Path) -> Self {
+        let patterns = fs::read_to_string(file)
+            .ok()
+            .and_then(|content| toml::from_str::<HistoryFile>(&content).ok())
+            .unwrap_or_default()
+            .patterns;
+        Self { patterns, ..Self::default() }
+    }
+
+    pub fn save(&self, file: &Path) -> std::io::Result<()> {
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let history = HistoryFile { patterns: self.patterns.clone() };
+        fs::write(file, toml::to_string_pretty(&history).unwrap_or_default())
+    }
+
+    /// Add a searched-for pattern as the newest, moving it if it was there.
+    pub fn push(&mut self, pattern: &str) {
+        self.browsing = None;
+        if pattern.is_empty() {
+            return;
+        }
+        self.patterns.retain(|p| p != pattern);
+        self.patterns.push(pattern.to_string());
+        if self.patterns.len() > LIMIT {
+            self.patterns.remove(0);
+        }
+    }
+
+    /// The newest pattern.
+    pub fn last(&self) -> Option<&str> {
+        self.patterns.last().map(String::as_str)
+    }
+
+    /// Up: the pattern before the one shown, starting from the newest, which
+    /// remembers `typed` for [`SearchHistory::newer`] to give back.
+    pub fn older(&mut self, typed: &str) -> Option<&str> {
+        let back = match self.browsing {
+            None => {
+                self.draft = typed.to_string();
+                0
+            }
+            Some(back) => back + 1,
+        };
+        let index = self.patterns.len().checked_sub(back + 1)?;
+        self.browsing = Some(back);
+        Some(&self.patterns[index])
+    }
+
+    /// Down: the pattern after the one shown, or what was typed once past
+    /// the newest. `None` when not going through the history.
+    pub fn newer(&mut self) -> Option<&str> {
+        match self.browsing? {
+            0 => {
+                self.browsing = None;
+                Some(&self.draft)
+            }
+            back => {
+                self.browsing = Some(back - 1);
+                Some(&self.patterns[self.patterns.len() - back])
+            }
+        }
+    }
+
+    /// Typing leaves the history: the next Up starts again from the newest.
+    pub fn stop_browsing(&mut self) {
+        self.browsing = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_support::TempDir;
+
+    #[test]
+    fn test_browse() {
+        let mut history = SearchHistory::default();
+        assert_eq!(history.older("typed"), None);
+        for pattern in ["alpha", "beta", "gamma", "beta"] {
+            history.push(pattern);
+        }
+        assert_eq!(history.last(), Some("beta"));
+
+        assert_eq!(history.newer(), None);
+        assert_eq!(history.older("bet"), Some("beta"));
+        assert_eq!(history.older("beta"), Some("gamma"));
+        assert_eq!(history.older("gamma"), Some("alpha"));
+        assert_eq!(history.older("alpha"), None);
+        assert_eq!(history.newer(), Some("gamma"));
+        assert_eq!(history.newer(), Some("beta"));
+        assert_eq!(history.newer(), Some("bet"));
+        assert_eq!(history.newer(), None);
+
+        history.older("");
+        history.stop_browsing();
+        assert_eq!(history.older(""), Some("beta"));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = TempDir::new("search-history");
+        let file = dir.join("cache/search_history.toml");
+
+        let mut history = SearchHistory::default();
+        for i in 0..=LIMIT {
+            history.push(&format!("pattern {}", i));
+        }
+        history.save(&file).unwrap();
+
+        let mut loaded = SearchHistory::load(&file);
+        assert_eq!(loaded.last(), Some(format!("pattern {}", LIMIT).as_str()));
+        assert_eq!(loaded.patterns.len(), LIMIT);
+        assert_eq!(loaded.older(""), Some(format!("pattern {}", LIMIT).as_str()));
+    }
+}
@@ -0,0 +1,264 @@
+//! Bulk task operations on the section under the cursor: check off every
+//! task, or clear out the checked ones, optionally into a `## Archive`
+//! section at the bottom of the note.
+
+use super::list_item::leading_columns;
+use crate::vim::command::TaskOp;
+
+/// Heading that cleared tasks are moved under when archiving is on.
+pub const ARCHIVE_HEADING: &str = "## Archive";
+
+/// The note's lines after a section task operation, held until the
+/// confirmation dialog is answered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionTaskEdit {
+    pub op: TaskOp,
+    /// Text of the section's heading, or None above the first heading.
+    pub heading: Option<String>,
+    /// How many task lines change or move.
+    pub affected: usize,
+    /// Whether cleared tasks go under [`ARCHIVE_HEADING`].
+    pub archived: bool,
+    pub lines: Vec<String>,
+}
+
+/// Plan `op` on the section containing `line`. Returns None when no task
+/// would be affected. Tasks in code fences are left alone.
+pub fn plan_section_tasks(lines: &[&str], line: usize, op: TaskOp, archive: bool) -> Option<SectionTaskEdit> {
+    let fenced = fenced_lines(lines);
+    let (heading, section) = section_range(lines, &fenced, line);
+    let heading_text = heading.map(|h| lines[h].trim_start_matches('#').trim().to_string());
+
+    let mut new_lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+    let mut affected = 0;
+    // Moving the archive's own tasks into itself would change nothing
+    let archived = archive && heading.is_none_or(|h| lines[h].trim() != ARCHIVE_HEADING);
+
+    match op {
+        TaskOp::Complete => {
+            for i in section {
+                if !fenced[i] && task_state(lines[i]) == Some(false) {
+                    new_lines[i] = lines[i].replacen("- [ ]", "- [x]", 1);
+                    affected += 1;
+                }
+            }
+        }
+        TaskOp::Clear => {
+            let mut removed = Vec::new();
+            let mut i = section.start;
+            while i < section.end {
+                // An archive nested in the section keeps its tasks
+                if archived && !fenced[i] && lines[i].trim() == ARCHIVE_HEADING {
+                    i = section_range(lines, &fenced, i).1.end;
+                    continue;
+                }
+                if fenced[i] || task_state(lines[i]) != Some(true) {
+                    i += 1;
+                    continue;
+                }
+                // The task with everything nested under it, up to a blank line
+                let indent = leading_columns(lines[i]);
+                let mut end = i + 1;
+                while end < section.end && !lines[end].trim().is_empty() && leading_columns(lines[end]) > indent {
+                    end += 1;
+                }
+                affected += (i..end).filter(|&j| !fenced[j] && task_state(lines[j]).is_some()).count();
+                removed.push((i..end, indent));
+                i = end;
+            }
+            if removed.is_empty() {
+                return None;
+            }
+
+            let mut moved = Vec::new();
+            for (range, indent) in removed.iter().rev() {
+                let block: Vec<String> = new_lines.drain(range.clone()).collect();
+                moved.splice(0..0, block.into_iter().map(|l| dedent(&l, *indent)));
+            }
+            if archived {
+                append_to_archive(&mut new_lines, moved);
+            }
+        }
+    }
+
+    (affected > 0).then_some(SectionTaskEdit {
+        op,
+        heading: heading_text,
+        affected,
+        archived,
+        lines: new_lines,
+    })
+}
+
+/// The heading above `line` and its rows, from the heading line through the
+/// end of its section, as `:narrow` shows them. None above the first heading.
+pub fn heading_section(lines: &[&str], line: usize) -> Option<(String, std::ops::Range<usize>)> {
+    let fenced = fenced_lines(lines);
+    let (heading, section) = section_range(lines, &fenced, line);
+    let heading = heading?;
+    Some((lines[heading].trim_start_matches('#').trim().to_string(), heading..section.end))
+}
+
+/// Whether `line` is an open (`false`) or checked (`true`) task.
+fn task_state(line: &str) -> Option<bool> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("- [ ] ") {
+        Some(false)
+    } else if trimmed.starts_with("- [x] ") || trimmed.starts_with("- [X] ") {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+fn heading_level(line: &str) -> Option<usize> {
+    let hashes = line.len() - line.trim_start_matches('#').len();
+    ((1..=6).contains(&hashes) && line[hashes..].starts_with(' ')).then_some(hashes)
+}
+
+/// Which lines are inside a code fence, fence lines included.
+fn fenced_lines(lines: &[&str]) -> Vec<bool> {
+    let mut in_fence = false;
+    lines
+        .iter()
+        .map(|line| {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+                return true;
+            }
+            in_fence
+        })
+        .collect()
+}
+
+/// The heading above `line` and the lines of its section below it, up to the
+/// next heading of the same or a higher level. Above the first heading, the
+/// section runs from the top of the note to that heading.
+fn section_range(lines: &[&str], fenced: &[bool], line: usize) -> (Option<usize>, std::ops::Range<usize>) {
+    let is_heading = |i: usize| if fenced[i] { None } else { heading_level(lines[i]) };
+    let line = line.min(lines.len().saturating_sub(1));
+
+    let heading = (0..=line).rev().find(|&i| i < lines.len() && is_heading(i).is_some());
+    let (start, level) = match heading {
+        Some(h) => (h + 1, is_heading(h).unwrap_or(1)),
+        None => (0, 6),
+    };
+    let end = (start..lines.len())
+        .find(|&i| is_heading(i).is_some_and(|l| l <= level))
+        .unwrap_or(lines.len());
+    (heading, start..end)
+}
+
+/// `line` with up to `columns` of its leading whitespace taken off, so a
+/// nested block keeps its shape when it moves to the top level.
+fn dedent(line: &str, columns: usize) -> String {
+    let mut taken = 0;
+    let rest = line.trim_start_matches(|c: char| {
+        let width = if c == '\t' { 4 } else { 1 };
+        if c.is_whitespace() && taken + width <= columns {
+            taken += width;
+            true
+        } else {
+            false
+        }
+    });
+    rest.to_string()
+}
+
+/// Add `moved` at the end of the archive section, creating it at the bottom
+/// of the note if there isn't one.
+fn append_to_archive(lines: &mut Vec<String>, moved: Vec<String>) {
+    let refs: Vec<&str> = lines.iter().map(String::as_str).collect();
+    let fenced = fenced_lines(&refs);
+    let archive = (0..refs.len()).find(|&i| !fenced[i] && refs[i].trim() == ARCHIVE_HEADING);
+
+    let Some(archive) = archive else {
+        while lines.last().is_some_and(|l| l.trim().is_empty()) {
+            lines.pop();
+        }
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(ARCHIVE_HEADING.to_string());
+        lines.push(String::new());
+        lines.extend(moved);
+        return;
+    };
+
+    let (_, section) = section_range(&refs, &fenced, archive);
+    // After the archive's last non-blank line, so its tasks stay one list
+    let at = (section.start..section.end)
+        .rev()
+        .find(|&i| !refs[i].trim().is_empty())
+        .map_or(section.start, |i| i + 1);
+    let needs_gap = at == archive + 1;
+    let at = if needs_gap {
+        lines.insert(at, String::new());
+        at + 1
+    } else {
+        at
+    };
+    lines.splice(at..at, moved);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(text: &str, line: usize, op: TaskOp, archive: bool) -> Option<SectionTaskEdit> {
+        let lines: Vec<&str> = text.lines().collect();
+        plan_section_tasks(&lines, line, op, archive)
+    }
+
+    #[test]
+    fn test_complete_only_touches_the_cursor_section() {
+        let text = "# Week\n- [ ] a\n## Mon\n- [ ] b\n- [x] c\n```\n- [ ] code\n```\n## Tue\n- [ ] d";
+        let edit = plan(text, 3, TaskOp::Complete, false).unwrap();
+        assert_eq!(edit.affected, 1);
+        assert_eq!(edit.heading.as_deref(), Some("Mon"));
+        assert_eq!(
+            edit.lines,
+            ["# Week", "- [ ] a", "## Mon", "- [x] b", "- [x] c", "```", "- [ ] code", "```", "## Tue", "- [ ] d"]
+        );
+
+        // A level-one section takes in its subsections
+        assert_eq!(plan(text, 0, TaskOp::Complete, false).unwrap().affected, 3);
+        assert_eq!(plan("- [x] done", 0, TaskOp::Complete, false), None);
+    }
+
+    #[test]
+    fn test_clear_moves_nested_blocks_whole() {
+        let text = "## Todo\n- [x] parent\n  - [ ] child\n  note\n- [ ] open\n  - [x] sub\n    - [ ] subsub\n\n## Later\n- [x] kept";
+        let edit = plan(text, 1, TaskOp::Clear, false).unwrap();
+        assert_eq!(edit.affected, 4);
+        assert_eq!(edit.lines, ["## Todo", "- [ ] open", "", "## Later", "- [x] kept"]);
+
+        let edit = plan(text, 1, TaskOp::Clear, true).unwrap();
+        assert_eq!(
+            edit.lines,
+            [
+                "## Todo", "- [ ] open", "", "## Later", "- [x] kept", "", "## Archive", "",
+                "- [x] parent", "  - [ ] child", "  note", "- [x] sub", "  - [ ] subsub",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clear_appends_to_an_existing_archive() {
+        let text = "## Todo\n- [x] new\n```\n- [x] code\n```\n## Archive\n\n- [x] old\n";
+        let edit = plan(text, 0, TaskOp::Clear, true).unwrap();
+        assert_eq!(edit.affected, 1);
+        assert_eq!(edit.lines, ["## Todo", "```", "- [x] code", "```", "## Archive", "", "- [x] old", "- [x] new"]);
+
+        // A level-one section leaves its archive subsection alone
+        let nested = "# Week\n- [x] new\n## Archive\n- [x] old";
+        let edit = plan(nested, 0, TaskOp::Clear, true).unwrap();
+        assert_eq!(edit.affected, 1);
+        assert_eq!(edit.lines, ["# Week", "## Archive", "- [x] old", "- [x] new"]);
+
+        // Clearing the archive itself deletes
+        let edit = plan(text, 7, TaskOp::Clear, true).unwrap();
+        assert!(!edit.archived);
+        assert_eq!(edit.lines, ["## Todo", "- [x] new", "```", "- [x] code", "```", "## Archive", ""]);
+    }
+}
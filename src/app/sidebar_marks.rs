@@ -0,0 +1,90 @@
+//! Notes and folders marked in the sidebar with Space, or a range of them
+//! with `V`, for `d` and `m` to delete or move together. Marks are kept by
+//! path, so they stay put when the sidebar is rebuilt.
+
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default)]
+pub struct SidebarMarks {
+    /// In the order they were marked
+    paths: Vec<PathBuf>,
+    /// The item last marked or unmarked with Space, where `V` marks from
+    anchor: Option<PathBuf>,
+}
+
+impl SidebarMarks {
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.paths.iter().any(|p| p == path)
+    }
+
+    pub fn anchor(&self) -> Option<&Path> {
+        self.anchor.as_deref()
+    }
+
+    pub fn toggle(&mut self, path: &Path) {
+        if self.contains(path) {
+            self.paths.retain(|p| p != path);
+        } else {
+            self.paths.push(path.to_path_buf());
+        }
+        self.anchor = Some(path.to_path_buf());
+    }
+
+    /// Mark without unmarking what's already marked, as a `V` range does.
+    pub fn mark(&mut self, path: &Path) {
+        if !self.contains(path) {
+            self.paths.push(path.to_path_buf());
+        }
+    }
+
+    pub fn set_anchor(&mut self, path: &Path) {
+        self.anchor = Some(path.to_path_buf());
+    }
+
+    pub fn clear(&mut self) {
+        self.paths.clear();
+        self.anchor = None;
+    }
+
+    /// The marked paths to act on: a marked folder takes everything in it,
+    /// so items inside one are left out rather than handled twice.
+    pub fn roots(&self) -> Vec<PathBuf> {
+        self.paths
+            .iter()
+            .filter(|path| !self.paths.iter().any(|other| other != *path && path.starts_with(other)))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marks_and_roots() {
+        let mut marks = SidebarMarks::default();
+        for path in ["/v/a.md", "/v/projects/plan.md", "/v/projects", "/v/projects-old"] {
+            marks.toggle(Path::new(path));
+        }
+        assert_eq!(marks.len(), 4);
+        assert_eq!(marks.anchor(), Some(Path::new("/v/projects-old")));
+        // The note in a marked folder goes with the folder
+        assert_eq!(marks.roots(), [Path::new("/v/a.md"), Path::new("/v/projects"), Path::new("/v/projects-old")]);
+
+        marks.toggle(Path::new("/v/a.md"));
+        marks.mark(Path::new("/v/projects"));
+        assert!(!marks.contains(Path::new("/v/a.md")));
+        assert_eq!(marks.len(), 3);
+        marks.clear();
+        assert!(marks.is_empty() && marks.anchor().is_none());
+    }
+}
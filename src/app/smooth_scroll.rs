@@ -0,0 +1,63 @@
+//! `reading.smooth_scroll`: a view mode jump of more than a screenful
+//! slides the content to where it lands over a few frames instead of
+//! cutting straight there.
+
+use std::time::{Duration, Instant};
+
+/// How long the slide takes
+const DURATION: Duration = Duration::from_millis(120);
+/// How often the main loop wakes to draw the next frame of a slide
+pub const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+/// A slide under way from the top item that was on screen at the jump.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SmoothScroll {
+    from: usize,
+    started: Instant,
+}
+
+impl SmoothScroll {
+    pub fn new(from: usize, started: Instant) -> Self {
+        Self { from, started }
+    }
+
+    pub fn is_over(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.started) >= DURATION
+    }
+
+    /// The top item to draw at `now` on the way to `target`, or `None` once
+    /// the slide is over or when `target` is within `screen` items, close
+    /// enough that it isn't worth one.
+    pub fn offset(&self, target: usize, screen: usize, now: Instant) -> Option<usize> {
+        let distance = target.abs_diff(self.from);
+        if distance <= screen || self.is_over(now) {
+            return None;
+        }
+        // Ease out: fast from the start, settling onto the target
+        let t = now.saturating_duration_since(self.started).as_secs_f64() / DURATION.as_secs_f64();
+        let moved = (distance as f64 * (1.0 - (1.0 - t).powi(3))).round() as usize;
+        Some(if target > self.from { self.from + moved } else { self.from - moved })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_eases_toward_the_target() {
+        let start = Instant::now();
+        let scroll = SmoothScroll::new(100, start);
+        let at = |ms| scroll.offset(400, 40, start + Duration::from_millis(ms));
+
+        assert_eq!(at(0), Some(100));
+        let (early, late) = (at(30).unwrap(), at(90).unwrap());
+        assert!(100 < early && early < late && late < 400);
+        // Further in the first half than the second
+        assert!(at(60).unwrap() - 100 > 400 - at(60).unwrap());
+        assert_eq!(at(120), None);
+
+        assert_eq!(SmoothScroll::new(400, start).offset(100, 40, start + Duration::from_millis(30)), Some(400 - (early - 100)));
+        assert_eq!(scroll.offset(130, 40, start), None);
+    }
+}
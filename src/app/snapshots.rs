@@ -0,0 +1,135 @@
+//! Earlier versions of notes, for vaults not kept in git. With
+//! `snapshots.keep` set, every save that changes a note first copies the
+//! file to `.snapshots/<path of the note>/<time>.md` in the notes folder.
+//! `:history` lists them; they follow the note when it's renamed or moved.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local, NaiveDateTime};
+
+pub const SNAPSHOT_DIR: &str = ".snapshots";
+
+/// How a snapshot's file is named, so the names sort by time.
+const TIME_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+/// Chars of a name taken by the time; a `-2` may follow for a second
+/// snapshot in the same second.
+const TIME_LEN: usize = 19;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub taken: NaiveDateTime,
+    /// Taken in the same second as others before it
+    order: u32,
+    pub bytes: u64,
+}
+
+impl Snapshot {
+    fn from_file(path: PathBuf) -> Option<Self> {
+        let stem = path.file_stem()?.to_str()?;
+        let (time, rest) = stem.split_at_checked(TIME_LEN)?;
+        let taken = NaiveDateTime::parse_from_str(time, TIME_FORMAT).ok()?;
+        let order = match rest {
+            "" => 0,
+            rest => rest.strip_prefix('-')?.parse().ok()?,
+        };
+        let bytes = fs::metadata(&path).ok()?.len();
+        Some(Self { path, taken, order, bytes })
+    }
+}
+
+/// The folder holding the snapshots of `note`, or None for a note outside
+/// the notes folder `root`.
+fn dir_for(root: &Path, note: &Path) -> Option<PathBuf> {
+    let relative = note.strip_prefix(root).ok()?;
+    Some(root.join(SNAPSHOT_DIR).join(relative))
+}
+
+/// The snapshots of `note`, newest first.
+pub fn list(root: &Path, note: &Path) -> Vec<Snapshot> {
+    let Some(entries) = dir_for(root, note).and_then(|dir| fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+    let mut snapshots: Vec<Snapshot> = entries
+        .flatten()
+        .filter_map(|entry| Snapshot::from_file(entry.path()))
+        .collect();
+    snapshots.sort_by_key(|s| std::cmp::Reverse((s.taken, s.order)));
+    snapshots
+}
+
+/// Copy `note`'s file as it is now into its snapshots, then drop all but the
+/// newest `keep` (or none, with `keep` 0). Nothing is taken of a note with
+/// no file yet or one outside `root`.
+pub fn take(root: &Path, note: &Path, now: DateTime<Local>, keep: usize) -> io::Result<Option<PathBuf>> {
+    let Some(dir) = dir_for(root, note).filter(|_| note.is_file()) else {
+        return Ok(None);
+    };
+    fs::create_dir_all(&dir)?;
+    let time = now.format(TIME_FORMAT).to_string();
+    let mut path = dir.join(format!("{}.md", time));
+    let mut order = 1;
+    while path.exists() {
+        order += 1;
+        path = dir.join(format!("{}-{}.md", time, order));
+    }
+    fs::copy(note, &path)?;
+
+    if keep > 0 {
+        for old in list(root, note).into_iter().skip(keep) {
+            fs::remove_file(old.path)?;
+        }
+    }
+    Ok(Some(path))
+}
+
+/// Move the snapshots of a note or folder renamed or moved from `from` to
+/// `to`, so the history follows it.
+pub fn moved(root: &Path, from: &Path, to: &Path) -> io::Result<()> {
+    let (Some(old_dir), Some(new_dir)) = (dir_for(root, from), dir_for(root, to)) else {
+        return Ok(());
+    };
+    if !old_dir.exists() {
+        return Ok(());
+    }
+    if let Some(parent) = new_dir.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(old_dir, new_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_support::TempDir;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_take_prune_and_move() {
+        let root = TempDir::new("snapshots");
+        let note = root.join("projects").join("Plan.md");
+        fs::create_dir_all(note.parent().unwrap()).unwrap();
+        let now = Local.with_ymd_and_hms(2024, 5, 1, 9, 30, 0).unwrap();
+
+        for (version, minutes) in [("one", 0), ("two", 1), ("three", 1), ("four", 2)] {
+            fs::write(&note, version).unwrap();
+            take(&root, &note, now + chrono::Duration::minutes(minutes), 3).unwrap();
+        }
+        let snapshots = list(&root, &note);
+        let kept: Vec<String> = snapshots.iter().map(|s| fs::read_to_string(&s.path).unwrap()).collect();
+        assert_eq!(kept, ["four", "three", "two"]);
+        assert!(snapshots[0].path.starts_with(root.join(".snapshots/projects/Plan.md")));
+        assert_eq!(snapshots[1].path.file_name().unwrap(), "2024-05-01_09-31-00-2.md");
+        assert_eq!(snapshots[0].bytes, 4);
+
+        // Moving the folder takes the note's history along
+        let moved_note = root.join("archive").join("Plan.md");
+        moved(&root, &root.join("projects"), &root.join("archive")).unwrap();
+        assert_eq!(list(&root, &moved_note).len(), 3);
+        assert!(list(&root, &note).is_empty());
+
+        assert_eq!(take(&root, &root.join("New.md"), now, 3).unwrap(), None);
+    }
+}
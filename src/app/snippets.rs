@@ -0,0 +1,64 @@
+//! Insert-mode snippets: a trigger like `;dt` followed by a space expands to
+//! its text from `[snippets]`, with `{{date}}`, `{{time}}` and
+//! `{{note_title}}` filled in.
+
+use std::fmt::Write;
+
+use chrono::{DateTime, Local};
+
+/// The snippet name typed before the space just left of `col`, and the
+/// column its prefix starts at. The trigger has to be a word of its own, at
+/// the start of the line or after whitespace.
+pub fn trigger_before(line: &str, col: usize, prefix: &str) -> Option<(usize, String)> {
+    let chars: Vec<char> = line.chars().collect();
+    if col == 0 || col > chars.len() || chars[col - 1] != ' ' {
+        return None;
+    }
+    let end = col - 1;
+    let start = chars[..end].iter().rposition(|c| c.is_whitespace()).map_or(0, |i| i + 1);
+    let word: String = chars[start..end].iter().collect();
+    let name = word.strip_prefix(prefix)?;
+    (!name.is_empty()).then(|| (start, name.to_string()))
+}
+
+/// `template` with its placeholders filled in. A `date_format` or
+/// `time_format` chrono can't use falls back to the default one.
+pub fn fill(template: &str, now: &DateTime<Local>, date_format: &str, time_format: &str, note_title: &str) -> String {
+    template
+        .replace("{{date}}", &format_time(now, date_format, "%Y-%m-%d"))
+        .replace("{{time}}", &format_time(now, time_format, "%H:%M"))
+        .replace("{{note_title}}", note_title)
+}
+
+fn format_time(now: &DateTime<Local>, format: &str, fallback: &str) -> String {
+    let mut out = String::new();
+    if write!(out, "{}", now.format(format)).is_err() {
+        out = now.format(fallback).to_string();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_trigger_before() {
+        assert_eq!(trigger_before(";dt ", 4, ";"), Some((0, "dt".to_string())));
+        assert_eq!(trigger_before("due ;dt ", 8, ";"), Some((4, "dt".to_string())));
+        assert_eq!(trigger_before("a;dt ", 5, ";"), None);
+        assert_eq!(trigger_before("; ", 2, ";"), None);
+        assert_eq!(trigger_before(";dt", 3, ";"), None);
+        assert_eq!(trigger_before(";dt x", 4, ";"), Some((0, "dt".to_string())));
+        assert_eq!(trigger_before("été ,,td ", 9, ",,"), Some((4, "td".to_string())));
+    }
+
+    #[test]
+    fn test_fill() {
+        let now = Local.with_ymd_and_hms(2026, 3, 7, 9, 5, 0).unwrap();
+        assert_eq!(fill("{{date}} {{time}}", &now, "%Y-%m-%d", "%H:%M", ""), "2026-03-07 09:05");
+        assert_eq!(fill("# {{note_title}} ({{date}})", &now, "%d/%m", "%H:%M", "Plan"), "# Plan (07/03)");
+        assert_eq!(fill("{{date}}", &now, "%Q", "%H:%M", ""), "2026-03-07");
+    }
+}
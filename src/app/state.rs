@@ -1,25 +1,85 @@
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{self, Receiver, Sender};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use image::DynamicImage;
 use ratatui::{
     layout::Rect,
-    style::Style,
+    style::{Modifier, Style},
     widgets::{Block, Borders, ListState},
 };
 use ratatui_image::{picker::Picker, protocol::StatefulProtocol};
 
-use crate::editor::{Editor, Position};
+use crate::editor::{Editor, HighlightRange, HighlightType, LineInput, Position};
 use crate::highlight::Highlighter;
 use crate::highlight_worker::{HighlightColors, HighlightResult, HighlightWorker};
-use crate::config::{Config, Theme, ThemeEntry, ThemeFile};
+use crate::config::{
+    Config, ConfigError, DisplayConfig, InlineImages, LineEnding, ReadingAlign, Theme, ThemeEntry, ThemeFile, VaultConfig,
+};
 use crate::search::{self, SearchIndex};
-use crate::vim::VimState;
+use crate::vim::{Region, VimState};
+use crate::vim::command::{ExportFormat, LineJump, SubstituteFlags, TaskOp};
+use crate::vim::substitute::find_matches;
+
+use super::callout::{self, CalloutKind};
+use super::capabilities::Capabilities;
+use super::clock::Clock;
+use super::leader::{LeaderMap, LeaderState};
+use super::content_motion::{self, ScreenLine};
+use super::folder_config::{self, FolderConfigLayer};
+use super::folder_view;
+use super::toc;
+use super::export::{self, PdfExport};
+use super::external_change::{external_change_action, ExternalChangeAction};
+use super::section_tasks::{heading_section, plan_section_tasks, SectionTaskEdit};
+use super::wiki_link::{self, split_link_alias, wiki_link_text, WikiLink};
+use super::global_substitute::{self, GlobalSubstitute};
+use super::bulk_rename::{self, BulkRename, RenameProblem};
+use super::heading_refactor::{self, LinkTarget};
+use super::note_merge::{self, NoteMerge};
+use super::line_diff;
+use super::pins::Pins;
+use super::sidebar_marks::SidebarMarks;
+use super::snapshots::{self, Snapshot};
+use super::image_info::{ImageInfo, ImageLoad};
+use super::image_size::{parse_image_line, ImageSize};
+use super::ipc::{self, IpcCommand, IpcResponse, IpcServer};
+use super::link_graph::{self, GraphFormat};
+use super::link_titles::{self, PastedLink};
+use super::line_ending::{with_newline, Newline};
+use super::code_fence;
+use super::live_outline::LiveOutline;
+use super::background_io::{self, FileOp, SaveJob, Saved, Scan, ScanError, ScanJob, SnapshotRequest};
+use super::markdown_lint::{self, Diagnostic, Severity};
+use super::prose_lint::ProseLinter;
+use super::delete_confirm::FolderContents;
+use super::encryption::{self, DecryptError, NoteLock, Passphrase, PassphraseCache};
+use super::link_copy::{self, CopiedLink};
+use super::link_targets;
+use super::note_stats::{analyze_note, NoteAnalysis};
+use super::opener::{self, PendingOpen};
+use super::path_input;
+use super::render_cache::RenderCache;
+use super::smooth_scroll::SmoothScroll;
+use super::large_paste::{self, PasteJob};
+use super::paths;
+use super::list_item;
+use super::list_style::{self, ListStyle};
+use super::scaffold::{self, ScaffoldEntry};
+use super::snippets;
+use super::quickfix::{QuickfixEntry, QuickfixJump, QuickfixList};
+use super::reading_positions::{ReadingPosition, ReadingPositions};
+use super::search_history::SearchHistory;
+use super::reference_links;
+use super::refile::{self, RefileKind, RefileTarget};
+
+/// Quiet period after the last edit before the gutter change markers are re-diffed.
+const CHANGE_MARKER_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(300);
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BlockInsertMode {
@@ -54,8 +114,78 @@ impl Toast {
     /// How long a toast stays on screen before auto-dismissing.
     const TTL: std::time::Duration = std::time::Duration::from_secs(4);
 
-    pub fn is_expired(&self) -> bool {
-        self.shown_at.elapsed() >= Self::TTL
+    pub fn is_expired(&self, now: std::time::Instant) -> bool {
+        now.duration_since(self.shown_at) >= Self::TTL
+    }
+}
+
+/// Lines of the viewed note that changed when it was reloaded from disk,
+/// tinted in the content view until the highlight fades out.
+#[derive(Debug, Clone)]
+pub struct ReloadHighlight {
+    pub note: usize,
+    pub lines: HashSet<usize>,
+    pub shown_at: std::time::Instant,
+}
+
+impl ReloadHighlight {
+    const TTL: std::time::Duration = std::time::Duration::from_secs(3);
+
+    pub fn is_expired(&self, now: std::time::Instant) -> bool {
+        now.duration_since(self.shown_at) >= Self::TTL
+    }
+
+    /// How far the highlight has faded, from 0.0 when shown to 1.0 when gone.
+    pub fn faded(&self, now: std::time::Instant) -> f32 {
+        (now.duration_since(self.shown_at).as_secs_f32() / Self::TTL.as_secs_f32()).min(1.0)
+    }
+}
+
+/// Timings for the debug overlay, F12 or `--debug`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameStats {
+    /// How long the last frame took to draw
+    pub last_frame: std::time::Duration,
+    /// Events read in the last batch before a frame
+    pub events_in_batch: usize,
+    pub frames: u64,
+}
+
+impl FrameStats {
+    pub fn drawn(&mut self, took: std::time::Duration) {
+        self.last_frame = took;
+        self.frames += 1;
+        log::debug!("frame {} drawn in {:.2?}", self.frames, took);
+    }
+}
+
+/// Side panels stepped aside because the terminal is narrower than
+/// `display.hide_sidebar_below`/`hide_outline_below`. Ctrl+B and Ctrl+O bring
+/// them back until the terminal is widened past the threshold again.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NarrowPanels {
+    pub sidebar_narrow: bool,
+    pub outline_narrow: bool,
+    pub sidebar_shown: bool,
+    pub outline_shown: bool,
+}
+
+impl NarrowPanels {
+    pub fn sidebar_hidden(&self) -> bool {
+        self.sidebar_narrow && !self.sidebar_shown
+    }
+
+    pub fn outline_hidden(&self) -> bool {
+        self.outline_narrow && !self.outline_shown
+    }
+
+    /// Whether the panel `focus` points at is hidden.
+    pub fn hides(&self, focus: Focus) -> bool {
+        match focus {
+            Focus::Sidebar => self.sidebar_hidden(),
+            Focus::Outline => self.outline_hidden(),
+            Focus::Content => false,
+        }
     }
 }
 
@@ -73,7 +203,7 @@ use super::welcome_notes::{GETTING_STARTED_CONTENT, DEMO_NOTE_CONTENT};
 /// Convert a heading into a link-fragment slug: lowercased, whitespace
 /// collapsed to dashes, punctuation stripped (GitHub-style). Matches the
 /// `[text](./file.md#sub-section1)` form used for jumping to headings.
-fn slugify_heading(s: &str) -> String {
+pub(super) fn slugify_heading(s: &str) -> String {
     let mut out = String::with_capacity(s.len());
     let mut last_dash = true;
     for ch in s.trim().chars() {
@@ -96,7 +226,7 @@ fn slugify_heading(s: &str) -> String {
 }
 
 /// Decode `%XX` escapes in a URL fragment, leaving other bytes intact.
-fn percent_decode(s: &str) -> String {
+pub(super) fn percent_decode(s: &str) -> String {
     let bytes = s.as_bytes();
     let mut out: Vec<u8> = Vec::with_capacity(bytes.len());
     let mut i = 0;
@@ -118,7 +248,7 @@ fn percent_decode(s: &str) -> String {
 
 /// If the line is a markdown ATX heading (`#` through `######`), return the
 /// heading text with any trailing `#`s stripped.
-fn heading_text(line: &str) -> Option<&str> {
+pub(super) fn heading_text(line: &str) -> Option<&str> {
     let trimmed = line.trim_start();
     let hash_count = trimmed.chars().take_while(|c| *c == '#').count();
     if hash_count == 0 || hash_count > 6 {
@@ -131,32 +261,192 @@ fn heading_text(line: &str) -> Option<&str> {
     Some(rest.trim_start().trim_end_matches(|c: char| c == '#' || c.is_whitespace()))
 }
 
+/// The terminal's image protocol. Tests have no terminal to ask.
+fn query_picker() -> Option<Picker> {
+    if cfg!(test) {
+        return None;
+    }
+    Picker::from_query_stdio().ok()
+}
+
 fn cache_dir() -> PathBuf {
-    dirs::cache_dir()
-        .unwrap_or_else(|| {
-            std::env::var("HOME")
-                .map(PathBuf::from)
-                .unwrap_or_else(|_| PathBuf::from("."))
-                .join(".cache")
+    Config::cache_dir()
+}
+
+fn last_note_path(notes_dir: &Path) -> PathBuf {
+    search::vault_cache_dir(notes_dir).join("last_note")
+}
+
+fn macros_path() -> PathBuf {
+    Config::config_dir().join("macros.toml")
+}
+
+fn prose_words_path() -> PathBuf {
+    Config::config_dir().join("prose_words.toml")
+}
+
+/// The socket `ipc = true` listens on and `ekphos --ipc-send` talks to.
+pub fn ipc_socket_path() -> PathBuf {
+    cache_dir().join("ipc.sock")
+}
+
+fn marks_path() -> PathBuf {
+    cache_dir().join("marks.toml")
+}
+
+fn pins_path() -> PathBuf {
+    cache_dir().join("pins.toml")
+}
+
+fn positions_path() -> PathBuf {
+    cache_dir().join("positions.toml")
+}
+
+fn search_history_path() -> PathBuf {
+    cache_dir().join("search_history.toml")
+}
+
+/// Columns a definition is indented by under its term.
+pub const DEFINITION_INDENT: usize = 4;
+
+/// Whether a text line can be the term of a definition that follows it.
+fn is_definition_term(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty()
+        && !trimmed.starts_with(['#', '>', '|', '<'])
+        && list_item::parse_list_marker(line).is_none()
+}
+
+/// Join each run of plain paragraph lines into one text line, flowed by the
+/// renderer, mapped to the run's first source line. Lines ending in a hard
+/// break (two spaces or `\\`) end their paragraph.
+fn join_paragraph_lines(items: &mut Vec<ContentItem>, source_lines: &mut Vec<usize>) {
+    let is_paragraph = |line: &str| {
+        let rule = line.trim().len() >= 3 && line.trim().chars().all(|c| matches!(c, '-' | '*' | '_' | '=' | ' '));
+        is_definition_term(line) && !rule
+    };
+    let mut joined: Vec<ContentItem> = Vec::with_capacity(items.len());
+    let mut joined_lines = Vec::with_capacity(source_lines.len());
+    let mut open = false;
+    for (item, line) in items.drain(..).zip(source_lines.drain(..)) {
+        let ContentItem::TextLine(text) = item else {
+            open = false;
+            joined.push(item);
+            joined_lines.push(line);
+            continue;
+        };
+        if !is_paragraph(&text) {
+            open = false;
+            joined.push(ContentItem::TextLine(text));
+            joined_lines.push(line);
+            continue;
+        }
+        let hard_break = text.ends_with("  ") || text.ends_with('\\');
+        match joined.last_mut() {
+            Some(ContentItem::TextLine(paragraph)) if open => {
+                paragraph.truncate(paragraph.trim_end().len());
+                paragraph.push(' ');
+                paragraph.push_str(text.trim());
+            }
+            _ => {
+                joined.push(ContentItem::TextLine(text));
+                joined_lines.push(line);
+            }
+        }
+        open = !hard_break;
+    }
+    *items = joined;
+    *source_lines = joined_lines;
+}
+
+/// `- [ ] text` or `- [x] text` on `line_index` as a task item, and its indent.
+fn parse_task_line(line: &str, line_index: usize) -> Option<(ContentItem, usize)> {
+    let trimmed = line.trim_start();
+    let checked = match trimmed.get(..6)? {
+        "- [ ] " => false,
+        "- [x] " | "- [X] " => true,
+        _ => return None,
+    };
+    let text = trimmed[6..].to_string();
+    let indent = line.chars().count() - trimmed.chars().count();
+    Some((ContentItem::TaskItem { text, checked, line_index, indent }, indent))
+}
+
+/// Drop blank text lines that directly follow another blank one. The first
+/// line of each run stays, with its source line, so lookups of any line in the
+/// run land on it.
+fn collapse_blank_runs(items: &mut Vec<ContentItem>, source_lines: &mut Vec<usize>) {
+    let is_blank = |item: &ContentItem| matches!(item, ContentItem::TextLine(line) if line.trim().is_empty());
+    let mut prev_blank = false;
+    let keep: Vec<bool> = items
+        .iter()
+        .map(|item| {
+            let blank = is_blank(item);
+            let keep = !(blank && prev_blank);
+            prev_blank = blank;
+            keep
         })
-        .join("ekphos")
+        .collect();
+
+    let mut flags = keep.iter();
+    items.retain(|_| *flags.next().unwrap_or(&true));
+    let mut flags = keep.iter();
+    source_lines.retain(|_| *flags.next().unwrap_or(&true));
+}
+
+/// Where a note named `name` goes, relative to the folder it's created in:
+/// `projects/2024/plan` is `plan.md` in `projects/2024`. Returns the path
+/// and the note's title, or why the name can't be used. Absolute paths and
+/// `..` are refused so a note can't end up outside that folder.
+fn nested_note_path(name: &str) -> Result<(PathBuf, String), String> {
+    if name.starts_with(['/', '\\']) || Path::new(name).has_root() {
+        return Err("Note name must be relative to the folder".to_string());
+    }
+    let mut segments: Vec<&str> =
+        name.split(['/', '\\']).map(str::trim).filter(|s| !s.is_empty() && *s != ".").collect();
+    if segments.contains(&"..") {
+        return Err("Note name can't contain '..'".to_string());
+    }
+    if name.trim_end().ends_with(['/', '\\']) {
+        return Err("Note name can't end with '/'".to_string());
+    }
+    let Some(title) = segments.pop() else {
+        return Err("Note name cannot be empty".to_string());
+    };
+    let mut path: PathBuf = segments.iter().collect();
+    path.push(format!("{}.md", title));
+    Ok((path, title.to_string()))
 }
 
-fn last_note_path() -> PathBuf {
-    cache_dir().join("last_note")
+/// Why `name` can't be used as a note's file name, if it can't.
+fn check_note_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Note name cannot be empty".to_string());
+    }
+    if name.chars().any(char::is_control) {
+        return Err("Note name can't contain control characters".to_string());
+    }
+    match name.chars().find(|c| "/\\:*?\"<>|#[]".contains(*c)) {
+        Some(c) => Err(format!("Note name can't contain '{}'", c)),
+        None => Ok(()),
+    }
 }
 
-fn read_last_opened_note() -> Option<PathBuf> {
-    std::fs::read_to_string(last_note_path())
+fn read_last_opened_note(notes_dir: &Path) -> Option<PathBuf> {
+    std::fs::read_to_string(last_note_path(notes_dir))
+        // Kept once for every vault before vaults
+        .or_else(|_| std::fs::read_to_string(cache_dir().join("last_note")))
         .ok()
         .map(|s| PathBuf::from(s.trim()))
         .filter(|p| p.exists())
 }
 
-fn save_last_opened_note(path: &PathBuf) {
-    let cache = cache_dir();
-    let _ = std::fs::create_dir_all(&cache);
-    let _ = std::fs::write(last_note_path(), path.to_string_lossy().as_bytes());
+fn save_last_opened_note(notes_dir: &Path, path: &Path) {
+    let file = last_note_path(notes_dir);
+    if let Some(parent) = file.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let _ = std::fs::write(file, path.to_string_lossy().as_bytes());
 }
 
 #[derive(Debug, Clone)]
@@ -168,6 +458,71 @@ pub struct Note {
     pub created_time: Option<std::time::SystemTime>,
     pub frontmatter: Option<super::frontmatter::Frontmatter>,
     pub content_start_line: usize,
+    /// Line ending of the file on disk; `content` always uses `\n`
+    pub newline: Newline,
+    /// Whether the file is encrypted; a locked note's content is empty
+    pub lock: NoteLock,
+}
+
+impl Note {
+    /// Load the note stored at `path`, which may be inside or outside the notes directory.
+    pub fn read(path: &Path) -> std::io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let newline = Newline::detect(&text);
+        let (content, lock) = if encryption::is_encrypted(&text) {
+            (String::new(), NoteLock::Locked)
+        } else {
+            (super::line_ending::normalize(text), NoteLock::Plain)
+        };
+        let title = path.file_stem()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let (modified_time, created_time) = fs::metadata(path)
+            .map(|m| (m.modified().ok(), m.created().ok()))
+            .unwrap_or((None, None));
+
+        // Parse frontmatter
+        let (frontmatter, content_start_line) = super::frontmatter::Frontmatter::parse(&content);
+
+        Ok(Self {
+            title,
+            content,
+            file_path: Some(path.to_path_buf()),
+            modified_time,
+            created_time,
+            frontmatter,
+            content_start_line,
+            newline,
+            lock,
+        })
+    }
+
+    /// Write the content back to the note's own `file_path`, with line
+    /// endings per `line_ending`. An unlocked note is encrypted again.
+    pub fn write(&mut self, line_ending: LineEnding) -> std::io::Result<()> {
+        let Some(path) = self.file_path.clone() else {
+            return Ok(());
+        };
+        let bytes = self.encode(line_ending)?;
+        fs::write(&path, bytes)?;
+        // Update modified time after save
+        self.modified_time = fs::metadata(&path).ok().and_then(|m| m.modified().ok());
+        Ok(())
+    }
+
+    /// The file's bytes for [`Note::write`].
+    fn encode(&mut self, line_ending: LineEnding) -> std::io::Result<Vec<u8>> {
+        self.newline = self.newline.for_saving(line_ending);
+        let text = with_newline(&self.content, self.newline);
+        match &self.lock {
+            NoteLock::Plain => Ok(text.into_owned().into_bytes()),
+            NoteLock::Locked => Err(std::io::Error::other("the note is locked")),
+            NoteLock::Unlocked(passphrase) => {
+                encryption::encrypt(&text, passphrase).map(String::into_bytes).map_err(std::io::Error::other)
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -176,6 +531,85 @@ pub enum Mode {
     Edit,
 }
 
+/// Shown in place of a locked note's content.
+const LOCKED_NOTE_PLACEHOLDER: &str = "> 🔒 This note is encrypted. Press `Enter` on it in the sidebar, or `e`, to unlock it.";
+
+/// Most view-mode file edits [`App::undo_view_edit`] can take back.
+const VIEW_UNDO_LIMIT: usize = 50;
+
+/// A note's content from before a view-mode edit wrote it to disk.
+#[derive(Debug, Clone)]
+pub struct ViewUndo {
+    pub path: PathBuf,
+    pub content: String,
+    /// Another note the same edit wrote, restored along with this one
+    pub paired: Option<Box<ViewUndo>>,
+}
+
+/// What the sidebar and content view had selected, see [`App::view_selection`].
+#[derive(Debug, Clone)]
+pub struct ViewSelection {
+    pub sidebar_index: usize,
+    pub folder_view: Option<PathBuf>,
+    pub content_cursor: usize,
+    pub content_scroll_offset: usize,
+}
+
+/// Action held back by the UnsavedChanges prompt for a kept buffer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AfterUnsavedPrompt {
+    Quit,
+    /// Select another note: its index and sidebar position.
+    SwitchNote { note: usize, sidebar_index: usize },
+    /// `:cd` to this notes folder
+    ChangeDir(PathBuf),
+}
+
+/// What's left to do once renames or deletes are made and the notes folder
+/// is loaded again, see [`App::run_file_ops`].
+#[derive(Debug, Clone)]
+pub enum FileOpsThen {
+    /// The selected note or folder was deleted
+    Deleted,
+    /// The marked items were deleted
+    DeletedMarked,
+    /// A note was renamed to this
+    RenamedNote(PathBuf),
+    /// A folder was renamed to this
+    RenamedFolder(PathBuf),
+    /// A `:bulk-rename`, and the note viewed when it was confirmed
+    BulkRenamed { bulk: BulkRename, current: Option<PathBuf> },
+}
+
+/// What the Passphrase dialog was opened for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PassphrasePurpose {
+    /// Unlock a note, then open it in the editor if `edit`
+    Unlock { edit: bool },
+    /// `:encrypt`: the new passphrase, then the same again
+    Encrypt,
+    ConfirmEncrypt,
+}
+
+/// The Passphrase dialog's note and step; what's typed is in `input_buffer`.
+#[derive(Debug, Clone)]
+pub struct PassphrasePrompt {
+    pub purpose: PassphrasePurpose,
+    pub note: usize,
+    /// The passphrase typed first, while it's being confirmed
+    pub first: Option<Passphrase>,
+}
+
+/// The Scaffold dialog: an outline is typed into `input_buffer`, then the
+/// project name if the entries use one.
+#[derive(Debug, Clone)]
+pub struct ScaffoldPrompt {
+    /// Where the folders and notes are created
+    pub folder: PathBuf,
+    /// Set once the outline is in, while the project name is asked for
+    pub entries: Option<Vec<ScaffoldEntry>>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DialogState {
     None,
@@ -190,10 +624,54 @@ pub enum DialogState {
     Help,
     EmptyDirectory,
     DirectoryNotFound,
+    /// A notes folder typed in, from `:cd` or in place of a missing one
+    ChangeNotesDir,
+    /// Create the missing folder `:cd` was given
+    CreateNotesDirConfirm,
     UnsavedChanges,
     CreateWikiNote,
+    ConfirmOpen,
     GraphView,
     ThemeSelector,
+    MacroList,
+    VaultPicker,
+    GlobalSubstituteConfirm,
+    /// The old and new names of a `:bulk-rename`, see [`BulkRename`]
+    BulkRenameConfirm,
+    /// Update the other notes' links to a renamed heading, see [`HeadingRename`]
+    HeadingRenameConfirm,
+    /// Merge another note into the current one, see [`NoteMerge`]
+    MergeConfirm,
+    /// The viewed note changed on disk: reload it or keep what's on screen
+    ExternalChange,
+    /// Confirm a bulk task edit on the cursor's section
+    SectionTasksConfirm,
+    /// Ask for the passphrase of an encrypted note, see [`PassphrasePrompt`]
+    Passphrase,
+    NoteStats,
+    Scaffold,
+    /// `:snippets`: the snippets insert mode expands
+    Snippets,
+    /// `:config`: where the settings for the current note come from
+    ConfigSources,
+    /// `:history`: the current note's snapshots, see [`HistoryList`]
+    History,
+    /// Confirm deleting the items marked in the sidebar
+    DeleteMarkedConfirm,
+    /// Pick the folder to move sidebar items to, see [`MovePicker`]
+    MovePicker,
+    /// Pick where a refiled list item goes in its note, see [`RefilePicker`]
+    RefilePicker,
+    /// `:prose`: the buffer's prose hints, see [`ProseReport`]
+    ProseReport,
+    /// Paste something past the large paste limits, see [`App::large_paste`]
+    LargePaste,
+    /// The notes the last scan couldn't read, see [`App::scan_errors`]
+    ScanErrors,
+    /// `:yanks`: the editor's yank ring, see [`YankList`]
+    YankList,
+    /// config.toml didn't parse at startup, see [`App::config_error`]
+    ConfigError,
 }
 
 /// State for the theme selector modal (opened with Ctrl+T). Live-previews the
@@ -208,6 +686,156 @@ pub struct ThemePicker {
     pub original_theme_name: String,
 }
 
+/// State for the `:macros` list. `renaming` is set after `r`, while waiting
+/// for the register to move the selected macro to.
+#[derive(Debug, Clone, Default)]
+pub struct MacroList {
+    pub selected: usize,
+    pub scroll_offset: usize,
+    pub renaming: bool,
+}
+
+/// State for the `:yanks` list of recent yanks and deletes, newest first.
+#[derive(Debug, Clone, Default)]
+pub struct YankList {
+    pub selected: usize,
+    pub scroll_offset: usize,
+}
+
+/// State for the `:history` list of the current note's snapshots, newest
+/// first. `viewing` holds the text of the one opened with Enter and how far
+/// it's scrolled.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryList {
+    pub entries: Vec<Snapshot>,
+    pub selected: usize,
+    pub scroll_offset: usize,
+    pub viewing: Option<(String, usize)>,
+}
+
+/// State for the `:prose` report: the prose lint's hints for the buffer,
+/// in row order.
+#[derive(Debug, Clone, Default)]
+pub struct ProseReport {
+    pub hints: Vec<Diagnostic>,
+    pub selected: usize,
+    pub scroll_offset: usize,
+}
+
+/// State for the folder picker `m` opens in the sidebar, to move the marked
+/// notes and folders (or the selected one) into.
+#[derive(Debug, Clone, Default)]
+pub struct MovePicker {
+    /// What's being moved
+    pub items: Vec<PathBuf>,
+    /// The notes folder, then every folder in it the items can go to
+    pub folders: Vec<PathBuf>,
+    pub selected: usize,
+    pub scroll_offset: usize,
+}
+
+/// State for choosing where in the picked note `:refile` or `gr` puts the
+/// list item under the cursor.
+#[derive(Debug, Clone, Default)]
+pub struct RefilePicker {
+    pub kind: RefileKind,
+    /// The note the item comes from and its row there
+    pub source: Option<(usize, usize)>,
+    pub target: usize,
+    /// The target's headings, by row
+    pub headings: Vec<(usize, String)>,
+    /// Narrows the headings, or names a new one
+    pub query: LineInput,
+    pub selected: usize,
+    pub scroll_offset: usize,
+}
+
+impl RefilePicker {
+    /// The places the query leaves: the end of the note, the headings it
+    /// matches and, when it names none of them, a new heading.
+    pub fn choices(&self) -> Vec<(RefileTarget, String)> {
+        let query = self.query.trim().to_lowercase();
+        let title = |heading: &str| heading.trim_start_matches('#').trim().to_string();
+        let mut choices = vec![(RefileTarget::End, "End of note".to_string())];
+        choices.extend(
+            self.headings
+                .iter()
+                .filter(|(_, heading)| title(heading).to_lowercase().contains(&query))
+                .map(|(row, heading)| (RefileTarget::Heading(*row), heading.clone())),
+        );
+        if !query.is_empty() && !self.headings.iter().any(|(_, heading)| title(heading).to_lowercase() == query) {
+            choices.push((RefileTarget::NewHeading(self.query.trim().to_string()), format!("New: ## {}", self.query.trim())));
+        }
+        choices
+    }
+}
+
+/// State for the vault picker, opened with `:vault` and at startup when
+/// there's more than one `[[vaults]]` entry.
+#[derive(Debug, Clone, Default)]
+pub struct VaultPicker {
+    pub selected: usize,
+    pub scroll_offset: usize,
+    /// Opened at startup, before any notes were loaded
+    pub at_startup: bool,
+}
+
+/// The sidebar's filter for recently modified notes, cycled with `f`.
+/// Folders without a matching note are left out.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RecentFilter {
+    #[default]
+    All,
+    Today,
+    /// Since Monday
+    ThisWeek,
+}
+
+impl RecentFilter {
+    pub fn next(self) -> Self {
+        match self {
+            RecentFilter::All => RecentFilter::Today,
+            RecentFilter::Today => RecentFilter::ThisWeek,
+            RecentFilter::ThisWeek => RecentFilter::All,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RecentFilter::All => "all",
+            RecentFilter::Today => "today",
+            RecentFilter::ThisWeek => "this week",
+        }
+    }
+
+    /// The earliest modification time the filter lets through at `now`,
+    /// None when it shows every note.
+    pub fn since(self, now: chrono::DateTime<chrono::Local>) -> Option<std::time::SystemTime> {
+        use chrono::{Datelike, TimeZone};
+        let today = now.date_naive();
+        let start = match self {
+            RecentFilter::All => return None,
+            RecentFilter::Today => today,
+            RecentFilter::ThisWeek => today - chrono::Days::new(today.weekday().num_days_from_monday().into()),
+        };
+        let midnight = start.and_time(chrono::NaiveTime::MIN);
+        Some(match chrono::Local.from_local_datetime(&midnight).earliest() {
+            Some(local) => local.into(),
+            // No midnight that day where clocks skip it
+            None => midnight.and_utc().into(),
+        })
+    }
+}
+
+/// An inline rename of the open note, typed into the title header of the
+/// content view after `r`.
+#[derive(Debug, Clone, Default)]
+pub struct TitleEdit {
+    pub input: LineInput,
+    /// Why the last Enter didn't rename the note
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum SortMode {
     #[default]
@@ -258,6 +886,73 @@ pub struct GraphViewState {
     pub view_width: f32,
     pub view_height: f32,
     pub needs_center: bool,
+    /// Dim the nodes and edges not linked to the selected node
+    pub focus_neighborhood: bool,
+    /// j/k/Enter work on the selected node's link list instead of the graph
+    pub links_focused: bool,
+    /// Selected entry of [`GraphViewState::selected_links`]
+    pub link_index: usize,
+    /// `/` node search, while it's open
+    pub search: Option<GraphSearch>,
+    /// Node just picked from the search, drawn out until it fades
+    pub flash: Option<NodeFlash>,
+}
+
+/// The graph's `/` search: nodes whose note titles fuzzy match the query,
+/// best first, as indices into [`GraphViewState::nodes`].
+#[derive(Debug, Clone, Default)]
+pub struct GraphSearch {
+    pub query: String,
+    pub matches: Vec<usize>,
+    pub selected: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NodeFlash {
+    pub node: usize,
+    pub shown_at: std::time::Instant,
+}
+
+impl NodeFlash {
+    const TTL: std::time::Duration = std::time::Duration::from_millis(1500);
+
+    pub fn is_expired(&self, now: std::time::Instant) -> bool {
+        now.duration_since(self.shown_at) >= Self::TTL
+    }
+}
+
+impl GraphViewState {
+    /// The selected node's links for the side strip: the notes it links to,
+    /// then the notes linking to it, each by title. A two-way link is in both.
+    pub fn selected_links(&self) -> Vec<GraphLink> {
+        let Some(selected) = self.selected_node else {
+            return Vec::new();
+        };
+        let mut outgoing = Vec::new();
+        let mut incoming = Vec::new();
+        for edge in &self.edges {
+            let (other, forward) = if edge.from == selected {
+                (edge.to, true)
+            } else if edge.to == selected {
+                (edge.from, false)
+            } else {
+                continue;
+            };
+            if forward || edge.bidirectional {
+                outgoing.push(GraphLink { node: other, outgoing: true });
+            }
+            if !forward || edge.bidirectional {
+                incoming.push(GraphLink { node: other, outgoing: false });
+            }
+        }
+        let title = |link: &GraphLink| {
+            self.nodes.get(link.node).map(|n| n.title.to_lowercase()).unwrap_or_default()
+        };
+        outgoing.sort_by_cached_key(title);
+        incoming.sort_by_cached_key(title);
+        outgoing.extend(incoming);
+        outgoing
+    }
 }
 
 impl Default for GraphViewState {
@@ -276,6 +971,11 @@ impl Default for GraphViewState {
             view_width: 100.0,
             view_height: 50.0,
             needs_center: false,
+            focus_neighborhood: true,
+            links_focused: false,
+            link_index: 0,
+            search: None,
+            flash: None,
         }
     }
 }
@@ -299,6 +999,14 @@ pub struct GraphEdge {
     pub bidirectional: bool,
 }
 
+/// An entry in the graph view's link strip.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GraphLink {
+    pub node: usize,
+    /// The selected node links to `node`, rather than `node` to it
+    pub outgoing: bool,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Focus {
     Sidebar,
@@ -306,18 +1014,118 @@ pub enum Focus {
     Outline,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct OutlineItem {
     pub level: usize,
     pub title: String,
     pub line: usize,
+    pub kind: OutlineKind,
 }
 
-pub struct ImageState {
-    pub image: StatefulProtocol,
-    pub path: String,
+/// What an outline entry points at. Everything but headings is optional, see
+/// the `outline_*` display settings, and sits one level below its heading.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutlineKind {
+    Heading,
+    /// A top-level list item or task
+    ListItem,
+    /// A fenced code block, titled by its language and first line
+    CodeBlock,
+    /// A line that is bold text only, used as a heading
+    BoldLine,
+}
+
+/// Characters of a list item or code line kept as its outline title.
+const OUTLINE_TITLE_CHARS: usize = 40;
+
+fn outline_title(text: &str) -> String {
+    let text = text.trim();
+    if text.chars().count() > OUTLINE_TITLE_CHARS {
+        let mut title: String = text.chars().take(OUTLINE_TITLE_CHARS - 1).collect();
+        title.push('…');
+        title
+    } else {
+        text.to_string()
+    }
+}
+
+/// The outline of a note's content items. Code fences are tracked the way
+/// the content view parsed them, so nothing inside a block is picked up.
+pub(super) fn outline_items(items: &[ContentItem], display: &DisplayConfig) -> Vec<OutlineItem> {
+    let mut outline = Vec::new();
+    let mut heading_level = 0;
+    let mut in_code_block = false;
+    let mut in_toc = false;
+    let mut push = |level, title, line, kind| outline.push(OutlineItem { level, title, line, kind });
+
+    for (idx, item) in items.iter().enumerate() {
+        // A `:toc` block only repeats the headings
+        match item {
+            ContentItem::TextLine(line) if line.trim() == toc::START => in_toc = true,
+            ContentItem::TextLine(line) if line.trim() == toc::END => in_toc = false,
+            _ => {}
+        }
+        if in_toc {
+            continue;
+        }
+        let below_heading = heading_level + 1;
+        match item {
+            ContentItem::CodeFence(lang) => {
+                in_code_block = !in_code_block;
+                if in_code_block && display.outline_code_blocks {
+                    let first_line = match items.get(idx + 1) {
+                        Some(ContentItem::CodeLine(code)) => code.trim(),
+                        _ => "",
+                    };
+                    let title = match (lang.trim(), first_line) {
+                        ("", code) => code.to_string(),
+                        (lang, "") => lang.to_string(),
+                        (lang, code) => format!("{}: {}", lang, code),
+                    };
+                    push(below_heading, outline_title(&title), idx, OutlineKind::CodeBlock);
+                }
+            }
+            ContentItem::TextLine(line) => {
+                let heading = [("# ", 1), ("## ", 2), ("### ", 3)]
+                    .into_iter()
+                    .find(|(prefix, _)| line.starts_with(prefix));
+                if let Some((prefix, level)) = heading {
+                    heading_level = level;
+                    push(level, line.trim_start_matches(prefix).to_string(), idx, OutlineKind::Heading);
+                } else if let Some(marker) = list_item::parse_list_marker(line) {
+                    if display.outline_list_items && marker.indent == 0 {
+                        push(below_heading, outline_title(marker.text), idx, OutlineKind::ListItem);
+                    }
+                } else if display.outline_bold_lines {
+                    let bold = line.trim().strip_prefix("**").and_then(|l| l.strip_suffix("**"));
+                    if let Some(text) = bold.filter(|t| !t.trim().is_empty() && !t.contains("**")) {
+                        push(below_heading, text.trim().to_string(), idx, OutlineKind::BoldLine);
+                    }
+                }
+            }
+            ContentItem::TaskItem { text, checked, indent: 0, .. } if display.outline_list_items => {
+                let title = format!("[{}] {}", if *checked { "x" } else { " " }, text);
+                push(below_heading, outline_title(&title), idx, OutlineKind::ListItem);
+            }
+            _ => {}
+        }
+    }
+    outline
+}
+
+/// A `:rename-heading` waiting to update the links in other notes.
+#[derive(Debug, Clone)]
+pub struct HeadingRename {
+    pub note: usize,
+    pub old_slug: String,
+    pub new_title: String,
+    pub preview: global_substitute::VaultSubstitution,
 }
 
+/// An image drawn in the content view, by the source line it's on and its
+/// target, so rebuilding the content items doesn't load it again.
+pub type ImageKey = (usize, String);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Alignment {
     Left,
@@ -342,15 +1150,27 @@ impl Alignment {
 #[derive(Debug, Clone)]
 pub enum ContentItem {
     TextLine(String),
-    Image(String),
+    /// An image on a line of its own, with the size asked for in the markdown
+    Image { path: String, size: ImageSize },
     CodeLine(String),
     CodeFence(String),
     TaskItem { text: String, checked: bool, line_index: usize, indent: usize },
     TableRow { cells: Vec<String>, is_separator: bool, is_header: bool, column_widths: Vec<usize>, alignments: Vec<Alignment> },
     Details { summary: String, content_lines: Vec<String>, id: usize },
+    /// A `> [!NOTE]` quote; `fold` is set for one that folds, to whether it
+    /// starts open. Open states are kept with the details blocks' by `id`.
+    Callout { kind: CalloutKind, title: String, lines: Vec<String>, fold: Option<bool>, id: usize },
     FrontmatterLine { key: String, value: String },
     FrontmatterDelimiter,
     TagBadges { tags: Vec<String>, date: Option<String> },
+    /// A child of the folder shown in the content view: a subfolder or a note.
+    FolderEntry { name: String, detail: String, path: PathBuf, is_folder: bool },
+    /// Indented line continuing the list item or definition above it, drawn
+    /// at that item's text column.
+    ListContinuation { text: String, indent: usize },
+    /// Term line of a definition list, followed by its `: definition` lines.
+    DefinitionTerm(String),
+    Definition(String),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -375,6 +1195,7 @@ pub enum ContextMenuState {
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ContextMenuItem {
     Copy,
+    CopyHtml,
     Cut,
     Paste,
     SelectAll,
@@ -384,6 +1205,7 @@ impl ContextMenuItem {
     pub fn all() -> &'static [ContextMenuItem] {
         &[
             ContextMenuItem::Copy,
+            ContextMenuItem::CopyHtml,
             ContextMenuItem::Cut,
             ContextMenuItem::Paste,
             ContextMenuItem::SelectAll,
@@ -393,6 +1215,7 @@ impl ContextMenuItem {
     pub fn label(&self) -> &'static str {
         match self {
             ContextMenuItem::Copy => "Copy",
+            ContextMenuItem::CopyHtml => "Copy as HTML",
             ContextMenuItem::Cut => "Cut",
             ContextMenuItem::Paste => "Paste",
             ContextMenuItem::SelectAll => "Select All",
@@ -423,26 +1246,68 @@ pub enum WikiAutocompleteState {
     },
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct BufferSearchMatch {
-    pub row: usize,
-    pub start_col: usize,
-    pub end_col: usize,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Default)]
-pub enum SearchDirection {
+/// Insert-mode `Ctrl-N`/`Ctrl-P` word completion.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum WordCompletionState {
     #[default]
-    Forward,
-    Backward,
+    None,
+    Open {
+        /// Column where the word being completed starts
+        start_col: usize,
+        /// What was typed before completion started
+        prefix: String,
+        candidates: Vec<String>,
+        /// `None` while the original prefix is shown
+        selected: Option<usize>,
+    },
 }
 
+/// Markdown link target completion, opened by typing `](` in insert mode.
 #[derive(Debug, Clone, PartialEq, Default)]
-pub struct BufferSearchState {
-    pub active: bool,
-    pub query: String,
-    pub matches: Vec<BufferSearchMatch>,
-    pub current_match_index: usize,
+pub enum LinkCompletionState {
+    #[default]
+    None,
+    Open {
+        /// Column just after the `(`
+        start_col: usize,
+        query: String,
+        /// Every vault file, gathered once when the popup opens
+        candidates: Vec<WikiSuggestion>,
+        /// `candidates` matching `query`, best first
+        suggestions: Vec<WikiSuggestion>,
+        selected_index: usize,
+    },
+}
+
+/// The read-only source view of a note, shown in place of the rendered
+/// content. `cursor` and `scroll` are source lines.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SourceView {
+    pub note: usize,
+    pub cursor: usize,
+    pub scroll: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BufferSearchMatch {
+    pub row: usize,
+    pub start_col: usize,
+    pub end_col: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SearchDirection {
+    #[default]
+    Forward,
+    Backward,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BufferSearchState {
+    pub active: bool,
+    pub query: LineInput,
+    pub matches: Vec<BufferSearchMatch>,
+    pub current_match_index: usize,
     pub case_sensitive: bool,
     pub direction: SearchDirection,
 }
@@ -491,13 +1356,28 @@ pub enum SearchPickerMode {
     Files,
     Content,
 }
+/// What picking a note in the search picker does.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PickerPurpose {
+    /// Open the note
+    #[default]
+    Open,
+    /// Insert a wiki link to it, or copy one outside edit mode
+    InsertLink,
+    /// Refile the list item under the cursor into it
+    Refile,
+    /// Merge it into the current note
+    Merge,
+}
+
 #[derive(Debug, Clone, PartialEq, Default)]
 pub enum SearchPickerState {
     #[default]
     Closed,
     Open {
         mode: SearchPickerMode,
-        query: String,
+        purpose: PickerPurpose,
+        query: LineInput,
         file_results: Vec<FilePickerResult>,
         content_results: Vec<ContentSearchResult>,
         selected_index: usize,
@@ -594,11 +1474,9 @@ pub enum FileTreeItem {
         path: PathBuf,
         expanded: bool,
         children: Vec<FileTreeItem>,
-        depth: usize,
     },
     Note {
         note_index: usize,
-        depth: usize,
     },
 }
 
@@ -613,6 +1491,8 @@ pub struct SidebarItem {
 pub enum SidebarItemKind {
     Folder { path: PathBuf, expanded: bool },
     Note { note_index: usize },
+    /// A section title, like Pinned; never selected
+    Header,
 }
 
 #[derive(Debug, Clone)]
@@ -630,14 +1510,27 @@ pub struct App {
     pub mode: Mode,
     pub editor: Editor,
     pub picker: Option<Picker>,
+    /// What the terminal can deliver, for key and mouse hints
+    pub capabilities: Capabilities,
     pub image_cache_dir: PathBuf,
-    pub current_image: Option<ImageState>,
+    pub image_states: HashMap<ImageKey, StatefulProtocol>,
     pub pending_images: HashSet<String>,
-    pub image_sender: Sender<(String, DynamicImage)>,
-    pub image_receiver: Receiver<(String, DynamicImage)>,
+    /// Pixel sizes of the images the content view has drawn, by their target
+    image_dimensions: HashMap<String, Option<(u32, u32)>>,
+    /// What the placeholder line above each image says, by its target
+    image_info: HashMap<String, ImageInfo>,
+    pub image_sender: Sender<(String, FetchedImage)>,
+    pub image_receiver: Receiver<(String, FetchedImage)>,
+    /// Pasted URLs whose page titles are being fetched
+    link_titles_pending: usize,
+    link_title_sender: Sender<(PastedLink, Option<String>)>,
+    link_title_receiver: Receiver<(PastedLink, Option<String>)>,
     pub show_welcome: bool,
     pub outline: Vec<OutlineItem>,
     pub outline_state: ListState,
+    /// Set while editing, when the outline follows the buffer and its
+    /// entries point at buffer rows
+    live_outline: Option<LiveOutline>,
     pub vim_mode: VimMode,
     pub vim: VimState,
     pub visual_line_anchor: Option<usize>,
@@ -646,24 +1539,111 @@ pub struct App {
     pub block_insert_state: Option<BlockInsertState>,
     pub content_cursor: usize,
     pub content_scroll_offset: usize,
+    /// Where each note was left in the content view
+    pub reading_positions: ReadingPositions,
+    pub search_history: SearchHistory,
+    /// The note the content view was last built for
+    reading_note: Option<PathBuf>,
+    /// Where a jump into the note (a wiki link, search result or mark) put
+    /// the view. Left there, it isn't remembered over the note's own place.
+    reading_jump: Option<ReadingPosition>,
     pub floating_cursor_mode: bool,
     pub content_items: Vec<ContentItem>,
     pub content_item_source_lines: Vec<usize>,
     pub theme: Theme,
+    /// The settings in effect: `config.toml` with the current note's
+    /// folder settings over it
     pub config: Config,
+    /// `config.toml` alone, which is what gets saved
+    base_config: Config,
+    /// The `.ekphos.toml` files applied to the current note, farthest first
+    pub folder_config: Vec<FolderConfigLayer>,
     pub dialog: DialogState,
-    pub input_buffer: String,
+    pub input_buffer: LineInput,
     pub search_active: bool,
-    pub search_query: String,
+    pub search_query: LineInput,
     pub filtered_indices: Vec<usize>,
     pub editor_scroll_top: usize,
     pub editor_view_height: usize,
-    pub pending_operator: Option<char>,
     pub pending_delete: Option<DeleteType>,
+    /// `:s///c` prompt in progress; keys answer it until every match is decided.
+    pub confirm_substitute: Option<crate::vim::ConfirmSubstitute>,
+    /// Note whose edits were kept in `editor` when leaving edit mode with
+    /// `esc_behavior = "keep"`. Only set in view mode.
+    pub dirty_note: Option<usize>,
+    /// What to do once the UnsavedChanges prompt for `dirty_note` is answered.
+    pub after_unsaved_prompt: Option<AfterUnsavedPrompt>,
+    /// Locations from the last search or check, stepped with `:cnext`/`:cprev`.
+    pub quickfix: QuickfixList,
+    /// Whether the quickfix window is shown below the content.
+    pub quickfix_open: bool,
+    /// `:gsub` waiting on its confirmation dialog.
+    pub pending_global_substitute: Option<GlobalSubstitute>,
+    /// `:bulk-rename` waiting on its preview dialog.
+    pub pending_bulk_rename: Option<BulkRename>,
+    /// `:export pdf` converter still running.
+    pub pdf_export: Option<PdfExport>,
+    /// Scan of the notes folder running with `background_io`
+    pub scan_job: Option<ScanJob>,
+    /// Notes still being written with `background_io`, oldest first
+    pub save_jobs: Vec<SaveJob>,
+    /// Version of a note on disk that the ExternalChange prompt kept the
+    /// screen's version over, by modification time, so it isn't asked again.
+    pub kept_external_version: Option<(PathBuf, Option<std::time::SystemTime>)>,
+    /// Section task edit waiting on the SectionTasksConfirm dialog.
+    pub pending_section_tasks: Option<SectionTaskEdit>,
+    pub passphrase_prompt: Option<PassphrasePrompt>,
+    /// What the `:stats` dialog shows
+    pub note_stats: Option<NoteAnalysis>,
+    pub pending_heading_rename: Option<HeadingRename>,
+    pub pending_merge: Option<NoteMerge>,
+    /// A view mode jump sliding into place, see `reading.smooth_scroll`
+    pub smooth_scroll: Option<SmoothScroll>,
+    /// View mode items as last drawn, see [`RenderCache`]
+    pub render_cache: RenderCache,
+    /// The notes and folders the last scan of the notes folder couldn't read
+    pub scan_errors: Vec<ScanError>,
+    pub scan_errors_scroll: usize,
+    /// A paste LargePaste asks about before it goes in
+    pub large_paste: Option<String>,
+    /// A large paste going into the editor a chunk per turn of the main loop
+    pub paste_job: Option<PasteJob>,
+    /// The missing folder CreateNotesDirConfirm asks about
+    pub pending_notes_dir: Option<PathBuf>,
+    pub scaffold_prompt: Option<ScaffoldPrompt>,
+    /// What the DeleteFolderConfirm dialog's folder holds
+    pub delete_folder_contents: Option<FolderContents>,
+    /// Notes and folders marked with Space in the sidebar
+    pub sidebar_marks: SidebarMarks,
+    /// What the DeleteMarkedConfirm dialog deletes, with what each folder holds
+    pub delete_marked: Vec<(PathBuf, Option<FolderContents>)>,
+    pub move_picker: MovePicker,
+    pub refile_picker: RefilePicker,
+    pub prose_report: ProseReport,
+    /// The heading `:narrow` has the editor showing only the section of
+    pub narrowed_heading: Option<String>,
+    /// F12 shows what the last frame took and the editor's state
+    pub debug_overlay: bool,
+    pub frame_stats: FrameStats,
+    /// Built on first use, see [`App::prose_hints`]
+    prose_linter: Option<ProseLinter>,
+    /// Passphrase that last unlocked a note, for `passphrase_timeout`.
+    passphrase_cache: Option<PassphraseCache>,
+    /// View-mode edits written straight to disk, newest last, for `u`.
+    pub view_undo: Vec<ViewUndo>,
     pub file_tree: Vec<FileTreeItem>,
     pub sidebar_items: Vec<SidebarItem>,
     pub selected_sidebar_index: usize,
+    /// Folder listed in the content view while it is selected in the sidebar
+    /// and has no index note.
+    pub folder_view: Option<PathBuf>,
+    /// Files opened from outside the notes directory, listed after the vault in the sidebar.
+    pub external_files: Vec<PathBuf>,
     pub folder_states: HashMap<PathBuf, bool>,
+    /// Notes and folders shown in the Pinned section instead of the tree
+    pub pins: Pins,
+    /// Listening for commands from other tools, with `ipc = true`
+    ipc: Option<IpcServer>,
     pub target_folder: Option<PathBuf>,
     pub dialog_error: Option<String>,
     pub search_matched_notes: Vec<usize>,
@@ -677,6 +1657,11 @@ pub struct App {
     pub selected_link_index: usize,
     pub details_open_states: HashMap<usize, bool>,
     pub heading_fold_states: HashMap<usize, bool>,  // content_item index -> is_folded
+    /// Content items parsed so far, to tell how much of a note a change reparsed
+    pub content_items_parsed: usize,
+    /// Bumped whenever `content_items` change, so the render cache can tell
+    /// an item is still the one it drew without hashing it again
+    pub content_generation: u64,
     pub highlighter: Option<Highlighter>,
     pub highlighter_loading: bool,
     pub highlighter_sender: Sender<Highlighter>,
@@ -684,6 +1669,7 @@ pub struct App {
     pub sidebar_collapsed: bool,
     pub outline_collapsed: bool,
     pub zen_mode: bool,
+    pub narrow: NarrowPanels,
     // Mouse selection state
     pub mouse_button_held: bool,
     pub mouse_drag_start: Option<(u16, u16)>,
@@ -692,13 +1678,32 @@ pub struct App {
     pub context_menu_state: ContextMenuState,
     // Wiki link support
     pub wiki_autocomplete: WikiAutocompleteState,
+    pub word_completion: WordCompletionState,
+    pub link_completion: LinkCompletionState,
     pub pending_wiki_target: Option<String>,
+    /// Link or image target waiting on the open confirmation dialog
+    pub pending_open: PendingOpen,
     pub needs_full_clear: bool,
+    /// Ctrl+Z was pressed; the event loop suspends, since it owns the terminal
+    pub suspend_requested: bool,
+    /// Why config.toml couldn't be used at startup, while the user decides
+    /// whether to edit it, go on with the defaults or quit
+    pub config_error: Option<ConfigError>,
+    /// The path ekphos was started with, opened once the config is sorted out
+    startup_path: Option<PathBuf>,
+    /// The config error dialog asked for `$EDITOR`; the event loop runs it,
+    /// since it owns the terminal
+    pub edit_config_requested: bool,
     pub pending_g: bool,
     pub pending_z: bool,  // For z-prefixed commands like zM, zR
+    pub pending_count: Option<usize>,  // Count typed before a content motion, like 5j
     pub status_message: Option<String>,  // Status message shown next to path
     pub toast: Option<Toast>,  // Transient error/info notification overlay
+    /// Changed lines of the viewed note after it was reloaded from disk.
+    pub reload_highlight: Option<ReloadHighlight>,
     pub buffer_search: BufferSearchState,
+    /// Set while the content panel shows the note's source (`gs`)
+    pub source_view: Option<SourceView>,
     pub help_scroll: usize,
     // Graph view state
     pub graph_view: GraphViewState,
@@ -711,6 +1716,16 @@ pub struct App {
     pub frontmatter_hidden: bool,
     // Theme selector modal (Ctrl+T)
     pub theme_picker: ThemePicker,
+    // Macro list (:macros)
+    pub macro_list: MacroList,
+    // Yank ring list (:yanks)
+    pub yank_list: YankList,
+    pub history: HistoryList,
+    pub vault_picker: VaultPicker,
+    pub title_edit: Option<TitleEdit>,
+    pub recent_filter: RecentFilter,
+    /// What timeouts and dates read the time from
+    pub clock: Clock,
     // Global search picker (file/content search)
     pub search_picker: SearchPickerState,
     pub search_picker_area: ratatui::layout::Rect,
@@ -738,6 +1753,15 @@ pub struct App {
     pub highlight_version: u64,
     /// Whether there's a pending highlight request waiting for results
     pub highlight_pending: bool,
+    /// When the debounced gutter change-marker diff should next run
+    pub change_markers_due: Option<std::time::Instant>,
+    /// Markdown lint warnings for the buffer, refreshed with the change markers
+    pub diagnostics: Vec<Diagnostic>,
+    /// Leader key from config (`None` if the configured value is invalid)
+    pub leader_key: Option<char>,
+    pub leader_map: LeaderMap,
+    /// Pending leader sequence, shared by view and edit normal modes
+    pub leader: LeaderState,
 }
 
 #[allow(dead_code)]
@@ -761,7 +1785,11 @@ impl App {
         // This must be checked before load_or_create() which creates the config
         let config_exists = Config::exists();
 
-        let config = Config::load_or_create();
+        // A config that doesn't parse is left for the user to fix or skip
+        let (config, config_error) = match Config::load_or_create() {
+            Ok(config) => (config, None),
+            Err(e) => (Config::default(), Some(e)),
+        };
 
         // For first launch: config was just created, so notes_dir won't exist yet
         let is_first_launch = !config_exists;
@@ -772,27 +1800,16 @@ impl App {
         list_state.select(Some(0));
 
         let mut editor = Editor::default();
-        editor.set_line_wrap(config.editor.line_wrap);
-        editor.set_tab_width(config.editor.tab_width);
-        editor.set_padding(config.editor.left_padding, config.editor.right_padding);
-        editor.set_line_number_mode(config.editor.line_numbers);
-        editor.set_scrolloff(config.editor.scrolloff as usize);
+        configure_editor(&mut editor, &config, &theme);
         editor.set_block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(theme.primary))
                 .title(" NORMAL | Ctrl+S: Save, Esc: Exit "),
         );
-        // No line highlighting in normal mode - only word highlighting via selection
-        editor.set_cursor_line_style(Style::default());
-        editor.set_selection_style(
-            Style::default()
-                .fg(theme.foreground)
-                .bg(theme.selection)
-        );
 
         // Initialize image picker for terminal graphics
-        let picker = Picker::from_query_stdio().ok();
+        let picker = query_picker();
 
         // Check if notes directory exists
         let notes_dir_exists = config.notes_path().exists();
@@ -804,8 +1821,15 @@ impl App {
             true
         };
 
-        let dialog = if is_first_launch {
+        // Which of several vaults to open is asked before loading any notes
+        let pick_vault = !is_first_launch && config.vaults.len() > 1;
+
+        let dialog = if config_error.is_some() {
+            DialogState::ConfigError
+        } else if is_first_launch {
             DialogState::Onboarding
+        } else if pick_vault {
+            DialogState::VaultPicker
         } else if !notes_dir_exists {
             DialogState::DirectoryNotFound
         } else if notes_dir_empty {
@@ -814,12 +1838,13 @@ impl App {
             DialogState::None
         };
 
-        let input_buffer = config.notes_dir.clone();
+        let input_buffer = LineInput::new(config.notes_dir.clone());
         let sidebar_collapsed = config.sidebar_collapsed;
         let outline_collapsed = config.outline_collapsed;
         let frontmatter_hidden = config.frontmatter_hidden;
 
         let (image_sender, image_receiver) = mpsc::channel();
+        let (link_title_sender, link_title_receiver) = mpsc::channel();
         let (highlighter_sender, highlighter_receiver) = mpsc::channel();
         let (content_search_sender, content_search_receiver) = mpsc::channel();
         let (_, index_receiver) = mpsc::channel();
@@ -832,13 +1857,20 @@ impl App {
             mode: Mode::Normal,
             editor,
             picker,
+            capabilities: Capabilities::default(),
             image_cache_dir: get_image_cache_dir(),
-            current_image: None,
+            image_states: HashMap::new(),
             pending_images: HashSet::new(),
+            image_dimensions: HashMap::new(),
+            image_info: HashMap::new(),
             image_sender,
             image_receiver,
+            link_titles_pending: 0,
+            link_title_sender,
+            link_title_receiver,
             show_welcome: !is_first_launch && config.welcome_shown && notes_dir_exists && !notes_dir_empty,
             outline: Vec::new(),
+            live_outline: None,
             outline_state: ListState::default(),
             vim_mode: VimMode::Normal,
             vim: VimState::new(),
@@ -848,24 +1880,72 @@ impl App {
             block_insert_state: None,
             content_cursor: 0,
             content_scroll_offset: 0,
+            reading_positions: ReadingPositions::default(),
+            search_history: SearchHistory::default(),
+            reading_note: None,
+            reading_jump: None,
             floating_cursor_mode: config.floating_cursor,
+            leader_key: super::leader::parse_leader_key(&config.leader_key),
+            leader_map: LeaderMap::from_config(&config.leader),
+            leader: LeaderState::default(),
             content_items: Vec::new(),
             content_item_source_lines: Vec::new(),
             theme,
+            base_config: config.clone(),
+            folder_config: Vec::new(),
             config,
             dialog,
             input_buffer,
             search_active: false,
-            search_query: String::new(),
+            search_query: LineInput::default(),
             filtered_indices: Vec::new(),
             editor_scroll_top: 0,
             editor_view_height: 0,
-            pending_operator: None,
             pending_delete: None,
+            confirm_substitute: None,
+            pending_global_substitute: None,
+            pending_bulk_rename: None,
+            quickfix: QuickfixList::default(),
+            quickfix_open: false,
+            pdf_export: None,
+            scan_job: None,
+            save_jobs: Vec::new(),
+            kept_external_version: None,
+            passphrase_prompt: None,
+            note_stats: None,
+            pending_heading_rename: None,
+            pending_merge: None,
+            smooth_scroll: None,
+            render_cache: RenderCache::default(),
+            scan_errors: Vec::new(),
+            scan_errors_scroll: 0,
+            large_paste: None,
+            paste_job: None,
+            pending_notes_dir: None,
+            scaffold_prompt: None,
+            delete_folder_contents: None,
+            sidebar_marks: SidebarMarks::default(),
+            delete_marked: Vec::new(),
+            move_picker: MovePicker::default(),
+            refile_picker: RefilePicker::default(),
+            prose_report: ProseReport::default(),
+            narrowed_heading: None,
+            debug_overlay: false,
+            frame_stats: FrameStats::default(),
+            prose_linter: None,
+            passphrase_cache: None,
+            pending_section_tasks: None,
+            view_undo: Vec::new(),
+            dirty_note: None,
+            after_unsaved_prompt: None,
             file_tree: Vec::new(),
             sidebar_items: Vec::new(),
             selected_sidebar_index: 0,
+            folder_view: None,
+            external_files: Vec::new(),
             folder_states: HashMap::new(),
+            pins: Pins::default(),
+            ipc: None,
             target_folder: None,
             dialog_error: None,
             search_matched_notes: Vec::new(),
@@ -879,6 +1959,8 @@ impl App {
             selected_link_index: 0,
             details_open_states: HashMap::new(),
             heading_fold_states: HashMap::new(),
+            content_items_parsed: 0,
+            content_generation: 0,
             highlighter: None,
             highlighter_loading: false,
             highlighter_sender,
@@ -886,6 +1968,7 @@ impl App {
             sidebar_collapsed,
             outline_collapsed,
             zen_mode: false,
+            narrow: NarrowPanels::default(),
             // Mouse selection state
             mouse_button_held: false,
             mouse_drag_start: None,
@@ -893,13 +1976,23 @@ impl App {
             editor_area: Rect::default(),
             context_menu_state: ContextMenuState::None,
             wiki_autocomplete: WikiAutocompleteState::None,
+            word_completion: WordCompletionState::None,
+            link_completion: LinkCompletionState::None,
             pending_wiki_target: None,
+            pending_open: PendingOpen::default(),
             needs_full_clear: false,
+            suspend_requested: false,
+            config_error,
+            startup_path: None,
+            edit_config_requested: false,
             pending_g: false,
             pending_z: false,
+            pending_count: None,
             status_message: None,
             toast: None,
+            reload_highlight: None,
             buffer_search: BufferSearchState::new(),
+            source_view: None,
             help_scroll: 0,
             graph_view: GraphViewState::default(),
             sort_mode: SortMode::default(),
@@ -907,6 +2000,13 @@ impl App {
             navigation_index: 0,
             frontmatter_hidden,
             theme_picker: ThemePicker::default(),
+            macro_list: MacroList::default(),
+            yank_list: YankList::default(),
+            history: HistoryList::default(),
+            recent_filter: RecentFilter::default(),
+            clock: Clock::default(),
+            title_edit: None,
+            vault_picker: VaultPicker::default(),
             search_picker: SearchPickerState::Closed,
             search_picker_area: ratatui::layout::Rect::default(),
             search_picker_results_area: ratatui::layout::Rect::default(),
@@ -924,15 +2024,28 @@ impl App {
             highlight_worker: Some(HighlightWorker::new()),
             highlight_version: 0,
             highlight_pending: false,
+            change_markers_due: None,
+            diagnostics: Vec::new(),
         };
+        app.load_macros();
+        app.vim.marks.load_global(&marks_path());
+        if app.config.remember_positions {
+            app.reading_positions = ReadingPositions::load(&positions_path());
+        }
+        if app.config.remember_search_history {
+            app.search_history = SearchHistory::load(&search_history_path());
+        }
+        app.start_ipc();
+        app.report_config_problems();
 
-        if !is_first_launch && notes_dir_exists {
-            app.load_notes_from_dir();
-            app.start_index_build();
-
-            if let Some(last_path) = read_last_opened_note() {
-                app.select_note_by_path(&last_path);
-            }
+        if app.config_error.is_some() {
+            // Notes are opened once the config is fixed or skipped
+        } else if pick_vault {
+            app.open_vault_picker();
+            app.vault_picker.at_startup = true;
+        } else if !is_first_launch && notes_dir_exists {
+            let last_path = read_last_opened_note(&app.config.notes_path());
+            app.load_notes_and_select(last_path);
         }
 
         app
@@ -956,8 +2069,11 @@ impl App {
         } else {
             return Self::new();
         };
-        let _config_exists = Config::exists();
-        let mut config = Config::load_or_create();
+        let startup_path = target_file.clone().unwrap_or_else(|| notes_dir.clone());
+        let (mut config, config_error) = match Config::load_or_create() {
+            Ok(config) => (config, None),
+            Err(e) => (Config::default(), Some(e)),
+        };
         config.notes_dir = notes_dir.to_string_lossy().to_string();
 
         let theme = Theme::from_name(&config.theme);
@@ -966,25 +2082,15 @@ impl App {
         list_state.select(Some(0));
 
         let mut editor = Editor::default();
-        editor.set_line_wrap(config.editor.line_wrap);
-        editor.set_tab_width(config.editor.tab_width);
-        editor.set_padding(config.editor.left_padding, config.editor.right_padding);
-        editor.set_line_number_mode(config.editor.line_numbers);
-        editor.set_scrolloff(config.editor.scrolloff as usize);
+        configure_editor(&mut editor, &config, &theme);
         editor.set_block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(theme.primary))
                 .title(" NORMAL | Ctrl+S: Save, Esc: Exit "),
         );
-        editor.set_cursor_line_style(Style::default());
-        editor.set_selection_style(
-            Style::default()
-                .fg(theme.foreground)
-                .bg(theme.selection)
-        );
 
-        let picker = Picker::from_query_stdio().ok();
+        let picker = query_picker();
 
         let notes_dir_exists = config.notes_path().exists();
         let notes_dir_empty = if notes_dir_exists {
@@ -994,7 +2100,9 @@ impl App {
         };
 
         // Skip onboarding when path is explicitly provided
-        let dialog = if !notes_dir_exists {
+        let dialog = if config_error.is_some() {
+            DialogState::ConfigError
+        } else if !notes_dir_exists {
             DialogState::DirectoryNotFound
         } else if notes_dir_empty {
             DialogState::EmptyDirectory
@@ -1002,12 +2110,13 @@ impl App {
             DialogState::None
         };
 
-        let input_buffer = config.notes_dir.clone();
+        let input_buffer = LineInput::new(config.notes_dir.clone());
         let sidebar_collapsed = config.sidebar_collapsed;
         let outline_collapsed = config.outline_collapsed;
         let frontmatter_hidden = config.frontmatter_hidden;
 
         let (image_sender, image_receiver) = mpsc::channel();
+        let (link_title_sender, link_title_receiver) = mpsc::channel();
         let (highlighter_sender, highlighter_receiver) = mpsc::channel();
         let (content_search_sender, content_search_receiver) = mpsc::channel();
         let (_, index_receiver) = mpsc::channel();
@@ -1020,13 +2129,20 @@ impl App {
             mode: Mode::Normal,
             editor,
             picker,
+            capabilities: Capabilities::default(),
             image_cache_dir: get_image_cache_dir(),
-            current_image: None,
+            image_states: HashMap::new(),
             pending_images: HashSet::new(),
+            image_dimensions: HashMap::new(),
+            image_info: HashMap::new(),
             image_sender,
             image_receiver,
+            link_titles_pending: 0,
+            link_title_sender,
+            link_title_receiver,
             show_welcome: false, // Don't show welcome when opening via CLI path
             outline: Vec::new(),
+            live_outline: None,
             outline_state: ListState::default(),
             vim_mode: VimMode::Normal,
             vim: VimState::new(),
@@ -1036,24 +2152,72 @@ impl App {
             block_insert_state: None,
             content_cursor: 0,
             content_scroll_offset: 0,
+            reading_positions: ReadingPositions::default(),
+            search_history: SearchHistory::default(),
+            reading_note: None,
+            reading_jump: None,
             floating_cursor_mode: config.floating_cursor,
+            leader_key: super::leader::parse_leader_key(&config.leader_key),
+            leader_map: LeaderMap::from_config(&config.leader),
+            leader: LeaderState::default(),
             content_items: Vec::new(),
             content_item_source_lines: Vec::new(),
             theme,
+            base_config: config.clone(),
+            folder_config: Vec::new(),
             config,
             dialog,
             input_buffer,
             search_active: false,
-            search_query: String::new(),
+            search_query: LineInput::default(),
             filtered_indices: Vec::new(),
             editor_scroll_top: 0,
             editor_view_height: 0,
-            pending_operator: None,
             pending_delete: None,
+            confirm_substitute: None,
+            pending_global_substitute: None,
+            pending_bulk_rename: None,
+            quickfix: QuickfixList::default(),
+            quickfix_open: false,
+            pdf_export: None,
+            scan_job: None,
+            save_jobs: Vec::new(),
+            kept_external_version: None,
+            passphrase_prompt: None,
+            note_stats: None,
+            pending_heading_rename: None,
+            pending_merge: None,
+            smooth_scroll: None,
+            render_cache: RenderCache::default(),
+            scan_errors: Vec::new(),
+            scan_errors_scroll: 0,
+            large_paste: None,
+            paste_job: None,
+            pending_notes_dir: None,
+            scaffold_prompt: None,
+            delete_folder_contents: None,
+            sidebar_marks: SidebarMarks::default(),
+            delete_marked: Vec::new(),
+            move_picker: MovePicker::default(),
+            refile_picker: RefilePicker::default(),
+            prose_report: ProseReport::default(),
+            narrowed_heading: None,
+            debug_overlay: false,
+            frame_stats: FrameStats::default(),
+            prose_linter: None,
+            passphrase_cache: None,
+            pending_section_tasks: None,
+            view_undo: Vec::new(),
+            dirty_note: None,
+            after_unsaved_prompt: None,
             file_tree: Vec::new(),
             sidebar_items: Vec::new(),
             selected_sidebar_index: 0,
+            folder_view: None,
+            external_files: Vec::new(),
             folder_states: HashMap::new(),
+            pins: Pins::default(),
+            ipc: None,
             target_folder: None,
             dialog_error: None,
             search_matched_notes: Vec::new(),
@@ -1067,6 +2231,8 @@ impl App {
             selected_link_index: 0,
             details_open_states: HashMap::new(),
             heading_fold_states: HashMap::new(),
+            content_items_parsed: 0,
+            content_generation: 0,
             highlighter: None,
             highlighter_loading: false,
             highlighter_sender,
@@ -1074,19 +2240,30 @@ impl App {
             sidebar_collapsed,
             outline_collapsed,
             zen_mode: false,
+            narrow: NarrowPanels::default(),
             mouse_button_held: false,
             mouse_drag_start: None,
             last_mouse_y: 0,
             editor_area: Rect::default(),
             context_menu_state: ContextMenuState::None,
             wiki_autocomplete: WikiAutocompleteState::None,
+            word_completion: WordCompletionState::None,
+            link_completion: LinkCompletionState::None,
             pending_wiki_target: None,
+            pending_open: PendingOpen::default(),
             needs_full_clear: false,
+            suspend_requested: false,
+            config_error,
+            startup_path: None,
+            edit_config_requested: false,
             pending_g: false,
             pending_z: false,
+            pending_count: None,
             status_message: None,
             toast: None,
+            reload_highlight: None,
             buffer_search: BufferSearchState::new(),
+            source_view: None,
             help_scroll: 0,
             graph_view: GraphViewState::default(),
             sort_mode: SortMode::default(),
@@ -1094,6 +2271,13 @@ impl App {
             navigation_index: 0,
             frontmatter_hidden,
             theme_picker: ThemePicker::default(),
+            macro_list: MacroList::default(),
+            yank_list: YankList::default(),
+            history: HistoryList::default(),
+            recent_filter: RecentFilter::default(),
+            clock: Clock::default(),
+            title_edit: None,
+            vault_picker: VaultPicker::default(),
             search_picker: SearchPickerState::Closed,
             search_picker_area: ratatui::layout::Rect::default(),
             search_picker_results_area: ratatui::layout::Rect::default(),
@@ -1111,16 +2295,25 @@ impl App {
             highlight_worker: Some(HighlightWorker::new()),
             highlight_version: 0,
             highlight_pending: false,
+            change_markers_due: None,
+            diagnostics: Vec::new(),
         };
+        app.load_macros();
+        app.vim.marks.load_global(&marks_path());
+        if app.config.remember_positions {
+            app.reading_positions = ReadingPositions::load(&positions_path());
+        }
+        if app.config.remember_search_history {
+            app.search_history = SearchHistory::load(&search_history_path());
+        }
+        app.start_ipc();
+        app.report_config_problems();
 
-        if notes_dir_exists {
-            app.load_notes_from_dir();
-            app.start_index_build();
-            if let Some(ref target_path) = target_file {
-                app.select_note_by_path(target_path);
-            } else if let Some(last_path) = read_last_opened_note() {
-                app.select_note_by_path(&last_path);
-            }
+        if app.config_error.is_some() {
+            app.startup_path = Some(startup_path);
+        } else if notes_dir_exists {
+            let select = target_file.or_else(|| read_last_opened_note(&app.config.notes_path()));
+            app.load_notes_and_select(select);
         }
 
         app
@@ -1154,14 +2347,381 @@ impl App {
         }
     }
 
+    /// Replace the quickfix list with the locations from a search or check.
+    pub fn set_quickfix(&mut self, title: impl Into<String>, entries: Vec<QuickfixEntry>) {
+        self.quickfix = QuickfixList::new(title, entries);
+    }
+
+    pub fn toggle_quickfix(&mut self) {
+        self.quickfix_open = !self.quickfix_open;
+    }
+
+    /// Status line message for the current mode.
+    fn set_status(&mut self, message: String) {
+        if self.mode == Mode::Edit {
+            self.vim.status_message = Some(message);
+        } else {
+            self.status_message = Some(message);
+        }
+    }
+
+    /// `:cnext`/`:cprev`: jump to the next or previous quickfix entry.
+    pub fn quickfix_step(&mut self, forward: bool) {
+        if self.quickfix.is_empty() {
+            self.set_status("No quickfix list".to_string());
+            return;
+        }
+        match self.quickfix.step(forward) {
+            Some(index) => self.jump_to_quickfix(index),
+            None => self.set_status("No more items".to_string()),
+        }
+    }
+
+    /// Open the note of quickfix entry `index` and put the cursor on it. In
+    /// edit mode the other note is opened for editing, unless the buffer has
+    /// unsaved changes.
+    pub fn jump_to_quickfix(&mut self, index: usize) {
+        let Some(jump) = self.quickfix.jump(index, self.selected_note) else {
+            return;
+        };
+        let (line, col) = match jump {
+            QuickfixJump::SameNote { line, col } => (line, col),
+            QuickfixJump::OtherNote { note, line, col } => {
+                let editing = self.mode == Mode::Edit;
+                if editing {
+                    if self.has_unsaved_changes() {
+                        self.vim.status_message = Some("No write since last change".to_string());
+                        return;
+                    }
+                    self.cancel_edit();
+                }
+                let Some(path) = self.notes.get(note).and_then(|n| n.file_path.clone()) else {
+                    return;
+                };
+                self.expand_folders_to(&path);
+                self.select_note_by_path(&path);
+                if self.selected_note != note {
+                    return;
+                }
+                self.push_navigation_history(note);
+                if editing {
+                    self.enter_edit_mode();
+                }
+                (line, col)
+            }
+        };
+
+        self.quickfix.select(index);
+        if self.mode == Mode::Edit {
+            let last_row = self.editor.lines().len().saturating_sub(1);
+            self.editor.set_cursor(line.min(last_row), col);
+        } else {
+            self.content_cursor = self.content_cursor_for_source_line(line);
+            self.focus = Focus::Content;
+        }
+        let text = self.quickfix.entries[index].text.clone();
+        self.set_status(format!("({} of {}) {}", index + 1, self.quickfix.entries.len(), text));
+    }
+
+    /// `:checklinks`: fill the quickfix list with wiki links whose target
+    /// note doesn't exist, ones with a bracket that needs escaping, and
+    /// reference links without a definition.
+    pub fn check_links(&mut self) {
+        let mut entries = Vec::new();
+        for (note, n) in self.notes.iter().enumerate() {
+            let definitions = reference_links::collect_definitions(&n.content);
+            let mut in_code_block = false;
+            for (line, text) in n.content.lines().enumerate() {
+                if text.trim_start().starts_with("```") {
+                    in_code_block = !in_code_block;
+                    continue;
+                }
+                if in_code_block {
+                    continue;
+                }
+                for (col, link) in wiki_link::links_in(text) {
+                    let text = match link {
+                        Ok(link) => {
+                            let target = link.target.trim();
+                            if target.is_empty() || self.wiki_link_exists(target) {
+                                continue;
+                            }
+                            format!("{}: [[{}]]", n.title, target)
+                        }
+                        Err(raw) => format!("{}: [[{}]] needs its brackets escaped as \\[ and \\]", n.title, raw),
+                    };
+                    entries.push(QuickfixEntry { note, line, col, text });
+                }
+                for (col, label) in reference_links::dangling_references(text, &definitions) {
+                    entries.push(QuickfixEntry {
+                        note,
+                        line,
+                        col,
+                        text: format!("{}: no definition for [{}]", n.title, label),
+                    });
+                }
+            }
+        }
+
+        let count = entries.len();
+        self.set_quickfix("Broken links", entries);
+        self.set_status(if count == 0 {
+            "No broken links".to_string()
+        } else {
+            format!("{} broken link{}, :cnext to step through", count, if count == 1 { "" } else { "s" })
+        });
+    }
+
+    /// Pick up changes made to the notes while ekphos was in the background.
+    /// A change to the viewed note is handled per the `external_change` policy.
     pub fn reload_on_focus(&mut self) {
+        let local_edits = (self.mode == Mode::Edit && self.has_unsaved_changes()) || self.has_dirty_buffer();
+        if !self.viewed_note_changed_on_disk() {
+            if self.mode == Mode::Normal && !local_edits {
+                self.reload_notes(true);
+            }
+            return;
+        }
+
+        match external_change_action(self.config.external_change, local_edits) {
+            ExternalChangeAction::Reload => self.reload_external_change(),
+            // Asked again on the next focus if another dialog is in the way
+            ExternalChangeAction::Prompt if self.dialog == DialogState::None => {
+                self.dialog = DialogState::ExternalChange;
+            }
+            ExternalChangeAction::Prompt => {}
+            ExternalChangeAction::Keep => self.keep_external_change(),
+        }
+    }
+
+    /// Whether the viewed note's file no longer matches what's on screen,
+    /// leaving out a version already kept over at the ExternalChange prompt.
+    fn viewed_note_changed_on_disk(&self) -> bool {
+        let Some(note) = self.current_note() else {
+            return false;
+        };
+        let Some(path) = note.file_path.as_ref() else {
+            return false;
+        };
+        if let Some((kept_path, kept_modified)) = &self.kept_external_version {
+            let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+            if kept_path == path && *kept_modified == modified {
+                return false;
+            }
+        }
+        if note.lock != NoteLock::Plain {
+            // Every save encrypts anew, so only the file's time tells
+            return fs::metadata(path).and_then(|m| m.modified()).ok() != note.modified_time;
+        }
+        fs::read_to_string(path).is_ok_and(|disk| super::line_ending::normalize(disk) != note.content)
+    }
+
+    /// Load the viewed note's new version from disk, dropping local edits.
+    pub fn reload_external_change(&mut self) {
+        self.kept_external_version = None;
         if self.mode == Mode::Edit {
+            let (row, col) = self.editor.cursor();
+            self.cancel_edit();
+            self.reload_notes(true);
+            self.enter_edit_mode();
+            let row = row.min(self.editor.line_count().saturating_sub(1));
+            self.editor.set_cursor(row, col);
+        } else {
+            self.dirty_note = None;
+            self.reload_notes(true);
+        }
+    }
+
+    /// Keep the viewed note as it is on screen over its new version on disk.
+    /// The rest of the vault is still reloaded outside edit mode.
+    pub fn keep_external_change(&mut self) {
+        if let Some(path) = self.current_note().and_then(|n| n.file_path.clone()) {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            self.kept_external_version = Some((path, modified));
+        }
+        if self.mode == Mode::Normal {
+            self.reload_notes(false);
+        }
+        self.set_status("Kept the version on screen; saving will overwrite the file".to_string());
+    }
+
+    /// Make sure the selected note can be read before it's opened. A locked
+    /// note is unlocked with the remembered passphrase if that fits, or else
+    /// the Passphrase dialog asks. False while the note stays locked.
+    pub fn unlock_selected_note(&mut self, edit: bool) -> bool {
+        let note = self.selected_note;
+        if self.notes.get(note).is_none_or(|n| n.lock != NoteLock::Locked) {
+            return true;
+        }
+        let now = self.clock.now();
+        if let Some(passphrase) = self.passphrase_cache.as_mut().map(|cache| cache.take_use(now)) {
+            if self.unlock_note(note, passphrase).is_ok() {
+                return true;
+            }
+        }
+        self.open_passphrase_prompt(PassphrasePurpose::Unlock { edit }, note);
+        false
+    }
+
+    /// Decrypt the note at `index` into memory; the file stays as it is.
+    fn unlock_note(&mut self, index: usize, passphrase: Passphrase) -> Result<(), DecryptError> {
+        let Some(path) = self.notes.get(index).and_then(|n| n.file_path.clone()) else {
+            return Ok(());
+        };
+        let text = fs::read_to_string(&path).map_err(|_| DecryptError::Corrupted)?;
+        let plaintext = encryption::decrypt(&text, &passphrase)?;
+
+        let note = &mut self.notes[index];
+        note.newline = Newline::detect(&plaintext);
+        note.content = super::line_ending::normalize(plaintext);
+        let (frontmatter, content_start_line) = super::frontmatter::Frontmatter::parse(&note.content);
+        note.frontmatter = frontmatter;
+        note.content_start_line = content_start_line;
+        note.lock = NoteLock::Unlocked(passphrase.clone());
+        self.remember_passphrase(passphrase);
+
+        if index == self.selected_note {
+            self.update_content_items();
+            self.update_outline();
+        }
+        Ok(())
+    }
+
+    fn remember_passphrase(&mut self, passphrase: Passphrase) {
+        if self.config.passphrase_timeout > 0 {
+            self.passphrase_cache = Some(PassphraseCache::new(passphrase, self.clock.now()));
+        }
+    }
+
+    fn open_passphrase_prompt(&mut self, purpose: PassphrasePurpose, note: usize) {
+        self.passphrase_prompt = Some(PassphrasePrompt { purpose, note, first: None });
+        self.input_buffer.clear();
+        self.dialog_error = None;
+        self.dialog = DialogState::Passphrase;
+    }
+
+    pub fn close_passphrase_prompt(&mut self) {
+        self.passphrase_prompt = None;
+        self.input_buffer.clear();
+        self.dialog_error = None;
+        self.dialog = DialogState::None;
+    }
+
+    /// Enter in the Passphrase dialog, with the passphrase in `input_buffer`.
+    pub fn submit_passphrase(&mut self) {
+        let Some(prompt) = self.passphrase_prompt.clone() else {
+            self.close_passphrase_prompt();
+            return;
+        };
+        let typed = Passphrase::new(self.input_buffer.take());
+        if typed.as_str().is_empty() {
+            self.dialog_error = Some("Passphrase cannot be empty".to_string());
             return;
         }
+
+        match prompt.purpose {
+            PassphrasePurpose::Unlock { edit } => match self.unlock_note(prompt.note, typed) {
+                Ok(()) => {
+                    self.close_passphrase_prompt();
+                    if edit {
+                        self.enter_edit_mode();
+                    }
+                }
+                Err(e) => self.dialog_error = Some(e.to_string()),
+            },
+            PassphrasePurpose::Encrypt => {
+                self.dialog_error = None;
+                self.passphrase_prompt = Some(PassphrasePrompt {
+                    purpose: PassphrasePurpose::ConfirmEncrypt,
+                    first: Some(typed),
+                    ..prompt
+                });
+            }
+            PassphrasePurpose::ConfirmEncrypt => {
+                if prompt.first.as_ref() != Some(&typed) {
+                    self.dialog_error = Some("Passphrases don't match, enter it again".to_string());
+                    self.passphrase_prompt = Some(PassphrasePrompt {
+                        purpose: PassphrasePurpose::Encrypt,
+                        first: None,
+                        ..prompt
+                    });
+                    return;
+                }
+                self.close_passphrase_prompt();
+                if let Some(note) = self.notes.get_mut(prompt.note) {
+                    note.lock = NoteLock::Unlocked(typed.clone());
+                }
+                self.remember_passphrase(typed);
+                self.save_edit();
+                self.status_message = Some("Note encrypted".to_string());
+            }
+        }
+    }
+
+    /// `:encrypt`: ask for a passphrase to encrypt the edited note with.
+    pub fn start_encrypt_note(&mut self) {
+        match self.current_note().map(|n| &n.lock) {
+            Some(NoteLock::Plain) => self.open_passphrase_prompt(PassphrasePurpose::Encrypt, self.selected_note),
+            Some(_) => self.set_status("Note is already encrypted".to_string()),
+            None => {}
+        }
+    }
+
+    /// `:decrypt`: save the edited note as plain text again.
+    pub fn decrypt_note(&mut self) {
+        match self.notes.get_mut(self.selected_note) {
+            Some(note) if matches!(note.lock, NoteLock::Unlocked(_)) => {
+                note.lock = NoteLock::Plain;
+                self.save_edit();
+                self.status_message = Some("Note decrypted".to_string());
+            }
+            Some(_) => self.set_status("Note is not encrypted".to_string()),
+            None => {}
+        }
+    }
+
+    /// When the remembered passphrase runs out, if one is remembered.
+    pub fn passphrase_expires_at(&self) -> Option<std::time::Instant> {
+        let timeout = std::time::Duration::from_secs(self.config.passphrase_timeout * 60);
+        self.passphrase_cache.as_ref().map(|cache| cache.expires_at(timeout))
+    }
+
+    /// Once `passphrase_timeout` has passed since the passphrase was last
+    /// used, forget it and lock the unlocked notes again, leaving out one
+    /// with edits in progress. Returns true when the screen needs a redraw.
+    pub fn expire_passphrase(&mut self) -> bool {
+        if self.passphrase_expires_at().is_none_or(|at| self.clock.now() < at) {
+            return false;
+        }
+        self.passphrase_cache = None;
+        let editing = (self.mode == Mode::Edit).then_some(self.selected_note);
+        let mut locked = false;
+        for (i, note) in self.notes.iter_mut().enumerate() {
+            if !matches!(note.lock, NoteLock::Unlocked(_)) || editing == Some(i) || self.dirty_note == Some(i) {
+                continue;
+            }
+            note.lock = NoteLock::Locked;
+            note.content.clear();
+            note.frontmatter = None;
+            note.content_start_line = 0;
+            locked = true;
+        }
+        if locked && self.mode == Mode::Normal {
+            self.update_content_items();
+            self.update_outline();
+        }
+        locked
+    }
+
+    /// Reload the vault from disk, keeping the selection on the viewed note.
+    /// Unless `reload_viewed` is set, the viewed note keeps its loaded text.
+    fn reload_notes(&mut self, reload_viewed: bool) {
         let current_note_path = self.current_note().and_then(|n| n.file_path.clone());
+        let previous_note = self.current_note().cloned();
         let scroll_offset = self.content_scroll_offset;
         let content_cursor = self.content_cursor;
         self.load_notes_from_dir();
+        let mut restored = false;
         if let Some(path) = current_note_path {
             for (idx, item) in self.sidebar_items.iter().enumerate() {
                 if let SidebarItemKind::Note { note_index } = &item.kind {
@@ -1172,11 +2732,21 @@ impl App {
                     {
                         self.selected_sidebar_index = idx;
                         self.selected_note = *note_index;
+                        restored = true;
                         break;
                     }
                 }
             }
         }
+        if restored {
+            if let Some(previous) = previous_note {
+                if reload_viewed {
+                    self.highlight_reload_changes(&previous.content);
+                } else {
+                    self.notes[self.selected_note] = previous;
+                }
+            }
+        }
         // Rebuild content_items for the restored note BEFORE clamping positions,
         // so that content_items.len() reflects the correct note's length
         self.update_content_items();
@@ -1190,45 +2760,344 @@ impl App {
         self.update_outline();
     }
 
+    /// Point out what changed in the viewed note since it was last read, when
+    /// it changed on disk while ekphos was in the background.
+    fn highlight_reload_changes(&mut self, previous: &str) {
+        let Some(note) = self.current_note() else {
+            return;
+        };
+        if note.content == previous {
+            return;
+        }
+        let diff = line_diff::diff_lines(previous, &note.content);
+        if diff.count == 0 {
+            return;
+        }
+        self.reload_highlight = Some(ReloadHighlight {
+            note: self.selected_note,
+            lines: diff.changed.into_iter().collect(),
+            shown_at: self.clock.now(),
+        });
+        self.status_message = Some(format!(
+            "Reloaded, {} line{} changed",
+            diff.count,
+            if diff.count == 1 { "" } else { "s" },
+        ));
+    }
+
+    /// Drop the reload highlight once it has faded. Returns `true` while it is
+    /// on screen, so each frame of the fade gets drawn.
+    pub fn tick_reload_highlight(&mut self) -> bool {
+        match &self.reload_highlight {
+            Some(h) if h.is_expired(self.clock.now()) => {
+                self.reload_highlight = None;
+                true
+            }
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    /// `:export html`/`:export pdf`: write the viewed note, unsaved edits
+    /// included, next to its file. PDFs are made in the background by the
+    /// `[export] pdf_command` converter.
+    pub fn export_note(&mut self, format: ExportFormat) {
+        let Some(note) = self.current_note() else {
+            self.set_status("No note to export".to_string());
+            return;
+        };
+        let Some(path) = note.file_path.clone() else {
+            self.set_status("Note has no file to export next to".to_string());
+            return;
+        };
+        let markdown = if self.mode == Mode::Edit {
+            self.editor.full_lines().join("\n")
+        } else {
+            note.content.clone()
+        };
+        let html = export::note_to_html(&note.title, &markdown);
+
+        match format {
+            ExportFormat::Html => {
+                let output = path.with_extension("html");
+                match fs::write(&output, html) {
+                    Ok(()) => self.set_status(format!("Exported {}", output.display())),
+                    Err(e) => self.show_error_toast(format!("Failed to export HTML: {}", e)),
+                }
+            }
+            ExportFormat::Pdf => {
+                if self.pdf_export.is_some() {
+                    self.set_status("A PDF export is already running".to_string());
+                    return;
+                }
+                let Some(template) = self.config.export.pdf_command.clone() else {
+                    self.show_error_toast(
+                        "Set [export] pdf_command in config.toml, e.g. \"wkhtmltopdf {input} {output}\"",
+                    );
+                    return;
+                };
+                let output = path.with_extension("pdf");
+                match PdfExport::start(&template, &html, output) {
+                    Ok(job) => {
+                        self.set_status(format!("Exporting {}...", job.output.display()));
+                        self.pdf_export = Some(job);
+                    }
+                    Err(e) => self.show_error_toast(e),
+                }
+            }
+        }
+    }
+
+    /// `:copy html`: the note, or `region` of the buffer, on the clipboard as
+    /// HTML with the markdown as its plain text. Images point at their files
+    /// so they come along when pasted on this machine.
+    pub fn copy_as_html(&mut self, region: Option<Region>) {
+        let Some(note) = self.current_note() else {
+            self.set_status("No note to copy".to_string());
+            return;
+        };
+        let dir = note
+            .file_path
+            .as_ref()
+            .and_then(|path| path.parent())
+            .map_or_else(|| self.config.notes_path(), Path::to_path_buf);
+        let markdown = match (self.mode == Mode::Edit, region) {
+            (true, Some(region)) => region.text(&self.editor.lines()),
+            (true, None) => self.editor.lines().join("\n"),
+            (false, _) => note.content.clone(),
+        };
+        let html = export::with_file_images(&export::markdown_to_html(&markdown), &dir);
+        let what = if region.is_some() { "Selection" } else { "Note" };
+        if crate::clipboard::set_system_html(&html, &markdown) {
+            self.set_status(format!("{} copied as HTML", what));
+        } else {
+            self.set_status(format!("{} copied as plain text; this clipboard can't hold HTML", what));
+        }
+    }
+
+    /// Write the vault's link graph to `path`, or to `graph.dot`/`graph.json`
+    /// in the notes folder.
+    pub fn export_graph(&mut self, format: GraphFormat, path: Option<PathBuf>) {
+        let notes_path = self.config.notes_path();
+        let output = match path {
+            Some(path) => PathBuf::from(shellexpand::tilde(&path.to_string_lossy()).to_string()),
+            None => notes_path.join(format!("graph.{}", format.extension())),
+        };
+        let graph = link_graph::export_graph(&self.notes, &notes_path, format);
+        match fs::write(&output, graph) {
+            Ok(()) => self.set_status(format!("Exported {}", output.display())),
+            Err(e) => self.show_error_toast(format!("Failed to export graph: {}", e)),
+        }
+    }
+
+    /// Report a finished PDF export. Returns `true` when one just finished.
+    pub fn poll_pdf_export(&mut self) -> bool {
+        let Some(result) = self.pdf_export.as_mut().and_then(|job| job.poll()) else {
+            return false;
+        };
+        let job = self.pdf_export.take().expect("polled export");
+        match result {
+            Ok(()) => self.set_status(format!("Exported {}", job.output.display())),
+            Err(e) => self.show_error_toast(e),
+        }
+        true
+    }
+
     pub fn reload_config(&mut self) {
         if self.mode == Mode::Edit {
             return;
         }
 
-        self.config = Config::load();
+        // The notes folder stays the one in use, whether it came from a path
+        // argument or `:vault`
+        // A config that doesn't parse now keeps the one in use
+        let config = match Config::try_load() {
+            Ok(config) => config,
+            Err(e) => {
+                self.show_error_toast(format!("Config not reloaded: {}", e));
+                return;
+            }
+        };
+        let notes_dir = std::mem::take(&mut self.base_config.notes_dir);
+        self.base_config = config;
+        self.base_config.notes_dir = notes_dir;
+        let note = self.reading_note.clone();
+        self.folder_config = note
+            .map(|note| folder_config::layers_for(&self.base_config.notes_path(), &note))
+            .unwrap_or_default();
+        self.config = folder_config::layered(&self.base_config, &self.folder_config);
+        self.report_config_problems();
+        // Picks up changes to prose_words.toml
+        self.prose_linter = None;
 
         self.theme = Theme::from_name(&self.config.theme);
+        self.render_cache.invalidate();
 
-        self.editor.set_line_wrap(self.config.editor.line_wrap);
-        self.editor.set_tab_width(self.config.editor.tab_width);
-        self.editor.set_padding(self.config.editor.left_padding, self.config.editor.right_padding);
-        self.editor.set_line_number_mode(self.config.editor.line_numbers);
-        self.editor.set_scrolloff(self.config.editor.scrolloff as usize);
+        // The editor may hold kept edits, so it is reconfigured in place
+        // rather than rebuilt, and its highlights redone in the new colors
+        configure_editor(&mut self.editor, &self.config, &self.theme);
+        if self.mode == Mode::Edit {
+            self.update_editor_highlights();
+        }
         self.editor.set_block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(self.theme.primary))
                 .title(" NORMAL | Ctrl+S: Save, Esc: Exit "),
         );
-        self.editor.set_selection_style(
-            Style::default()
-                .fg(self.theme.foreground)
-                .bg(self.theme.selection)
-        );
+        self.needs_full_clear = true;
 
         self.highlighter = None;
+        if self.config.background_io {
+            self.load_notes_and_select(self.current_note().and_then(|n| n.file_path.clone()));
+            return;
+        }
         self.load_notes_from_dir();
         self.update_content_items();
         self.update_outline();
     }
 
-    /// Swap the active runtime theme without touching config or reloading notes
+    /// Listen for commands from other tools when `ipc = true`.
+    fn start_ipc(&mut self) {
+        if !self.config.ipc {
+            return;
+        }
+        match IpcServer::start(&ipc_socket_path()) {
+            Ok(server) => self.ipc = Some(server),
+            Err(e) => self.show_error_toast(format!("IPC: {}", e)),
+        }
+    }
+
+    pub fn is_ipc_listening(&self) -> bool {
+        self.ipc.is_some()
+    }
+
+    /// Run the commands that came in on the IPC socket. Returns whether any
+    /// did, so the screen is redrawn.
+    pub fn poll_ipc(&mut self) -> bool {
+        let mut ran = false;
+        while let Some(request) = self.ipc.as_ref().and_then(IpcServer::try_recv) {
+            let response = match self.run_ipc_command(&request.command) {
+                Ok(result) => IpcResponse::ok(result),
+                Err(e) => IpcResponse::error(e),
+            };
+            request.respond(response);
+            ran = true;
+        }
+        ran
+    }
+
+    fn run_ipc_command(&mut self, command: &IpcCommand) -> Result<serde_json::Value, String> {
+        match command {
+            IpcCommand::OpenNote { note } => {
+                let idx = ipc::find_note(&self.notes, &self.config.notes_path(), note)
+                    .ok_or_else(|| format!("No note named '{}'", note))?;
+                if self.dirty_note.is_some() || (self.mode == Mode::Edit && self.has_unsaved_changes()) {
+                    return Err("The note being edited has unsaved changes".to_string());
+                }
+                let Some(path) = self.notes[idx].file_path.clone() else {
+                    return Err(format!("'{}' has no file", note));
+                };
+                if self.mode == Mode::Edit {
+                    self.cancel_edit();
+                }
+                self.folder_view = None;
+                self.expand_folders_to(&path);
+                self.select_note_by_path(&path);
+                self.push_navigation_history(idx);
+                self.focus = Focus::Content;
+                Ok(ipc::note_summary(&self.notes[idx]))
+            }
+            IpcCommand::GetCurrentNote => {
+                let note = self.current_note().ok_or("No note is open")?;
+                if matches!(note.lock, NoteLock::Locked) {
+                    return Err("The current note is locked".to_string());
+                }
+                let editing = self.mode == Mode::Edit || self.dirty_note == Some(self.selected_note);
+                let content = if editing { self.editor.full_lines().join("\n") } else { note.content.clone() };
+                let mut result = ipc::note_summary(note);
+                result["content"] = content.into();
+                result["editing"] = (self.mode == Mode::Edit).into();
+                result["modified"] = (editing && self.has_unsaved_changes()).into();
+                Ok(result)
+            }
+            IpcCommand::InsertText { text } => {
+                if self.mode != Mode::Edit {
+                    self.enter_edit_mode();
+                }
+                if self.mode != Mode::Edit {
+                    return Err("The current note can't be edited".to_string());
+                }
+                self.editor.insert_str(text);
+                self.request_highlight_update();
+                let (row, col) = self.editor.cursor();
+                Ok(serde_json::json!({ "line": row + 1, "column": col + 1 }))
+            }
+            IpcCommand::Search { query } => ipc::search_notes(&self.notes, query),
+            IpcCommand::ListNotes => Ok(self.notes.iter().map(ipc::note_summary).collect()),
+            IpcCommand::Save => {
+                let saved = self.mode == Mode::Edit || self.dirty_note == Some(self.selected_note);
+                if !saved && self.dirty_note.is_some() {
+                    return Err("The unsaved changes belong to another note".to_string());
+                }
+                if saved {
+                    self.save_edit();
+                }
+                Ok(serde_json::json!({ "saved": saved }))
+            }
+        }
+    }
+
+    /// Surface settings that loaded but can't be used, e.g. a misspelled
+    /// status bar segment, instead of silently leaving them out.
+    fn report_config_problems(&mut self) {
+        let mut problems = self.config.validate();
+        problems.extend(folder_config_problems(&self.folder_config));
+        if !problems.is_empty() {
+            self.show_error_toast(format!("Config: {}", problems.join("; ")));
+        }
+    }
+
+    /// Switch to the settings for `note`, with the `.ekphos.toml` files over
+    /// it. Not while editing, where the editor is set up for the note being
+    /// edited.
+    fn apply_folder_config(&mut self, note: Option<&Path>) {
+        if self.mode == Mode::Edit {
+            return;
+        }
+        let layers = note
+            .map(|note| folder_config::layers_for(&self.base_config.notes_path(), note))
+            .unwrap_or_default();
+        if layers == self.folder_config {
+            return;
+        }
+        let new: Vec<FolderConfigLayer> =
+            layers.iter().filter(|layer| !self.folder_config.contains(layer)).cloned().collect();
+        let problems = folder_config_problems(&new);
+        if !problems.is_empty() {
+            self.show_error_toast(format!("Config: {}", problems.join("; ")));
+        }
+
+        let config = folder_config::layered(&self.base_config, &layers);
+        self.folder_config = layers;
+        let theme_changed = config.theme != self.config.theme;
+        self.config = config;
+        self.render_cache.invalidate();
+        if theme_changed {
+            let name = self.config.theme.clone();
+            self.apply_theme_named(&name);
+        }
+        configure_editor(&mut self.editor, &self.config, &self.theme);
+    }
+
+    /// Swap the active runtime theme without touching config or reloading notes
     /// from disk. Content/editor views read `self.theme` live each frame, so the
     /// whole UI re-skins on the next render; the syntect code-block highlighter
     /// keys off `syntax_theme` (unchanged here) so it is intentionally left
     /// alone. Used for both live preview and final apply in the theme selector.
     fn apply_theme_named(&mut self, name: &str) {
         self.theme = Theme::from_name(name);
+        self.render_cache.invalidate();
         self.editor.set_block(
             Block::default()
                 .borders(Borders::ALL)
@@ -1300,7 +3169,8 @@ impl App {
         if let Some(entry) = self.theme_picker.themes.get(self.theme_picker.selected) {
             let name = entry.name.clone();
             self.config.theme = name.clone();
-            let _ = self.config.save();
+            self.base_config.theme = name.clone();
+            let _ = self.base_config.save();
             self.apply_theme_named(&name);
             self.status_message = Some(format!("Theme: {}", name));
         }
@@ -1318,6 +3188,220 @@ impl App {
         self.theme_picker = ThemePicker::default();
     }
 
+    fn load_macros(&mut self) {
+        let warnings = self.vim.macros.load(&macros_path());
+        if let Some(first) = warnings.first() {
+            self.status_message = Some(if warnings.len() > 1 {
+                format!("{} (+{} more)", first, warnings.len() - 1)
+            } else {
+                first.clone()
+            });
+        }
+    }
+
+    /// Persist the uppercase marks; called whenever one is set.
+    pub fn save_marks(&mut self) {
+        if let Err(e) = self.vim.marks.save_global(&marks_path()) {
+            self.vim.status_message = Some(format!("Failed to save marks: {}", e));
+        }
+    }
+
+    /// Open the note at `path` in the editor, for a jump to an uppercase mark
+    /// set in another note. Returns false, with a status message, if the
+    /// current buffer has unsaved changes or the note no longer exists.
+    pub fn open_note_for_mark(&mut self, path: &std::path::Path) -> bool {
+        if self.has_unsaved_changes() {
+            self.vim.status_message = Some("No write since last change".to_string());
+            return false;
+        }
+        let Some(note_idx) = self
+            .notes
+            .iter()
+            .position(|n| n.file_path.as_deref() == Some(path))
+        else {
+            self.vim.status_message = Some(format!("Mark note not found: {}", path.display()));
+            return false;
+        };
+
+        self.cancel_edit();
+        self.expand_folders_to(path);
+        self.select_note_by_path(&path.to_path_buf());
+        self.push_navigation_history(note_idx);
+        self.content_cursor = 0;
+        self.content_scroll_offset = 0;
+        self.mark_reading_jump();
+        self.enter_edit_mode();
+        true
+    }
+
+    /// Persist named macros; called whenever one is recorded, deleted or renamed.
+    pub fn save_macros(&mut self) {
+        if let Err(e) = self.vim.macros.save(&macros_path()) {
+            self.vim.status_message = Some(format!("Failed to save macros: {}", e));
+        }
+    }
+
+    pub fn open_macro_list(&mut self) {
+        if self.vim.macros.registers().is_empty() {
+            self.vim.status_message = Some("No macros recorded".to_string());
+            return;
+        }
+        self.macro_list = MacroList::default();
+        self.dialog = DialogState::MacroList;
+    }
+
+    pub fn close_macro_list(&mut self) {
+        self.dialog = DialogState::None;
+        self.macro_list = MacroList::default();
+    }
+
+    pub fn open_yank_list(&mut self) {
+        if self.editor.yank_ring().is_empty() {
+            self.vim.status_message = Some("Nothing yanked yet".to_string());
+            return;
+        }
+        self.yank_list = YankList::default();
+        self.dialog = DialogState::YankList;
+    }
+
+    pub fn close_yank_list(&mut self) {
+        self.dialog = DialogState::None;
+        self.yank_list = YankList::default();
+    }
+
+    /// Paste the yank picked in the `:yanks` list after the cursor, in full
+    /// however much of it the list showed.
+    pub fn paste_selected_yank(&mut self) {
+        let index = self.yank_list.selected;
+        self.close_yank_list();
+        if self.editor.paste_yank(index) {
+            self.update_editor_highlights();
+        }
+    }
+
+    fn selected_macro_register(&self) -> Option<char> {
+        self.vim.macros.registers().get(self.macro_list.selected).copied()
+    }
+
+    pub fn macro_list_select_next(&mut self) {
+        let len = self.vim.macros.registers().len();
+        if self.macro_list.selected + 1 < len {
+            self.macro_list.selected += 1;
+        }
+    }
+
+    pub fn macro_list_select_prev(&mut self) {
+        self.macro_list.selected = self.macro_list.selected.saturating_sub(1);
+    }
+
+    pub fn delete_selected_macro(&mut self) {
+        let Some(register) = self.selected_macro_register() else {
+            return;
+        };
+        self.vim.macros.delete(register);
+        self.save_macros();
+        self.vim.status_message = Some(format!("Deleted macro @{}", register));
+
+        let len = self.vim.macros.registers().len();
+        if len == 0 {
+            self.close_macro_list();
+        } else {
+            self.macro_list.selected = self.macro_list.selected.min(len - 1);
+        }
+    }
+
+    pub fn rename_selected_macro(&mut self, to: char) {
+        self.macro_list.renaming = false;
+        let Some(from) = self.selected_macro_register() else {
+            return;
+        };
+        if !to.is_ascii_lowercase() || !self.vim.macros.rename(from, to) {
+            return;
+        }
+        self.save_macros();
+        self.vim.status_message = Some(format!("Moved macro @{} to @{}", from, to));
+        self.macro_list.selected = self
+            .vim
+            .macros
+            .registers()
+            .iter()
+            .position(|&r| r == to)
+            .unwrap_or(0);
+    }
+
+    /// `:history`: list the snapshots taken of the current note on save.
+    pub fn open_history(&mut self) {
+        let Some(path) = self.current_note().and_then(|n| n.file_path.clone()) else {
+            return;
+        };
+        let entries = snapshots::list(&self.config.notes_path(), &path);
+        if entries.is_empty() {
+            let message = if self.config.snapshots.keep == 0 {
+                "No snapshots of this note; set snapshots.keep to take them"
+            } else {
+                "No snapshots of this note yet"
+            };
+            self.set_status(message.to_string());
+            return;
+        }
+        self.history = HistoryList { entries, ..HistoryList::default() };
+        self.dialog = DialogState::History;
+    }
+
+    pub fn close_history(&mut self) {
+        self.dialog = DialogState::None;
+        self.history = HistoryList::default();
+    }
+
+    pub fn history_select_next(&mut self) {
+        if self.history.selected + 1 < self.history.entries.len() {
+            self.history.selected += 1;
+        }
+    }
+
+    pub fn history_select_prev(&mut self) {
+        self.history.selected = self.history.selected.saturating_sub(1);
+    }
+
+    /// Show the selected snapshot's text in place of the list.
+    pub fn view_selected_snapshot(&mut self) {
+        let Some(snapshot) = self.history.entries.get(self.history.selected) else {
+            return;
+        };
+        match fs::read_to_string(&snapshot.path) {
+            Ok(text) => self.history.viewing = Some((text, 0)),
+            Err(e) => self.set_status(format!("Failed to read snapshot: {}", e)),
+        }
+    }
+
+    /// Put the selected snapshot back as the note. The note as it is now is
+    /// snapshotted first, so a restore can itself be undone from `:history`.
+    pub fn restore_selected_snapshot(&mut self) {
+        let Some(snapshot) = self.history.entries.get(self.history.selected).cloned() else {
+            return;
+        };
+        let Some(path) = self.current_note().and_then(|n| n.file_path.clone()) else {
+            return;
+        };
+        if self.mode == Mode::Edit && self.has_unsaved_changes() {
+            self.set_status("No write since last change".to_string());
+            return;
+        }
+        let root = self.config.notes_path();
+        // Read before snapshotting, which may prune the one being restored
+        let result = fs::read(&snapshot.path).and_then(|bytes| {
+            snapshots::take(&root, &path, self.clock.local_now(), self.config.snapshots.keep)?;
+            fs::write(&path, bytes)
+        });
+        self.close_history();
+        if let Err(e) = result {
+            self.set_status(format!("Failed to restore snapshot: {}", e));
+            return;
+        }
+        self.reload_external_change();
+        self.set_status(format!("Restored the version from {}", snapshot.taken.format("%Y-%m-%d %H:%M:%S")));
+    }
+
     /// Journal mode (`t`): open today's daily note, creating it from a small
     /// dated template if it doesn't exist yet. The file is `journal.<date>.md`
     /// in the notes directory, using the user's *local* date. Either way the
@@ -1378,104 +3462,338 @@ impl App {
     }
 
     pub fn load_notes_from_dir(&mut self) {
-        self.notes.clear();
-        self.file_tree.clear();
         let notes_path = self.config.notes_path();
-
         if !notes_path.exists() {
             let _ = fs::create_dir_all(&notes_path);
         }
+        let (mut notes, mut errors) = (Vec::new(), Vec::new());
+        let (progress, cancel) = (AtomicUsize::new(0), AtomicBool::new(false));
+        let tree = background_io::scan_dir(
+            &notes_path,
+            self.config.show_empty_dir,
+            &self.folder_states,
+            &mut notes,
+            &mut errors,
+            &progress,
+            &cancel,
+        );
+        self.install_scan(Scan { tree, notes, errors });
+    }
+
+    /// Put the notes a scan of the notes folder found in place of the ones
+    /// loaded before. A folder whose notes are all unreadable gets the
+    /// ScanErrors dialog rather than passing for an empty one.
+    fn install_scan(&mut self, scan: Scan) {
+        let none_read = scan.notes.is_empty();
+        self.scan_errors = scan.errors;
+        if none_read
+            && !self.scan_errors.is_empty()
+            && matches!(self.dialog, DialogState::None | DialogState::EmptyDirectory)
+        {
+            self.scan_errors_scroll = 0;
+            self.dialog = DialogState::ScanErrors;
+        }
+        let dirty_path = self.dirty_note
+            .and_then(|i| self.notes.get(i))
+            .and_then(|n| n.file_path.clone());
+        let unlocked: Vec<Note> = self.notes
+            .drain(..)
+            .filter(|n| matches!(n.lock, NoteLock::Unlocked(_)))
+            .collect();
+        let notes_path = self.config.notes_path();
 
-        self.file_tree = self.build_tree(&notes_path, 0);
+        self.notes = scan.notes;
+        self.file_tree = scan.tree;
+        self.load_external_notes();
+        if self.pins.root() != notes_path {
+            self.pins = Pins::load(&pins_path(), &notes_path);
+        }
+        if self.pins.prune() {
+            self.save_pins();
+        }
+        // Notes unlocked before stay unlocked, unless their file changed since
+        for note in unlocked {
+            if let Some(reloaded) = self.notes.iter_mut().find(|n| {
+                n.lock == NoteLock::Locked && n.file_path == note.file_path && n.modified_time == note.modified_time
+            }) {
+                *reloaded = note;
+            }
+        }
+        // Kept edits follow their note to its new index, and go with it if deleted
+        self.dirty_note = dirty_path
+            .and_then(|path| self.notes.iter().position(|n| n.file_path.as_ref() == Some(&path)));
 
         // Sort the tree according to current sort mode
         self.sort_tree();
 
         self.rebuild_sidebar_items();
 
-        self.selected_sidebar_index = 0;
+        self.selected_sidebar_index = self.first_selectable_sidebar_index();
         self.sync_selected_note_from_sidebar();
 
         self.update_content_items();
         self.update_outline();
     }
 
-    fn build_tree(&mut self, dir: &PathBuf, depth: usize) -> Vec<FileTreeItem> {
-        let mut items = Vec::new();
+    /// Load the notes folder, build the search index and select `select`.
+    /// With `background_io` the folder is scanned on a thread instead, and
+    /// [`App::poll_notes_scan`] does the rest once it's in.
+    fn load_notes_and_select(&mut self, select: Option<PathBuf>) {
+        if !self.config.background_io {
+            self.load_notes_from_dir();
+            self.start_index_build();
+            if let Some(path) = select {
+                self.select_note_by_path(&path);
+            }
+            return;
+        }
+        if let Some(job) = self.scan_job.take() {
+            job.cancel();
+        }
+        let mut job = ScanJob::start(self.config.notes_path(), self.config.show_empty_dir, self.folder_states.clone());
+        job.select = select;
+        self.scan_job = Some(job);
+    }
 
-        if let Ok(entries) = fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
+    /// Take in a finished background scan. Returns whether one finished.
+    pub fn poll_notes_scan(&mut self) -> bool {
+        // Edit mode stays on its note; the scan comes in once it's left
+        if self.mode == Mode::Edit {
+            return false;
+        }
+        let Some(result) = self.scan_job.as_ref().and_then(|job| job.poll()) else {
+            return false;
+        };
+        let job = self.scan_job.take().expect("polled scan");
+        let results = job.ops_done();
+        self.file_ops_done(&job.ops, &results);
+        match result {
+            Some(scan) => {
+                self.install_scan(scan);
+                self.start_index_build();
+                if let Some(path) = job.select {
+                    self.select_note_by_path(&path);
+                }
+                if let Some(then) = job.then {
+                    self.finish_file_ops(&job.ops, &results, then);
+                }
+            }
+            None => self.set_status("Scan of the notes folder cancelled".to_string()),
+        }
+        true
+    }
 
-                if path.is_dir() {
-                    if path.file_name()
-                        .map(|n| n.to_string_lossy().starts_with('.'))
-                        .unwrap_or(false)
-                    {
-                        continue;
-                    }
+    /// Rename or delete files from the sidebar, load the notes folder again
+    /// and do `then`. With `background_io` the files are changed on the
+    /// scan's thread and [`App::poll_notes_scan`] does the rest.
+    fn run_file_ops(&mut self, ops: Vec<FileOp>, stop_at_failure: bool, then: FileOpsThen) {
+        // Folders open in the sidebar stay open under their new name
+        for op in &ops {
+            if let FileOp::Rename { from, to } = op {
+                let moved: Vec<(PathBuf, bool)> = self
+                    .folder_states
+                    .iter()
+                    .filter_map(|(path, &expanded)| Some((to.join(path.strip_prefix(from).ok()?), expanded)))
+                    .collect();
+                self.folder_states.extend(moved);
+            }
+        }
+        if !self.config.background_io {
+            let results = background_io::run_ops(&ops, stop_at_failure);
+            self.file_ops_done(&ops, &results);
+            self.load_notes_from_dir();
+            self.finish_file_ops(&ops, &results, then);
+            return;
+        }
+        if self.scan_job.as_ref().is_some_and(|job| !job.ops.is_empty()) {
+            self.show_error_toast("Still working on the last rename or delete".to_string());
+            return;
+        }
+        if let Some(job) = self.scan_job.take() {
+            job.cancel();
+        }
+        let expanded = self.folder_states.clone();
+        let mut job =
+            ScanJob::after(ops, stop_at_failure, self.config.notes_path(), self.config.show_empty_dir, expanded);
+        job.then = Some(then);
+        self.scan_job = Some(job);
+    }
+
+    /// Carry pins, snapshots and open folders over to the paths `ops` moved
+    /// them to, and forget the ones deleted. Done before the notes that are
+    /// left are loaded, which drops pins to paths that are gone.
+    fn file_ops_done(&mut self, ops: &[FileOp], results: &[io::Result<()>]) {
+        for (op, result) in ops.iter().zip(results) {
+            if result.is_err() {
+                continue;
+            }
+            match op {
+                FileOp::Rename { from, to } => {
+                    self.folder_states.retain(|path, _| !path.starts_with(from));
+                    self.path_moved(from, to);
+                }
+                FileOp::Remove(path) => self.folder_states.retain(|folder, _| !folder.starts_with(path)),
+            }
+        }
+    }
 
-                    let children = self.build_tree(&path, depth + 1);
+    /// The rest of a rename or delete, once the notes folder is loaded again.
+    fn finish_file_ops(&mut self, ops: &[FileOp], results: &[io::Result<()>], then: FileOpsThen) {
+        let failure = results.iter().find_map(|result| result.as_ref().err());
+        match then {
+            FileOpsThen::Deleted | FileOpsThen::DeletedMarked => {
+                if self.selected_sidebar_index >= self.sidebar_items.len() {
+                    self.selected_sidebar_index = self.sidebar_items.len().saturating_sub(1);
+                }
+                self.sync_selected_note_from_sidebar();
+                self.update_content_items();
+                self.update_outline();
+                let deleted = results.iter().filter(|result| result.is_ok()).count();
+                if matches!(then, FileOpsThen::DeletedMarked) {
+                    self.sidebar_marks.clear();
+                    self.status_message = Some(if deleted == ops.len() {
+                        format!("Deleted {} items", deleted)
+                    } else {
+                        format!("Deleted {} of {} items", deleted, ops.len())
+                    });
+                } else if let Some(e) = failure {
+                    self.show_error_toast(format!("Failed to delete: {}", e));
+                }
+            }
+            FileOpsThen::RenamedNote(path) => match failure {
+                None => {
+                    self.expand_folders_to(&path);
+                    self.select_note_by_path(&path);
+                }
+                Some(e) => self.show_error_toast(format!("Failed to rename note: {}", e)),
+            },
+            FileOpsThen::RenamedFolder(path) => {
+                if let Some(e) = failure {
+                    self.show_error_toast(format!("Failed to rename folder: {}", e));
+                    return;
+                }
+                let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                let selected = self.sidebar_items.iter().position(|item| match &item.kind {
+                    SidebarItemKind::Folder { path: folder, .. } => folder == &path || item.display_name == name,
+                    _ => false,
+                });
+                if let Some(index) = selected {
+                    self.selected_sidebar_index = index;
+                }
+                self.update_content_items();
+                self.update_outline();
+            }
+            FileOpsThen::BulkRenamed { bulk, current } => self.finish_bulk_rename(bulk, current, results),
+        }
+    }
 
-                    if self.config.show_empty_dir || Self::tree_has_notes(&children) {
-                        let name = path.file_name()
-                            .map(|n| n.to_string_lossy().to_string())
-                            .unwrap_or_default();
+    /// Report the background saves that finished. Returns whether any did.
+    pub fn poll_saves(&mut self) -> bool {
+        let mut finished = false;
+        let mut i = 0;
+        while i < self.save_jobs.len() {
+            match self.save_jobs[i].poll() {
+                Some(saved) => {
+                    let job = self.save_jobs.remove(i);
+                    self.finish_save(job, saved);
+                    finished = true;
+                }
+                None => i += 1,
+            }
+        }
+        finished
+    }
 
-                        let expanded = self.folder_states
-                            .get(&path)
-                            .copied()
-                            .unwrap_or(false);
+    /// Wait for the saves still being written, when quitting. Returns the
+    /// errors, to print once the terminal is restored.
+    pub fn finish_saves(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.save_jobs)
+            .into_iter()
+            .filter_map(|job| {
+                let path = job.path.clone();
+                job.wait().written.err().map(|e| format!("Failed to save {}: {}", path.display(), e))
+            })
+            .collect()
+    }
 
-                        items.push(FileTreeItem::Folder {
-                            name,
-                            path,
-                            expanded,
-                            children,
-                            depth,
-                        });
-                    }
-                } else if path.extension().map(|e| e == "md").unwrap_or(false) {
-                    if let Ok(content) = fs::read_to_string(&path) {
-                        let title = path.file_stem()
-                            .map(|n| n.to_string_lossy().to_string())
-                            .unwrap_or_default();
-
-                        let (modified_time, created_time) = fs::metadata(&path)
-                            .map(|m| (m.modified().ok(), m.created().ok()))
-                            .unwrap_or((None, None));
-
-                        // Parse frontmatter
-                        let (frontmatter, content_start_line) = super::frontmatter::Frontmatter::parse(&content);
-
-                        let note_index = self.notes.len();
-                        self.notes.push(Note {
-                            title,
-                            content,
-                            file_path: Some(path),
-                            modified_time,
-                            created_time,
-                            frontmatter,
-                            content_start_line,
-                        });
+    /// Whether the viewed note is still being written in the background.
+    pub fn is_saving_current_note(&self) -> bool {
+        let path = self.current_note().and_then(|n| n.file_path.as_ref());
+        path.is_some_and(|path| self.save_jobs.iter().any(|job| &job.path == path))
+    }
 
-                        items.push(FileTreeItem::Note {
-                            note_index,
-                            depth,
-                        });
-                    }
+    /// A background save is done: take in the new modified time, or keep the
+    /// edits it failed to write as kept edits when the editor still holds
+    /// them, the same as leaving edit mode with `esc_behavior = "keep"`.
+    fn finish_save(&mut self, job: SaveJob, saved: Saved) {
+        if let Err(e) = saved.snapshot {
+            self.show_error_toast(format!("Failed to snapshot note: {}", e));
+        }
+        let index = self.notes.iter().position(|n| n.file_path.as_ref() == Some(&job.path));
+        let e = match saved.written {
+            Ok(modified_time) => {
+                if let Some(note) = index.and_then(|i| self.notes.get_mut(i)) {
+                    note.modified_time = modified_time;
+                }
+                if self.mode == Mode::Normal {
+                    self.sort_tree();
+                    self.rebuild_sidebar_items();
+                    self.select_current_note_in_sidebar();
                 }
+                return;
             }
+            Err(e) => e,
+        };
+
+        let Some(index) = index else {
+            self.show_error_toast(format!("Failed to save {}: {}", job.path.display(), e));
+            return;
+        };
+        // Newer text saved since isn't undone by this save failing
+        let latest = self.notes[index].content == job.content;
+        let editing = latest && self.mode == Mode::Edit && self.selected_note == index;
+        let keep = latest
+            && self.mode == Mode::Normal
+            && self.dirty_note.is_none()
+            && super::line_ending::same_text(&self.editor.lines(), &job.content);
+        if editing || keep {
+            let note = &mut self.notes[index];
+            let (frontmatter, content_start_line) = super::frontmatter::Frontmatter::parse(&job.previous);
+            note.content = job.previous;
+            note.frontmatter = frontmatter;
+            note.content_start_line = content_start_line;
+        }
+        let title = self.notes[index].title.clone();
+        if keep {
+            self.dirty_note = Some(index);
+            self.update_content_items();
+            self.update_outline();
+            self.show_error_toast(format!("Failed to save {}: {}; the edits are kept", title, e));
+        } else {
+            self.show_error_toast(format!("Failed to save {}: {}", title, e));
         }
+    }
 
-        items
+    /// Esc while the notes folder is scanned in the background: stop it.
+    /// Returns whether there was a scan to stop.
+    pub fn cancel_notes_scan(&mut self) -> bool {
+        match &self.scan_job {
+            Some(job) => {
+                job.cancel();
+                true
+            }
+            None => false,
+        }
     }
 
-    fn tree_has_notes(items: &[FileTreeItem]) -> bool {
-        items.iter().any(|item| match item {
-            FileTreeItem::Note { .. } => true,
-            FileTreeItem::Folder { children, .. } => Self::tree_has_notes(children),
-        })
+    /// "Scanning notes…" with how many are read so far, for the status bar.
+    pub fn notes_scan_status(&self) -> Option<String> {
+        self.scan_job
+            .as_ref()
+            .map(|job| {
+                let doing = if job.ops.is_empty() { "Scanning notes…" } else { "Updating files, then scanning…" };
+                format!("{} {} read (Esc to stop)", doing, job.notes_read())
+            })
     }
 
     fn sort_tree(&mut self) {
@@ -1574,8 +3892,28 @@ impl App {
 
     pub fn rebuild_sidebar_items(&mut self) {
         self.sidebar_items.clear();
+        let since = self.recent_filter_since();
+
+        // Pinned notes and folders go above the tree, in pin order
+        let tree = std::mem::take(&mut self.file_tree);
+        for path in self.pins.paths().collect::<Vec<_>>() {
+            if let Some(item) = Self::find_tree_item(&tree, &path, &self.notes) {
+                if !Self::tree_item_modified_since(item, &self.notes, since) {
+                    continue;
+                }
+                if self.sidebar_items.is_empty() {
+                    self.sidebar_items.push(SidebarItem {
+                        kind: SidebarItemKind::Header,
+                        depth: 0,
+                        display_name: "Pinned".to_string(),
+                    });
+                }
+                self.push_tree_item(item, 1, since);
+            }
+        }
+        self.file_tree = tree;
 
-        // Add root folder first
+        // Then the root folder
         let notes_path = self.config.notes_path();
         let root_name = notes_path
             .file_name()
@@ -1598,38 +3936,796 @@ impl App {
 
         // Only add children if root is expanded
         if root_expanded {
-            let tree_clone = self.file_tree.clone();
-            self.flatten_tree_into_sidebar(&tree_clone, 1); // Start at depth 1
+            let tree = std::mem::take(&mut self.file_tree);
+            self.flatten_tree_into_sidebar(&tree, 1, since); // Start at depth 1
+            self.file_tree = tree;
+        }
+
+        // Files from outside the notes directory stand on their own after the vault
+        for path in &self.external_files {
+            let found = self.notes.iter().position(|n| n.file_path.as_ref() == Some(path));
+            if let Some(note_index) = found.filter(|&i| Self::note_modified_since(&self.notes[i], since)) {
+                self.sidebar_items.push(SidebarItem {
+                    kind: SidebarItemKind::Note { note_index },
+                    depth: 0,
+                    display_name: self.notes[note_index].title.clone(),
+                });
+            }
         }
     }
 
-    fn flatten_tree_into_sidebar(&mut self, items: &[FileTreeItem], depth_offset: usize) {
-        for item in items {
-            match item {
-                FileTreeItem::Folder { name, path, expanded, children, depth } => {
-                    self.sidebar_items.push(SidebarItem {
-                        kind: SidebarItemKind::Folder {
-                            path: path.clone(),
-                            expanded: *expanded,
-                        },
-                        depth: *depth + depth_offset,
-                        display_name: name.clone(),
-                    });
+    /// Re-read the files in `external_files`, dropping any that can no longer be read.
+    fn load_external_notes(&mut self) {
+        for path in std::mem::take(&mut self.external_files) {
+            if let Ok(note) = Note::read(&path) {
+                self.notes.push(note);
+                self.external_files.push(path);
+            }
+        }
+    }
 
-                    if *expanded {
-                        self.flatten_tree_into_sidebar(children, depth_offset);
-                    }
+    /// Open `path` for editing. Notes in the vault are selected as usual; any
+    /// other file is added to the sidebar as a standalone entry and saved back
+    /// to its own path.
+    pub fn open_file(&mut self, path: &Path) -> Result<(), String> {
+        let expanded = PathBuf::from(shellexpand::tilde(&path.to_string_lossy()).to_string());
+        let path = paths::canonicalize(&expanded)
+            .map_err(|_| format!("Can't open file: {}", expanded.display()))?;
+        if !path.is_file() {
+            return Err(format!("Not a file: {}", path.display()));
+        }
+
+        let notes_root = self.config.notes_path();
+        let in_vault = paths::canonicalize(&notes_root)
+            .ok()
+            .and_then(|root| path.strip_prefix(root).ok().map(|rel| notes_root.join(rel)));
+        if let Some(vault_path) = in_vault {
+            self.expand_folders_to(&vault_path);
+            self.select_note_by_path(&vault_path);
+            if self.current_note().and_then(|n| n.file_path.as_ref()) == Some(&vault_path) {
+                return Ok(());
+            }
+            // Not listed in the vault (hidden folder or not markdown): open it standalone
+        }
+
+        if !self.external_files.contains(&path) {
+            let note = Note::read(&path)
+                .map_err(|e| format!("Can't read {}: {}", path.display(), e))?;
+            self.notes.push(note);
+            self.external_files.push(path.clone());
+            self.rebuild_sidebar_items();
+        }
+        self.select_note_by_path(&path);
+        Ok(())
+    }
+
+    /// Paths of the notes in the notes directory, leaving out files opened with `:e`.
+    fn vault_note_paths(&self) -> Vec<PathBuf> {
+        self.notes
+            .iter()
+            .filter_map(|note| note.file_path.clone())
+            .filter(|path| !self.external_files.contains(path))
+            .collect()
+    }
+
+    /// The note being edited, if its buffer has changes not yet written.
+    fn unsaved_note_path(&self) -> Option<PathBuf> {
+        if let Some(i) = self.dirty_note {
+            self.notes.get(i)?.file_path.clone()
+        } else if self.mode == Mode::Edit && self.has_unsaved_changes() {
+            self.current_note()?.file_path.clone()
+        } else {
+            None
+        }
+    }
+
+    /// Start `:gsub`: count the matches in every note, then either report them
+    /// (`n` flag) or ask before writing anything. A note with unsaved changes
+    /// in the editor is left alone.
+    pub fn start_global_substitute(&mut self, pattern: String, replacement: String, flags: SubstituteFlags) {
+        let paths = self.vault_note_paths();
+        let skip = self.unsaved_note_path();
+        let preview = global_substitute::substitute_notes(&paths, &pattern, &replacement, &flags, skip.as_deref(), false);
+
+        if preview.files.is_empty() {
+            let mut message = format!("Pattern not found: {}", pattern);
+            if !preview.skipped.is_empty() {
+                message.push_str(&global_substitute::skipped_notes_suffix(&preview.skipped));
+            }
+            self.vim.status_message = Some(message);
+            return;
+        }
+        if flags.report_only {
+            let entries = self.global_substitute_entries(&preview.files, &pattern, &flags);
+            self.set_quickfix(format!("gsub /{}/", pattern), entries);
+            self.vim.status_message = Some(format!(
+                "{} match{} in {} note{}{}",
+                preview.occurrences(),
+                if preview.occurrences() == 1 { "" } else { "es" },
+                preview.files.len(),
+                if preview.files.len() == 1 { "" } else { "s" },
+                global_substitute::skipped_notes_suffix(&preview.skipped),
+            ));
+            return;
+        }
+
+        self.pending_global_substitute = Some(GlobalSubstitute {
+            pattern,
+            replacement,
+            flags,
+            preview,
+        });
+        self.dialog = DialogState::GlobalSubstituteConfirm;
+    }
+
+    /// Quickfix entries for each match of a `:gsub` in the notes at `files`.
+    fn global_substitute_entries(&self, files: &[(PathBuf, usize)], pattern: &str, flags: &SubstituteFlags) -> Vec<QuickfixEntry> {
+        let mut entries = Vec::new();
+        for (path, _) in files {
+            let Some(note) = self.notes.iter().position(|n| n.file_path.as_ref() == Some(path)) else {
+                continue;
+            };
+            let lines: Vec<&str> = self.notes[note].content.lines().collect();
+            let region = Region::lines(0, lines.len().saturating_sub(1));
+            for (line, col) in find_matches(&lines, &region, pattern, flags) {
+                entries.push(QuickfixEntry {
+                    note,
+                    line,
+                    col,
+                    text: lines[line].trim().to_string(),
+                });
+            }
+        }
+        entries
+    }
+
+    /// Write the confirmed `:gsub` to disk and reload the notes it changed.
+    pub fn run_global_substitute(&mut self) {
+        let Some(gsub) = self.pending_global_substitute.take() else {
+            return;
+        };
+        let paths: Vec<PathBuf> = gsub.preview.files.iter().map(|(path, _)| path.clone()).collect();
+        let skip = self.unsaved_note_path();
+        let result = global_substitute::substitute_notes(
+            &paths,
+            &gsub.pattern,
+            &gsub.replacement,
+            &gsub.flags,
+            skip.as_deref(),
+            true,
+        );
+
+        let current_path = self.current_note().and_then(|n| n.file_path.clone());
+        for (path, _) in &result.files {
+            if let Some(note) = self.notes.iter_mut().find(|n| n.file_path.as_ref() == Some(path)) {
+                if let Ok(fresh) = Note::read(path) {
+                    *note = fresh;
                 }
-                FileTreeItem::Note { note_index, depth } => {
-                    self.sidebar_items.push(SidebarItem {
-                        kind: SidebarItemKind::Note {
-                            note_index: *note_index,
-                        },
-                        depth: *depth + depth_offset,
-                        display_name: self.notes[*note_index].title.clone(),
-                    });
+            }
+        }
+
+        // The open buffer has no changes of its own here, so reload it from disk
+        let current_changed = result.files.iter().any(|(path, _)| Some(path) == current_path.as_ref());
+        if current_changed && self.mode == Mode::Edit {
+            let cursor = self.editor.cursor();
+            self.enter_edit_mode();
+            let last_row = self.editor.lines().len().saturating_sub(1);
+            self.editor.set_cursor(cursor.0.min(last_row), cursor.1);
+        }
+
+        self.sort_tree();
+        self.rebuild_sidebar_items();
+        self.select_current_note_in_sidebar();
+        if self.mode == Mode::Normal {
+            self.update_content_items();
+        }
+
+        let mut skipped = gsub.preview.skipped;
+        skipped.extend(result.skipped.iter().cloned());
+        self.vim.status_message = Some(format!(
+            "{} substitution{} in {} note{}{}",
+            result.occurrences(),
+            if result.occurrences() == 1 { "" } else { "s" },
+            result.files.len(),
+            if result.files.len() == 1 { "" } else { "s" },
+            global_substitute::skipped_notes_suffix(&skipped),
+        ));
+    }
+
+    /// Title of the heading on the editor's cursor line.
+    pub fn heading_at_cursor(&self) -> Option<String> {
+        let (row, _) = self.editor.cursor();
+        let lines = self.editor.lines();
+        let (_, title) = heading_refactor::parse_heading(lines.get(row)?)?;
+        Some(title.to_string())
+    }
+
+    /// Whether a link in the note at `from` points at note `note`.
+    fn link_points_at(&self, from: &Path, target: LinkTarget, note: usize) -> bool {
+        let note_path = self.notes.get(note).and_then(|n| n.file_path.as_ref());
+        match target {
+            LinkTarget::Wiki("") | LinkTarget::Path("") => note_path.is_some_and(|p| p == from),
+            LinkTarget::Wiki(name) => self.resolve_wiki_link(name) == Some(note),
+            LinkTarget::Path(path) => {
+                let Some(dir) = from.parent() else {
+                    return false;
+                };
+                let linked = paths::canonicalize(&dir.join(percent_decode(path)));
+                note_path.is_some_and(|p| linked.ok() == paths::canonicalize(p).ok())
+            }
+        }
+    }
+
+    /// `:rename-heading`: retitle the heading on the cursor line and update
+    /// the links to it in this note, as one undo step. Links from other notes
+    /// are updated once the HeadingRenameConfirm dialog is answered.
+    pub fn rename_heading(&mut self, new_title: &str) {
+        let new_title = new_title.trim();
+        let (row, col) = self.editor.cursor();
+        let note = self.selected_note;
+        let Some(from) = self.current_note().and_then(|n| n.file_path.clone()) else {
+            return;
+        };
+        let (old_slug, changes, local) = {
+            let lines = self.editor.lines();
+            let Some((level, old_title)) = lines.get(row).and_then(|line| heading_refactor::parse_heading(line)) else {
+                self.vim.status_message = Some("Not on a heading".to_string());
+                return;
+            };
+            if new_title.is_empty() || new_title == old_title {
+                return;
+            }
+            let old_slug = slugify_heading(old_title);
+            let (renamed, local) = heading_refactor::rename_heading_links(&lines.join("\n"), &old_slug, new_title, |target| {
+                self.link_points_at(&from, target, note)
+            });
+            let mut changes: Vec<(usize, String)> = renamed
+                .split('\n')
+                .zip(&lines)
+                .enumerate()
+                .filter(|(_, (new, old))| new != *old)
+                .map(|(i, (new, _))| (i, new.to_string()))
+                .collect();
+            changes.retain(|(i, _)| *i != row);
+            changes.push((row, format!("{} {}", "#".repeat(level), new_title)));
+            changes.sort_by_key(|(i, _)| *i);
+            (old_slug, changes, local)
+        };
+        self.editor.replace_lines(&changes);
+        self.editor.set_cursor(row, col);
+        self.update_editor_highlights();
+
+        let paths: Vec<PathBuf> = self.vault_note_paths().into_iter().filter(|path| *path != from).collect();
+        let preview = heading_refactor::rename_heading_in_notes(
+            &paths,
+            &old_slug,
+            new_title,
+            |path, target| self.link_points_at(path, target, note),
+            false,
+        );
+        self.vim.status_message = Some(format!(
+            "Renamed heading, updated {} link{} in this note",
+            local,
+            if local == 1 { "" } else { "s" },
+        ));
+        if !preview.files.is_empty() {
+            self.pending_heading_rename = Some(HeadingRename {
+                note,
+                old_slug,
+                new_title: new_title.to_string(),
+                preview,
+            });
+            self.dialog = DialogState::HeadingRenameConfirm;
+        }
+    }
+
+    /// Update the other notes' links to the heading renamed by
+    /// [`App::rename_heading`] and reload them.
+    pub fn run_heading_rename(&mut self) {
+        let Some(rename) = self.pending_heading_rename.take() else {
+            return;
+        };
+        let paths: Vec<PathBuf> = rename.preview.files.iter().map(|(path, _)| path.clone()).collect();
+        let result = heading_refactor::rename_heading_in_notes(
+            &paths,
+            &rename.old_slug,
+            &rename.new_title,
+            |path, target| self.link_points_at(path, target, rename.note),
+            true,
+        );
+        for (path, _) in &result.files {
+            if let Some(note) = self.notes.iter_mut().find(|n| n.file_path.as_ref() == Some(path)) {
+                if let Ok(fresh) = Note::read(path) {
+                    *note = fresh;
+                }
+            }
+        }
+        self.sort_tree();
+        self.rebuild_sidebar_items();
+        self.select_current_note_in_sidebar();
+
+        let links = result.occurrences();
+        self.vim.status_message = Some(format!(
+            "Updated {} link{} in {} note{}{}",
+            links,
+            if links == 1 { "" } else { "s" },
+            result.files.len(),
+            if result.files.len() == 1 { "" } else { "s" },
+            global_substitute::skipped_notes_suffix(&result.skipped),
+        ));
+    }
+
+    /// `:merge [note]`: merge the named note into the current one, or pick
+    /// it from the note picker without a name. Nothing changes until the
+    /// MergeConfirm dialog is answered.
+    pub fn start_merge(&mut self, name: Option<&str>) {
+        let Some(name) = name else {
+            self.open_picker(PickerPurpose::Merge);
+            return;
+        };
+        let name = name.trim().trim_start_matches("[[").trim_end_matches("]]");
+        match self.resolve_wiki_link(name) {
+            Some(source) => self.choose_merge_source(source),
+            None => self.set_status(format!("No note named {}", name)),
+        }
+    }
+
+    /// Work out what merging note `source` into the current one would change
+    /// and ask before doing it.
+    fn choose_merge_source(&mut self, source: usize) {
+        let target = self.selected_note;
+        let (Some(source_note), Some(target_note)) = (self.notes.get(source), self.notes.get(target)) else {
+            return;
+        };
+        if source == target {
+            self.set_status("Can't merge a note into itself".to_string());
+            return;
+        }
+        if source_note.file_path.is_none() || target_note.file_path.is_none() {
+            self.set_status("Only saved notes can be merged".to_string());
+            return;
+        }
+        if source_note.lock != NoteLock::Plain || target_note.lock != NoteLock::Plain {
+            self.set_status("Encrypted notes can't be merged".to_string());
+            return;
+        }
+        if self.dirty_note.is_some_and(|i| i == source || i == target) {
+            self.set_status("Save or discard the kept edits before merging".to_string());
+            return;
+        }
+
+        // Named as a link to it would be once the other note is gone
+        let title_shared = self.notes.iter().enumerate().any(|(i, n)| i != source && i != target && n.title == target_note.title);
+        let link_target = if title_shared {
+            self.get_wiki_path_for_note(target).unwrap_or_else(|| target_note.title.clone())
+        } else {
+            target_note.title.clone()
+        };
+        let source_path = source_note.file_path.clone();
+        let paths: Vec<PathBuf> =
+            self.vault_note_paths().into_iter().filter(|path| Some(path) != source_path.as_ref()).collect();
+        let preview =
+            note_merge::relink_notes(&paths, &link_target, |name| self.resolve_wiki_link(name) == Some(source), false);
+        self.pending_merge = Some(NoteMerge { source, target, link_target, preview });
+        self.dialog = DialogState::MergeConfirm;
+    }
+
+    /// Carry out the merge [`App::start_merge`] asked about: point the links
+    /// at the current note, add the other note's text to it and move the
+    /// other note to the trash.
+    pub fn run_merge(&mut self) {
+        let Some(merge) = self.pending_merge.take() else {
+            return;
+        };
+        let (Some(source), Some(target)) = (self.notes.get(merge.source), self.notes.get(merge.target)) else {
+            return;
+        };
+        let (Some(source_path), Some(target_path)) = (source.file_path.clone(), target.file_path.clone()) else {
+            return;
+        };
+        let source_title = source.title.clone();
+        let links_here = |name: &str| self.resolve_wiki_link(name) == Some(merge.source);
+        let (source_text, _) = note_merge::relink(&source.content, &merge.link_target, links_here);
+        let paths: Vec<PathBuf> = merge.preview.files.iter().map(|(path, _)| path.clone()).collect();
+        let relinked = note_merge::relink_notes(&paths, &merge.link_target, links_here, true);
+
+        // The current note's own links to the other note were just relinked
+        let mut note = match Note::read(&target_path) {
+            Ok(note) => note,
+            Err(e) => {
+                self.show_error_toast(format!("Failed to merge: {}", e));
+                return;
+            }
+        };
+        note.content = note_merge::merged_text(&note.content, &source_title, &source_text, self.config.editor.merge_style);
+        if let Err(e) = note.write(self.config.editor.line_ending) {
+            self.show_error_toast(format!("Failed to merge: {}", e));
+            return;
+        }
+        let trashed = note_merge::move_to_trash(&self.config.notes_path(), &source_path);
+
+        self.load_notes_from_dir();
+        self.start_index_build();
+        self.select_note_by_path(&target_path);
+        self.update_content_items();
+        self.update_outline();
+
+        let links = relinked.occurrences();
+        let files = relinked.files.len();
+        let status = format!(
+            "Merged {}, updated {} link{} in {} note{}{}",
+            source_title,
+            links,
+            if links == 1 { "" } else { "s" },
+            files,
+            if files == 1 { "" } else { "s" },
+            global_substitute::skipped_notes_suffix(&relinked.skipped),
+        );
+        match trashed {
+            Ok(_) => self.set_status(format!("{}, moved it to {}", status, note_merge::TRASH_DIR)),
+            Err(e) => self.show_error_toast(format!("{}, but couldn't move it to {}: {}", status, note_merge::TRASH_DIR, e)),
+        }
+    }
+
+    /// `:promote`/`:demote`: move the heading on the cursor line and its
+    /// subheadings `delta` levels, as one undo step.
+    pub fn shift_heading_section(&mut self, delta: isize) {
+        let (row, col) = self.editor.cursor();
+        let result = heading_refactor::shift_section(&self.editor.lines(), row, delta);
+        match result {
+            Ok(changes) => {
+                self.editor.replace_lines(&changes);
+                self.editor.set_cursor(row, col.saturating_add_signed(delta));
+                self.update_editor_highlights();
+                self.vim.status_message = Some(format!(
+                    "{} {} heading{}",
+                    if delta < 0 { "Promoted" } else { "Demoted" },
+                    changes.len(),
+                    if changes.len() == 1 { "" } else { "s" },
+                ));
+            }
+            Err(message) => self.vim.status_message = Some(message),
+        }
+    }
+
+    /// `:listify`: make rows `start..=end` `style` list items as one undo
+    /// step, keeping the cursor on the same text.
+    pub fn listify(&mut self, start: usize, end: usize, style: ListStyle, nested: bool) {
+        let lines = self.editor.lines();
+        let end = end.min(lines.len().saturating_sub(1));
+        if start > end {
+            return;
+        }
+        let converted = list_style::convert(&lines[start..=end], style, nested);
+        let (row, col) = self.editor.cursor();
+        let col = match row.checked_sub(start).and_then(|i| converted.get(i)) {
+            Some(new) => {
+                let (old_prefix, new_prefix) = (list_style::prefix_len(lines[row]), list_style::prefix_len(new));
+                if col >= old_prefix { col + new_prefix - old_prefix } else { col.min(new_prefix) }
+            }
+            None => col,
+        };
+        let changes: Vec<(usize, String)> = converted
+            .into_iter()
+            .enumerate()
+            .map(|(i, line)| (start + i, line))
+            .filter(|(row, line)| lines[*row] != line)
+            .collect();
+        if changes.is_empty() {
+            return;
+        }
+        self.editor.replace_lines(&changes);
+        self.editor.set_cursor(row, col);
+        self.update_editor_highlights();
+    }
+
+    /// `:set name=value`: change a layout setting for the rest of the session,
+    /// over the config file and the folder's own settings alike. Returns
+    /// what was set, or why it couldn't be.
+    pub fn set_option(&mut self, setting: &str) -> Result<String, String> {
+        let Some((name, value)) = setting.split_once('=') else {
+            return Err("Usage: :set name=value".to_string());
+        };
+        let (name, value) = (name.trim(), value.trim());
+        let width = || match value {
+            "" | "0" | "none" => Ok(None),
+            _ => value
+                .parse::<u16>()
+                .map(Some)
+                .map_err(|_| format!("{} takes a number of columns, or none", name)),
+        };
+        let apply: Box<dyn Fn(&mut Config)> = match name {
+            "reading.max_width" => {
+                let width = width()?;
+                Box::new(move |config| config.reading.max_width = width)
+            }
+            "editor.max_width" => {
+                let width = width()?;
+                Box::new(move |config| config.editor.max_width = width)
+            }
+            "reading.align" => {
+                let align = match value {
+                    "left" => ReadingAlign::Left,
+                    "center" => ReadingAlign::Center,
+                    _ => return Err("reading.align is left or center".to_string()),
+                };
+                Box::new(move |config| config.reading.align = align)
+            }
+            _ => return Err(format!("Unknown setting: {}", name)),
+        };
+        apply(&mut self.base_config);
+        apply(&mut self.config);
+        self.render_cache.invalidate();
+        Ok(format!("{}={}", name, value))
+    }
+
+    /// `:toc`: redo the note's table of contents in place, or put one in
+    /// below the cursor line, or over it when it's blank.
+    pub fn insert_toc(&mut self) {
+        if self.update_toc() {
+            return;
+        }
+        let lines = self.editor.lines();
+        let toc = toc::generate(&lines, self.config.toc.depth);
+        let (row, _) = self.editor.cursor();
+        let at = if lines.get(row).is_some_and(|line| line.trim().is_empty()) { row..row + 1 } else { row + 1..row + 1 };
+        self.editor.replace_rows(at, toc);
+        self.update_editor_highlights();
+        self.set_status("Table of contents added".to_string());
+    }
+
+    /// Bring the note's table of contents in line with its headings, as one
+    /// undo step. Returns false when the note has none.
+    fn update_toc(&mut self) -> bool {
+        let lines = self.editor.lines();
+        let Some((start, end)) = toc::find(&lines) else {
+            return false;
+        };
+        let toc = toc::generate(&lines, self.config.toc.depth);
+        if lines[start..=end] == toc[..] {
+            self.set_status("Table of contents is up to date".to_string());
+            return true;
+        }
+        let cursor = self.editor.cursor();
+        let shift = toc.len() as isize - (end + 1 - start) as isize;
+        self.editor.replace_rows(start..end + 1, toc);
+        // The cursor stays on its line, moved down or up by the change
+        let row = if cursor.0 > end { cursor.0.saturating_add_signed(shift) } else { cursor.0.min(start) };
+        self.editor.set_cursor(row, if cursor.0 > end || cursor.0 < start { cursor.1 } else { 0 });
+        self.update_editor_highlights();
+        self.set_status("Table of contents updated".to_string());
+        true
+    }
+
+    /// `gl`: the next kind of list for `count` lines from the cursor, after
+    /// the kind the cursor line is.
+    pub fn cycle_list_style(&mut self, count: usize) {
+        let row = self.editor.cursor().0;
+        let style = self.editor.lines().get(row).map_or(ListStyle::Plain, |line| ListStyle::of(line)).next();
+        self.listify(row, row + count.max(1) - 1, style, true);
+    }
+
+    /// Add `items` to the sidebar at `depth`, with the contents of expanded
+    /// folders below them. Pinned items are left out, as they're listed above
+    /// the tree.
+    fn flatten_tree_into_sidebar(&mut self, items: &[FileTreeItem], depth: usize, since: Option<std::time::SystemTime>) {
+        for item in items {
+            if !Self::tree_item_pinned(item, &self.pins, &self.notes) {
+                self.push_tree_item(item, depth, since);
+            }
+        }
+    }
+
+    /// `item` and what's under it, leaving out anything the recent filter
+    /// hides: notes modified before `since` and folders without any others.
+    fn push_tree_item(&mut self, item: &FileTreeItem, depth: usize, since: Option<std::time::SystemTime>) {
+        if !Self::tree_item_modified_since(item, &self.notes, since) {
+            return;
+        }
+        match item {
+            FileTreeItem::Folder { name, path, expanded, children, .. } => {
+                self.sidebar_items.push(SidebarItem {
+                    kind: SidebarItemKind::Folder {
+                        path: path.clone(),
+                        expanded: *expanded,
+                    },
+                    depth,
+                    display_name: name.clone(),
+                });
+
+                if *expanded {
+                    self.flatten_tree_into_sidebar(children, depth + 1, since);
+                }
+            }
+            FileTreeItem::Note { note_index, .. } => {
+                self.sidebar_items.push(SidebarItem {
+                    kind: SidebarItemKind::Note {
+                        note_index: *note_index,
+                    },
+                    depth,
+                    display_name: self.notes[*note_index].title.clone(),
+                });
+            }
+        }
+    }
+
+    fn note_modified_since(note: &Note, since: Option<std::time::SystemTime>) -> bool {
+        since.is_none_or(|since| note.modified_time.is_some_and(|modified| modified >= since))
+    }
+
+    fn tree_item_modified_since(item: &FileTreeItem, notes: &[Note], since: Option<std::time::SystemTime>) -> bool {
+        match item {
+            _ if since.is_none() => true,
+            FileTreeItem::Folder { children, .. } => {
+                children.iter().any(|child| Self::tree_item_modified_since(child, notes, since))
+            }
+            FileTreeItem::Note { note_index } => {
+                notes.get(*note_index).is_some_and(|note| Self::note_modified_since(note, since))
+            }
+        }
+    }
+
+    fn recent_filter_since(&self) -> Option<std::time::SystemTime> {
+        self.recent_filter.since(self.clock.local_now())
+    }
+
+    /// `f` in the sidebar: step through showing all notes, the ones modified
+    /// today and this week. An active search only looks at the notes shown.
+    pub fn cycle_recent_filter(&mut self) {
+        self.recent_filter = self.recent_filter.next();
+        self.refresh_sidebar_keeping_selection();
+        self.status_message = Some(format!("Showing notes modified: {}", self.recent_filter.label()));
+    }
+
+    /// `:close-others`: collapse every folder but the ones leading to the
+    /// selected note or folder.
+    pub fn close_other_folders(&mut self) {
+        let selected = match self.sidebar_items.get(self.selected_sidebar_index).map(|item| &item.kind) {
+            Some(SidebarItemKind::Folder { path, .. }) => Some(path.clone()),
+            Some(SidebarItemKind::Note { note_index }) => {
+                self.notes.get(*note_index).and_then(|note| note.file_path.clone())
+            }
+            _ => None,
+        };
+        let notes_root = self.config.notes_path();
+        let mut open: HashSet<PathBuf> = HashSet::from([notes_root.clone()]);
+        if let Some(selected) = selected {
+            open.extend(selected.ancestors().take_while(|dir| dir.starts_with(&notes_root)).map(Path::to_path_buf));
+        }
+
+        fn collapse(items: &mut [FileTreeItem], open: &HashSet<PathBuf>, states: &mut HashMap<PathBuf, bool>) {
+            for item in items {
+                if let FileTreeItem::Folder { path, expanded, children, .. } = item {
+                    *expanded = open.contains(path);
+                    states.insert(path.clone(), *expanded);
+                    collapse(children, open, states);
+                }
+            }
+        }
+        collapse(&mut self.file_tree, &open, &mut self.folder_states);
+        self.folder_states.insert(notes_root, true);
+        self.refresh_sidebar_keeping_selection();
+    }
+
+    /// Rebuild the sidebar, keeping the selected note or folder selected
+    /// when it's still listed.
+    fn refresh_sidebar_keeping_selection(&mut self) {
+        let selected = self.sidebar_items.get(self.selected_sidebar_index).map(|item| item.kind.clone());
+        let previous_note = self.selected_note;
+        if self.search_active && !self.search_query.is_empty() {
+            self.update_filtered_indices();
+        } else {
+            self.rebuild_sidebar_items();
+        }
+        let same = |kind: &SidebarItemKind| match (kind, &selected) {
+            (SidebarItemKind::Note { note_index }, Some(SidebarItemKind::Note { note_index: was })) => note_index == was,
+            (SidebarItemKind::Folder { path, .. }, Some(SidebarItemKind::Folder { path: was, .. })) => path == was,
+            _ => false,
+        };
+        let visible = self.get_visible_sidebar_indices();
+        self.selected_sidebar_index = visible
+            .iter()
+            .copied()
+            .find(|&i| same(&self.sidebar_items[i].kind))
+            .or_else(|| visible.iter().copied().find(|&i| i >= self.selected_sidebar_index))
+            .or_else(|| visible.last().copied())
+            .unwrap_or(0);
+        self.sync_selected_note_from_sidebar();
+        if self.selected_note == previous_note {
+            return;
+        }
+        self.update_content_items();
+        self.update_outline();
+    }
+
+    fn tree_item_pinned(item: &FileTreeItem, pins: &Pins, notes: &[Note]) -> bool {
+        match item {
+            FileTreeItem::Folder { path, .. } => pins.is_pinned(path),
+            FileTreeItem::Note { note_index, .. } => notes
+                .get(*note_index)
+                .and_then(|note| note.file_path.as_ref())
+                .is_some_and(|path| pins.is_pinned(path)),
+        }
+    }
+
+    /// The note or folder at `path` in the tree.
+    fn find_tree_item<'a>(items: &'a [FileTreeItem], path: &Path, notes: &[Note]) -> Option<&'a FileTreeItem> {
+        items.iter().find_map(|item| match item {
+            FileTreeItem::Folder { path: folder, children, .. } => {
+                if folder == path {
+                    Some(item)
+                } else if path.starts_with(folder) {
+                    Self::find_tree_item(children, path, notes)
+                } else {
+                    None
+                }
+            }
+            FileTreeItem::Note { note_index, .. } => notes
+                .get(*note_index)
+                .filter(|note| note.file_path.as_deref() == Some(path))
+                .map(|_| item),
+        })
+    }
+
+    /// Whether the sidebar row shows a pinned note or folder.
+    pub fn is_pinned_sidebar_item(&self, item: &SidebarItem) -> bool {
+        match &item.kind {
+            SidebarItemKind::Folder { path, .. } => self.pins.is_pinned(path),
+            SidebarItemKind::Note { note_index } => self
+                .notes
+                .get(*note_index)
+                .and_then(|note| note.file_path.as_ref())
+                .is_some_and(|path| self.pins.is_pinned(path)),
+            SidebarItemKind::Header => false,
+        }
+    }
+
+    /// `p` in the sidebar: pin the selected note or folder to the top, or
+    /// put it back in the tree.
+    pub fn toggle_pin_selected(&mut self) {
+        let path = match self.sidebar_items.get(self.selected_sidebar_index).map(|item| &item.kind) {
+            Some(SidebarItemKind::Folder { path, .. }) if *path != self.config.notes_path() => path.clone(),
+            Some(SidebarItemKind::Note { note_index }) => {
+                match self.notes.get(*note_index).and_then(|note| note.file_path.clone()) {
+                    Some(path) => path,
+                    None => return,
                 }
             }
+            _ => return,
+        };
+        let name = path.file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        if !path.starts_with(self.pins.root()) {
+            self.status_message = Some(format!("{} is outside the notes folder and can't be pinned", name));
+            return;
+        }
+        let pinned = self.pins.toggle(&path);
+        self.save_pins();
+        self.rebuild_sidebar_items();
+        // Keep the selection on the item in its new place
+        if let Some(idx) = self.sidebar_items.iter().position(|item| match &item.kind {
+            SidebarItemKind::Folder { path: folder, .. } => *folder == path,
+            SidebarItemKind::Note { note_index } => self.notes[*note_index].file_path.as_ref() == Some(&path),
+            SidebarItemKind::Header => false,
+        }) {
+            self.selected_sidebar_index = idx;
+        }
+        self.status_message = Some(format!("{} {}", if pinned { "Pinned" } else { "Unpinned" }, name));
+    }
+
+    fn save_pins(&mut self) {
+        if let Err(e) = self.pins.save(&pins_path()) {
+            self.status_message = Some(format!("Failed to save pins: {}", e));
+        }
+    }
+
+    /// Carry what's kept by path over to a renamed or moved note or folder:
+    /// its pins and its snapshots.
+    fn path_moved(&mut self, from: &Path, to: &Path) {
+        if self.pins.moved(from, to) {
+            self.save_pins();
+        }
+        if let Err(e) = snapshots::moved(&self.config.notes_path(), from, to) {
+            self.status_message = Some(format!("Failed to move snapshots: {}", e));
         }
     }
 
@@ -1649,10 +4745,136 @@ impl App {
                 self.end_buffer_search();
             }
             self.selected_note = new_note_idx;
-            self.current_image = None;
+            self.folder_view = None;
+            self.image_states.clear();
         }
     }
 
+    /// Show the folder selected in the sidebar: its index note when it has
+    /// one, otherwise a listing of its contents.
+    pub fn show_selected_folder(&mut self) {
+        let Some(SidebarItemKind::Folder { path, .. }) = self
+            .sidebar_items
+            .get(self.selected_sidebar_index)
+            .map(|item| item.kind.clone())
+        else {
+            return;
+        };
+
+        let index_note = folder_view::find_index_note(
+            &path,
+            &self.config.display.folder_index_notes,
+            &self.notes,
+        );
+        self.end_buffer_search();
+        match index_note {
+            Some(note_idx) => {
+                self.selected_note = note_idx;
+                self.folder_view = None;
+            }
+            None => self.folder_view = Some(path),
+        }
+        self.image_states.clear();
+    }
+
+    /// The folder listed in the content view, if the sidebar still has it selected.
+    pub fn folder_view_path(&self) -> Option<&Path> {
+        let path = self.folder_view.as_deref()?;
+        match self.sidebar_items.get(self.selected_sidebar_index).map(|item| &item.kind) {
+            Some(SidebarItemKind::Folder { path: selected, .. }) if selected == path => Some(path),
+            _ => None,
+        }
+    }
+
+    fn build_folder_listing(&mut self, folder: &Path) {
+        let name = folder
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Notes".to_string());
+        let mut items = vec![
+            ContentItem::TextLine(format!("# {}", name)),
+            ContentItem::TextLine(String::new()),
+            ContentItem::TextLine("*No index note. Press e to create one.*".to_string()),
+            ContentItem::TextLine(String::new()),
+        ];
+
+        let entries = folder_view::folder_listing(
+            &self.file_tree,
+            &self.config.notes_path(),
+            folder,
+            &self.notes,
+        );
+        if entries.is_empty() {
+            items.push(ContentItem::TextLine("*Empty folder*".to_string()));
+        } else {
+            items.extend(entries);
+        }
+
+        self.content_item_source_lines = vec![0; items.len()];
+        self.content_items = items;
+    }
+
+    pub fn is_folder_entry_at(&self, index: usize) -> bool {
+        matches!(self.content_items.get(index), Some(ContentItem::FolderEntry { .. }))
+    }
+
+    /// Open the folder entry under the content cursor: a note opens in the
+    /// content view, a subfolder is selected in the sidebar.
+    pub fn open_current_folder_entry(&mut self) {
+        let Some(ContentItem::FolderEntry { path, is_folder, .. }) =
+            self.content_items.get(self.content_cursor).cloned()
+        else {
+            return;
+        };
+
+        self.expand_folders_to(&path);
+        if is_folder {
+            let found = self.sidebar_items.iter().position(|item| {
+                matches!(&item.kind, SidebarItemKind::Folder { path: p, .. } if *p == path)
+            });
+            if let Some(idx) = found {
+                self.selected_sidebar_index = idx;
+                self.show_selected_folder();
+                self.update_content_items();
+                self.update_outline();
+            }
+        } else {
+            self.select_note_by_path(&path);
+            self.push_navigation_history(self.selected_note);
+        }
+    }
+
+    /// Create the index note for the folder listed in the content view and
+    /// open it in the editor.
+    pub fn create_folder_index_note(&mut self) {
+        let Some(folder) = self.folder_view_path().map(Path::to_path_buf) else {
+            return;
+        };
+        let Some(path) =
+            folder_view::new_index_note_path(&folder, &self.config.display.folder_index_notes)
+        else {
+            self.status_message = Some("No folder index note name configured".to_string());
+            return;
+        };
+
+        if !path.exists() {
+            let title = folder
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if let Err(e) = fs::write(&path, format!("# {}\n\n", title)) {
+                self.show_error_toast(format!("Failed to create index note: {}", e));
+                return;
+            }
+        }
+
+        self.load_notes_from_dir();
+        self.expand_folders_to(&path);
+        self.select_note_by_path(&path);
+        self.push_navigation_history(self.selected_note);
+        self.enter_edit_mode();
+    }
+
     /// find and select the current note in the sidebar after re sorting
     fn select_current_note_in_sidebar(&mut self) {
         for (idx, item) in self.sidebar_items.iter().enumerate() {
@@ -1665,46 +4887,53 @@ impl App {
         }
     }
 
-    pub fn create_note(&mut self, name: &str) {
+    /// Create a note in the context folder. A name like `projects/2024/plan`
+    /// creates the folders on the way. Returns whether the dialog can close;
+    /// when it can't, `dialog_error` says why.
+    pub fn create_note(&mut self, name: &str) -> bool {
         let name = name.trim();
         if name.is_empty() {
-            return;
+            return false;
         }
+        let (relative, title) = match nested_note_path(name) {
+            Ok(parts) => parts,
+            Err(e) => {
+                self.dialog_error = Some(e);
+                return false;
+            }
+        };
 
         let parent_path = self.target_folder.clone()
             .unwrap_or_else(|| self.config.notes_path());
-        let file_path = parent_path.join(format!("{}.md", name));
+        let file_path = parent_path.join(relative);
 
         // Don't overwrite existing files
         if file_path.exists() {
-            return;
+            self.dialog_error = Some(format!("Note '{}' already exists", name));
+            return false;
         }
-
-        let content = format!("# {}\n\n", name);
-        if fs::write(&file_path, &content).is_ok() {
-            if let Some(ref folder_path) = self.target_folder {
-                self.folder_states.insert(folder_path.clone(), true);
-            }
-
-            self.load_notes_from_dir();
-
-            let name_owned = name.to_string();
-            for (idx, item) in self.sidebar_items.iter().enumerate() {
-                if let SidebarItemKind::Note { note_index } = &item.kind {
-                    if self.notes[*note_index].title == name_owned {
-                        self.selected_sidebar_index = idx;
-                        self.selected_note = *note_index;
-                        break;
-                    }
-                }
+        if let Some(folder) = file_path.parent() {
+            if let Err(e) = fs::create_dir_all(folder) {
+                self.dialog_error = Some(format!("Failed to create folder: {}", e));
+                return false;
             }
+        }
 
-            self.update_content_items();
-            self.update_outline();
-            self.focus = Focus::Content;
+        let content = format!("# {}\n\n", title);
+        if let Err(e) = fs::write(&file_path, &content) {
+            self.dialog_error = Some(format!("Failed to create note: {}", e));
+            return false;
         }
 
+        if let Some(ref folder_path) = self.target_folder {
+            self.folder_states.insert(folder_path.clone(), true);
+        }
+        self.load_notes_from_dir();
+        self.expand_folders_to(&file_path);
+        self.select_note_by_path(&file_path);
+        self.focus = Focus::Content;
         self.target_folder = None;
+        true
     }
 
     pub fn create_folder(&mut self, name: &str) -> bool {
@@ -1744,6 +4973,7 @@ impl App {
                     }
                     None
                 }
+                SidebarItemKind::Header => None,
             }
         } else {
             None
@@ -1771,87 +5001,483 @@ impl App {
     pub fn delete_current_note(&mut self) {
         if let Some(item) = self.sidebar_items.get(self.selected_sidebar_index) {
             if let SidebarItemKind::Note { note_index } = &item.kind {
-                if let Some(ref path) = self.notes[*note_index].file_path {
-                    let _ = fs::remove_file(path);
-                }
+                let ops = self.notes[*note_index].file_path.iter().cloned().map(FileOp::Remove).collect();
+                self.run_file_ops(ops, false, FileOpsThen::Deleted);
+            }
+        }
+    }
 
-                self.load_notes_from_dir();
+    /// `d` in the sidebar: ask before deleting the selected note or folder. For
+    /// a folder, count what it holds so the dialog can show it.
+    pub fn confirm_delete_selected(&mut self) {
+        let Some(item) = self.sidebar_items.get(self.selected_sidebar_index) else {
+            return;
+        };
+        match &item.kind {
+            SidebarItemKind::Note { .. } => self.dialog = DialogState::DeleteConfirm,
+            SidebarItemKind::Folder { path, .. } => {
+                self.delete_folder_contents = Some(FolderContents::count(path));
+                self.input_buffer.clear();
+                self.dialog_error = None;
+                self.dialog = DialogState::DeleteFolderConfirm;
+            }
+            SidebarItemKind::Header => {}
+        }
+    }
 
-                if self.selected_sidebar_index >= self.sidebar_items.len() {
-                    self.selected_sidebar_index = self.sidebar_items.len().saturating_sub(1);
+    /// The note the DeleteConfirm dialog is about, selected in the sidebar.
+    pub fn selected_sidebar_note(&self) -> Option<&Note> {
+        match self.sidebar_items.get(self.selected_sidebar_index)?.kind {
+            SidebarItemKind::Note { note_index } => self.notes.get(note_index),
+            SidebarItemKind::Folder { .. } | SidebarItemKind::Header => None,
+        }
+    }
+
+    pub fn delete_current_folder(&mut self) {
+        if let Some(path) = self.get_selected_folder_path() {
+            self.run_file_ops(vec![FileOp::Remove(path)], false, FileOpsThen::Deleted);
+        }
+    }
+
+    /// The note or folder a sidebar row stands for.
+    fn sidebar_item_path(&self, item: &SidebarItem) -> Option<PathBuf> {
+        match &item.kind {
+            SidebarItemKind::Folder { path, .. } => Some(path.clone()),
+            SidebarItemKind::Note { note_index } => self.notes.get(*note_index)?.file_path.clone(),
+            SidebarItemKind::Header => None,
+        }
+    }
+
+    pub fn is_marked_sidebar_item(&self, item: &SidebarItem) -> bool {
+        !self.sidebar_marks.is_empty()
+            && self.sidebar_item_path(item).is_some_and(|path| self.sidebar_marks.contains(&path))
+    }
+
+    fn selected_sidebar_path(&self) -> Option<PathBuf> {
+        self.sidebar_items
+            .get(self.selected_sidebar_index)
+            .and_then(|item| self.sidebar_item_path(item))
+    }
+
+    /// Space in the sidebar: mark or unmark the selected note or folder.
+    pub fn toggle_mark_selected(&mut self) {
+        if let Some(path) = self.selected_sidebar_path() {
+            self.sidebar_marks.toggle(&path);
+        }
+    }
+
+    /// `V` in the sidebar: mark every item from the one last marked with
+    /// Space down or up to the selected one.
+    pub fn mark_range_to_selected(&mut self) {
+        let anchor = self.sidebar_marks.anchor().and_then(|anchor| {
+            self.sidebar_items
+                .iter()
+                .position(|item| self.sidebar_item_path(item).as_deref() == Some(anchor))
+        });
+        let Some(anchor) = anchor else {
+            self.toggle_mark_selected();
+            return;
+        };
+        let selected = self.selected_sidebar_index.min(self.sidebar_items.len().saturating_sub(1));
+        let range = anchor.min(selected)..=anchor.max(selected);
+        let paths: Vec<PathBuf> = self.sidebar_items[range]
+            .iter()
+            .filter_map(|item| self.sidebar_item_path(item))
+            .collect();
+        for path in &paths {
+            self.sidebar_marks.mark(path);
+        }
+        if let Some(path) = self.selected_sidebar_path() {
+            self.sidebar_marks.set_anchor(&path);
+        }
+    }
+
+    /// Esc in the sidebar. Returns whether there were marks to clear.
+    pub fn clear_marks(&mut self) -> bool {
+        if self.sidebar_marks.is_empty() {
+            return false;
+        }
+        self.sidebar_marks.clear();
+        self.status_message = Some("Marks cleared".to_string());
+        true
+    }
+
+    /// Esc in the sidebar: drop the marks, or the pending cut once there are
+    /// none.
+    pub fn clear_marks_or_cut(&mut self) {
+        if !self.clear_marks() {
+            self.clear_cut_buffer();
+        }
+    }
+
+    /// `d` with items marked: ask once before deleting all of them. Folders
+    /// are counted so the dialog can warn about what goes with them.
+    pub fn confirm_delete_marked(&mut self) {
+        self.delete_marked = self
+            .sidebar_marks
+            .roots()
+            .into_iter()
+            .filter(|path| path.exists())
+            .map(|path| {
+                let contents = path.is_dir().then(|| FolderContents::count(&path));
+                (path, contents)
+            })
+            .collect();
+        if self.delete_marked.is_empty() {
+            self.sidebar_marks.clear();
+            return;
+        }
+        self.dialog = DialogState::DeleteMarkedConfirm;
+    }
+
+    pub fn delete_marked_items(&mut self) {
+        let ops = std::mem::take(&mut self.delete_marked).into_iter().map(|(path, _)| FileOp::Remove(path)).collect();
+        self.run_file_ops(ops, false, FileOpsThen::DeletedMarked);
+    }
+
+    /// `m` in the sidebar: pick a folder to move the marked items to, or the
+    /// selected one when nothing is marked.
+    pub fn open_move_picker(&mut self) {
+        let items: Vec<PathBuf> = if self.sidebar_marks.is_empty() {
+            self.selected_sidebar_path().into_iter().collect()
+        } else {
+            self.sidebar_marks.roots().into_iter().filter(|path| path.exists()).collect()
+        };
+        if items.is_empty() {
+            return;
+        }
+        fn add_folders(tree: &[FileTreeItem], folders: &mut Vec<PathBuf>) {
+            for item in tree {
+                if let FileTreeItem::Folder { path, children, .. } = item {
+                    folders.push(path.clone());
+                    add_folders(children, folders);
                 }
-                self.sync_selected_note_from_sidebar();
+            }
+        }
+        let mut folders = vec![self.config.notes_path()];
+        add_folders(&self.file_tree, &mut folders);
+        // A folder can't go into itself
+        folders.retain(|folder| !items.iter().any(|item| folder.starts_with(item)));
 
-                self.update_content_items();
-                self.update_outline();
+        self.move_picker = MovePicker { items, folders, ..MovePicker::default() };
+        self.dialog = DialogState::MovePicker;
+    }
+
+    pub fn close_move_picker(&mut self) {
+        self.dialog = DialogState::None;
+        self.move_picker = MovePicker::default();
+    }
+
+    pub fn move_picker_select_next(&mut self) {
+        if self.move_picker.selected + 1 < self.move_picker.folders.len() {
+            self.move_picker.selected += 1;
+        }
+    }
+
+    pub fn move_picker_select_prev(&mut self) {
+        self.move_picker.selected = self.move_picker.selected.saturating_sub(1);
+    }
+
+    /// Move the picker's items into the selected folder and clear the marks.
+    pub fn move_to_picked_folder(&mut self) {
+        let picker = std::mem::take(&mut self.move_picker);
+        self.dialog = DialogState::None;
+        let Some(dest) = picker.folders.get(picker.selected) else {
+            return;
+        };
+        let mut moved = 0;
+        let mut first_error = None;
+        for item in &picker.items {
+            let result = if item.is_dir() {
+                let name = item.file_name().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+                self.move_folder(item, dest, &name)
+            } else {
+                let title = self
+                    .notes
+                    .iter()
+                    .find(|note| note.file_path.as_ref() == Some(item))
+                    .map(|note| note.title.clone())
+                    .or_else(|| item.file_stem().map(|s| s.to_string_lossy().to_string()))
+                    .unwrap_or_default();
+                self.move_note(item, dest, &title)
+            };
+            match result {
+                Ok(()) => moved += 1,
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
             }
         }
+        self.sidebar_marks.clear();
+        if picker.items.len() > 1 || first_error.is_some() {
+            self.status_message = Some(match first_error {
+                None => format!("Moved {} items", moved),
+                Some(e) => format!("Moved {} of {} items: {}", moved, picker.items.len(), e),
+            });
+        }
     }
 
-    pub fn delete_current_folder(&mut self) {
-        if let Some(path) = self.get_selected_folder_path() {
-            if fs::remove_dir_all(&path).is_ok() {
-                self.folder_states.remove(&path);
+    /// Rename the note selected in the sidebar. Returns whether the dialog
+    /// can close; when it can't, `dialog_error` says why.
+    pub fn rename_note(&mut self, new_name: &str) -> bool {
+        let Some(SidebarItemKind::Note { note_index }) =
+            self.sidebar_items.get(self.selected_sidebar_index).map(|item| &item.kind)
+        else {
+            return true;
+        };
+        match self.rename_note_at(*note_index, new_name) {
+            Ok(()) => true,
+            Err(e) => {
+                self.dialog_error = Some(e);
+                false
+            }
+        }
+    }
 
-                self.load_notes_from_dir();
+    /// Rename the note at `note_index` within its folder and select it. A
+    /// note that has no file yet is saved under the new name instead.
+    fn rename_note_at(&mut self, note_index: usize, new_name: &str) -> Result<(), String> {
+        let new_name = new_name.trim();
+        check_note_name(new_name)?;
+        let Some(note) = self.notes.get(note_index) else {
+            return Ok(());
+        };
+        if note.title == new_name {
+            return Ok(());
+        }
+        if self.dirty_note == Some(note_index) {
+            return Err("Save or discard the note's changes first".to_string());
+        }
 
-                if self.selected_sidebar_index >= self.sidebar_items.len() {
-                    self.selected_sidebar_index = self.sidebar_items.len().saturating_sub(1);
-                }
-                self.sync_selected_note_from_sidebar();
+        let file_name = format!("{}.md", new_name);
+        let new_file_path = match note.file_path.as_ref().and_then(|path| path.parent()) {
+            Some(parent) => parent.join(file_name),
+            None => self.config.notes_path().join(file_name),
+        };
+        // A change of case alone is the same file where case is ignored
+        let same_file = note.file_path.as_ref().is_some_and(|path| paths::same_path(path, &new_file_path));
+        if new_file_path.exists() && !same_file {
+            return Err(format!("Note '{}' already exists", new_name));
+        }
 
-                self.update_content_items();
-                self.update_outline();
+        match note.file_path.clone() {
+            Some(old_path) => {
+                let ops = vec![FileOp::Rename { from: old_path, to: new_file_path.clone() }];
+                self.run_file_ops(ops, true, FileOpsThen::RenamedNote(new_file_path));
+            }
+            None => {
+                fs::write(&new_file_path, &note.content).map_err(|e| format!("Failed to save note: {}", e))?;
+                self.load_notes_from_dir();
+                self.expand_folders_to(&new_file_path);
+                self.select_note_by_path(&new_file_path);
             }
         }
+        Ok(())
     }
 
-    pub fn rename_note(&mut self, new_name: &str) {
-        let new_name = new_name.trim();
-        if new_name.is_empty() {
+    /// Start `:bulk-rename`: work out the new names of the notes marked in the
+    /// sidebar, or else of the ones its filter matches, for the preview dialog.
+    pub fn start_bulk_rename(&mut self, pattern: String, replacement: String, flags: SubstituteFlags) {
+        let notes: Vec<(PathBuf, String)> = if !self.sidebar_marks.is_empty() {
+            self.sidebar_marks
+                .roots()
+                .into_iter()
+                .filter_map(|path| {
+                    let note = self.notes.iter().find(|note| note.file_path.as_ref() == Some(&path))?;
+                    Some((path, note.title.clone()))
+                })
+                .collect()
+        } else if !self.search_query.is_empty() {
+            self.search_matched_notes
+                .iter()
+                .filter_map(|&i| {
+                    let note = self.notes.get(i)?;
+                    Some((note.file_path.clone()?, note.title.clone()))
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        if notes.is_empty() {
+            self.vim.status_message = Some("Mark notes in the sidebar or filter it first".to_string());
             return;
         }
 
-        if let Some(item) = self.sidebar_items.get(self.selected_sidebar_index) {
-            if let SidebarItemKind::Note { note_index } = &item.kind {
-                let note_index = *note_index;
-
-                if self.notes[note_index].title == new_name {
-                    return;
+        let unsaved = self.unsaved_note_path();
+        let mut renames = bulk_rename::plan(&notes, &pattern, &replacement, &flags, unsaved.as_deref(), check_note_name);
+        if renames.iter().all(|rename| rename.problem == Some(RenameProblem::Unchanged)) {
+            self.vim.status_message = Some(format!("Pattern not found: {}", pattern));
+            return;
+        }
+        // Count the links each rename will update, and note how they're written
+        for rename in renames.iter_mut().filter(|rename| rename.problem.is_none()) {
+            let Some(index) = self.notes.iter().position(|note| note.file_path.as_ref() == Some(&rename.from)) else {
+                continue;
+            };
+            let names = std::cell::RefCell::new(Vec::new());
+            let links_here = |name: &str| {
+                let here = self.resolve_wiki_link(name) == Some(index);
+                if here && !names.borrow().iter().any(|known| known == name) {
+                    names.borrow_mut().push(name.to_string());
                 }
+                here
+            };
+            rename.links = self.relinkable_notes().map(|(_, note)| note_merge::relink(&note.content, "", links_here).1).sum();
+            rename.link_names = names.into_inner();
+        }
+        self.pending_bulk_rename = Some(BulkRename { pattern, replacement, renames, scroll: 0 });
+        self.dialog = DialogState::BulkRenameConfirm;
+    }
 
-                let new_file_path = if let Some(ref old_path) = self.notes[note_index].file_path {
-                    if let Some(parent) = old_path.parent() {
-                        parent.join(format!("{}.md", new_name))
-                    } else {
-                        return;
-                    }
-                } else {
-                    return;
-                };
+    /// Notes a rename may update the links in: saved, not encrypted, and
+    /// without edits in the editor.
+    fn relinkable_notes(&self) -> impl Iterator<Item = (usize, &Note)> {
+        let editing = (self.mode == Mode::Edit).then_some(self.selected_note);
+        self.notes.iter().enumerate().filter(move |&(i, note)| {
+            note.lock == NoteLock::Plain && note.file_path.is_some() && self.dirty_note != Some(i) && editing != Some(i)
+        })
+    }
 
-                if new_file_path.exists() {
-                    return;
-                }
+    /// Point the links written as one of `names` at the note now at `path`,
+    /// by the name a link to it takes, and save the notes they're in.
+    /// Returns how many links were updated.
+    fn relink_renamed(&mut self, path: &Path, names: &[String]) -> usize {
+        let Some(index) = self.notes.iter().position(|note| note.file_path.as_deref() == Some(path)) else {
+            return 0;
+        };
+        if names.is_empty() {
+            return 0;
+        }
+        let title = self.notes[index].title.clone();
+        let title_shared = self.notes.iter().enumerate().any(|(i, note)| i != index && note.title == title);
+        let target = match title_shared {
+            true => self.get_wiki_path_for_note(index).unwrap_or(title),
+            false => title,
+        };
+        let relinked: Vec<(usize, String, usize)> = self
+            .relinkable_notes()
+            .filter_map(|(i, note)| {
+                let (text, count) = note_merge::relink(&note.content, &target, |name| names.iter().any(|n| n == name));
+                (count > 0).then_some((i, text, count))
+            })
+            .collect();
+        let mut links = 0;
+        for (i, text, count) in relinked {
+            match self.write_note_text(i, text) {
+                Ok(()) => links += count,
+                Err(e) => self.show_error_toast(format!("Failed to update links in {}: {}", self.notes[i].title, e)),
+            }
+        }
+        links
+    }
 
-                if let Some(ref old_path) = self.notes[note_index].file_path {
-                    if fs::rename(old_path, &new_file_path).is_ok() {
-                        self.load_notes_from_dir();
+    /// Give the note at `index` new text and save it, in the background with
+    /// `background_io`.
+    fn write_note_text(&mut self, index: usize, content: String) -> io::Result<()> {
+        let line_ending = self.config.editor.line_ending;
+        let note = &mut self.notes[index];
+        let previous = std::mem::replace(&mut note.content, content);
+        let (frontmatter, content_start_line) = super::frontmatter::Frontmatter::parse(&note.content);
+        note.frontmatter = frontmatter;
+        note.content_start_line = content_start_line;
+        let path = note.file_path.clone().filter(|_| self.config.background_io);
+        let Some(path) = path else {
+            let written = note.write(line_ending);
+            if written.is_err() {
+                note.content = previous;
+            }
+            return written;
+        };
+        let bytes = note.encode(line_ending)?;
+        let content = note.content.clone();
+        let after = self.save_jobs.iter_mut().rev().find(|job| job.path == path).and_then(SaveJob::take_done);
+        self.save_jobs.push(SaveJob::start(path, previous, content, bytes, None, after));
+        Ok(())
+    }
 
-                        let new_name_owned = new_name.to_string();
-                        for (idx, item) in self.sidebar_items.iter().enumerate() {
-                            if let SidebarItemKind::Note { note_index } = &item.kind {
-                                if self.notes[*note_index].title == new_name_owned {
-                                    self.selected_sidebar_index = idx;
-                                    self.selected_note = *note_index;
-                                    break;
-                                }
-                            }
-                        }
+    /// Do the confirmed `:bulk-rename`, one note at a time. The first rename
+    /// that fails stops the rest, so what's done and what isn't is clear.
+    /// Links to each note renamed are updated across the vault.
+    pub fn run_bulk_rename(&mut self) {
+        let Some(bulk) = self.pending_bulk_rename.take() else {
+            return;
+        };
+        let current = self.current_note().and_then(|note| note.file_path.clone());
+        let ops = bulk.ready().map(|rename| FileOp::Rename { from: rename.from.clone(), to: rename.to.clone() }).collect();
+        self.run_file_ops(ops, true, FileOpsThen::BulkRenamed { bulk, current });
+    }
+
+    /// The rest of a `:bulk-rename`, once its renames are made.
+    fn finish_bulk_rename(&mut self, bulk: BulkRename, current: Option<PathBuf>, results: &[io::Result<()>]) {
+        let ready: Vec<_> = bulk.ready().collect();
+        let renamed = results.iter().take_while(|result| result.is_ok()).count();
+        let failure = results
+            .get(renamed)
+            .and_then(|result| result.as_ref().err())
+            .map(|e| format!("'{}': {}", ready[renamed].old_name, e));
+        let current = current.map(|path| match ready[..renamed].iter().find(|rename| rename.from == path) {
+            Some(rename) => rename.to.clone(),
+            None => path,
+        });
 
-                        self.update_content_items();
-                        self.update_outline();
-                    }
+        let links: usize = ready[..renamed].iter().map(|rename| self.relink_renamed(&rename.to, &rename.link_names)).sum();
+        let links = match links {
+            0 => String::new(),
+            1 => ", updated 1 link".to_string(),
+            n => format!(", updated {} links", n),
+        };
+
+        self.sidebar_marks.clear();
+        self.start_index_build();
+        if !self.search_query.is_empty() {
+            self.update_filtered_indices();
+        }
+        if let Some(path) = current {
+            self.expand_folders_to(&path);
+            self.select_note_by_path(&path);
+        }
+
+        let held_back = bulk.renames.len() - ready.len();
+        self.vim.status_message = Some(match failure {
+            None => format!(
+                "Renamed {} note{}{}{}",
+                renamed,
+                if renamed == 1 { "" } else { "s" },
+                links,
+                if held_back > 0 { format!(", {} left as they were", held_back) } else { String::new() },
+            ),
+            Some(error) => {
+                let not_renamed: Vec<PathBuf> = ready[renamed..].iter().map(|rename| rename.from.clone()).collect();
+                format!(
+                    "Renamed {} of {} notes{}, stopped at {}. Not renamed: {}",
+                    renamed,
+                    ready.len(),
+                    links,
+                    error,
+                    global_substitute::note_names(&not_renamed),
+                )
+            }
+        });
+    }
+
+    /// Start renaming the open note from the title header of the content view.
+    pub fn start_title_edit(&mut self) {
+        if let Some(note) = self.notes.get(self.selected_note) {
+            self.title_edit = Some(TitleEdit { input: LineInput::new(note.title.clone()), error: None });
+        }
+    }
+
+    /// Rename the open note to the edited title. On an error the edit stays
+    /// open with the error shown under it.
+    pub fn apply_title_edit(&mut self) {
+        let Some(name) = self.title_edit.as_ref().map(|edit| edit.input.to_string()) else {
+            return;
+        };
+        match self.rename_note_at(self.selected_note, &name) {
+            Ok(()) => self.title_edit = None,
+            Err(e) => {
+                if let Some(edit) = self.title_edit.as_mut() {
+                    edit.error = Some(e);
                 }
             }
         }
@@ -1875,37 +5501,13 @@ impl App {
             if let Some(parent) = old_path.parent() {
                 let new_path = parent.join(new_name);
 
-                if new_path.exists() {
+                if new_path.exists() && !paths::same_path(&old_path, &new_path) {
                     self.dialog_error = Some(format!("Folder '{}' already exists", new_name));
                     return;
                 }
 
-                if fs::rename(&old_path, &new_path).is_ok() {
-                    if let Some(expanded) = self.folder_states.remove(&old_path) {
-                        self.folder_states.insert(new_path.clone(), expanded);
-                    }
-
-                    self.load_notes_from_dir();
-
-                    let new_name_owned = new_name.to_string();
-                    for (idx, item) in self.sidebar_items.iter().enumerate() {
-                        if let SidebarItemKind::Folder { path, .. } = &item.kind {
-                            if path == &new_path {
-                                self.selected_sidebar_index = idx;
-                                break;
-                            }
-                        }
-                        if item.display_name == new_name_owned {
-                            if let SidebarItemKind::Folder { .. } = &item.kind {
-                                self.selected_sidebar_index = idx;
-                                break;
-                            }
-                        }
-                    }
-
-                    self.update_content_items();
-                    self.update_outline();
-                }
+                let ops = vec![FileOp::Rename { from: old_path, to: new_path.clone() }];
+                self.run_file_ops(ops, true, FileOpsThen::RenamedFolder(new_path));
             }
         }
     }
@@ -1933,6 +5535,7 @@ impl App {
                     });
                     self.status_message = Some(format!("Cut: {}/", name));
                 }
+                SidebarItemKind::Header => {}
             }
         }
     }
@@ -1973,6 +5576,7 @@ impl App {
                         }
                     }
                 }
+                SidebarItemKind::Header => {}
             }
         }
         self.config.notes_path()
@@ -1996,6 +5600,7 @@ impl App {
         let new_wiki_path = Self::calculate_wiki_path(&dest_path, &notes_root);
         fs::rename(source, &dest_path)
             .map_err(|e| format!("Failed to move file: {}", e))?;
+        self.path_moved(source, &dest_path);
         self.update_wiki_links_after_move(&old_wiki_path, &new_wiki_path, title);
         self.load_notes_from_dir();
         self.start_index_build();
@@ -2052,6 +5657,7 @@ impl App {
 
         fs::rename(source, &dest_path)
             .map_err(|e| format!("Failed to move folder: {}", e))?;
+        self.path_moved(source, &dest_path);
 
         let keys_to_update: Vec<PathBuf> = self.folder_states.keys()
             .filter(|k| k.starts_with(source))
@@ -2094,8 +5700,8 @@ impl App {
 
         for file_path in md_files {
             let content = match fs::read_to_string(&file_path) {
-                Ok(c) => c,
-                Err(_) => continue,
+                Ok(c) if !encryption::is_encrypted(&c) => c,
+                _ => continue,
             };
 
             let modified_content = self.replace_wiki_links_in_content(
@@ -2137,20 +5743,16 @@ impl App {
 
         while let Some(start) = remaining.find("[[") {
             result.push_str(&remaining[..start]);
-            remaining = &remaining[start + 2..];
-
-            if let Some(end) = remaining.find("]]") {
-                let link_content = &remaining[..end];
+            remaining = &remaining[start..];
 
-                let (target, suffix) = if let Some(hash_pos) = link_content.find('#') {
-                    (&link_content[..hash_pos], &link_content[hash_pos..])
-                } else if let Some(pipe_pos) = link_content.find('|') {
-                    (&link_content[..pipe_pos], &link_content[pipe_pos..])
-                } else {
-                    (link_content, "")
-                };
+            if let Some(link) = WikiLink::parse(remaining) {
+                let link_content = &remaining[2..link.len - 2];
+                // The heading and alias are kept as written
+                let destination = wiki_link::split_unescaped(link_content, '|').map_or(link_content, |(d, _)| d);
+                let target_len = wiki_link::split_unescaped(destination, '#').map_or(destination.len(), |(t, _)| t.len());
+                let suffix = &link_content[target_len..];
 
-                let target_lower = target.to_lowercase();
+                let target_lower = link.target.to_lowercase();
                 let old_path_lower = old_path.to_lowercase();
                 let old_title_lower = old_title.to_lowercase();
 
@@ -2164,68 +5766,396 @@ impl App {
                         old_title.to_string()
                     };
                     result.push_str("[[");
-                    result.push_str(&new_target);
+                    result.push_str(&wiki_link::escape(&new_target));
                     result.push_str(suffix);
                     result.push_str("]]");
                 } else {
                     // Keep original
-                    result.push_str("[[");
-                    result.push_str(link_content);
-                    result.push_str("]]");
+                    result.push_str(&remaining[..link.len]);
+                }
+
+                remaining = &remaining[link.len..];
+            } else {
+                result.push_str("[[");
+                remaining = &remaining[2..];
+            }
+        }
+
+        result.push_str(remaining);
+        result
+    }
+
+    fn calculate_wiki_path(file_path: &std::path::Path, notes_root: &std::path::Path) -> String {
+        if let Ok(relative) = file_path.strip_prefix(notes_root) {
+            let path_str = relative.to_string_lossy();
+            if let Some(stripped) = path_str.strip_suffix(".md") {
+                return stripped.to_string();
+            }
+            path_str.to_string()
+        } else {
+            file_path.file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default()
+        }
+    }
+
+    pub fn complete_onboarding(&mut self) {
+        if self.input_buffer.trim().is_empty() {
+            self.dialog_error = Some("Enter a folder for your notes".to_string());
+            return;
+        }
+        let path = path_input::resolve(&self.input_buffer, &std::env::current_dir().unwrap_or_default());
+        if path.exists() && !path.is_dir() {
+            self.dialog_error = Some(format!("{} is not a folder", path.display()));
+            return;
+        }
+        // 1. Save config
+        self.config.notes_dir = self.input_buffer.to_string();
+        self.base_config.notes_dir = self.input_buffer.to_string();
+        let _ = self.base_config.save();
+
+        let notes_path = self.config.notes_path();
+        let _ = fs::create_dir_all(&notes_path);
+
+        let _ = fs::write(notes_path.join("01-Getting Started.md"), GETTING_STARTED_CONTENT);
+        let _ = fs::write(notes_path.join("02-Demo Note.md"), DEMO_NOTE_CONTENT);
+        self.dialog = DialogState::None;
+        self.load_notes_from_dir();
+
+        self.show_welcome = true;
+        self.needs_full_clear = true;
+    }
+
+    /// Create the notes directory when it doesn't exist
+    pub fn create_notes_directory(&mut self) {
+        let notes_path = self.config.notes_path();
+        if fs::create_dir_all(&notes_path).is_ok() {
+            self.load_notes_from_dir();
+            // Show empty directory dialog since we just created an empty directory
+            if self.notes.is_empty() {
+                self.dialog = DialogState::EmptyDirectory;
+            } else {
+                self.dialog = DialogState::None;
+            }
+        }
+    }
+
+    pub fn open_vault_picker(&mut self) {
+        if self.config.vaults.is_empty() {
+            self.set_status("No vaults: add [[vaults]] entries to the config".to_string());
+            return;
+        }
+        let active = self.config.active_vault();
+        let selected = self.config.vaults.iter().position(|vault| Some(vault) == active).unwrap_or(0);
+        self.vault_picker = VaultPicker { selected, ..VaultPicker::default() };
+        self.dialog = DialogState::VaultPicker;
+    }
+
+    pub fn vault_picker_select_next(&mut self) {
+        if self.vault_picker.selected + 1 < self.config.vaults.len() {
+            self.vault_picker.selected += 1;
+        }
+    }
+
+    pub fn vault_picker_select_prev(&mut self) {
+        self.vault_picker.selected = self.vault_picker.selected.saturating_sub(1);
+    }
+
+    /// Open the highlighted vault.
+    pub fn confirm_vault_selection(&mut self) {
+        let picker = std::mem::take(&mut self.vault_picker);
+        self.dialog = DialogState::None;
+        if let Some(vault) = self.config.vaults.get(picker.selected).cloned() {
+            if picker.at_startup {
+                self.set_notes_dir(vault.path.clone());
+                self.open_notes_dir();
+            } else {
+                self.switch_vault(&vault);
+            }
+        }
+    }
+
+    /// Close the picker. At startup that opens `notes_dir`, as without vaults.
+    pub fn cancel_vault_selection(&mut self) {
+        let picker = std::mem::take(&mut self.vault_picker);
+        self.dialog = DialogState::None;
+        if picker.at_startup {
+            self.open_notes_dir();
+        }
+    }
+
+    /// `:vault {name}`: switch to the vault of that name.
+    pub fn switch_vault_named(&mut self, name: &str) {
+        match self.config.vaults.iter().find(|vault| vault.name == name).cloned() {
+            Some(vault) => self.switch_vault(&vault),
+            None => self.set_status(format!("No vault named {}", name)),
+        }
+    }
+
+    /// Leave the current vault for `vault`: what's kept per vault is saved,
+    /// everything loaded from this one is dropped and the other one's notes
+    /// are read in, opening the note last viewed there.
+    pub fn switch_vault(&mut self, vault: &VaultConfig) {
+        if self.mode == Mode::Edit {
+            return;
+        }
+        if vault.path() == self.config.notes_path() {
+            self.set_status(format!("Already in vault {}", vault.name));
+            return;
+        }
+        if let Some(note) = self.dirty_note.and_then(|i| self.notes.get(i)) {
+            self.set_status(format!("{} has unsaved changes", note.title));
+            return;
+        }
+
+        self.leave_notes_dir();
+        self.set_notes_dir(vault.path.clone());
+        self.open_notes_dir();
+        if self.dialog == DialogState::None {
+            self.status_message = Some(format!("Vault: {}", vault.name));
+        }
+    }
+
+    /// Save what's kept per notes folder and drop everything loaded from
+    /// it, before another one is opened.
+    fn leave_notes_dir(&mut self) {
+        self.save_last_opened_note_to_cache();
+        self.save_reading_positions();
+
+        self.clear_search();
+        self.end_buffer_search();
+        self.close_search_picker();
+        self.notes.clear();
+        self.file_tree.clear();
+        self.sidebar_items.clear();
+        self.selected_note = 0;
+        self.selected_sidebar_index = 0;
+        self.folder_view = None;
+        self.source_view = None;
+        self.navigation_history.clear();
+        self.navigation_index = 0;
+        self.quickfix = QuickfixList::default();
+        self.quickfix_open = false;
+        self.view_undo.clear();
+        self.diagnostics.clear();
+        self.passphrase_cache = None;
+        self.content_cursor = 0;
+        self.content_scroll_offset = 0;
+        // An index still being built is for the vault being left
+        self.indexing_in_progress = false;
+        self.index_started_at = None;
+        self.index_receiver = mpsc::channel().1;
+        self.search_index = SearchIndex::default();
+        self.graph_view = GraphViewState::default();
+    }
+
+    /// Open the ChangeNotesDir dialog on the current notes folder, for `:cd`
+    /// without a path or in place of a missing folder.
+    pub fn open_change_notes_dir(&mut self) {
+        self.input_buffer = LineInput::new(self.config.notes_dir.clone());
+        self.dialog_error = None;
+        self.dialog = DialogState::ChangeNotesDir;
+    }
+
+    /// Tab in a notes folder input: complete the folder typed so far.
+    pub fn complete_notes_dir_input(&mut self) {
+        match path_input::complete(&self.input_buffer, &self.notes_dir_base()) {
+            Some(completed) => self.input_buffer.set(completed),
+            None => self.dialog_error = Some("No folder starts that way".to_string()),
+        }
+    }
+
+    /// Tab after `:cd `: complete the folder typed so far.
+    pub fn complete_cd_command(&mut self) {
+        let Some(typed) = self.vim.command_buffer.strip_prefix("cd ") else {
+            return;
+        };
+        if let Some(completed) = path_input::complete(typed.trim_start(), &self.notes_dir_base()) {
+            self.vim.command_buffer = format!("cd {}", completed);
+        }
+    }
+
+    /// What relative `:cd` paths are under: the notes folder, or the
+    /// working folder when that's missing.
+    fn notes_dir_base(&self) -> PathBuf {
+        let notes_path = self.config.notes_path();
+        if notes_path.is_dir() {
+            notes_path
+        } else {
+            std::env::current_dir().unwrap_or_default()
+        }
+    }
+
+    /// `:cd {path}` or Enter in the ChangeNotesDir dialog: make `input` the
+    /// notes folder, saved to the config, and reload everything from it. A
+    /// missing folder is offered to be created, and changes not yet saved
+    /// are asked about first. `Err` says why the path can't be used.
+    pub fn change_notes_dir(&mut self, input: &str) -> Result<(), String> {
+        if input.trim().is_empty() {
+            return Err("Enter a folder for your notes".to_string());
+        }
+        let path = path_input::resolve(input, &self.notes_dir_base());
+        if path.exists() && !path.is_dir() {
+            return Err(format!("{} is not a folder", path.display()));
+        }
+        if paths::same_path(&path, &self.config.notes_path()) && path.is_dir() {
+            return Err(format!("Already in {}", path.display()));
+        }
+        self.dialog = DialogState::None;
+        let edited = self.mode == Mode::Edit && self.has_unsaved_changes();
+        if edited || self.dirty_note.is_some() {
+            if let Some(dirty) = self.dirty_note.filter(|_| !edited) {
+                self.selected_note = dirty;
+                self.update_content_items();
+            }
+            self.after_unsaved_prompt = Some(AfterUnsavedPrompt::ChangeDir(path));
+            self.dialog = DialogState::UnsavedChanges;
+            return Ok(());
+        }
+        self.change_notes_dir_to(path);
+        Ok(())
+    }
+
+    /// [`App::change_notes_dir`] once nothing is left unsaved.
+    fn change_notes_dir_to(&mut self, path: PathBuf) {
+        if self.mode == Mode::Edit {
+            self.cancel_edit();
+        }
+        if !path.exists() {
+            self.pending_notes_dir = Some(path);
+            self.dialog = DialogState::CreateNotesDirConfirm;
+            return;
+        }
+        self.leave_notes_dir();
+        self.set_notes_dir(path.display().to_string());
+        let saved = self.base_config.save();
+        self.open_notes_dir();
+        match saved {
+            Ok(()) => self.set_status(format!("Notes folder: {}", path.display())),
+            Err(e) => self.set_status(format!("Notes folder: {}, but the config wasn't saved: {}", path.display(), e)),
+        }
+    }
+
+    /// Answer CreateNotesDirConfirm: create the folder and open it, or go
+    /// back to asking about the missing one at startup.
+    pub fn finish_create_notes_dir(&mut self, create: bool) {
+        self.dialog = DialogState::None;
+        let Some(path) = self.pending_notes_dir.take() else {
+            return;
+        };
+        if !create {
+            if !self.config.notes_path().exists() {
+                self.dialog = DialogState::DirectoryNotFound;
+            }
+            return;
+        }
+        match fs::create_dir_all(&path) {
+            Ok(()) => self.change_notes_dir_to(path),
+            Err(e) => {
+                self.set_status(format!("Couldn't create {}: {}", path.display(), e));
+                if !self.config.notes_path().exists() {
+                    self.dialog = DialogState::DirectoryNotFound;
                 }
+            }
+        }
+    }
+
+    fn set_notes_dir(&mut self, notes_dir: String) {
+        self.base_config.notes_dir = notes_dir.clone();
+        self.config.notes_dir = notes_dir;
+        self.apply_folder_config(None);
+    }
+
+    /// Load the notes of `notes_dir` and open the one last viewed there, or
+    /// ask about a missing or empty folder.
+    fn open_notes_dir(&mut self) {
+        let notes_path = self.config.notes_path();
+        if !notes_path.exists() {
+            self.dialog = DialogState::DirectoryNotFound;
+            return;
+        }
+        if !Self::directory_has_notes(&notes_path) {
+            self.dialog = DialogState::EmptyDirectory;
+        }
+        self.load_notes_and_select(read_last_opened_note(&notes_path));
+    }
 
-                remaining = &remaining[end + 2..];
-            } else {
-                result.push_str("[[");
-            }
+    /// Read config.toml again after it was edited from the config error
+    /// dialog: go on with it if it parses now, or show what's still wrong.
+    pub fn retry_config(&mut self) {
+        match Config::try_load() {
+            Ok(config) => self.finish_config_recovery(config),
+            Err(e) => self.config_error = Some(e),
         }
+    }
 
-        result.push_str(remaining);
-        result
+    /// Go on with the default settings for this session. The broken
+    /// config.toml is kept as it is, and `Config::save` won't replace it.
+    pub fn continue_with_default_config(&mut self) {
+        self.finish_config_recovery(Config::default());
+        self.set_status("Using the default settings; config.toml was left as it is".to_string());
     }
 
-    fn calculate_wiki_path(file_path: &std::path::Path, notes_root: &std::path::Path) -> String {
-        if let Ok(relative) = file_path.strip_prefix(notes_root) {
-            let path_str = relative.to_string_lossy();
-            if let Some(stripped) = path_str.strip_suffix(".md") {
-                return stripped.to_string();
+    /// Take `config` in place of the one that didn't parse, then open the
+    /// notes as startup would have.
+    fn finish_config_recovery(&mut self, config: Config) {
+        self.config_error = None;
+        self.dialog = DialogState::None;
+        self.base_config = config;
+        let startup_path = self.startup_path.take();
+        let select = match &startup_path {
+            Some(path) if path.is_file() => {
+                if let Some(parent) = path.parent() {
+                    self.base_config.notes_dir = parent.to_string_lossy().to_string();
+                }
+                Some(path.clone())
             }
-            path_str.to_string()
+            Some(dir) => {
+                self.base_config.notes_dir = dir.to_string_lossy().to_string();
+                None
+            }
+            None => None,
+        };
+        self.folder_config = Vec::new();
+        self.config = self.base_config.clone();
+        self.input_buffer = LineInput::new(self.config.notes_dir.clone());
+        self.apply_theme_named(&self.config.theme.clone());
+        configure_editor(&mut self.editor, &self.config, &self.theme);
+        self.report_config_problems();
+
+        if startup_path.is_none() && self.config.vaults.len() > 1 {
+            self.open_vault_picker();
+            self.vault_picker.at_startup = true;
+        } else if select.is_some() && self.config.notes_path().exists() {
+            self.load_notes_and_select(select);
         } else {
-            file_path.file_stem()
-                .map(|s| s.to_string_lossy().to_string())
-                .unwrap_or_default()
+            self.open_notes_dir();
         }
     }
 
-    pub fn complete_onboarding(&mut self) {
-        // 1. Save config
-        self.config.notes_dir = self.input_buffer.clone();
-        let _ = self.config.save();
-
-        let notes_path = self.config.notes_path();
-        let _ = fs::create_dir_all(&notes_path);
+    /// `:errors`: list what the last scan couldn't read.
+    pub fn show_scan_errors(&mut self) {
+        if self.scan_errors.is_empty() {
+            self.set_status("Every note was read".to_string());
+            return;
+        }
+        self.scan_errors_scroll = 0;
+        self.dialog = DialogState::ScanErrors;
+    }
 
-        let _ = fs::write(notes_path.join("01-Getting Started.md"), GETTING_STARTED_CONTENT);
-        let _ = fs::write(notes_path.join("02-Demo Note.md"), DEMO_NOTE_CONTENT);
+    /// Scan the notes folder again for notes that couldn't be read before.
+    pub fn retry_scan(&mut self) {
         self.dialog = DialogState::None;
-        self.load_notes_from_dir();
-
-        self.show_welcome = true;
-        self.needs_full_clear = true;
+        self.reload_notes(false);
+        self.report_scan_errors();
     }
 
-    /// Create the notes directory when it doesn't exist
-    pub fn create_notes_directory(&mut self) {
-        let notes_path = self.config.notes_path();
-        if fs::create_dir_all(&notes_path).is_ok() {
-            self.load_notes_from_dir();
-            // Show empty directory dialog since we just created an empty directory
-            if self.notes.is_empty() {
-                self.dialog = DialogState::EmptyDirectory;
-            } else {
-                self.dialog = DialogState::None;
-            }
+    /// Say in the status bar how many notes the last scan skipped, if any.
+    pub fn report_scan_errors(&mut self) {
+        match self.scan_errors.len() {
+            0 => {}
+            1 => self.set_status("1 note couldn't be read, see :errors".to_string()),
+            n => self.set_status(format!("{} notes couldn't be read, see :errors", n)),
         }
     }
 
@@ -2234,31 +6164,8 @@ impl App {
     }
 
     pub fn update_outline(&mut self) {
-        self.outline.clear();
-
-        for (idx, item) in self.content_items.iter().enumerate() {
-            if let ContentItem::TextLine(line) = item {
-                if line.starts_with("# ") {
-                    self.outline.push(OutlineItem {
-                        level: 1,
-                        title: line.trim_start_matches("# ").to_string(),
-                        line: idx,
-                    });
-                } else if line.starts_with("## ") {
-                    self.outline.push(OutlineItem {
-                        level: 2,
-                        title: line.trim_start_matches("## ").to_string(),
-                        line: idx,
-                    });
-                } else if line.starts_with("### ") {
-                    self.outline.push(OutlineItem {
-                        level: 3,
-                        title: line.trim_start_matches("### ").to_string(),
-                        line: idx,
-                    });
-                }
-            }
-        }
+        self.live_outline = None;
+        self.outline = outline_items(&self.content_items, &self.config.display);
 
         if !self.outline.is_empty() {
             self.outline_state.select(Some(0));
@@ -2266,19 +6173,85 @@ impl App {
     }
 
     pub fn update_content_items(&mut self) {
+        let note = match self.folder_view_path() {
+            Some(_) => None,
+            None => self.current_note().and_then(|n| n.file_path.clone()),
+        };
+        let entered = note != self.reading_note;
+        if entered {
+            self.remember_reading_position();
+            self.reading_note = note.clone();
+            self.render_cache.clear();
+            self.reading_jump = None;
+            // Images may have changed on disk since the note was last shown,
+            // and remote ones that failed get another try
+            self.image_dimensions.clear();
+            let pending = &self.pending_images;
+            self.image_info.retain(|url, _| pending.contains(url));
+            self.apply_folder_config(note.as_deref());
+        }
+
+        // Folded headings stay folded while the same note is shown again, as
+        // long as the heading is still on the same source line
+        let folded: Vec<(usize, String)> = if entered {
+            Vec::new()
+        } else {
+            self.heading_fold_states
+                .iter()
+                .filter(|&(_, &folded)| folded)
+                .filter_map(|(&index, _)| match self.content_items.get(index)? {
+                    ContentItem::TextLine(line) => Some((*self.content_item_source_lines.get(index)?, line.clone())),
+                    _ => None,
+                })
+                .collect()
+        };
         self.content_items.clear();
         self.content_item_source_lines.clear();
-        self.details_open_states.clear();
+        self.content_generation += 1;
         self.heading_fold_states.clear();
+        if entered {
+            self.details_open_states.clear();
+            self.image_states.clear();
+        }
 
-        // Get note data to extract frontmatter info
-        let note_data = self.current_note().map(|n| {
-            (n.content.clone(), n.frontmatter.clone(), n.content_start_line)
-        });
+        if let Some(folder) = self.folder_view_path().map(Path::to_path_buf) {
+            self.build_folder_listing(&folder);
+            self.content_cursor = 0;
+            return;
+        }
+
+        // Get note data to extract frontmatter info. Changes kept in the buffer
+        // with `esc_behavior = "keep"` are shown in place of the saved note.
+        let note_data = if self.has_dirty_buffer() {
+            let content = self.editor.lines().join("\n");
+            let (frontmatter, content_start_line) = super::frontmatter::Frontmatter::parse(&content);
+            Some((content, frontmatter, content_start_line))
+        } else {
+            self.current_note().map(|n| match n.lock {
+                NoteLock::Locked => (LOCKED_NOTE_PLACEHOLDER.to_string(), None, 0),
+                _ => (n.content.clone(), n.frontmatter.clone(), n.content_start_line),
+            })
+        };
 
         if let Some((content, frontmatter, content_start_line)) = note_data {
             let mut in_code_block = false;
-            let lines: Vec<&str> = content.lines().collect();
+            // Reference links are shown as the inline links they stand for
+            let definitions = reference_links::collect_definitions(&content);
+            let mut in_fence = false;
+            let resolved: Vec<Cow<str>> = content
+                .lines()
+                .map(|line| {
+                    if code_fence::is_fence(line) {
+                        in_fence = !in_fence;
+                    }
+                    if in_fence {
+                        Cow::Borrowed(line)
+                    } else {
+                        reference_links::resolve_references(line, &definitions)
+                    }
+                })
+                .collect();
+            let lines: Vec<&str> = resolved.iter().map(AsRef::as_ref).collect();
             let mut i = 0;
 
             // Handle frontmatter display
@@ -2331,12 +6304,17 @@ impl App {
                 i = content_start_line;
             }
 
+            // Source and rendered columns of the text of the list item or
+            // definition that indented lines continue
+            let mut list_context: Option<(usize, usize)> = None;
+
             while i < lines.len() {
                 let line = lines[i];
                 let line_index = i;
 
                 // Check for code fence
-                if line.starts_with("```") {
+                if code_fence::is_fence(line) {
+                    list_context = None;
                     let lang = line.trim_start_matches('`').to_string();
                     self.content_items.push(ContentItem::CodeFence(lang));
                     self.content_item_source_lines.push(line_index);
@@ -2354,28 +6332,86 @@ impl App {
                 }
 
                 // Check for image
-                if line.starts_with("![") && line.contains("](") && line.contains(')') {
-                    if let Some(start) = line.find("](") {
-                        if let Some(end) = line[start..].find(')') {
-                            let path = &line[start + 2..start + end];
-                            if !path.is_empty() {
-                                self.content_items.push(ContentItem::Image(path.to_string()));
-                                self.content_item_source_lines.push(line_index);
-                                i += 1;
-                                continue;
-                            }
+                if let Some((path, size)) = parse_image_line(line) {
+                    self.content_items.push(ContentItem::Image { path, size });
+                    self.content_item_source_lines.push(line_index);
+                    i += 1;
+                    continue;
+                }
+
+                if let Some((task, indent)) = parse_task_line(line, line_index) {
+                    self.content_items.push(task);
+                    self.content_item_source_lines.push(line_index);
+                    // The checkbox is drawn as `[ ] `, two columns narrower than its source
+                    list_context = Some((indent + 6, indent + 4));
+                    i += 1;
+                    continue;
+                }
+
+                // Indented lines under a list item (or a definition) continue it,
+                // even across blank lines
+                if let Some((source_column, column)) = list_context {
+                    if line.trim().is_empty() {
+                        self.content_items.push(ContentItem::TextLine(line.to_string()));
+                        self.content_item_source_lines.push(line_index);
+                        i += 1;
+                        continue;
+                    }
+                    if list_item::leading_columns(line) >= source_column
+                        && list_item::parse_list_marker(line).is_none()
+                    {
+                        self.content_items.push(ContentItem::ListContinuation {
+                            text: line.trim().to_string(),
+                            indent: column,
+                        });
+                        self.content_item_source_lines.push(line_index);
+                        i += 1;
+                        continue;
+                    }
+                }
+                list_context = None;
+
+                if let Some(marker) = list_item::parse_list_marker(line) {
+                    let column = marker.content_column();
+                    list_context = Some((column, column));
+                } else if let Some(text) = list_item::definition_text(line) {
+                    // `: definition` under a term line, or under another definition
+                    let term = match self.content_items.last_mut() {
+                        Some(ContentItem::TextLine(term)) if is_definition_term(term) => Some(std::mem::take(term)),
+                        Some(ContentItem::Definition(_) | ContentItem::DefinitionTerm(_)) => None,
+                        _ => {
+                            self.content_items.push(ContentItem::TextLine(line.to_string()));
+                            self.content_item_source_lines.push(line_index);
+                            i += 1;
+                            continue;
                         }
+                    };
+                    if let Some(term) = term {
+                        *self.content_items.last_mut().expect("term item") = ContentItem::DefinitionTerm(term);
                     }
+                    self.content_items.push(ContentItem::Definition(text.to_string()));
+                    self.content_item_source_lines.push(line_index);
+                    list_context = Some((2, DEFINITION_INDENT));
+                    i += 1;
+                    continue;
                 }
 
-                let trimmed = line.trim_start();
-                if trimmed.starts_with("- [ ] ") || trimmed.starts_with("- [x] ") || trimmed.starts_with("- [X] ") {
-                    let checked = trimmed.starts_with("- [x] ") || trimmed.starts_with("- [X] ");
-                    let text = trimmed[6..].to_string();
-                    let indent = line.chars().count() - trimmed.chars().count();
-                    self.content_items.push(ContentItem::TaskItem { text, checked, line_index, indent });
-                    self.content_item_source_lines.push(line_index);
+                if let Some(header) = callout::parse_header(line) {
+                    list_context = None;
+                    let mut body = Vec::new();
                     i += 1;
+                    while let Some(text) = lines.get(i).and_then(|line| callout::quoted_text(line)) {
+                        body.push(text.to_string());
+                        i += 1;
+                    }
+                    self.content_items.push(ContentItem::Callout {
+                        kind: header.kind,
+                        title: header.title,
+                        lines: body,
+                        fold: header.fold,
+                        id: line_index,
+                    });
+                    self.content_item_source_lines.push(line_index);
                     continue;
                 }
 
@@ -2508,8 +6544,118 @@ impl App {
                 self.content_item_source_lines.push(line_index);
                 i += 1;
             }
+
+            let join_lines = frontmatter.as_ref().and_then(|fm| fm.join_paragraph_lines());
+            if join_lines.unwrap_or(self.config.reading.join_paragraph_lines) {
+                join_paragraph_lines(&mut self.content_items, &mut self.content_item_source_lines);
+            }
+            if self.config.display.collapse_blank_lines {
+                collapse_blank_runs(&mut self.content_items, &mut self.content_item_source_lines);
+            }
+            self.content_items_parsed += self.content_items.len();
         }
+        self.keep_content_states(folded);
         self.content_cursor = 0;
+        if entered {
+            let remembered = note.and_then(|path| self.reading_positions.get(&path));
+            let position = remembered.unwrap_or_default().clamped(self.content_items.len());
+            self.content_cursor = position.cursor;
+            self.content_scroll_offset = position.scroll;
+        }
+    }
+
+    /// Carry folded headings, open details and drawn images over to rebuilt
+    /// content items, by the source lines they're on.
+    fn keep_content_states(&mut self, folded: Vec<(usize, String)>) {
+        for (line, heading) in folded {
+            let index = self.content_item_source_lines.partition_point(|&l| l < line);
+            if matches!(self.content_items.get(index), Some(ContentItem::TextLine(text)) if *text == heading) {
+                self.heading_fold_states.insert(index, true);
+            }
+        }
+        let mut blocks = HashSet::new();
+        let mut images = HashSet::new();
+        for (item, &line) in self.content_items.iter().zip(&self.content_item_source_lines) {
+            match item {
+                ContentItem::Details { id, .. } | ContentItem::Callout { id, .. } => {
+                    blocks.insert(*id);
+                }
+                ContentItem::Image { path, .. } => {
+                    images.insert((line, path.as_str()));
+                }
+                _ => {}
+            }
+        }
+        self.details_open_states.retain(|id, _| blocks.contains(id));
+        self.image_states.retain(|(line, path), _| images.contains(&(*line, path.as_str())));
+    }
+
+    /// Show the task on `line_index` again after it was checked or unchecked,
+    /// parsing only its own item; anything else rebuilds the note's items.
+    fn refresh_task_item(&mut self, line_index: usize) {
+        let saved_cursor = self.content_cursor;
+        let index = self.content_item_source_lines.partition_point(|&l| l < line_index);
+        let is_task = matches!(self.content_items.get(index), Some(ContentItem::TaskItem { line_index: l, .. }) if *l == line_index);
+        let task = self.displayed_source().filter(|_| is_task).and_then(|content| {
+            let line = content.lines().nth(line_index)?;
+            let definitions = reference_links::collect_definitions(&content);
+            parse_task_line(&reference_links::resolve_references(line, &definitions), line_index)
+        });
+        match task {
+            Some((task, _)) => {
+                self.content_items[index] = task;
+                self.content_items_parsed += 1;
+                self.content_generation += 1;
+            }
+            None => {
+                self.update_content_items();
+                self.content_cursor = saved_cursor.min(self.content_items.len().saturating_sub(1));
+            }
+        }
+    }
+
+    /// Keep where the content view is in the note it shows, unless it's
+    /// still where a jump into the note put it.
+    fn remember_reading_position(&mut self) {
+        let Some(path) = self.reading_note.clone() else {
+            return;
+        };
+        let position = ReadingPosition { cursor: self.content_cursor, scroll: self.content_scroll_offset };
+        if self.reading_jump != Some(position) {
+            self.reading_positions.remember(path, position);
+        }
+    }
+
+    /// Slide from what's on screen to where the jump about to be made lands,
+    /// with `reading.smooth_scroll` on. Only the content view slides.
+    fn start_smooth_scroll(&mut self) {
+        if self.config.reading.smooth_scroll && self.mode != Mode::Edit && self.source_view.is_none() {
+            let from = self.content_scroll_offset.saturating_sub(1);
+            self.smooth_scroll = Some(SmoothScroll::new(from, self.clock.now()));
+        }
+    }
+
+    /// Record that the content view was just put at its current position by
+    /// a jump to a heading, link target, search result or mark.
+    pub(crate) fn mark_reading_jump(&mut self) {
+        self.reading_jump = Some(ReadingPosition { cursor: self.content_cursor, scroll: self.content_scroll_offset });
+    }
+
+    /// Remember the current note's position and, with `remember_positions`,
+    /// write them all to the cache for the next start.
+    pub fn save_reading_positions(&mut self) {
+        self.remember_reading_position();
+        if self.config.remember_positions {
+            let _ = self.reading_positions.save(&positions_path());
+        }
+    }
+
+    /// With `remember_search_history`, write the search history to the
+    /// cache for the next start.
+    pub fn save_search_history(&self) {
+        if self.config.remember_search_history {
+            let _ = self.search_history.save(&search_history_path());
+        }
     }
 
     pub fn next_content_line(&mut self) {
@@ -2543,67 +6689,343 @@ impl App {
         }
     }
 
-    pub fn goto_first_content_line(&mut self) {
-        // Find first visible item
-        self.content_cursor = 0;
-        while self.content_cursor < self.content_items.len() && !self.is_content_item_visible(self.content_cursor) {
-            self.content_cursor += 1;
+    pub fn goto_first_content_line(&mut self) {
+        self.start_smooth_scroll();
+        // Find first visible item
+        self.content_cursor = 0;
+        while self.content_cursor < self.content_items.len() && !self.is_content_item_visible(self.content_cursor) {
+            self.content_cursor += 1;
+        }
+        self.selected_link_index = 0;
+    }
+
+    pub fn goto_last_content_line(&mut self) {
+        self.start_smooth_scroll();
+        if !self.content_items.is_empty() {
+            // Find last visible item
+            self.content_cursor = self.content_items.len() - 1;
+            while self.content_cursor > 0 && !self.is_content_item_visible(self.content_cursor) {
+                self.content_cursor -= 1;
+            }
+            self.selected_link_index = 0;
+        }
+    }
+
+    pub fn half_page_down_content(&mut self) {
+        let half = self.content_area.height.saturating_sub(2) as usize / 2;
+        self.move_content_cursor_by(half, true);
+    }
+
+    pub fn half_page_up_content(&mut self) {
+        let half = self.content_area.height.saturating_sub(2) as usize / 2;
+        self.move_content_cursor_by(half, false);
+    }
+
+    /// PageDown/PageUp in the content view.
+    pub fn page_content(&mut self, forward: bool) {
+        let page = (self.content_area.height.saturating_sub(2) as usize).max(1);
+        self.move_content_cursor_by(page, forward);
+    }
+
+    /// Moves the cursor `items` visible items down or up, stopping at the
+    /// first or last one.
+    fn move_content_cursor_by(&mut self, items: usize, forward: bool) {
+        if self.content_items.is_empty() {
+            return;
+        }
+        let max_cursor = self.content_items.len() - 1;
+        let mut moved = 0;
+        let mut new_cursor = self.content_cursor;
+        while moved < items && (if forward { new_cursor < max_cursor } else { new_cursor > 0 }) {
+            new_cursor = if forward { new_cursor + 1 } else { new_cursor - 1 };
+            if self.is_content_item_visible(new_cursor) {
+                moved += 1;
+            }
+        }
+        self.content_cursor = new_cursor;
+        self.selected_link_index = 0;
+    }
+
+    /// Move to the content item for 1-based source line `line` (`42G`), or to
+    /// the closest visible item above it when the line is inside a fold.
+    /// The text the content view shows: changes kept in the buffer, the
+    /// saved note, or the placeholder of a locked note.
+    pub fn displayed_source(&self) -> Option<String> {
+        if self.has_dirty_buffer() {
+            return Some(self.editor.lines().join("\n"));
+        }
+        self.current_note().map(|note| match note.lock {
+            NoteLock::Locked => LOCKED_NOTE_PLACEHOLDER.to_string(),
+            _ => note.content.clone(),
+        })
+    }
+
+    /// `:stats`: summarize the note being edited or viewed in a dialog.
+    pub fn show_note_stats(&mut self) {
+        let content = if self.mode == Mode::Edit {
+            Some(self.editor.lines().join("\n"))
+        } else {
+            self.displayed_source()
+        };
+        if let Some(content) = content {
+            self.note_stats = Some(analyze_note(&content));
+            self.dialog = DialogState::NoteStats;
+        }
+    }
+
+    /// `:scaffold [template]`: create folders and notes in the current
+    /// context folder from a template folder, or from an outline typed into
+    /// a dialog when no template is named.
+    pub fn start_scaffold(&mut self, template: Option<&str>) {
+        let folder = self.get_current_context_folder().unwrap_or_else(|| self.config.notes_path());
+        self.scaffold_prompt = Some(ScaffoldPrompt { folder, entries: None });
+        self.input_buffer.clear();
+        self.dialog_error = None;
+
+        let Some(name) = template else {
+            self.dialog = DialogState::Scaffold;
+            return;
+        };
+        let dir = Config::templates_dir().join(name);
+        match scaffold::read_template(&dir) {
+            Ok(entries) if !entries.is_empty() => self.ask_scaffold_project(entries),
+            Ok(_) => {
+                self.scaffold_prompt = None;
+                self.set_status(format!("Template '{}' has no notes", name));
+            }
+            Err(_) => {
+                self.scaffold_prompt = None;
+                self.set_status(format!("No template folder {}", dir.display()));
+            }
+        }
+    }
+
+    /// Enter in the Scaffold dialog: take the outline, or the project name.
+    pub fn submit_scaffold(&mut self) {
+        let Some(prompt) = self.scaffold_prompt.as_ref() else {
+            return;
+        };
+        match prompt.entries.clone() {
+            None => {
+                let entries = scaffold::parse_outline(&self.input_buffer);
+                if entries.is_empty() {
+                    self.dialog_error = Some("Type a note or folder name per line".to_string());
+                } else {
+                    self.input_buffer.clear();
+                    self.ask_scaffold_project(entries);
+                }
+            }
+            Some(entries) => {
+                let project = self.input_buffer.trim().to_string();
+                if project.is_empty() {
+                    self.dialog_error = Some("Project name can't be empty".to_string());
+                } else {
+                    self.create_scaffold(&entries, &project);
+                }
+            }
+        }
+    }
+
+    pub fn close_scaffold_prompt(&mut self) {
+        self.scaffold_prompt = None;
+        self.input_buffer.clear();
+        self.dialog_error = None;
+        self.dialog = DialogState::None;
+    }
+
+    fn ask_scaffold_project(&mut self, entries: Vec<ScaffoldEntry>) {
+        if scaffold::uses_project(&entries) {
+            if let Some(prompt) = self.scaffold_prompt.as_mut() {
+                prompt.entries = Some(entries);
+            }
+            self.dialog = DialogState::Scaffold;
+        } else {
+            self.create_scaffold(&entries, "");
+        }
+    }
+
+    fn create_scaffold(&mut self, entries: &[ScaffoldEntry], project: &str) {
+        let Some(prompt) = self.scaffold_prompt.take() else {
+            return;
+        };
+        self.close_scaffold_prompt();
+        let notes_path = self.config.notes_path();
+        let link_prefix = prompt
+            .folder
+            .strip_prefix(&notes_path)
+            .map(|relative| {
+                relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .unwrap_or_default();
+
+        let report = match scaffold::create_scaffold(entries, &prompt.folder, project, &link_prefix) {
+            Ok(report) => report,
+            Err(e) => {
+                self.show_error_toast(e);
+                return;
+            }
+        };
+
+        if self.mode == Mode::Edit {
+            self.cancel_edit();
+        }
+        // Show the first thing created: its folder expanded, or the note
+        let first = report.created.first();
+        if let Some(folder) = first.filter(|path| path.is_dir()) {
+            self.folder_states.insert(folder.clone(), true);
+        }
+        self.load_notes_from_dir();
+        if let Some(first) = first {
+            self.expand_folders_to(first);
+            if first.is_dir() {
+                if let Some(idx) = self.sidebar_items.iter().position(
+                    |item| matches!(&item.kind, SidebarItemKind::Folder { path, .. } if path == first),
+                ) {
+                    self.selected_sidebar_index = idx;
+                }
+            } else {
+                self.select_note_by_path(first);
+            }
+        }
+
+        let notes = report.created.iter().filter(|path| path.is_file()).count();
+        let mut message = format!("Created {} {}", notes, if notes == 1 { "note" } else { "notes" });
+        if !report.skipped.is_empty() {
+            let names: Vec<String> = report
+                .skipped
+                .iter()
+                .map(|path| path.strip_prefix(&prompt.folder).unwrap_or(path).display().to_string())
+                .collect();
+            message.push_str(&format!(", skipped existing {}", names.join(", ")));
+        }
+        self.set_status(message);
+    }
+
+    /// `gs`: switch the content panel between the rendered note and its
+    /// read-only source, keeping the cursor on the same source line.
+    pub fn toggle_source_view(&mut self) {
+        match self.source_view.take() {
+            Some(view) => {
+                if view.note == self.selected_note {
+                    self.goto_content_source_line(view.cursor + 1);
+                    self.sync_outline_to_content();
+                }
+            }
+            None => {
+                if self.folder_view_path().is_some() || self.current_note().is_none() {
+                    return;
+                }
+                let cursor = self.content_item_source_lines.get(self.content_cursor).copied().unwrap_or(0);
+                self.source_view = Some(SourceView {
+                    note: self.selected_note,
+                    cursor,
+                    scroll: cursor.saturating_sub(self.content_area.height.saturating_sub(2) as usize / 2),
+                });
+            }
+        }
+    }
+
+    /// Put the source view's cursor on `row`, or on the last line past the end.
+    pub fn set_source_cursor(&mut self, row: usize) {
+        let line_count = self.displayed_source().map_or(0, |source| source.lines().count());
+        if let Some(view) = self.source_view.as_mut() {
+            view.cursor = row.min(line_count.saturating_sub(1));
         }
-        self.selected_link_index = 0;
     }
 
-    pub fn goto_last_content_line(&mut self) {
-        if !self.content_items.is_empty() {
-            // Find last visible item
-            self.content_cursor = self.content_items.len() - 1;
-            while self.content_cursor > 0 && !self.is_content_item_visible(self.content_cursor) {
-                self.content_cursor -= 1;
-            }
-            self.selected_link_index = 0;
+    /// Move the source view's cursor by `delta` lines.
+    pub fn move_source_cursor(&mut self, delta: isize) {
+        if let Some(view) = self.source_view {
+            self.set_source_cursor(view.cursor.saturating_add_signed(delta));
         }
     }
 
-    pub fn half_page_down_content(&mut self) {
+    pub fn goto_content_source_line(&mut self, line: usize) {
         if self.content_items.is_empty() {
             return;
         }
-        let content_height = self.content_area.height.saturating_sub(2) as usize;
-        let half = content_height / 2;
-        let max_cursor = self.content_items.len().saturating_sub(1);
-
-        // Count visible items to move by half page
-        let mut moved = 0;
-        let mut new_cursor = self.content_cursor;
-        while moved < half && new_cursor < max_cursor {
-            new_cursor += 1;
-            if self.is_content_item_visible(new_cursor) {
-                moved += 1;
-            }
+        let mut idx = self.content_cursor_for_source_line(line.saturating_sub(1));
+        while idx > 0 && !self.is_content_item_visible(idx) {
+            idx -= 1;
         }
-        self.content_cursor = new_cursor;
+        self.content_cursor = idx;
         self.selected_link_index = 0;
     }
 
-    pub fn half_page_up_content(&mut self) {
-        if self.content_items.is_empty() {
+    /// `:42`, `:+5` or `:50%` typed into the view mode search box: moves the
+    /// content cursor, or the source view's, to that source line.
+    pub fn jump_to_source_line(&mut self, jump: LineJump) {
+        let Some(total) = self.displayed_source().map(|source| source.lines().count()) else {
+            return;
+        };
+        if let Some(view) = self.source_view {
+            self.set_source_cursor(jump.target(view.cursor + 1, total) - 1);
             return;
         }
-        let content_height = self.content_area.height.saturating_sub(2) as usize;
-        let half = content_height / 2;
+        let current = self.content_item_source_lines.get(self.content_cursor).map_or(1, |line| line + 1);
+        self.goto_content_source_line(jump.target(current, total));
+        let half_height = self.content_area.height.saturating_sub(2) as usize / 2;
+        self.content_scroll_offset = self.content_cursor.saturating_sub(half_height);
+        self.sync_outline_to_content();
+    }
 
-        // Count visible items to move by half page
-        let mut moved = 0;
-        let mut new_cursor = self.content_cursor;
-        while moved < half && new_cursor > 0 {
-            new_cursor -= 1;
-            if self.is_content_item_visible(new_cursor) {
-                moved += 1;
-            }
+    /// The jump a view mode search for `:<line>` asks for, if it's one.
+    pub fn buffer_search_line_jump(&self) -> Option<Result<LineJump, String>> {
+        if self.mode == Mode::Edit {
+            return None;
         }
-        self.content_cursor = new_cursor;
+        let spec = self.buffer_search.query.strip_prefix(':')?;
+        Some(LineJump::parse(spec).ok_or_else(|| format!("Not a line number: {}", spec)))
+    }
+
+    /// The setting a view mode search for `:set <name>=<value>` changes.
+    pub fn buffer_search_setting(&self) -> Option<String> {
+        if self.mode == Mode::Edit {
+            return None;
+        }
+        self.buffer_search.query.strip_prefix(":set ").map(|setting| setting.trim().to_string())
+    }
+
+    fn is_paragraph_boundary(item: &ContentItem) -> bool {
+        match item {
+            ContentItem::TextLine(line) => line.trim().is_empty() || Self::heading_level(line).is_some(),
+            _ => false,
+        }
+    }
+
+    /// `}` / `{`: move to the blank line or heading past the next (or previous) paragraph.
+    pub fn move_content_paragraph(&mut self, forward: bool) {
+        let visible: Vec<usize> = (0..self.content_items.len())
+            .filter(|&i| self.is_content_item_visible(i))
+            .collect();
+        let Some(from) = visible.iter().position(|&i| i >= self.content_cursor) else {
+            return;
+        };
+        let boundaries: Vec<bool> = visible
+            .iter()
+            .map(|&i| Self::is_paragraph_boundary(&self.content_items[i]))
+            .collect();
+        let to = if forward {
+            content_motion::next_paragraph(&boundaries, from)
+        } else {
+            content_motion::prev_paragraph(&boundaries, from)
+        };
+        self.content_cursor = visible[to];
         self.selected_link_index = 0;
     }
 
+    /// `H` / `M` / `L`: move to an item on screen, using the layout from the last frame.
+    pub fn goto_content_screen_line(&mut self, line: ScreenLine, offset: usize) {
+        if let Some(idx) = content_motion::screen_item(&self.content_item_rects, line, offset) {
+            self.content_cursor = idx;
+            self.selected_link_index = 0;
+        }
+    }
+
     pub fn toggle_floating_cursor(&mut self) {
         self.floating_cursor_mode = !self.floating_cursor_mode;
     }
@@ -2643,48 +7065,66 @@ impl App {
         }
     }
 
-    pub fn toggle_current_task(&mut self) {
-        let saved_cursor = self.content_cursor;
+    /// Check or uncheck the task on `line_index` of the selected note and save
+    /// it. Edits kept in the buffer get the change there instead, unsaved.
+    fn set_task_checked(&mut self, line_index: usize, checked: bool) {
+        let toggle = |line: &str| {
+            if checked {
+                line.replacen("- [ ]", "- [x]", 1)
+            } else {
+                line.replacen("- [x]", "- [ ]", 1)
+                    .replacen("- [X]", "- [ ]", 1)
+            }
+        };
 
+        if self.has_dirty_buffer() {
+            let new_line = self.editor.lines().get(line_index).map(|line| toggle(line));
+            if let Some(new_line) = new_line {
+                self.editor.replace_lines(&[(line_index, new_line)]);
+            }
+            return;
+        }
+
+        let line_ending = self.config.editor.line_ending;
+        let ensure_final_newline = self.config.editor.ensure_final_newline;
+        if let Some(note) = self.notes.get_mut(self.selected_note) {
+            let mut lines: Vec<&str> = note.content.lines().collect();
+            if line_index < lines.len() {
+                let toggled = toggle(lines[line_index]);
+                lines[line_index] = &toggled;
+                note.content = super::line_ending::join_lines(&lines, &note.content, ensure_final_newline);
+
+                let _ = note.write(line_ending);
+            }
+        }
+    }
+
+    pub fn toggle_current_task(&mut self) {
         if let Some(item) = self.content_items.get(self.content_cursor) {
             if let ContentItem::TaskItem { line_index, checked, .. } = item {
                 let line_index = *line_index;
                 let new_checked = !*checked;
 
-                if let Some(note) = self.notes.get_mut(self.selected_note) {
-                    let lines: Vec<&str> = note.content.lines().collect();
-                    if line_index < lines.len() {
-                        let line = lines[line_index];
-                        let new_line = if new_checked {
-                            line.replacen("- [ ]", "- [x]", 1)
-                        } else {
-                            line.replacen("- [x]", "- [ ]", 1)
-                                .replacen("- [X]", "- [ ]", 1)
-                        };
-
-                        let mut new_lines: Vec<String> = lines.iter().map(|s| s.to_string()).collect();
-                        new_lines[line_index] = new_line;
-                        note.content = new_lines.join("\n");
-
-                        if let Some(ref path) = note.file_path {
-                            let _ = fs::write(path, &note.content);
-                        }
-                    }
-                }
-
-                self.update_content_items();
-                self.content_cursor = saved_cursor.min(self.content_items.len().saturating_sub(1));
+                self.set_task_checked(line_index, new_checked);
+                self.refresh_task_item(line_index);
             }
         }
     }
 
     pub fn toggle_current_details(&mut self) {
-        if let Some(item) = self.content_items.get(self.content_cursor) {
-            if let ContentItem::Details { id, .. } = item {
-                let id = *id;
-                let current = self.details_open_states.get(&id).copied().unwrap_or(false);
-                self.details_open_states.insert(id, !current);
+        self.toggle_details_at(self.content_cursor);
+    }
+
+    /// Whether the details block or callout at `index` is open. A callout
+    /// that doesn't fold always is.
+    pub fn is_details_open(&self, index: usize) -> bool {
+        match self.content_items.get(index) {
+            Some(ContentItem::Details { id, .. }) => self.details_open_states.get(id).copied().unwrap_or(false),
+            Some(ContentItem::Callout { fold: Some(open), id, .. }) => {
+                self.details_open_states.get(id).copied().unwrap_or(*open)
             }
+            Some(ContentItem::Callout { fold: None, .. }) => true,
+            _ => false,
         }
     }
     pub fn heading_level(line: &str) -> Option<usize> {
@@ -2799,7 +7239,7 @@ impl App {
     }
 
     pub fn current_item_is_image(&self) -> Option<&str> {
-        if let Some(ContentItem::Image(path)) = self.content_items.get(self.content_cursor) {
+        if let Some(ContentItem::Image { path, .. }) = self.content_items.get(self.content_cursor) {
             Some(path)
         } else {
             None
@@ -3009,6 +7449,11 @@ impl App {
         let text = match self.content_items.get(index) {
             Some(ContentItem::TextLine(line)) => line.as_str(),
             Some(ContentItem::TaskItem { text, .. }) => text.as_str(),
+            Some(
+                ContentItem::ListContinuation { text, .. }
+                | ContentItem::DefinitionTerm(text)
+                | ContentItem::Definition(text),
+            ) => text.as_str(),
             Some(ContentItem::TableRow { cells, is_separator, column_widths, alignments, .. }) => {
                 if *is_separator {
                     return Vec::new();
@@ -3121,11 +7566,9 @@ impl App {
                 let from_bracket = &text[abs_bracket_pos..];
 
                 // skip if this is part of a wiki link
-                if from_bracket.starts_with("[[") {
-                    if let Some(close_pos) = from_bracket[2..].find("]]") {
-                        search_start = abs_bracket_pos + 2 + close_pos + 2;
-                        continue;
-                    }
+                if let Some(link) = WikiLink::parse(from_bracket) {
+                    search_start = abs_bracket_pos + link.len;
+                    continue;
                 }
 
                 if let Some(bracket_end) = from_bracket.find("](") {
@@ -3237,18 +7680,13 @@ impl App {
                 }
             }
 
-            if remaining.starts_with("[[") {
-                if let Some(end_pos) = remaining[2..].find("]]") {
-                    let target = &remaining[2..2 + end_pos];
-                    let full_link_len = 2 + end_pos + 2;
-
-                    if i + full_link_len <= target_pos {
-                        rendered_pos += target.chars().count();
-                        i += full_link_len;
-                        continue;
-                    } else {
-                        break;
-                    }
+            if let Some(link) = WikiLink::parse(remaining) {
+                if i + link.len <= target_pos {
+                    rendered_pos += link.shown().chars().count();
+                    i += link.len;
+                    continue;
+                } else {
+                    break;
                 }
             }
 
@@ -3337,28 +7775,36 @@ impl App {
                 len
             }
             Some(ContentItem::TaskItem { indent, .. }) => 6 + indent,
+            Some(ContentItem::ListContinuation { indent, .. }) => 2 + indent,
+            Some(ContentItem::Definition(_)) => 2 + DEFINITION_INDENT,
             Some(ContentItem::TableRow { .. }) => 3, // "  " cursor indicator + "│" left border
             _ => 2,
         }
     }
 
     pub fn item_is_image_at(&self, index: usize) -> Option<&str> {
-        if let Some(ContentItem::Image(path)) = self.content_items.get(index) {
+        if let Some(ContentItem::Image { path, .. }) = self.content_items.get(index) {
             Some(path)
         } else {
             None
         }
     }
 
+    /// A details block or a callout that folds, toggled with Space or a click.
     pub fn item_is_details_at(&self, index: usize) -> bool {
-        matches!(self.content_items.get(index), Some(ContentItem::Details { .. }))
+        matches!(
+            self.content_items.get(index),
+            Some(ContentItem::Details { .. } | ContentItem::Callout { fold: Some(_), .. })
+        )
     }
 
     pub fn toggle_details_at(&mut self, index: usize) {
-        if let Some(ContentItem::Details { id, .. }) = self.content_items.get(index) {
-            let id = *id;
-            let current = self.details_open_states.get(&id).copied().unwrap_or(false);
-            self.details_open_states.insert(id, !current);
+        if !self.item_is_details_at(index) {
+            return;
+        }
+        let open = self.is_details_open(index);
+        if let Some(ContentItem::Details { id, .. } | ContentItem::Callout { id, .. }) = self.content_items.get(index) {
+            self.details_open_states.insert(*id, !open);
         }
     }
 
@@ -3372,36 +7818,374 @@ impl App {
     }
 
     pub fn toggle_task_at(&mut self, index: usize) {
-        let saved_cursor = self.content_cursor;
-
         if let Some(item) = self.content_items.get(index) {
             if let ContentItem::TaskItem { line_index, checked, .. } = item {
                 let line_index = *line_index;
                 let new_checked = !*checked;
 
-                if let Some(note) = self.notes.get_mut(self.selected_note) {
-                    let lines: Vec<&str> = note.content.lines().collect();
-                    if line_index < lines.len() {
-                        let line = lines[line_index];
-                        let new_line = if new_checked {
-                            line.replacen("- [ ]", "- [x]", 1)
-                        } else {
-                            line.replacen("- [x]", "- [ ]", 1)
-                                .replacen("- [X]", "- [ ]", 1)
-                        };
+                self.set_task_checked(line_index, new_checked);
+                self.refresh_task_item(line_index);
+            }
+        }
+    }
 
-                        let mut new_lines: Vec<String> = lines.iter().map(|s| s.to_string()).collect();
-                        new_lines[line_index] = new_line;
-                        note.content = new_lines.join("\n");
+    /// Work out a bulk task edit on the section under the cursor and ask for
+    /// confirmation, reading the editor buffer when it holds the note.
+    pub fn start_section_tasks(&mut self, op: TaskOp) {
+        let in_buffer = self.mode == Mode::Edit || self.has_dirty_buffer();
+        let line = if self.mode == Mode::Edit {
+            self.editor.cursor().0
+        } else {
+            self.content_item_source_lines.get(self.content_cursor).copied().unwrap_or(0)
+        };
+        let archive = self.config.editor.archive_cleared_tasks;
+        let edit = if in_buffer {
+            plan_section_tasks(&self.editor.lines(), line, op, archive)
+        } else {
+            let Some(note) = self.current_note() else {
+                return;
+            };
+            plan_section_tasks(&note.content.lines().collect::<Vec<_>>(), line, op, archive)
+        };
 
-                        if let Some(ref path) = note.file_path {
-                            let _ = fs::write(path, &note.content);
-                        }
-                    }
+        match edit {
+            Some(edit) => {
+                self.pending_section_tasks = Some(edit);
+                self.dialog = DialogState::SectionTasksConfirm;
+            }
+            None => self.set_status(match op {
+                TaskOp::Complete => "No open tasks in this section".to_string(),
+                TaskOp::Clear => "No completed tasks in this section".to_string(),
+            }),
+        }
+    }
+
+    /// Carry out the confirmed section task edit. In the editor buffer it is
+    /// one undo step; otherwise the note is written and `u` takes it back.
+    pub fn apply_section_tasks(&mut self) {
+        let Some(edit) = self.pending_section_tasks.take() else {
+            return;
+        };
+        let saved_cursor = self.content_cursor;
+
+        if self.mode == Mode::Edit {
+            self.editor.replace_all_lines(edit.lines);
+            self.update_editor_highlights();
+        } else if self.has_dirty_buffer() {
+            self.editor.replace_all_lines(edit.lines);
+        } else if let Some(note) = self.notes.get_mut(self.selected_note) {
+            let lines: Vec<&str> = edit.lines.iter().map(String::as_str).collect();
+            let content = super::line_ending::join_lines(&lines, &note.content, self.config.editor.ensure_final_newline);
+            let previous = std::mem::replace(&mut note.content, content);
+            if let Some(path) = note.file_path.clone() {
+                if let Err(e) = note.write(self.config.editor.line_ending) {
+                    note.content = previous;
+                    self.show_error_toast(format!("Failed to save note: {}", e));
+                    return;
+                }
+                self.view_undo.push(ViewUndo { path, content: previous, paired: None });
+                if self.view_undo.len() > VIEW_UNDO_LIMIT {
+                    self.view_undo.remove(0);
                 }
+            }
+        }
 
-                self.update_content_items();
-                self.content_cursor = saved_cursor.min(self.content_items.len().saturating_sub(1));
+        if self.mode == Mode::Normal {
+            self.update_content_items();
+            self.update_outline();
+            self.content_cursor = saved_cursor.min(self.content_items.len().saturating_sub(1));
+        }
+        let tasks = if edit.affected == 1 { "task" } else { "tasks" };
+        self.set_status(match (edit.op, edit.archived) {
+            (TaskOp::Complete, _) => format!("Checked off {} {}", edit.affected, tasks),
+            (TaskOp::Clear, true) => format!("Archived {} {}", edit.affected, tasks),
+            (TaskOp::Clear, false) => format!("Removed {} {}", edit.affected, tasks),
+        });
+    }
+
+    /// Undo the last view-mode edit to the selected note: a change to its
+    /// kept buffer, or the last [`ViewUndo`] written to its file.
+    pub fn undo_view_edit(&mut self) {
+        let saved_cursor = self.content_cursor;
+        if self.has_dirty_buffer() {
+            if !self.editor.undo() {
+                self.set_status("Already at oldest change".to_string());
+                return;
+            }
+        } else {
+            let path = self.current_note().and_then(|n| n.file_path.clone());
+            let Some(pos) = self.view_undo.iter().rposition(|u| Some(&u.path) == path.as_ref()) else {
+                self.set_status("Already at oldest change".to_string());
+                return;
+            };
+            let undo = self.view_undo.remove(pos);
+            if let Some(note) = self.notes.get_mut(self.selected_note) {
+                let previous = std::mem::replace(&mut note.content, undo.content);
+                if let Err(e) = note.write(self.config.editor.line_ending) {
+                    note.content = previous;
+                    self.show_error_toast(format!("Failed to save note: {}", e));
+                    return;
+                }
+            }
+            let paired = undo.paired.and_then(|paired| {
+                let index = self.notes.iter().position(|n| n.file_path.as_ref() == Some(&paired.path))?;
+                Some((index, paired.content))
+            });
+            if let Some((index, content)) = paired {
+                let note = &mut self.notes[index];
+                let previous = std::mem::replace(&mut note.content, content);
+                if let Err(e) = note.write(self.config.editor.line_ending) {
+                    note.content = previous;
+                    let message = format!("Failed to save {}: {}", note.title, e);
+                    self.show_error_toast(message);
+                }
+            }
+        }
+
+        self.update_content_items();
+        self.update_outline();
+        self.content_cursor = saved_cursor.min(self.content_items.len().saturating_sub(1));
+        self.set_status("Undone".to_string());
+    }
+
+    /// Start refiling the list item under the cursor: pick the note, then
+    /// the heading in it, see [`App::confirm_refile`].
+    pub fn start_refile(&mut self) {
+        self.begin_refile(RefileKind::Item);
+    }
+
+    /// Start moving or copying the section of the heading under the cursor,
+    /// picking where it goes the way refiling does.
+    pub fn start_section_refile(&mut self, copy: bool) {
+        self.begin_refile(RefileKind::Section { copy });
+    }
+
+    fn begin_refile(&mut self, kind: RefileKind) {
+        let doing = kind.verb().to_lowercase();
+        if self.mode == Mode::Normal && self.has_dirty_buffer() {
+            self.set_status(format!("Save or discard the kept edits before you {}", doing));
+            return;
+        }
+        // Refiling saves the whole note, which the editor doesn't hold narrowed
+        if self.narrowed_heading.is_some() {
+            self.set_status(format!(":widen before you {}", doing));
+            return;
+        }
+        let row = if self.mode == Mode::Edit {
+            self.editor.cursor().0
+        } else {
+            match self.content_item_source_lines.get(self.content_cursor) {
+                Some(&row) => row,
+                None => return,
+            }
+        };
+        let lines = self.refile_source_lines();
+        let refs: Vec<&str> = lines.iter().map(String::as_str).collect();
+        if let Err(e) = refile::take(kind, &refs, row) {
+            self.set_status(e.to_string());
+            return;
+        }
+        self.refile_picker = RefilePicker { kind, source: Some((self.selected_note, row)), ..RefilePicker::default() };
+        self.open_picker(PickerPurpose::Refile);
+    }
+
+    /// The lines refiling takes the item from: the editor's in edit mode.
+    fn refile_source_lines(&self) -> Vec<String> {
+        if self.mode == Mode::Edit {
+            self.editor.lines().iter().map(|l| l.to_string()).collect()
+        } else {
+            self.current_note().map_or_else(Vec::new, |n| n.content.lines().map(str::to_string).collect())
+        }
+    }
+
+    /// Open the heading picker for the note picked to refile into.
+    fn choose_refile_heading(&mut self, note_index: usize) {
+        let Some((source, row)) = self.refile_picker.source else {
+            return;
+        };
+        let Some(note) = self.notes.get(note_index) else {
+            return;
+        };
+        if note.lock == NoteLock::Locked {
+            self.set_status(format!("Unlock {} before changing it", note.title));
+            return;
+        }
+        if note.file_path.is_none() {
+            return;
+        }
+        let headings = if note_index == source {
+            let lines = self.refile_source_lines();
+            let refs: Vec<&str> = lines.iter().map(String::as_str).collect();
+            let Ok(taken) = refile::take(self.refile_picker.kind, &refs, row) else {
+                return;
+            };
+            refile::headings(&taken.remaining.iter().map(String::as_str).collect::<Vec<_>>())
+        } else {
+            refile::headings(&note.content.lines().collect::<Vec<_>>())
+        };
+        self.refile_picker.target = note_index;
+        self.refile_picker.headings = headings;
+        self.dialog = DialogState::RefilePicker;
+    }
+
+    /// Move the item to the chosen place and save both notes. If the source
+    /// can't be written the target is put back, so the item is never lost or
+    /// doubled. In view mode `u` restores both notes. A copied section only
+    /// changes the target.
+    pub fn confirm_refile(&mut self) {
+        self.dialog = DialogState::None;
+        let picker = std::mem::take(&mut self.refile_picker);
+        let Some((source, row)) = picker.source.filter(|&(source, _)| source == self.selected_note) else {
+            return;
+        };
+        let Some((target, label)) = picker.choices().into_iter().nth(picker.selected) else {
+            return;
+        };
+        let lines = self.refile_source_lines();
+        let refs: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let mut taken = match refile::take(picker.kind, &refs, row) {
+            Ok(taken) => taken,
+            Err(e) => {
+                self.set_status(e.to_string());
+                return;
+            }
+        };
+        let moved_lines = taken.block.len();
+        let place = match &target {
+            RefileTarget::End => "the end".to_string(),
+            RefileTarget::Heading(_) => label,
+            RefileTarget::NewHeading(title) => format!("## {}", title),
+        };
+        let done = match picker.kind {
+            RefileKind::Item => "Refiled".to_string(),
+            RefileKind::Section { copy } => {
+                format!("{} {} line{}", if copy { "Copied" } else { "Moved" }, moved_lines, if moved_lines == 1 { "" } else { "s" })
+            }
+        };
+
+        let (new_source, paired) = if picker.target == source {
+            if picker.kind != RefileKind::Item && self.config.editor.relevel_sections {
+                if let Some(level) = refile::section_level(&taken.remaining, &target) {
+                    taken.block = refile::relevel(&taken.block, level);
+                }
+            }
+            (refile::insert(&taken.remaining, &target, &taken.block), None)
+        } else {
+            let Some(note) = self.notes.get(picker.target) else {
+                return;
+            };
+            let target_lines: Vec<String> = note.content.lines().map(str::to_string).collect();
+            if picker.kind != RefileKind::Item && self.config.editor.relevel_sections {
+                if let Some(level) = refile::section_level(&target_lines, &target) {
+                    taken.block = refile::relevel(&taken.block, level);
+                }
+            }
+            let new_target = refile::insert(&target_lines, &target, &taken.block);
+            let Some(previous) = self.write_note_lines(picker.target, &new_target) else {
+                return;
+            };
+            if picker.kind == (RefileKind::Section { copy: true }) {
+                self.finish_section_copy(source, picker.target, previous);
+                self.set_status(format!("{} to {} › {}", done, self.notes[picker.target].title, place));
+                return;
+            }
+            if picker.kind == (RefileKind::Section { copy: false }) && self.config.editor.leave_section_link {
+                let heading = refs.get(row).and_then(|line| heading_refactor::parse_heading(line)).map_or("", |(_, title)| title);
+                let link = wiki_link_text(&self.wiki_link_target(picker.target), Some(heading), None);
+                taken.remaining.insert(taken.rows.start, link);
+            }
+            (taken.remaining, Some(previous))
+        };
+
+        let saved_cursor = self.content_cursor;
+        if self.mode == Mode::Edit {
+            self.editor.replace_all_lines(new_source.clone());
+            self.update_editor_highlights();
+        }
+        let Some(previous) = self.write_note_lines(source, &new_source) else {
+            if let Some(previous) = paired {
+                self.restore_note_content(picker.target, previous);
+            }
+            if self.mode == Mode::Edit {
+                self.editor.undo();
+                self.update_editor_highlights();
+            }
+            return;
+        };
+
+        if self.mode == Mode::Edit {
+            self.editor.reset_change_baseline();
+        } else if let Some(path) = self.notes[source].file_path.clone() {
+            let paired = paired.and_then(|content| {
+                let path = self.notes[picker.target].file_path.clone()?;
+                Some(Box::new(ViewUndo { path, content, paired: None }))
+            });
+            self.view_undo.push(ViewUndo { path, content: previous, paired });
+            if self.view_undo.len() > VIEW_UNDO_LIMIT {
+                self.view_undo.remove(0);
+            }
+            self.update_content_items();
+            self.update_outline();
+            self.content_cursor = saved_cursor.min(self.content_items.len().saturating_sub(1));
+        }
+        self.set_status(format!("{} to {} › {}", done, self.notes[picker.target].title, place));
+    }
+
+    /// After a section is copied into another note, keep `u` in the note it
+    /// came from able to take it out again in view mode.
+    fn finish_section_copy(&mut self, source: usize, target: usize, previous: String) {
+        if self.mode == Mode::Edit {
+            return;
+        }
+        let (Some(path), Some(target_path)) = (self.notes[source].file_path.clone(), self.notes[target].file_path.clone())
+        else {
+            return;
+        };
+        let content = self.notes[source].content.clone();
+        let paired = Some(Box::new(ViewUndo { path: target_path, content: previous, paired: None }));
+        self.view_undo.push(ViewUndo { path, content, paired });
+        if self.view_undo.len() > VIEW_UNDO_LIMIT {
+            self.view_undo.remove(0);
+        }
+    }
+
+    pub fn cancel_refile(&mut self) {
+        self.dialog = DialogState::None;
+        self.refile_picker = RefilePicker::default();
+    }
+
+    pub fn refile_picker_select_next(&mut self) {
+        if self.refile_picker.selected + 1 < self.refile_picker.choices().len() {
+            self.refile_picker.selected += 1;
+        }
+    }
+
+    pub fn refile_picker_select_prev(&mut self) {
+        self.refile_picker.selected = self.refile_picker.selected.saturating_sub(1);
+    }
+
+    /// Write `lines` as the content of the note at `index`, returning what it
+    /// held before, or `None` after showing why it couldn't be saved.
+    fn write_note_lines(&mut self, index: usize, lines: &[String]) -> Option<String> {
+        let note = self.notes.get_mut(index)?;
+        let refs: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let content = super::line_ending::join_lines(&refs, &note.content, self.config.editor.ensure_final_newline);
+        let previous = std::mem::replace(&mut note.content, content);
+        if let Err(e) = note.write(self.config.editor.line_ending) {
+            note.content = previous;
+            let message = format!("Failed to save {}: {}", note.title, e);
+            self.show_error_toast(message);
+            return None;
+        }
+        Some(previous)
+    }
+
+    fn restore_note_content(&mut self, index: usize, content: String) {
+        if let Some(note) = self.notes.get_mut(index) {
+            note.content = content;
+            if let Err(e) = note.write(self.config.editor.line_ending) {
+                let message = format!("Failed to restore {}: {}", note.title, e);
+                self.show_error_toast(message);
             }
         }
     }
@@ -3438,11 +8222,11 @@ impl App {
                     .unwrap_or_else(|| self.config.notes_path());
 
                 let resolved = base_dir.join(path_part);
-                if let Ok(canonical) = resolved.canonicalize() {
+                if let Ok(canonical) = paths::canonicalize(&resolved) {
                     // Find matching note by canonical path
                     let found = self.notes.iter().enumerate().find_map(|(idx, note)| {
                         note.file_path.as_ref()
-                            .and_then(|fp| fp.canonicalize().ok())
+                            .and_then(|fp| paths::canonicalize(fp).ok())
                             .filter(|cp| *cp == canonical)
                             .map(|_| idx)
                     });
@@ -3478,15 +8262,16 @@ impl App {
                                     self.selected_sidebar_index = idx;
                                     self.selected_note = note_idx;
                                     self.push_navigation_history(note_idx);
-                                    self.content_cursor = 0;
-                                    self.content_scroll_offset = 0;
                                     self.selected_link_index = 0;
                                     self.update_content_items();
                                     self.update_outline();
+                                    self.content_cursor = 0;
+                                    self.content_scroll_offset = 0;
 
                                     if let Some(heading_text) = heading {
                                         self.navigate_to_heading(heading_text);
                                     }
+                                    self.mark_reading_jump();
                                     return;
                                 }
                             }
@@ -3497,12 +8282,57 @@ impl App {
             }
         }
 
-        #[cfg(target_os = "macos")]
-        let _ = Command::new("open").arg(url).spawn();
-        #[cfg(target_os = "linux")]
-        let _ = Command::new("xdg-open").arg(url).spawn();
-        #[cfg(target_os = "windows")]
-        let _ = Command::new("cmd").args(["/c", "start", "", url]).spawn();
+        self.open_external(url.to_string());
+    }
+
+    /// Hand `target` to the system opener. With `security.confirm_open` on,
+    /// a dialog showing the exact target has to be answered first.
+    pub fn open_external(&mut self, target: String) {
+        if self.pending_open.request(target, self.config.security.confirm_open) {
+            self.dialog = DialogState::ConfirmOpen;
+        }
+    }
+
+    /// The note or folder `gp`, `go`, `:path` and `:reveal` act on: the one
+    /// selected in the sidebar, else the open note.
+    fn path_target(&self) -> Option<PathBuf> {
+        if self.focus == Focus::Sidebar && self.mode != Mode::Edit {
+            return self.selected_sidebar_path();
+        }
+        self.current_note()?.file_path.clone()
+    }
+
+    /// `gp` or `:path`: the absolute path of the note or folder to the clipboard.
+    pub fn copy_path(&mut self) {
+        let Some(path) = self.path_target() else {
+            self.set_status("No note or folder selected".to_string());
+            return;
+        };
+        let path = path.display().to_string();
+        if crate::clipboard::set_system_text(&path) {
+            self.set_status(format!("Copied {}", path));
+        } else {
+            self.set_status("Couldn't reach the system clipboard".to_string());
+        }
+    }
+
+    /// `go` or `:reveal`: show the note or folder in the system file manager.
+    pub fn reveal_path(&mut self) {
+        let Some(path) = self.path_target() else {
+            self.set_status("No note or folder selected".to_string());
+            return;
+        };
+        let name = path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().to_string());
+        match opener::reveal(&path) {
+            Ok(()) => self.set_status(format!("Showing {} in the file manager", name)),
+            Err(e) => self.set_status(format!("Couldn't open the file manager: {}", e)),
+        }
+    }
+
+    /// Answer the open confirmation dialog, opening the target if `confirmed`.
+    pub fn finish_open_confirmation(&mut self, confirmed: bool) {
+        self.pending_open.answer(confirmed);
+        self.dialog = DialogState::None;
     }
 
     // ==================== Wiki Link Support ====================
@@ -3511,42 +8341,7 @@ impl App {
     /// "note" -> searches all notes recursively for matching title (root first, then subfolders)
     /// "folder/note" -> searches for note in specific folder
     pub fn resolve_wiki_link(&self, target: &str) -> Option<usize> {
-        if target.is_empty() {
-            return None;
-        }
-
-        let notes_path = self.config.notes_path();
-
-        if target.contains('/') {
-            let expected_path = notes_path.join(format!("{}.md", target));
-            let expected_str = expected_path.to_string_lossy();
-            for (idx, note) in self.notes.iter().enumerate() {
-                if let Some(file_path) = &note.file_path {
-                    if file_path.to_string_lossy() == expected_str {
-                        return Some(idx);
-                    }
-                }
-            }
-        } else {
-            // First, try to find in root directory (for backwards compatibility)
-            for (idx, note) in self.notes.iter().enumerate() {
-                if note.title.eq_ignore_ascii_case(target) {
-                    if let Some(file_path) = &note.file_path {
-                        if file_path.parent() == Some(notes_path.as_path()) {
-                            return Some(idx);
-                        }
-                    }
-                }
-            }
-            // If not found in root, search recursively in all subdirectories
-            // all notes in self.notes are already from the notes directory
-            for (idx, note) in self.notes.iter().enumerate() {
-                if note.title.eq_ignore_ascii_case(target) {
-                    return Some(idx);
-                }
-            }
-        }
-        None
+        link_graph::resolve_wiki_target(&self.notes, &self.config.notes_path(), target)
     }
 
     /// Check if a wiki link target exists
@@ -3555,6 +8350,34 @@ impl App {
     }
 
     /// Check if cursor position is inside code (inline code or code block)
+    /// After a space typed in insert mode: expand the snippet trigger before
+    /// it, like `;dt`, as one undo step. Triggers in code are left alone.
+    pub fn expand_snippet(&mut self) -> bool {
+        let (row, col) = self.editor.cursor();
+        let Some(line) = self.editor.lines().get(row).map(|line| line.to_string()) else {
+            return false;
+        };
+        let Some((start, name)) = snippets::trigger_before(&line, col, &self.config.editor.snippet_prefix) else {
+            return false;
+        };
+        let Some(template) = self.config.snippets.get(&name) else {
+            return false;
+        };
+        if self.is_cursor_in_code(row, start) {
+            return false;
+        }
+        let title = self.current_note().map(|note| note.title.as_str()).unwrap_or_default();
+        let editor = &self.config.editor;
+        let mut text = snippets::fill(template, &self.clock.local_now(), &editor.date_format, &editor.time_format, title);
+        // The typed space stays, unless the snippet ends in its own
+        if !text.ends_with(char::is_whitespace) {
+            text.push(' ');
+        }
+        self.editor.expand_before_cursor(start, &text);
+        self.update_editor_highlights();
+        true
+    }
+
     pub fn is_cursor_in_code(&self, row: usize, col: usize) -> bool {
         let lines = self.editor.lines();
 
@@ -3663,23 +8486,17 @@ impl App {
             }
         }
 
+        // Queries are as typed, escapes and all
         let content: String = chars[start..col].iter().collect();
 
-        if let Some(pipe_pos) = content.find('|') {
-            let before_pipe = &content[..pipe_pos];
-            let alias_query = content[pipe_pos + 1..].to_string();
-
-            if let Some(hash_pos) = before_pipe.find('#') {
-                let note_query = before_pipe[..hash_pos].to_string();
-                let heading_query = before_pipe[hash_pos + 1..].to_string();
-                Some((note_query, Some(heading_query), Some(alias_query), WikiAutocompleteMode::Alias))
+        if let Some((before_pipe, alias_query)) = wiki_link::split_unescaped(&content, '|') {
+            if let Some((note_query, heading_query)) = wiki_link::split_unescaped(before_pipe, '#') {
+                Some((note_query.to_string(), Some(heading_query.to_string()), Some(alias_query.to_string()), WikiAutocompleteMode::Alias))
             } else {
-                Some((before_pipe.to_string(), None, Some(alias_query), WikiAutocompleteMode::Alias))
+                Some((before_pipe.to_string(), None, Some(alias_query.to_string()), WikiAutocompleteMode::Alias))
             }
-        } else if let Some(hash_pos) = content.find('#') {
-            let note_query = content[..hash_pos].to_string();
-            let heading_query = content[hash_pos + 1..].to_string();
-            Some((note_query, Some(heading_query), None, WikiAutocompleteMode::Heading))
+        } else if let Some((note_query, heading_query)) = wiki_link::split_unescaped(&content, '#') {
+            Some((note_query.to_string(), Some(heading_query.to_string()), None, WikiAutocompleteMode::Heading))
         } else {
             Some((content, None, None, WikiAutocompleteMode::Note))
         }
@@ -3702,6 +8519,11 @@ impl App {
         let text = match self.content_items.get(index) {
             Some(ContentItem::TextLine(line)) => line.as_str(),
             Some(ContentItem::TaskItem { text, .. }) => text.as_str(),
+            Some(
+                ContentItem::ListContinuation { text, .. }
+                | ContentItem::DefinitionTerm(text)
+                | ContentItem::Definition(text),
+            ) => text.as_str(),
             _ => return Vec::new(),
         };
 
@@ -3737,47 +8559,28 @@ impl App {
 
             if let Some(start_pos) = remaining.find("[[") {
                 let abs_start = search_start + start_pos;
-                let after_brackets = &text[abs_start + 2..];
-
-                if let Some(end_pos) = after_brackets.find("]]") {
-                    let raw_content = &after_brackets[..end_pos];
-                    if !raw_content.is_empty() && !raw_content.contains('[') && !raw_content.contains(']') {
-                        // Parse: [[target#heading|display]]
-                        // First split by | to get display text (alias)
-                        let (content, display_text) = if let Some(pipe_pos) = raw_content.find('|') {
-                            (&raw_content[..pipe_pos], Some(raw_content[pipe_pos + 1..].to_string()))
-                        } else {
-                            (raw_content, None)
-                        };
-
-                        // Then split by # to get heading
-                        let (target, heading) = if let Some(hash_pos) = content.find('#') {
-                            (&content[..hash_pos], Some(content[hash_pos + 1..].to_string()))
-                        } else {
-                            (content, None)
-                        };
-
-                        let rendered_start = Self::calc_wiki_rendered_pos(text, abs_start);
-                        // Display text determines rendered length if present (use unicode width for CJK support)
-                        use unicode_width::UnicodeWidthStr;
-                        let display_len = display_text.as_ref().map_or(raw_content.width(), |d| d.width());
-                        let rendered_end = rendered_start + display_len;
-                        // Validate against target file (without heading)
-                        let is_valid = self.wiki_link_exists(target);
-
-                        links.push(WikiLinkInfo {
-                            target: target.to_string(),
-                            heading,
-                            display_text,
-                            start_col: rendered_start,
-                            end_col: rendered_end,
-                            is_valid,
-                        });
-                    }
-
-                    search_start = abs_start + 2 + end_pos + 2;
+                // Parse: [[target#heading|display]]
+                if let Some(link) = WikiLink::parse(&text[abs_start..]) {
+                    let rendered_start = Self::calc_wiki_rendered_pos(text, abs_start);
+                    // Display text determines rendered length (use unicode width for CJK support)
+                    use unicode_width::UnicodeWidthStr;
+                    let rendered_end = rendered_start + link.shown().width();
+                    // Validate against target file (without heading)
+                    let is_valid = self.wiki_link_exists(&link.target);
+
+                    search_start = abs_start + link.len;
+                    links.push(WikiLinkInfo {
+                        target: link.target,
+                        heading: link.heading,
+                        display_text: link.alias,
+                        start_col: rendered_start,
+                        end_col: rendered_end,
+                        is_valid,
+                    });
                     continue;
                 }
+                search_start = abs_start + 2;
+                continue;
             }
             break;
         }
@@ -3841,18 +8644,13 @@ impl App {
                 }
             }
 
-            if remaining.starts_with("[[") {
-                if let Some(end_pos) = remaining[2..].find("]]") {
-                    let target = &remaining[2..2 + end_pos];
-                    let full_link_len = 2 + end_pos + 2;
-
-                    if i + full_link_len <= target_pos {
-                        rendered_pos += target.width();
-                        i += full_link_len;
-                        continue;
-                    } else {
-                        break;
-                    }
+            if let Some(link) = WikiLink::parse(remaining) {
+                if i + link.len <= target_pos {
+                    rendered_pos += link.shown().width();
+                    i += link.len;
+                    continue;
+                } else {
+                    break;
                 }
             }
 
@@ -3900,26 +8698,8 @@ impl App {
 
     pub fn navigate_to_wiki_link_with_heading(&mut self, target: &str, heading: Option<&str>) -> bool {
         if let Some(note_idx) = self.resolve_wiki_link(target) {
-            if let Some(note) = self.notes.get(note_idx) {
-                if let Some(ref file_path) = note.file_path {
-                    let notes_root = self.config.notes_path();
-                    let mut current = file_path.parent();
-                    let mut needs_rebuild = false;
-                    while let Some(parent) = current {
-                        if parent == notes_root {
-                            break;
-                        }
-                        if !self.folder_states.get(&parent.to_path_buf()).copied().unwrap_or(false) {
-                            self.folder_states.insert(parent.to_path_buf(), true);
-                            needs_rebuild = true;
-                        }
-                        current = parent.parent();
-                    }
-                    if needs_rebuild {
-                        Self::update_tree_expanded_states(&mut self.file_tree, &self.folder_states);
-                        self.rebuild_sidebar_items();
-                    }
-                }
+            if let Some(file_path) = self.notes.get(note_idx).and_then(|n| n.file_path.clone()) {
+                self.expand_folders_to(&file_path);
             }
 
             for (idx, item) in self.sidebar_items.iter().enumerate() {
@@ -3930,16 +8710,17 @@ impl App {
                         self.selected_sidebar_index = idx;
                         self.selected_note = note_idx;
                         self.push_navigation_history(note_idx);
-                        self.content_cursor = 0;
-                        self.content_scroll_offset = 0;
                         self.selected_link_index = 0;
                         self.update_content_items();
                         self.update_outline();
+                        self.content_cursor = 0;
+                        self.content_scroll_offset = 0;
 
                         // If heading is specified, navigate to it
                         if let Some(heading_text) = heading {
                             self.navigate_to_heading(heading_text);
                         }
+                        self.mark_reading_jump();
 
                         return true;
                     }
@@ -3949,6 +8730,27 @@ impl App {
         false
     }
 
+    /// Expand every collapsed folder above `file_path` so its note shows up in the sidebar.
+    fn expand_folders_to(&mut self, file_path: &std::path::Path) {
+        let notes_root = self.config.notes_path();
+        let mut current = file_path.parent();
+        let mut needs_rebuild = false;
+        while let Some(parent) = current {
+            if parent == notes_root {
+                break;
+            }
+            if !self.folder_states.get(&parent.to_path_buf()).copied().unwrap_or(false) {
+                self.folder_states.insert(parent.to_path_buf(), true);
+                needs_rebuild = true;
+            }
+            current = parent.parent();
+        }
+        if needs_rebuild {
+            Self::update_tree_expanded_states(&mut self.file_tree, &self.folder_states);
+            self.rebuild_sidebar_items();
+        }
+    }
+
     /// Navigate to a heading in the current note's content.
     ///
     /// Matches against the GitHub-style heading slug (lowercased, whitespace
@@ -3964,6 +8766,7 @@ impl App {
             if let ContentItem::TextLine(line) = item {
                 if let Some(title) = heading_text(line) {
                     if slugify_heading(title) == target_slug {
+                        self.start_smooth_scroll();
                         self.content_cursor = idx;
                         self.content_scroll_offset = idx.saturating_sub(2);
                         return;
@@ -4106,6 +8909,9 @@ impl App {
         let mut edges: Vec<GraphEdge> = Vec::new();
         let mut note_to_node: HashMap<usize, usize> = HashMap::new();
         for (note_idx, note) in self.notes.iter().enumerate() {
+            if note.lock == NoteLock::Locked {
+                continue;
+            }
             let node_idx = nodes.len();
             note_to_node.insert(note_idx, node_idx);
 
@@ -4140,30 +8946,18 @@ impl App {
             });
         }
 
-        for (note_idx, note) in self.notes.iter().enumerate() {
-            let wiki_targets = self.extract_wiki_targets_from_content(&note.content);
-
-            for target in wiki_targets {
-                if let Some(target_note_idx) = self.resolve_wiki_link(&target) {
-                    if let (Some(&from_node), Some(&to_node)) =
-                        (note_to_node.get(&note_idx), note_to_node.get(&target_note_idx))
-                    {
-                        let existing = edges.iter_mut().find(|e| e.from == to_node && e.to == from_node);
-
-                        if let Some(edge) = existing {
-                            edge.bidirectional = true;
-                        } else {
-                            let already_exists = edges.iter().any(|e| e.from == from_node && e.to == to_node);
-                            if !already_exists {
-                                edges.push(GraphEdge {
-                                    from: from_node,
-                                    to: to_node,
-                                    bidirectional: false,
-                                });
-                            }
-                        }
-                    }
-                }
+        for link in link_graph::note_links(&self.notes, &self.config.notes_path()) {
+            let (Some(&from_node), Some(&to_node)) = (note_to_node.get(&link.from), note_to_node.get(&link.to)) else {
+                continue;
+            };
+            if let Some(edge) = edges.iter_mut().find(|e| e.from == to_node && e.to == from_node) {
+                edge.bidirectional = true;
+            } else if !edges.iter().any(|e| e.from == from_node && e.to == to_node) {
+                edges.push(GraphEdge {
+                    from: from_node,
+                    to: to_node,
+                    bidirectional: false,
+                });
             }
         }
 
@@ -4179,25 +8973,80 @@ impl App {
         }
     }
 
-    fn extract_wiki_targets_from_content(&self, content: &str) -> Vec<String> {
-        let mut targets = Vec::new();
-        for line in content.lines() {
-            for wiki_link in self.extract_wiki_links_from_text(line) {
-                if !targets.contains(&wiki_link.target) {
-                    targets.push(wiki_link.target);
-                }
-            }
+    /// Open the graph's `/` search, listing every node until something is typed.
+    pub fn open_graph_search(&mut self) {
+        self.graph_view.search = Some(GraphSearch::default());
+        self.update_graph_search();
+    }
+
+    /// Match the graph search query against the nodes' full note titles,
+    /// with the quick switcher's scorer.
+    pub fn update_graph_search(&mut self) {
+        let Some(search) = &self.graph_view.search else {
+            return;
+        };
+        let title = |idx: usize| {
+            let node = &self.graph_view.nodes[idx];
+            self.notes.get(node.note_index).map_or(node.title.as_str(), |n| n.title.as_str())
+        };
+        let mut scored: Vec<(i32, usize)> = (0..self.graph_view.nodes.len())
+            .filter_map(|idx| fuzzy_match(title(idx), &search.query).map(|score| (score, idx)))
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| title(a.1).to_lowercase().cmp(&title(b.1).to_lowercase())));
+        let matches: Vec<usize> = scored.into_iter().map(|(_, idx)| idx).collect();
+        if let Some(search) = &mut self.graph_view.search {
+            search.selected = search.selected.min(matches.len().saturating_sub(1));
+            search.matches = matches;
+        }
+    }
+
+    /// Select and center the graph search's chosen node, closing the search.
+    pub fn pick_graph_search(&mut self) {
+        let Some(search) = self.graph_view.search.take() else {
+            return;
+        };
+        let Some(&node) = search.matches.get(search.selected) else {
+            self.status_message = Some("No matching note in the graph".to_string());
+            return;
+        };
+        self.graph_view.selected_node = Some(node);
+        self.graph_view.link_index = 0;
+        self.graph_view.needs_center = true;
+        self.graph_view.flash = Some(NodeFlash { node, shown_at: self.clock.now() });
+    }
+
+    /// Drop a leader sequence that waited too long for its next key. Returns
+    /// `true` when the status bar needs drawing again without it.
+    pub fn tick_leader(&mut self) -> bool {
+        if self.leader.is_expired(self.clock.now()) {
+            self.leader.cancel();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Drop the picked node's highlight once it's over. Returns `true` when
+    /// the graph needs drawing again without it.
+    pub fn tick_graph_flash(&mut self) -> bool {
+        if self.graph_view.flash.is_some_and(|f| f.is_expired(self.clock.now())) {
+            self.graph_view.flash = None;
+            true
+        } else {
+            false
         }
-        targets
     }
 
+    /// Suggestions for a `[[` query as typed, their `insert_text` escaped for
+    /// inside the brackets.
     pub fn build_wiki_suggestions(&self, query: &str) -> Vec<WikiSuggestion> {
         let mut suggestions = Vec::new();
         let notes_path = self.config.notes_path();
+        let query = wiki_link::unescape(query);
         let (folder_prefix, note_query) = if let Some(last_slash) = query.rfind('/') {
             (&query[..=last_slash], &query[last_slash + 1..])
         } else {
-            ("", query)
+            ("", query.as_str())
         };
 
         for (idx, note) in self.notes.iter().enumerate() {
@@ -4216,7 +9065,7 @@ impl App {
                     };
                     suggestions.push(WikiSuggestion {
                         display_name: note.title.clone(),
-                        insert_text: note.title.clone(),
+                        insert_text: wiki_link::escape(&note.title),
                         is_folder: false,
                         path: note.file_path.as_ref()
                             .map(|p| p.display().to_string())
@@ -4246,7 +9095,7 @@ impl App {
                     if let Some(score) = fuzzy_match(&item.display_name, note_query) {
                         suggestions.push(WikiSuggestion {
                             display_name: item.display_name.clone(),
-                            insert_text: format!("{}/", folder_path),
+                            insert_text: format!("{}/", wiki_link::escape(&folder_path)),
                             is_folder: true,
                             path: path.display().to_string(),
                             score,
@@ -4273,6 +9122,8 @@ impl App {
     /// This extracts headings from the note's content and filters by query
     pub fn build_heading_suggestions(&self, note_target: &str, query: &str) -> Vec<WikiSuggestion> {
         let mut suggestions = Vec::new();
+        let (note_target, query) = (wiki_link::unescape(note_target), wiki_link::unescape(query));
+        let query = query.as_str();
 
         for (idx, note) in self.notes.iter().enumerate() {
             if let Some(wiki_path) = self.get_wiki_path_for_note(idx) {
@@ -4301,7 +9152,7 @@ impl App {
                             let prefix = "  ".repeat(level.saturating_sub(1));
                             suggestions.push(WikiSuggestion {
                                 display_name: format!("{}{}", prefix, title),
-                                insert_text: title.clone(), // Just the heading text for insertion
+                                insert_text: wiki_link::escape(&title), // Just the heading text for insertion
                                 is_folder: false,
                                 path: format!("{}#{}", wiki_path, title),
                                 score,
@@ -4319,13 +9170,195 @@ impl App {
         suggestions
     }
 
-    pub fn create_note_from_wiki_target(&mut self, target: &str) -> bool {
-        if target.starts_with('/') || target.starts_with('\\') {
-            return false;
+    /// Start `Ctrl-N`/`Ctrl-P` completion of the word before the cursor. Candidates
+    /// are buffer words (nearest first) followed by matching note titles.
+    pub fn start_word_completion(&mut self, forward: bool) {
+        let Some((start_col, prefix)) = self.editor.completion_prefix() else {
+            self.vim.status_message = Some("No word to complete".to_string());
+            return;
+        };
+
+        let mut candidates = self.editor.completion_candidates(&prefix);
+        for note in &self.notes {
+            if note.title.len() > prefix.len()
+                && note.title.starts_with(&prefix)
+                && !candidates.contains(&note.title)
+            {
+                candidates.push(note.title.clone());
+            }
+        }
+
+        if candidates.is_empty() {
+            self.vim.status_message = Some(format!("No completions for '{}'", prefix));
+            return;
+        }
+
+        self.word_completion = WordCompletionState::Open {
+            start_col,
+            prefix,
+            candidates,
+            selected: None,
+        };
+        self.cycle_word_completion(forward);
+    }
+
+    /// Move to the next/previous candidate and put it in the buffer. Cycling past
+    /// either end restores the original prefix, like vim.
+    pub fn cycle_word_completion(&mut self, forward: bool) {
+        let WordCompletionState::Open { start_col, ref prefix, ref candidates, ref mut selected } =
+            self.word_completion
+        else {
+            return;
+        };
+
+        let last = candidates.len() - 1;
+        *selected = match (*selected, forward) {
+            (None, true) => Some(0),
+            (None, false) => Some(last),
+            (Some(i), true) if i < last => Some(i + 1),
+            (Some(i), false) if i > 0 => Some(i - 1),
+            _ => None,
+        };
+        let text = selected.map_or(prefix, |i| &candidates[i]).clone();
+        self.editor.replace_before_cursor(start_col, &text);
+    }
+
+    pub fn close_word_completion(&mut self) {
+        self.word_completion = WordCompletionState::None;
+    }
+
+    /// Open link target completion if the text before the cursor ends in `](`.
+    pub fn maybe_open_link_completion(&mut self) {
+        let (row, col) = self.editor.cursor();
+        if col < 2 || self.is_cursor_in_code(row, col) {
+            return;
+        }
+        let ends_with_trigger = self.editor.lines().get(row).is_some_and(|line| {
+            let chars: Vec<char> = line.chars().take(col).collect();
+            chars[col - 2..] == [']', '(']
+        });
+        if !ends_with_trigger {
+            return;
+        }
+
+        let candidates = self.build_link_candidates();
+        let suggestions = Self::filter_link_candidates(&candidates, "");
+        self.link_completion = LinkCompletionState::Open {
+            start_col: col,
+            query: String::new(),
+            candidates,
+            suggestions,
+            selected_index: 0,
+        };
+    }
+
+    /// Vault files other than the note being edited, with link text relative to it.
+    fn build_link_candidates(&self) -> Vec<WikiSuggestion> {
+        let notes_path = self.config.notes_path();
+        let current = self.current_note().and_then(|n| n.file_path.clone());
+        let from_dir = current
+            .as_ref()
+            .and_then(|p| p.parent())
+            .unwrap_or(&notes_path)
+            .to_path_buf();
+
+        link_targets::vault_files(&notes_path)
+            .into_iter()
+            .filter(|path| Some(path) != current.as_ref())
+            .map(|path| {
+                let relative = path.strip_prefix(&notes_path).unwrap_or(&path);
+                let display_name = relative
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let folder_hint = relative
+                    .parent()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .filter(|p| !p.is_empty());
+                WikiSuggestion {
+                    display_name,
+                    insert_text: link_targets::relative_link(&from_dir, &path),
+                    is_folder: false,
+                    path: relative.to_string_lossy().to_string(),
+                    score: 0,
+                    folder_hint,
+                }
+            })
+            .collect()
+    }
+
+    fn filter_link_candidates(candidates: &[WikiSuggestion], query: &str) -> Vec<WikiSuggestion> {
+        let mut suggestions: Vec<WikiSuggestion> = candidates
+            .iter()
+            .filter_map(|c| {
+                let score = fuzzy_match(&c.display_name, query)
+                    .max(fuzzy_match(&c.path, query))?;
+                Some(WikiSuggestion { score, ..c.clone() })
+            })
+            .collect();
+        suggestions.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+        suggestions
+    }
+
+    /// Re-filter the open link completion after its query changed.
+    pub fn set_link_completion_query(&mut self, new_query: String) {
+        if let LinkCompletionState::Open {
+            ref mut query,
+            ref candidates,
+            ref mut suggestions,
+            ref mut selected_index,
+            ..
+        } = self.link_completion
+        {
+            *suggestions = Self::filter_link_candidates(candidates, &new_query);
+            *query = new_query;
+            *selected_index = 0;
+        }
+    }
+
+    /// Replace the typed query with the selected target and close the link.
+    pub fn accept_link_completion(&mut self) {
+        let LinkCompletionState::Open {
+            start_col,
+            ref suggestions,
+            selected_index,
+            ..
+        } = self.link_completion
+        else {
+            return;
+        };
+        let Some(target) = suggestions.get(selected_index).map(|s| s.insert_text.clone()) else {
+            return;
+        };
+
+        self.editor.replace_before_cursor(start_col, &target);
+        let (row, col) = self.editor.cursor();
+        let already_closed = self
+            .editor
+            .lines()
+            .get(row)
+            .is_some_and(|line| line.chars().nth(col) == Some(')'));
+        if !already_closed {
+            self.editor.insert_char(')');
         }
+        self.close_link_completion();
+    }
+
+    pub fn close_link_completion(&mut self) {
+        self.link_completion = LinkCompletionState::None;
+    }
+
+    pub fn create_note_from_wiki_target(&mut self, target: &str) -> bool {
+        let (relative, title) = match nested_note_path(target) {
+            Ok(parts) => parts,
+            Err(e) => {
+                self.show_error_toast(format!("Can't create [[{}]]: {}", target, e));
+                return false;
+            }
+        };
 
         let notes_path = self.config.notes_path();
-        let file_path = notes_path.join(format!("{}.md", target));
+        let file_path = notes_path.join(relative);
 
         if let Some(parent) = file_path.parent() {
             if !parent.exists() {
@@ -4335,8 +9368,6 @@ impl App {
             }
         }
 
-        let title = target.rsplit('/').next().unwrap_or(target);
-
         let content = format!("# {}\n\n", title);
         if fs::write(&file_path, &content).is_err() {
             return false;
@@ -4347,13 +9378,14 @@ impl App {
         self.navigate_to_wiki_link(target)
     }
 
-    pub fn open_current_image(&self) {
+    pub fn open_current_image(&mut self) {
         if let Some(path) = self.current_item_is_image() {
-            self.open_path_or_url(path);
+            let path = path.to_string();
+            self.open_path_or_url(&path);
         }
     }
 
-    pub fn open_path_or_url(&self, path: &str) {
+    pub fn open_path_or_url(&mut self, path: &str) {
         let is_url = path.starts_with("http://") || path.starts_with("https://");
 
         let open_path = if is_url {
@@ -4364,20 +9396,16 @@ impl App {
             path.to_string()
         };
 
-        #[cfg(target_os = "macos")]
-        let _ = Command::new("open").arg(&open_path).spawn();
-        #[cfg(target_os = "linux")]
-        let _ = Command::new("xdg-open").arg(&open_path).spawn();
-        #[cfg(target_os = "windows")]
-        let _ = Command::new("cmd").args(["/c", "start", "", &open_path]).spawn();
+        self.open_external(open_path);
     }
 
     pub fn next_sidebar_item(&mut self) {
         if self.sidebar_items.is_empty() {
             return;
         }
-        self.selected_sidebar_index = (self.selected_sidebar_index + 1) % self.sidebar_items.len();
+        self.selected_sidebar_index = self.step_sidebar_index(true);
         self.sync_selected_note_from_sidebar();
+        self.show_selected_folder();
         self.update_content_items();
         self.update_outline();
     }
@@ -4386,22 +9414,66 @@ impl App {
         if self.sidebar_items.is_empty() {
             return;
         }
-        self.selected_sidebar_index = if self.selected_sidebar_index == 0 {
-            self.sidebar_items.len() - 1
+        self.selected_sidebar_index = self.step_sidebar_index(false);
+        self.sync_selected_note_from_sidebar();
+        self.show_selected_folder();
+        self.update_content_items();
+        self.update_outline();
+    }
+
+    /// The row after (or before) the selected one, wrapping around and
+    /// skipping section headers.
+    fn step_sidebar_index(&self, forward: bool) -> usize {
+        let len = self.sidebar_items.len();
+        let mut idx = self.selected_sidebar_index.min(len - 1);
+        for _ in 0..len {
+            idx = if forward { (idx + 1) % len } else { (idx + len - 1) % len };
+            if !matches!(self.sidebar_items[idx].kind, SidebarItemKind::Header) {
+                break;
+            }
+        }
+        idx
+    }
+
+    /// PageDown/PageUp in the sidebar: a panel's height of rows, stopping at
+    /// the first or last one instead of wrapping.
+    pub fn page_sidebar(&mut self, forward: bool) {
+        if self.sidebar_items.is_empty() {
+            return;
+        }
+        let page = (self.sidebar_area.height.saturating_sub(2) as usize).max(1);
+        let selectable = |i: &usize| !matches!(self.sidebar_items[*i].kind, SidebarItemKind::Header);
+        let current = self.selected_sidebar_index.min(self.sidebar_items.len() - 1);
+        let target = if forward {
+            (current + 1..self.sidebar_items.len()).filter(selectable).take(page).last()
         } else {
-            self.selected_sidebar_index - 1
+            (0..current).rev().filter(selectable).take(page).last()
         };
+        let Some(target) = target else {
+            return;
+        };
+        self.selected_sidebar_index = target;
         self.sync_selected_note_from_sidebar();
+        self.show_selected_folder();
         self.update_content_items();
         self.update_outline();
     }
 
+    /// The top row that can be selected, below any section header.
+    fn first_selectable_sidebar_index(&self) -> usize {
+        self.sidebar_items
+            .iter()
+            .position(|item| !matches!(item.kind, SidebarItemKind::Header))
+            .unwrap_or(0)
+    }
+
     pub fn goto_first_sidebar_item(&mut self) {
         if self.sidebar_items.is_empty() {
             return;
         }
-        self.selected_sidebar_index = 0;
+        self.selected_sidebar_index = self.first_selectable_sidebar_index();
         self.sync_selected_note_from_sidebar();
+        self.show_selected_folder();
         self.update_content_items();
         self.update_outline();
     }
@@ -4412,15 +9484,17 @@ impl App {
         }
         self.selected_sidebar_index = self.sidebar_items.len() - 1;
         self.sync_selected_note_from_sidebar();
+        self.show_selected_folder();
         self.update_content_items();
         self.update_outline();
     }
 
     pub fn handle_sidebar_enter(&mut self) {
-        let item_info = self.sidebar_items.get(self.selected_sidebar_index).map(|item| {
+        let item_info = self.sidebar_items.get(self.selected_sidebar_index).and_then(|item| {
             match &item.kind {
-                SidebarItemKind::Folder { path, .. } => (true, path.clone(), 0),
-                SidebarItemKind::Note { note_index } => (false, PathBuf::new(), *note_index),
+                SidebarItemKind::Folder { path, .. } => Some((true, path.clone(), 0)),
+                SidebarItemKind::Note { note_index } => Some((false, PathBuf::new(), *note_index)),
+                SidebarItemKind::Header => None,
             }
         });
 
@@ -4430,6 +9504,7 @@ impl App {
             } else {
                 self.toggle_focus(false);
                 self.push_navigation_history(note_index);
+                self.unlock_selected_note(false);
             }
         }
     }
@@ -4461,20 +9536,86 @@ impl App {
         }
     }
 
-    pub fn toggle_focus(&mut self, backwards: bool) {
-        self.focus = match self.focus {
-            Focus::Sidebar => if backwards { Focus::Outline } else { Focus::Content },
-            Focus::Content => if backwards { Focus::Sidebar } else { Focus::Outline },
-            Focus::Outline => if backwards {Focus::Content} else {Focus::Sidebar},
-        };
+    pub fn toggle_focus(&mut self, backwards: bool) {
+        for _ in 0..3 {
+            self.focus = match self.focus {
+                Focus::Sidebar => if backwards { Focus::Outline } else { Focus::Content },
+                Focus::Content => if backwards { Focus::Sidebar } else { Focus::Outline },
+                Focus::Outline => if backwards {Focus::Content} else {Focus::Sidebar},
+            };
+            // Skip panels hidden by a narrow terminal
+            if !self.narrow.hides(self.focus) {
+                break;
+            }
+        }
+    }
+
+    pub fn toggle_sidebar_collapsed(&mut self) {
+        if self.narrow.sidebar_narrow {
+            self.narrow.sidebar_shown = !self.narrow.sidebar_shown;
+            if self.narrow.hides(self.focus) {
+                self.focus = Focus::Content;
+            }
+            return;
+        }
+        self.sidebar_collapsed = !self.sidebar_collapsed;
+    }
+
+    pub fn toggle_outline_collapsed(&mut self) {
+        if self.narrow.outline_narrow {
+            self.narrow.outline_shown = !self.narrow.outline_shown;
+            if self.narrow.hides(self.focus) {
+                self.focus = Focus::Content;
+            }
+            return;
+        }
+        self.outline_collapsed = !self.outline_collapsed;
+    }
+
+    /// Hide or restore the side panels as the terminal crosses the width
+    /// thresholds. Leaving the narrow range forgets panels shown by hand, so
+    /// they hide again next time.
+    pub fn update_narrow_panels(&mut self, sidebar_narrow: bool, outline_narrow: bool) {
+        let narrow = &mut self.narrow;
+        if !sidebar_narrow {
+            narrow.sidebar_shown = false;
+        }
+        if !outline_narrow {
+            narrow.outline_shown = false;
+        }
+        narrow.sidebar_narrow = sidebar_narrow;
+        narrow.outline_narrow = outline_narrow;
+
+        if narrow.hides(self.focus) {
+            self.focus = Focus::Content;
+        }
     }
 
-    pub fn toggle_sidebar_collapsed(&mut self) {
-        self.sidebar_collapsed = !self.sidebar_collapsed;
+    /// Catch up with a terminal that may have been resized, or swapped for
+    /// another, while suspended: image support is asked for again and the
+    /// shown image is redrawn at the new size.
+    pub fn resume_after_suspend(&mut self, capabilities: Capabilities) {
+        self.picker = query_picker();
+        self.capabilities = Capabilities { images: self.picker.is_some(), ..capabilities };
+        self.image_states.clear();
+        self.needs_full_clear = true;
     }
 
-    pub fn toggle_outline_collapsed(&mut self) {
-        self.outline_collapsed = !self.outline_collapsed;
+    /// Take what the terminal supports at startup, warning once about
+    /// features it refused and built-in chords it can't send that no leader
+    /// sequence stands in for.
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = Capabilities { images: self.picker.is_some(), ..capabilities };
+        let warnings: Vec<String> = [
+            self.capabilities.degraded_warning(),
+            self.capabilities.unreachable_warning(self.leader_key, &self.leader_map),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !warnings.is_empty() {
+            self.set_status(warnings.join(". "));
+        }
     }
 
     pub fn toggle_zen_mode(&mut self) {
@@ -4497,11 +9638,12 @@ impl App {
         }
 
         let query = self.search_query.to_lowercase();
+        let since = self.recent_filter_since();
 
         self.search_matched_notes = self.notes
             .iter()
             .enumerate()
-            .filter(|(_, note)| note.title.to_lowercase().contains(&query))
+            .filter(|(_, note)| note.title.to_lowercase().contains(&query) && Self::note_modified_since(note, since))
             .map(|(i, _)| i)
             .collect();
 
@@ -4589,6 +9731,7 @@ impl App {
     }
 
     pub fn start_buffer_search_with_direction(&mut self, direction: SearchDirection) {
+        self.search_history.stop_browsing();
         self.buffer_search.active = true;
         self.buffer_search.query.clear();
         self.buffer_search.matches.clear();
@@ -4604,20 +9747,24 @@ impl App {
         self.buffer_search.matches.clear();
         self.buffer_search.current_match_index = 0;
 
-        if self.buffer_search.query.is_empty() {
+        // `:42` is a jump there, not a search, until it stops looking like one
+        if self.buffer_search.query.is_empty()
+            || self.buffer_search_setting().is_some()
+            || self.buffer_search_line_jump().is_some_and(|jump| jump.is_ok() || self.buffer_search.query.as_str() == ":")
+        {
             return;
         }
 
         let query = if self.buffer_search.case_sensitive {
-            self.buffer_search.query.clone()
+            self.buffer_search.query.to_string()
         } else {
             self.buffer_search.query.to_lowercase()
         };
 
         let lines: Vec<String> = if self.mode == Mode::Edit {
             self.editor.lines().iter().map(|s| s.to_string()).collect()
-        } else if let Some(note) = self.notes.get(self.selected_note) {
-            note.content.lines().map(|s| s.to_string()).collect()
+        } else if let Some(source) = self.displayed_source() {
+            source.lines().map(|s| s.to_string()).collect()
         } else {
             return;
         };
@@ -4662,7 +9809,9 @@ impl App {
         if let Some(m) = self.buffer_search.current_match() {
             let target_row = m.row;
 
-            if self.mode == Mode::Edit {
+            if let Some(view) = self.source_view.as_mut().filter(|_| self.mode != Mode::Edit) {
+                view.cursor = target_row;
+            } else if self.mode == Mode::Edit {
                 let start_col = m.start_col;
                 self.editor.set_cursor(target_row, start_col);
                 let half_height = self.editor_view_height / 2;
@@ -4671,39 +9820,110 @@ impl App {
                 } else {
                     self.editor_scroll_top = 0;
                 }
-            } else {
-                for (idx, &source_line) in self.content_item_source_lines.iter().enumerate() {
-                    if source_line >= target_row {
-                        self.content_cursor = idx;
-                        let content_height = self.content_area.height.saturating_sub(2) as usize;
-                        let half_height = content_height / 2;
-                        if idx > half_height {
-                            self.content_scroll_offset = idx - half_height;
-                        } else {
-                            self.content_scroll_offset = 0;
-                        }
-                        break;
-                    }
-                }
+            } else if !self.content_item_source_lines.is_empty() {
+                // The item the match is in, a joined paragraph taking several lines
+                let idx = self.content_cursor_for_source_line(target_row);
+                self.content_cursor = idx;
+                let half_height = self.content_area.height.saturating_sub(2) as usize / 2;
+                self.content_scroll_offset = idx.saturating_sub(half_height);
             }
         }
     }
 
     pub fn buffer_search_next(&mut self) {
+        self.start_smooth_scroll();
         self.buffer_search.next_match();
         self.scroll_to_current_match();
     }
 
     pub fn buffer_search_prev(&mut self) {
+        self.start_smooth_scroll();
         self.buffer_search.prev_match();
         self.scroll_to_current_match();
     }
 
+    /// Search for `pattern` in place of what the prompt holds, showing the
+    /// first match without moving past it.
+    pub fn set_buffer_search_query(&mut self, pattern: &str) {
+        self.buffer_search.query.set(pattern);
+        self.perform_buffer_search();
+        if !self.buffer_search.matches.is_empty() {
+            self.scroll_to_current_match();
+        }
+    }
+
+    /// Up in the search prompt: the pattern searched for before the shown one.
+    pub fn buffer_search_history_older(&mut self) {
+        let typed = self.buffer_search.query.to_string();
+        if let Some(pattern) = self.search_history.older(&typed).map(str::to_string) {
+            self.set_buffer_search_query(&pattern);
+        }
+    }
+
+    /// Down in the search prompt: back toward what was typed.
+    pub fn buffer_search_history_newer(&mut self) {
+        if let Some(pattern) = self.search_history.newer().map(str::to_string) {
+            self.set_buffer_search_query(&pattern);
+        }
+    }
+
+    /// `Ctrl+R` in the search prompt: the last pattern searched for.
+    pub fn recall_last_search(&mut self) {
+        match self.search_history.last().map(str::to_string) {
+            Some(pattern) => self.set_buffer_search_query(&pattern),
+            None => self.set_status("No previous search".to_string()),
+        }
+    }
+
+    /// Open the search prompt with the word under the editor's cursor in it.
+    /// The content view has no column to find a word at, so it opens empty.
+    pub fn start_buffer_search_for_word(&mut self) {
+        self.start_buffer_search();
+        if let Some(word) = self.word_under_cursor() {
+            self.set_buffer_search_query(&word);
+        }
+    }
+
+    fn word_under_cursor(&self) -> Option<String> {
+        if self.mode != Mode::Edit {
+            return None;
+        }
+        let (row, col) = self.editor.cursor();
+        let chars: Vec<char> = self.editor.lines().get(row)?.chars().collect();
+        let word_chars = self.editor.word_chars();
+        // On the gap after a word, as after typing it, the word is the one before
+        let col = if chars.get(col).is_none_or(|&c| !word_chars.is_word_char(c)) { col.checked_sub(1)? } else { col };
+        if !chars.get(col).is_some_and(|&c| word_chars.is_word_char(c)) {
+            return None;
+        }
+        let start = (0..col).rev().find(|&i| !word_chars.is_word_char(chars[i])).map_or(0, |i| i + 1);
+        let end = (col..chars.len()).find(|&i| !word_chars.is_word_char(chars[i])).unwrap_or(chars.len());
+        Some(chars[start..end].iter().collect())
+    }
+
+    /// When a note was opened since the last search, search it again for the
+    /// last pattern so `n`/`N` have matches to go to. False when nothing does.
+    pub fn resume_last_search(&mut self) -> bool {
+        if self.buffer_search.matches.is_empty() {
+            let Some(pattern) = self.search_history.last().map(str::to_string) else {
+                return false;
+            };
+            self.buffer_search.query.set(pattern);
+            self.perform_buffer_search();
+        }
+        !self.buffer_search.matches.is_empty()
+    }
+
     pub fn get_visible_sidebar_indices(&self) -> Vec<usize> {
         if self.search_active && !self.search_query.is_empty() {
             self.filtered_indices.clone()
         } else {
-            (0..self.sidebar_items.len()).collect()
+            self.sidebar_items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| !matches!(item.kind, SidebarItemKind::Header))
+                .map(|(idx, _)| idx)
+                .collect()
         }
     }
 
@@ -4735,6 +9955,21 @@ impl App {
         self.outline_state.select(Some(i));
     }
 
+    /// PageDown/PageUp in the outline, without wrapping.
+    pub fn page_outline(&mut self, forward: bool) {
+        if self.outline.is_empty() {
+            return;
+        }
+        let page = (self.outline_area.height.saturating_sub(2) as usize).max(1);
+        let current = self.outline_state.selected().unwrap_or(0);
+        let i = if forward {
+            (current + page).min(self.outline.len() - 1)
+        } else {
+            current.saturating_sub(page)
+        };
+        self.outline_state.select(Some(i));
+    }
+
     pub fn goto_first_outline(&mut self) {
         if !self.outline.is_empty() {
             self.outline_state.select(Some(0));
@@ -4751,9 +9986,31 @@ impl App {
         if let Some(selected) = self.outline_state.selected() {
             if let Some(outline_item) = self.outline.get(selected) {
                 let target_line = outline_item.line;
+                if self.live_outline.is_some() {
+                    self.editor.set_cursor(target_line, 0);
+                    self.focus = Focus::Content;
+                    return;
+                }
                 // Set content cursor to the target line
                 if target_line < self.content_items.len() {
-                    self.unfold_heading_at(target_line);
+                    // Open the headings folded over it too
+                    let mut level = outline_item.level;
+                    let folded: Vec<usize> = self.outline[..=selected]
+                        .iter()
+                        .rev()
+                        .filter(|item| {
+                            let encloses = item.kind == OutlineKind::Heading && item.level <= level;
+                            if encloses {
+                                level = item.level - 1;
+                            }
+                            encloses
+                        })
+                        .map(|item| item.line)
+                        .collect();
+                    for line in folded {
+                        self.unfold_heading_at(line);
+                    }
+                    self.start_smooth_scroll();
                     self.content_cursor = target_line;
                 }
                 // Switch focus to content
@@ -4771,7 +10028,7 @@ impl App {
         self.toast = Some(Toast {
             message: message.into(),
             kind,
-            shown_at: std::time::Instant::now(),
+            shown_at: self.clock.now(),
         });
     }
 
@@ -4784,7 +10041,8 @@ impl App {
     /// Drop the active toast once it has outlived its TTL. Returns `true` when
     /// the screen needs a redraw because a toast was just dismissed.
     pub fn tick_toast(&mut self) -> bool {
-        if self.toast.as_ref().map_or(false, |t| t.is_expired()) {
+        let now = self.clock.now();
+        if self.toast.as_ref().is_some_and(|t| t.is_expired(now)) {
             self.toast = None;
             true
         } else {
@@ -4795,7 +10053,7 @@ impl App {
     pub fn save_last_opened_note_to_cache(&self) {
         if let Some(note) = self.current_note() {
             if let Some(ref path) = note.file_path {
-                save_last_opened_note(path);
+                save_last_opened_note(&self.config.notes_path(), path);
             }
         }
     }
@@ -4836,6 +10094,12 @@ impl App {
             return Some(path_buf);
         }
 
+        // Completed links percent-encode spaces
+        let decoded = percent_decode(path);
+        if decoded != path {
+            return self.resolve_image_path(&decoded);
+        }
+
         None
     }
 
@@ -4860,53 +10124,55 @@ impl App {
         }
         self.highlight_pending = false;
 
+        // The buffer holds another note's kept edits; the switch here is held
+        // back by the UnsavedChanges prompt
+        if self.dirty_note.is_some_and(|i| i != self.selected_note) {
+            return;
+        }
+        // An encrypted note opens once the Passphrase dialog unlocks it
+        if !self.unlock_selected_note(true) {
+            return;
+        }
+
+        // Pick the kept buffer back up as it was, undo history and all
+        if self.dirty_note.is_some_and(|i| i == self.selected_note) {
+            self.dirty_note = None;
+            self.vim_mode = VimMode::Normal;
+            self.vim.mode = crate::vim::VimMode::Normal;
+            self.vim.reset_pending();
+            self.vim.command_buffer.clear();
+            self.update_editor_block();
+            self.mode = Mode::Edit;
+            self.focus = Focus::Content;
+            self.request_highlight_update();
+            return;
+        }
+
         if let Some(note) = self.current_note() {
             let lines: Vec<String> = note.content.lines().map(String::from).collect();
             let line_count = lines.len();
             let content_start_line = note.content_start_line;
 
-            let target_row = self.content_item_source_lines
-                .get(self.content_cursor)
-                .copied()
-                .unwrap_or(0)
-                .min(line_count.saturating_sub(1));
+            let target_row = match self.source_view {
+                Some(view) => view.cursor,
+                None => self.content_item_source_lines.get(self.content_cursor).copied().unwrap_or(0),
+            }
+            .min(line_count.saturating_sub(1));
 
+            // The yank ring lasts the session, not just this editor
+            let yank_ring = self.editor.take_yank_ring();
             self.editor = Editor::new(lines);
-            self.editor.set_line_wrap(self.config.editor.line_wrap);
-            self.editor.set_tab_width(self.config.editor.tab_width);
-            self.editor.set_padding(self.config.editor.left_padding, self.config.editor.right_padding);
-            self.editor.set_line_number_mode(self.config.editor.line_numbers);
-            self.editor.set_scrolloff(self.config.editor.scrolloff as usize);
+            self.editor.restore_yank_ring(yank_ring);
+            configure_editor(&mut self.editor, &self.config, &self.theme);
 
             self.vim_mode = VimMode::Normal;
             self.vim.mode = crate::vim::VimMode::Normal;
             self.vim.reset_pending();
             self.vim.command_buffer.clear();
 
-            // Set wiki link styles from theme
-            self.editor.set_wiki_link_styles(
-                ratatui::style::Style::default().fg(self.theme.info),
-                ratatui::style::Style::default().fg(self.theme.error),
-            );
-
-            // Set markdown highlighting colors from theme
-            self.editor.set_markdown_colors(
-                [
-                    self.theme.editor.heading1,
-                    self.theme.editor.heading2,
-                    self.theme.editor.heading3,
-                    self.theme.editor.heading4,
-                    self.theme.editor.heading5,
-                    self.theme.editor.heading6,
-                ],
-                self.theme.editor.code,
-                self.theme.editor.link,
-                self.theme.editor.blockquote,
-                self.theme.editor.list_marker,
-                Some(self.theme.editor.bold),
-                Some(self.theme.editor.italic),
-            );
-            self.editor.set_frontmatter_color(self.theme.content.frontmatter);
+            self.editor.reset_change_baseline();
+            self.change_markers_due = None;
+            self.diagnostics.clear();
 
             self.editor.set_cursor(target_row, 0);
 
@@ -4977,11 +10243,20 @@ impl App {
                 VimMode::VisualBlock => "V-BLOCK",
             }
         };
-        let pending_str = match (&self.pending_delete, self.pending_operator) {
-            (Some(_), _) => " [DEL]",
-            (None, Some('d')) => " d-",
-            _ => "",
+        let pending_keys = self.vim.pending_keys();
+        let pending_str = if self.pending_delete.is_some() {
+            " [DEL]".to_string()
+        } else if pending_keys.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", pending_keys)
         };
+        let recording_str = self
+            .vim
+            .macros
+            .recording_register()
+            .map(|reg| format!("recording @{} ", reg))
+            .unwrap_or_default();
         let color = if is_command_mode {
             self.theme.info
         } else if self.block_insert_state.is_some() {
@@ -4989,7 +10264,7 @@ impl App {
         } else {
             match (&self.pending_delete, self.vim_mode) {
                 (Some(_), _) => self.theme.error,
-                (None, VimMode::Normal) if self.pending_operator.is_some() => self.theme.warning,
+                (None, VimMode::Normal) if self.vim.pending_operator.is_some() => self.theme.warning,
                 (None, VimMode::Normal) => self.theme.primary,
                 (None, VimMode::Insert) => self.theme.success,
                 (None, VimMode::Replace) => self.theme.warning,
@@ -5008,7 +10283,8 @@ impl App {
                 (None, VimMode::Visual | VimMode::VisualLine | VimMode::VisualBlock) => {
                     "y: Yank, d: Delete, Esc: Cancel"
                 }
-                (None, _) if self.pending_operator == Some('d') => "d: Line, w: Word→, b: Word←",
+                (None, _) if self.vim.pending_operator == Some('d') => "d: Line, w: Word→, b: Word←",
+                _ if self.capabilities.mouse => "Ctrl+S: Save, Esc: Exit, Right-click: Menu",
                 _ => "Ctrl+S: Save, Esc: Exit",
             }
         };
@@ -5019,7 +10295,7 @@ impl App {
                 Block::default()
                     .borders(Borders::ALL)
                     .border_style(Style::default().fg(color))
-                    .title(format!(" {}{} | {} ", mode_str, pending_str, hint)),
+                    .title(format!(" {}{}{} | {} ", recording_str, mode_str, pending_str, hint)),
             );
         }
         self.editor.set_selection_style(
@@ -5031,6 +10307,10 @@ impl App {
     }
 
     pub fn save_edit(&mut self) {
+        self.end_narrowing();
+        if self.config.toc.auto_update {
+            self.update_toc();
+        }
         // Clear search state and vim state when exiting edit mode
         self.end_buffer_search();
         self.vim.reset_pending();
@@ -5044,19 +10324,65 @@ impl App {
 
         let cursor_offset_from_top = cursor_row.saturating_sub(editor_scroll);
 
+        self.dirty_note = None;
+        let mut write_result = Ok(());
+        let line_ending = self.config.editor.line_ending;
+        let keep = self.config.snapshots.keep;
+        let notes_root = self.config.notes_path();
+        let mut snapshot_result = Ok(None);
+        let background = self.config.background_io;
+        let now = self.clock.local_now();
+        let mut save_job = None;
         if let Some(note) = self.notes.get_mut(self.selected_note) {
-            note.content = self.editor.lines().join("\n");
+            let previous = if background { note.content.clone() } else { String::new() };
+            note.content = super::line_ending::join_lines(
+                &self.editor.lines(),
+                &note.content,
+                self.config.editor.ensure_final_newline,
+            );
             // Re-parse frontmatter after content change
             let (frontmatter, content_start_line) = super::frontmatter::Frontmatter::parse(&note.content);
             note.frontmatter = frontmatter;
             note.content_start_line = content_start_line;
-            // Save to file
-            if let Some(ref path) = note.file_path {
-                let _ = fs::write(path, &note.content);
-                // Update modified time after save
-                note.modified_time = fs::metadata(path).ok().and_then(|m| m.modified().ok());
+            if let Some(path) = note.file_path.clone().filter(|_| background) {
+                let snapshot = (keep > 0).then(|| SnapshotRequest {
+                    root: notes_root.clone(),
+                    now,
+                    keep,
+                    always: note.lock != NoteLock::Plain,
+                });
+                match note.encode(line_ending) {
+                    Ok(bytes) => {
+                        // Written after any save of the note still in flight
+                        let after = self.save_jobs.iter_mut().rev().find(|job| job.path == path).and_then(SaveJob::take_done);
+                        save_job = Some(SaveJob::start(path, previous, note.content.clone(), bytes, snapshot, after));
+                    }
+                    Err(e) => write_result = Err(e),
+                }
+            } else {
+                if let Some(path) = note.file_path.as_ref().filter(|_| keep > 0) {
+                    // Encrypted files differ on every save, so they're always taken
+                    let changed = note.lock != NoteLock::Plain
+                        || fs::read_to_string(path).is_ok_and(|disk| super::line_ending::normalize(disk) != note.content);
+                    if changed {
+                        snapshot_result = snapshots::take(&notes_root, path, now, keep);
+                    }
+                }
+                // Save to the note's own path, which may be outside the notes directory
+                write_result = note.write(line_ending);
             }
         }
+        if let Err(e) = snapshot_result {
+            self.show_error_toast(format!("Failed to snapshot note: {}", e));
+        }
+        if let Err(e) = write_result {
+            self.show_error_toast(format!("Failed to save note: {}", e));
+        }
+        if let Some(job) = save_job {
+            self.save_jobs.push(job);
+        }
+        self.editor.reset_change_baseline();
+        self.change_markers_due = None;
 
         // Re-sort and rebuild sidebar to reflect updated modified time
         self.sort_tree();
@@ -5075,6 +10401,7 @@ impl App {
     }
 
     pub fn cancel_edit(&mut self) {
+        self.end_narrowing();
         self.end_buffer_search();
         self.vim.reset_pending();
         self.vim.command_buffer.clear();
@@ -5087,28 +10414,129 @@ impl App {
 
         let cursor_offset_from_top = cursor_row.saturating_sub(editor_scroll);
         self.mode = Mode::Normal;
+        // Back to entries pointing at content items
+        if self.live_outline.take().is_some() {
+            self.outline = outline_items(&self.content_items, &self.config.display);
+        }
 
         self.content_cursor = self.content_cursor_for_source_line(cursor_row);
         let preview_scroll = self.content_cursor.saturating_sub(cursor_offset_from_top);
         self.content_scroll_offset = preview_scroll + 1;
     }
 
+    /// Leave edit mode with the changes still pending in the buffer, for
+    /// `esc_behavior = "keep"`. The content view shows them until they are
+    /// saved or discarded.
+    pub fn keep_edit(&mut self) {
+        self.end_narrowing();
+        self.dirty_note = Some(self.selected_note);
+        self.update_content_items();
+        self.update_outline();
+        self.cancel_edit();
+    }
+
+    /// Whether the selected note has edits kept from leaving edit mode.
+    pub fn has_dirty_buffer(&self) -> bool {
+        self.dirty_note.is_some_and(|i| i == self.selected_note)
+    }
+
+    /// Drop the edits kept by [`App::keep_edit`].
+    pub fn discard_dirty_buffer(&mut self) {
+        if self.dirty_note.take().is_some() {
+            self.update_content_items();
+            self.update_outline();
+        }
+    }
+
+    pub fn view_selection(&self) -> ViewSelection {
+        ViewSelection {
+            sidebar_index: self.selected_sidebar_index,
+            folder_view: self.folder_view.clone(),
+            content_cursor: self.content_cursor,
+            content_scroll_offset: self.content_scroll_offset,
+        }
+    }
+
+    /// Hold back a switch away from the note with kept edits, or quitting,
+    /// until the UnsavedChanges prompt is answered. `before` is the selection
+    /// from before the event that switched. Returns whether to quit now.
+    pub fn guard_dirty_buffer(&mut self, before: ViewSelection, quit: bool) -> bool {
+        let Some(dirty) = self.dirty_note else {
+            return quit;
+        };
+        let after = if quit {
+            AfterUnsavedPrompt::Quit
+        } else if self.selected_note != dirty {
+            AfterUnsavedPrompt::SwitchNote {
+                note: self.selected_note,
+                sidebar_index: self.selected_sidebar_index,
+            }
+        } else {
+            return false;
+        };
+
+        self.selected_note = dirty;
+        self.selected_sidebar_index = before.sidebar_index;
+        self.folder_view = before.folder_view;
+        self.update_content_items();
+        self.update_outline();
+        self.content_cursor = before.content_cursor;
+        self.content_scroll_offset = before.content_scroll_offset;
+
+        self.after_unsaved_prompt = Some(after);
+        self.dialog = DialogState::UnsavedChanges;
+        false
+    }
+
+    /// Carry out what the UnsavedChanges prompt held back, once the kept
+    /// edits are saved or discarded. Returns whether to quit.
+    pub fn resume_after_unsaved_prompt(&mut self) -> bool {
+        match self.after_unsaved_prompt.take() {
+            Some(AfterUnsavedPrompt::Quit) => true,
+            Some(AfterUnsavedPrompt::SwitchNote { note, sidebar_index }) => {
+                self.selected_note = note;
+                self.selected_sidebar_index = sidebar_index;
+                self.folder_view = None;
+                self.update_content_items();
+                self.update_outline();
+                false
+            }
+            Some(AfterUnsavedPrompt::ChangeDir(path)) => {
+                self.change_notes_dir_to(path);
+                false
+            }
+            None => false,
+        }
+    }
+
     pub fn has_unsaved_changes(&self) -> bool {
         if let Some(note) = self.notes.get(self.selected_note) {
-            // Compare line-by-line with the same semantics `enter_edit_mode` uses
-            // (`str::lines()` drops trailing newlines). Comparing the raw strings instead
-            // fires a false positive whenever the file ends with "\n" — which is most files.
-            let note_lines: Vec<&str> = note.content.lines().collect();
-            self.editor.lines() != note_lines
+            // A final newline or CRLF endings alone aren't changes: neither
+            // makes it into the buffer `enter_edit_mode` builds
+            let lines = self.editor.full_lines();
+            let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+            !super::line_ending::same_text(&lines, &note.content)
         } else {
             false
         }
     }
 
     pub fn poll_pending_images(&mut self) {
-        while let Ok((url, img)) = self.image_receiver.try_recv() {
+        while let Ok((url, fetched)) = self.image_receiver.try_recv() {
             self.pending_images.remove(&url);
-            self.cache_image(&url, img);
+            let info = match fetched {
+                Ok((img, bytes)) => {
+                    let info = ImageInfo {
+                        load: ImageLoad::Loaded,
+                        bytes: Some(bytes),
+                        dimensions: Some((img.width(), img.height())),
+                    };
+                    self.cache_image(&url, img);
+                    info
+                }
+                Err(reason) => ImageInfo { load: ImageLoad::Failed(reason), ..ImageInfo::default() },
+            };
+            self.image_info.insert(url, info);
         }
     }
     pub fn cache_image(&self, key: &str, img: DynamicImage) {
@@ -5120,6 +10548,64 @@ impl App {
         let path = self.image_cache_dir.join(cache_key_to_filename(key));
         image::open(&path).ok()
     }
+    /// The pixel size of the image `path` points at, read from its header
+    /// once. A remote image has none until it's fetched.
+    pub fn image_dimensions(&mut self, path: &str) -> Option<(u32, u32)> {
+        if let Some(dimensions) = self.image_dimensions.get(path) {
+            return *dimensions;
+        }
+        let file = if path.starts_with("http://") || path.starts_with("https://") {
+            if !self.is_image_cached(path) {
+                return None;
+            }
+            self.image_cache_dir.join(cache_key_to_filename(path))
+        } else {
+            self.resolve_image_path(path)?
+        };
+        let dimensions = image::image_dimensions(file).ok();
+        self.image_dimensions.insert(path.to_string(), dimensions);
+        dimensions
+    }
+
+    /// What the placeholder line for the image `path` points at shows. A
+    /// local image is read once; a remote one is loading until its fetch
+    /// comes back.
+    pub fn image_info(&mut self, path: &str) -> ImageInfo {
+        if let Some(info) = self.image_info.get(path) {
+            return info.clone();
+        }
+        let info = if path.starts_with("http://") || path.starts_with("https://") {
+            if !self.is_image_cached(path) {
+                return ImageInfo::default();
+            }
+            // Fetched in an earlier session; only the resized copy is left
+            ImageInfo { load: ImageLoad::Loaded, bytes: None, dimensions: self.image_dimensions(path) }
+        } else {
+            match self.resolve_image_path(path) {
+                Some(file) => {
+                    let bytes = fs::metadata(&file).ok().map(|meta| meta.len());
+                    match image::image_dimensions(&file) {
+                        Ok(dimensions) => ImageInfo { load: ImageLoad::Loaded, bytes, dimensions: Some(dimensions) },
+                        Err(e) => ImageInfo { load: ImageLoad::Failed(e.to_string()), bytes, dimensions: None },
+                    }
+                }
+                None => ImageInfo { load: ImageLoad::Failed("not found".to_string()), ..ImageInfo::default() },
+            }
+        };
+        self.image_info.insert(path.to_string(), info.clone());
+        info
+    }
+
+    /// Whether the open note's images are drawn, or only their placeholder
+    /// lines: its frontmatter's `images:` if set, else `images.inline`.
+    pub fn shows_inline_images(&self) -> bool {
+        let note_setting = self
+            .current_note()
+            .and_then(|note| note.frontmatter.as_ref())
+            .and_then(|frontmatter| frontmatter.inline_images());
+        note_setting.unwrap_or(self.config.images.inline) == InlineImages::Auto
+    }
+
     pub fn is_image_cached(&self, key: &str) -> bool {
         let path = self.image_cache_dir.join(cache_key_to_filename(key));
         path.exists()
@@ -5130,21 +10616,175 @@ impl App {
     }
 
     pub fn start_remote_image_fetch(&mut self, url: &str) {
-        if self.pending_images.contains(url) || self.is_image_cached(url) {
+        let failed = matches!(self.image_info.get(url), Some(ImageInfo { load: ImageLoad::Failed(_), .. }));
+        if failed || self.pending_images.contains(url) || self.is_image_cached(url) {
             return;
         }
 
         self.pending_images.insert(url.to_string());
+        self.image_info.insert(url.to_string(), ImageInfo::default());
         let url_owned = url.to_string();
         let sender = self.image_sender.clone();
 
         std::thread::spawn(move || {
-            if let Some(img) = fetch_remote_image_blocking(&url_owned) {
-                let _ = sender.send((url_owned, img));
+            let fetched = fetch_remote_image_blocking(&url_owned);
+            let _ = sender.send((url_owned, fetched));
+        });
+    }
+
+    /// Hold `text` back for the LargePaste dialog if it's past the limits in
+    /// the config. Returns whether it was held.
+    pub fn hold_large_paste(&mut self, text: &str) -> bool {
+        let editor = &self.config.editor;
+        if !large_paste::is_large(text, editor.large_paste_kb, editor.large_paste_lines) {
+            return false;
+        }
+        self.large_paste = Some(text.to_string());
+        self.dialog = DialogState::LargePaste;
+        true
+    }
+
+    /// Start inserting the held paste at the cursor, see [`App::step_paste_job`].
+    pub fn start_large_paste(&mut self) {
+        self.dialog = DialogState::None;
+        let Some(text) = self.large_paste.take() else {
+            return;
+        };
+        self.needs_full_clear = true;
+        let bulk = self.editor.begin_bulk_insert();
+        self.paste_job = Some(PasteJob::new(text, bulk));
+        self.set_status("Pasting… 0%".to_string());
+    }
+
+    /// Insert the next chunk of a large paste, finishing it after the last.
+    /// Highlighting and link checks wait for the end. Returns whether there
+    /// was a paste going.
+    pub fn step_paste_job(&mut self) -> bool {
+        let Some(job) = self.paste_job.as_mut() else {
+            return false;
+        };
+        match job.next_chunk() {
+            Some(chunk) => {
+                self.editor.insert_chunk(chunk);
+                let percent = job.percent_done();
+                self.set_status(format!("Pasting… {}%", percent));
+            }
+            None => self.finish_paste_job(),
+        }
+        true
+    }
+
+    /// End the large paste, all in or stopped part way, as one undo step.
+    pub fn finish_paste_job(&mut self) {
+        let Some(job) = self.paste_job.take() else {
+            return;
+        };
+        let stopped = job.percent_done() < 100;
+        let (bulk, text) = job.finish();
+        let lines = text.lines().count();
+        self.editor.end_bulk_insert(bulk, text);
+        self.needs_full_clear = true;
+        self.update_editor_highlights();
+        let verb = if stopped { "Stopped after" } else { "Pasted" };
+        self.set_status(format!("{} {} line{}", verb, lines, if lines == 1 { "" } else { "s" }));
+    }
+
+    /// Save the held paste as a note of its own in the notes folder and put
+    /// a link to it at the cursor instead.
+    pub fn paste_as_new_note(&mut self) {
+        self.dialog = DialogState::None;
+        let Some(mut text) = self.large_paste.take() else {
+            return;
+        };
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+        // Another paste in the same second gets a number rather than
+        // writing over the first
+        let stamp = format!("Pasted {}", self.clock.local_now().format("%Y-%m-%d %H%M%S"));
+        let mut name = stamp.clone();
+        let mut n = 2;
+        let created = loop {
+            let path = self.config.notes_path().join(format!("{}.md", name));
+            match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    name = format!("{} {}", stamp, n);
+                    n += 1;
+                }
+                opened => break opened.and_then(|mut file| std::io::Write::write_all(&mut file, text.as_bytes())),
             }
+        };
+        if let Err(e) = created {
+            self.show_error_toast(format!("Failed to create note: {}", e));
+            return;
+        }
+        self.reload_notes(false);
+        self.editor.insert_str(&wiki_link_text(&name, None, None));
+        self.update_editor_highlights();
+        let lines = text.lines().count();
+        self.set_status(format!("Pasted {} line{} into {}", lines, if lines == 1 { "" } else { "s" }, name));
+    }
+
+    /// Fetch the page title for `pasted` in the background, if it is a lone
+    /// web URL just inserted at `row`, `col`, to link it with once it comes.
+    pub fn fetch_pasted_link_title(&mut self, pasted: &str, row: usize, col: usize) {
+        if !self.config.network.fetch_link_titles || self.mode != Mode::Edit {
+            return;
+        }
+        let Some(url) = crate::clipboard::pasted_url(pasted).filter(|url| link_titles::is_web_url(url)) else {
+            return;
+        };
+        let lead = &pasted[..pasted.find(url).unwrap_or(0)];
+        if lead.contains('\n') {
+            return;
+        }
+        let col = col + lead.chars().count();
+        let before = col
+            .checked_sub(1)
+            .and_then(|i| self.editor.lines().get(row).and_then(|line| line.chars().nth(i)));
+        let link = PastedLink {
+            note: self.current_note().and_then(|note| note.file_path.clone()),
+            row,
+            col,
+            url: url.to_string(),
+            before,
+        };
+
+        self.link_titles_pending += 1;
+        let sender = self.link_title_sender.clone();
+        std::thread::spawn(move || {
+            let title = link_titles::fetch_title_blocking(&link.url);
+            let _ = sender.send((link, title));
         });
     }
 
+    pub fn is_fetching_link_titles(&self) -> bool {
+        self.link_titles_pending > 0
+    }
+
+    /// Turn pasted URLs whose titles came in into markdown links, when they
+    /// are still as pasted. Returns whether any was.
+    pub fn poll_link_titles(&mut self) -> bool {
+        let mut linked = false;
+        while let Ok((link, title)) = self.link_title_receiver.try_recv() {
+            self.link_titles_pending = self.link_titles_pending.saturating_sub(1);
+            let Some(title) = title else {
+                continue;
+            };
+            let note = self.current_note().and_then(|note| note.file_path.clone());
+            let intact = self.editor.lines().get(link.row).is_some_and(|line| link.is_intact(line));
+            if self.mode != Mode::Edit || note != link.note || !intact {
+                continue;
+            }
+            let end_col = link.col + link.url.chars().count();
+            self.editor.replace_range(link.row, link.col, end_col, &link.markdown(&title));
+            self.request_highlight_update();
+            self.set_status(format!("Linked \"{}\"", title));
+            linked = true;
+        }
+        linked
+    }
+
     // ==================== Highlighter Lazy Loading ====================
 
     // Syntect syntax highlighter takes around extra 30mb of memory, which I think it should be considered
@@ -5176,9 +10816,32 @@ impl App {
 
     // Background Highlight Worker
 
+    /// Keep the outline in step with the buffer while editing.
+    fn update_live_outline(&mut self) {
+        if self.mode != Mode::Edit {
+            return;
+        }
+        let lines = self.editor.lines();
+        match self.live_outline.as_mut() {
+            Some(live) => {
+                live.update(&mut self.outline, &lines, &self.config.display);
+            }
+            None => {
+                let (live, outline) = LiveOutline::build(&lines, &self.config.display);
+                self.outline = outline;
+                self.live_outline = Some(live);
+            }
+        }
+        if self.outline_state.selected().is_some_and(|i| i >= self.outline.len()) {
+            self.outline_state.select(self.outline.len().checked_sub(1));
+        }
+    }
+
     pub fn request_highlight_update(&mut self) {
+        self.update_live_outline();
         self.highlight_version += 1;
         self.highlight_pending = true;
+        self.change_markers_due = Some(self.clock.now() + CHANGE_MARKER_DEBOUNCE);
 
         if let Some(ref worker) = self.highlight_worker {
             let content = self.editor.lines().join("\n");
@@ -5187,25 +10850,24 @@ impl App {
         }
     }
 
-    fn get_highlight_colors(&self) -> HighlightColors {
+    pub fn get_highlight_colors(&self) -> HighlightColors {
+        let markdown = &self.theme.markdown;
         HighlightColors {
-            heading_colors: [
-                self.theme.editor.heading1,
-                self.theme.editor.heading2,
-                self.theme.editor.heading3,
-                self.theme.editor.heading4,
-                self.theme.editor.heading5,
-                self.theme.editor.heading6,
-            ],
-            code_color: self.theme.editor.code,
-            link_color: self.theme.editor.link,
-            blockquote_color: self.theme.editor.blockquote,
-            list_marker_color: self.theme.editor.list_marker,
-            bold_color: Some(self.theme.editor.bold),
-            italic_color: Some(self.theme.editor.italic),
+            heading_colors: markdown.headings,
+            code_color: markdown.code,
+            code_block_color: markdown.code_block,
+            link_color: markdown.link,
+            blockquote_color: markdown.blockquote,
+            callout_colors: self.theme.callout,
+            list_marker_color: markdown.list_marker,
+            list_marker_colors: self.config.editor.indent_guides.then(|| self.theme.editor.list_markers()),
+            tab_width: self.config.editor.tab_width as usize,
+            bold_color: Some(markdown.bold),
+            italic_color: Some(markdown.italic),
+            strikethrough_modifier: self.config.display.strikethrough.modifier(),
             frontmatter_color: self.theme.content.frontmatter,
-            details_color: self.theme.editor.link, // Use link color for HTML details tags
-            horizontal_rule_color: self.theme.editor.blockquote, // Use blockquote color for horizontal rules
+            details_color: markdown.link, // Use link color for HTML details tags
+            horizontal_rule_color: markdown.hr,
         }
     }
 
@@ -5234,6 +10896,7 @@ impl App {
 
         self.editor.clear_highlights();
         self.editor.add_highlights(result.highlights);
+        self.editor.add_highlights(self.diagnostic_highlights());
         self.update_editor_wiki_links_with_ranges(&result.wiki_links);
         self.editor.invalidate_all_styles();
         true
@@ -5246,8 +10909,11 @@ impl App {
         for note in &self.notes {
             if let Some(file_path) = &note.file_path {
                 if let Ok(relative) = file_path.strip_prefix(&notes_path) {
-                    let path_str = relative.to_string_lossy();
+                    let path_str = paths::slash_path(relative);
                     if let Some(stripped) = path_str.strip_suffix(".md") {
+                        if paths::CASE_INSENSITIVE {
+                            valid_targets.insert(stripped.to_lowercase());
+                        }
                         valid_targets.insert(stripped.to_string());
                         valid_targets.insert(note.title.clone());
                         valid_targets.insert(note.title.to_lowercase());
@@ -5279,38 +10945,178 @@ impl App {
             None => return false,
         };
 
-        let chars: Vec<char> = line.chars().collect();
-        if start_col + 2 >= chars.len() {
-            return false;
+        let start = line.char_indices().nth(start_col).map_or(line.len(), |(at, _)| at);
+        let Some(link) = WikiLink::parse(&line[start..]) else {
+            return false;
+        };
+        let target = link.target.as_str();
+        if valid_targets.contains(target) {
+            return true;
+        }
+        // Titles match in any case, folder paths where the filesystem's do
+        if !target.contains('/') || paths::CASE_INSENSITIVE {
+            return valid_targets.contains(&target.to_lowercase());
+        }
+        false
+    }
+
+    pub fn has_highlight_work(&self) -> bool {
+        self.highlight_pending
+    }
+
+    /// Re-diff the editor against the saved note and lint it once edits have
+    /// settled. Returns true when the gutter was refreshed.
+    pub fn poll_change_markers(&mut self) -> bool {
+        match self.change_markers_due {
+            Some(due) if self.clock.now() >= due => {
+                self.change_markers_due = None;
+                if self.mode != Mode::Edit {
+                    return false;
+                }
+                if self.config.editor.change_markers {
+                    self.editor.refresh_change_markers();
+                }
+                self.refresh_diagnostics();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Lint the buffer with the rules enabled under `[lint]`.
+    pub fn refresh_diagnostics(&mut self) {
+        self.diagnostics = if self.config.lint.any_enabled() {
+            markdown_lint::lint(&self.editor.lines(), &self.config.lint)
+        } else {
+            Vec::new()
+        };
+        if self.config.lint.prose {
+            let hints = self.prose_hints();
+            self.diagnostics.extend(hints);
+            self.diagnostics.sort_by_key(|d| (d.row, d.start_col));
+        }
+        self.editor.set_diagnostics(self.diagnostic_highlights());
+    }
+
+    /// The buffer's prose hints under `[lint.prose_rules]`. Lines already
+    /// linted with the same text are looked up rather than checked again.
+    fn prose_hints(&mut self) -> Vec<Diagnostic> {
+        let rules = &self.config.lint.prose_rules;
+        let linter = match &mut self.prose_linter {
+            Some(linter) if linter.rules() == rules => linter,
+            linter => linter.insert(ProseLinter::new(rules, &prose_words_path())),
+        };
+        linter.lint(&self.editor.lines())
+    }
+
+    /// Warnings underlined in the warning color and marked in the gutter,
+    /// prose hints in a muted one and not.
+    fn diagnostic_highlights(&self) -> Vec<HighlightRange> {
+        let underline = |color| Style::default().add_modifier(Modifier::UNDERLINED).underline_color(color);
+        self.diagnostics
+            .iter()
+            .map(|d| {
+                let (color, priority) = match d.severity {
+                    Severity::Warning => (self.theme.warning, 1),
+                    Severity::Hint => (self.theme.muted, 0),
+                };
+                HighlightRange::new(d.row, d.start_col, d.end_col, underline(color), HighlightType::Diagnostic)
+                    .with_priority(priority)
+            })
+            .collect()
+    }
+
+    /// The lint warning under the editor's cursor, for the status bar; a
+    /// prose hint only when no warning is there.
+    pub fn diagnostic_at_cursor(&self) -> Option<&Diagnostic> {
+        let (row, col) = self.editor.cursor();
+        self.diagnostics
+            .iter()
+            .filter(|d| d.contains(row, col))
+            .min_by_key(|d| d.severity == Severity::Hint)
+    }
+
+    /// `:narrow`: show only the section under the cursor's heading in the
+    /// editor, until `:widen` or leaving edit mode.
+    pub fn narrow_to_section(&mut self) {
+        if self.mode != Mode::Edit {
+            return;
+        }
+        if self.narrowed_heading.is_some() {
+            self.set_status("Already narrowed, :widen first".to_string());
+            return;
+        }
+        let section = heading_section(&self.editor.lines(), self.editor.cursor().0);
+        let Some((heading, rows)) = section else {
+            self.set_status("Not under a heading".to_string());
+            return;
+        };
+        self.editor.narrow(rows);
+        self.set_status(format!("Narrowed to {}", heading));
+        self.narrowed_heading = Some(heading);
+        self.update_editor_highlights();
+    }
+
+    /// `:widen`: back to the whole note after `:narrow`, with the section as
+    /// it's been edited.
+    pub fn widen(&mut self) {
+        if self.mode != Mode::Edit {
+            return;
+        }
+        if self.end_narrowing() {
+            self.set_status("Widened to the whole note".to_string());
+            self.update_editor_highlights();
+        } else {
+            self.set_status("Not narrowed".to_string());
         }
+    }
 
-        let after_open: String = chars[start_col + 2..].iter().collect();
-        if let Some(end_pos) = after_open.find("]]") {
-            let raw_content = &after_open[..end_pos];
+    fn end_narrowing(&mut self) -> bool {
+        self.narrowed_heading.take().is_some() && self.editor.widen()
+    }
 
-            let content = if let Some(pipe_pos) = raw_content.find('|') {
-                &raw_content[..pipe_pos]
-            } else {
-                raw_content
-            };
-            let target = if let Some(hash_pos) = content.find('#') {
-                &content[..hash_pos]
-            } else {
-                content
-            };
+    /// `:prose`: list the buffer's prose hints, whether or not `lint.prose`
+    /// underlines them.
+    pub fn open_prose_report(&mut self) {
+        let hints = self.prose_hints();
+        if hints.is_empty() {
+            self.set_status("No prose hints".to_string());
+            return;
+        }
+        self.prose_report = ProseReport { hints, ..ProseReport::default() };
+        self.dialog = DialogState::ProseReport;
+    }
 
-            if valid_targets.contains(target) {
-                return true;
-            }
-            if !target.contains('/') {
-                return valid_targets.contains(&target.to_lowercase());
-            }
+    pub fn close_prose_report(&mut self) {
+        self.dialog = DialogState::None;
+        self.prose_report = ProseReport::default();
+    }
+
+    pub fn prose_report_select_next(&mut self) {
+        if self.prose_report.selected + 1 < self.prose_report.hints.len() {
+            self.prose_report.selected += 1;
         }
-        false
     }
 
-    pub fn has_highlight_work(&self) -> bool {
-        self.highlight_pending
+    pub fn prose_report_select_prev(&mut self) {
+        self.prose_report.selected = self.prose_report.selected.saturating_sub(1);
+    }
+
+    /// Close the report and put the cursor on the selected hint.
+    pub fn jump_to_prose_hint(&mut self) {
+        let Some(hint) = self.prose_report.hints.get(self.prose_report.selected).cloned() else {
+            return;
+        };
+        self.close_prose_report();
+        let last_row = self.editor.lines().len().saturating_sub(1);
+        self.editor.set_cursor(hint.row.min(last_row), hint.start_col);
+        self.set_status(hint.message);
+    }
+
+    /// Bring the gutter markers up to date immediately, e.g. before `]c`/`[c`.
+    pub fn refresh_change_markers_now(&mut self) {
+        self.change_markers_due = None;
+        self.editor.refresh_change_markers();
     }
 
     pub fn get_highlighter(&self) -> Option<&Highlighter> {
@@ -5331,6 +11137,8 @@ impl App {
         let note_data: Vec<(usize, String, String, u64)> = self.notes
             .iter()
             .enumerate()
+            // Encrypted notes stay out of the index, which is saved to disk
+            .filter(|(_, note)| note.lock == NoteLock::Plain)
             .filter_map(|(idx, note)| {
                 let path = note.file_path.as_ref()?;
                 let rel_path = path.strip_prefix(&notes_dir).ok()?
@@ -5356,7 +11164,7 @@ impl App {
         }
 
         self.indexing_in_progress = true;
-        self.index_started_at = Some(std::time::Instant::now());
+        self.index_started_at = Some(self.clock.now());
 
         self.index_progress.store(0, Ordering::Relaxed);
         self.index_total.store(note_data.len(), Ordering::Relaxed);
@@ -5455,7 +11263,7 @@ impl App {
 
         const INDEXING_TIMEOUT_SECS: u64 = 60;
         if let Some(started) = self.index_started_at {
-            if started.elapsed().as_secs() > INDEXING_TIMEOUT_SECS {
+            if self.clock.now().duration_since(started).as_secs() > INDEXING_TIMEOUT_SECS {
                 self.indexing_in_progress = false;
                 self.index_started_at = None;
                 self.index_progress.store(0, Ordering::Relaxed);
@@ -5681,9 +11489,20 @@ impl App {
     }
 
     pub fn open_search_picker(&mut self) {
+        self.open_picker(PickerPurpose::Open);
+    }
+
+    /// Open the note picker to insert a wiki link to the picked note. Typing
+    /// `note|alias` gives the link an alias.
+    pub fn open_link_picker(&mut self) {
+        self.open_picker(PickerPurpose::InsertLink);
+    }
+
+    fn open_picker(&mut self, purpose: PickerPurpose) {
         self.search_picker = SearchPickerState::Open {
             mode: SearchPickerMode::Files,
-            query: String::new(),
+            purpose,
+            query: LineInput::default(),
             file_results: Vec::new(),
             content_results: Vec::new(),
             selected_index: 0,
@@ -5738,7 +11557,72 @@ impl App {
         }
     }
 
+    /// `y` in the content view: copy the URL or path of the selected link, or
+    /// of the image on the cursor line.
+    pub fn copy_link_target(&mut self) {
+        let image = self.current_item_is_image().map(str::to_string);
+        let link = self.current_selected_link();
+        let (copied, resolved) = match (&image, &link) {
+            (Some(path), _) => (CopiedLink::Image(path), self.resolve_image_path(path).filter(|p| p.is_absolute())),
+            (None, Some(LinkInfo::Markdown { url, .. })) => (CopiedLink::Url(url), None),
+            (None, Some(LinkInfo::Wiki { target, heading, .. })) => {
+                let note_path = self
+                    .resolve_wiki_link(target)
+                    .and_then(|idx| self.notes.get(idx))
+                    .and_then(|note| note.file_path.clone());
+                (CopiedLink::Wiki { target, heading: heading.as_deref() }, note_path)
+            }
+            (None, None) => {
+                self.status_message = Some("No link under cursor".to_string());
+                return;
+            }
+        };
+        let text = link_copy::copy_text(copied, self.config.copy_wiki_link, resolved.as_deref());
+        crate::clipboard::set_system_text(&text);
+        self.status_message = Some(format!("Copied {}", text));
+    }
+
+    /// How a wiki link names the note at `note_index`: by its title, or by
+    /// its path when another note shares the title.
+    fn wiki_link_target(&self, note_index: usize) -> String {
+        let note = &self.notes[note_index];
+        let title_shared = self.notes.iter().filter(|n| n.title == note.title).count() > 1;
+        if title_shared {
+            self.get_wiki_path_for_note(note_index).unwrap_or_else(|| note.title.clone())
+        } else {
+            note.title.clone()
+        }
+    }
+
+    /// Put a wiki link to `note_index` at the editor cursor, or on the
+    /// clipboard outside edit mode. The link names the note by its title, or
+    /// by its path when another note shares the title.
+    pub fn insert_link_to_note(&mut self, note_index: usize, alias: Option<&str>) {
+        if note_index >= self.notes.len() {
+            return;
+        }
+        let target = self.wiki_link_target(note_index);
+        if let Some(why) = WikiLink::unlinkable(&target) {
+            self.set_status(format!("Can't link to {:?}: {}", target, why));
+            return;
+        }
+        let link = wiki_link_text(&target, None, alias);
+
+        if self.mode == Mode::Edit {
+            self.editor.insert_str(&link);
+            self.update_editor_highlights();
+        } else {
+            crate::clipboard::set_system_text(&link);
+            self.status_message = Some(format!("Copied {}", link));
+        }
+    }
+
     fn build_file_picker_results(&self, query: &str) -> Vec<FilePickerResult> {
+        // The alias typed after `|` isn't part of the note's name
+        let query = match &self.search_picker {
+            SearchPickerState::Open { purpose: PickerPurpose::InsertLink, .. } => split_link_alias(query).0,
+            _ => query,
+        };
         let query_lower = query.to_lowercase();
 
         let mut results: Vec<FilePickerResult> = self
@@ -5983,6 +11867,44 @@ impl App {
     }
 
     pub fn select_search_picker_result(&mut self) {
+        if let SearchPickerState::Open {
+            purpose: PickerPurpose::InsertLink, query, file_results, selected_index, ..
+        } = &self.search_picker {
+            let picked = file_results.get(*selected_index).map(|r| r.note_index);
+            let alias = split_link_alias(query).1.map(str::to_string);
+            self.search_picker = SearchPickerState::Closed;
+            if let Some(note_index) = picked {
+                self.insert_link_to_note(note_index, alias.as_deref());
+            }
+            return;
+        }
+        if let SearchPickerState::Open { purpose: PickerPurpose::Refile, mode, file_results, content_results, selected_index, .. } =
+            &self.search_picker
+        {
+            let picked = match mode {
+                SearchPickerMode::Files => file_results.get(*selected_index).map(|r| r.note_index),
+                SearchPickerMode::Content => content_results.get(*selected_index).map(|r| r.note_index),
+            };
+            self.search_picker = SearchPickerState::Closed;
+            if let Some(note_index) = picked {
+                self.choose_refile_heading(note_index);
+            }
+            return;
+        }
+        if let SearchPickerState::Open { purpose: PickerPurpose::Merge, mode, file_results, content_results, selected_index, .. } =
+            &self.search_picker
+        {
+            let picked = match mode {
+                SearchPickerMode::Files => file_results.get(*selected_index).map(|r| r.note_index),
+                SearchPickerMode::Content => content_results.get(*selected_index).map(|r| r.note_index),
+            };
+            self.search_picker = SearchPickerState::Closed;
+            if let Some(note_index) = picked {
+                self.choose_merge_source(note_index);
+            }
+            return;
+        }
+
         let result_info = if let SearchPickerState::Open {
             mode, file_results, content_results, selected_index, ..
         } = &self.search_picker {
@@ -6002,6 +11924,7 @@ impl App {
             self.search_picker = SearchPickerState::Closed;
             return;
         };
+        self.content_results_to_quickfix();
 
         if note_index < self.notes.len() {
             if let Some(note) = self.notes.get(note_index) {
@@ -6033,10 +11956,10 @@ impl App {
                         self.selected_sidebar_index = idx;
                         self.selected_note = note_index;
                         self.push_navigation_history(note_index);
-                        self.content_cursor = 0;
-                        self.content_scroll_offset = 0;
                         self.update_content_items();
                         self.update_outline();
+                        self.content_cursor = 0;
+                        self.content_scroll_offset = 0;
 
                         if let Some(target_line) = line_number {
                             let target_line_0indexed = target_line.saturating_sub(1);
@@ -6068,6 +11991,7 @@ impl App {
                             let target_scroll = self.content_cursor.saturating_sub(visible_height / 3);
                             self.content_scroll_offset = target_scroll;
                         }
+                        self.mark_reading_jump();
 
                         self.focus = Focus::Content;
                         break;
@@ -6079,6 +12003,39 @@ impl App {
         self.search_picker = SearchPickerState::Closed;
     }
 
+    /// Put the content search results into the quickfix list, with the
+    /// selected result as its current entry.
+    fn content_results_to_quickfix(&mut self) {
+        let SearchPickerState::Open {
+            mode: SearchPickerMode::Content, query, content_results, selected_index, ..
+        } = &self.search_picker else {
+            return;
+        };
+        let query_lower = query.to_lowercase();
+        let entries = content_results
+            .iter()
+            .map(|result| {
+                let line = result.line_number.saturating_sub(1);
+                let text = self.notes
+                    .get(result.note_index)
+                    .and_then(|n| n.content.lines().nth(line))
+                    .unwrap_or_default();
+                let lower = text.to_lowercase();
+                let col = lower.find(&query_lower).map_or(0, |byte| lower[..byte].chars().count());
+                QuickfixEntry {
+                    note: result.note_index,
+                    line,
+                    col,
+                    text: format!("{}: {}", result.display_name, text.trim()),
+                }
+            })
+            .collect();
+        let title = format!("Search \"{}\"", query.as_str());
+        let selected = *selected_index;
+        self.set_quickfix(title, entries);
+        self.quickfix.select(selected);
+    }
+
     pub fn search_picker_select_prev(&mut self) {
         // Must match POPUP_MAX_VISIBLE_ITEMS / POPUP_MAX_VISIBLE_ITEMS_CONTENT in ui/file_picker.rs
         const MAX_VISIBLE_FILES: usize = 10;
@@ -6136,19 +12093,6 @@ impl App {
         }
     }
 
-    pub fn search_picker_push_char(&mut self, c: char) {
-        if let SearchPickerState::Open { query, .. } = &mut self.search_picker {
-            query.push(c);
-        }
-        self.update_search_picker_results();
-    }
-
-    pub fn search_picker_pop_char(&mut self) {
-        if let SearchPickerState::Open { query, .. } = &mut self.search_picker {
-            query.pop();
-        }
-        self.update_search_picker_results();
-    }
     pub fn is_inside_search_picker(&self, x: u16, y: u16) -> bool {
         let area = self.search_picker_area;
         x >= area.x && x < area.x + area.width && y >= area.y && y < area.y + area.height
@@ -6204,7 +12148,7 @@ impl App {
 
             if clicked_index < results_len {
                 *selected_index = clicked_index;
-                let now = std::time::Instant::now();
+                let now = self.clock.now();
                 let is_double_click = if let Some((last_time, last_index)) = self.search_picker_last_click {
                     last_index == clicked_index && now.duration_since(last_time).as_millis() < 400
                 } else {
@@ -6249,13 +12193,19 @@ impl App {
     }
 }
 
-fn fetch_remote_image_blocking(url: &str) -> Option<DynamicImage> {
+/// A remote image and the bytes downloaded for it, or why it couldn't be had.
+pub type FetchedImage = Result<(DynamicImage, u64), String>;
+
+fn fetch_remote_image_blocking(url: &str) -> FetchedImage {
     use std::io::Read;
 
     let response = ureq::get(url)
         .set("User-Agent", "ekphos/0.4")
         .call()
-        .ok()?;
+        .map_err(|e| match e {
+            ureq::Error::Status(code, _) => format!("HTTP {}", code),
+            ureq::Error::Transport(transport) => transport.kind().to_string(),
+        })?;
 
     let content_type = response
         .header("Content-Type")
@@ -6263,13 +12213,18 @@ fn fetch_remote_image_blocking(url: &str) -> Option<DynamicImage> {
         .to_lowercase();
 
     if !content_type.starts_with("image/") {
-        return None;
+        return Err("not an image".to_string());
     }
 
     let mut bytes = Vec::new();
-    response.into_reader().take(10 * 1024 * 1024).read_to_end(&mut bytes).ok()?;
-
-    image::load_from_memory(&bytes).ok()
+    response
+        .into_reader()
+        .take(10 * 1024 * 1024)
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+
+    let img = image::load_from_memory(&bytes).map_err(|e| e.to_string())?;
+    Ok((img, bytes.len() as u64))
 }
 fn get_image_cache_dir() -> PathBuf {
     let dir = cache_dir().join("images");
@@ -6369,9 +12324,414 @@ fn fuzzy_match(text: &str, query: &str) -> Option<i32> {
     }
 }
 
+/// Apply the `[editor]` settings and the theme's editor colors to `editor`,
+/// for a new editor or after the config is reloaded.
+/// Why some of `layers` can't be used, as messages for the user.
+fn folder_config_problems(layers: &[FolderConfigLayer]) -> Vec<String> {
+    layers
+        .iter()
+        .filter_map(|layer| Some(format!("{}: {}", layer.path.display(), layer.config.as_ref().err()?)))
+        .collect()
+}
+
+fn configure_editor(editor: &mut Editor, config: &Config, theme: &Theme) {
+    editor.set_line_wrap(config.editor.line_wrap);
+    editor.set_tab_width(config.editor.tab_width);
+    editor.set_padding(config.editor.left_padding, config.editor.right_padding);
+    editor.set_line_number_mode(config.editor.line_numbers);
+    editor.set_scrolloff(config.editor.scrolloff as usize);
+    editor.set_scroll_past_end(config.editor.scroll_past_end);
+    editor.set_typewriter(config.editor.typewriter);
+    editor.set_show_change_markers(config.editor.change_markers);
+    editor.set_conceal(config.editor.conceal);
+    editor.set_word_chars(&config.editor.word_chars);
+    editor.set_yank_ring_size(config.editor.yank_ring_size);
+    editor.set_strikethrough_modifier(config.display.strikethrough.modifier());
+
+    // No line highlighting in normal mode - only word highlighting via selection
+    editor.set_cursor_line_style(Style::default());
+    editor.set_selection_style(
+        Style::default()
+            .fg(theme.foreground)
+            .bg(theme.selection)
+    );
+    editor.set_markdown_colors(&theme.markdown);
+    editor.set_frontmatter_color(theme.content.frontmatter);
+    editor.set_indent_guides(config.editor.indent_guides, theme.editor.indent_guide, theme.editor.list_markers());
+    editor.set_callout_colors(theme.callout);
+    editor.set_change_marker_colors(theme.success, theme.warning, theme.error);
+    editor.set_show_diagnostics(config.lint.any_enabled());
+    editor.set_diagnostic_marker_color(theme.warning);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::app::test_support::{test_app, TempDir};
+    use chrono::{Local, TimeZone};
+    use std::time::Duration;
+
+    #[test]
+    fn app_keeps_its_config_out_of_the_users_home() {
+        let dir = TempDir::new("hermetic");
+        fs::write(dir.join("Inbox.md"), "# Inbox\n").unwrap();
+        let (app, _) = test_app(&dir);
+        assert_eq!(app.current_note().map(|n| n.title.as_str()), Some("Inbox"));
+        assert!(Config::config_path().starts_with(std::env::temp_dir()));
+        assert!(Config::config_path().exists());
+        assert!(search::vault_cache_dir(&dir).starts_with(std::env::temp_dir()));
+    }
+
+    /// With the config error dialog answered by going on with the defaults,
+    /// the folder ekphos was started on opens as it would have.
+    #[test]
+    fn startup_goes_on_with_defaults_past_a_broken_config() {
+        let dir = TempDir::new("config-error");
+        fs::write(dir.join("Inbox.md"), "# Inbox\n").unwrap();
+        fs::write(dir.join("Later.md"), "# Later\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.notes.clear();
+        app.config_error = Some(ConfigError {
+            path: dir.join("config.toml"),
+            message: "invalid table header".to_string(),
+            position: Some((2, 8)),
+            snippet: Some("[editor".to_string()),
+        });
+        app.startup_path = Some(dir.join("Later.md"));
+        app.dialog = DialogState::ConfigError;
+
+        app.continue_with_default_config();
+        assert_eq!(app.dialog, DialogState::None);
+        assert!(app.config_error.is_none());
+        assert_eq!(app.config.notes_path(), dir.to_path_buf());
+        assert_eq!(app.current_note().map(|n| n.title.as_str()), Some("Later"));
+    }
+
+    #[test]
+    fn toasts_and_reload_highlights_expire_by_the_app_clock() {
+        let dir = TempDir::new("toast");
+        fs::write(dir.join("Inbox.md"), "# Inbox\n").unwrap();
+        let (mut app, clock) = test_app(&dir);
+
+        app.show_toast("Saved", ToastKind::Info);
+        clock.advance(Duration::from_secs(3));
+        assert!(!app.tick_toast());
+        clock.advance(Duration::from_secs(1));
+        assert!(app.tick_toast());
+        assert!(app.toast.is_none());
+
+        app.highlight_reload_changes("# Inbox\nold line\n");
+        let shown = app.reload_highlight.clone().unwrap();
+        clock.advance(Duration::from_millis(1500));
+        assert_eq!(shown.faded(app.clock.now()), 0.5);
+        assert!(app.tick_reload_highlight());
+        assert!(app.reload_highlight.is_some());
+        clock.advance(Duration::from_millis(1500));
+        assert!(app.tick_reload_highlight());
+        assert!(app.reload_highlight.is_none());
+    }
+
+    #[test]
+    fn remembered_passphrase_is_forgotten_after_the_timeout() {
+        let dir = TempDir::new("passphrase-timeout");
+        fs::write(dir.join("Inbox.md"), "# Inbox\n").unwrap();
+        let (mut app, clock) = test_app(&dir);
+        app.config.passphrase_timeout = 5;
+        app.passphrase_cache = Some(PassphraseCache::new(Passphrase::new("secret".to_string()), clock.now()));
+
+        clock.advance(Duration::from_secs(4 * 60));
+        app.expire_passphrase();
+        assert!(app.passphrase_cache.is_some());
+        clock.advance(Duration::from_secs(60));
+        app.expire_passphrase();
+        assert!(app.passphrase_cache.is_none());
+    }
+
+    #[test]
+    fn note_outside_notes_dir_saves_to_its_own_path() {
+        let dir = TempDir::new("external");
+        let vault = dir.join("vault");
+        let elsewhere = dir.join("elsewhere");
+        fs::create_dir_all(&vault).unwrap();
+        fs::create_dir_all(&elsewhere).unwrap();
+        let path = elsewhere.join("draft.md");
+        fs::write(&path, "# Draft\n").unwrap();
+
+        let mut note = Note::read(&path).unwrap();
+        assert_eq!(note.title, "draft");
+        assert!(!path.starts_with(&vault));
+
+        note.content = "# Draft\n\nEdited outside the vault".to_string();
+        note.write(LineEnding::Preserve).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# Draft\n\nEdited outside the vault");
+        assert_eq!(fs::read_dir(&vault).unwrap().count(), 0);
+        assert!(note.modified_time.is_some());
+    }
+
+    #[test]
+    fn nested_note_names_split_into_folders() {
+        let ok = |name: &str| nested_note_path(name).unwrap();
+        assert_eq!(ok("plan"), (PathBuf::from("plan.md"), "plan".to_string()));
+        assert_eq!(ok("projects/2024/plan"), (PathBuf::from("projects/2024/plan.md"), "plan".to_string()));
+        assert_eq!(ok("projects//./ plan "), (PathBuf::from("projects/plan.md"), "plan".to_string()));
+
+        assert!(nested_note_path("/etc/plan").is_err());
+        assert!(nested_note_path("\\server\\plan").is_err());
+        assert!(nested_note_path("projects/../../plan").is_err());
+        assert!(nested_note_path("..\\plan").is_err());
+        assert!(nested_note_path("projects/").is_err());
+    }
+
+    #[test]
+    fn recent_filter_starts_at_midnight_and_monday() {
+        // A Thursday
+        let now = Local.with_ymd_and_hms(2024, 5, 2, 15, 0, 0).unwrap();
+        let midnight = |m, d| std::time::SystemTime::from(Local.with_ymd_and_hms(2024, m, d, 0, 0, 0).unwrap());
+        assert_eq!(RecentFilter::All.since(now), None);
+        assert_eq!(RecentFilter::Today.since(now), Some(midnight(5, 2)));
+        assert_eq!(RecentFilter::ThisWeek.since(now), Some(midnight(4, 29)));
+        assert_eq!(RecentFilter::ThisWeek.next(), RecentFilter::All);
+    }
+
+    #[test]
+    fn recent_filter_and_close_others_keep_the_selection() {
+        let dir = TempDir::new("recent-filter");
+        // The app's clock says Wednesday 1 May, 9:30
+        for (path, (month, day)) in [("Inbox.md", (5, 1)), ("work/Plan.md", (4, 30)), ("work/old/Spec.md", (4, 1)), ("home/List.md", (4, 1))] {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let file = fs::File::create(&path).unwrap();
+            file.set_modified(Local.with_ymd_and_hms(2024, month, day, 8, 0, 0).unwrap().into()).unwrap();
+        }
+        let (mut app, _) = test_app(&dir);
+        for folder in ["work", "work/old", "home"] {
+            app.toggle_folder(dir.join(folder));
+        }
+        let listed = |app: &App| -> Vec<String> {
+            app.get_visible_sidebar_indices().iter().map(|&i| app.sidebar_items[i].display_name.clone()).collect()
+        };
+        app.select_note_by_path(&dir.join("work/Plan.md"));
+
+        // Plan is hidden today, so the selection moves on
+        app.cycle_recent_filter();
+        assert_eq!(listed(&app)[1..], ["Inbox"]);
+        assert_eq!(app.current_note().unwrap().title, "Inbox");
+
+        // This week brings back Plan and its folder, but not the old ones
+        app.cycle_recent_filter();
+        assert_eq!(listed(&app)[1..], ["work", "Plan", "Inbox"]);
+        app.select_note_by_path(&dir.join("work/Plan.md"));
+        app.cycle_recent_filter();
+        assert_eq!(app.current_note().unwrap().title, "Plan");
+        assert_eq!(listed(&app).len(), 8);
+
+        app.close_other_folders();
+        assert_eq!(listed(&app)[1..], ["home", "work", "old", "Plan", "Inbox"]);
+        assert_eq!(app.current_note().unwrap().title, "Plan");
+    }
+
+    #[test]
+    fn note_names_refuse_path_and_link_characters() {
+        assert!(check_note_name("Meeting notes (2024-05-01)").is_ok());
+        assert!(check_note_name("").is_err());
+        assert_eq!(check_note_name("a/b"), Err("Note name can't contain '/'".to_string()));
+        assert_eq!(check_note_name("Q: why?"), Err("Note name can't contain ':'".to_string()));
+        assert!(check_note_name("[[x]]").is_err());
+        assert!(check_note_name("tab\there").is_err());
+        assert!(check_note_name("line\nbreak").is_err());
+    }
+
+    #[test]
+    fn crlf_note_keeps_its_line_endings_unless_configured() {
+        let dir = TempDir::new("crlf");
+        let path = dir.join("windows.md");
+        let disk = "# Windows\r\n\r\n- [ ] task\r\n";
+        fs::write(&path, disk).unwrap();
+
+        let mut note = Note::read(&path).unwrap();
+        assert_eq!(note.content, "# Windows\n\n- [ ] task\n");
+        note.write(LineEnding::Preserve).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), disk);
+
+        note.write(LineEnding::Lf).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# Windows\n\n- [ ] task\n");
+    }
+
+    #[test]
+    fn outline_lists_extra_nodes_under_their_heading() {
+        let text = |s: &str| ContentItem::TextLine(s.to_string());
+        let items = vec![
+            text("# Week"),
+            text("- buy milk"),
+            text("  - nested"),
+            ContentItem::TaskItem { text: "call Bob".to_string(), checked: true, line_index: 3, indent: 0 },
+            text("**Someday**"),
+            ContentItem::CodeFence("rust".to_string()),
+            ContentItem::CodeLine("fn main() {".to_string()),
+            ContentItem::CodeLine("- not a list item".to_string()),
+            ContentItem::CodeFence(String::new()),
+            text("## Done"),
+        ];
+        let headings_only = outline_items(&items, &DisplayConfig::default());
+        assert_eq!(headings_only.iter().map(|i| i.line).collect::<Vec<_>>(), [0, 9]);
+
+        let display = DisplayConfig {
+            outline_list_items: true,
+            outline_code_blocks: true,
+            outline_bold_lines: true,
+            ..DisplayConfig::default()
+        };
+        let outline = outline_items(&items, &display);
+        let summary: Vec<(usize, &str, usize, OutlineKind)> =
+            outline.iter().map(|i| (i.level, i.title.as_str(), i.line, i.kind)).collect();
+        assert_eq!(
+            summary,
+            [
+                (1, "Week", 0, OutlineKind::Heading),
+                (2, "buy milk", 1, OutlineKind::ListItem),
+                (2, "[x] call Bob", 3, OutlineKind::ListItem),
+                (2, "Someday", 4, OutlineKind::BoldLine),
+                (2, "rust: fn main() {", 5, OutlineKind::CodeBlock),
+                (2, "Done", 9, OutlineKind::Heading),
+            ]
+        );
+        assert_eq!(outline_title(&"x".repeat(50)).chars().count(), OUTLINE_TITLE_CHARS);
+    }
+
+    #[test]
+    fn encrypted_note_reads_locked_and_saves_encrypted() {
+        let dir = TempDir::new("encrypted");
+        let path = dir.join("secret.md");
+        let passphrase = Passphrase::new("pässwörd".to_string());
+        fs::write(&path, encryption::encrypt("# Secret\n\nkey: 42\n", &passphrase).unwrap()).unwrap();
+
+        let mut note = Note::read(&path).unwrap();
+        assert_eq!(note.lock, NoteLock::Locked);
+        assert_eq!(note.content, "");
+        assert!(note.write(LineEnding::Preserve).is_err());
+
+        note.content = "# Secret\n\nkey: 43\n".to_string();
+        note.lock = NoteLock::Unlocked(passphrase.clone());
+        note.write(LineEnding::Preserve).unwrap();
+        let disk = fs::read_to_string(&path).unwrap();
+        assert!(!disk.contains("key"));
+        assert_eq!(encryption::decrypt(&disk, &passphrase).unwrap(), "# Secret\n\nkey: 43\n");
+    }
+
+    #[test]
+    fn graph_selected_links_lists_outgoing_then_incoming() {
+        let node = |title: &str| GraphNode {
+            note_index: 0,
+            title: title.to_string(),
+            x: 0.0,
+            y: 0.0,
+            home_x: 0.0,
+            home_y: 0.0,
+            vx: 0.0,
+            vy: 0.0,
+        };
+        let edge = |from, to, bidirectional| GraphEdge { from, to, bidirectional };
+        let graph = GraphViewState {
+            nodes: vec![node("Hub"), node("zeta"), node("Alpha"), node("Both"), node("Other")],
+            edges: vec![edge(0, 1, false), edge(0, 2, false), edge(4, 0, false), edge(3, 0, true), edge(1, 4, false)],
+            selected_node: Some(0),
+            ..GraphViewState::default()
+        };
+        let links: Vec<(usize, bool)> = graph.selected_links().iter().map(|l| (l.node, l.outgoing)).collect();
+        assert_eq!(links, [(2, true), (3, true), (1, true), (3, false), (4, false)]);
+
+        let unselected = GraphViewState { selected_node: None, ..graph };
+        assert!(unselected.selected_links().is_empty());
+    }
+
+    #[test]
+    fn configure_editor_applies_reloaded_settings() {
+        let mut config = Config::default();
+        let theme = Theme::default();
+        let mut editor = Editor::new(vec!["\tx".to_string()]);
+        editor.set_cursor(0, 1);
+
+        configure_editor(&mut editor, &config, &theme);
+        assert_eq!(editor.cursor_display_col(), 4);
+
+        // What reload_config does with the reloaded `[editor]` table
+        config.editor.tab_width = 8;
+        config.editor.left_padding = 3;
+        config.editor.line_wrap = false;
+        configure_editor(&mut editor, &config, &theme);
+        assert_eq!(editor.cursor_display_col(), 8);
+        assert!(!editor.line_wrap_enabled());
+        assert_eq!(editor.content_left_offset(), 3 + editor.gutter_width());
+    }
+
+    /// Reloading redoes the editor's highlights in the new theme's
+    /// `[markdown]` colors, not just the ones worked out after it.
+    #[test]
+    fn reloaded_markdown_colors_reach_existing_highlights() {
+        let dir = TempDir::new("markdown-colors");
+        fs::write(dir.join("note.md"), "# Title\n```\ncode\n```\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.select_note_by_path(&dir.join("note.md"));
+        app.enter_edit_mode();
+        let wait_for_highlights = |app: &mut App| {
+            let deadline = std::time::Instant::now() + Duration::from_secs(5);
+            while !app.poll_highlight_worker() && std::time::Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        };
+        wait_for_highlights(&mut app);
+        assert_eq!(app.editor.get_row_styles(0)[2].fg, Some(app.theme.editor.heading1));
+
+        // What reload_config does with a theme that has a `[markdown]` table
+        let mut file = ThemeFile::default();
+        file.markdown.h1 = Some("#102030".to_string());
+        file.markdown.code_block = Some("#405060".to_string());
+        app.theme = Theme::from_file(&file);
+        configure_editor(&mut app.editor, &app.config, &app.theme);
+        app.update_editor_highlights();
+        wait_for_highlights(&mut app);
+        assert_eq!(app.editor.get_row_styles(0)[2].fg, Some(ratatui::style::Color::Rgb(16, 32, 48)));
+        assert_eq!(app.editor.get_row_styles(2)[0].fg, Some(ratatui::style::Color::Rgb(64, 80, 96)));
+    }
+
+    #[test]
+    fn joined_paragraphs_map_to_their_first_source_line() {
+        let dir = TempDir::new("join-lines");
+        let prose = "# Title\nOne line\nflows on.\n\n- item\nthen a\nbreak  \nhere\n> quote\n> more\n";
+        fs::write(dir.join("prose.md"), prose).unwrap();
+        fs::write(dir.join("own.md"), "---\njoin_paragraph_lines: false\n---\na\nb\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.config.reading.join_paragraph_lines = true;
+        app.select_note_by_path(&dir.join("prose.md"));
+        app.update_content_items();
+        let texts: Vec<&str> = app
+            .content_items
+            .iter()
+            .map(|item| match item {
+                ContentItem::TextLine(text) => text.as_str(),
+                _ => "?",
+            })
+            .collect();
+        assert_eq!(texts, ["# Title", "One line flows on.", "", "- item", "then a break", "here", "> quote", "> more"]);
+        assert_eq!(app.content_item_source_lines, [0, 1, 3, 4, 5, 7, 8, 9]);
+        assert_eq!(app.content_cursor_for_source_line(2), 1);
+
+        app.select_note_by_path(&dir.join("own.md"));
+        assert!(app.content_items.iter().any(|item| matches!(item, ContentItem::TextLine(t) if t == "b")));
+    }
+
+    #[test]
+    fn collapse_blank_runs_keeps_first_blank_and_its_source_line() {
+        let text = |s: &str| ContentItem::TextLine(s.to_string());
+        let mut items = vec![text("a"), text(""), text("  "), text(""), text("b")];
+        let mut sources = vec![0, 1, 2, 3, 4];
+        collapse_blank_runs(&mut items, &mut sources);
+
+        assert_eq!(items.len(), 3);
+        assert!(matches!(&items[1], ContentItem::TextLine(l) if l.is_empty()));
+        assert_eq!(sources, vec![0, 1, 4]);
+    }
 
     #[test]
     fn alignment_from_separator_cell_classifies_each_form() {
@@ -6481,4 +12841,613 @@ mod tests {
         assert_eq!(links[0].0, "alpha");
         assert_eq!(links[1].0, "beta");
     }
+
+    #[test]
+    fn image_placeholders_follow_loading_and_the_inline_setting() {
+        let dir = TempDir::new("image-info");
+        DynamicImage::new_rgb8(3, 2).save(dir.join("pic.png")).unwrap();
+        fs::write(dir.join("Gallery.md"), "![](pic.png)\n![](missing.png)\n").unwrap();
+        fs::write(dir.join("Plain.md"), "---\nimages: off\n---\n![](pic.png)\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.select_note_by_path(&dir.join("Gallery.md"));
+
+        let pic = app.image_info("pic.png");
+        assert_eq!((pic.load, pic.dimensions), (ImageLoad::Loaded, Some((3, 2))));
+        assert_eq!(pic.bytes, fs::metadata(dir.join("pic.png")).ok().map(|m| m.len()));
+        assert_eq!(app.image_info("missing.png").load, ImageLoad::Failed("not found".to_string()));
+
+        // Fetch results arrive through the poll loop
+        let (broken, photo) = ("https://example.com/broken.png", "https://example.com/photo.jpg");
+        for url in [broken, photo] {
+            app.pending_images.insert(url.to_string());
+            app.image_info.insert(url.to_string(), ImageInfo::default());
+        }
+        assert_eq!(app.image_info(photo).load, ImageLoad::Loading);
+        app.image_sender.send((broken.to_string(), Err("HTTP 404".to_string()))).unwrap();
+        app.image_sender.send((photo.to_string(), Ok((DynamicImage::new_rgb8(640, 480), 5000)))).unwrap();
+        app.poll_pending_images();
+        assert!(app.pending_images.is_empty());
+        assert_eq!(app.image_info(broken).load, ImageLoad::Failed("HTTP 404".to_string()));
+        let fetched = app.image_info(photo);
+        assert_eq!((fetched.load, fetched.bytes, fetched.dimensions), (ImageLoad::Loaded, Some(5000), Some((640, 480))));
+        // A failed fetch isn't retried on every draw
+        app.start_remote_image_fetch(broken);
+        assert!(app.pending_images.is_empty());
+
+        assert!(app.shows_inline_images());
+        app.config.images.inline = InlineImages::Off;
+        assert!(!app.shows_inline_images());
+        app.config.images.inline = InlineImages::Auto;
+        app.select_note_by_path(&dir.join("Plain.md"));
+        assert!(!app.shows_inline_images());
+    }
+
+    #[test]
+    fn escaped_wiki_links_resolve_and_unescaped_ones_are_flagged() {
+        let dir = TempDir::new("wiki-escapes");
+        fs::write(dir.join("C++ [advanced].md"), "# Templates\n").unwrap();
+        fs::write(dir.join("Home.md"), "[[C++ \\[advanced\\]#Templates|C++]] then [[C++ [advanced]]]\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.select_note_by_path(&dir.join("Home.md"));
+
+        let links = app.item_wiki_links_at(0);
+        assert_eq!(links.len(), 1);
+        assert_eq!((links[0].target.as_str(), links[0].heading.as_deref()), ("C++ [advanced]", Some("Templates")));
+        assert!(links[0].is_valid);
+        assert_eq!((links[0].start_col, links[0].end_col), (0, 3));
+
+        app.check_links();
+        let flagged: Vec<&str> = app.quickfix.entries.iter().map(|entry| entry.text.as_str()).collect();
+        assert_eq!(flagged, ["Home: [[C++ [advanced]] needs its brackets escaped as \\[ and \\]"]);
+
+        let suggestions = app.build_wiki_suggestions("C++ \\[adv");
+        assert_eq!(suggestions[0].insert_text, "C++ \\[advanced\\]");
+        assert_eq!(app.build_heading_suggestions("C++ \\[advanced\\]", "temp")[0].insert_text, "Templates");
+    }
+
+    #[test]
+    fn callouts_parse_and_fold() {
+        let dir = TempDir::new("callouts");
+        let note = "> [!WARNING] Mind the gap\n> First\n>\n> Second\nAfter\n\n> [!recipe]- Folded\n> Hidden\n\n> [!tip]\n";
+        fs::write(dir.join("Callouts.md"), note).unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.select_note_by_path(&dir.join("Callouts.md"));
+
+        let callouts: Vec<_> = app
+            .content_items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| match item {
+                ContentItem::Callout { kind, title, lines, fold, .. } => Some((index, *kind, title.clone(), lines.clone(), *fold)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(callouts.len(), 3);
+        let (warning, folded, tip) = (&callouts[0], &callouts[1], &callouts[2]);
+        assert_eq!((warning.1, warning.2.as_str(), warning.4), (CalloutKind::Warning, "Mind the gap", None));
+        assert_eq!(warning.3, ["First", "", "Second"]);
+        assert!(matches!(&app.content_items[warning.0 + 1], ContentItem::TextLine(line) if line == "After"));
+        assert_eq!((folded.1, folded.2.as_str(), folded.4), (CalloutKind::Other, "Folded", Some(false)));
+        assert_eq!((tip.1, tip.2.as_str()), (CalloutKind::Tip, "Tip"));
+
+        // Only a callout with a fold marker toggles
+        assert!(app.is_details_open(warning.0));
+        assert!(!app.item_is_details_at(warning.0));
+        app.toggle_details_at(warning.0);
+        assert!(app.is_details_open(warning.0));
+        assert!(!app.is_details_open(folded.0));
+        app.toggle_details_at(folded.0);
+        assert!(app.is_details_open(folded.0));
+    }
+
+    #[test]
+    fn saves_keep_snapshots_that_follow_renames_and_restore() {
+        let dir = TempDir::new("snapshots-app");
+        fs::write(dir.join("Plan.md"), "v0\n").unwrap();
+        let (mut app, clock) = test_app(&dir);
+        app.config.snapshots.keep = 2;
+        app.select_note_by_path(&dir.join("Plan.md"));
+
+        for version in ["v1", "v2", "v3"] {
+            app.enter_edit_mode();
+            app.editor.set_cursor(0, 0);
+            app.editor.insert_str(&format!("{} ", version));
+            app.save_edit();
+            clock.advance(std::time::Duration::from_secs(1));
+        }
+        // Saving without a change takes nothing
+        app.enter_edit_mode();
+        app.save_edit();
+        let note = dir.join("Plan.md");
+        let taken = |note: &Path| -> Vec<String> {
+            snapshots::list(&dir, note).iter().map(|s| fs::read_to_string(&s.path).unwrap()).collect()
+        };
+        assert_eq!(taken(&note), ["v2 v1 v0\n", "v1 v0\n"]);
+
+        app.rename_note_at(app.selected_note, "Roadmap").unwrap();
+        let renamed = dir.join("Roadmap.md");
+        assert!(taken(&note).is_empty());
+        assert_eq!(taken(&renamed).len(), 2);
+
+        app.open_history();
+        assert_eq!(app.dialog, DialogState::History);
+        app.history_select_next();
+        app.view_selected_snapshot();
+        assert_eq!(app.history.viewing.as_ref().map(|(text, _)| text.as_str()), Some("v1 v0\n"));
+        app.restore_selected_snapshot();
+        assert_eq!(app.dialog, DialogState::None);
+        assert_eq!(fs::read_to_string(&renamed).unwrap(), "v1 v0\n");
+        assert_eq!(app.current_note().map(|n| n.content.as_str()), Some("v1 v0\n"));
+        // The version replaced by the restore is the newest snapshot
+        assert_eq!(taken(&renamed), ["v3 v2 v1 v0\n", "v2 v1 v0\n"]);
+    }
+
+    #[test]
+    fn marked_sidebar_items_move_and_delete_together() {
+        let dir = TempDir::new("sidebar-marks");
+        fs::create_dir_all(dir.join("archive")).unwrap();
+        fs::create_dir_all(dir.join("old")).unwrap();
+        for note in ["a.md", "b.md", "c.md", "old/d.md"] {
+            fs::write(dir.join(note), "note\n").unwrap();
+        }
+        let (mut app, _) = test_app(&dir);
+        let select = |app: &mut App, path: &str| {
+            let path = dir.join(path);
+            app.selected_sidebar_index = app
+                .sidebar_items
+                .iter()
+                .position(|item| app.sidebar_item_path(item) == Some(path.clone()))
+                .unwrap();
+        };
+
+        select(&mut app, "a.md");
+        app.toggle_mark_selected();
+        select(&mut app, "c.md");
+        app.mark_range_to_selected();
+        assert_eq!(app.sidebar_marks.len(), 3);
+        app.open_move_picker();
+        assert_eq!(app.move_picker.folders, [dir.to_path_buf(), dir.join("archive"), dir.join("old")]);
+        app.move_picker_select_next();
+        app.move_to_picked_folder();
+        assert!(["a.md", "b.md", "c.md"].iter().all(|note| dir.join("archive").join(note).exists()));
+        assert!(app.sidebar_marks.is_empty());
+        assert_eq!(app.status_message.as_deref(), Some("Moved 3 items"));
+
+        // A marked folder takes its notes along, asked about once
+        app.load_notes_from_dir();
+        select(&mut app, "old");
+        app.toggle_mark_selected();
+        app.expand_folders_to(&dir.join("archive/a.md"));
+        select(&mut app, "archive/a.md");
+        app.toggle_mark_selected();
+        app.confirm_delete_marked();
+        assert_eq!(app.dialog, DialogState::DeleteMarkedConfirm);
+        assert_eq!(app.delete_marked[0].1.map(|contents| contents.notes), Some(1));
+        app.delete_marked_items();
+        assert!(!dir.join("old").exists() && !dir.join("archive/a.md").exists());
+        assert!(dir.join("archive/b.md").exists());
+        assert_eq!(app.status_message.as_deref(), Some("Deleted 2 items"));
+    }
+
+    #[test]
+    fn refile_moves_an_item_between_notes_and_undo_restores_both() {
+        let dir = TempDir::new("refile");
+        fs::write(dir.join("Inbox.md"), "# Inbox\n- call Sam\n- read paper\n  - section 3\n").unwrap();
+        fs::write(dir.join("Work.md"), "# Work\n\n## Reading\n- old paper\n\n## Calls\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.select_note_by_path(&dir.join("Inbox.md"));
+        let work = app.notes.iter().position(|n| n.title == "Work").unwrap();
+        let at_row = |app: &App, row| app.content_item_source_lines.iter().position(|&r| r == row).unwrap();
+
+        app.content_cursor = at_row(&app, 2);
+        app.start_refile();
+        assert!(matches!(app.search_picker, SearchPickerState::Open { purpose: PickerPurpose::Refile, .. }));
+        app.search_picker = SearchPickerState::Closed;
+        app.choose_refile_heading(work);
+        assert_eq!(app.dialog, DialogState::RefilePicker);
+        app.refile_picker.query.set("read");
+        assert_eq!(app.refile_picker.choices().len(), 3);
+        app.refile_picker.selected = 1;
+        app.confirm_refile();
+
+        assert_eq!(fs::read_to_string(dir.join("Inbox.md")).unwrap(), "# Inbox\n- call Sam\n");
+        assert_eq!(
+            fs::read_to_string(dir.join("Work.md")).unwrap(),
+            "# Work\n\n## Reading\n- old paper\n- read paper\n  - section 3\n\n## Calls\n"
+        );
+        assert_eq!(app.status_message.as_deref(), Some("Refiled to Work › ## Reading"));
+
+        app.undo_view_edit();
+        assert_eq!(fs::read_to_string(dir.join("Inbox.md")).unwrap(), "# Inbox\n- call Sam\n- read paper\n  - section 3\n");
+        assert_eq!(fs::read_to_string(dir.join("Work.md")).unwrap(), "# Work\n\n## Reading\n- old paper\n\n## Calls\n");
+
+        // Within the same note, under a heading that doesn't exist yet
+        let inbox = app.selected_note;
+        app.content_cursor = at_row(&app, 1);
+        app.start_refile();
+        app.choose_refile_heading(inbox);
+        app.refile_picker.query.set("Someday");
+        app.refile_picker.selected = 1;
+        app.confirm_refile();
+        assert_eq!(
+            fs::read_to_string(dir.join("Inbox.md")).unwrap(),
+            "# Inbox\n- read paper\n  - section 3\n\n## Someday\n\n- call Sam\n"
+        );
+
+        app.content_cursor = at_row(&app, 0);
+        app.start_refile();
+        assert_eq!(app.status_message.as_deref(), Some("Not on a list item"));
+    }
+
+    #[test]
+    fn sections_move_and_copy_to_another_note() {
+        let dir = TempDir::new("move-section");
+        fs::write(dir.join("Inbox.md"), "# Inbox\n## Ideas\n- a\n### More\ntext\n## Later\nend\n").unwrap();
+        fs::write(dir.join("Work.md"), "# Work\n\n## Projects\n- p\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.config.editor.leave_section_link = true;
+        app.select_note_by_path(&dir.join("Inbox.md"));
+        let work = app.notes.iter().position(|n| n.title == "Work").unwrap();
+        let at_row = |app: &App, row| app.content_item_source_lines.iter().position(|&r| r == row).unwrap();
+
+        app.content_cursor = at_row(&app, 1);
+        app.start_section_refile(false);
+        app.search_picker = SearchPickerState::Closed;
+        app.choose_refile_heading(work);
+        app.refile_picker.selected = 2;
+        app.confirm_refile();
+        assert_eq!(fs::read_to_string(dir.join("Inbox.md")).unwrap(), "# Inbox\n[[Work#Ideas]]\n## Later\nend\n");
+        assert_eq!(
+            fs::read_to_string(dir.join("Work.md")).unwrap(),
+            "# Work\n\n## Projects\n- p\n\n### Ideas\n- a\n#### More\ntext\n"
+        );
+        assert_eq!(app.status_message.as_deref(), Some("Moved 4 lines to Work › ## Projects"));
+
+        app.content_cursor = at_row(&app, 2);
+        app.start_section_refile(true);
+        app.search_picker = SearchPickerState::Closed;
+        app.choose_refile_heading(work);
+        app.confirm_refile();
+        assert_eq!(fs::read_to_string(dir.join("Inbox.md")).unwrap(), "# Inbox\n[[Work#Ideas]]\n## Later\nend\n");
+        assert!(fs::read_to_string(dir.join("Work.md")).unwrap().ends_with("#### More\ntext\n\n## Later\nend\n"));
+        assert_eq!(app.status_message.as_deref(), Some("Copied 2 lines to Work › the end"));
+
+        app.undo_view_edit();
+        assert!(fs::read_to_string(dir.join("Work.md")).unwrap().ends_with("#### More\ntext\n"));
+
+        app.content_cursor = at_row(&app, 3);
+        app.start_section_refile(false);
+        assert_eq!(app.status_message.as_deref(), Some("Not on a heading"));
+    }
+
+    #[test]
+    fn toggling_a_task_reparses_only_its_item() {
+        let dir = TempDir::new("incremental-toggle");
+        let mut note = String::from("# Long\n<details>\n<summary>More</summary>\nhidden\n</details>\n");
+        for i in 0..5000 {
+            note.push_str(&format!("- [ ] task {}\n", i));
+        }
+        fs::write(dir.join("Long.md"), &note).unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.select_note_by_path(&dir.join("Long.md"));
+        app.update_content_items();
+        let items = app.content_items.len();
+        assert!(items > 5000);
+
+        app.toggle_details_at(1);
+        app.toggle_heading_fold_at(0);
+        app.content_cursor = 1000;
+        let parsed = app.content_items_parsed;
+        app.toggle_current_task();
+        assert_eq!(app.content_items_parsed - parsed, 1);
+        assert!(matches!(app.content_items[1000], ContentItem::TaskItem { checked: true, .. }));
+        assert!(fs::read_to_string(dir.join("Long.md")).unwrap().contains("- [x] task 998\n"));
+        assert_eq!(app.content_cursor, 1000);
+
+        // A full rebuild of the same note keeps the details open and the heading folded
+        app.update_content_items();
+        assert_eq!(app.content_items_parsed - parsed, 1 + items);
+        assert!(app.is_details_open(1));
+        assert!(app.is_heading_folded(0));
+    }
+
+    #[test]
+    fn prose_hints_sit_under_warnings_and_are_listed_by_prose() {
+        let dir = TempDir::new("prose-lint");
+        let path = dir.join("Draft.md");
+        fs::write(&path, "# Draft\n\n**Bold and very good.\nIt was written by the the team.\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.select_note_by_path(&path);
+        app.enter_edit_mode();
+
+        app.refresh_diagnostics();
+        assert!(app.diagnostics.iter().all(|d| d.severity == Severity::Warning));
+        app.config.lint.prose = true;
+        app.refresh_diagnostics();
+        app.editor.set_cursor(2, 0);
+        assert_eq!(app.diagnostic_at_cursor().map(|d| d.message.as_str()), Some("Unbalanced ** bold marker"));
+        app.editor.set_cursor(2, 12);
+        assert_eq!(app.diagnostic_at_cursor().map(|d| d.message.as_str()), Some("weasel-word: \"very\" is vague"));
+
+        app.config.lint.prose = false;
+        app.open_prose_report();
+        assert_eq!(app.dialog, DialogState::ProseReport);
+        let messages: Vec<&str> = app.prose_report.hints.iter().map(|h| h.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "weasel-word: \"very\" is vague",
+                "passive-voice: \"was written\" may be passive",
+                "repeated-word: \"the\" twice",
+            ]
+        );
+        app.prose_report_select_next();
+        app.prose_report_select_next();
+        app.jump_to_prose_hint();
+        assert_eq!(app.dialog, DialogState::None);
+        assert_eq!(app.editor.cursor(), (3, 18));
+    }
+
+    #[test]
+    fn only_jumps_slide_with_smooth_scroll() {
+        let dir = TempDir::new("smooth-scroll");
+        let note: String = (0..300).map(|i| format!("line {}\n\n", i)).collect();
+        fs::write(dir.join("Long.md"), note).unwrap();
+        let (mut app, clock) = test_app(&dir);
+        app.select_note_by_path(&dir.join("Long.md"));
+
+        app.goto_last_content_line();
+        assert!(app.smooth_scroll.is_none());
+
+        app.config.reading.smooth_scroll = true;
+        app.goto_first_content_line();
+        let slide = app.smooth_scroll.take().unwrap();
+        app.next_content_line();
+        assert!(app.smooth_scroll.is_none());
+
+        clock.advance(Duration::from_millis(200));
+        assert!(slide.is_over(app.clock.now()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unreadable_notes_are_not_an_empty_folder() {
+        let dir = TempDir::new("scan-errors");
+        std::os::unix::fs::symlink(dir.join("gone.md"), dir.join("Broken.md")).unwrap();
+        let (mut app, _) = test_app(&dir);
+        assert_eq!(app.dialog, DialogState::ScanErrors);
+        assert_eq!(app.scan_errors.len(), 1);
+        assert_eq!(app.scan_errors[0].message, "broken symlink");
+
+        // Fixed on disk, a retry picks it up
+        fs::write(dir.join("gone.md"), "# Gone\n").unwrap();
+        app.retry_scan();
+        assert_eq!(app.dialog, DialogState::None);
+        assert!(app.scan_errors.is_empty());
+        assert_eq!(app.notes.len(), 2);
+        app.show_scan_errors();
+        assert_eq!(app.dialog, DialogState::None);
+        assert_eq!(app.status_message.as_deref(), Some("Every note was read"));
+
+        // With other notes to show, a skipped one is only counted
+        fs::write(dir.join("Other.md"), "# Other\n").unwrap();
+        fs::remove_file(dir.join("gone.md")).unwrap();
+        app.retry_scan();
+        assert_eq!(app.status_message.as_deref(), Some("1 note couldn't be read, see :errors"));
+        assert_eq!(app.dialog, DialogState::None);
+        app.show_scan_errors();
+        assert_eq!(app.dialog, DialogState::ScanErrors);
+    }
+
+    #[test]
+    fn cd_opens_another_notes_folder_in_place() {
+        let dir = TempDir::new("cd");
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::create_dir_all(dir.join("b")).unwrap();
+        fs::write(dir.join("a/One.md"), "one").unwrap();
+        fs::write(dir.join("b/Two.md"), "two").unwrap();
+        let (mut app, _) = test_app(&dir.join("a"));
+
+        assert!(app.change_notes_dir("One.md").unwrap_err().ends_with("is not a folder"));
+        assert!(app.change_notes_dir(".").unwrap_err().starts_with("Already in"));
+        app.change_notes_dir("../b").unwrap();
+        assert_eq!(app.notes.iter().map(|n| n.title.as_str()).collect::<Vec<_>>(), ["Two"]);
+        assert!(paths::same_path(&app.config.notes_path(), &paths::canonicalize(&dir.join("b")).unwrap()));
+
+        app.change_notes_dir("new").unwrap();
+        assert_eq!(app.dialog, DialogState::CreateNotesDirConfirm);
+        app.finish_create_notes_dir(true);
+        assert!(dir.join("b/new").is_dir());
+        assert_eq!(app.dialog, DialogState::EmptyDirectory);
+        assert!(app.notes.is_empty());
+    }
+
+    #[test]
+    fn merge_folds_a_note_in_and_relinks_the_vault() {
+        let dir = TempDir::new("merge");
+        fs::write(dir.join("Meeting notes.md"), "# Meetings\nMonday\n").unwrap();
+        fs::write(dir.join("meeting-notes.md"), "---\ntags: [dup]\n---\nTuesday, see [[meeting-notes#Top]]\n").unwrap();
+        fs::write(dir.join("Home.md"), "[[meeting-notes]] and [[meeting-notes|the notes]] and [[Meeting notes]]\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.select_note_by_path(&dir.join("Meeting notes.md"));
+
+        app.start_merge(Some("[[Nope]]"));
+        assert_eq!(app.dialog, DialogState::None);
+        app.start_merge(Some("[[Meeting notes]]"));
+        assert_eq!(app.dialog, DialogState::None);
+        app.start_merge(Some("[[meeting-notes]]"));
+        assert_eq!(app.dialog, DialogState::MergeConfirm);
+        let preview = &app.pending_merge.as_ref().unwrap().preview;
+        assert_eq!((preview.occurrences(), preview.files.len()), (2, 1));
+
+        app.dialog = DialogState::None;
+        app.run_merge();
+        assert_eq!(
+            fs::read_to_string(dir.join("Meeting notes.md")).unwrap(),
+            "# Meetings\nMonday\n\n## meeting-notes\n\nTuesday, see [[Meeting notes#Top]]\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("Home.md")).unwrap(),
+            "[[Meeting notes]] and [[Meeting notes|the notes]] and [[Meeting notes]]\n"
+        );
+        assert!(!dir.join("meeting-notes.md").exists());
+        assert!(dir.join(".trash/meeting-notes.md").exists());
+        assert_eq!(app.current_note().map(|n| n.title.as_str()), Some("Meeting notes"));
+        assert!(app.notes.iter().all(|n| n.title != "meeting-notes"));
+    }
+
+    #[test]
+    fn narrowed_section_edits_are_saved_into_the_whole_note() {
+        let dir = TempDir::new("narrow");
+        let path = dir.join("Plan.md");
+        fs::write(&path, "# Plan
+intro
+## Week
+- a
+## Later
+- z
+").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.select_note_by_path(&path);
+        app.enter_edit_mode();
+
+        app.editor.set_cursor(1, 0);
+        app.narrow_to_section();
+        assert_eq!(app.narrowed_heading.as_deref(), Some("Plan"));
+        assert_eq!(app.editor.lines().len(), 6);
+        app.widen();
+
+        app.editor.set_cursor(3, 0);
+        app.narrow_to_section();
+        assert_eq!(app.narrowed_heading.as_deref(), Some("Week"));
+        assert_eq!(app.editor.lines(), vec!["## Week", "- a"]);
+        app.editor.set_cursor(1, 3);
+        app.editor.insert_str("\n- b");
+        assert!(app.has_unsaved_changes());
+
+        app.save_edit();
+        assert_eq!(app.narrowed_heading, None);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# Plan\nintro\n## Week\n- a\n- b\n## Later\n- z\n");
+    }
+
+    #[test]
+    fn background_saves_finish_later_and_a_failed_one_keeps_its_edits() {
+        let dir = TempDir::new("background-save");
+        let path = dir.join("Note.md");
+        fs::write(&path, "old\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.config.background_io = true;
+        app.select_note_by_path(&path);
+        let finish = |app: &mut App| {
+            while !app.save_jobs.is_empty() {
+                app.poll_saves();
+                std::thread::sleep(std::time::Duration::from_millis(1));
+            }
+        };
+
+        app.enter_edit_mode();
+        app.editor.insert_str("new ");
+        app.save_edit();
+        assert_eq!(app.save_jobs.len(), 1);
+        assert_eq!(app.current_note().map(|n| n.content.as_str()), Some("new old\n"));
+        finish(&mut app);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new old\n");
+        assert!(!app.has_dirty_buffer());
+
+        // A second save while the first is in flight lands after it
+        for text in ["one ", "two "] {
+            app.enter_edit_mode();
+            app.editor.insert_str(text);
+            app.save_edit();
+        }
+        assert_eq!(app.save_jobs.len(), 2);
+        finish(&mut app);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two one new old\n");
+
+        // A folder where the file was makes the write fail
+        fs::remove_file(&path).unwrap();
+        fs::create_dir(&path).unwrap();
+        app.enter_edit_mode();
+        app.editor.insert_str("lost ");
+        app.save_edit();
+        finish(&mut app);
+        assert!(app.has_dirty_buffer());
+        assert_eq!(app.editor.lines(), ["lost two one new old"]);
+        assert_eq!(app.current_note().map(|n| n.content.as_str()), Some("two one new old\n"));
+        assert!(app.toast.as_ref().is_some_and(|t| t.message.ends_with("the edits are kept")));
+    }
+
+    #[test]
+    fn background_renames_and_deletes_finish_after_the_scan() {
+        let dir = TempDir::new("background-file-ops");
+        fs::write(dir.join("Draft.md"), "draft\n").unwrap();
+        fs::write(dir.join("Old.md"), "old\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.config.background_io = true;
+        let finish = |app: &mut App| {
+            while app.scan_job.is_some() {
+                app.poll_notes_scan();
+                std::thread::sleep(std::time::Duration::from_millis(1));
+            }
+        };
+
+        app.select_note_by_path(&dir.join("Draft.md"));
+        app.rename_note_at(app.selected_note, "Plan").unwrap();
+        assert!(app.notes_scan_status().is_some_and(|status| status.starts_with("Updating files")));
+        finish(&mut app);
+        assert!(dir.join("Plan.md").exists() && !dir.join("Draft.md").exists());
+        assert_eq!(app.current_note().map(|n| n.title.as_str()), Some("Plan"));
+
+        app.select_note_by_path(&dir.join("Old.md"));
+        app.select_current_note_in_sidebar();
+        app.delete_current_note();
+        assert!(app.notes.iter().any(|n| n.title == "Old"));
+        finish(&mut app);
+        assert!(!dir.join("Old.md").exists());
+        assert_eq!(app.notes.iter().map(|n| n.title.as_str()).collect::<Vec<_>>(), ["Plan"]);
+    }
+
+    #[test]
+    fn large_pastes_ask_first_and_go_in_as_one_undo_step() {
+        let dir = TempDir::new("large-paste");
+        fs::write(dir.join("Log.md"), "# Log\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.select_note_by_path(&dir.join("Log.md"));
+        app.config.editor.large_paste_lines = 10;
+        app.enter_edit_mode();
+        app.editor.set_cursor(0, 5);
+
+        assert!(!app.hold_large_paste("short\n"));
+        let big: String = (0..20_000).map(|i| format!("line {}\n", i)).collect();
+        assert!(app.hold_large_paste(&big));
+        assert_eq!(app.dialog, DialogState::LargePaste);
+
+        app.start_large_paste();
+        assert_eq!(app.dialog, DialogState::None);
+        let mut steps = 0;
+        while app.step_paste_job() {
+            steps += 1;
+        }
+        assert!(steps > 2);
+        assert_eq!(app.editor.lines().len(), 20_001);
+        assert_eq!(app.vim.status_message.as_deref(), Some("Pasted 20000 lines"));
+        assert!(app.editor.undo());
+        assert_eq!(app.editor.lines(), ["# Log"]);
+
+        // Or kept out of the note, with a link to where it went
+        assert!(app.hold_large_paste(&big));
+        app.paste_as_new_note();
+        let line = app.editor.lines()[0].to_string();
+        let name = line.trim_start_matches("# Log[[").trim_end_matches("]]");
+        assert!(name.starts_with("Pasted "));
+        assert_eq!(fs::read_to_string(dir.join(format!("{}.md", name))).unwrap(), big);
+        assert!(app.notes.iter().any(|n| n.title == name));
+        assert_eq!(app.current_note().map(|n| n.title.as_str()), Some("Log"));
+
+        // Again within the same second, into a note of its own
+        assert!(app.hold_large_paste("second\n".repeat(20).as_str()));
+        app.paste_as_new_note();
+        assert_eq!(fs::read_to_string(dir.join(format!("{}.md", name))).unwrap(), big);
+        assert_eq!(fs::read_to_string(dir.join(format!("{} 2.md", name))).unwrap(), "second\n".repeat(20));
+    }
 }
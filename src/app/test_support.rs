@@ -0,0 +1,44 @@
+//! Helpers for tests that drive a whole [`App`] on notes in a temp folder.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, TimeZone};
+
+use super::clock::Clock;
+use super::App;
+
+/// A folder under the system temp folder, removed again when dropped.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("ekphos-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+
+impl std::ops::Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// An app on the notes in `dir`, and a handle to its clock, stopped at
+/// 9:30 until advanced.
+pub fn test_app(dir: &Path) -> (App, Clock) {
+    let mut app = App::new_with_path(Some(dir.to_path_buf()));
+    let clock = Clock::manual(Local.with_ymd_and_hms(2024, 5, 1, 9, 30, 0).unwrap());
+    app.clock = clock.clone();
+    (app, clock)
+}
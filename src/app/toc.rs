@@ -0,0 +1,102 @@
+//! `:toc`: a bulleted table of contents of the note's headings, each linked
+//! by its `#anchor`. It's kept between `<!-- toc -->` and `<!-- /toc -->`,
+//! so running `:toc` again, or saving with `toc.auto_update`, redoes the
+//! list in place. Headings in the frontmatter, in code blocks and inside the
+//! table itself are left out of it.
+
+use super::code_fence::is_fence;
+use super::frontmatter::Frontmatter;
+use super::state::{heading_text, slugify_heading};
+
+pub const START: &str = "<!-- toc -->";
+pub const END: &str = "<!-- /toc -->";
+
+/// Rows of the opening and closing markers of the first table of contents
+/// in `lines`.
+pub fn find<S: AsRef<str>>(lines: &[S]) -> Option<(usize, usize)> {
+    let start = lines.iter().position(|line| line.as_ref().trim() == START)?;
+    let end = lines[start..].iter().position(|line| line.as_ref().trim() == END)?;
+    Some((start, start + end))
+}
+
+/// The table of contents for `lines`, markers included: a `- [title](#anchor)`
+/// for each heading down to level `depth`, nested by level under the
+/// shallowest one. A heading with nothing to make an anchor from is listed
+/// as plain text.
+pub fn generate<S: AsRef<str>>(lines: &[S], depth: usize) -> Vec<String> {
+    let joined: Vec<&str> = lines.iter().map(AsRef::as_ref).collect();
+    let (_, body_start) = Frontmatter::parse(&joined.join("\n"));
+    let existing = find(lines);
+
+    let mut in_code = false;
+    let mut headings: Vec<(usize, &str)> = Vec::new();
+    for (row, line) in joined.iter().enumerate().skip(body_start) {
+        if is_fence(line) {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code || existing.is_some_and(|(start, end)| (start..=end).contains(&row)) {
+            continue;
+        }
+        let Some(title) = heading_text(line).filter(|title| !title.is_empty()) else {
+            continue;
+        };
+        let level = line.trim_start().chars().take_while(|&c| c == '#').count();
+        if level <= depth {
+            headings.push((level, title));
+        }
+    }
+
+    let top = headings.iter().map(|&(level, _)| level).min().unwrap_or(1);
+    let mut toc = vec![START.to_string()];
+    toc.extend(headings.into_iter().map(|(level, title)| {
+        let indent = "  ".repeat(level - top);
+        match slugify_heading(title) {
+            slug if slug.is_empty() => format!("{}- {}", indent, title),
+            slug => format!("{}- [{}](#{})", indent, title, slug),
+        }
+    }));
+    toc.push(END.to_string());
+    toc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toc_lists_headings_outside_code_and_itself() {
+        let mut lines = vec![
+            "---",
+            "title: Notes",
+            "---",
+            "# Notes",
+            START,
+            "## Stale",
+            END,
+            "## Setup steps",
+            "```sh",
+            "# not a heading",
+            "```",
+            "### Details",
+            "#### Too deep",
+            "## ???",
+        ];
+        assert_eq!(find(&lines), Some((4, 6)));
+        assert_eq!(
+            generate(&lines, 3),
+            [
+                START,
+                "- [Notes](#notes)",
+                "  - [Setup steps](#setup-steps)",
+                "    - [Details](#details)",
+                "  - ???",
+                END,
+            ]
+        );
+
+        lines.truncate(4);
+        assert_eq!(find(&lines), None);
+        assert_eq!(generate(&lines, 1), [START, "- [Notes](#notes)", END]);
+    }
+}
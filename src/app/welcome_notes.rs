@@ -36,7 +36,8 @@ Use `Tab` or `Shift+Tab` to switch between panels.
 - `/`: Search notes
 - `?`: Show help dialog
 - `Ctrl+g`: Open graph view
-- `Ctrl+z`: Toggle zen mode
+- `Z`: Toggle zen mode
+- `Ctrl+z`: Suspend to the shell, `fg` to come back
 - `Ctrl+m`: Toggle frontmatter
 
 Press `?` for the full keybind reference, or visit [docs.ekphos.xyz](https://docs.ekphos.xyz) for comprehensive vim keybindings and documentation.
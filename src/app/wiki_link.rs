@@ -0,0 +1,215 @@
+//! Wiki links: parsing `[[target#heading|alias]]`, and writing them for the
+//! note picker and autocomplete. A backslash escapes `[`, `]`, `#`, `|` and
+//! itself inside the brackets, so a note called `C++ [advanced]` is linked
+//! as `[[C++ \[advanced\]]]`. A backslash before anything else is kept as
+//! it is.
+
+/// Chars a backslash escapes inside `[[...]]`
+const ESCAPED: [char; 5] = ['[', ']', '#', '|', '\\'];
+
+/// A wiki link's parts with the escapes taken out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WikiLink {
+    pub target: String,
+    pub heading: Option<String>,
+    pub alias: Option<String>,
+    /// Byte length of the link as written, brackets included
+    pub len: usize,
+}
+
+impl WikiLink {
+    /// The link `text` starts with. An empty or unclosed link, or one with an
+    /// unescaped bracket inside, isn't one.
+    pub fn parse(text: &str) -> Option<Self> {
+        let inner = text.strip_prefix("[[")?;
+        let end = closing_brackets(inner)?;
+        let raw = &inner[..end];
+        if raw.is_empty() {
+            return None;
+        }
+        let (destination, alias) = match split_unescaped(raw, '|') {
+            Some((destination, alias)) => (destination, Some(unescape(alias))),
+            None => (raw, None),
+        };
+        let (target, heading) = match split_unescaped(destination, '#') {
+            Some((target, heading)) => (target, Some(unescape(heading))),
+            None => (destination, None),
+        };
+        Some(Self { target: unescape(target), heading, alias, len: end + 4 })
+    }
+
+    /// Why a note called `title` can't be linked to, if it can't: a wiki
+    /// link has to stay on one line.
+    pub fn unlinkable(title: &str) -> Option<&'static str> {
+        title.contains(char::is_control).then_some("its name has a line break or control character")
+    }
+
+    /// What the content view shows for the link: its alias, or the target
+    /// and heading.
+    pub fn shown(&self) -> String {
+        match (&self.alias, &self.heading) {
+            (Some(alias), _) => alias.clone(),
+            (None, Some(heading)) => format!("{}#{}", self.target, heading),
+            (None, None) => self.target.clone(),
+        }
+    }
+}
+
+/// The chars of `text` with their byte offsets, an escaped char standing
+/// for its backslash too, and whether it was escaped.
+fn unescaped_chars(text: &str) -> impl Iterator<Item = (usize, char, bool)> + '_ {
+    let mut chars = text.char_indices().peekable();
+    std::iter::from_fn(move || {
+        let (at, c) = chars.next()?;
+        match chars.peek() {
+            Some(&(_, next)) if c == '\\' && ESCAPED.contains(&next) => {
+                chars.next();
+                Some((at, next, true))
+            }
+            _ => Some((at, c, false)),
+        }
+    })
+}
+
+/// Byte offset of the `]]` closing a link whose inside starts `inner`, or
+/// None if a bracket that isn't escaped comes first.
+fn closing_brackets(inner: &str) -> Option<usize> {
+    for (at, c, escaped) in unescaped_chars(inner) {
+        match c {
+            _ if escaped => {}
+            ']' if inner[at + 1..].starts_with(']') => return Some(at),
+            '[' | ']' => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `text` split around the first `sep` that isn't escaped.
+pub fn split_unescaped(text: &str, sep: char) -> Option<(&str, &str)> {
+    let (at, _, _) = unescaped_chars(text).find(|&(_, c, escaped)| c == sep && !escaped)?;
+    Some((&text[..at], &text[at + sep.len_utf8()..]))
+}
+
+pub fn unescape(text: &str) -> String {
+    unescaped_chars(text).map(|(_, c, _)| c).collect()
+}
+
+/// `text` written to go inside `[[...]]`. A backslash is only doubled where
+/// it would otherwise escape what follows it, or end the link.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let before_escapable = c == '\\' && chars.peek().is_none_or(|next| ESCAPED.contains(next));
+        if before_escapable || matches!(c, '[' | ']' | '#' | '|') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// The wiki links on `line` outside inline code, by the char column of their
+/// opening brackets. A `[[...]]` with a bracket that needs escaping inside is
+/// given as its text up to the first `]]`, as it isn't a link.
+pub fn links_in(line: &str) -> Vec<(usize, Result<WikiLink, &str>)> {
+    let mut links = Vec::new();
+    let mut in_code = false;
+    let mut at = 0;
+    while let Some(c) = line[at..].chars().next() {
+        let rest = &line[at..];
+        if c == '`' {
+            in_code = !in_code;
+        } else if !in_code && rest.starts_with("[[") {
+            let col = line[..at].chars().count();
+            if let Some(link) = WikiLink::parse(rest) {
+                at += link.len;
+                links.push((col, Ok(link)));
+                continue;
+            }
+            if let Some(end) = rest[2..].find("]]").filter(|&end| end > 0) {
+                links.push((col, Err(&rest[2..2 + end])));
+                at += 2 + end + 2;
+                continue;
+            }
+        }
+        at += c.len_utf8();
+    }
+    links
+}
+
+/// Split a link picker query typed as `note|alias` into the part the notes
+/// are searched by and the alias, if one was typed.
+pub fn split_link_alias(query: &str) -> (&str, Option<&str>) {
+    match query.split_once('|') {
+        Some((note, alias)) => {
+            let alias = alias.trim();
+            (note.trim_end(), (!alias.is_empty()).then_some(alias))
+        }
+        None => (query, None),
+    }
+}
+
+/// `[[target]]`, with `#heading` and `|alias` when given, escaped.
+pub fn wiki_link_text(target: &str, heading: Option<&str>, alias: Option<&str>) -> String {
+    let mut link = format!("[[{}", escape(target));
+    if let Some(heading) = heading {
+        link.push('#');
+        link.push_str(&escape(heading));
+    }
+    if let Some(alias) = alias {
+        link.push('|');
+        link.push_str(&escape(alias));
+    }
+    link.push_str("]]");
+    link
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wiki_link_text() {
+        assert_eq!(wiki_link_text("Project Plan", None, None), "[[Project Plan]]");
+        assert_eq!(wiki_link_text("work/Project Plan", None, Some("the plan")), "[[work/Project Plan|the plan]]");
+        assert_eq!(wiki_link_text("C++ [advanced]", Some("#1"), None), "[[C++ \\[advanced\\]#\\#1]]");
+    }
+
+    #[test]
+    fn test_escapes_round_trip() {
+        for title in ["C++ [advanced]", "a|b", "Issue #4", "trailing\\", "dir\\file", "two\\\\]", "]]"] {
+            let link = wiki_link_text(title, None, None);
+            let parsed = WikiLink::parse(&link).unwrap();
+            assert_eq!((parsed.target.as_str(), parsed.len), (title, link.len()), "{}", link);
+        }
+        assert_eq!(escape("dir\\file"), "dir\\file");
+        assert_eq!(escape("trailing\\"), "trailing\\\\");
+
+        let link = WikiLink::parse("[[Plans \\| ideas#Q\\#3|the \\]\\] plan]] after").unwrap();
+        assert_eq!(link.target, "Plans | ideas");
+        assert_eq!(link.heading.as_deref(), Some("Q#3"));
+        assert_eq!(link.alias.as_deref(), Some("the ]] plan"));
+        assert_eq!(link.shown(), "the ]] plan");
+        assert_eq!(WikiLink::parse("[[]]"), None);
+        assert_eq!(WikiLink::parse("[[open"), None);
+        assert_eq!(WikiLink::unlinkable("two\nlines"), Some("its name has a line break or control character"));
+    }
+
+    #[test]
+    fn test_links_in() {
+        let links = links_in("[[A]] `[[B]]` [[C++ [advanced]]] [[é\\]]]");
+        let found: Vec<(usize, Result<&str, &str>)> =
+            links.iter().map(|(col, link)| (*col, link.as_ref().map(|l| l.target.as_str()).map_err(|raw| *raw))).collect();
+        assert_eq!(found, [(0, Ok("A")), (14, Err("C++ [advanced")), (33, Ok("é]"))]);
+    }
+
+    #[test]
+    fn test_split_link_alias() {
+        assert_eq!(split_link_alias("proj"), ("proj", None));
+        assert_eq!(split_link_alias("proj | the plan "), ("proj", Some("the plan")));
+        assert_eq!(split_link_alias("proj|"), ("proj", None));
+        assert_eq!(split_link_alias("|x"), ("", Some("x")));
+    }
+}
@@ -1,7 +1,7 @@
 //! Clipboard utilities with HTML-to-Markdown conversion support
 
 #[cfg(not(target_os = "android"))]
-use clipboard_rs::{Clipboard as ClipboardTrait, ClipboardContext, ContentFormat};
+use clipboard_rs::{Clipboard as ClipboardTrait, ClipboardContent as ContentData, ClipboardContext, ContentFormat};
 #[cfg(not(target_os = "android"))]
 use std::sync::{Mutex, OnceLock};
 use htmd::{Element, HtmlToMarkdown, element_handler::Handlers, options::{BulletListMarker, Options}};
@@ -64,14 +64,36 @@ fn with_clipboard<T>(f: impl FnOnce(&ClipboardContext) -> T) -> Option<T> {
 /// No-op on platforms without a system clipboard backend (e.g. Android/Termux),
 /// where the editor relies on its internal clipboard instead. Failures are
 /// swallowed silently — never logged to stdout/stderr, which would corrupt the
-/// TUI.
+/// TUI. Returns whether the text went on.
 #[cfg(not(target_os = "android"))]
-pub fn set_system_text(text: &str) {
-    let _ = with_clipboard(|ctx| ctx.set_text(text.to_string()));
+pub fn set_system_text(text: &str) -> bool {
+    with_clipboard(|ctx| ctx.set_text(text.to_string()).is_ok()).unwrap_or(false)
 }
 
 #[cfg(target_os = "android")]
-pub fn set_system_text(_text: &str) {}
+pub fn set_system_text(_text: &str) -> bool {
+    false
+}
+
+/// Write `html` to the system clipboard, with `text` for pastes that don't
+/// take HTML. Falls back to just `text` where HTML can't be set; returns
+/// whether the HTML went on.
+#[cfg(not(target_os = "android"))]
+pub fn set_system_html(html: &str, text: &str) -> bool {
+    let contents = vec![ContentData::Html(html.to_string()), ContentData::Text(text.to_string())];
+    with_clipboard(|ctx| {
+        ctx.set(contents).is_ok() || {
+            let _ = ctx.set_text(text.to_string());
+            false
+        }
+    })
+    .unwrap_or(false)
+}
+
+#[cfg(target_os = "android")]
+pub fn set_system_html(_html: &str, _text: &str) -> bool {
+    false
+}
 
 /// Read plain text from the system clipboard, or `None` if unavailable.
 #[cfg(not(target_os = "android"))]
@@ -215,3 +237,29 @@ pub fn get_content_plain() -> ClipboardResult<ClipboardContent> {
         Err(e) => Err(e),
     }
 }
+
+/// The URL when pasted text is a single `scheme://...` URL with no
+/// whitespace inside it, for turning a selection into a link.
+pub fn pasted_url(text: &str) -> Option<&str> {
+    let url = text.trim();
+    let (scheme, rest) = url.split_once("://")?;
+    let valid_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    (valid_scheme && !rest.is_empty() && !url.contains(char::is_whitespace)).then_some(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pasted_url() {
+        assert_eq!(pasted_url("https://example.com/a?b=c"), Some("https://example.com/a?b=c"));
+        assert_eq!(pasted_url("  obsidian://open?vault=x\n"), Some("obsidian://open?vault=x"));
+        assert_eq!(pasted_url("https://example.com and more"), None);
+        assert_eq!(pasted_url("example.com"), None);
+        assert_eq!(pasted_url("https://"), None);
+        assert_eq!(pasted_url("see ://x"), None);
+        assert_eq!(pasted_url("1http://x"), None);
+    }
+}
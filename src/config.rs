@@ -1,9 +1,11 @@
 #![allow(dead_code)]
 
-use ratatui::style::Color;
+use ratatui::style::{Color, Modifier};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -33,10 +35,114 @@ pub struct Config {
     pub transparent_bg: bool,
     #[serde(default = "default_floating_cursor")]
     pub floating_cursor: bool,
+    /// Most frames drawn per second; `0` draws after every batch of events
+    #[serde(default = "default_max_fps")]
+    pub max_fps: u32,
+    /// What happens when the viewed note changes on disk while ekphos is in
+    /// the background
+    #[serde(default)]
+    pub external_change: ExternalChange,
+    /// What `y` on a wiki link copies: the note's file path, or the link itself
+    #[serde(default)]
+    pub copy_wiki_link: CopyWikiLink,
+    /// Minutes an unused passphrase for encrypted notes stays remembered;
+    /// unlocked notes lock again when it runs out. `0` never remembers it.
+    #[serde(default = "default_passphrase_timeout")]
+    pub passphrase_timeout: u64,
+    /// Keep where each note was left in the content view across restarts,
+    /// not just for the session
+    #[serde(default)]
+    pub remember_positions: bool,
+    /// Keep the patterns searched for in notes across restarts, not just
+    /// for the session
+    #[serde(default)]
+    pub remember_search_history: bool,
+    /// Scan the notes folder and save notes on background threads, for notes
+    /// on a slow or network filesystem
+    #[serde(default)]
+    pub background_io: bool,
+    /// Take JSON commands from other tools on a Unix socket in the cache
+    /// folder; see `ekphos --ipc-send`
+    #[serde(default)]
+    pub ipc: bool,
+    /// Leader key: `"space"` or a single character. Space keeps its own meaning
+    /// in the content view, so there the leader only works from other panes.
+    #[serde(default = "default_leader_key")]
+    pub leader_key: String,
+    /// Status bar segments, by name from [`STATUS_SEGMENTS`], left to right
+    #[serde(default = "default_status_left")]
+    pub status_left: Vec<String>,
+    #[serde(default = "default_status_right")]
+    pub status_right: Vec<String>,
     #[serde(default)]
     pub editor: EditorConfig,
+    #[serde(default)]
+    pub display: DisplayConfig,
+    #[serde(default)]
+    pub reading: ReadingConfig,
+    #[serde(default)]
+    pub export: ExportConfig,
+    #[serde(default)]
+    pub security: SecurityConfig,
+    #[serde(default)]
+    pub lint: LintConfig,
+    #[serde(default)]
+    pub images: ImagesConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub snapshots: SnapshotsConfig,
+    #[serde(default)]
+    pub toc: TocConfig,
+    #[serde(default)]
+    pub log: LogConfig,
+    /// Leader sequences mapped to action names, e.g. `ff = "file_finder"`
+    #[serde(default = "default_leader_map")]
+    pub leader: BTreeMap<String, String>,
+    /// Insert-mode snippets by trigger name, e.g. `dt = "{{date}} {{time}}"`
+    /// expands `;dt` and a space. `{{date}}`, `{{time}}` and `{{note_title}}`
+    /// are filled in. A `[snippets]` table replaces the defaults.
+    #[serde(default = "default_snippets")]
+    pub snippets: BTreeMap<String, String>,
+    /// Folders of notes to switch between with `:vault`. With more than one,
+    /// ekphos asks which to open when started without a path.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vaults: Vec<VaultConfig>,
+}
+
+/// A `[[vaults]]` entry.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VaultConfig {
+    pub name: String,
+    pub path: String,
+}
+
+impl VaultConfig {
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(shellexpand::tilde(&self.path).to_string())
+    }
 }
 
+/// Every status bar segment that can be named in `status_left` and
+/// `status_right`.
+pub const STATUS_SEGMENTS: &[&str] = &[
+    "brand",
+    "vault",
+    "mode",
+    "pending",
+    "note_title",
+    "dirty",
+    "message",
+    "recording",
+    "indexing",
+    "zen",
+    "search_count",
+    "word_count",
+    "cursor_pos",
+    "clock",
+    "help",
+];
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum LineNumberMode {
@@ -61,6 +167,473 @@ pub struct EditorConfig {
     pub line_numbers: LineNumberMode,
     #[serde(default = "default_scrolloff")]
     pub scrolloff: u8,
+    /// Let the last line scroll up to the top of the view instead of
+    /// stopping at the bottom
+    #[serde(default)]
+    pub scroll_past_end: bool,
+    /// Keep the cursor line in the middle of the view while typing and
+    /// moving. Scrolls past the end as needed.
+    #[serde(default)]
+    pub typewriter: bool,
+    #[serde(default = "default_change_markers")]
+    pub change_markers: bool,
+    /// Hide link syntax on lines other than the cursor line
+    #[serde(default)]
+    pub conceal: bool,
+    /// What Esc does in edit mode when the buffer has unsaved changes
+    #[serde(default)]
+    pub esc_behavior: EscBehavior,
+    /// Move tasks cleared from a section under `## Archive` at the bottom of
+    /// the note instead of deleting them
+    #[serde(default)]
+    pub archive_cleared_tasks: bool,
+    /// How `:merge` adds the other note to the current one
+    #[serde(default)]
+    pub merge_style: MergeStyle,
+    /// A section moved or copied under a heading has its headings shifted to
+    /// sit one level below it
+    #[serde(default = "default_relevel_sections")]
+    pub relevel_sections: bool,
+    /// A section moved to another note leaves a `[[Note#Heading]]` link
+    /// where it was
+    #[serde(default)]
+    pub leave_section_link: bool,
+    /// Pasting a URL over a visual selection turns the selection into a
+    /// markdown link to it instead of replacing it
+    #[serde(default = "default_paste_url_as_link")]
+    pub paste_url_as_link: bool,
+    /// Pastes bigger than this many KB ask first and go in a chunk at a
+    /// time; 0 turns the check off
+    #[serde(default = "default_large_paste_kb")]
+    pub large_paste_kb: usize,
+    /// Likewise for pastes with more lines than this
+    #[serde(default = "default_large_paste_lines")]
+    pub large_paste_lines: usize,
+    /// How many yanks and deletes `Ctrl-P` / `Ctrl-N` and `:yanks` remember
+    #[serde(default = "default_yank_ring_size")]
+    pub yank_ring_size: usize,
+    /// Line endings notes are saved with
+    #[serde(default)]
+    pub line_ending: LineEnding,
+    /// End every non-empty note with a newline when saving, instead of
+    /// keeping whether the file had one
+    #[serde(default)]
+    pub ensure_final_newline: bool,
+    /// Characters word motions and `iw` take as part of a word, besides
+    /// letters and digits. `"_-"` keeps kebab-case words whole.
+    #[serde(default = "default_word_chars")]
+    pub word_chars: String,
+    /// Typed before a snippet name to make it a trigger
+    #[serde(default = "default_snippet_prefix")]
+    pub snippet_prefix: String,
+    /// chrono format of a snippet's `{{date}}`
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
+    /// chrono format of a snippet's `{{time}}`
+    #[serde(default = "default_time_format")]
+    pub time_format: String,
+    /// Draw a line down each indent level and color list bullets by depth,
+    /// a level every `tab_width` columns
+    #[serde(default)]
+    pub indent_guides: bool,
+    /// Columns of text the editor wraps at, at most, placed by
+    /// `reading.align`. Unset fills the panel
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_width: Option<u16>,
+}
+
+/// How `:merge` adds the merged-away note's text to the current note.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeStyle {
+    /// Under a `##` heading with the merged note's title
+    #[default]
+    Heading,
+    /// As it is, after a blank line
+    Verbatim,
+}
+
+/// Line endings written when a note is saved.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    /// Whatever the file used when it was loaded
+    #[default]
+    Preserve,
+    Lf,
+    Crlf,
+}
+
+/// What leaving edit mode with Esc does to unsaved changes.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EscBehavior {
+    /// Ask whether to save or discard them
+    #[default]
+    Ask,
+    /// Go back to view mode with the changes still pending in the buffer
+    Keep,
+    /// Drop them
+    Discard,
+}
+
+/// Policy for a viewed note that changed on disk. Local unsaved edits always
+/// get the prompt, since reloading would lose them.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExternalChange {
+    /// Load the new version
+    #[default]
+    Reload,
+    /// Ask whether to load it or keep the version on screen
+    Prompt,
+    /// Keep the version on screen; only the sidebar picks up added and
+    /// removed notes
+    Ignore,
+}
+
+/// What copying a wiki link from the content view puts on the clipboard.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CopyWikiLink {
+    /// The path of the note it points at
+    #[default]
+    Path,
+    /// `[[target]]`, to paste into another note
+    Link,
+}
+
+/// How headings are drawn in the content view.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HeadingStyle {
+    /// Colored heading text
+    #[default]
+    Plain,
+    /// Barred/underlined H1 and H2, with each section indented by its level
+    Document,
+}
+
+/// How `~~strikethrough~~` text is drawn.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Strikethrough {
+    /// Crossed out, where the terminal supports it
+    #[default]
+    Crossed,
+    /// Dimmed, for terminals that don't draw crossed-out text
+    Dim,
+}
+
+impl Strikethrough {
+    pub fn modifier(self) -> Modifier {
+        match self {
+            Strikethrough::Crossed => Modifier::CROSSED_OUT,
+            Strikethrough::Dim => Modifier::DIM,
+        }
+    }
+}
+
+/// How notes read in view mode.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReadingConfig {
+    /// Draw a line down each indent level of nested lists and color their
+    /// bullets by depth, a level every `editor.tab_width` columns
+    #[serde(default)]
+    pub indent_guides: bool,
+    /// Flow the lines of each paragraph together, for prose pasted with
+    /// hard-wrapped lines. Off shows each source line on its own, as with
+    /// one sentence per line. A note's `join_paragraph_lines:` wins
+    #[serde(default)]
+    pub join_paragraph_lines: bool,
+    /// Jumps of more than a screenful, to a heading, search match or the
+    /// top or bottom of the note, slide into place instead of cutting there
+    #[serde(default)]
+    pub smooth_scroll: bool,
+    /// Columns the note is drawn in at most, with the side panels shown or
+    /// not. Unset fills the content panel
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_width: Option<u16>,
+    /// Where the note sits in a content panel wider than `max_width`, and
+    /// the editor in one wider than `editor.max_width`
+    #[serde(default)]
+    pub align: ReadingAlign,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadingAlign {
+    #[default]
+    Left,
+    Center,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportConfig {
+    /// Converter `:export pdf` runs on the note's HTML, e.g.
+    /// `"wkhtmltopdf {input} {output}"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pdf_command: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecurityConfig {
+    /// Ask before handing a link or image target to the system opener, since
+    /// the target comes from note content
+    #[serde(default)]
+    pub confirm_open: bool,
+}
+
+/// What ekphos fetches from the web besides remote images.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// A bare `http(s)` URL pasted in insert mode becomes `[Page Title](url)`
+    /// once the page's title has been fetched in the background
+    #[serde(default)]
+    pub fetch_link_titles: bool,
+}
+
+/// Earlier versions of notes kept under `.snapshots` in the notes folder,
+/// listed by `:history`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotsConfig {
+    /// How many versions of each note to keep, taken on every save that
+    /// changes it. 0 turns snapshots off.
+    #[serde(default)]
+    pub keep: usize,
+}
+
+/// The table of contents `:toc` puts in a note.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TocConfig {
+    /// Deepest heading level listed, 1 to 6
+    #[serde(default = "default_toc_depth")]
+    pub depth: usize,
+    /// Redo a note's table of contents, if it has one, whenever it's saved
+    #[serde(default)]
+    pub auto_update: bool,
+}
+
+fn default_toc_depth() -> usize {
+    3
+}
+
+impl Default for TocConfig {
+    fn default() -> Self {
+        Self {
+            depth: default_toc_depth(),
+            auto_update: false,
+        }
+    }
+}
+
+/// Diagnostics written to `ekphos.log` in the cache folder, for reporting
+/// glitches that are hard to reproduce. Nothing is logged by default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogConfig {
+    #[serde(default)]
+    pub level: LogLevel,
+    /// Size in KiB the log grows to before it's moved to `ekphos.log.1`
+    #[serde(default = "default_log_max_size_kb")]
+    pub max_size_kb: u64,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: LogLevel::default(),
+            max_size_kb: default_log_max_size_kb(),
+        }
+    }
+}
+
+fn default_log_max_size_kb() -> u64 {
+    1024
+}
+
+/// The least severe messages written to the log.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    #[default]
+    Off,
+    Error,
+    Warn,
+    Info,
+    /// Also what each key and frame took
+    Debug,
+    /// Also every key and how long each step of the main loop took
+    Trace,
+}
+
+/// Images on a line of their own in the content view.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImagesConfig {
+    /// Tallest an image is drawn, in rows, unless the markdown asks for a
+    /// size with `![alt|300](path)` or `![alt](path "height=10")`
+    #[serde(default = "default_max_height_rows")]
+    pub max_height_rows: u16,
+    #[serde(default)]
+    pub align: ImageAlign,
+    /// Whether images are drawn in the content view or only named on a
+    /// placeholder line. A note can set `images: off` in its frontmatter.
+    #[serde(default)]
+    pub inline: InlineImages,
+}
+
+impl Default for ImagesConfig {
+    fn default() -> Self {
+        Self { max_height_rows: default_max_height_rows(), align: ImageAlign::default(), inline: InlineImages::default() }
+    }
+}
+
+fn default_max_height_rows() -> u16 {
+    15
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageAlign {
+    #[default]
+    Left,
+    Center,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum InlineImages {
+    /// Drawn when the terminal can show images
+    #[default]
+    Auto,
+    Off,
+}
+
+impl InlineImages {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "auto" => Some(Self::Auto),
+            "off" => Some(Self::Off),
+            _ => None,
+        }
+    }
+}
+
+/// Markdown mistakes flagged while editing, one switch per rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintConfig {
+    /// A code fence that is never closed, turning the rest of the note into code
+    #[serde(default = "default_lint_rule")]
+    pub unclosed_fence: bool,
+    /// `[[` without `]]` on the same line
+    #[serde(default = "default_lint_rule")]
+    pub unclosed_wiki_link: bool,
+    /// A line with an odd number of `**`
+    #[serde(default = "default_lint_rule")]
+    pub unbalanced_bold: bool,
+    /// A table row with a different number of cells than the header
+    #[serde(default = "default_lint_rule")]
+    pub table_cells: bool,
+    /// Writing-style hints under `[lint.prose_rules]`, listed by `:prose`
+    #[serde(default)]
+    pub prose: bool,
+    #[serde(default)]
+    pub prose_rules: ProseRules,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            unclosed_fence: default_lint_rule(),
+            unclosed_wiki_link: default_lint_rule(),
+            unbalanced_bold: default_lint_rule(),
+            table_cells: default_lint_rule(),
+            prose: false,
+            prose_rules: ProseRules::default(),
+        }
+    }
+}
+
+impl LintConfig {
+    pub fn any_enabled(&self) -> bool {
+        self.unclosed_fence || self.unclosed_wiki_link || self.unbalanced_bold || self.table_cells
+    }
+}
+
+/// The hints `lint.prose` gives, one switch per rule. Extra words go in
+/// `prose_words.toml` next to the config file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProseRules {
+    /// Vague qualifiers like "very" and "quite"
+    #[serde(default = "default_lint_rule")]
+    pub weasel_words: bool,
+    /// A form of "to be" followed by a past participle
+    #[serde(default = "default_lint_rule")]
+    pub passive_voice: bool,
+    /// The same word twice in a row
+    #[serde(default = "default_lint_rule")]
+    pub repeated_words: bool,
+    /// Sentences over `max_sentence_words`
+    #[serde(default = "default_lint_rule")]
+    pub long_sentences: bool,
+    #[serde(default = "default_max_sentence_words")]
+    pub max_sentence_words: usize,
+}
+
+impl Default for ProseRules {
+    fn default() -> Self {
+        Self {
+            weasel_words: default_lint_rule(),
+            passive_voice: default_lint_rule(),
+            repeated_words: default_lint_rule(),
+            long_sentences: default_lint_rule(),
+            max_sentence_words: default_max_sentence_words(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisplayConfig {
+    #[serde(default)]
+    pub heading_style: HeadingStyle,
+    /// Number headings (`1.`, `1.2`) when `heading_style = "document"`
+    #[serde(default)]
+    pub number_headings: bool,
+    /// Show runs of blank lines as a single blank line in the content view
+    #[serde(default)]
+    pub collapse_blank_lines: bool,
+    /// Note names tried, in order, as a folder's index note; `{folder}` is the folder's own name
+    #[serde(default = "default_folder_index_notes")]
+    pub folder_index_notes: Vec<String>,
+    /// Hide the outline when the terminal is narrower than this; 0 never hides it
+    #[serde(default = "default_hide_outline_below")]
+    pub hide_outline_below: u16,
+    /// Hide the sidebar too when the terminal is narrower than this
+    #[serde(default = "default_hide_sidebar_below")]
+    pub hide_sidebar_below: u16,
+    /// List top-level list items and tasks in the outline, under their heading
+    #[serde(default)]
+    pub outline_list_items: bool,
+    /// List fenced code blocks in the outline, by language and first line
+    #[serde(default)]
+    pub outline_code_blocks: bool,
+    /// List lines that are only bold text in the outline, as pseudo-headings
+    #[serde(default)]
+    pub outline_bold_lines: bool,
+    #[serde(default)]
+    pub strikethrough: Strikethrough,
+    /// Render `<kbd>`, `<mark>` and `<br>` in notes, and hide other inline tags
+    #[serde(default)]
+    pub render_inline_html: bool,
+    /// Show the note title as a header line at the top of the content view
+    #[serde(default)]
+    pub title_header: bool,
+}
+
+fn default_hide_outline_below() -> u16 {
+    80
+}
+fn default_hide_sidebar_below() -> u16 {
+    60
 }
 
 fn default_line_wrap() -> bool {
@@ -78,6 +651,45 @@ fn default_right_padding() -> u16 {
 fn default_scrolloff() -> u8 {
     0
 }
+fn default_change_markers() -> bool {
+    true
+}
+fn default_paste_url_as_link() -> bool {
+    true
+}
+fn default_relevel_sections() -> bool {
+    true
+}
+fn default_large_paste_kb() -> usize {
+    100
+}
+fn default_large_paste_lines() -> usize {
+    2000
+}
+fn default_yank_ring_size() -> usize {
+    crate::editor::YANK_RING_SIZE
+}
+fn default_word_chars() -> String {
+    "_".to_string()
+}
+fn default_snippet_prefix() -> String {
+    ";".to_string()
+}
+fn default_date_format() -> String {
+    "%Y-%m-%d".to_string()
+}
+fn default_time_format() -> String {
+    "%H:%M".to_string()
+}
+fn default_lint_rule() -> bool {
+    true
+}
+fn default_max_sentence_words() -> usize {
+    40
+}
+fn default_folder_index_notes() -> Vec<String> {
+    vec!["{folder}".to_string(), "index".to_string(), "README".to_string()]
+}
 
 impl Default for EditorConfig {
     fn default() -> Self {
@@ -88,6 +700,46 @@ impl Default for EditorConfig {
             right_padding: default_right_padding(),
             line_numbers: LineNumberMode::default(),
             scrolloff: default_scrolloff(),
+            scroll_past_end: false,
+            typewriter: false,
+            change_markers: default_change_markers(),
+            conceal: false,
+            esc_behavior: EscBehavior::default(),
+            archive_cleared_tasks: false,
+            merge_style: MergeStyle::default(),
+            relevel_sections: default_relevel_sections(),
+            leave_section_link: false,
+            paste_url_as_link: default_paste_url_as_link(),
+            large_paste_kb: default_large_paste_kb(),
+            large_paste_lines: default_large_paste_lines(),
+            yank_ring_size: default_yank_ring_size(),
+            line_ending: LineEnding::default(),
+            ensure_final_newline: false,
+            word_chars: default_word_chars(),
+            snippet_prefix: default_snippet_prefix(),
+            date_format: default_date_format(),
+            time_format: default_time_format(),
+            indent_guides: false,
+            max_width: None,
+        }
+    }
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            heading_style: HeadingStyle::default(),
+            number_headings: false,
+            collapse_blank_lines: false,
+            folder_index_notes: default_folder_index_notes(),
+            hide_outline_below: default_hide_outline_below(),
+            hide_sidebar_below: default_hide_sidebar_below(),
+            outline_list_items: false,
+            outline_code_blocks: false,
+            outline_bold_lines: false,
+            strikethrough: Strikethrough::default(),
+            render_inline_html: false,
+            title_header: false,
         }
     }
 }
@@ -128,9 +780,52 @@ fn default_check_updates() -> bool {
 fn default_transparent_bg() -> bool {
     false
 }
+fn default_leader_key() -> String {
+    "space".to_string()
+}
+fn default_leader_map() -> BTreeMap<String, String> {
+    [
+        ("w", "save"),
+        ("q", "quit"),
+        ("ff", "file_finder"),
+        ("fs", "content_search"),
+        ("/", "buffer_search"),
+        ("gg", "graph"),
+        ("z", "zen"),
+        ("c", "quickfix"),
+        ("v", "vault"),
+        ("rc", "reload_config"),
+        ("fm", "frontmatter"),
+    ]
+    .into_iter()
+    .map(|(keys, action)| (keys.to_string(), action.to_string()))
+    .collect()
+}
+fn default_snippets() -> BTreeMap<String, String> {
+    [("dt", "{{date}} {{time}}"), ("td", "- [ ] ")]
+        .into_iter()
+        .map(|(name, text)| (name.to_string(), text.to_string()))
+        .collect()
+}
+fn default_status_left() -> Vec<String> {
+    ["brand", "vault", "mode", "pending", "note_title", "dirty", "message"]
+        .map(String::from)
+        .to_vec()
+}
+fn default_status_right() -> Vec<String> {
+    ["recording", "indexing", "zen", "word_count", "cursor_pos", "help"]
+        .map(String::from)
+        .to_vec()
+}
 fn default_floating_cursor() -> bool {
     false
 }
+fn default_max_fps() -> u32 {
+    60
+}
+fn default_passphrase_timeout() -> u64 {
+    15
+}
 
 impl Default for Config {
     fn default() -> Self {
@@ -148,7 +843,31 @@ impl Default for Config {
             check_updates: default_check_updates(),
             transparent_bg: default_transparent_bg(),
             floating_cursor: default_floating_cursor(),
+            max_fps: default_max_fps(),
+            external_change: ExternalChange::default(),
+            copy_wiki_link: CopyWikiLink::default(),
+            passphrase_timeout: default_passphrase_timeout(),
+            remember_positions: false,
+            remember_search_history: false,
+            background_io: false,
+            ipc: false,
+            leader_key: default_leader_key(),
+            status_left: default_status_left(),
+            status_right: default_status_right(),
             editor: EditorConfig::default(),
+            display: DisplayConfig::default(),
+            reading: ReadingConfig::default(),
+            export: ExportConfig::default(),
+            security: SecurityConfig::default(),
+            lint: LintConfig::default(),
+            images: ImagesConfig::default(),
+            network: NetworkConfig::default(),
+            snapshots: SnapshotsConfig::default(),
+            toc: TocConfig::default(),
+            log: LogConfig::default(),
+            leader: default_leader_map(),
+            snippets: default_snippets(),
+            vaults: Vec::new(),
         }
     }
 }
@@ -158,21 +877,61 @@ impl Config {
         Self::config_path().exists()
     }
 
+    /// The config, or the defaults when there's none or it can't be used.
+    /// For the command line; the TUI asks what to do about a broken file.
     pub fn load() -> Self {
-        let config_path = Self::config_path();
-        if config_path.exists() {
-            match fs::read_to_string(&config_path) {
-                Ok(content) => match toml::from_str(&content) {
-                    Ok(config) => return config,
-                    Err(e) => eprintln!("Failed to parse config: {}", e),
-                },
-                Err(e) => eprintln!("Failed to read config: {}", e),
+        Self::try_load().unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            Self::default()
+        })
+    }
+
+    /// The config, the defaults when there's no file, or what's wrong with it.
+    pub fn try_load() -> Result<Self, ConfigError> {
+        Ok(Self::read(&Self::config_path())?.unwrap_or_default())
+    }
+
+    /// The config at `path`, `None` when there's no file there.
+    pub fn read(path: &Path) -> Result<Option<Self>, ConfigError> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path).map_err(|e| ConfigError::unreadable(path, &e))?;
+        toml::from_str(&content)
+            .map(Some)
+            .map_err(|e| ConfigError::unparseable(path, &content, &e))
+    }
+
+    /// Settings that parsed but can't be used, as messages for the user.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems: Vec<String> = self
+            .status_left
+            .iter()
+            .chain(&self.status_right)
+            .filter(|name| !STATUS_SEGMENTS.contains(&name.as_str()))
+            .map(|name| format!("unknown status bar segment \"{}\"", name))
+            .collect();
+        for (i, vault) in self.vaults.iter().enumerate() {
+            if self.vaults[..i].iter().any(|other| other.name == vault.name) {
+                problems.push(format!("more than one vault named \"{}\"", vault.name));
             }
         }
-        Self::default()
+        problems
+    }
+
+    /// The `[[vaults]]` entry for the notes folder in use, if it has one.
+    pub fn active_vault(&self) -> Option<&VaultConfig> {
+        let notes_path = self.notes_path();
+        self.vaults.iter().find(|vault| vault.path() == notes_path)
+    }
+
+    pub fn shows_status_segment(&self, name: &str) -> bool {
+        self.status_left.iter().chain(&self.status_right).any(|s| s == name)
     }
 
-    pub fn load_or_create() -> Self {
+    /// The config, written out with the defaults first when there's none.
+    /// A config that doesn't parse is left as it is and reported.
+    pub fn load_or_create() -> Result<Self, ConfigError> {
         let config_dir = Self::config_dir();
         let config_path = Self::config_path();
         let themes_dir = Self::themes_dir();
@@ -190,35 +949,69 @@ impl Config {
             let _ = fs::write(&default_theme_path, default_theme_content);
         }
 
-        if !config_path.exists() {
-            let default_config = Self::default();
-            if let Ok(toml_string) = toml::to_string_pretty(&default_config) {
-                let _ = fs::write(&config_path, toml_string);
-            }
+        Self::read_or_create(&config_path)
+    }
+
+    /// The config at `path`, creating it with the defaults when missing.
+    pub fn read_or_create(path: &Path) -> Result<Self, ConfigError> {
+        if let Some(config) = Self::read(path)? {
+            return Ok(config);
         }
-        Self::load()
+        let default_config = Self::default();
+        if let Ok(toml_string) = toml::to_string_pretty(&default_config) {
+            let _ = fs::write(path, toml_string);
+        }
+        Ok(default_config)
     }
 
     pub fn config_path() -> PathBuf {
         Self::config_dir().join("config.toml")
     }
     pub fn config_dir() -> PathBuf {
+        if let Some(home) = home_override() {
+            return home.join("config");
+        }
         dirs::home_dir()
             .unwrap_or_else(|| PathBuf::from("."))
             .join(".config")
             .join("ekphos")
     }
+    /// Where the search indexes, image cache, pins and positions are kept
+    pub fn cache_dir() -> PathBuf {
+        if let Some(home) = home_override() {
+            return home.join("cache");
+        }
+        dirs::cache_dir()
+            .unwrap_or_else(|| {
+                std::env::var("HOME")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|_| PathBuf::from("."))
+                    .join(".cache")
+            })
+            .join("ekphos")
+    }
     pub fn themes_dir() -> PathBuf {
         Self::config_dir().join("themes")
     }
+    /// Folders of notes `:scaffold {name}` creates from
+    pub fn templates_dir() -> PathBuf {
+        Self::config_dir().join("templates")
+    }
 
     pub fn save(&self) -> std::io::Result<()> {
         let config_dir = Self::config_dir();
         fs::create_dir_all(&config_dir)?;
-        let config_path = Self::config_path();
+        self.save_to(&Self::config_path())
+    }
+
+    /// Write the config to `path`, unless what's there doesn't parse: a
+    /// broken config is the user's to fix, not to be replaced with this one.
+    pub fn save_to(&self, path: &Path) -> std::io::Result<()> {
+        if let Err(e) = Self::read(path) {
+            return Err(std::io::Error::other(format!("not overwriting it: {}", e)));
+        }
         let toml_string = toml::to_string_pretty(self).unwrap_or_else(|_| String::new());
-        fs::write(&config_path, toml_string)?;
-        Ok(())
+        fs::write(path, toml_string)
     }
 
     pub fn notes_path(&self) -> PathBuf {
@@ -227,6 +1020,76 @@ impl Config {
     }
 }
 
+/// Why the config file couldn't be used, with where in it when it didn't
+/// parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub path: PathBuf,
+    pub message: String,
+    /// 1-based line and column of the problem
+    pub position: Option<(usize, usize)>,
+    /// The line the problem is on
+    pub snippet: Option<String>,
+}
+
+impl ConfigError {
+    fn unreadable(path: &Path, error: &std::io::Error) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            message: error.to_string(),
+            position: None,
+            snippet: None,
+        }
+    }
+
+    fn unparseable(path: &Path, content: &str, error: &toml::de::Error) -> Self {
+        let (position, snippet) = match error.span() {
+            Some(span) => {
+                let start = span.start.min(content.len());
+                let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+                let line = content[line_start..].lines().next().unwrap_or("");
+                let line_number = content[..start].matches('\n').count() + 1;
+                let column = content[line_start..start].chars().count() + 1;
+                (Some((line_number, column)), Some(line.to_string()))
+            }
+            None => (None, None),
+        };
+        Self {
+            path: path.to_path_buf(),
+            message: error.message().trim().to_string(),
+            position,
+            snippet,
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let Some((line, column)) = self.position {
+            write!(f, " line {}, column {}", line, column)?;
+        }
+        write!(f, ": {}", self.message.replace('\n', "; "))
+    }
+}
+
+/// Set to keep the config, themes and caches in one folder instead of
+/// `~/.config/ekphos` and the system cache folder, e.g. for a portable setup.
+pub const HOME_ENV: &str = "EKPHOS_HOME";
+
+/// The folder from `EKPHOS_HOME`, if set. Tests always get a temporary one,
+/// so they never read or write the user's own config.
+fn home_override() -> Option<&'static Path> {
+    static HOME: OnceLock<Option<PathBuf>> = OnceLock::new();
+    HOME.get_or_init(|| {
+        if cfg!(test) {
+            return Some(std::env::temp_dir().join(format!("ekphos-test-home-{}", std::process::id())));
+        }
+        std::env::var_os(HOME_ENV).filter(|dir| !dir.is_empty()).map(PathBuf::from)
+    })
+    .as_deref()
+}
+
 // ============================================================================
 // Theme File Format (TOML parsing structures)
 // ============================================================================
@@ -241,6 +1104,8 @@ pub struct ThemeFile {
     pub semantic: SemanticColors,
     #[serde(default)]
     pub ui: UiColorsFile,
+    #[serde(default)]
+    pub markdown: MarkdownColors,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -294,6 +1159,8 @@ pub struct UiColorsFile {
     #[serde(default)]
     pub content: ContentColors,
     #[serde(default)]
+    pub callout: CalloutColors,
+    #[serde(default)]
     pub outline: OutlineColors,
     #[serde(default)]
     pub search: SearchColors,
@@ -325,6 +1192,12 @@ pub struct DialogColors {
     pub title: String,
     #[serde(default = "defaults::foreground")]
     pub text: String,
+    /// Destructive confirmations and errors; the semantic error color if unset
+    #[serde(default)]
+    pub danger: Option<String>,
+    /// The key that goes ahead with a dialog; the semantic success color if unset
+    #[serde(default)]
+    pub confirm: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -367,6 +1240,14 @@ pub struct ContentColors {
     pub blockquote: String,
     #[serde(default = "defaults::secondary")]
     pub list_marker: String,
+    /// Bullets nested one level, with `indent_guides`
+    #[serde(default = "defaults::info")]
+    pub list_marker2: String,
+    /// Bullets nested two levels; deeper ones go round the three again
+    #[serde(default = "defaults::success")]
+    pub list_marker3: String,
+    #[serde(default = "defaults::border")]
+    pub indent_guide: String,
     #[serde(default = "defaults::secondary")]
     pub tag: String,
     #[serde(default = "defaults::background_secondary")]
@@ -375,6 +1256,24 @@ pub struct ContentColors {
     pub frontmatter: String,
 }
 
+/// `> [!NOTE]` and the other callout types; `other` is for types without
+/// a color of their own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalloutColors {
+    #[serde(default = "defaults::info")]
+    pub note: String,
+    #[serde(default = "defaults::success")]
+    pub tip: String,
+    #[serde(default = "defaults::secondary")]
+    pub important: String,
+    #[serde(default = "defaults::warning")]
+    pub warning: String,
+    #[serde(default = "defaults::error")]
+    pub caution: String,
+    #[serde(default = "defaults::primary")]
+    pub other: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutlineColors {
     #[serde(default = "defaults::background")]
@@ -427,12 +1326,48 @@ pub struct EditorColors {
     pub blockquote: String,
     #[serde(default = "defaults::secondary")]
     pub list_marker: String,
+    /// Bullets nested one level, with `indent_guides`
+    #[serde(default = "defaults::info")]
+    pub list_marker2: String,
+    /// Bullets nested two levels; deeper ones go round the three again
+    #[serde(default = "defaults::success")]
+    pub list_marker3: String,
+    #[serde(default = "defaults::border")]
+    pub indent_guide: String,
     #[serde(default = "defaults::warning")]
     pub bold: String,
     #[serde(default = "defaults::info")]
     pub italic: String,
 }
 
+/// `[markdown]`: how Markdown is colored in the editor and the view alike.
+/// Each one left out keeps the `[ui.editor]` color in the editor and the
+/// `[ui.content]` one in the view.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MarkdownColors {
+    pub h1: Option<String>,
+    pub h2: Option<String>,
+    pub h3: Option<String>,
+    pub h4: Option<String>,
+    pub h5: Option<String>,
+    pub h6: Option<String>,
+    pub bold: Option<String>,
+    pub italic: Option<String>,
+    /// Inline `code`
+    pub code: Option<String>,
+    /// Fenced code blocks, fences included
+    pub code_block: Option<String>,
+    pub link: Option<String>,
+    /// `[[wiki links]]` to a note that exists
+    pub wiki_valid: Option<String>,
+    /// `[[wiki links]]` to no note
+    pub wiki_invalid: Option<String>,
+    pub blockquote: Option<String>,
+    pub list_marker: Option<String>,
+    /// Horizontal rules
+    pub hr: Option<String>,
+}
+
 // Default color values module
 mod defaults {
     pub fn background() -> String {
@@ -532,6 +1467,8 @@ impl Default for DialogColors {
             border: defaults::primary(),
             title: defaults::primary(),
             text: defaults::foreground(),
+            danger: None,
+            confirm: None,
         }
     }
 }
@@ -563,6 +1500,9 @@ impl Default for ContentColors {
             code_background: defaults::background_secondary(),
             blockquote: defaults::muted(),
             list_marker: defaults::secondary(),
+            list_marker2: defaults::info(),
+            list_marker3: defaults::success(),
+            indent_guide: defaults::border(),
             tag: defaults::secondary(),
             tag_background: defaults::background_secondary(),
             frontmatter: defaults::muted(),
@@ -570,6 +1510,19 @@ impl Default for ContentColors {
     }
 }
 
+impl Default for CalloutColors {
+    fn default() -> Self {
+        Self {
+            note: defaults::info(),
+            tip: defaults::success(),
+            important: defaults::secondary(),
+            warning: defaults::warning(),
+            caution: defaults::error(),
+            other: defaults::primary(),
+        }
+    }
+}
+
 impl Default for OutlineColors {
     fn default() -> Self {
         Self {
@@ -608,6 +1561,9 @@ impl Default for EditorColors {
             link: defaults::info(),
             blockquote: defaults::muted(),
             list_marker: defaults::secondary(),
+            list_marker2: defaults::info(),
+            list_marker3: defaults::success(),
+            indent_guide: defaults::border(),
             bold: defaults::warning(),
             italic: defaults::info(),
         }
@@ -616,7 +1572,7 @@ impl Default for EditorColors {
 
 /// Names of the official themes bundled into the binary via `include_str!`.
 /// Keep this in sync with the match arms in `ThemeFile::get_bundled_theme`.
-pub const BUNDLED_THEMES: &[&str] = &["ekphos-dawn", "dracula", "kanagawa"];
+pub const BUNDLED_THEMES: &[&str] = &["ekphos-dawn", "dracula", "kanagawa", "high-contrast"];
 
 /// A theme available for selection, with its origin.
 #[derive(Debug, Clone, PartialEq)]
@@ -642,6 +1598,7 @@ impl ThemeFile {
             "ekphos-dawn" => include_str!("../themes/ekphos-dawn.toml"),
             "dracula" => include_str!("../themes/dracula.toml"),
             "kanagawa" => include_str!("../themes/kanagawa.toml"),
+            "high-contrast" => include_str!("../themes/high-contrast.toml"),
             _ => return None,
         };
         Self::load_from_str(content)
@@ -751,9 +1708,12 @@ pub struct Theme {
     pub dialog: DialogTheme,
     pub sidebar: SidebarTheme,
     pub content: ContentTheme,
+    pub callout: CalloutTheme,
     pub outline: OutlineTheme,
     pub search: SearchTheme,
     pub editor: EditorTheme,
+    /// What the editor highlights Markdown with
+    pub markdown: MarkdownTheme,
 }
 
 #[derive(Debug, Clone)]
@@ -771,6 +1731,8 @@ pub struct DialogTheme {
     pub border: Color,
     pub title: Color,
     pub text: Color,
+    pub danger: Color,
+    pub confirm: Color,
 }
 
 #[derive(Debug, Clone)]
@@ -790,17 +1752,50 @@ pub struct ContentTheme {
     pub heading2: Color,
     pub heading3: Color,
     pub heading4: Color,
+    pub heading5: Color,
+    pub heading6: Color,
+    pub bold: Color,
+    pub italic: Color,
     pub link: Color,
+    pub wiki_link: Color,
     pub link_invalid: Color,
     pub code: Color,
+    pub code_block: Color,
     pub code_background: Color,
     pub blockquote: Color,
+    pub hr: Color,
     pub list_marker: Color,
+    pub list_marker2: Color,
+    pub list_marker3: Color,
+    pub indent_guide: Color,
     pub tag: Color,
     pub tag_background: Color,
     pub frontmatter: Color,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct CalloutTheme {
+    pub note: Color,
+    pub tip: Color,
+    pub important: Color,
+    pub warning: Color,
+    pub caution: Color,
+    pub other: Color,
+}
+
+impl Default for CalloutTheme {
+    fn default() -> Self {
+        Self {
+            note: Color::Blue,
+            tip: Color::Green,
+            important: Color::Magenta,
+            warning: Color::Yellow,
+            caution: Color::Red,
+            other: Color::Cyan,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct OutlineTheme {
     pub background: Color,
@@ -832,12 +1827,49 @@ pub struct EditorTheme {
     pub link: Color,
     pub blockquote: Color,
     pub list_marker: Color,
+    pub list_marker2: Color,
+    pub list_marker3: Color,
+    pub indent_guide: Color,
     pub bold: Color,
     pub italic: Color,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct MarkdownTheme {
+    pub headings: [Color; 6],
+    pub bold: Color,
+    pub italic: Color,
+    pub code: Color,
+    pub code_block: Color,
+    pub link: Color,
+    pub wiki_valid: Color,
+    pub wiki_invalid: Color,
+    pub blockquote: Color,
+    pub list_marker: Color,
+    pub hr: Color,
+}
+
+impl ContentTheme {
+    /// Bullet color of a list item nested `depth` levels deep.
+    pub fn list_marker_at(&self, depth: usize) -> Color {
+        [self.list_marker, self.list_marker2, self.list_marker3][depth % 3]
+    }
+}
+
+impl EditorTheme {
+    /// Bullet colors by nesting depth, as [`ContentTheme::list_marker_at`]
+    /// goes round them.
+    pub fn list_markers(&self) -> [Color; 3] {
+        [self.list_marker, self.list_marker2, self.list_marker3]
+    }
+}
+
 impl Theme {
     pub fn from_file(tf: &ThemeFile) -> Self {
+        let md = &tf.markdown;
+        // A `[markdown]` color where it's set, else the mode's own one
+        let pick = |color: &Option<String>, fallback: &str| parse_hex_color(color.as_deref().unwrap_or(fallback));
+        let (content, editor) = (&tf.ui.content, &tf.ui.editor);
         Self {
             background: parse_hex_color(&tf.base.background),
             background_secondary: parse_hex_color(&tf.base.background_secondary),
@@ -869,6 +1901,8 @@ impl Theme {
                 border: parse_hex_color(&tf.ui.dialog.border),
                 title: parse_hex_color(&tf.ui.dialog.title),
                 text: parse_hex_color(&tf.ui.dialog.text),
+                danger: parse_hex_color(tf.ui.dialog.danger.as_deref().unwrap_or(&tf.semantic.error)),
+                confirm: parse_hex_color(tf.ui.dialog.confirm.as_deref().unwrap_or(&tf.semantic.success)),
             },
             sidebar: SidebarTheme {
                 background: parse_hex_color(&tf.ui.sidebar.background),
@@ -880,20 +1914,38 @@ impl Theme {
             content: ContentTheme {
                 background: parse_hex_color(&tf.ui.content.background),
                 text: parse_hex_color(&tf.ui.content.text),
-                heading1: parse_hex_color(&tf.ui.content.heading1),
-                heading2: parse_hex_color(&tf.ui.content.heading2),
-                heading3: parse_hex_color(&tf.ui.content.heading3),
-                heading4: parse_hex_color(&tf.ui.content.heading4),
-                link: parse_hex_color(&tf.ui.content.link),
-                link_invalid: parse_hex_color(&tf.ui.content.link_invalid),
-                code: parse_hex_color(&tf.ui.content.code),
+                heading1: pick(&md.h1, &content.heading1),
+                heading2: pick(&md.h2, &content.heading2),
+                heading3: pick(&md.h3, &content.heading3),
+                heading4: pick(&md.h4, &content.heading4),
+                heading5: pick(&md.h5, &content.heading4),
+                heading6: pick(&md.h6, &content.text),
+                bold: pick(&md.bold, &content.text),
+                italic: pick(&md.italic, &content.text),
+                link: pick(&md.link, &content.link),
+                wiki_link: pick(&md.wiki_valid, &content.link),
+                link_invalid: pick(&md.wiki_invalid, &content.link_invalid),
+                code: pick(&md.code, &content.code),
+                code_block: pick(&md.code_block, &content.code),
                 code_background: parse_hex_color(&tf.ui.content.code_background),
-                blockquote: parse_hex_color(&tf.ui.content.blockquote),
-                list_marker: parse_hex_color(&tf.ui.content.list_marker),
+                blockquote: pick(&md.blockquote, &content.blockquote),
+                hr: pick(&md.hr, &tf.ui.border),
+                list_marker: pick(&md.list_marker, &content.list_marker),
+                list_marker2: parse_hex_color(&tf.ui.content.list_marker2),
+                list_marker3: parse_hex_color(&tf.ui.content.list_marker3),
+                indent_guide: parse_hex_color(&tf.ui.content.indent_guide),
                 tag: parse_hex_color(&tf.ui.content.tag),
                 tag_background: parse_hex_color(&tf.ui.content.tag_background),
                 frontmatter: parse_hex_color(&tf.ui.content.frontmatter),
             },
+            callout: CalloutTheme {
+                note: parse_hex_color(&tf.ui.callout.note),
+                tip: parse_hex_color(&tf.ui.callout.tip),
+                important: parse_hex_color(&tf.ui.callout.important),
+                warning: parse_hex_color(&tf.ui.callout.warning),
+                caution: parse_hex_color(&tf.ui.callout.caution),
+                other: parse_hex_color(&tf.ui.callout.other),
+            },
             outline: OutlineTheme {
                 background: parse_hex_color(&tf.ui.outline.background),
                 heading1: parse_hex_color(&tf.ui.outline.heading1),
@@ -919,10 +1971,33 @@ impl Theme {
                 code: parse_hex_color(&tf.ui.editor.code),
                 link: parse_hex_color(&tf.ui.editor.link),
                 blockquote: parse_hex_color(&tf.ui.editor.blockquote),
-                list_marker: parse_hex_color(&tf.ui.editor.list_marker),
+                list_marker: pick(&md.list_marker, &editor.list_marker),
+                list_marker2: parse_hex_color(&tf.ui.editor.list_marker2),
+                list_marker3: parse_hex_color(&tf.ui.editor.list_marker3),
+                indent_guide: parse_hex_color(&tf.ui.editor.indent_guide),
                 bold: parse_hex_color(&tf.ui.editor.bold),
                 italic: parse_hex_color(&tf.ui.editor.italic),
             },
+            markdown: MarkdownTheme {
+                headings: [
+                    pick(&md.h1, &editor.heading1),
+                    pick(&md.h2, &editor.heading2),
+                    pick(&md.h3, &editor.heading3),
+                    pick(&md.h4, &editor.heading4),
+                    pick(&md.h5, &editor.heading5),
+                    pick(&md.h6, &editor.heading6),
+                ],
+                bold: pick(&md.bold, &editor.bold),
+                italic: pick(&md.italic, &editor.italic),
+                code: pick(&md.code, &editor.code),
+                code_block: pick(&md.code_block, &editor.code),
+                link: pick(&md.link, &editor.link),
+                wiki_valid: pick(&md.wiki_valid, &tf.semantic.info),
+                wiki_invalid: pick(&md.wiki_invalid, &tf.semantic.error),
+                blockquote: pick(&md.blockquote, &editor.blockquote),
+                list_marker: pick(&md.list_marker, &editor.list_marker),
+                hr: pick(&md.hr, &editor.blockquote),
+            },
         }
     }
 
@@ -974,4 +2049,95 @@ mod tests {
         assert_eq!(parse_hex_color("aé234"), Color::White); // 6 bytes, 5 chars
         assert_eq!(parse_hex_color("世界AB"), Color::White);
     }
+
+    /// Themes written before `danger` and `confirm` get their semantic colors.
+    #[test]
+    fn dialog_danger_and_confirm_fall_back_to_semantic_colors() {
+        let dracula = Theme::from_file(&ThemeFile::get_bundled_theme("dracula").unwrap());
+        assert_eq!(dracula.dialog.danger, dracula.error);
+        assert_eq!(dracula.dialog.confirm, dracula.success);
+
+        let high_contrast = Theme::from_file(&ThemeFile::get_bundled_theme("high-contrast").unwrap());
+        assert_eq!(high_contrast.dialog.danger, Color::Rgb(255, 48, 48));
+        assert_eq!(high_contrast.dialog.background, Color::Rgb(0, 0, 0));
+        assert!(BUNDLED_THEMES.iter().all(|name| ThemeFile::get_bundled_theme(name).is_some()));
+    }
+
+    /// `[markdown]` colors both modes where set, and each mode keeps its own
+    /// colors where not.
+    #[test]
+    fn markdown_colors_apply_to_editor_and_view() {
+        let dracula = Theme::from_file(&ThemeFile::get_bundled_theme("dracula").unwrap());
+        assert_eq!(dracula.markdown.headings[0], dracula.editor.heading1);
+        assert_eq!(dracula.markdown.code_block, dracula.editor.code);
+        assert_eq!(dracula.markdown.wiki_valid, dracula.info);
+        assert_eq!(dracula.content.heading5, dracula.content.heading4);
+        assert_eq!(dracula.content.hr, dracula.border);
+
+        let mut file = ThemeFile::get_bundled_theme("dracula").unwrap();
+        file.markdown = toml::from_str("h1 = \"#102030\"\ncode_block = \"#405060\"\nwiki_invalid = \"#708090\"").unwrap();
+        let theme = Theme::from_file(&file);
+        assert_eq!((theme.markdown.headings[0], theme.content.heading1), (Color::Rgb(16, 32, 48), Color::Rgb(16, 32, 48)));
+        assert_eq!((theme.markdown.code_block, theme.content.code_block), (Color::Rgb(64, 80, 96), Color::Rgb(64, 80, 96)));
+        assert_eq!(theme.content.link_invalid, Color::Rgb(112, 128, 144));
+        assert_eq!(theme.markdown.wiki_invalid, Color::Rgb(112, 128, 144));
+        assert_eq!(theme.content.code, dracula.content.code);
+        assert_eq!(theme.markdown.headings[1], dracula.markdown.headings[1]);
+    }
+
+    /// A config that doesn't parse is reported with where, and neither
+    /// loading nor saving writes over it.
+    #[test]
+    fn broken_config_is_reported_and_left_alone() {
+        let dir = crate::app::test_support::TempDir::new("broken-config");
+        let path = dir.join("config.toml");
+        let broken = "theme = \"ekphos-dawn\"\n[editor\ntab_width = 4\n";
+        fs::write(&path, broken).unwrap();
+
+        let error = Config::read(&path).unwrap_err();
+        assert_eq!(error.position.map(|(line, _)| line), Some(2));
+        assert_eq!(error.snippet.as_deref(), Some("[editor"));
+        assert!(error.to_string().contains("line 2"));
+
+        assert!(Config::read_or_create(&path).is_err());
+        assert!(Config::default().save_to(&path).is_err());
+        assert_eq!(fs::read(&path).unwrap(), broken.as_bytes());
+
+        // Missing is not broken: the defaults are written out
+        let missing = dir.join("new.toml");
+        assert!(Config::read(&missing).unwrap().is_none());
+        assert!(Config::read_or_create(&missing).is_ok());
+        assert!(Config::read(&missing).unwrap().is_some());
+        Config::default().save_to(&missing).unwrap();
+    }
+
+    #[test]
+    fn esc_behavior_defaults_to_ask() {
+        let editor: EditorConfig = toml::from_str("esc_behavior = \"keep\"").unwrap();
+        assert_eq!(editor.esc_behavior, EscBehavior::Keep);
+        let editor: EditorConfig = toml::from_str("").unwrap();
+        assert_eq!(editor.esc_behavior, EscBehavior::Ask);
+    }
+
+    #[test]
+    fn validate_reports_unknown_status_segments() {
+        assert!(Config::default().validate().is_empty());
+        let config: Config =
+            toml::from_str("status_left = [\"mode\", \"title\"]\nstatus_right = [\"clock\"]").unwrap();
+        assert_eq!(config.validate(), ["unknown status bar segment \"title\""]);
+    }
+
+    #[test]
+    fn vaults_match_the_notes_folder() {
+        let config: Config = toml::from_str(
+            "notes_dir = \"/notes/home\"\n\
+             [[vaults]]\nname = \"work\"\npath = \"/notes/work\"\n\
+             [[vaults]]\nname = \"home\"\npath = \"/notes/home\"\n\
+             [[vaults]]\nname = \"work\"\npath = \"/elsewhere\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.active_vault().map(|v| v.name.as_str()), Some("home"));
+        assert_eq!(config.validate(), ["more than one vault named \"work\""]);
+        assert!(Config::default().active_vault().is_none());
+    }
 }
@@ -106,6 +106,7 @@ impl TextBuffer {
         }
     }
 
+    #[allow(dead_code)]
     pub fn delete_char(&mut self, row: usize, col: usize) -> Option<char> {
         if let Some(line) = self.line_mut(row) {
             let chars: Vec<char> = line.chars().collect();
@@ -0,0 +1,78 @@
+//! Insert-mode word completion (`Ctrl-N` / `Ctrl-P`) from words already in the buffer.
+
+use std::collections::HashMap;
+
+use super::cursor::is_word_char;
+
+/// The partial word ending at `col`, as `(start_col, prefix)`.
+pub fn word_prefix_before(line: &str, col: usize) -> Option<(usize, String)> {
+    let chars: Vec<char> = line.chars().collect();
+    let end = col.min(chars.len());
+    let start = chars[..end]
+        .iter()
+        .rposition(|&c| !is_word_char(c))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    if start == end {
+        return None;
+    }
+    Some((start, chars[start..end].iter().collect()))
+}
+
+/// Words in `lines` that start with `prefix` (and are longer than it), ranked by
+/// distance from `cursor_row`, then by how often they occur, then alphabetically.
+pub fn buffer_candidates(lines: &[&str], cursor_row: usize, prefix: &str) -> Vec<String> {
+    // word -> (closest row distance, occurrences)
+    let mut seen: HashMap<&str, (usize, usize)> = HashMap::new();
+
+    for (row, line) in lines.iter().enumerate() {
+        let distance = row.abs_diff(cursor_row);
+        for word in line.split(|c: char| !is_word_char(c)) {
+            if word.len() <= prefix.len() || !word.starts_with(prefix) {
+                continue;
+            }
+            let entry = seen.entry(word).or_insert((distance, 0));
+            entry.0 = entry.0.min(distance);
+            entry.1 += 1;
+        }
+    }
+
+    let mut ranked: Vec<(&str, (usize, usize))> = seen.into_iter().collect();
+    ranked.sort_by(|(a, (da, ca)), (b, (db, cb))| {
+        da.cmp(db).then(cb.cmp(ca)).then(a.cmp(b))
+    });
+    ranked.into_iter().map(|(word, _)| word.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_prefix_before() {
+        assert_eq!(word_prefix_before("see comp", 8), Some((4, "comp".to_string())));
+        assert_eq!(word_prefix_before("see ", 4), None);
+        assert_eq!(word_prefix_before("", 0), None);
+    }
+
+    #[test]
+    fn test_candidates_filter_by_prefix() {
+        let lines = ["complete compile", "other words", "com"];
+        let words = buffer_candidates(&lines, 2, "com");
+        assert_eq!(words.len(), 2);
+        assert!(words.contains(&"complete".to_string()));
+        assert!(words.contains(&"compile".to_string()));
+        assert!(!words.contains(&"com".to_string()));
+    }
+
+    #[test]
+    fn test_candidates_ranked_by_proximity_then_frequency() {
+        let lines = ["alpha alps", "alps alpine", "", "al"];
+        let words = buffer_candidates(&lines, 3, "al");
+        assert_eq!(words, vec!["alps", "alpine", "alpha"]);
+
+        let lines = ["al", "beta betas betas"];
+        let words = buffer_candidates(&lines, 0, "be");
+        assert_eq!(words, vec!["betas", "beta"]);
+    }
+}
@@ -0,0 +1,157 @@
+//! Conceal mode: link syntax is hidden on lines the cursor isn't on, so
+//! `[[target|alias]]` reads as `alias` and `[text](url)` as `text`. The
+//! tildes around `~~strikethrough~~` go too.
+//!
+//! Concealing only changes what is drawn. Buffer columns are untouched, and a
+//! row's mask just marks which of its chars take no space on screen.
+
+use super::strikethrough_at;
+use crate::app::{split_wiki_unescaped, WikiLink};
+
+/// Hidden flags for each char column of `line`, or an empty mask when nothing
+/// on the line is concealed. Links inside inline code and image embeds
+/// (`![alt](src)`) are left alone.
+pub fn concealed_columns(line: &str) -> Vec<bool> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut hidden = vec![false; chars.len()];
+    let mut any = false;
+
+    // Closing `~~` of the strikethrough being scanned, already hidden
+    let mut closing_tildes = None;
+    let mut i = 0;
+    while i < chars.len() {
+        if closing_tildes == Some(i) {
+            closing_tildes = None;
+            i += 2;
+            continue;
+        }
+        match chars[i] {
+            '`' => match chars[i + 1..].iter().position(|&c| c == '`') {
+                Some(len) => i += len + 2,
+                None => break,
+            },
+            '[' if chars.get(i + 1) == Some(&'[') => match wiki_link_at(&chars, i) {
+                Some((end, shown)) => {
+                    hide_except(&mut hidden, i..end, shown);
+                    any = true;
+                    i = end;
+                }
+                None => i += 1,
+            },
+            '[' if i == 0 || chars[i - 1] != '!' => match markdown_link_at(&chars, i) {
+                Some((end, shown)) => {
+                    hide_except(&mut hidden, i..end, shown);
+                    any = true;
+                    i = end;
+                }
+                None => i += 1,
+            },
+            // Only the delimiters: links and code inside are still handled
+            '~' => match strikethrough_at(&chars, i) {
+                Some(end) => {
+                    hidden[i..i + 2].fill(true);
+                    hidden[end - 2..end].fill(true);
+                    any = true;
+                    closing_tildes = Some(end - 2);
+                    i += 2;
+                }
+                None => i += 1,
+            },
+            _ => i += 1,
+        }
+    }
+
+    if any {
+        hidden
+    } else {
+        Vec::new()
+    }
+}
+
+/// Whether `col` is hidden by `mask`; columns past its end never are.
+#[inline]
+pub fn is_concealed(mask: &[bool], col: usize) -> bool {
+    mask.get(col).copied().unwrap_or(false)
+}
+
+fn hide_except(hidden: &mut [bool], link: std::ops::Range<usize>, shown: std::ops::Range<usize>) {
+    for col in link {
+        hidden[col] = !shown.contains(&col);
+    }
+}
+
+/// `[[target#heading|alias]]` starting at `start`: the end of the link and the
+/// columns left visible (the alias, or the target when there is none).
+fn wiki_link_at(chars: &[char], start: usize) -> Option<(usize, std::ops::Range<usize>)> {
+    let text: String = chars[start..].iter().collect();
+    let link = WikiLink::parse(&text)?;
+    let inner = &text[2..link.len - 2];
+    let inner_start = start + 2;
+    let end = start + text[..link.len].chars().count();
+
+    let shown = match split_wiki_unescaped(inner, '|') {
+        Some((target, alias)) if !alias.is_empty() => inner_start + target.chars().count() + 1..end - 2,
+        Some((target, _)) => inner_start..inner_start + target.chars().count(),
+        None => inner_start..end - 2,
+    };
+    Some((end, shown))
+}
+
+/// `[text](url)` starting at `start`: the end of the link and the text columns.
+fn markdown_link_at(chars: &[char], start: usize) -> Option<(usize, std::ops::Range<usize>)> {
+    let text_start = start + 1;
+    let text_len = chars[text_start..].iter().position(|&c| c == ']')?;
+    let text = &chars[text_start..text_start + text_len];
+    if text.is_empty() || text.contains(&'[') {
+        return None;
+    }
+
+    let paren = text_start + text_len + 1;
+    if chars.get(paren) != Some(&'(') {
+        return None;
+    }
+    let url_len = chars[paren + 1..].iter().position(|&c| c == ')')?;
+    Some((paren + 1 + url_len + 1, text_start..text_start + text_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The visible text of `line` once its concealed columns are dropped.
+    fn shown(line: &str) -> String {
+        let mask = concealed_columns(line);
+        line.chars()
+            .enumerate()
+            .filter(|&(col, _)| !is_concealed(&mask, col))
+            .map(|(_, c)| c)
+            .collect()
+    }
+
+    #[test]
+    fn test_wiki_links_show_alias_or_target() {
+        assert_eq!(shown("see [[notes/todo|my list]] now"), "see my list now");
+        assert_eq!(shown("see [[todo#today]]"), "see todo#today");
+        assert_eq!(shown("[[todo|]]"), "todo");
+    }
+
+    #[test]
+    fn test_markdown_links_show_text() {
+        assert_eq!(shown("a [site](https://x.test) b"), "a site b");
+        assert_eq!(shown("[one](1) and [two](2)"), "one and two");
+    }
+
+    #[test]
+    fn test_strikethrough_tildes_are_hidden() {
+        assert_eq!(shown("~~done~~ and ~~[x](y)~~"), "done and x");
+        assert_eq!(shown("~~a~~b~~"), "ab~~");
+        assert!(concealed_columns("cd ~/notes").is_empty());
+    }
+
+    #[test]
+    fn test_code_images_and_broken_links_stay_visible() {
+        assert!(concealed_columns("`[[todo]]` and ![img](a.png)").is_empty());
+        assert!(concealed_columns("[not a link] (x)").is_empty());
+        assert!(concealed_columns("[[unclosed").is_empty());
+    }
+}
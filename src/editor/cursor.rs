@@ -74,11 +74,22 @@ impl Selection {
     }
 }
 
+/// The column Up/Down and page motions aim for, kept over lines too short to
+/// reach it so the cursor comes back to it on longer ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreferredCol {
+    /// Display column in the line
+    pub col: usize,
+    /// Display column on its screen line, when the line wraps
+    pub x: usize,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct Cursor {
     pub position: Position,
     pub selection: Selection,
-    pub preferred_col: Option<usize>,
+    /// Set by vertical motions; any other move or edit clears it
+    pub preferred_col: Option<PreferredCol>,
 }
 
 impl Cursor {
@@ -90,16 +101,18 @@ impl Cursor {
         self.position
     }
 
+    /// Moves the cursor; with `update_preferred` its own column becomes the
+    /// preferred one again.
     pub fn set_pos(&mut self, pos: Position, update_preferred: bool) {
         self.position = pos;
         if update_preferred {
-            self.preferred_col = Some(pos.col);
+            self.preferred_col = None;
         }
     }
 
     pub fn move_to(&mut self, row: usize, col: usize) {
         self.position = Position::new(row, col);
-        self.preferred_col = Some(col);
+        self.preferred_col = None;
     }
 
     pub fn start_selection(&mut self) {
@@ -123,7 +136,30 @@ pub fn is_word_char(c: char) -> bool {
     c.is_alphanumeric() || c == '_'
 }
 
-pub fn find_word_forward(line: &str, col: usize) -> usize {
+/// What word motions and `iw` take as part of a word: letters, digits and
+/// the characters set as `[editor] word_chars`, like vim's `iskeyword`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordChars {
+    extra: Vec<char>,
+}
+
+impl WordChars {
+    pub fn new(extra: &str) -> Self {
+        Self { extra: extra.chars().filter(|c| !c.is_whitespace()).collect() }
+    }
+
+    pub fn is_word_char(&self, c: char) -> bool {
+        c.is_alphanumeric() || self.extra.contains(&c)
+    }
+}
+
+impl Default for WordChars {
+    fn default() -> Self {
+        Self::new("_")
+    }
+}
+
+pub fn find_word_forward(line: &str, col: usize, word_chars: &WordChars) -> usize {
     let chars: Vec<char> = line.chars().collect();
     let len = chars.len();
 
@@ -133,18 +169,18 @@ pub fn find_word_forward(line: &str, col: usize) -> usize {
 
     let mut pos = col;
 
-    while pos < len && is_word_char(chars[pos]) {
+    while pos < len && word_chars.is_word_char(chars[pos]) {
         pos += 1;
     }
 
-    while pos < len && !is_word_char(chars[pos]) {
+    while pos < len && !word_chars.is_word_char(chars[pos]) {
         pos += 1;
     }
 
     pos
 }
 
-pub fn find_word_back(line: &str, col: usize) -> usize {
+pub fn find_word_back(line: &str, col: usize, word_chars: &WordChars) -> usize {
     let chars: Vec<char> = line.chars().collect();
 
     if col == 0 {
@@ -153,11 +189,11 @@ pub fn find_word_back(line: &str, col: usize) -> usize {
 
     let mut pos = col.min(chars.len()).saturating_sub(1);
 
-    while pos > 0 && !is_word_char(chars[pos]) {
+    while pos > 0 && !word_chars.is_word_char(chars[pos]) {
         pos -= 1;
     }
 
-    while pos > 0 && is_word_char(chars[pos - 1]) {
+    while pos > 0 && word_chars.is_word_char(chars[pos - 1]) {
         pos -= 1;
     }
 
@@ -170,16 +206,18 @@ mod tests {
 
     #[test]
     fn test_word_forward() {
-        assert_eq!(find_word_forward("hello world", 0), 6);
-        assert_eq!(find_word_forward("hello world", 5), 6);
-        assert_eq!(find_word_forward("hello world", 6), 11);
+        let words = WordChars::default();
+        assert_eq!(find_word_forward("hello world", 0, &words), 6);
+        assert_eq!(find_word_forward("hello world", 5, &words), 6);
+        assert_eq!(find_word_forward("hello world", 6, &words), 11);
     }
 
     #[test]
     fn test_word_back() {
-        assert_eq!(find_word_back("hello world", 11), 6);
-        assert_eq!(find_word_back("hello world", 6), 0);
-        assert_eq!(find_word_back("hello world", 5), 0);
+        let words = WordChars::default();
+        assert_eq!(find_word_back("hello world", 11, &words), 6);
+        assert_eq!(find_word_back("hello world", 6, &words), 0);
+        assert_eq!(find_word_back("hello world", 5, &words), 0);
     }
 
     #[test]
@@ -0,0 +1,225 @@
+//! Line-based diff between the saved note and the live buffer, used for the
+//! change markers drawn in the editor gutter.
+
+/// Above this many LCS cells the changed middle section is treated as one
+/// modified hunk instead of being diffed line by line.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineChange {
+    Added,
+    Modified,
+    /// Lines were removed just above this row.
+    DeletedAbove,
+    /// Lines were removed just below this row (only used for the last row).
+    DeletedBelow,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangeMarkers {
+    rows: Vec<Option<LineChange>>,
+    hunk_starts: Vec<usize>,
+}
+
+impl ChangeMarkers {
+    pub fn get(&self, row: usize) -> Option<LineChange> {
+        self.rows.get(row).copied().flatten()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hunk_starts.is_empty()
+    }
+
+    /// The markers of `len` rows from `start`, numbered from there.
+    pub fn window(&self, start: usize, len: usize) -> ChangeMarkers {
+        ChangeMarkers {
+            rows: (start..start + len).map(|row| self.get(row)).collect(),
+            hunk_starts: self
+                .hunk_starts
+                .iter()
+                .filter(|&&row| (start..start + len).contains(&row))
+                .map(|row| row - start)
+                .collect(),
+        }
+    }
+
+    /// First row of the next hunk strictly below `row`.
+    pub fn next_hunk(&self, row: usize) -> Option<usize> {
+        self.hunk_starts.iter().copied().find(|&start| start > row)
+    }
+
+    /// First row of the previous hunk strictly above `row`.
+    pub fn prev_hunk(&self, row: usize) -> Option<usize> {
+        self.hunk_starts.iter().rev().copied().find(|&start| start < row)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Diff `new` against `old`, returning per-row markers for `new`.
+pub fn diff_lines<O: AsRef<str>, N: AsRef<str>>(old: &[O], new: &[N]) -> ChangeMarkers {
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a.as_ref() == b.as_ref())
+        .count();
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let suffix = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a.as_ref() == b.as_ref())
+        .count();
+
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut ops = vec![Op::Equal; prefix];
+    ops.extend(diff_middle(old_mid, new_mid));
+    ops.extend(std::iter::repeat_n(Op::Equal, suffix));
+
+    markers_from_ops(&ops, new.len())
+}
+
+fn diff_middle<O: AsRef<str>, N: AsRef<str>>(old: &[O], new: &[N]) -> Vec<Op> {
+    let (n, m) = (old.len(), new.len());
+    if n == 0 || m == 0 || n.saturating_mul(m) > MAX_LCS_CELLS {
+        let mut ops = vec![Op::Delete; n];
+        ops.extend(std::iter::repeat_n(Op::Insert, m));
+        return ops;
+    }
+
+    // lcs[i][j] = LCS length of old[i..] and new[j..]
+    let width = m + 1;
+    let mut lcs = vec![0u32; (n + 1) * width];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i * width + j] = if old[i].as_ref() == new[j].as_ref() {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(n + m);
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i].as_ref() == new[j].as_ref() {
+            ops.push(Op::Equal);
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            ops.push(Op::Delete);
+            i += 1;
+        } else {
+            ops.push(Op::Insert);
+            j += 1;
+        }
+    }
+    ops.extend(std::iter::repeat_n(Op::Delete, n - i));
+    ops.extend(std::iter::repeat_n(Op::Insert, m - j));
+    ops
+}
+
+fn markers_from_ops(ops: &[Op], new_len: usize) -> ChangeMarkers {
+    let mut markers = ChangeMarkers {
+        rows: vec![None; new_len],
+        hunk_starts: Vec::new(),
+    };
+
+    let mut row = 0;
+    let mut idx = 0;
+    while idx < ops.len() {
+        if ops[idx] == Op::Equal {
+            row += 1;
+            idx += 1;
+            continue;
+        }
+
+        // Collect one run of deletes/inserts
+        let hunk_row = row;
+        let mut deleted = 0;
+        let mut inserted = 0;
+        while idx < ops.len() && ops[idx] != Op::Equal {
+            match ops[idx] {
+                Op::Delete => deleted += 1,
+                Op::Insert => inserted += 1,
+                Op::Equal => unreachable!(),
+            }
+            idx += 1;
+        }
+
+        if inserted > 0 {
+            for offset in 0..inserted {
+                markers.rows[row + offset] = Some(if offset < deleted {
+                    LineChange::Modified
+                } else {
+                    LineChange::Added
+                });
+            }
+            markers.hunk_starts.push(hunk_row);
+            row += inserted;
+        } else if new_len > 0 {
+            let (marker_row, change) = if row < new_len {
+                (row, LineChange::DeletedAbove)
+            } else {
+                (new_len - 1, LineChange::DeletedBelow)
+            };
+            if markers.rows[marker_row].is_none() {
+                markers.rows[marker_row] = Some(change);
+            }
+            if markers.hunk_starts.last() != Some(&marker_row) {
+                markers.hunk_starts.push(marker_row);
+            }
+        }
+    }
+
+    markers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_has_no_markers() {
+        let markers = diff_lines(&["a", "b"], &["a", "b"]);
+        assert!(markers.is_empty());
+        assert_eq!(markers.get(0), None);
+    }
+
+    #[test]
+    fn test_added_and_modified_lines() {
+        let markers = diff_lines(&["a", "b", "c"], &["a", "B", "new", "c"]);
+        assert_eq!(markers.get(0), None);
+        assert_eq!(markers.get(1), Some(LineChange::Modified));
+        assert_eq!(markers.get(2), Some(LineChange::Added));
+        assert_eq!(markers.get(3), None);
+    }
+
+    #[test]
+    fn test_deleted_lines_mark_neighbour() {
+        let markers = diff_lines(&["a", "b", "c"], &["a", "c"]);
+        assert_eq!(markers.get(1), Some(LineChange::DeletedAbove));
+
+        let markers = diff_lines(&["a", "b"], &["a"]);
+        assert_eq!(markers.get(0), Some(LineChange::DeletedBelow));
+    }
+
+    #[test]
+    fn test_hunk_navigation() {
+        let markers = diff_lines(&["a", "b", "c", "d", "e"], &["a", "X", "c", "d", "Y"]);
+        assert_eq!(markers.next_hunk(0), Some(1));
+        assert_eq!(markers.next_hunk(1), Some(4));
+        assert_eq!(markers.next_hunk(4), None);
+        assert_eq!(markers.prev_hunk(4), Some(1));
+        assert_eq!(markers.prev_hunk(1), None);
+    }
+}
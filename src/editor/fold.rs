@@ -0,0 +1,135 @@
+//! Fenced code blocks and the frontmatter folded away by `zc`, each drawn as
+//! one summary line in place of its rows. A fold is kept by its first row,
+//! moved along with edits like the other row-keyed caches, and checked
+//! against the buffer again after each edit, so it keeps to its block as the
+//! lines around it change and goes away once the block does.
+
+use std::collections::BTreeMap;
+
+use super::spliced_row;
+
+#[derive(Debug, Clone, Default)]
+pub struct FoldMap {
+    /// First row of each fold, to its last
+    folds: BTreeMap<usize, usize>,
+}
+
+impl FoldMap {
+    pub fn is_empty(&self) -> bool {
+        self.folds.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.folds.clear();
+    }
+
+    pub fn insert(&mut self, start: usize, end: usize) {
+        self.folds.insert(start, end);
+    }
+
+    /// Open the fold starting at `start`, returning whether there was one.
+    pub fn remove(&mut self, start: usize) -> bool {
+        self.folds.remove(&start).is_some()
+    }
+
+    /// First and last row of the fold `row` is in.
+    pub fn containing(&self, row: usize) -> Option<(usize, usize)> {
+        self.folds
+            .range(..=row)
+            .next_back()
+            .filter(|(_, &end)| end >= row)
+            .map(|(&start, &end)| (start, end))
+    }
+
+    /// Last row of the fold starting at `row`, which is drawn as its summary.
+    pub fn folded_at(&self, row: usize) -> Option<usize> {
+        self.folds.get(&row).copied()
+    }
+
+    /// Whether `row` is folded away under another row's summary.
+    pub fn hides(&self, row: usize) -> bool {
+        self.containing(row).is_some_and(|(start, _)| start != row)
+    }
+
+    /// Move the folds along when `removed` rows at `row` are replaced by
+    /// `inserted` ones. A fold whose first row went goes with it.
+    pub fn splice_rows(&mut self, row: usize, removed: usize, inserted: usize) {
+        let folds = std::mem::take(&mut self.folds);
+        self.folds = folds
+            .into_iter()
+            .filter_map(|(start, end)| {
+                let start = spliced_row(start, row, removed, inserted)?;
+                let end = spliced_row(end, row, removed, inserted).unwrap_or(row.saturating_sub(1));
+                Some((start, end.max(start)))
+            })
+            .collect();
+    }
+
+    /// Keep the folds still on the first row of one of `blocks`, reaching to
+    /// where that block ends now.
+    pub fn retain_blocks(&mut self, blocks: &[(usize, usize)]) {
+        let folds = std::mem::take(&mut self.folds);
+        self.folds = folds
+            .into_keys()
+            .filter_map(|start| blocks.iter().find(|(first, _)| *first == start).copied())
+            .collect();
+    }
+}
+
+/// First and last row of the frontmatter, when it ends at `frontmatter_end`,
+/// and of each closed fenced code block after it.
+pub fn foldable_blocks<'a>(lines: impl IntoIterator<Item = &'a str>, frontmatter_end: Option<usize>) -> Vec<(usize, usize)> {
+    let mut blocks: Vec<(usize, usize)> = frontmatter_end.map(|end| (0, end)).into_iter().collect();
+    let mut open = None;
+    for (row, line) in lines.into_iter().enumerate() {
+        if frontmatter_end.is_some_and(|end| row <= end) || !line.trim_start().starts_with("```") {
+            continue;
+        }
+        match open.take() {
+            Some(start) => blocks.push((start, row)),
+            None => open = Some(row),
+        }
+    }
+    blocks
+}
+
+/// The line a fold of `rows` rows starting with `first_line` is drawn as,
+/// e.g. "``` rust — 42 lines folded".
+pub fn summary(first_line: &str, rows: usize) -> String {
+    let first_line = first_line.trim();
+    let label = match first_line.strip_prefix("```") {
+        Some(lang) if !lang.trim().is_empty() => format!("``` {}", lang.trim()),
+        Some(_) => "```".to_string(),
+        None => format!("{} frontmatter", first_line),
+    };
+    format!("{} — {} lines folded", label, rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_folds_follow_their_blocks() {
+        let lines = ["---", "title: x", "---", "text", "```rust", "fn main() {}", "```", "```", "unclosed"];
+        let blocks = foldable_blocks(lines, Some(2));
+        assert_eq!(blocks, [(0, 2), (4, 6)]);
+        assert_eq!(summary(lines[4], 3), "``` rust — 3 lines folded");
+        assert_eq!(summary("---", 3), "--- frontmatter — 3 lines folded");
+
+        let mut folds = FoldMap::default();
+        folds.insert(4, 6);
+        assert!(folds.hides(5) && !folds.hides(4) && !folds.hides(7));
+        assert_eq!(folds.containing(6), Some((4, 6)));
+
+        // A line added above moves it, one in the block grows it
+        folds.splice_rows(3, 0, 1);
+        assert_eq!(folds.folded_at(5), Some(7));
+        folds.retain_blocks(&[(5, 8)]);
+        assert_eq!(folds.folded_at(5), Some(8));
+
+        // Gone with its opening fence
+        folds.splice_rows(5, 1, 0);
+        assert!(folds.is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+//! Grapheme clusters over char columns.
+//!
+//! Positions, highlight ranges, conceal ranges and the buffer all count
+//! `char`s. What the user sees as one character, like `👩‍💻`, a flag, `é`
+//! written as `e` and a combining accent, or a Hangul syllable in conjoining
+//! jamo, can be several of them, so the cursor is kept on the first char of
+//! a cluster, moves and deletes a whole cluster at a time, and the cluster's
+//! display width is given to its first char.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// What a char column takes up on screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CharCell {
+    /// Display width of the cluster this char starts, 0 inside a cluster
+    pub width: u16,
+    /// Chars in the cluster this char starts, 0 inside a cluster
+    pub len: usize,
+}
+
+impl CharCell {
+    /// Whether this char continues a cluster started before it.
+    pub fn is_continuation(self) -> bool {
+        self.len == 0
+    }
+}
+
+/// One [`CharCell`] per char of `line`.
+pub fn cells(line: &str, tab_width: u16) -> Vec<CharCell> {
+    let mut cells = Vec::with_capacity(line.len());
+    for cluster in line.graphemes(true) {
+        let len = cluster.chars().count();
+        let width = if cluster == "\t" { tab_width } else { cluster.width() as u16 };
+        cells.push(CharCell { width, len });
+        cells.extend(std::iter::repeat_n(CharCell::default(), len - 1));
+    }
+    cells
+}
+
+/// The chars of the cluster starting at char `col` of `chars`, per `cells`.
+pub fn cluster_at(chars: &[char], cells: &[CharCell], col: usize) -> String {
+    let len = cells.get(col).map_or(1, |cell| cell.len.max(1));
+    chars[col..(col + len).min(chars.len())].iter().collect()
+}
+
+/// The char column the cluster containing `col` starts at.
+pub fn floor_boundary(line: &str, col: usize) -> usize {
+    let mut start = 0;
+    for cluster in line.graphemes(true) {
+        let end = start + cluster.chars().count();
+        if end > col {
+            return start;
+        }
+        start = end;
+    }
+    col
+}
+
+/// The first cluster boundary after char `col`, which is the line length
+/// at the last cluster.
+pub fn next_boundary(line: &str, col: usize) -> usize {
+    let mut start = 0;
+    for cluster in line.graphemes(true) {
+        start += cluster.chars().count();
+        if start > col {
+            return start;
+        }
+    }
+    start.max(col)
+}
+
+/// The last cluster boundary before char `col`, or 0 at the first cluster.
+pub fn prev_boundary(line: &str, col: usize) -> usize {
+    let mut start = 0;
+    for cluster in line.graphemes(true) {
+        let end = start + cluster.chars().count();
+        if end >= col {
+            return start;
+        }
+        start = end;
+    }
+    start
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 👩‍💻 is three chars, 🇳🇴 two, e + U+0301 two, and 한 as conjoining
+    // jamo three
+    const LINE: &str = "a👩\u{200d}💻🇳🇴e\u{301}\u{1112}\u{1161}\u{11ab}b";
+
+    #[test]
+    fn test_boundaries() {
+        let starts = [0, 1, 4, 6, 8, 11];
+        for pair in starts.windows(2) {
+            assert_eq!(next_boundary(LINE, pair[0]), pair[1]);
+            assert_eq!(prev_boundary(LINE, pair[1]), pair[0]);
+        }
+        assert_eq!(next_boundary(LINE, 11), 12);
+        assert_eq!(next_boundary(LINE, 12), 12);
+        assert_eq!(prev_boundary(LINE, 12), 11);
+        assert_eq!(prev_boundary(LINE, 0), 0);
+
+        assert_eq!(floor_boundary(LINE, 2), 1);
+        assert_eq!(floor_boundary(LINE, 3), 1);
+        assert_eq!(floor_boundary(LINE, 7), 6);
+        assert_eq!(floor_boundary(LINE, 10), 8);
+        assert_eq!(floor_boundary(LINE, 12), 12);
+        // From inside a cluster, the boundaries either side of it
+        assert_eq!(next_boundary(LINE, 2), 4);
+        assert_eq!(prev_boundary(LINE, 2), 1);
+    }
+
+    #[test]
+    fn test_cells() {
+        let cells = cells(LINE, 4);
+        let widths: Vec<u16> = cells.iter().map(|cell| cell.width).collect();
+        assert_eq!(widths, [1, 2, 0, 0, 2, 0, 1, 0, 2, 0, 0, 1]);
+        assert!(cells[2].is_continuation());
+        assert!(!cells[1].is_continuation());
+
+        let chars: Vec<char> = LINE.chars().collect();
+        assert_eq!(cluster_at(&chars, &cells, 1), "👩\u{200d}💻");
+        assert_eq!(cluster_at(&chars, &cells, 6), "e\u{301}");
+
+        let tabbed: Vec<u16> = super::cells("\tx", 4).iter().map(|cell| cell.width).collect();
+        assert_eq!(tabbed, [4, 1]);
+    }
+}
@@ -80,12 +80,38 @@ impl EditOperation {
     }
 }
 
-fn calculate_end_position(start: Position, text: &str) -> Position {
-    let lines: Vec<&str> = text.lines().collect();
-    if lines.is_empty() {
-        return start;
+impl EditOperation {
+    /// The same edit `rows` further down the buffer.
+    fn shifted(self, rows: usize) -> EditOperation {
+        let down = |pos: Position| Position::new(pos.row + rows, pos.col);
+        match self {
+            EditOperation::Insert { pos, text } => EditOperation::Insert { pos: down(pos), text },
+            EditOperation::Delete { start, end, deleted_text } => {
+                EditOperation::Delete { start: down(start), end: down(end), deleted_text }
+            }
+            EditOperation::SplitLine { pos } => EditOperation::SplitLine { pos: down(pos) },
+            EditOperation::JoinLine { row, col } => EditOperation::JoinLine { row: row + rows, col },
+            EditOperation::BlockDelete { start_row, end_row, start_col, end_col, deleted_lines } => {
+                EditOperation::BlockDelete {
+                    start_row: start_row + rows,
+                    end_row: end_row + rows,
+                    start_col,
+                    end_col,
+                    deleted_lines,
+                }
+            }
+            EditOperation::BlockInsert { start_row, col, lines } => {
+                EditOperation::BlockInsert { start_row: start_row + rows, col, lines }
+            }
+            EditOperation::LineInsert { row, lines } => EditOperation::LineInsert { row: row + rows, lines },
+            EditOperation::LineDelete { row, lines } => EditOperation::LineDelete { row: row + rows, lines },
+        }
     }
+}
 
+fn calculate_end_position(start: Position, text: &str) -> Position {
+    // split rather than lines() so a trailing newline ends on the next row
+    let lines: Vec<&str> = text.split('\n').collect();
     if lines.len() == 1 {
         Position::new(start.row, start.col + text.chars().count())
     } else {
@@ -137,6 +163,15 @@ impl HistoryEntry {
         self.cursor_after = cursor_after;
         self.timestamp = Instant::now();
     }
+
+    fn shifted(self, rows: usize) -> Self {
+        Self {
+            operations: self.operations.into_iter().map(|op| op.shifted(rows)).collect(),
+            cursor_before: Position::new(self.cursor_before.row + rows, self.cursor_before.col),
+            cursor_after: Position::new(self.cursor_after.row + rows, self.cursor_after.col),
+            timestamp: self.timestamp,
+        }
+    }
 }
 
 pub struct History {
@@ -182,6 +217,24 @@ impl History {
         }
     }
 
+    /// Record several operations as a single undo step.
+    pub fn record_group(&mut self, ops: Vec<EditOperation>, cursor_before: Position, cursor_after: Position) {
+        if ops.is_empty() {
+            return;
+        }
+        self.redo_stack.clear();
+        self.undo_stack.push_back(HistoryEntry {
+            operations: ops,
+            cursor_before,
+            cursor_after,
+            timestamp: Instant::now(),
+        });
+
+        while self.undo_stack.len() > self.max_entries {
+            self.undo_stack.pop_front();
+        }
+    }
+
     pub fn pop_undo(&mut self) -> Option<HistoryEntry> {
         if let Some(entry) = self.undo_stack.pop_back() {
             self.redo_stack.push(entry.clone());
@@ -199,6 +252,20 @@ impl History {
             None
         }
     }
+
+    /// Carry on from `narrowed`, the history of a section of this buffer
+    /// starting `offset` rows down: its edits go after this history's own,
+    /// moved to their rows in the whole buffer.
+    pub fn absorb_narrowed(&mut self, narrowed: History, offset: usize) {
+        if narrowed.undo_stack.is_empty() && narrowed.redo_stack.is_empty() {
+            return;
+        }
+        self.undo_stack.extend(narrowed.undo_stack.into_iter().map(|entry| entry.shifted(offset)));
+        self.redo_stack = narrowed.redo_stack.into_iter().map(|entry| entry.shifted(offset)).collect();
+        while self.undo_stack.len() > self.max_entries {
+            self.undo_stack.pop_front();
+        }
+    }
 }
 
 #[cfg(test)]
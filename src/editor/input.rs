@@ -6,13 +6,27 @@ pub enum InputAction {
     InsertNewline,
     DeleteChar,
     DeleteCharBefore,
+    DeleteWordBefore,
+    DeleteWordAfter,
     Move(super::cursor::CursorMove),
+    /// Move while extending the selection, starting one if there is none
+    Select(super::cursor::CursorMove),
     None,
 }
 
 pub fn process_key(key: KeyEvent) -> InputAction {
     use super::cursor::CursorMove;
 
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    // Shift turns any movement into selecting
+    let movement = |movement: CursorMove| {
+        if key.modifiers.contains(KeyModifiers::SHIFT) {
+            InputAction::Select(movement)
+        } else {
+            InputAction::Move(movement)
+        }
+    };
+
     match key.code {
         KeyCode::Char(c) => {
             if key.modifiers.contains(KeyModifiers::CONTROL) {
@@ -22,14 +36,18 @@ pub fn process_key(key: KeyEvent) -> InputAction {
             }
         }
         KeyCode::Enter => InputAction::InsertNewline,
+        KeyCode::Backspace if ctrl => InputAction::DeleteWordBefore,
         KeyCode::Backspace => InputAction::DeleteCharBefore,
+        KeyCode::Delete if ctrl => InputAction::DeleteWordAfter,
         KeyCode::Delete => InputAction::DeleteChar,
-        KeyCode::Left => InputAction::Move(CursorMove::Back),
-        KeyCode::Right => InputAction::Move(CursorMove::Forward),
-        KeyCode::Up => InputAction::Move(CursorMove::Up),
-        KeyCode::Down => InputAction::Move(CursorMove::Down),
-        KeyCode::Home => InputAction::Move(CursorMove::Head),
-        KeyCode::End => InputAction::Move(CursorMove::End),
+        KeyCode::Left if ctrl => movement(CursorMove::WordBack),
+        KeyCode::Right if ctrl => movement(CursorMove::WordForward),
+        KeyCode::Left => movement(CursorMove::Back),
+        KeyCode::Right => movement(CursorMove::Forward),
+        KeyCode::Up => movement(CursorMove::Up),
+        KeyCode::Down => movement(CursorMove::Down),
+        KeyCode::Home => movement(CursorMove::Head),
+        KeyCode::End => movement(CursorMove::End),
         KeyCode::Tab => InputAction::InsertChar('\t'),
         _ => InputAction::None,
     }
@@ -0,0 +1,194 @@
+//! A single line of text typed into a dialog or search box, with a cursor
+//! that moves by grapheme cluster and the usual readline keys.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::ops::Deref;
+
+use super::grapheme::{next_boundary, prev_boundary};
+
+/// What a key did to a [`LineInput`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineEdit {
+    Changed,
+    Moved,
+    Ignored,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LineInput {
+    text: String,
+    /// Char column of the cursor
+    cursor: usize,
+}
+
+impl Deref for LineInput {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl LineInput {
+    pub fn new(text: impl Into<String>) -> Self {
+        let mut input = Self::default();
+        input.set(text);
+        input
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Replaces the text, with the cursor at its end.
+    pub fn set(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.cursor = self.text.chars().count();
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    /// The text, leaving the input empty.
+    pub fn take(&mut self) -> String {
+        self.cursor = 0;
+        std::mem::take(&mut self.text)
+    }
+
+    fn byte_at(&self, col: usize) -> usize {
+        self.text.char_indices().nth(col).map_or(self.text.len(), |(i, _)| i)
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let at = self.byte_at(self.cursor);
+        self.text.insert(at, c);
+        self.cursor += 1;
+    }
+
+    pub fn insert_str(&mut self, s: &str) {
+        let at = self.byte_at(self.cursor);
+        self.text.insert_str(at, s);
+        self.cursor += s.chars().count();
+    }
+
+    fn delete(&mut self, start: usize, end: usize) -> LineEdit {
+        if start == end {
+            return LineEdit::Ignored;
+        }
+        let (from, to) = (self.byte_at(start), self.byte_at(end));
+        self.text.replace_range(from..to, "");
+        self.cursor = start;
+        LineEdit::Changed
+    }
+
+    fn move_to(&mut self, col: usize) -> LineEdit {
+        if col == self.cursor {
+            return LineEdit::Ignored;
+        }
+        self.cursor = col;
+        LineEdit::Moved
+    }
+
+    /// The start of the word before the cursor, past any spaces before it.
+    fn word_start(&self) -> usize {
+        let chars: Vec<char> = self.text.chars().take(self.cursor).collect();
+        let mut col = chars.len();
+        while col > 0 && chars[col - 1].is_whitespace() {
+            col -= 1;
+        }
+        while col > 0 && !chars[col - 1].is_whitespace() {
+            col -= 1;
+        }
+        col
+    }
+
+    /// Applies an editing or movement key: Left/Right, Home/End (or Ctrl+A/E),
+    /// Backspace/Delete, Ctrl+U/K to delete to the start/end and Ctrl+W the
+    /// word before the cursor. Any other char without Ctrl or Alt is typed.
+    pub fn handle_key(&mut self, key: KeyEvent) -> LineEdit {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let len = self.text.chars().count();
+        match key.code {
+            KeyCode::Char('a') if ctrl => self.move_to(0),
+            KeyCode::Char('e') if ctrl => self.move_to(len),
+            KeyCode::Char('u') if ctrl => self.delete(0, self.cursor),
+            KeyCode::Char('k') if ctrl => {
+                let cursor = self.cursor;
+                self.delete(cursor, len)
+            }
+            KeyCode::Char('w') if ctrl => self.delete(self.word_start(), self.cursor),
+            KeyCode::Char(c) if !ctrl && !key.modifiers.contains(KeyModifiers::ALT) => {
+                self.insert(c);
+                LineEdit::Changed
+            }
+            KeyCode::Backspace if ctrl => self.delete(self.word_start(), self.cursor),
+            KeyCode::Backspace => self.delete(prev_boundary(&self.text, self.cursor), self.cursor),
+            KeyCode::Delete => {
+                let cursor = self.cursor;
+                self.delete(cursor, next_boundary(&self.text, cursor).min(len))
+            }
+            KeyCode::Left => self.move_to(prev_boundary(&self.text, self.cursor)),
+            KeyCode::Right => self.move_to(next_boundary(&self.text, self.cursor).min(len)),
+            KeyCode::Home => self.move_to(0),
+            KeyCode::End => self.move_to(len),
+            _ => LineEdit::Ignored,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(input: &mut LineInput, code: KeyCode, modifiers: KeyModifiers) -> LineEdit {
+        input.handle_key(KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn test_editing_mid_line() {
+        let mut input = LineInput::new("Meting notes");
+        let none = KeyModifiers::NONE;
+        press(&mut input, KeyCode::Home, none);
+        for _ in 0..2 {
+            press(&mut input, KeyCode::Right, none);
+        }
+        assert_eq!(press(&mut input, KeyCode::Char('e'), none), LineEdit::Changed);
+        assert_eq!(input.as_str(), "Meeting notes");
+        assert_eq!(input.cursor(), 3);
+
+        press(&mut input, KeyCode::End, none);
+        assert_eq!(press(&mut input, KeyCode::Delete, none), LineEdit::Ignored);
+        press(&mut input, KeyCode::Char('w'), KeyModifiers::CONTROL);
+        assert_eq!(input.as_str(), "Meeting ");
+        press(&mut input, KeyCode::Char('a'), KeyModifiers::CONTROL);
+        press(&mut input, KeyCode::Delete, none);
+        assert_eq!(input.as_str(), "eeting ");
+        press(&mut input, KeyCode::Right, none);
+        press(&mut input, KeyCode::Char('k'), KeyModifiers::CONTROL);
+        assert_eq!(input.as_str(), "e");
+        press(&mut input, KeyCode::Char('u'), KeyModifiers::CONTROL);
+        assert_eq!((input.as_str(), input.cursor()), ("", 0));
+    }
+
+    #[test]
+    fn test_moves_by_cluster() {
+        // 👩‍💻 is three chars drawn as one
+        let mut input = LineInput::new("a👩‍💻b");
+        let none = KeyModifiers::NONE;
+        press(&mut input, KeyCode::Left, none);
+        assert_eq!(input.cursor(), 4);
+        press(&mut input, KeyCode::Left, none);
+        assert_eq!(input.cursor(), 1);
+        press(&mut input, KeyCode::Delete, none);
+        assert_eq!(input.as_str(), "ab");
+        assert_eq!(press(&mut input, KeyCode::Char('x'), KeyModifiers::ALT), LineEdit::Ignored);
+        assert_eq!(input.take(), "ab");
+        assert!(input.is_empty());
+    }
+}
@@ -1,18 +1,31 @@
 mod buffer;
+mod completion;
+mod conceal;
 mod cursor;
+mod diff;
+mod fold;
+mod grapheme;
 mod history;
 mod input;
+mod line_input;
+mod strikethrough;
 mod wrap;
+mod yank_ring;
 
-pub use cursor::{CursorMove, Position};
+pub use cursor::{CursorMove, Position, WordChars};
+pub use diff::{ChangeMarkers, LineChange};
 pub use input::{process_key, InputAction};
+pub use line_input::{LineEdit, LineInput};
+pub use strikethrough::strikethrough_at;
+pub use yank_ring::{preview as yank_preview, YankRing, DEFAULT_SIZE as YANK_RING_SIZE};
 // HighlightRange and HighlightType are defined in this module and automatically public
 
 // Re-export LineNumberMode for use in other modules
 pub use crate::config::LineNumberMode;
 
 use buffer::TextBuffer;
-use cursor::Cursor;
+use cursor::{Cursor, PreferredCol};
+use fold::FoldMap;
 use history::{EditOperation, History};
 use wrap::WrapCache;
 
@@ -25,16 +38,14 @@ use ratatui::{
 };
 use std::cell::RefCell;
 use std::collections::{BTreeMap, HashSet};
-use unicode_width::UnicodeWidthChar;
+use std::ops::Range;
 
-#[inline]
-fn char_display_width(ch: char, tab_width: u16) -> u16 {
-    if ch == '\t' {
-        tab_width
-    } else {
-        ch.width().unwrap_or(1) as u16
-    }
-}
+use crate::app::WikiLink;
+use crate::config::{CalloutTheme, MarkdownTheme};
+
+/// Screen rows the cursor may drift from the middle in typewriter mode
+/// before the view follows it.
+const TYPEWRITER_BAND: usize = 2;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum HighlightType {
@@ -42,6 +53,7 @@ pub enum HighlightType {
     Header,
     Bold,
     Italic,
+    Strikethrough,
     InlineCode,
     CodeBlock,
     Link,
@@ -52,6 +64,9 @@ pub enum HighlightType {
     SearchMatchCurrent,
     Frontmatter,
     Details,
+    /// A markdown lint warning, drawn over the other highlights rather than
+    /// in place of them. Priority 0 is a prose hint, left out of the gutter
+    Diagnostic,
     Custom(u8),
 }
 
@@ -143,36 +158,14 @@ impl HighlightIndex {
         self.by_row.retain(|_, v| !v.is_empty());
     }
 
-    fn shift_rows_after(&mut self, row: usize, delta: isize) {
-        if delta == 0 {
-            return;
-        }
-
-        // Collect rows that need to be shifted
-        let rows_to_shift: Vec<usize> = if delta > 0 {
-            self.by_row.range(row..).map(|(r, _)| *r).collect()
-        } else {
-            self.by_row.range(row..).map(|(r, _)| *r).collect()
-        };
-
-        // Remove and re-insert with new row numbers
-        let mut shifted: Vec<(usize, Vec<HighlightRange>)> = Vec::new();
-        for old_row in rows_to_shift {
-            if let Some(mut highlights) = self.by_row.remove(&old_row) {
-                let new_row = if delta > 0 {
-                    old_row + delta as usize
-                } else {
-                    old_row.saturating_sub((-delta) as usize)
-                };
-                for h in &mut highlights {
-                    h.row = new_row;
-                }
-                shifted.push((new_row, highlights));
+    /// Follow `removed` rows at `row` being replaced by `inserted` ones.
+    fn splice_rows(&mut self, row: usize, removed: usize, inserted: usize) {
+        splice_row_keys(&mut self.by_row, row, removed, inserted);
+        for (&row, highlights) in self.by_row.range_mut(row..) {
+            for highlight in highlights {
+                highlight.row = row;
             }
         }
-        for (new_row, highlights) in shifted {
-            self.by_row.insert(new_row, highlights);
-        }
     }
 
     fn iter(&self) -> impl Iterator<Item = &HighlightRange> {
@@ -242,50 +235,35 @@ impl RowStyleCache {
         self.dirty_rows.clear();
     }
 
-    fn shift_rows_after(&mut self, row: usize, delta: isize) {
-        if delta == 0 {
-            return;
-        }
-
-        let rows_to_shift: Vec<usize> = if delta > 0 {
-            self.rows.range(row..).map(|(r, _)| *r).collect()
-        } else {
-            self.rows.range(row..).map(|(r, _)| *r).collect()
-        };
-
-        let mut shifted: Vec<(usize, Vec<Style>)> = Vec::new();
-        for old_row in rows_to_shift {
-            if let Some(styles) = self.rows.remove(&old_row) {
-                let new_row = if delta > 0 {
-                    old_row + delta as usize
-                } else {
-                    old_row.saturating_sub((-delta) as usize)
-                };
-                shifted.push((new_row, styles));
-            }
-        }
-        for (new_row, styles) in shifted {
-            self.rows.insert(new_row, styles);
-        }
-
-        let dirty_to_shift: Vec<usize> = self
+    /// Follow `removed` rows at `row` being replaced by `inserted` ones.
+    fn splice_rows(&mut self, row: usize, removed: usize, inserted: usize) {
+        splice_row_keys(&mut self.rows, row, removed, inserted);
+        self.dirty_rows = self
             .dirty_rows
-            .iter()
-            .filter(|&&r| r >= row)
-            .cloned()
+            .drain()
+            .filter_map(|r| spliced_row(r, row, removed, inserted))
             .collect();
-        for old_row in dirty_to_shift {
-            self.dirty_rows.remove(&old_row);
-            let new_row = if delta > 0 {
-                old_row + delta as usize
-            } else {
-                old_row.saturating_sub((-delta) as usize)
-            };
-            self.dirty_rows.insert(new_row);
-        }
     }
 }
 
+/// Where `r` ends up after `removed` rows at `row` are replaced by
+/// `inserted` ones, or `None` if it was one of those removed.
+fn spliced_row(r: usize, row: usize, removed: usize, inserted: usize) -> Option<usize> {
+    if r < row {
+        Some(r)
+    } else if r < row + removed {
+        None
+    } else {
+        Some(r - removed + inserted)
+    }
+}
+
+/// Re-key a row-keyed map for [`spliced_row`].
+fn splice_row_keys<V>(map: &mut BTreeMap<usize, V>, row: usize, removed: usize, inserted: usize) {
+    let moved = map.split_off(&row);
+    map.extend(moved.into_iter().filter_map(|(r, v)| Some((spliced_row(r, row, removed, inserted)?, v))));
+}
+
 #[derive(Debug, Clone)]
 pub struct WikiLinkRange {
     pub row: usize,
@@ -370,16 +348,36 @@ pub enum CursorShape {
     Underline,
 }
 
+/// Where a bulk insertion started and the selection it replaced, see
+/// [`Editor::begin_bulk_insert`].
+#[derive(Debug)]
+pub struct BulkInsert {
+    cursor_before: Position,
+    pos: Position,
+    deleted: Option<(Position, Position, String)>,
+}
+
+/// The last `p` / `P`, which [`Editor::cycle_paste`] can swap for another
+/// yank ring entry while the cursor is still where it left it.
+#[derive(Debug, Clone, Copy)]
+struct LastPaste {
+    index: usize,
+    before: bool,
+    at: Position,
+    cursor: Position,
+}
+
 pub struct Editor {
     buffer: TextBuffer,
     cursor: Cursor,
     history: History,
     wrap_cache: WrapCache,
+    // Code blocks and frontmatter closed with `zc`
+    folds: FoldMap,
     scroll_offset: usize,
     h_scroll_offset: usize,
     view_height: usize,
     view_width: usize,
-    preferred_visual_x: Option<usize>,
     line_wrap_enabled: bool,
     tab_width: u16,
     left_padding: u16,
@@ -389,6 +387,8 @@ pub struct Editor {
     selection_style: Style,
     clipboard: Option<String>,
     clipboard_linewise: bool,
+    yank_ring: YankRing,
+    last_paste: Option<LastPaste>,
     highlight_index: HighlightIndex,
     row_style_cache: RefCell<RowStyleCache>,
     code_block_rows: HashSet<usize>,
@@ -399,24 +399,64 @@ pub struct Editor {
     wiki_link_invalid_style: Style,
     visual_line_selection: Option<(usize, usize)>,
     visual_block_selection: Option<(Position, Position)>,
+    /// Set by `$` in visual block mode: each row of the block runs to its
+    /// own end of line instead of a fixed column
+    block_to_eol: bool,
     inclusive_selection: bool,
     // Markdown highlighting colors
     heading_colors: [Color; 6],
     code_color: Color,
+    code_block_color: Color,
     link_color: Color,
     blockquote_color: Color,
+    callout_colors: CalloutTheme,
     list_marker_color: Color,
+    // Indent guides, and list bullets colored by depth from these
+    indent_guides: bool,
+    indent_guide_style: Style,
+    list_marker_colors: [Color; 3],
     bold_color: Option<Color>,
     italic_color: Option<Color>,
+    strikethrough_modifier: Modifier,
     frontmatter_color: Color,
     // Line number display
     line_number_mode: LineNumberMode,
     line_number_style: Style,
     line_number_width: u16,
+    // Change markers against the saved note, shown in a one-column gutter
+    show_change_markers: bool,
+    change_baseline: Vec<String>,
+    change_markers: ChangeMarkers,
+    change_added_style: Style,
+    change_modified_style: Style,
+    change_deleted_style: Style,
+    // The rest of the buffer while `:narrow` shows one section of it
+    narrowing: Option<Narrowing>,
+    // Lint warnings, marked in the same gutter column as the change markers
+    show_diagnostics: bool,
+    diagnostic_marker_style: Style,
     // scrolloff, minimum lines above/below cursor
     scrolloff: usize,
+    // Let the last line scroll up to the top of the view
+    scroll_past_end: bool,
+    // Keep the cursor line in the middle of the view
+    typewriter: bool,
+    // Where the cursor was when a mouse scroll or zt/zb took over from
+    // typewriter mode, which resumes once it moves
+    typewriter_paused_at: Option<(usize, usize)>,
     // Cursor shape for visual mode feedback
     cursor_shape: CursorShape,
+    // Hide link syntax on lines other than the cursor line
+    conceal_enabled: bool,
+    // What word motions count as part of a word
+    word_chars: WordChars,
+}
+
+/// The rows outside a narrowed section, and the undo history from before.
+struct Narrowing {
+    above: Vec<String>,
+    below: Vec<String>,
+    history: History,
 }
 
 impl Default for Editor {
@@ -432,11 +472,11 @@ impl Editor {
             cursor: Cursor::new(),
             history: History::new(),
             wrap_cache: WrapCache::new(),
+            folds: FoldMap::default(),
             scroll_offset: 0,
             h_scroll_offset: 0,
             view_height: 0,
             view_width: 0,
-            preferred_visual_x: None,
             line_wrap_enabled: true,
             tab_width: 4,
             left_padding: 0,
@@ -446,6 +486,8 @@ impl Editor {
             selection_style: Style::default().bg(ratatui::style::Color::DarkGray),
             clipboard: None,
             clipboard_linewise: false,
+            yank_ring: YankRing::default(),
+            last_paste: None,
             highlight_index: HighlightIndex::new(),
             row_style_cache: RefCell::new(RowStyleCache::new()),
             code_block_rows: HashSet::new(),
@@ -455,6 +497,7 @@ impl Editor {
             wiki_link_invalid_style: Style::default().fg(Color::Red),
             visual_line_selection: None,
             visual_block_selection: None,
+            block_to_eol: false,
             inclusive_selection: false,
             heading_colors: [
                 Color::Blue,
@@ -465,17 +508,37 @@ impl Editor {
                 Color::Gray,
             ],
             code_color: Color::Green,
+            code_block_color: Color::Green,
             link_color: Color::Cyan,
             blockquote_color: Color::Cyan,
+            callout_colors: CalloutTheme::default(),
             list_marker_color: Color::Yellow,
+            indent_guides: false,
+            indent_guide_style: Style::default().fg(Color::DarkGray),
+            list_marker_colors: [Color::Yellow, Color::Cyan, Color::Green],
             bold_color: None,
             italic_color: None,
+            strikethrough_modifier: Modifier::CROSSED_OUT,
             frontmatter_color: Color::DarkGray,
             line_number_mode: LineNumberMode::Absolute,
             line_number_style: Style::default().fg(Color::DarkGray),
             line_number_width: 4, // Default width for line numbers
+            show_change_markers: false,
+            change_baseline: Vec::new(),
+            change_markers: ChangeMarkers::default(),
+            narrowing: None,
+            change_added_style: Style::default().fg(Color::Green),
+            change_modified_style: Style::default().fg(Color::Yellow),
+            change_deleted_style: Style::default().fg(Color::Red),
+            show_diagnostics: false,
+            diagnostic_marker_style: Style::default().fg(Color::Yellow),
             scrolloff: 0,
+            scroll_past_end: false,
+            typewriter: false,
+            typewriter_paused_at: None,
             cursor_shape: CursorShape::Block,
+            conceal_enabled: false,
+            word_chars: WordChars::default(),
         }
     }
 
@@ -489,18 +552,236 @@ impl Editor {
         if self.line_number_mode == LineNumberMode::None {
             self.line_number_width = 0;
         } else {
-            let line_count = self.buffer.line_count();
+            let line_count = self.buffer.line_count() + self.narrowed_offset().unwrap_or(0);
             self.line_number_width = (line_count.to_string().len() as u16).max(2) + 1;
             // +1 for spacing
         }
     }
 
+    pub fn set_conceal(&mut self, enabled: bool) {
+        self.conceal_enabled = enabled;
+    }
+
+    pub fn set_word_chars(&mut self, extra: &str) {
+        self.word_chars = WordChars::new(extra);
+    }
+
+    pub fn word_chars(&self) -> &WordChars {
+        &self.word_chars
+    }
+
+    /// Hidden columns of `row` in conceal mode (empty when none are). The
+    /// cursor line, code blocks and frontmatter are always shown in full.
+    fn concealed_columns(&self, row: usize) -> Vec<bool> {
+        if !self.conceal_enabled
+            || row == self.cursor.pos().row
+            || self.code_block_rows.contains(&row)
+            || self.frontmatter_end.is_some_and(|end| row <= end)
+        {
+            return Vec::new();
+        }
+        conceal::concealed_columns(self.buffer.line(row).unwrap_or(""))
+    }
+
+    pub fn set_show_change_markers(&mut self, show: bool) {
+        self.show_change_markers = show;
+    }
+
+    pub fn set_change_marker_colors(&mut self, added: Color, modified: Color, deleted: Color) {
+        self.change_added_style = Style::default().fg(added);
+        self.change_modified_style = Style::default().fg(modified);
+        self.change_deleted_style = Style::default().fg(deleted);
+    }
+
+    /// Use the current buffer as the saved state that change markers compare against.
+    pub fn reset_change_baseline(&mut self) {
+        self.change_baseline = self.full_lines();
+        self.change_markers = ChangeMarkers::default();
+    }
+
+    /// Re-diff the buffer against the baseline. Callers debounce this; it is not
+    /// meant to run on every keystroke.
+    pub fn refresh_change_markers(&mut self) {
+        self.change_markers = match &self.narrowing {
+            Some(narrowing) => diff::diff_lines(&self.change_baseline, &self.full_lines())
+                .window(narrowing.above.len(), self.buffer.line_count()),
+            None => diff::diff_lines(&self.change_baseline, &self.buffer.lines()),
+        };
+    }
+
+    // ==================== Narrowing ====================
+
+    /// Show and edit only `rows` of the buffer, for `:narrow`. The other
+    /// rows are set aside until [`Editor::widen`], out of reach of edits and
+    /// of undo. False when already narrowed or `rows` holds no lines.
+    pub fn narrow(&mut self, rows: Range<usize>) -> bool {
+        let end = rows.end.min(self.buffer.line_count());
+        if self.narrowing.is_some() || rows.start >= end {
+            return false;
+        }
+        let cursor = self.cursor.pos();
+        let mut above: Vec<String> = self.buffer.lines().iter().map(|l| l.to_string()).collect();
+        let below = above.split_off(end);
+        let section = above.split_off(rows.start);
+        let history = std::mem::take(&mut self.history);
+        self.narrowing = Some(Narrowing { above, below, history });
+        self.swap_buffer(section);
+
+        if (rows.start..end).contains(&cursor.row) {
+            self.set_cursor(cursor.row - rows.start, cursor.col);
+        }
+        true
+    }
+
+    /// Put back the rows [`Editor::narrow`] set aside, around the section as
+    /// it's been edited. Undo goes on through the edits made while narrowed
+    /// to those made before. False when not narrowed.
+    pub fn widen(&mut self) -> bool {
+        let lines = self.full_lines();
+        let Some(narrowing) = self.narrowing.take() else {
+            return false;
+        };
+        let offset = narrowing.above.len();
+        let cursor = self.cursor.pos();
+        let narrowed = std::mem::replace(&mut self.history, narrowing.history);
+        self.history.absorb_narrowed(narrowed, offset);
+        self.swap_buffer(lines);
+        self.set_cursor(cursor.row + offset, cursor.col);
+        true
+    }
+
+    /// The row of the whole buffer the narrowed section starts at, while
+    /// narrowed.
+    pub fn narrowed_offset(&self) -> Option<usize> {
+        self.narrowing.as_ref().map(|narrowing| narrowing.above.len())
+    }
+
+    /// Every line of the buffer, with those set aside while narrowed.
+    pub fn full_lines(&self) -> Vec<String> {
+        let section = self.buffer.lines().into_iter().map(str::to_string);
+        match &self.narrowing {
+            Some(narrowing) => narrowing.above.iter().cloned().chain(section).chain(narrowing.below.iter().cloned()).collect(),
+            None => section.collect(),
+        }
+    }
+
+    /// Replace the buffer outright, dropping everything worked out from the
+    /// old one. Highlights are redone by the caller.
+    fn swap_buffer(&mut self, lines: Vec<String>) {
+        self.buffer = TextBuffer::from_lines(lines);
+        self.wrap_cache = WrapCache::new();
+        self.folds.clear();
+        self.clear_highlights();
+        self.code_block_rows.clear();
+        self.wiki_link_ranges.clear();
+        self.visual_line_selection = None;
+        self.visual_block_selection = None;
+        self.cursor.cancel_selection();
+        self.cursor.move_to(0, 0);
+        self.scroll_offset = 0;
+        self.h_scroll_offset = 0;
+        self.update_line_number_width();
+        self.refresh_change_markers();
+    }
+
+    pub fn change_markers(&self) -> &ChangeMarkers {
+        &self.change_markers
+    }
+
+    /// Move the cursor to the first line of the next (or previous) change hunk.
+    pub fn goto_change_hunk(&mut self, forward: bool) -> bool {
+        let row = self.cursor.pos().row;
+        let target = if forward {
+            self.change_markers.next_hunk(row)
+        } else {
+            self.change_markers.prev_hunk(row)
+        };
+        match target {
+            Some(target) => {
+                self.set_cursor(target.min(self.buffer.line_count().saturating_sub(1)), 0);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn set_show_diagnostics(&mut self, show: bool) {
+        self.show_diagnostics = show;
+    }
+
+    pub fn set_diagnostic_marker_color(&mut self, color: Color) {
+        self.diagnostic_marker_style = Style::default().fg(color);
+    }
+
+    /// Replace the lint warnings with `diagnostics`.
+    pub fn set_diagnostics(&mut self, diagnostics: impl IntoIterator<Item = HighlightRange>) {
+        self.clear_highlights_of_type(HighlightType::Diagnostic);
+        self.add_highlights(diagnostics);
+    }
+
+    fn has_diagnostic(&self, row: usize) -> bool {
+        self.highlight_index
+            .get_row(row)
+            .iter()
+            .any(|h| h.highlight_type == HighlightType::Diagnostic && h.priority > 0)
+    }
+
+    /// Move the cursor to the start of the next (or previous) lint warning.
+    pub fn goto_diagnostic(&mut self, forward: bool) -> bool {
+        let pos = self.cursor.pos();
+        let starts = self
+            .highlight_index
+            .by_row
+            .values()
+            .flatten()
+            .filter(|h| h.highlight_type == HighlightType::Diagnostic)
+            .map(|h| (h.row, h.start_col));
+        let target = if forward {
+            starts.filter(|&start| start > (pos.row, pos.col)).min()
+        } else {
+            starts.filter(|&start| start < (pos.row, pos.col)).max()
+        };
+        match target {
+            Some((row, col)) => {
+                self.set_cursor(row, col);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn change_marker_width(&self) -> u16 {
+        if self.show_change_markers || self.show_diagnostics {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn render_change_marker(&self, buf: &mut RatatuiBuffer, x: u16, y: u16, row: usize) {
+        let change = self.change_markers.get(row).filter(|_| self.show_change_markers);
+        let (ch, style) = match change {
+            _ if self.show_diagnostics && self.has_diagnostic(row) => ('•', self.diagnostic_marker_style),
+            Some(LineChange::Added) => ('▎', self.change_added_style),
+            Some(LineChange::Modified) => ('▎', self.change_modified_style),
+            Some(LineChange::DeletedAbove) => ('▔', self.change_deleted_style),
+            Some(LineChange::DeletedBelow) => ('▁', self.change_deleted_style),
+            None => return,
+        };
+        if let Some(cell) = buf.cell_mut((x, y)) {
+            cell.set_char(ch);
+            cell.set_style(style);
+        }
+    }
+
     fn get_line_number_str(&self, row: usize, cursor_row: usize) -> Option<String> {
+        // Narrowed, a line keeps its number in the whole note
+        let offset = self.narrowed_offset().unwrap_or(0);
         match self.line_number_mode {
             LineNumberMode::None => None,
             LineNumberMode::Absolute => Some(format!(
                 "{:>width$}",
-                row + 1,
+                offset + row + 1,
                 width = (self.line_number_width - 1) as usize
             )),
             LineNumberMode::Relative => {
@@ -515,7 +796,7 @@ impl Editor {
                 if row == cursor_row {
                     Some(format!(
                         "{:>width$}",
-                        row + 1,
+                        offset + row + 1,
                         width = (self.line_number_width - 1) as usize
                     ))
                 } else {
@@ -560,6 +841,26 @@ impl Editor {
         self.scrolloff = scrolloff;
     }
 
+    pub fn set_scroll_past_end(&mut self, enabled: bool) {
+        self.scroll_past_end = enabled;
+    }
+
+    pub fn set_typewriter(&mut self, enabled: bool) {
+        self.typewriter = enabled;
+        self.typewriter_paused_at = None;
+    }
+
+    fn typewriter_active(&self) -> bool {
+        self.typewriter && self.typewriter_paused_at.is_none()
+    }
+
+    /// Leaves the view where a mouse scroll put it until the cursor moves.
+    pub fn pause_typewriter(&mut self) {
+        if self.typewriter {
+            self.typewriter_paused_at = Some(self.cursor());
+        }
+    }
+
     pub fn line_wrap_enabled(&self) -> bool {
         self.line_wrap_enabled
     }
@@ -595,6 +896,28 @@ impl Editor {
 
     pub fn clear_visual_block_selection(&mut self) {
         self.visual_block_selection = None;
+        self.block_to_eol = false;
+    }
+
+    pub fn set_block_to_eol(&mut self, to_eol: bool) {
+        self.block_to_eol = to_eol;
+    }
+
+    /// The visual block's top-left and bottom-right corners. A ragged-right
+    /// block's right edge is the end of its longest line, which takes every
+    /// row to its own end.
+    pub fn visual_block_rect(&self) -> Option<(Position, Position)> {
+        let (anchor, current) = self.visual_block_selection?;
+        let (start_row, end_row) = (anchor.row.min(current.row), anchor.row.max(current.row));
+        let end_col = if self.block_to_eol {
+            (start_row..=end_row).map(|row| self.buffer.line_len(row)).max().unwrap_or(0)
+        } else {
+            anchor.col.max(current.col)
+        };
+        Some((
+            Position::new(start_row, anchor.col.min(current.col)),
+            Position::new(end_row, end_col),
+        ))
     }
 
     pub fn visual_line_selected_text(&self) -> Option<String> {
@@ -617,9 +940,7 @@ impl Editor {
 
     pub fn copy_visual_lines(&mut self) {
         if let Some(text) = self.visual_line_selected_text() {
-            self.clipboard = Some(text.clone());
-            self.clipboard_linewise = true;
-            crate::clipboard::set_system_text(&text);
+            self.set_clipboard(text, true);
         }
     }
 
@@ -640,18 +961,17 @@ impl Editor {
             }
 
             // Set clipboard (with newlines for vim compatibility)
-            let clipboard_text = deleted_lines.join("\n") + "\n";
-            self.clipboard = Some(clipboard_text.clone());
-            self.clipboard_linewise = true;
-            crate::clipboard::set_system_text(&clipboard_text);
+            self.set_clipboard(deleted_lines.join("\n") + "\n", true);
 
             let cursor_before = self.cursor.pos();
 
             // Delete lines from end to start to preserve row indices
+            let lines_before = self.buffer.line_count();
             for row in (start_row..=end_row).rev() {
                 self.buffer.delete_line(row);
                 self.wrap_cache.remove_line(row);
             }
+            self.rows_replaced(start_row, end_row - start_row + 1, lines_before);
 
             // Move cursor to start of deleted region
             let new_row = start_row.min(self.buffer.line_count().saturating_sub(1));
@@ -675,17 +995,9 @@ impl Editor {
     }
 
     pub fn visual_block_selected_text(&self) -> Option<String> {
-        let (anchor, current) = self.visual_block_selection?;
-        let (start_row, end_row) = if anchor.row <= current.row {
-            (anchor.row, current.row)
-        } else {
-            (current.row, anchor.row)
-        };
-        let (start_col, end_col) = if anchor.col <= current.col {
-            (anchor.col, current.col)
-        } else {
-            (current.col, anchor.col)
-        };
+        let (start, end) = self.visual_block_rect()?;
+        let (start_row, end_row) = (start.row, end.row);
+        let (start_col, end_col) = (start.col, end.col);
 
         let mut result = Vec::new();
         for row in start_row..=end_row {
@@ -708,24 +1020,14 @@ impl Editor {
 
     pub fn copy_visual_block(&mut self) {
         if let Some(text) = self.visual_block_selected_text() {
-            self.clipboard = Some(text.clone());
-            self.clipboard_linewise = false;
-            crate::clipboard::set_system_text(&text);
+            self.set_clipboard(text, false);
         }
     }
 
     pub fn cut_visual_block(&mut self) {
-        if let Some((anchor, current)) = self.visual_block_selection {
-            let (start_row, end_row) = if anchor.row <= current.row {
-                (anchor.row, current.row)
-            } else {
-                (current.row, anchor.row)
-            };
-            let (start_col, end_col) = if anchor.col <= current.col {
-                (anchor.col, current.col)
-            } else {
-                (current.col, anchor.col)
-            };
+        if let Some((start, end)) = self.visual_block_rect() {
+            let (start_row, end_row) = (start.row, end.row);
+            let (start_col, end_col) = (start.col, end.col);
 
             // Collect deleted text for each line (for undo)
             let mut deleted_lines = Vec::new();
@@ -745,10 +1047,7 @@ impl Editor {
             }
 
             // Get the text for clipboard (newline-separated)
-            let clipboard_text = deleted_lines.join("\n");
-            self.clipboard = Some(clipboard_text.clone());
-            self.clipboard_linewise = false;
-            crate::clipboard::set_system_text(&clipboard_text);
+            self.set_clipboard(deleted_lines.join("\n"), false);
 
             let cursor_before = self.cursor.pos();
 
@@ -799,34 +1098,84 @@ impl Editor {
         }
     }
 
-    pub fn set_wiki_link_styles(&mut self, valid_style: Style, invalid_style: Style) {
-        self.wiki_link_valid_style = valid_style;
-        self.wiki_link_invalid_style = invalid_style;
+
+    /// Colors to highlight Markdown with from here on; highlights already
+    /// worked out keep theirs until redone.
+    pub fn set_markdown_colors(&mut self, colors: &MarkdownTheme) {
+        self.heading_colors = colors.headings;
+        self.code_color = colors.code;
+        self.code_block_color = colors.code_block;
+        self.link_color = colors.link;
+        self.blockquote_color = colors.blockquote;
+        self.list_marker_color = colors.list_marker;
+        self.bold_color = Some(colors.bold);
+        self.italic_color = Some(colors.italic);
+        self.wiki_link_valid_style = Style::default().fg(colors.wiki_valid);
+        self.wiki_link_invalid_style = Style::default().fg(colors.wiki_invalid);
     }
 
-    pub fn set_markdown_colors(
-        &mut self,
-        heading_colors: [Color; 6],
-        code_color: Color,
-        link_color: Color,
-        blockquote_color: Color,
-        list_marker_color: Color,
-        bold_color: Option<Color>,
-        italic_color: Option<Color>,
-    ) {
-        self.heading_colors = heading_colors;
-        self.code_color = code_color;
-        self.link_color = link_color;
-        self.blockquote_color = blockquote_color;
-        self.list_marker_color = list_marker_color;
-        self.bold_color = bold_color;
-        self.italic_color = italic_color;
+    /// How `~~strikethrough~~` is drawn: crossed out, or dimmed where the
+    /// terminal can't cross text out.
+    pub fn set_strikethrough_modifier(&mut self, modifier: Modifier) {
+        self.strikethrough_modifier = modifier;
+    }
+
+    pub fn set_callout_colors(&mut self, colors: CalloutTheme) {
+        self.callout_colors = colors;
     }
 
     pub fn set_frontmatter_color(&mut self, color: Color) {
         self.frontmatter_color = color;
     }
 
+    /// Draw a guide down each indent level, and color list bullets by how
+    /// deeply they're nested from `list_marker_colors`.
+    pub fn set_indent_guides(&mut self, enabled: bool, guide_color: Color, list_marker_colors: [Color; 3]) {
+        self.indent_guides = enabled;
+        self.indent_guide_style = Style::default().fg(guide_color);
+        self.list_marker_colors = list_marker_colors;
+    }
+
+    /// Bullet color of a list item on `line`.
+    fn list_marker_color_for(&self, line: &str) -> Color {
+        if !self.indent_guides {
+            return self.list_marker_color;
+        }
+        let tab_width = self.tab_width as usize;
+        let depth = crate::app::indent_guides(line, tab_width, tab_width).len();
+        self.list_marker_colors[depth % 3]
+    }
+
+    /// Draw `row`'s indent guides over its leading whitespace, starting at
+    /// `x`. Only the rows on screen are worked out, and code blocks and
+    /// frontmatter get none.
+    fn render_indent_guides(&self, buf: &mut RatatuiBuffer, x: u16, end_x: u16, y: u16, row: usize) {
+        let in_code = self.code_block_rows.contains(&row)
+            || self.highlight_index.get_row(row).iter().any(|h| {
+                matches!(h.highlight_type, HighlightType::CodeBlock | HighlightType::Frontmatter)
+            });
+        if !self.indent_guides || in_code {
+            return;
+        }
+        let line = self.buffer.line(row).unwrap_or("");
+        let tab_width = self.tab_width as usize;
+        let cursor = self.cursor.pos();
+        let cursor_x = (cursor.row == row).then(|| {
+            let cells = grapheme::cells(line, self.tab_width);
+            cells[..cursor.col.min(cells.len())].iter().map(|c| c.width as usize).sum::<usize>()
+        });
+        for column in crate::app::indent_guides(line, tab_width, tab_width) {
+            let guide_x = x + column as u16;
+            if guide_x >= end_x || cursor_x == Some(column) {
+                continue;
+            }
+            if let Some(cell) = buf.cell_mut((guide_x, y)) {
+                cell.set_char('│');
+                cell.set_style(self.indent_guide_style);
+            }
+        }
+    }
+
     pub fn update_wiki_links<F>(&mut self, validator: F)
     where
         F: Fn(&str) -> bool,
@@ -866,43 +1215,22 @@ impl Editor {
 
                 if let Some(start_pos) = remaining.find("[[") {
                     let abs_start = search_start + start_pos;
-                    let after_brackets = &line[abs_start + 2..];
-
-                    if let Some(end_pos) = after_brackets.find("]]") {
-                        let raw_content = &after_brackets[..end_pos];
-
-                        if !raw_content.is_empty()
-                            && !raw_content.contains('[')
-                            && !raw_content.contains(']')
-                        {
-                            // Parse: [[target#heading|display]] - extract target for validation
-                            let content = if let Some(pipe_pos) = raw_content.find('|') {
-                                &raw_content[..pipe_pos]
-                            } else {
-                                raw_content
-                            };
-                            let target = if let Some(hash_pos) = content.find('#') {
-                                &content[..hash_pos]
-                            } else {
-                                content
-                            };
-
-                            let is_valid = validator(target);
-
-                            let start_col = line[..abs_start].chars().count();
-                            let end_col = start_col + 2 + raw_content.chars().count() + 2; // [[content]]
-
-                            self.wiki_link_ranges.push(WikiLinkRange {
-                                row,
-                                start_col,
-                                end_col,
-                                is_valid,
-                            });
-                        }
+                    // Parse: [[target#heading|display]] - the target is validated
+                    if let Some(link) = WikiLink::parse(&line[abs_start..]) {
+                        let start_col = line[..abs_start].chars().count();
+                        let end_col = start_col + line[abs_start..abs_start + link.len].chars().count();
 
-                        search_start = abs_start + 2 + end_pos + 2;
-                        continue;
+                        self.wiki_link_ranges.push(WikiLinkRange {
+                            row,
+                            start_col,
+                            end_col,
+                            is_valid: validator(&link.target),
+                        });
+                        search_start = abs_start + link.len;
+                    } else {
+                        search_start = abs_start + 2;
                     }
+                    continue;
                 }
                 break;
             }
@@ -973,7 +1301,7 @@ impl Editor {
 
         // O(log n) lookup to get highlights for this row, then scan only that row's highlights
         for highlight in self.highlight_index.get_row(row) {
-            if highlight.contains(row, col) {
+            if highlight.contains(row, col) && highlight.highlight_type != HighlightType::Diagnostic {
                 match best_match {
                     None => best_match = Some(highlight),
                     Some(current) if highlight.priority > current.priority => {
@@ -1014,7 +1342,16 @@ impl Editor {
                 .highlight_style_at(row, col)
                 .or_else(|| self.wiki_link_style_at(row, col))
                 .unwrap_or_default();
-            styles.push(style);
+            let diagnostic = self
+                .highlight_index
+                .get_row(row)
+                .iter()
+                .filter(|h| h.highlight_type == HighlightType::Diagnostic && h.contains(row, col))
+                .max_by_key(|h| h.priority);
+            styles.push(match diagnostic {
+                Some(diagnostic) => style.patch(diagnostic.style),
+                None => style,
+            });
         }
 
         styles
@@ -1047,14 +1384,19 @@ impl Editor {
         !self.highlight_index.is_empty()
     }
 
-    #[allow(dead_code)]
     pub fn highlight_count(&self) -> usize {
         self.highlight_index.len()
     }
 
+    /// Rows whose styles are cached, for the debug overlay.
+    pub fn cached_row_styles(&self) -> usize {
+        self.row_style_cache.borrow().rows.len()
+    }
+
     // ==================== Markdown Syntax Highlighting ====================
 
     pub fn update_markdown_highlights(&mut self) {
+        self.refresh_folds();
         self.highlight_index
             .retain(|h| h.highlight_type == HighlightType::WikiLink);
         self.code_block_rows.clear();
@@ -1089,7 +1431,7 @@ impl Editor {
                     row,
                     start,
                     line.chars().count(),
-                    Style::default().fg(self.code_color),
+                    Style::default().fg(self.code_block_color),
                     HighlightType::CodeBlock,
                 ));
                 continue;
@@ -1101,7 +1443,7 @@ impl Editor {
                     row,
                     0,
                     line.chars().count(),
-                    Style::default().fg(self.code_color),
+                    Style::default().fg(self.code_block_color),
                     HighlightType::CodeBlock,
                 ));
                 continue;
@@ -1111,7 +1453,40 @@ impl Editor {
         }
     }
 
+    /// Move highlights, cached row styles and wiki link ranges along when
+    /// `removed` rows at `row` are replaced by `inserted` ones, dropping those
+    /// of the removed rows, so nothing is drawn on the wrong line before the
+    /// next full re-highlight.
+    fn splice_rows(&mut self, row: usize, removed: usize, inserted: usize) {
+        if removed == 0 && inserted == 0 {
+            return;
+        }
+        self.highlight_index.splice_rows(row, removed, inserted);
+        self.row_style_cache.borrow_mut().splice_rows(row, removed, inserted);
+        self.folds.splice_rows(row, removed, inserted);
+        self.wiki_link_ranges.retain_mut(|range| {
+            match spliced_row(range.row, row, removed, inserted) {
+                Some(new_row) => {
+                    range.row = new_row;
+                    true
+                }
+                None => false,
+            }
+        });
+    }
+
+    /// [`Self::splice_rows`] after deleting `removed` lines at `row` from a
+    /// buffer of `lines_before`, which keeps an empty line when it would
+    /// otherwise run out.
+    fn rows_replaced(&mut self, row: usize, removed: usize, lines_before: usize) {
+        let removed = removed.min(lines_before.saturating_sub(row));
+        let inserted = (self.buffer.line_count() + removed).saturating_sub(lines_before);
+        self.splice_rows(row, removed, inserted);
+        self.update_row_highlights(row);
+    }
+
     pub fn update_row_highlights(&mut self, row: usize) {
+        self.refresh_folds();
         self.highlight_index
             .clear_row_of_type(row, HighlightType::Frontmatter);
         self.highlight_index
@@ -1130,6 +1505,8 @@ impl Editor {
             .clear_row_of_type(row, HighlightType::Bold);
         self.highlight_index
             .clear_row_of_type(row, HighlightType::Italic);
+        self.highlight_index
+            .clear_row_of_type(row, HighlightType::Strikethrough);
 
         self.row_style_cache.borrow_mut().invalidate_row(row);
 
@@ -1162,7 +1539,7 @@ impl Editor {
                 row,
                 start,
                 line.chars().count(),
-                Style::default().fg(self.code_color),
+                Style::default().fg(self.code_block_color),
                 HighlightType::CodeBlock,
             ));
             if !was_in_code_block
@@ -1179,7 +1556,7 @@ impl Editor {
                 row,
                 0,
                 line.chars().count(),
-                Style::default().fg(self.code_color),
+                Style::default().fg(self.code_block_color),
                 HighlightType::CodeBlock,
             ));
             return;
@@ -1237,6 +1614,8 @@ impl Editor {
                 .clear_row_of_type(row, HighlightType::Bold);
             self.highlight_index
                 .clear_row_of_type(row, HighlightType::Italic);
+            self.highlight_index
+                .clear_row_of_type(row, HighlightType::Strikethrough);
             self.row_style_cache.borrow_mut().invalidate_row(row);
 
             if line.trim_start().starts_with("```") {
@@ -1248,7 +1627,7 @@ impl Editor {
                     row,
                     start,
                     line.chars().count(),
-                    Style::default().fg(self.code_color),
+                    Style::default().fg(self.code_block_color),
                     HighlightType::CodeBlock,
                 ));
                 continue;
@@ -1260,7 +1639,7 @@ impl Editor {
                     row,
                     0,
                     line.chars().count(),
-                    Style::default().fg(self.code_color),
+                    Style::default().fg(self.code_block_color),
                     HighlightType::CodeBlock,
                 ));
             } else {
@@ -1283,6 +1662,62 @@ impl Editor {
         }
     }
 
+    // ==================== Folds ====================
+
+    /// First and last row of the frontmatter and each fenced code block.
+    fn foldable_blocks(&self) -> Vec<(usize, usize)> {
+        fold::foldable_blocks(self.buffer.lines(), self.detect_frontmatter_end())
+    }
+
+    /// Fit the folds to the blocks as they are after an edit.
+    fn refresh_folds(&mut self) {
+        if !self.folds.is_empty() {
+            let blocks = self.foldable_blocks();
+            self.folds.retain_blocks(&blocks);
+        }
+    }
+
+    /// `zc`: fold the code block or frontmatter the cursor is in, leaving the
+    /// cursor on its summary line. Returns whether there was one.
+    pub fn close_fold(&mut self) -> bool {
+        let row = self.cursor.pos().row;
+        let Some((start, end)) = self.foldable_blocks().into_iter().find(|&(start, end)| (start..=end).contains(&row))
+        else {
+            return false;
+        };
+        self.folds.insert(start, end);
+        self.cursor.move_to(start, 0);
+        self.ensure_cursor_visible();
+        true
+    }
+
+    /// `zo`: unfold the fold the cursor is on. Returns whether there was one.
+    pub fn open_fold(&mut self) -> bool {
+        let row = self.cursor.pos().row;
+        let Some((start, _)) = self.folds.containing(row) else {
+            return false;
+        };
+        self.folds.remove(start);
+        self.ensure_cursor_visible();
+        true
+    }
+
+    /// `za`: unfold the fold the cursor is on, or fold the block it's in.
+    pub fn toggle_fold(&mut self) -> bool {
+        self.open_fold() || self.close_fold()
+    }
+
+    /// The row Down goes to from `row`: past a fold starting there.
+    fn row_below(&self, row: usize) -> usize {
+        self.folds.folded_at(row).unwrap_or(row) + 1
+    }
+
+    /// The row Up goes to from `row`: the summary of a fold ending above.
+    fn row_above(&self, row: usize) -> Option<usize> {
+        let above = row.checked_sub(1)?;
+        Some(self.folds.containing(above).map_or(above, |(start, _)| start))
+    }
+
     /// detect the line index where frontmatter ends, returns None if no valid frontmatter is found.
     fn detect_frontmatter_end(&self) -> Option<usize> {
         let line_count = self.buffer.line_count();
@@ -1325,6 +1760,18 @@ impl Editor {
             return;
         }
 
+        // `[ref]: url`, the definition of a reference link
+        if crate::app::parse_definition(line).is_some() {
+            self.highlight_index.insert(HighlightRange::new(
+                row,
+                0,
+                line_len,
+                Style::default().fg(self.link_color).add_modifier(Modifier::ITALIC),
+                HighlightType::Link,
+            ));
+            return;
+        }
+
         if line.trim_start().starts_with('>') {
             let byte_start = line.find('>').unwrap_or(0);
             let start = line[..byte_start].chars().count();
@@ -1335,6 +1782,16 @@ impl Editor {
                 Style::default().fg(self.blockquote_color),
                 HighlightType::Blockquote,
             ));
+            // The `[!NOTE]` line of a callout, in its type's color
+            if let Some((kind, marker)) = crate::app::callout_marker_start(line) {
+                self.highlight_index.insert(HighlightRange::new(
+                    row,
+                    marker,
+                    line_len,
+                    Style::default().fg(kind.color(&self.callout_colors)).add_modifier(Modifier::BOLD),
+                    HighlightType::Blockquote,
+                ));
+            }
         }
 
         self.highlight_list_marker(row, line);
@@ -1343,6 +1800,7 @@ impl Editor {
         self.highlight_links(row, line);
         self.highlight_bold(row, line);
         self.highlight_italic(row, line);
+        self.highlight_strikethrough(row, line);
     }
 
     fn detect_header(&self, line: &str) -> Option<usize> {
@@ -1362,13 +1820,14 @@ impl Editor {
     fn highlight_list_marker(&mut self, row: usize, line: &str) {
         let trimmed = line.trim_start();
         let indent_chars = line.chars().take_while(|c| c.is_whitespace()).count();
+        let marker_color = self.list_marker_color_for(line);
 
         if trimmed.starts_with("- ") || trimmed.starts_with("* ") || trimmed.starts_with("+ ") {
             self.highlight_index.insert(HighlightRange::new(
                 row,
                 indent_chars,
                 indent_chars + 1,
-                Style::default().fg(self.list_marker_color),
+                Style::default().fg(marker_color),
                 HighlightType::ListMarker,
             ));
 
@@ -1394,7 +1853,7 @@ impl Editor {
                     row,
                     indent_chars,
                     indent_chars + dot_pos + 1,
-                    Style::default().fg(self.list_marker_color),
+                    Style::default().fg(marker_color),
                     HighlightType::ListMarker,
                 ));
             }
@@ -1456,6 +1915,29 @@ impl Editor {
                             continue;
                         }
                     }
+                    // A reference link, `[text][ref]`
+                    if bracket_end_pos + 1 < chars.len() && chars[bracket_end_pos + 1] == '[' {
+                        let label = &chars[bracket_end_pos + 2..];
+                        if let Some(label_end) = label.iter().position(|&c| c == ']' || c == '[') {
+                            if label[label_end] == ']' {
+                                let end = bracket_end_pos + 2 + label_end + 1;
+                                self.highlight_index.insert(
+                                    HighlightRange::new(
+                                        row,
+                                        i,
+                                        end,
+                                        Style::default()
+                                            .fg(self.link_color)
+                                            .add_modifier(Modifier::UNDERLINED),
+                                        HighlightType::Link,
+                                    )
+                                    .with_priority(1),
+                                );
+                                i = end;
+                                continue;
+                            }
+                        }
+                    }
                 }
             }
             i += 1;
@@ -1550,6 +2032,29 @@ impl Editor {
         }
     }
 
+    fn highlight_strikethrough(&mut self, row: usize, line: &str) {
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+
+        while i < chars.len() {
+            match strikethrough_at(&chars, i) {
+                Some(end) => {
+                    if !self.is_position_highlighted(row, i) {
+                        self.highlight_index.insert(HighlightRange::new(
+                            row,
+                            i,
+                            end,
+                            Style::default().add_modifier(self.strikethrough_modifier),
+                            HighlightType::Strikethrough,
+                        ));
+                    }
+                    i = end;
+                }
+                None => i += 1,
+            }
+        }
+    }
+
     fn is_position_highlighted(&self, row: usize, col: usize) -> bool {
         self.highlight_index.get_row(row).iter().any(|h| {
             col >= h.start_col
@@ -1607,7 +2112,12 @@ impl Editor {
         let safe_row = row.min(line_count.saturating_sub(1));
         let line_len = self.buffer.line_len(safe_row);
         let safe_col = col.min(line_len);
+        // A jump into a fold, e.g. to a search match, opens it
+        if let Some((start, _)) = self.folds.containing(safe_row).filter(|&(start, _)| start != safe_row) {
+            self.folds.remove(start);
+        }
         self.cursor.move_to(safe_row, safe_col);
+        self.snap_cursor_to_cluster();
         self.ensure_cursor_visible();
     }
 
@@ -1617,110 +2127,33 @@ impl Editor {
         let line_len = self.buffer.line_len(safe_row);
         let safe_col = col.min(line_len);
         self.cursor.move_to(safe_row, safe_col);
+        self.snap_cursor_to_cluster();
     }
 
     pub fn move_cursor(&mut self, movement: CursorMove) {
         let pos = self.cursor.pos();
         let line_count = self.buffer.line_count();
 
-        if !matches!(movement, CursorMove::Up | CursorMove::Down) {
-            self.preferred_visual_x = None;
-        }
-
         match movement {
             CursorMove::Forward => {
-                let line_len = self.buffer.line_len(pos.row);
-                if pos.col < line_len {
-                    self.cursor.move_to(pos.row, pos.col + 1);
+                let line = self.buffer.line(pos.row).unwrap_or("");
+                if pos.col < line.chars().count() {
+                    self.cursor.move_to(pos.row, grapheme::next_boundary(line, pos.col));
                 } else if pos.row + 1 < line_count {
                     self.cursor.move_to(pos.row + 1, 0);
                 }
             }
             CursorMove::Back => {
                 if pos.col > 0 {
-                    self.cursor.move_to(pos.row, pos.col - 1);
+                    let line = self.buffer.line(pos.row).unwrap_or("");
+                    self.cursor.move_to(pos.row, grapheme::prev_boundary(line, pos.col));
                 } else if pos.row > 0 {
                     let prev_len = self.buffer.line_len(pos.row - 1);
                     self.cursor.move_to(pos.row - 1, prev_len);
                 }
             }
-            CursorMove::Up => {
-                if self.line_wrap_enabled && self.view_width > 0 {
-                    let content_width = self.wrap_content_width();
-                    if content_width > 0 {
-                        let (cur_visual_line, cur_x) = self.cursor_wrapped_position();
-                        let preferred_x = self.preferred_visual_x.unwrap_or(cur_x);
-                        self.preferred_visual_x = Some(preferred_x);
-
-                        if cur_visual_line > 0 {
-                            let new_col = self.col_at_visual_pos(
-                                pos.row,
-                                cur_visual_line - 1,
-                                preferred_x,
-                                content_width,
-                            );
-                            self.cursor.set_pos(Position::new(pos.row, new_col), false);
-                        } else if pos.row > 0 {
-                            let prev_visual_lines =
-                                self.visual_lines_for_row(pos.row - 1, content_width);
-                            let new_col = self.col_at_visual_pos(
-                                pos.row - 1,
-                                prev_visual_lines.saturating_sub(1),
-                                preferred_x,
-                                content_width,
-                            );
-                            self.cursor
-                                .set_pos(Position::new(pos.row - 1, new_col), false);
-                        }
-                    } else if pos.row > 0 {
-                        let preferred = self.cursor.preferred_col.unwrap_or(pos.col);
-                        let prev_len = self.buffer.line_len(pos.row - 1);
-                        self.cursor
-                            .set_pos(Position::new(pos.row - 1, preferred.min(prev_len)), false);
-                    }
-                } else if pos.row > 0 {
-                    let preferred = self.cursor.preferred_col.unwrap_or(pos.col);
-                    let prev_len = self.buffer.line_len(pos.row - 1);
-                    self.cursor
-                        .set_pos(Position::new(pos.row - 1, preferred.min(prev_len)), false);
-                }
-            }
-            CursorMove::Down => {
-                if self.line_wrap_enabled && self.view_width > 0 {
-                    let content_width = self.wrap_content_width();
-                    if content_width > 0 {
-                        let (cur_visual_line, cur_x) = self.cursor_wrapped_position();
-                        let preferred_x = self.preferred_visual_x.unwrap_or(cur_x);
-                        self.preferred_visual_x = Some(preferred_x);
-                        let total_visual_lines = self.visual_lines_for_row(pos.row, content_width);
-
-                        if cur_visual_line + 1 < total_visual_lines {
-                            let new_col = self.col_at_visual_pos(
-                                pos.row,
-                                cur_visual_line + 1,
-                                preferred_x,
-                                content_width,
-                            );
-                            self.cursor.set_pos(Position::new(pos.row, new_col), false);
-                        } else if pos.row + 1 < line_count {
-                            let new_col =
-                                self.col_at_visual_pos(pos.row + 1, 0, preferred_x, content_width);
-                            self.cursor
-                                .set_pos(Position::new(pos.row + 1, new_col), false);
-                        }
-                    } else if pos.row + 1 < line_count {
-                        let preferred = self.cursor.preferred_col.unwrap_or(pos.col);
-                        let next_len = self.buffer.line_len(pos.row + 1);
-                        self.cursor
-                            .set_pos(Position::new(pos.row + 1, preferred.min(next_len)), false);
-                    }
-                } else if pos.row + 1 < line_count {
-                    let preferred = self.cursor.preferred_col.unwrap_or(pos.col);
-                    let next_len = self.buffer.line_len(pos.row + 1);
-                    self.cursor
-                        .set_pos(Position::new(pos.row + 1, preferred.min(next_len)), false);
-                }
-            }
+            CursorMove::Up => self.move_vertically(false),
+            CursorMove::Down => self.move_vertically(true),
             CursorMove::Head => self.cursor.move_to(pos.row, 0),
             CursorMove::End => self.cursor.move_to(pos.row, self.buffer.line_len(pos.row)),
             CursorMove::Top => self.cursor.move_to(0, 0),
@@ -1808,33 +2241,28 @@ impl Editor {
             CursorMove::HalfPageUp => {
                 let half = self.view_height / 2;
                 let new_row = pos.row.saturating_sub(half);
-                let line_len = self.buffer.line_len(new_row);
-                self.cursor.move_to(new_row, pos.col.min(line_len));
+                self.move_to_row_keeping_column(new_row);
                 self.scroll_offset = self.scroll_offset.saturating_sub(half);
             }
             CursorMove::HalfPageDown => {
                 let half = self.view_height / 2;
                 let new_row = (pos.row + half).min(line_count.saturating_sub(1));
-                let line_len = self.buffer.line_len(new_row);
-                self.cursor.move_to(new_row, pos.col.min(line_len));
-                if self.scroll_offset + half < line_count.saturating_sub(self.view_height) {
+                self.move_to_row_keeping_column(new_row);
+                if self.scroll_offset + half < self.max_scroll() {
                     self.scroll_offset += half;
                 }
             }
             CursorMove::PageUp => {
                 let page = self.view_height.saturating_sub(2);
                 let new_row = pos.row.saturating_sub(page);
-                let line_len = self.buffer.line_len(new_row);
-                self.cursor.move_to(new_row, pos.col.min(line_len));
+                self.move_to_row_keeping_column(new_row);
                 self.scroll_offset = self.scroll_offset.saturating_sub(page);
             }
             CursorMove::PageDown => {
                 let page = self.view_height.saturating_sub(2);
                 let new_row = (pos.row + page).min(line_count.saturating_sub(1));
-                let line_len = self.buffer.line_len(new_row);
-                self.cursor.move_to(new_row, pos.col.min(line_len));
-                let max_scroll = line_count.saturating_sub(self.view_height);
-                self.scroll_offset = (self.scroll_offset + page).min(max_scroll);
+                self.move_to_row_keeping_column(new_row);
+                self.scroll_offset = (self.scroll_offset + page).min(self.max_scroll());
             }
             CursorMove::MatchingBracket => {
                 if let Some(new_pos) = self.find_matching_bracket() {
@@ -1856,16 +2284,115 @@ impl Editor {
                     .move_to(pos.row, col.saturating_sub(1).min(line_len));
             }
         }
+        // Motions stop on a fold's summary line, never in the rows it hides
+        let row = self.cursor.pos().row;
+        if let Some((start, _)) = self.folds.containing(row).filter(|&(start, _)| start != row) {
+            self.cursor.move_to(start, 0);
+        }
+        self.snap_cursor_to_cluster();
         self.ensure_cursor_visible();
     }
 
+    /// Where vertical motions aim: the preferred column they kept, or the
+    /// cursor's own at the start of one.
+    fn preferred_col(&self) -> PreferredCol {
+        self.cursor.preferred_col.unwrap_or_else(|| PreferredCol {
+            col: self.cursor_display_col(),
+            x: self.cursor_wrapped_position().1,
+        })
+    }
+
+    /// Char column of `row` at display column `target`, or its end when the
+    /// line is shorter.
+    fn col_at_display_col(&self, row: usize, target: usize) -> usize {
+        let line = self.buffer.line(row).unwrap_or("");
+        let cells = grapheme::cells(line, self.tab_width);
+        let mut x = 0;
+        for (col, cell) in cells.iter().enumerate() {
+            if cell.is_continuation() {
+                continue;
+            }
+            x += cell.width as usize;
+            if x > target {
+                return col;
+            }
+        }
+        cells.len()
+    }
+
+    /// Up/Down, by screen line when lines wrap. The preferred column is kept
+    /// across lines too short for it, and wherever a line reaches it the
+    /// cursor lands back on it. A wrapped screen line that doesn't reach it
+    /// gets the same screen column instead.
+    fn move_vertically(&mut self, down: bool) {
+        let pos = self.cursor.pos();
+        let line_count = self.buffer.line_count();
+        let content_width = self.wrap_content_width();
+        if !self.line_wrap_enabled || self.view_width == 0 || content_width == 0 {
+            let row = if down { Some(self.row_below(pos.row)) } else { self.row_above(pos.row) };
+            if let Some(row) = row.filter(|&row| row < line_count) {
+                self.move_to_row_keeping_column(row);
+            }
+            return;
+        }
+
+        let preferred = self.preferred_col();
+        let (visual_line, _) = self.cursor_wrapped_position();
+        let (row, visual_line) = if down {
+            if visual_line + 1 < self.visual_lines_for_row(pos.row, content_width) {
+                (pos.row, visual_line + 1)
+            } else if self.row_below(pos.row) < line_count {
+                (self.row_below(pos.row), 0)
+            } else {
+                return;
+            }
+        } else if visual_line > 0 {
+            (pos.row, visual_line - 1)
+        } else if let Some(above) = self.row_above(pos.row) {
+            let last = self.visual_lines_for_row(above, content_width).saturating_sub(1);
+            (above, last)
+        } else {
+            return;
+        };
+
+        let exact = self.col_at_display_col(row, preferred.col);
+        let start = self.col_at_visual_pos(row, visual_line, 0, content_width);
+        let next = self.col_at_visual_pos(row, visual_line + 1, 0, content_width);
+        let on_this_line = start <= exact && (exact < next || next == self.buffer.line_len(row));
+        let col = if on_this_line {
+            exact
+        } else {
+            self.col_at_visual_pos(row, visual_line, preferred.x, content_width)
+        };
+        self.cursor.set_pos(Position::new(row, col), false);
+        self.cursor.preferred_col = Some(preferred);
+    }
+
+    fn move_to_row_keeping_column(&mut self, row: usize) {
+        let preferred = self.preferred_col();
+        let col = self.col_at_display_col(row, preferred.col);
+        self.cursor.set_pos(Position::new(row, col), false);
+        self.cursor.preferred_col = Some(preferred);
+    }
+
+    /// Move the cursor back to the start of the grapheme cluster it's in, so
+    /// motions that count chars never leave it inside one.
+    fn snap_cursor_to_cluster(&mut self) {
+        let pos = self.cursor.pos();
+        let line = self.buffer.line(pos.row).unwrap_or("");
+        let col = grapheme::floor_boundary(line, pos.col);
+        if col != pos.col {
+            self.cursor.set_pos(Position::new(pos.row, col), false);
+        }
+    }
+
     fn move_word_forward(&mut self) {
         let pos = self.cursor.pos();
         let Some(line) = self.buffer.line(pos.row) else {
             return;
         };
 
-        let new_col = cursor::find_word_forward(line, pos.col);
+        let new_col = cursor::find_word_forward(line, pos.col, &self.word_chars);
         let line_len = line.chars().count();
 
         if new_col >= line_len && pos.row + 1 < self.buffer.line_count() {
@@ -1890,7 +2417,7 @@ impl Editor {
 
         if let Some(line) = self.buffer.line(pos.row) {
             self.cursor
-                .move_to(pos.row, cursor::find_word_back(line, pos.col));
+                .move_to(pos.row, cursor::find_word_back(line, pos.col, &self.word_chars));
         }
     }
 
@@ -1921,9 +2448,9 @@ impl Editor {
             }
             return;
         }
-        let is_word = cursor::is_word_char(chars[col]);
+        let is_word = self.word_chars.is_word_char(chars[col]);
         while col < len.saturating_sub(1) {
-            let next_is_word = cursor::is_word_char(chars[col + 1]);
+            let next_is_word = self.word_chars.is_word_char(chars[col + 1]);
             if chars[col + 1].is_whitespace() || next_is_word != is_word {
                 break;
             }
@@ -1949,9 +2476,9 @@ impl Editor {
         while col > 0 && chars[col].is_whitespace() {
             col -= 1;
         }
-        let is_word = cursor::is_word_char(chars[col]);
+        let is_word = self.word_chars.is_word_char(chars[col]);
         while col > 0
-            && cursor::is_word_char(chars[col - 1]) == is_word
+            && self.word_chars.is_word_char(chars[col - 1]) == is_word
             && !chars[col - 1].is_whitespace()
         {
             col -= 1;
@@ -2168,14 +2695,10 @@ impl Editor {
     fn effective_selection_range(&self) -> Option<(Position, Position)> {
         let (start, end) = self.cursor.selection_range()?;
         if self.inclusive_selection {
-            let line_len = self
-                .buffer
-                .line(end.row)
-                .map(|l| l.chars().count())
-                .unwrap_or(0);
+            let line = self.buffer.line(end.row).unwrap_or("");
             let end = Position {
                 row: end.row,
-                col: (end.col + 1).min(line_len),
+                col: grapheme::next_boundary(line, end.col).min(line.chars().count()),
             };
             Some((start, end))
         } else {
@@ -2192,11 +2715,44 @@ impl Editor {
     }
 
     // Clipboard
+    /// Text last yanked or deleted in this editor (vim's unnamed register).
+    pub fn clipboard_text(&self) -> Option<&str> {
+        self.clipboard.as_deref()
+    }
+
+    /// Make `text` what `p` pastes, in the editor and system clipboards, and
+    /// the newest yank ring entry.
+    fn set_clipboard(&mut self, text: String, linewise: bool) {
+        crate::clipboard::set_system_text(&text);
+        self.yank_ring.push(&text, linewise);
+        self.clipboard = Some(text);
+        self.clipboard_linewise = linewise;
+    }
+
+    pub fn yank_ring(&self) -> &YankRing {
+        &self.yank_ring
+    }
+
+    pub fn set_yank_ring_size(&mut self, size: usize) {
+        self.yank_ring.set_size(size);
+    }
+
+    pub fn take_yank_ring(&mut self) -> YankRing {
+        std::mem::take(&mut self.yank_ring)
+    }
+
+    pub fn restore_yank_ring(&mut self, ring: YankRing) {
+        self.yank_ring = ring;
+    }
+
+    /// Add text pasted in from outside the editor to the yank ring.
+    pub fn remember_paste(&mut self, text: &str) {
+        self.yank_ring.push(text, text.ends_with('\n'));
+    }
+
     pub fn copy(&mut self) {
         if let Some(text) = self.selected_text() {
-            self.clipboard = Some(text.clone());
-            self.clipboard_linewise = false;
-            crate::clipboard::set_system_text(&text);
+            self.set_clipboard(text, false);
         }
     }
 
@@ -2206,10 +2762,10 @@ impl Editor {
             let deleted = self
                 .buffer
                 .delete_text_range(start.row, start.col, end.row, end.col);
-            self.clipboard = Some(deleted.clone());
-            self.clipboard_linewise = false;
-            crate::clipboard::set_system_text(&deleted);
+            self.set_clipboard(deleted.clone(), false);
             self.wrap_cache.invalidate_from(start.row);
+            self.splice_rows(start.row + 1, end.row - start.row, 0);
+            self.update_row_highlights(start.row);
 
             self.history.record(
                 EditOperation::Delete {
@@ -2237,13 +2793,12 @@ impl Editor {
         let line_text = self.buffer.line(row).unwrap_or("").to_string();
         let deleted_text = format!("{}\n", line_text);
 
-        // Copy to clipboard
-        self.clipboard = Some(deleted_text.clone());
-        crate::clipboard::set_system_text(&deleted_text);
+        self.set_clipboard(deleted_text, true);
 
         // Delete the line
         self.buffer.delete_line(row);
         self.wrap_cache.invalidate_from(row);
+        self.rows_replaced(row, 1, line_count);
 
         let cursor_after = Position {
             row: row.min(self.buffer.line_count().saturating_sub(1)),
@@ -2294,36 +2849,87 @@ impl Editor {
         self.ensure_cursor_visible();
     }
 
-    pub fn paste(&mut self) {
-        let text = self
-            .clipboard
-            .clone()
-            .or_else(crate::clipboard::get_system_text);
-        if let Some(text) = text {
-            self.insert_str(&text);
-        }
-    }
-
     /// Paste after cursor (vim 'p' command)
     /// For line-wise content: paste below current line
     /// For character-wise content: paste after cursor
     pub fn paste_after(&mut self) {
+        self.paste(false);
+    }
+
+    /// Paste before cursor (vim 'P' command)
+    /// For line-wise content: paste above current line
+    /// For character-wise content: paste before cursor
+    pub fn paste_before(&mut self) {
+        self.paste(true);
+    }
+
+    fn paste(&mut self, before: bool) {
         // Try internal clipboard first, then fall back to system clipboard
         let (text, linewise) = if let Some(text) = self.clipboard.clone() {
             (text, self.clipboard_linewise)
         } else if let Some(text) = crate::clipboard::get_system_text() {
             // For system clipboard, detect linewise by checking if ends with newline
             let linewise = text.ends_with('\n');
+            self.yank_ring.push(&text, linewise);
             (text, linewise)
         } else {
             return;
         };
+        let index = self.yank_ring.position(&text, linewise).unwrap_or(0);
+        self.put(index, &text, linewise, before);
+    }
+
+    /// Paste yank ring entry `index` after the cursor, as `p` would.
+    pub fn paste_yank(&mut self, index: usize) -> bool {
+        let Some(yank) = self.yank_ring.get(index).cloned() else {
+            return false;
+        };
+        self.put(index, &yank.text, yank.linewise, false);
+        true
+    }
+
+    /// Swap the text just pasted for the next older (or newer) yank ring
+    /// entry, vim's yank-ring `Ctrl-P` / `Ctrl-N`. False when the last thing
+    /// done wasn't a paste or there's nothing else to swap in.
+    pub fn cycle_paste(&mut self, older: bool) -> bool {
+        let Some(last) = self.last_paste.take() else {
+            return false;
+        };
+        let len = self.yank_ring.len();
+        if len < 2 || self.cursor.pos() != last.cursor || !self.undo() {
+            return false;
+        }
+        let index = if older {
+            (last.index + 1) % len
+        } else {
+            (last.index + len - 1) % len
+        };
+        let Some(yank) = self.yank_ring.get(index).cloned() else {
+            return false;
+        };
+        self.cursor.move_to(last.at.row, last.at.col);
+        self.put(index, &yank.text, yank.linewise, last.before);
+        true
+    }
+
+    /// Stop `Ctrl-P` / `Ctrl-N` from swapping the last paste, once something
+    /// else has been done since.
+    pub fn forget_paste(&mut self) {
+        self.last_paste = None;
+    }
+
+    /// Which yank ring entry was last pasted, while it can still be swapped.
+    pub fn last_paste_index(&self) -> Option<usize> {
+        self.last_paste.map(|last| last.index)
+    }
 
+    fn put(&mut self, index: usize, text: &str, linewise: bool, before: bool) {
+        let at = self.cursor.pos();
         if linewise {
             let (row, col) = self.cursor();
             let cursor_before = Position { row, col };
 
-            let new_row = row + 1;
+            let new_row = if before { row } else { row + 1 };
 
             let text_to_insert = text.trim_end_matches('\n');
             let lines: Vec<String> = text_to_insert.split('\n').map(|s| s.to_string()).collect();
@@ -2332,6 +2938,10 @@ impl Editor {
                 self.buffer.insert_line(new_row + i, line.clone());
                 self.wrap_cache.insert_line(new_row + i);
             }
+            self.splice_rows(new_row, 0, lines.len());
+            for row in new_row..new_row + lines.len() {
+                self.update_row_highlights(row);
+            }
 
             self.cursor.move_to(new_row, 0);
 
@@ -2347,65 +2957,31 @@ impl Editor {
                 },
             );
         } else {
-            let (row, col) = self.cursor();
-            let line_len = self
-                .buffer
-                .line(row)
-                .map(|l| l.chars().count())
-                .unwrap_or(0);
-            let new_col = (col + 1).min(line_len);
-            self.cursor.move_to(row, new_col);
-            self.insert_str(&text);
-        }
-        self.ensure_cursor_visible();
-    }
-
-    /// Paste before cursor (vim 'P' command)
-    /// For line-wise content: paste above current line
-    /// For character-wise content: paste before cursor
-    pub fn paste_before(&mut self) {
-        let (text, linewise) = if let Some(text) = self.clipboard.clone() {
-            (text, self.clipboard_linewise)
-        } else if let Some(text) = crate::clipboard::get_system_text() {
-            // For system clipboard, detect linewise by checking if ends with newline
-            let linewise = text.ends_with('\n');
-            (text, linewise)
-        } else {
-            return;
-        };
-
-        if linewise {
-            let (row, col) = self.cursor();
-            let cursor_before = Position { row, col };
-
-            let text_to_insert = text.trim_end_matches('\n');
-            let lines: Vec<String> = text_to_insert.split('\n').map(|s| s.to_string()).collect();
-
-            for (i, line) in lines.iter().enumerate() {
-                self.buffer.insert_line(row + i, line.clone());
-                self.wrap_cache.insert_line(row + i);
+            if !before {
+                let (row, col) = self.cursor();
+                let line_len = self
+                    .buffer
+                    .line(row)
+                    .map(|l| l.chars().count())
+                    .unwrap_or(0);
+                let new_col = (col + 1).min(line_len);
+                self.cursor.move_to(row, new_col);
             }
-
-            self.cursor.move_to(row, 0);
-
-            self.history.record(
-                EditOperation::LineInsert { row, lines },
-                cursor_before,
-                Position { row, col: 0 },
-            );
-        } else {
-            self.insert_str(&text);
+            self.insert_str(text);
         }
         self.ensure_cursor_visible();
+        self.last_paste = Some(LastPaste {
+            index,
+            before,
+            at,
+            cursor: self.cursor.pos(),
+        });
     }
 
     // Text manipulation
     pub fn insert_char(&mut self, c: char) {
         let cursor_before = self.cursor.pos();
-
-        if self.cursor.has_selection() {
-            self.delete_selection_internal();
-        }
+        let deleted = self.delete_selection_internal();
 
         let pos = self.cursor.pos();
         self.buffer.insert_char(pos.row, pos.col, c);
@@ -2413,14 +2989,16 @@ impl Editor {
 
         self.update_row_highlights(pos.row);
 
-        self.history.record(
-            EditOperation::Insert {
-                pos,
-                text: c.to_string(),
-            },
-            cursor_before,
-            Position::new(pos.row, pos.col + 1),
-        );
+        let insert = EditOperation::Insert {
+            pos,
+            text: c.to_string(),
+        };
+        let cursor_after = Position::new(pos.row, pos.col + 1);
+        match deleted {
+            // Typed over a selection: one undo step brings the selection back
+            Some(delete) => self.history.record_group(vec![delete, insert], cursor_before, cursor_after),
+            None => self.history.record(insert, cursor_before, cursor_after),
+        }
 
         self.cursor.move_to(pos.row, pos.col + 1);
         self.ensure_cursor_visible();
@@ -2435,22 +3013,83 @@ impl Editor {
         let cursor_before = self.cursor.pos();
 
         // Delete selection first, record for undo
-        let deleted_selection = if self.cursor.has_selection() {
-            if let Some((start, end)) = self.cursor.selection_range() {
-                let deleted = self
-                    .buffer
-                    .delete_text_range(start.row, start.col, end.row, end.col);
-                self.wrap_cache.invalidate_from(start.row);
-                self.cursor.move_to(start.row, start.col);
-                self.cursor.cancel_selection();
-                Some((start, end, deleted))
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+        let deleted_selection = self.take_selection();
+
+        let pos = self.cursor.pos();
+        self.insert_at_cursor(s);
+
+        // Record undo operations
+        let had_selection = deleted_selection.is_some();
+        if let Some((start, end, deleted_text)) = deleted_selection {
+            self.history.record(
+                EditOperation::Delete {
+                    start,
+                    end,
+                    deleted_text,
+                },
+                cursor_before,
+                pos,
+            );
+        }
+
+        self.history.record(
+            EditOperation::Insert {
+                pos,
+                text: s.to_string(),
+            },
+            if had_selection { pos } else { cursor_before },
+            self.cursor.pos(),
+        );
+
+        self.ensure_cursor_visible();
+    }
+
+    /// Start inserting text too big for one [`Editor::insert_str`] a piece at
+    /// a time with [`Editor::insert_chunk`]. The selection goes now; undo
+    /// sees the whole insertion once [`Editor::end_bulk_insert`] is called.
+    pub fn begin_bulk_insert(&mut self) -> BulkInsert {
+        let cursor_before = self.cursor.pos();
+        let deleted = self.take_selection();
+        BulkInsert { cursor_before, pos: self.cursor.pos(), deleted }
+    }
+
+    /// Insert the next piece of a bulk insertion at the cursor.
+    pub fn insert_chunk(&mut self, s: &str) {
+        if !s.is_empty() {
+            self.insert_at_cursor(s);
+        }
+    }
+
+    /// Finish a bulk insertion of `text`, what went in through
+    /// [`Editor::insert_chunk`], as one undo step.
+    pub fn end_bulk_insert(&mut self, bulk: BulkInsert, text: String) {
+        let mut ops = Vec::with_capacity(2);
+        if let Some((start, end, deleted_text)) = bulk.deleted {
+            ops.push(EditOperation::Delete { start, end, deleted_text });
+        }
+        if !text.is_empty() {
+            ops.push(EditOperation::Insert { pos: bulk.pos, text });
+        }
+        self.history.record_group(ops, bulk.cursor_before, self.cursor.pos());
+        self.ensure_cursor_visible();
+    }
+
+    /// Delete the selection, if any, returning where it was and its text.
+    fn take_selection(&mut self) -> Option<(Position, Position, String)> {
+        if !self.cursor.has_selection() {
+            return None;
+        }
+        let (start, end) = self.cursor.selection_range()?;
+        let deleted = self.buffer.delete_text_range(start.row, start.col, end.row, end.col);
+        self.wrap_cache.invalidate_from(start.row);
+        self.splice_rows(start.row + 1, end.row - start.row, 0);
+        self.cursor.move_to(start.row, start.col);
+        self.cursor.cancel_selection();
+        Some((start, end, deleted))
+    }
 
+    /// Insert `s` at the cursor and move past it, without recording undo.
+    fn insert_at_cursor(&mut self, s: &str) {
         let pos = self.cursor.pos();
         let parts: Vec<&str> = s.split('\n').collect();
         let newline_count = parts.len().saturating_sub(1);
@@ -2482,48 +3121,19 @@ impl Editor {
 
             self.wrap_cache.invalidate_from(pos.row);
 
-            self.highlight_index
-                .shift_rows_after(pos.row + 1, newline_count as isize);
-            self.row_style_cache
-                .borrow_mut()
-                .shift_rows_after(pos.row + 1, newline_count as isize);
+            self.splice_rows(pos.row + 1, 0, newline_count);
             self.recalc_code_blocks_from(pos.row);
 
             self.cursor.move_to(last_idx, last_part.chars().count());
         }
-
-        // Record undo operations
-        let had_selection = deleted_selection.is_some();
-        if let Some((start, end, deleted_text)) = deleted_selection {
-            self.history.record(
-                EditOperation::Delete {
-                    start,
-                    end,
-                    deleted_text,
-                },
-                cursor_before,
-                pos,
-            );
-        }
-
-        self.history.record(
-            EditOperation::Insert {
-                pos,
-                text: s.to_string(),
-            },
-            if had_selection { pos } else { cursor_before },
-            self.cursor.pos(),
-        );
-
-        self.ensure_cursor_visible();
     }
 
+    /// Enter: split the line, carrying a list item's marker onto the new
+    /// one, or clear the marker of an empty item. Whatever it does, replacing
+    /// a selection included, is one undo step.
     pub fn insert_newline(&mut self) {
         let cursor_before = self.cursor.pos();
-
-        if self.cursor.has_selection() {
-            self.delete_selection_internal();
-        }
+        let mut ops: Vec<EditOperation> = self.delete_selection_internal().into_iter().collect();
 
         let pos = self.cursor.pos();
 
@@ -2541,15 +3151,12 @@ impl Editor {
             let deleted = self.buffer.delete_range(pos.row, 0, *prefix_len);
             self.wrap_cache.invalidate_line(pos.row);
             self.update_row_highlights(pos.row);
-            self.history.record(
-                EditOperation::Delete {
-                    start: Position::new(pos.row, 0),
-                    end: Position::new(pos.row, *prefix_len),
-                    deleted_text: deleted,
-                },
-                cursor_before,
-                Position::new(pos.row, 0),
-            );
+            ops.push(EditOperation::Delete {
+                start: Position::new(pos.row, 0),
+                end: Position::new(pos.row, *prefix_len),
+                deleted_text: deleted,
+            });
+            self.history.record_group(ops, cursor_before, Position::new(pos.row, 0));
             self.cursor.move_to(pos.row, 0);
             self.ensure_cursor_visible();
             return;
@@ -2559,34 +3166,22 @@ impl Editor {
         self.wrap_cache.insert_line(pos.row + 1);
         self.wrap_cache.invalidate_line(pos.row);
 
-        self.highlight_index.shift_rows_after(pos.row + 1, 1);
-        self.row_style_cache
-            .borrow_mut()
-            .shift_rows_after(pos.row + 1, 1);
-
-        self.history.record(
-            EditOperation::SplitLine { pos },
-            cursor_before,
-            Position::new(pos.row + 1, 0),
-        );
+        self.splice_rows(pos.row + 1, 0, 1);
+        ops.push(EditOperation::SplitLine { pos });
 
+        let mut cursor_after = Position::new(pos.row + 1, 0);
         if let Some((prefix, _, false)) = list_prefix {
             let next_prefix = prefix.next_prefix();
-            let prefix_char_count = next_prefix.chars().count();
+            cursor_after.col = next_prefix.chars().count();
             self.buffer.insert_str(pos.row + 1, 0, &next_prefix);
             self.wrap_cache.invalidate_line(pos.row + 1);
-            self.history.record(
-                EditOperation::Insert {
-                    pos: Position::new(pos.row + 1, 0),
-                    text: next_prefix,
-                },
-                Position::new(pos.row + 1, 0),
-                Position::new(pos.row + 1, prefix_char_count),
-            );
-            self.cursor.move_to(pos.row + 1, prefix_char_count);
-        } else {
-            self.cursor.move_to(pos.row + 1, 0);
+            ops.push(EditOperation::Insert {
+                pos: Position::new(pos.row + 1, 0),
+                text: next_prefix,
+            });
         }
+        self.history.record_group(ops, cursor_before, cursor_after);
+        self.cursor.move_to(cursor_after.row, cursor_after.col);
 
         // Update highlights for both affected rows
         self.update_row_highlights(pos.row);
@@ -2609,10 +3204,7 @@ impl Editor {
         self.wrap_cache.insert_line(pos.row);
 
         // Shift highlights for inserted line
-        self.highlight_index.shift_rows_after(pos.row, 1);
-        self.row_style_cache
-            .borrow_mut()
-            .shift_rows_after(pos.row, 1);
+        self.splice_rows(pos.row, 0, 1);
         self.update_row_highlights(pos.row);
 
         self.history.record(
@@ -2633,29 +3225,26 @@ impl Editor {
         let line_len = self.buffer.line_len(pos.row);
 
         if pos.col < line_len {
-            if let Some(c) = self.buffer.delete_char(pos.row, pos.col) {
-                self.wrap_cache.invalidate_line(pos.row);
-                // Reactive highlight update
-                self.update_row_highlights(pos.row);
-                self.history.record(
-                    EditOperation::Delete {
-                        start: pos,
-                        end: Position::new(pos.row, pos.col + 1),
-                        deleted_text: c.to_string(),
-                    },
-                    pos,
-                    pos,
-                );
-            }
+            let end_col = grapheme::next_boundary(self.buffer.line(pos.row).unwrap_or(""), pos.col);
+            let deleted = self.buffer.delete_range(pos.row, pos.col, end_col);
+            self.wrap_cache.invalidate_line(pos.row);
+            // Reactive highlight update
+            self.update_row_highlights(pos.row);
+            self.history.record(
+                EditOperation::Delete {
+                    start: pos,
+                    end: Position::new(pos.row, end_col),
+                    deleted_text: deleted,
+                },
+                pos,
+                pos,
+            );
         } else if pos.row + 1 < self.buffer.line_count() {
             self.buffer.join_with_previous(pos.row + 1);
             self.wrap_cache.remove_line(pos.row + 1);
             self.wrap_cache.invalidate_line(pos.row);
             // Line joined: shift highlights and update
-            self.highlight_index.shift_rows_after(pos.row + 1, -1);
-            self.row_style_cache
-                .borrow_mut()
-                .shift_rows_after(pos.row + 1, -1);
+            self.splice_rows(pos.row + 1, 1, 0);
             self.update_row_highlights(pos.row);
             self.history.record(
                 EditOperation::JoinLine {
@@ -2672,34 +3261,30 @@ impl Editor {
         let pos = self.cursor.pos();
 
         if pos.col > 0 {
-            let cursor_before = pos;
-            self.cursor.move_to(pos.row, pos.col - 1);
-            if let Some(c) = self.buffer.delete_char(pos.row, pos.col - 1) {
-                self.wrap_cache.invalidate_line(pos.row);
-                // Reactive highlight update
-                self.update_row_highlights(pos.row);
-                self.history.record(
-                    EditOperation::Delete {
-                        start: Position::new(pos.row, pos.col - 1),
-                        end: pos,
-                        deleted_text: c.to_string(),
-                    },
-                    cursor_before,
-                    self.cursor.pos(),
-                );
-            }
-        } else if pos.row > 0 {
-            let prev_len = self.buffer.line_len(pos.row - 1);
-            let cursor_before = pos;
+            let start_col = grapheme::prev_boundary(self.buffer.line(pos.row).unwrap_or(""), pos.col);
+            self.cursor.move_to(pos.row, start_col);
+            let deleted = self.buffer.delete_range(pos.row, start_col, pos.col);
+            self.wrap_cache.invalidate_line(pos.row);
+            // Reactive highlight update
+            self.update_row_highlights(pos.row);
+            self.history.record(
+                EditOperation::Delete {
+                    start: Position::new(pos.row, start_col),
+                    end: pos,
+                    deleted_text: deleted,
+                },
+                pos,
+                self.cursor.pos(),
+            );
+        } else if pos.row > 0 {
+            let prev_len = self.buffer.line_len(pos.row - 1);
+            let cursor_before = pos;
 
             self.buffer.join_with_previous(pos.row);
             self.wrap_cache.remove_line(pos.row);
             self.wrap_cache.invalidate_line(pos.row - 1);
 
-            self.highlight_index.shift_rows_after(pos.row, -1);
-            self.row_style_cache
-                .borrow_mut()
-                .shift_rows_after(pos.row, -1);
+            self.splice_rows(pos.row, 1, 0);
             self.update_row_highlights(pos.row - 1);
 
             self.history.record(
@@ -2717,25 +3302,332 @@ impl Editor {
         self.ensure_cursor_visible();
     }
 
-    fn delete_selection_internal(&mut self) {
-        if let Some((start, end)) = self.cursor.selection_range() {
-            let lines_deleted = end.row - start.row;
-            self.buffer
-                .delete_text_range(start.row, start.col, end.row, end.col);
-            self.wrap_cache.invalidate_from(start.row);
+    /// Delete the word before the cursor (insert-mode `Ctrl-W`). At the start
+    /// of a line this joins with the previous line, like Backspace.
+    pub fn delete_word_before_cursor(&mut self) {
+        let pos = self.cursor.pos();
+        if pos.col == 0 {
+            self.delete_newline();
+            return;
+        }
+        let start_col = self
+            .buffer
+            .line(pos.row)
+            .map(|line| cursor::find_word_back(line, pos.col, &self.word_chars))
+            .unwrap_or(0);
+        self.delete_before_cursor_from(start_col);
+    }
 
-            if lines_deleted > 0 {
-                self.highlight_index
-                    .shift_rows_after(end.row + 1, -(lines_deleted as isize));
-                self.row_style_cache
-                    .borrow_mut()
-                    .shift_rows_after(end.row + 1, -(lines_deleted as isize));
-            }
-            self.update_row_highlights(start.row);
+    /// Delete from the cursor to the start of the next word (`Ctrl+Delete`).
+    /// At the end of a line this joins the next line, like Delete.
+    pub fn delete_word_after_cursor(&mut self) {
+        let pos = self.cursor.pos();
+        let end_col = self
+            .buffer
+            .line(pos.row)
+            .map(|line| cursor::find_word_forward(line, pos.col, &self.word_chars))
+            .unwrap_or(pos.col);
+        if end_col <= pos.col {
+            self.delete_char();
+            return;
+        }
+        let deleted = self.buffer.delete_range(pos.row, pos.col, end_col);
+        self.wrap_cache.invalidate_line(pos.row);
+        self.update_row_highlights(pos.row);
+        self.history.record(
+            EditOperation::Delete {
+                start: pos,
+                end: Position::new(pos.row, end_col),
+                deleted_text: deleted,
+            },
+            pos,
+            pos,
+        );
+        self.ensure_cursor_visible();
+    }
 
-            self.cursor.move_to(start.row, start.col);
+    /// Delete everything before the cursor on its line (insert-mode `Ctrl-U`).
+    /// At the start of a line this joins with the previous line, like Backspace.
+    pub fn delete_to_line_start(&mut self) {
+        if self.cursor.pos().col == 0 {
+            self.delete_newline();
+            return;
+        }
+        self.delete_before_cursor_from(0);
+    }
+
+    /// Remove `start_col..cursor` on the cursor row as a single undo step.
+    fn delete_before_cursor_from(&mut self, start_col: usize) {
+        let pos = self.cursor.pos();
+        if start_col >= pos.col {
+            return;
+        }
+        let deleted = self.buffer.delete_range(pos.row, start_col, pos.col);
+        self.wrap_cache.invalidate_line(pos.row);
+        self.update_row_highlights(pos.row);
+        self.history.record(
+            EditOperation::Delete {
+                start: Position::new(pos.row, start_col),
+                end: pos,
+                deleted_text: deleted,
+            },
+            pos,
+            Position::new(pos.row, start_col),
+        );
+        self.cursor.move_to(pos.row, start_col);
+        self.ensure_cursor_visible();
+    }
+
+    /// Delete the selected text as one undo step. Returns false when nothing
+    /// is selected.
+    pub fn delete_selection(&mut self) -> bool {
+        let Some((start, end)) = self.effective_selection_range() else {
+            return false;
+        };
+        if start == end {
+            self.cancel_selection();
+            return false;
+        }
+        let cursor_before = self.cursor.pos();
+        let deleted = self
+            .buffer
+            .delete_text_range(start.row, start.col, end.row, end.col);
+        self.splice_rows(start.row + 1, end.row - start.row, 0);
+        self.wrap_cache.invalidate_from(start.row);
+        self.update_row_highlights(start.row);
+        self.history.record(
+            EditOperation::Delete {
+                start,
+                end,
+                deleted_text: deleted,
+            },
+            cursor_before,
+            start,
+        );
+        self.cancel_selection();
+        self.cursor.move_to(start.row, start.col);
+        self.ensure_cursor_visible();
+        true
+    }
+
+    /// Delete the selection for what's typed over it, returning the edit
+    /// for the caller to record along with its own.
+    #[must_use]
+    fn delete_selection_internal(&mut self) -> Option<EditOperation> {
+        let (start, end) = self.cursor.selection_range()?;
+        let deleted_text = self.buffer.delete_text_range(start.row, start.col, end.row, end.col);
+        self.wrap_cache.invalidate_from(start.row);
+        self.splice_rows(start.row + 1, end.row - start.row, 0);
+        self.update_row_highlights(start.row);
+
+        self.cursor.move_to(start.row, start.col);
+        self.cursor.cancel_selection();
+        Some(EditOperation::Delete { start, end, deleted_text })
+    }
+
+    // Completion
+    /// The partial word before the cursor, as `(start_col, prefix)`.
+    pub fn completion_prefix(&self) -> Option<(usize, String)> {
+        let pos = self.cursor.pos();
+        completion::word_prefix_before(self.buffer.line(pos.row).unwrap_or(""), pos.col)
+    }
+
+    /// Buffer words completing `prefix`, nearest to the cursor first.
+    pub fn completion_candidates(&self, prefix: &str) -> Vec<String> {
+        completion::buffer_candidates(&self.buffer.lines(), self.cursor.pos().row, prefix)
+    }
+
+    /// Replace the text between `start_col` and the cursor with `text`.
+    pub fn replace_before_cursor(&mut self, start_col: usize, text: &str) {
+        self.delete_before_cursor_from(start_col);
+        self.insert_str(text);
+    }
+
+    /// Like [`Self::replace_before_cursor`], but as one undo step, so undoing
+    /// brings back exactly what was typed. `text` may span lines.
+    pub fn expand_before_cursor(&mut self, start_col: usize, text: &str) {
+        let pos = self.cursor.pos();
+        if start_col >= pos.col {
+            return;
+        }
+        let start = Position::new(pos.row, start_col);
+        let deleted_text: String = self
+            .buffer
+            .line(pos.row)
+            .map(|line| line.chars().skip(start_col).take(pos.col - start_col).collect())
+            .unwrap_or_default();
+        let ops = vec![
+            EditOperation::Delete { start, end: pos, deleted_text },
+            EditOperation::Insert { pos: start, text: text.to_string() },
+        ];
+        for op in &ops {
+            self.apply_operation(op);
+        }
+
+        let newlines = text.matches('\n').count();
+        let cursor_after = match text.rsplit_once('\n') {
+            Some((_, last)) => Position::new(pos.row + newlines, last.chars().count()),
+            None => Position::new(pos.row, start_col + text.chars().count()),
+        };
+        if newlines > 0 {
+            self.recalc_code_blocks_from(pos.row);
+        }
+        for row in pos.row..=cursor_after.row {
+            self.update_row_highlights(row);
+        }
+        self.history.record_group(ops, pos, cursor_after);
+        self.cursor.move_to(cursor_after.row, cursor_after.col);
+        self.ensure_cursor_visible();
+    }
+
+    /// Replace whole lines, given as `(row, new_line)`, as one undo step. The
+    /// cursor moves to the start of the last replaced line.
+    pub fn replace_lines(&mut self, changes: &[(usize, String)]) {
+        let cursor_before = self.cursor.pos();
+        let mut ops = Vec::with_capacity(changes.len() * 2);
+        let mut last_row = None;
+
+        for (row, new_line) in changes {
+            let Some(line) = self.buffer.line_mut(*row) else {
+                continue;
+            };
+            let old_line = std::mem::replace(line, new_line.clone());
+            ops.push(EditOperation::Delete {
+                start: Position::new(*row, 0),
+                end: Position::new(*row, old_line.chars().count()),
+                deleted_text: old_line,
+            });
+            ops.push(EditOperation::Insert {
+                pos: Position::new(*row, 0),
+                text: new_line.clone(),
+            });
+            self.wrap_cache.invalidate_line(*row);
+            self.update_row_highlights(*row);
+            last_row = Some(*row);
+        }
+
+        if let Some(row) = last_row {
+            let cursor_after = Position::new(row, 0);
+            self.history.record_group(ops, cursor_before, cursor_after);
+            self.cursor.move_to(row, 0);
             self.cursor.cancel_selection();
+            self.ensure_cursor_visible();
+        }
+    }
+
+    /// Replace the chars from `start_col` to `end_col` of `row` with `text`,
+    /// which has no newlines, as one undo step. The cursor keeps its place in
+    /// the text around it.
+    pub fn replace_range(&mut self, row: usize, start_col: usize, end_col: usize, text: &str) {
+        let Some(line) = self.buffer.line(row) else {
+            return;
+        };
+        let deleted_text: String = line.chars().skip(start_col).take(end_col - start_col).collect();
+        let cursor_before = self.cursor.pos();
+        let ops = vec![
+            EditOperation::Delete {
+                start: Position::new(row, start_col),
+                end: Position::new(row, end_col),
+                deleted_text,
+            },
+            EditOperation::Insert { pos: Position::new(row, start_col), text: text.to_string() },
+        ];
+        for op in &ops {
+            self.apply_operation(op);
+        }
+        self.update_row_highlights(row);
+
+        let cursor_after = if cursor_before.row == row && cursor_before.col >= end_col {
+            Position::new(row, cursor_before.col - end_col + start_col + text.chars().count())
+        } else {
+            cursor_before
+        };
+        self.history.record_group(ops, cursor_before, cursor_after);
+        self.cursor.move_to(cursor_after.row, cursor_after.col);
+        self.ensure_cursor_visible();
+    }
+
+    /// Wrap the selection as `[selection](url)` in one undo step, leaving the
+    /// cursor after the closing paren. Returns false without a selection.
+    pub fn wrap_selection_in_link(&mut self, url: &str) -> bool {
+        let Some((start, end)) = self.effective_selection_range() else {
+            return false;
+        };
+        if start == end {
+            return false;
         }
+        let cursor_before = self.cursor.pos();
+        let closing = format!("]({})", url);
+        // Close first so the opening bracket doesn't shift the end
+        let ops = vec![
+            EditOperation::Insert { pos: end, text: closing.clone() },
+            EditOperation::Insert { pos: start, text: "[".to_string() },
+        ];
+        for op in &ops {
+            self.apply_operation(op);
+        }
+        let shift = if start.row == end.row { 1 } else { 0 };
+        let cursor_after = Position::new(end.row, end.col + shift + closing.chars().count());
+        self.history.record_group(ops, cursor_before, cursor_after);
+        self.cancel_selection();
+        self.cursor.move_to(cursor_after.row, cursor_after.col);
+        self.ensure_cursor_visible();
+        true
+    }
+
+    /// Replace the whole buffer with `lines` as one undo step, keeping the
+    /// cursor on its row where the buffer is still long enough.
+    pub fn replace_all_lines(&mut self, lines: Vec<String>) {
+        let lines = if lines.is_empty() { vec![String::new()] } else { lines };
+        let old: Vec<String> = self.buffer.lines().iter().map(|l| l.to_string()).collect();
+        if old == lines {
+            return;
+        }
+        let cursor_before = self.cursor.pos();
+        // Insert before deleting so the buffer never runs out of lines
+        let ops = vec![
+            EditOperation::LineInsert { row: 0, lines: lines.clone() },
+            EditOperation::LineDelete { row: lines.len(), lines: old },
+        ];
+        for op in &ops {
+            self.apply_operation(op);
+        }
+        let row = cursor_before.row.min(lines.len() - 1);
+        let cursor_after = Position::new(row, 0);
+        self.history.record_group(ops, cursor_before, cursor_after);
+        self.cursor.move_to(row, 0);
+        self.cursor.cancel_selection();
+        self.ensure_cursor_visible();
+    }
+
+    /// Replace the rows in `rows` with `lines`, which may be more or fewer,
+    /// as one undo step. An empty range puts `lines` in before its start. The
+    /// cursor moves to the first of them.
+    pub fn replace_rows(&mut self, rows: Range<usize>, lines: Vec<String>) {
+        let end = rows.end.min(self.buffer.line_count());
+        let start = rows.start.min(end);
+        let old: Vec<String> = (start..end).filter_map(|row| self.buffer.line(row).map(str::to_string)).collect();
+        if old == lines {
+            return;
+        }
+        let cursor_before = self.cursor.pos();
+        // Insert before deleting so the buffer never runs out of lines
+        let mut ops = Vec::with_capacity(2);
+        if !lines.is_empty() {
+            ops.push(EditOperation::LineInsert { row: start, lines: lines.clone() });
+        }
+        if !old.is_empty() {
+            ops.push(EditOperation::LineDelete { row: start + lines.len(), lines: old });
+        }
+        for op in &ops {
+            self.apply_operation(op);
+        }
+        let row = start.min(self.buffer.line_count().saturating_sub(1));
+        let cursor_after = Position::new(row, 0);
+        self.history.record_group(ops, cursor_before, cursor_after);
+        self.cursor.move_to(row, 0);
+        self.cursor.cancel_selection();
+        self.ensure_cursor_visible();
     }
 
     // Undo/Redo
@@ -2799,21 +3691,25 @@ impl Editor {
                     self.buffer.insert_str(pos.row, pos.col, text);
                 }
                 self.wrap_cache.invalidate_from(pos.row);
+                self.splice_rows(pos.row + 1, 0, text.matches('\n').count());
             }
             EditOperation::Delete { start, end, .. } => {
                 self.buffer
                     .delete_text_range(start.row, start.col, end.row, end.col);
                 self.wrap_cache.invalidate_from(start.row);
+                self.splice_rows(start.row + 1, end.row - start.row, 0);
             }
             EditOperation::SplitLine { pos } => {
                 self.buffer.split_line(pos.row, pos.col);
                 self.wrap_cache.insert_line(pos.row + 1);
                 self.wrap_cache.invalidate_line(pos.row);
+                self.splice_rows(pos.row + 1, 0, 1);
             }
             EditOperation::JoinLine { row, .. } => {
                 self.buffer.join_with_previous(*row);
                 self.wrap_cache.remove_line(*row);
                 self.wrap_cache.invalidate_line(row - 1);
+                self.splice_rows(*row, 1, 0);
             }
             EditOperation::BlockDelete {
                 start_row,
@@ -2859,26 +3755,56 @@ impl Editor {
                     self.buffer.insert_line(row + i, line.clone());
                     self.wrap_cache.insert_line(row + i);
                 }
+                self.splice_rows(*row, 0, lines.len());
             }
             EditOperation::LineDelete { row, lines } => {
+                let lines_before = self.buffer.line_count();
                 for _ in 0..lines.len() {
                     if *row < self.buffer.line_count() {
                         self.buffer.delete_line(*row);
                         self.wrap_cache.remove_line(*row);
                     }
                 }
+                self.rows_replaced(*row, lines.len(), lines_before);
             }
         }
     }
 
     // Input processing
     pub fn input(&mut self, key: KeyEvent) {
-        match process_key(key) {
+        let action = process_key(key);
+        let typing = matches!(action, InputAction::InsertChar(_) | InputAction::InsertNewline);
+        let deleting = matches!(
+            action,
+            InputAction::DeleteChar
+                | InputAction::DeleteCharBefore
+                | InputAction::DeleteWordBefore
+                | InputAction::DeleteWordAfter
+        );
+        // Typing replaces a selection and deleting removes just the selection,
+        // like mainstream editors
+        let deleted = (typing || deleting) && self.delete_selection();
+        if deleted && deleting {
+            return;
+        }
+        match action {
             InputAction::InsertChar(c) => self.insert_char(c),
             InputAction::InsertNewline => self.insert_newline(),
             InputAction::DeleteChar => self.delete_char(),
             InputAction::DeleteCharBefore => self.delete_newline(),
-            InputAction::Move(movement) => self.move_cursor(movement),
+            InputAction::DeleteWordBefore => self.delete_word_before_cursor(),
+            InputAction::DeleteWordAfter => self.delete_word_after_cursor(),
+            InputAction::Move(movement) => {
+                // A plain movement drops the selection
+                self.cancel_selection();
+                self.move_cursor(movement);
+            }
+            InputAction::Select(movement) => {
+                if !self.has_selection() {
+                    self.start_selection();
+                }
+                self.move_cursor(movement);
+            }
             InputAction::None => {}
         }
     }
@@ -2904,16 +3830,24 @@ impl Editor {
         }
 
         let (cursor_row, cursor_col) = self.cursor();
-        let line_count = self.buffer.line_count();
-
-        let effective_scrolloff = self.scrolloff.min(view_height / 2);
-
-        if cursor_row < self.scroll_offset + effective_scrolloff {
-            self.scroll_offset = cursor_row.saturating_sub(effective_scrolloff);
+        if self.typewriter_paused_at.is_some_and(|at| at != (cursor_row, cursor_col)) {
+            self.typewriter_paused_at = None;
         }
 
+        // Typewriter mode is a scrolloff reaching to the middle band
+        let effective_scrolloff = if self.typewriter_active() {
+            (view_height.saturating_sub(1) / 2).saturating_sub(TYPEWRITER_BAND)
+        } else {
+            self.scrolloff.min(view_height / 2)
+        };
+
         if self.line_wrap_enabled && self.view_width > 0 {
             let (cursor_visual_offset, _) = self.cursor_wrapped_position();
+            // Counted in screen lines, as wrapped lines above take several
+            self.scroll_offset = self.scroll_offset.min(cursor_row);
+            while self.scroll_offset > 0 && self.cursor_screen_row() < effective_scrolloff {
+                self.scroll_offset -= 1;
+            }
             while self.scroll_offset < cursor_row {
                 let lines_before = self.visual_lines_in_range(self.scroll_offset, cursor_row - 1);
                 let total_lines = lines_before + cursor_visual_offset + 1;
@@ -2923,6 +3857,9 @@ impl Editor {
                 self.scroll_offset += 1;
             }
         } else {
+            if cursor_row < self.scroll_offset + effective_scrolloff {
+                self.scroll_offset = cursor_row.saturating_sub(effective_scrolloff);
+            }
             if cursor_row + effective_scrolloff >= self.scroll_offset + view_height {
                 self.scroll_offset = cursor_row
                     .saturating_add(effective_scrolloff)
@@ -2930,9 +3867,7 @@ impl Editor {
             }
         }
 
-        // Clamp to valid range
-        let max_scroll = line_count.saturating_sub(1);
-        self.scroll_offset = self.scroll_offset.min(max_scroll);
+        self.scroll_offset = self.scroll_offset.min(self.max_scroll());
 
         if self.view_width > 0 {
             let effective_width = self.view_width.saturating_sub(1);
@@ -2944,7 +3879,36 @@ impl Editor {
         }
     }
 
+    /// The furthest the view scrolls down: with `scroll_past_end`, or in
+    /// typewriter mode, until the last line is at the top, otherwise until
+    /// it reaches the bottom.
+    pub fn max_scroll(&self) -> usize {
+        let last = self.buffer.line_count().saturating_sub(1);
+        if self.scroll_past_end || self.typewriter {
+            return last;
+        }
+        if !self.line_wrap_enabled || self.view_width == 0 {
+            return (last + 1).saturating_sub(self.view_height).min(last);
+        }
+        let mut top = last + 1;
+        let mut used = 0;
+        while top > 0 {
+            used += self.visual_lines_in_range(top - 1, top - 1);
+            if used > self.view_height {
+                break;
+            }
+            top -= 1;
+        }
+        top.min(last)
+    }
+
     fn visual_lines_for_row(&self, row: usize, content_width: usize) -> usize {
+        if self.folds.hides(row) {
+            return 0;
+        }
+        if self.folds.folded_at(row).is_some() {
+            return 1;
+        }
         let line = match self.buffer.line(row) {
             Some(l) => l,
             None => return 1,
@@ -2953,6 +3917,8 @@ impl Editor {
         if chars.is_empty() {
             return 1;
         }
+        let concealed = self.concealed_columns(row);
+        let cells = grapheme::cells(line, self.tab_width);
 
         let mut col = 0;
         let mut visual_lines = 1;
@@ -2967,10 +3933,13 @@ impl Editor {
                 }
             }
 
-            while col < chars.len() && x < content_width {
-                let ch = chars[col];
-                let ch_width = char_display_width(ch, self.tab_width) as usize;
-                x += ch_width;
+            while col < chars.len() && (x < content_width || cells[col].is_continuation()) {
+                if !conceal::is_concealed(&concealed, col) {
+                    x += cells[col].width as usize;
+                }
+                col += 1;
+            }
+            while conceal::is_concealed(&concealed, col) {
                 col += 1;
             }
 
@@ -3025,16 +3994,11 @@ impl Editor {
 
         let pos = self.cursor.pos();
         let line = self.buffer.line(pos.row).unwrap_or("");
-        let chars: Vec<char> = line.chars().collect();
-
-        let mut display_offset: usize = 0;
-        for (i, ch) in chars.iter().enumerate() {
-            if i >= self.h_scroll_offset {
-                break;
-            }
-            display_offset += char_display_width(*ch, self.tab_width) as usize;
-        }
-        display_offset
+        grapheme::cells(line, self.tab_width)
+            .iter()
+            .take(self.h_scroll_offset)
+            .map(|cell| cell.width as usize)
+            .sum()
     }
 
     pub fn line_number_gutter_width(&self) -> u16 {
@@ -3044,44 +4008,44 @@ impl Editor {
             0
         }
     }
+    /// Width of everything left of the text: line numbers plus the change-marker column.
+    pub fn gutter_width(&self) -> u16 {
+        self.change_marker_width() + self.line_number_gutter_width()
+    }
+
     pub fn content_left_offset(&self) -> u16 {
-        self.left_padding + self.line_number_gutter_width()
+        self.left_padding + self.gutter_width()
     }
 
     /// Returns the cursor's display column position, accounting for Unicode character widths and tabs.
     pub fn cursor_display_col(&self) -> usize {
         let pos = self.cursor.pos();
         let line = self.buffer.line(pos.row).unwrap_or("");
-        let chars: Vec<char> = line.chars().collect();
-
-        let mut display_col: usize = 0;
-        for (i, ch) in chars.iter().enumerate() {
-            if i >= pos.col {
-                break;
-            }
-            display_col += char_display_width(*ch, self.tab_width) as usize;
-        }
-        display_col
+        grapheme::cells(line, self.tab_width)
+            .iter()
+            .take(pos.col)
+            .map(|cell| cell.width as usize)
+            .sum()
     }
 
     /// Returns the cursor screen position info for native cursor positioning.
     pub fn cursor_screen_info(&self) -> (usize, bool, usize) {
         let pos = self.cursor.pos();
         let line = self.buffer.line(pos.row).unwrap_or("");
-        let chars: Vec<char> = line.chars().collect();
+        let cells = grapheme::cells(line, self.tab_width);
 
         let mut display_col: usize = 0;
         let mut line_display_width: usize = 0;
 
-        for (i, ch) in chars.iter().enumerate() {
-            let ch_width = char_display_width(*ch, self.tab_width) as usize;
+        for (i, cell) in cells.iter().enumerate() {
+            let ch_width = cell.width as usize;
             if i < pos.col {
                 display_col += ch_width;
             }
             line_display_width += ch_width;
         }
 
-        let is_at_line_end = pos.col >= chars.len();
+        let is_at_line_end = pos.col >= cells.len();
         (display_col, is_at_line_end, line_display_width)
     }
 
@@ -3111,6 +4075,7 @@ impl Editor {
         if chars.is_empty() || content_width == 0 {
             return 0;
         }
+        let cells = grapheme::cells(line, self.tab_width);
 
         let mut col = 0;
         let mut visual_line = 0;
@@ -3126,23 +4091,23 @@ impl Editor {
 
             if visual_line == target_visual_line {
                 let mut x = 0;
-                while col < chars.len() && x < content_width {
-                    let w = char_display_width(chars[col], self.tab_width) as usize;
+                while col < chars.len() && (x < content_width || cells[col].is_continuation()) {
+                    let w = cells[col].width as usize;
                     if x + w > target_x {
-                        return col;
+                        return grapheme::floor_boundary(line, col);
                     }
                     x += w;
                     col += 1;
                 }
                 if col < chars.len() {
-                    return col.saturating_sub(1);
+                    return grapheme::prev_boundary(line, col);
                 }
                 return col;
             }
 
             let mut x = 0;
-            while col < chars.len() && x < content_width {
-                x += char_display_width(chars[col], self.tab_width) as usize;
+            while col < chars.len() && (x < content_width || cells[col].is_continuation()) {
+                x += cells[col].width as usize;
                 col += 1;
             }
             is_wrapped_continuation = true;
@@ -3170,9 +4135,10 @@ impl Editor {
         let pos = self.cursor.pos();
         let line = self.buffer.line(pos.row).unwrap_or("");
         let chars: Vec<char> = line.chars().collect();
-        if chars.is_empty() {
+        if chars.is_empty() || self.folds.folded_at(pos.row).is_some() {
             return (0, 0);
         }
+        let cells = grapheme::cells(line, self.tab_width);
 
         let mut col = 0;
         let mut visual_line: usize = 0;
@@ -3194,9 +4160,8 @@ impl Editor {
                 }
             }
 
-            while col < chars.len() && x < content_width {
-                let ch = chars[col];
-                let ch_width = char_display_width(ch, self.tab_width) as usize;
+            while col < chars.len() && (x < content_width || cells[col].is_continuation()) {
+                let ch_width = cells[col].width as usize;
 
                 if col == pos.col {
                     return (visual_line, x);
@@ -3226,15 +4191,21 @@ impl Editor {
         if content_width == 0 {
             return 1;
         }
+        if self.folds.hides(row) {
+            return 0;
+        }
 
         let line = self.buffer.line(row).unwrap_or("");
-        if line.is_empty() {
+        if line.is_empty() || self.folds.folded_at(row).is_some() {
             return 1;
         }
 
+        let concealed = self.concealed_columns(row);
         let mut display_width: usize = 0;
-        for ch in line.chars() {
-            display_width += char_display_width(ch, self.tab_width) as usize;
+        for (col, cell) in grapheme::cells(line, self.tab_width).iter().enumerate() {
+            if !conceal::is_concealed(&concealed, col) {
+                display_width += cell.width as usize;
+            }
         }
         ((display_width + content_width - 1) / content_width).max(1)
     }
@@ -3250,19 +4221,69 @@ impl Editor {
     pub fn scroll_cursor_to_top(&mut self) {
         let (cursor_row, _) = self.cursor();
         self.scroll_offset = cursor_row;
+        self.pause_typewriter();
     }
 
     /// Scroll so cursor line is at bottom of screen (zb command)
     pub fn scroll_cursor_to_bottom(&mut self) {
         let (cursor_row, _) = self.cursor();
         self.scroll_offset = cursor_row.saturating_sub(self.view_height.saturating_sub(1));
+        self.pause_typewriter();
     }
 
+    /// A new width reflows wrapped lines, so the scroll is adjusted to keep
+    /// the cursor on the same screen row; see [`Editor::stabilize_viewport`].
     pub fn set_view_size(&mut self, width: usize, height: usize) {
+        if self.view_width > 0 && width != self.view_width {
+            self.stabilize_viewport(self.view_width, width);
+        }
         self.view_width = width;
         self.view_height = height;
     }
 
+    /// Scroll so the cursor stays on the screen row it had at `old_width`
+    /// now that the view is `new_width` wide, give or take a row where the
+    /// lines above no longer fit exactly. Leaves `new_width` in effect.
+    pub fn stabilize_viewport(&mut self, old_width: usize, new_width: usize) {
+        self.view_width = old_width;
+        let screen_row = self.cursor_screen_row();
+        self.view_width = new_width;
+        if !self.line_wrap_enabled || old_width == new_width {
+            return;
+        }
+
+        let (cursor_row, _) = self.cursor();
+        let mut used = self.cursor_wrapped_position().0;
+        let mut top = cursor_row;
+        while top > 0 {
+            let above = self.visual_lines_in_range(top - 1, top - 1);
+            if used + above > screen_row {
+                // One row too many may still be nearer, if it stays on screen
+                if used + above - screen_row < screen_row - used && used + above < self.view_height {
+                    top -= 1;
+                }
+                break;
+            }
+            used += above;
+            top -= 1;
+        }
+        self.scroll_offset = top;
+    }
+
+    /// Screen lines between the top of the view and the cursor.
+    fn cursor_screen_row(&self) -> usize {
+        let (cursor_row, _) = self.cursor();
+        let above = if self.line_wrap_enabled && self.view_width > 0 {
+            match cursor_row.checked_sub(1) {
+                Some(last) if cursor_row > self.scroll_offset => self.visual_lines_in_range(self.scroll_offset, last),
+                _ => 0,
+            }
+        } else {
+            cursor_row.saturating_sub(self.scroll_offset)
+        };
+        above + self.cursor_wrapped_position().0
+    }
+
     pub fn get_overflow_info(&self) -> (bool, bool) {
         let (cursor_row, _) = self.cursor();
         let line_len = self.buffer.line_len(cursor_row);
@@ -3273,17 +4294,15 @@ impl Editor {
     }
 
     pub fn content_x_offset(&self) -> u16 {
-        let gutter_width = if self.line_number_mode != LineNumberMode::None {
-            self.line_number_width
-        } else {
-            0
-        };
-        self.left_padding + gutter_width
+        self.left_padding + self.gutter_width()
     }
 
     pub fn visual_to_logical_coords(&self, visual_y: usize, visual_x: usize) -> (usize, usize) {
         if !self.line_wrap_enabled || self.view_width == 0 {
-            let row = visual_y + self.scroll_offset;
+            let row = (self.scroll_offset..self.buffer.line_count())
+                .filter(|&row| !self.folds.hides(row))
+                .nth(visual_y)
+                .unwrap_or(visual_y + self.scroll_offset);
             let col = visual_x + self.h_scroll_offset;
             return (row, col);
         }
@@ -3302,10 +4321,14 @@ impl Editor {
         let mut row = self.scroll_offset;
 
         while row < line_count {
+            if self.folds.hides(row) {
+                row += 1;
+                continue;
+            }
             let line = self.buffer.line(row).unwrap_or("");
             let chars: Vec<char> = line.chars().collect();
 
-            if chars.is_empty() {
+            if chars.is_empty() || self.folds.folded_at(row).is_some() {
                 if visual_lines_consumed == visual_y {
                     return (row, 0);
                 }
@@ -3314,6 +4337,8 @@ impl Editor {
                 continue;
             }
 
+            let concealed = self.concealed_columns(row);
+            let cells = grapheme::cells(line, self.tab_width);
             let mut col_idx = 0;
             let mut visual_line_of_row = 0;
 
@@ -3333,20 +4358,25 @@ impl Editor {
                     }
                 }
 
-                while col_idx < chars.len() && x < content_width {
-                    let ch = chars[col_idx];
-                    let ch_width = char_display_width(ch, self.tab_width) as usize;
-                    x += ch_width;
+                while col_idx < chars.len() && (x < content_width || cells[col_idx].is_continuation()) {
+                    if !conceal::is_concealed(&concealed, col_idx) {
+                        x += cells[col_idx].width as usize;
+                    }
+                    col_idx += 1;
+                }
+                while conceal::is_concealed(&concealed, col_idx) {
                     col_idx += 1;
                 }
 
                 if visual_lines_consumed + visual_line_of_row == visual_y {
                     let mut target_x: usize = 0;
                     for i in visual_line_start..col_idx {
-                        let ch = chars[i];
-                        let ch_width = char_display_width(ch, self.tab_width) as usize;
+                        if conceal::is_concealed(&concealed, i) {
+                            continue;
+                        }
+                        let ch_width = cells[i].width as usize;
                         if target_x + ch_width > visual_x {
-                            return (row, i);
+                            return (row, grapheme::floor_boundary(line, i));
                         }
                         target_x += ch_width;
                     }
@@ -3400,25 +4430,25 @@ impl Editor {
         buf: &mut RatatuiBuffer,
         x: u16,
         y: u16,
-        ch: char,
+        symbol: &str,
         base_style: Style,
     ) {
         if let Some(cell) = buf.cell_mut((x, y)) {
             match self.cursor_shape {
                 CursorShape::Block => {
                     // Full reversed block for Normal mode
-                    cell.set_char(ch);
+                    cell.set_symbol(symbol);
                     cell.set_style(base_style.add_modifier(Modifier::REVERSED));
                 }
                 CursorShape::Bar => {
                     // For bar cursor, don't render custom cursor - use terminal's native cursor
                     // Just render the character normally, terminal cursor will be positioned here
-                    cell.set_char(ch);
+                    cell.set_symbol(symbol);
                     cell.set_style(base_style);
                 }
                 CursorShape::Underline => {
                     // Underline + Reversed for Replace mode - more visible than underline alone
-                    cell.set_char(ch);
+                    cell.set_symbol(symbol);
                     cell.set_style(
                         base_style.add_modifier(Modifier::UNDERLINED | Modifier::REVERSED),
                     );
@@ -3433,12 +4463,10 @@ impl Editor {
     }
 
     fn render_wrapped(&self, area: Rect, buf: &mut RatatuiBuffer) {
-        // Account for line number gutter
-        let gutter_width = if self.line_number_mode != LineNumberMode::None {
-            self.line_number_width
-        } else {
-            0
-        };
+        // Account for line number and change marker gutter
+        let gutter_width = self.gutter_width();
+        let marker_x = area.x + self.left_padding;
+        let line_number_x = marker_x + self.change_marker_width();
         let content_start_x = area.x + self.left_padding + gutter_width;
         let content_end_x = area.x + area.width.saturating_sub(self.right_padding);
         let content_width = content_end_x.saturating_sub(content_start_x) as usize;
@@ -3471,7 +4499,7 @@ impl Editor {
         } else {
             self.effective_selection_range()
         };
-        let block_selection = self.visual_block_selection;
+        let block_selection = self.visual_block_rect();
         let line_count = self.buffer.line_count();
 
         // Use row-based scrolling (consistent with update_scroll)
@@ -3483,11 +4511,16 @@ impl Editor {
             if screen_y >= area.y + area.height {
                 break;
             }
+            if self.folds.hides(row) {
+                continue;
+            }
 
             let line = self.buffer.line(row).unwrap_or("");
             let is_cursor_line = row == cursor_pos.row;
             let chars: Vec<char> = line.chars().collect();
 
+            self.render_change_marker(buf, marker_x, screen_y, row);
+
             // Render line numbers if enabled (only for first visual line of a row)
             if let Some(ln_str) = self.get_line_number_str(row, cursor_pos.row) {
                 let ln_style = if is_cursor_line {
@@ -3496,18 +4529,22 @@ impl Editor {
                     self.line_number_style
                 };
                 for (i, ch) in ln_str.chars().enumerate() {
-                    if let Some(cell) =
-                        buf.cell_mut((area.x + self.left_padding + i as u16, screen_y))
-                    {
+                    if let Some(cell) = buf.cell_mut((line_number_x + i as u16, screen_y)) {
                         cell.set_char(ch);
                         cell.set_style(ln_style);
                     }
                 }
             }
 
+            if self.folds.folded_at(row).is_some() {
+                self.render_fold_summary(buf, content_start_x, content_end_x, screen_y, row);
+                screen_y += 1;
+                continue;
+            }
+
             if chars.is_empty() {
                 if is_cursor_line {
-                    self.render_cursor_at(buf, content_start_x, screen_y, ' ', Style::default());
+                    self.render_cursor_at(buf, content_start_x, screen_y, " ", Style::default());
                 }
                 screen_y += 1;
                 continue;
@@ -3515,6 +4552,8 @@ impl Editor {
 
             // Get cached row styles once per row (O(1) per char instead of O(H) per char)
             let row_styles = self.get_row_styles_cached(row);
+            let concealed = self.concealed_columns(row);
+            let cells = grapheme::cells(line, self.tab_width);
 
             // Render line with wrapping
             let mut col = 0;
@@ -3532,7 +4571,7 @@ impl Editor {
                         col += 1;
                         if col >= chars.len() {
                             if is_cursor_line && cursor_pos.col >= chars.len() {
-                                self.render_cursor_at(buf, x, screen_y, ' ', Style::default());
+                                self.render_cursor_at(buf, x, screen_y, " ", Style::default());
                             }
                             screen_y += 1;
                             break;
@@ -3540,20 +4579,25 @@ impl Editor {
                     }
                 }
 
-                while col < chars.len() && x < content_end_x {
+                while col < chars.len() && (x < content_end_x || cells[col].is_continuation()) {
+                    // The rest of a cluster is drawn with its first char
+                    if conceal::is_concealed(&concealed, col) || cells[col].is_continuation() {
+                        col += 1;
+                        continue;
+                    }
                     let ch = chars[col];
                     let base_style =
                         self.get_char_style_fast(&row_styles, col, row, selection, block_selection);
                     let is_cursor = is_cursor_line && col == cursor_pos.col;
 
-                    let ch_width = char_display_width(ch, self.tab_width);
+                    let ch_width = cells[col].width;
                     if ch == '\t' {
                         for i in 0..ch_width {
                             if x >= content_end_x {
                                 break;
                             }
                             if i == 0 && is_cursor {
-                                self.render_cursor_at(buf, x, screen_y, ' ', base_style);
+                                self.render_cursor_at(buf, x, screen_y, " ", base_style);
                             } else if let Some(cell) = buf.cell_mut((x, screen_y)) {
                                 cell.set_char(' ');
                                 cell.set_style(base_style);
@@ -3561,42 +4605,47 @@ impl Editor {
                             x += 1;
                         }
                     } else {
+                        let symbol = grapheme::cluster_at(&chars, &cells, col);
                         if is_cursor {
-                            self.render_cursor_at(buf, x, screen_y, ch, base_style);
+                            self.render_cursor_at(buf, x, screen_y, &symbol, base_style);
                         } else if let Some(cell) = buf.cell_mut((x, screen_y)) {
-                            cell.set_char(ch);
+                            cell.set_symbol(&symbol);
                             cell.set_style(base_style);
                         }
                         x += ch_width;
                     }
                     col += 1;
                 }
+                while conceal::is_concealed(&concealed, col) {
+                    col += 1;
+                }
 
                 // Render cursor at end of line if cursor is past last char
                 // Use full area width to allow cursor in right padding
                 if is_cursor_line && cursor_pos.col >= chars.len() && col == chars.len() {
                     if x < area.x + area.width {
-                        self.render_cursor_at(buf, x, screen_y, ' ', Style::default());
+                        self.render_cursor_at(buf, x, screen_y, " ", Style::default());
                     }
                 }
 
+                if !is_wrapped_continuation {
+                    self.render_indent_guides(buf, content_start_x, content_end_x, screen_y, row);
+                }
                 is_wrapped_continuation = true;
                 screen_y += 1;
             }
         }
 
         if self.buffer.is_empty() {
-            self.render_cursor_at(buf, content_start_x, area.y, ' ', Style::default());
+            self.render_cursor_at(buf, content_start_x, area.y, " ", Style::default());
         }
     }
 
     fn render_no_wrap(&self, area: Rect, buf: &mut RatatuiBuffer) {
-        // Account for line number gutter
-        let gutter_width = if self.line_number_mode != LineNumberMode::None {
-            self.line_number_width
-        } else {
-            0
-        };
+        // Account for line number and change marker gutter
+        let gutter_width = self.gutter_width();
+        let marker_x = area.x + self.left_padding;
+        let line_number_x = marker_x + self.change_marker_width();
         let content_start_x = area.x + self.left_padding + gutter_width;
         let content_end_x = area.x + area.width.saturating_sub(self.right_padding);
 
@@ -3625,22 +4674,26 @@ impl Editor {
         } else {
             self.effective_selection_range()
         };
-        let block_selection = self.visual_block_selection;
+        let block_selection = self.visual_block_rect();
         let h_scroll = self.h_scroll_offset;
 
         let mut y = area.y;
-        let end_row = (self.scroll_offset + area.height as usize).min(self.buffer.line_count());
 
-        for row in self.scroll_offset..end_row {
+        for row in self.scroll_offset..self.buffer.line_count() {
             if y >= area.y + area.height {
                 break;
             }
+            if self.folds.hides(row) {
+                continue;
+            }
 
             let line = self.buffer.line(row).unwrap_or("");
             let is_cursor_line = row == cursor_pos.row;
             let chars: Vec<char> = line.chars().collect();
             let line_h_scroll = if is_cursor_line { h_scroll } else { 0 };
 
+            self.render_change_marker(buf, marker_x, y, row);
+
             // Render line numbers if enabled
             if let Some(ln_str) = self.get_line_number_str(row, cursor_pos.row) {
                 let ln_style = if is_cursor_line {
@@ -3649,34 +4702,46 @@ impl Editor {
                     self.line_number_style
                 };
                 for (i, ch) in ln_str.chars().enumerate() {
-                    if let Some(cell) = buf.cell_mut((area.x + self.left_padding + i as u16, y)) {
+                    if let Some(cell) = buf.cell_mut((line_number_x + i as u16, y)) {
                         cell.set_char(ch);
                         cell.set_style(ln_style);
                     }
                 }
             }
 
+            if self.folds.folded_at(row).is_some() {
+                self.render_fold_summary(buf, content_start_x, content_end_x, y, row);
+                y += 1;
+                continue;
+            }
+
             let row_styles = self.get_row_styles_cached(row);
+            let concealed = self.concealed_columns(row);
+            let cells = grapheme::cells(line, self.tab_width);
 
             let mut x = content_start_x;
             for col in line_h_scroll..chars.len() {
                 if x >= content_end_x {
                     break;
                 }
+                // The rest of a cluster is drawn with its first char
+                if conceal::is_concealed(&concealed, col) || cells[col].is_continuation() {
+                    continue;
+                }
 
                 let ch = chars[col];
                 let base_style =
                     self.get_char_style_fast(&row_styles, col, row, selection, block_selection);
                 let is_cursor = is_cursor_line && col == cursor_pos.col;
 
-                let ch_width = char_display_width(ch, self.tab_width);
+                let ch_width = cells[col].width;
                 if ch == '\t' {
                     for i in 0..ch_width {
                         if x >= content_end_x {
                             break;
                         }
                         if i == 0 && is_cursor {
-                            self.render_cursor_at(buf, x, y, ' ', base_style);
+                            self.render_cursor_at(buf, x, y, " ", base_style);
                         } else if let Some(cell) = buf.cell_mut((x, y)) {
                             cell.set_char(' ');
                             cell.set_style(base_style);
@@ -3684,10 +4749,11 @@ impl Editor {
                         x += 1;
                     }
                 } else {
+                    let symbol = grapheme::cluster_at(&chars, &cells, col);
                     if is_cursor {
-                        self.render_cursor_at(buf, x, y, ch, base_style);
+                        self.render_cursor_at(buf, x, y, &symbol, base_style);
                     } else if let Some(cell) = buf.cell_mut((x, y)) {
-                        cell.set_char(ch);
+                        cell.set_symbol(&symbol);
                         cell.set_style(base_style);
                     }
                     x += ch_width;
@@ -3696,15 +4762,33 @@ impl Editor {
 
             if is_cursor_line && cursor_pos.col >= chars.len() {
                 if x < area.x + area.width {
-                    self.render_cursor_at(buf, x, y, ' ', Style::default());
+                    self.render_cursor_at(buf, x, y, " ", Style::default());
                 }
             }
+            if line_h_scroll == 0 {
+                self.render_indent_guides(buf, content_start_x, content_end_x, y, row);
+            }
 
             y += 1;
         }
 
         if self.buffer.line_count() <= self.scroll_offset {
-            self.render_cursor_at(buf, content_start_x, area.y, ' ', Style::default());
+            self.render_cursor_at(buf, content_start_x, area.y, " ", Style::default());
+        }
+    }
+
+    /// The line the fold at `row` is drawn as, in the color of what it
+    /// folds, with the cursor on its first cell when it's on the fold.
+    fn render_fold_summary(&self, buf: &mut RatatuiBuffer, x: u16, end_x: u16, y: u16, row: usize) {
+        let end = self.folds.folded_at(row).unwrap_or(row);
+        let is_frontmatter = row == 0 && self.frontmatter_end == Some(end);
+        let color = if is_frontmatter { self.frontmatter_color } else { self.code_block_color };
+        let style = Style::default().fg(color).add_modifier(Modifier::ITALIC);
+        let text = fold::summary(self.buffer.line(row).unwrap_or(""), end - row + 1);
+        buf.set_stringn(x, y, &text, end_x.saturating_sub(x) as usize, style);
+        if row == self.cursor.pos().row {
+            let first: String = text.chars().take(1).collect();
+            self.render_cursor_at(buf, x, y, &first, style);
         }
     }
 
@@ -3926,4 +5010,848 @@ mod tests {
         ed.move_cursor(CursorMove::Up);
         assert_eq!(ed.cursor(), (0, 1)); // back to 'b'
     }
+
+    fn mixed_lines() -> Vec<String> {
+        vec!["a".repeat(60), "abc".to_string(), String::new(), "b".repeat(60), "c".repeat(50)]
+    }
+
+    fn walk(ed: &mut Editor, movement: CursorMove, times: usize) -> Vec<(usize, usize)> {
+        (0..times)
+            .map(|_| {
+                ed.move_cursor(movement);
+                ed.cursor()
+            })
+            .collect()
+    }
+
+    /// j/k come back to the column they started on after short and empty lines.
+    #[test]
+    fn vertical_moves_keep_the_preferred_column() {
+        let mut ed = Editor::new(mixed_lines());
+        ed.set_line_number_mode(LineNumberMode::None);
+        ed.set_cursor(0, 47);
+        assert_eq!(walk(&mut ed, CursorMove::Down, 4), [(1, 3), (2, 0), (3, 47), (4, 47)]);
+        assert_eq!(walk(&mut ed, CursorMove::Up, 4), [(3, 47), (2, 0), (1, 3), (0, 47)]);
+
+        // Moving sideways, or typing, starts over from the new column
+        ed.set_cursor(1, 3);
+        ed.move_cursor(CursorMove::Back);
+        assert_eq!(walk(&mut ed, CursorMove::Down, 2), [(2, 0), (3, 2)]);
+        ed.insert_char('x');
+        assert_eq!(walk(&mut ed, CursorMove::Down, 1), [(4, 3)]);
+    }
+
+    /// With wrapping, screen lines that don't reach the preferred column keep
+    /// its screen column; the first one that does puts the cursor back on it.
+    #[test]
+    fn wrapped_vertical_moves_return_to_the_preferred_column() {
+        let mut ed = Editor::new(mixed_lines());
+        ed.set_line_wrap(true);
+        ed.set_line_number_mode(LineNumberMode::None);
+        ed.set_view_size(21, 10); // content width 20: the long lines take 3 screen lines
+        ed.set_cursor(0, 47); // third screen line, screen column 7
+        assert_eq!(
+            walk(&mut ed, CursorMove::Down, 7),
+            [(1, 3), (2, 0), (3, 7), (3, 27), (3, 47), (4, 7), (4, 27)]
+        );
+        ed.move_cursor(CursorMove::Down);
+        assert_eq!(ed.cursor(), (4, 47));
+        assert_eq!(walk(&mut ed, CursorMove::Up, 3), [(4, 27), (4, 7), (3, 47)]);
+    }
+
+    /// Page and half-page motions keep the preferred column too.
+    #[test]
+    fn page_moves_keep_the_preferred_column() {
+        let mut lines = mixed_lines();
+        lines.extend(["x".to_string(), "y".repeat(60)]);
+        let mut ed = Editor::new(lines);
+        ed.set_line_number_mode(LineNumberMode::None);
+        ed.set_view_size(80, 4); // half a page is 2 lines, a page 2 as well
+        ed.set_cursor(0, 47);
+        ed.move_cursor(CursorMove::Down);
+        assert_eq!(walk(&mut ed, CursorMove::HalfPageDown, 2), [(3, 47), (5, 1)]);
+        assert_eq!(walk(&mut ed, CursorMove::PageDown, 1), [(6, 47)]);
+        assert_eq!(walk(&mut ed, CursorMove::PageUp, 1), [(4, 47)]);
+        assert_eq!(walk(&mut ed, CursorMove::HalfPageUp, 1), [(2, 0)]);
+    }
+
+    #[test]
+    fn scroll_past_end_and_typewriter() {
+        let lines: Vec<String> = (0..40).map(|i| format!("line {}", i)).collect();
+        let mut ed = Editor::new(lines);
+        ed.set_line_number_mode(LineNumberMode::None);
+        ed.set_view_size(40, 10);
+        ed.set_cursor(39, 0);
+        ed.update_scroll(10);
+        assert_eq!(ed.scroll_offset(), 30);
+        ed.scroll_cursor_to_top();
+        ed.update_scroll(10);
+        assert_eq!(ed.scroll_offset(), 30, "the last line stays at the bottom");
+        ed.set_scroll_past_end(true);
+        ed.scroll_cursor_to_top();
+        ed.update_scroll(10);
+        assert_eq!(ed.scroll_offset(), 39);
+
+        // Rows 2 to 7 of ten are the middle band
+        ed.set_scroll_past_end(false);
+        ed.set_typewriter(true);
+        ed.set_cursor(20, 0);
+        ed.update_scroll(10);
+        assert_eq!(ed.cursor_screen_row(), 2);
+        ed.move_cursor(CursorMove::Down);
+        ed.update_scroll(10);
+        assert_eq!(ed.cursor_screen_row(), 3);
+        ed.set_cursor(39, 0);
+        ed.update_scroll(10);
+        assert_eq!(ed.cursor_screen_row(), 7);
+
+        // A mouse scroll holds the view until the cursor moves
+        ed.set_scroll_offset(20);
+        ed.set_cursor_no_scroll(29, 0);
+        ed.pause_typewriter();
+        ed.update_scroll(10);
+        assert_eq!(ed.scroll_offset(), 20);
+        ed.move_cursor(CursorMove::Up);
+        ed.update_scroll(10);
+        assert_eq!(ed.cursor_screen_row(), 7);
+
+        // Wrapped lines above count by the rows they take
+        let mut ed = Editor::new(vec!["word ".repeat(30), "a".to_string(), "b".to_string(), "c".to_string()]);
+        ed.set_line_wrap(true);
+        ed.set_line_number_mode(LineNumberMode::None);
+        ed.set_view_size(41, 10);
+        ed.set_typewriter(true);
+        ed.set_cursor(3, 0);
+        ed.update_scroll(10);
+        assert_eq!((ed.scroll_offset(), ed.cursor_screen_row()), (0, 6));
+    }
+
+    /// Narrowing the view rewraps the lines above the cursor; the scroll
+    /// follows so the cursor's line stays where it was on screen.
+    #[test]
+    fn width_change_keeps_the_cursor_screen_row() {
+        let lines: Vec<String> = (0..40)
+            .map(|i| format!("{} {}", i, "lorem ipsum dolor sit amet ".repeat(i % 4 + 1)))
+            .collect();
+        let mut ed = Editor::new(lines);
+        ed.set_line_wrap(true);
+        ed.set_line_number_mode(LineNumberMode::None);
+        ed.set_view_size(81, 30);
+        ed.set_cursor(30, 5);
+        ed.update_scroll(30);
+        let before = ed.cursor_screen_row();
+        assert!(ed.scroll_offset() > 0 && before > 10);
+
+        ed.set_view_size(41, 30);
+        let narrow = ed.cursor_screen_row();
+        assert!(narrow.abs_diff(before) <= 1);
+        ed.set_view_size(81, 30);
+        assert!(ed.cursor_screen_row().abs_diff(narrow) <= 1);
+    }
+
+    fn type_str(ed: &mut Editor, s: &str) {
+        for c in s.chars() {
+            ed.insert_char(c);
+        }
+    }
+
+    /// Insert-mode `Ctrl-W` removes only the last word typed.
+    #[test]
+    fn ctrl_w_deletes_word_before_cursor() {
+        let mut ed = Editor::default();
+        type_str(&mut ed, "foo bar");
+        ed.delete_word_before_cursor();
+        assert_eq!(ed.lines(), vec!["foo "]);
+        assert_eq!(ed.cursor(), (0, 4));
+    }
+
+    /// `Ctrl-W` is a single undo step, independent of the typing before it.
+    #[test]
+    fn ctrl_w_is_one_undo_unit() {
+        let mut ed = Editor::default();
+        type_str(&mut ed, "foo bar");
+        ed.delete_word_before_cursor();
+        ed.undo();
+        assert_eq!(ed.lines(), vec!["foo bar"]);
+    }
+
+    /// `Ctrl-W` at column 0 joins with the previous line like Backspace.
+    #[test]
+    fn ctrl_w_at_line_start_joins_lines() {
+        let mut ed = Editor::new(vec!["foo".to_string(), "bar".to_string()]);
+        ed.set_cursor(1, 0);
+        ed.delete_word_before_cursor();
+        assert_eq!(ed.lines(), vec!["foobar"]);
+    }
+
+    /// `Ctrl-U` deletes back to the start of the line, keeping what follows.
+    #[test]
+    fn ctrl_u_deletes_to_line_start() {
+        let mut ed = editor_with("  hello world");
+        ed.set_cursor(0, 8);
+        ed.delete_to_line_start();
+        assert_eq!(ed.lines(), vec!["world"]);
+        assert_eq!(ed.cursor(), (0, 0));
+        ed.undo();
+        assert_eq!(ed.lines(), vec!["  hello world"]);
+    }
+
+    /// `Ctrl-R"` pastes the unnamed register, which is the editor clipboard.
+    #[test]
+    fn ctrl_r_unnamed_inserts_clipboard() {
+        let mut ed = editor_with("hello");
+        ed.set_cursor(0, 0);
+        ed.start_selection();
+        ed.set_cursor(0, 5);
+        ed.copy();
+        ed.cancel_selection();
+        let vim = crate::vim::VimState::new();
+        let text = vim.register_text('"', ed.clipboard_text()).unwrap();
+        ed.insert_str(&text);
+        assert_eq!(ed.lines(), vec!["hellohello"]);
+    }
+
+    /// Change markers track the baseline and `]c`/`[c` walk the hunks.
+    #[test]
+    fn change_markers_follow_baseline() {
+        let mut ed = Editor::new(vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        ed.set_show_change_markers(true);
+        ed.reset_change_baseline();
+        ed.set_cursor(2, 0);
+        ed.insert_char('x');
+        ed.refresh_change_markers();
+        assert_eq!(ed.change_markers().get(2), Some(LineChange::Modified));
+        assert_eq!(ed.gutter_width(), 1 + ed.line_number_gutter_width());
+
+        ed.set_cursor(0, 0);
+        assert!(ed.goto_change_hunk(true));
+        assert_eq!(ed.cursor(), (2, 0));
+        assert!(!ed.goto_change_hunk(true));
+
+        ed.reset_change_baseline();
+        assert!(ed.change_markers().is_empty());
+    }
+
+    #[test]
+    fn diagnostics_draw_over_highlights() {
+        let mut ed = Editor::new(vec!["**a".to_string(), "ok".to_string(), "[[b".to_string()]);
+        ed.add_highlight(HighlightRange::new(0, 2, 3, Style::default().fg(Color::Green), HighlightType::Bold));
+        let underline = Style::default().add_modifier(Modifier::UNDERLINED);
+        ed.set_diagnostics([
+            HighlightRange::new(0, 0, 3, underline, HighlightType::Diagnostic),
+            HighlightRange::new(2, 0, 3, underline, HighlightType::Diagnostic),
+        ]);
+        assert_eq!(ed.get_row_styles(0)[2], Style::default().fg(Color::Green).add_modifier(Modifier::UNDERLINED));
+
+        ed.set_cursor(0, 0);
+        assert!(ed.goto_diagnostic(true));
+        assert_eq!(ed.cursor(), (2, 0));
+        assert!(!ed.goto_diagnostic(true));
+        assert!(ed.goto_diagnostic(false));
+        assert_eq!(ed.cursor(), (0, 0));
+
+        ed.set_diagnostics([]);
+        assert!(!ed.goto_diagnostic(true));
+        assert_eq!(ed.get_row_styles(0)[0], Style::default());
+    }
+
+    fn rendered_rows(ed: &Editor, width: u16, height: u16) -> Vec<String> {
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = RatatuiBuffer::empty(area);
+        ed.render(area, &mut buf);
+        (0..height)
+            .map(|y| {
+                let row: String = (0..width).map(|x| buf[(x, y)].symbol().to_string()).collect();
+                row.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    // 👩‍💻 is three chars, e + U+0301 two, and 한 as conjoining jamo three
+    const CLUSTERS: &str = "a👩\u{200d}💻e\u{301}\u{1112}\u{1161}\u{11ab}b";
+
+    #[test]
+    fn cursor_moves_by_grapheme_cluster() {
+        let mut ed = Editor::new(vec![CLUSTERS.to_string()]);
+        let mut cols = vec![ed.cursor().1];
+        for _ in 0..5 {
+            ed.move_cursor(CursorMove::Forward);
+            cols.push(ed.cursor().1);
+        }
+        assert_eq!(cols, [0, 1, 4, 6, 9, 10]);
+        ed.move_cursor(CursorMove::Back);
+        ed.move_cursor(CursorMove::Back);
+        assert_eq!(ed.cursor(), (0, 6));
+
+        // Columns that land inside a cluster snap to its start
+        ed.set_cursor(0, 2);
+        assert_eq!(ed.cursor(), (0, 1));
+        ed.move_cursor(CursorMove::End);
+        assert_eq!(ed.cursor(), (0, 10));
+        assert_eq!(ed.cursor_display_col(), 1 + 2 + 1 + 2 + 1);
+    }
+
+    #[test]
+    fn delete_removes_whole_grapheme_cluster() {
+        let mut ed = Editor::new(vec![CLUSTERS.to_string()]);
+        ed.set_cursor(0, 1);
+        ed.delete_char();
+        assert_eq!(ed.lines(), vec!["ae\u{301}\u{1112}\u{1161}\u{11ab}b"]);
+        ed.set_cursor(0, 6);
+        ed.delete_newline();
+        assert_eq!(ed.lines(), vec!["ae\u{301}b"]);
+        assert_eq!(ed.cursor(), (0, 3));
+        ed.delete_newline();
+        assert_eq!(ed.lines(), vec!["ab"]);
+
+        assert!(ed.undo());
+        assert!(ed.undo());
+        assert!(ed.undo());
+        assert_eq!(ed.lines(), vec![CLUSTERS]);
+    }
+
+    #[test]
+    fn visual_selection_covers_whole_cluster() {
+        let mut ed = Editor::new(vec![CLUSTERS.to_string()]);
+        ed.set_cursor(0, 1);
+        ed.start_selection();
+        ed.move_cursor(CursorMove::Forward);
+        ed.set_inclusive_selection(true);
+        ed.cut();
+        assert_eq!(ed.lines(), vec!["a\u{1112}\u{1161}\u{11ab}b"]);
+    }
+
+    #[test]
+    fn render_draws_clusters_in_their_width() {
+        let mut ed = Editor::new(vec![CLUSTERS.to_string(), "x".to_string()]);
+        ed.set_line_number_mode(LineNumberMode::None);
+        ed.set_cursor(1, 0);
+        let area = Rect::new(0, 0, 10, 2);
+        let mut buf = RatatuiBuffer::empty(area);
+        ed.render(area, &mut buf);
+        let symbols: Vec<&str> = (0..7).map(|x| buf[(x, 0)].symbol()).collect();
+        assert_eq!(symbols[..4], ["a", "👩\u{200d}💻", " ", "e\u{301}"]);
+        assert_eq!(symbols[4], "\u{1112}\u{1161}\u{11ab}");
+        assert_eq!(symbols[6], "b");
+
+        // Wrapping never splits a cluster across rows
+        ed.set_cursor(0, 0);
+        assert_eq!(ed.line_wrapped_height(0), 1);
+        let rows = rendered_rows(&ed, 3, 4);
+        assert_eq!(rows[0], "a👩\u{200d}💻");
+        assert_eq!(rows[1], "e\u{301}\u{1112}\u{1161}\u{11ab}");
+        assert_eq!(rows[2], "b");
+    }
+
+    #[test]
+    fn indent_guides_follow_tab_stops_outside_code() {
+        let lines = ["- a", "    - b", "  \t- c", "```", "    - code", "```"];
+        let mut ed = Editor::new(lines.iter().map(|l| l.to_string()).collect());
+        ed.set_line_number_mode(LineNumberMode::None);
+        ed.set_indent_guides(true, Color::Blue, [Color::Red, Color::Green, Color::Yellow]);
+        ed.update_markdown_highlights();
+
+        let rows = rendered_rows(&ed, 20, 6);
+        // The editor draws a tab its full width wherever it starts
+        assert_eq!(rows[1..], ["│   - b", "│     - c", "```", "    - code", "```"]);
+        assert_eq!(ed.get_row_styles(0)[0].fg, Some(Color::Red));
+        assert_eq!(ed.get_row_styles(1)[4].fg, Some(Color::Green));
+        assert_eq!(ed.get_row_styles(2)[3].fg, Some(Color::Green));
+
+        ed.set_tab_width(2);
+        ed.update_markdown_highlights();
+        assert_eq!(rendered_rows(&ed, 20, 3)[1..], ["│ │ - b", "│ │ - c"]);
+        assert_eq!(ed.get_row_styles(1)[4].fg, Some(Color::Yellow));
+    }
+
+    /// Conceal mode hides link syntax everywhere but the cursor line.
+    #[test]
+    fn conceal_reveals_links_on_cursor_line_only() {
+        let mut ed = Editor::new(vec![
+            "see [[notes/todo|my list]]".to_string(),
+            "a [site](https://x.test) b".to_string(),
+        ]);
+        ed.set_line_number_mode(LineNumberMode::None);
+        ed.set_conceal(true);
+        ed.set_cursor(0, 0);
+        assert_eq!(
+            rendered_rows(&ed, 40, 2),
+            vec!["see [[notes/todo|my list]]", "a site b"]
+        );
+
+        ed.set_cursor(1, 0);
+        assert_eq!(
+            rendered_rows(&ed, 40, 2),
+            vec!["see my list", "a [site](https://x.test) b"]
+        );
+
+        ed.set_conceal(false);
+        assert_eq!(rendered_rows(&ed, 40, 2)[0], "see [[notes/todo|my list]]");
+    }
+
+    /// Concealed lines wrap by their shown width, and clicks map back to buffer columns.
+    #[test]
+    fn conceal_keeps_logical_columns_for_wrapping_and_clicks() {
+        let mut ed = Editor::new(vec![
+            String::new(),
+            "[[a-very-long-target|ab]] cd".to_string(),
+        ]);
+        ed.set_line_number_mode(LineNumberMode::None);
+        ed.set_view_size(11, 10); // content_width 10
+        ed.set_conceal(true);
+        ed.set_cursor(0, 0);
+        assert_eq!(ed.line_wrapped_height(1), 1);
+        // "ab cd": screen column 3 is the 'c' at buffer column 26
+        assert_eq!(ed.visual_to_logical_coords(1, 3), (1, 26));
+
+        ed.set_cursor(1, 0);
+        assert_eq!(ed.line_wrapped_height(1), 3);
+    }
+
+    /// A multi-line replacement is undone and redone in one step.
+    #[test]
+    fn replace_lines_is_one_undo_step() {
+        let mut ed = Editor::new(vec!["foo".to_string(), "bar".to_string(), "foo".to_string()]);
+        ed.replace_lines(&[(0, "baz".to_string()), (2, "qux".to_string())]);
+        assert_eq!(ed.lines(), vec!["baz", "bar", "qux"]);
+        assert_eq!(ed.cursor(), (2, 0));
+
+        assert!(ed.undo());
+        assert_eq!(ed.lines(), vec!["foo", "bar", "foo"]);
+        assert!(ed.redo());
+        assert_eq!(ed.lines(), vec!["baz", "bar", "qux"]);
+    }
+
+    type Spans = Vec<(usize, usize)>;
+
+    /// Search matches on a row and the wiki links on it, as `(start, end)`.
+    fn row_ranges(ed: &Editor, row: usize) -> (Spans, Spans) {
+        let search = ed.highlights_for_row(row).iter().map(|h| (h.start_col, h.end_col)).collect();
+        let wiki = ed
+            .wiki_link_ranges
+            .iter()
+            .filter(|r| r.row == row)
+            .map(|r| (r.start_col, r.end_col))
+            .collect();
+        (search, wiki)
+    }
+
+    fn marked_editor() -> Editor {
+        let mut ed = Editor::new(["one", "two [[a]]", "three", "four [[b]]"].map(String::from).to_vec());
+        ed.set_search_highlights(&[(0, 0, 1), (1, 0, 2), (2, 0, 3), (3, 0, 4)], 0, Color::Red, Color::Red);
+        ed.set_wiki_link_ranges(vec![
+            WikiLinkRange { row: 1, start_col: 4, end_col: 9, is_valid: true },
+            WikiLinkRange { row: 3, start_col: 5, end_col: 10, is_valid: false },
+        ]);
+        ed
+    }
+
+    /// Deleting a line drops its highlights and wiki links and moves the
+    /// ones below it up with their text.
+    #[test]
+    fn deleted_line_takes_its_highlights_with_it() {
+        let mut ed = marked_editor();
+        ed.set_cursor(1, 0);
+        ed.delete_current_line();
+        assert_eq!(ed.lines(), vec!["one", "three", "four [[b]]"]);
+        assert_eq!(row_ranges(&ed, 0), (vec![(0, 1)], vec![]));
+        assert_eq!(row_ranges(&ed, 1), (vec![(0, 3)], vec![]));
+        assert_eq!(row_ranges(&ed, 2), (vec![(0, 4)], vec![(5, 10)]));
+        assert_eq!(row_ranges(&ed, 3), (vec![], vec![]));
+
+        // Undo puts the line back and moves the rest down again
+        assert!(ed.undo());
+        assert_eq!(row_ranges(&ed, 3), (vec![(0, 4)], vec![(5, 10)]));
+        assert_eq!(row_ranges(&ed, 2), (vec![(0, 3)], vec![]));
+    }
+
+    /// Joining lines, from either end, moves what follows up a row.
+    #[test]
+    fn joined_lines_shift_highlights() {
+        let mut ed = marked_editor();
+        ed.set_cursor(2, 5);
+        ed.delete_char();
+        assert_eq!(ed.lines(), vec!["one", "two [[a]]", "threefour [[b]]"]);
+        assert_eq!(row_ranges(&ed, 2).1, vec![]);
+        assert_eq!(row_ranges(&ed, 3), (vec![], vec![]));
+
+        let mut ed = marked_editor();
+        ed.set_cursor(1, 0);
+        ed.delete_newline();
+        assert_eq!(ed.lines(), vec!["onetwo [[a]]", "three", "four [[b]]"]);
+        assert_eq!(row_ranges(&ed, 1), (vec![(0, 3)], vec![]));
+        assert_eq!(row_ranges(&ed, 2), (vec![(0, 4)], vec![(5, 10)]));
+    }
+
+    /// A new line pushes what's below it down.
+    #[test]
+    fn inserted_line_shifts_highlights() {
+        let mut ed = marked_editor();
+        ed.set_cursor(1, 0);
+        ed.open_line_above();
+        assert_eq!(row_ranges(&ed, 1), (vec![], vec![]));
+        assert_eq!(row_ranges(&ed, 2), (vec![(0, 2)], vec![(4, 9)]));
+        assert_eq!(row_ranges(&ed, 4), (vec![(0, 4)], vec![(5, 10)]));
+    }
+
+    #[test]
+    fn replace_all_lines_is_one_undo_step() {
+        let mut ed = Editor::new(vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        ed.set_cursor(2, 0);
+        ed.replace_all_lines(vec!["a".to_string(), "x".to_string()]);
+        assert_eq!(ed.lines(), vec!["a", "x"]);
+        assert_eq!(ed.cursor(), (1, 0));
+
+        assert!(ed.undo());
+        assert_eq!(ed.lines(), vec!["a", "b", "c"]);
+        assert!(ed.redo());
+        assert_eq!(ed.lines(), vec!["a", "x"]);
+    }
+
+    #[test]
+    fn wrap_selection_in_link_is_one_undo_step() {
+        let mut ed = Editor::new(vec!["see the docs here".to_string()]);
+        ed.set_cursor(0, 4);
+        ed.start_selection();
+        ed.set_cursor(0, 11);
+        ed.set_inclusive_selection(true);
+        assert!(ed.wrap_selection_in_link("https://x.io"));
+        assert_eq!(ed.lines(), vec!["see [the docs](https://x.io) here"]);
+        assert_eq!(ed.cursor(), (0, 28));
+        assert!(!ed.has_selection());
+
+        assert!(ed.undo());
+        assert_eq!(ed.lines(), vec!["see the docs here"]);
+        assert!(!ed.wrap_selection_in_link("https://x.io"));
+    }
+
+    /// `Ctrl-P` / `Ctrl-N` after `p` swap the pasted lines for the other
+    /// yank ring entries, and the paste stays one undo step.
+    #[test]
+    fn paste_cycles_through_the_yank_ring() {
+        let mut ed = Editor::new(vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        ed.set_cursor(0, 0);
+        ed.delete_current_line();
+        ed.delete_current_line();
+        assert_eq!(ed.lines(), vec!["c"]);
+
+        ed.paste_after();
+        assert_eq!(ed.lines(), vec!["c", "b"]);
+        assert!(ed.cycle_paste(true));
+        assert_eq!(ed.lines(), vec!["c", "a"]);
+        assert!(ed.cycle_paste(true));
+        assert_eq!(ed.lines(), vec!["c", "b"]);
+        assert!(ed.cycle_paste(false));
+        assert_eq!(ed.lines(), vec!["c", "a"]);
+        ed.undo();
+        assert_eq!(ed.lines(), vec!["c"]);
+
+        // Characterwise entries go back in after the cursor, not on new lines
+        ed.start_selection();
+        ed.set_cursor(0, 1);
+        ed.copy();
+        ed.cancel_selection();
+        ed.set_cursor(0, 0);
+        assert!(ed.paste_yank(0));
+        assert_eq!(ed.lines(), vec!["cc"]);
+        assert!(ed.cycle_paste(true));
+        assert_eq!(ed.lines(), vec!["c", "b"]);
+
+        // Nothing to swap once the cursor has moved off the paste
+        ed.set_cursor(0, 0);
+        assert!(!ed.cycle_paste(true));
+        assert_eq!(ed.lines(), vec!["c", "b"]);
+    }
+
+    #[test]
+    fn bulk_insert_is_one_undo_step() {
+        let mut ed = Editor::new(vec!["keep OLD end".to_string()]);
+        ed.set_cursor(0, 5);
+        ed.start_selection();
+        ed.set_cursor(0, 8);
+        let bulk = ed.begin_bulk_insert();
+        assert_eq!(ed.lines(), vec!["keep  end"]);
+        for chunk in ["one\ntw", "o\n", "three"] {
+            ed.insert_chunk(chunk);
+        }
+        ed.end_bulk_insert(bulk, "one\ntwo\nthree".to_string());
+        assert_eq!(ed.lines(), vec!["keep one", "two", "three end"]);
+        assert_eq!(ed.cursor(), (2, 5));
+
+        assert!(ed.undo());
+        assert_eq!(ed.lines(), vec!["keep OLD end"]);
+        assert!(ed.redo());
+        assert_eq!(ed.lines(), vec!["keep one", "two", "three end"]);
+    }
+
+    #[test]
+    fn expand_before_cursor_is_one_undo_step() {
+        let mut ed = Editor::new(vec!["due ;dt end".to_string()]);
+        ed.set_cursor(0, 8);
+        ed.expand_before_cursor(4, "2026-03-07 ");
+        assert_eq!(ed.lines(), vec!["due 2026-03-07 end"]);
+        assert_eq!(ed.cursor(), (0, 15));
+        assert!(ed.undo());
+        assert_eq!(ed.lines(), vec!["due ;dt end"]);
+        assert_eq!(ed.cursor(), (0, 8));
+
+        ed.expand_before_cursor(4, "# Plan\n\n");
+        assert_eq!(ed.lines(), vec!["due # Plan", "", "end"]);
+        assert_eq!(ed.cursor(), (2, 0));
+        assert!(ed.undo());
+        assert_eq!(ed.lines(), vec!["due ;dt end"]);
+    }
+
+    /// Enter on a list item, its undo and its redo, each checked by the whole
+    /// buffer and the cursor.
+    fn assert_enter_round_trips(lines: &[&str], at: (usize, usize), entered: &[&str], cursor_after: (usize, usize)) {
+        let mut ed = Editor::new(lines.iter().map(|l| l.to_string()).collect());
+        ed.set_cursor(at.0, at.1);
+        ed.insert_newline();
+        assert_eq!((ed.lines(), ed.cursor()), (entered.to_vec(), cursor_after));
+        assert!(ed.undo());
+        assert_eq!((ed.lines(), ed.cursor()), (lines.to_vec(), at));
+        assert!(ed.redo());
+        assert_eq!((ed.lines(), ed.cursor()), (entered.to_vec(), cursor_after));
+    }
+
+    #[test]
+    fn enter_mid_list_item_is_one_undo_step() {
+        assert_enter_round_trips(&["- apples pears", "tail"], (0, 9), &["- apples ", "- pears", "tail"], (1, 2));
+        assert_enter_round_trips(&["  3. three"], (0, 10), &["  3. three", "  4. "], (1, 5));
+    }
+
+    #[test]
+    fn enter_at_end_of_list_item_is_one_undo_step() {
+        assert_enter_round_trips(&["- [x] done", "- next"], (0, 10), &["- [x] done", "- [ ] ", "- next"], (1, 6));
+    }
+
+    #[test]
+    fn enter_on_empty_list_item_is_one_undo_step() {
+        assert_enter_round_trips(&["- one", "- "], (1, 2), &["- one", ""], (1, 0));
+        assert_enter_round_trips(&["1. one", "2. "], (1, 3), &["1. one", ""], (1, 0));
+    }
+
+    /// Typed over a selection, the deleted text comes back with the same `u`.
+    #[test]
+    fn enter_over_a_selection_undoes_with_it() {
+        let mut ed = editor_with("- keep this gone");
+        ed.set_cursor(0, 6);
+        ed.start_selection();
+        ed.set_cursor(0, 11);
+        ed.insert_newline();
+        assert_eq!((ed.lines(), ed.cursor()), (vec!["- keep", "-  gone"], (1, 2)));
+        assert!(ed.undo());
+        assert_eq!((ed.lines(), ed.cursor()), (vec!["- keep this gone"], (0, 11)));
+        assert!(ed.redo());
+        assert_eq!((ed.lines(), ed.cursor()), (vec!["- keep", "-  gone"], (1, 2)));
+    }
+
+    #[test]
+    fn typing_over_a_selection_undoes_with_it() {
+        let mut ed = Editor::new(vec!["one two".to_string(), "three four".to_string()]);
+        ed.set_cursor(0, 4);
+        ed.start_selection();
+        ed.set_cursor(1, 6);
+        ed.insert_char('x');
+        assert_eq!((ed.lines(), ed.cursor()), (vec!["one xfour"], (0, 5)));
+        assert!(ed.undo());
+        assert_eq!((ed.lines(), ed.cursor()), (vec!["one two", "three four"], (1, 6)));
+        assert!(ed.redo());
+        assert_eq!((ed.lines(), ed.cursor()), (vec!["one xfour"], (0, 5)));
+    }
+
+    fn ragged_block_editor() -> Editor {
+        let lines = ["ab cdef", "ab c", "ab cdefgh", "a"];
+        let mut ed = Editor::new(lines.iter().map(|l| l.to_string()).collect());
+        // Ctrl-V at column 3, down two lines, then `$`
+        ed.set_visual_block_selection(Position::new(0, 3), Position::new(2, 3));
+        ed.set_block_to_eol(true);
+        ed
+    }
+
+    #[test]
+    fn ragged_block_yanks_and_deletes_to_each_line_end() {
+        let mut ed = ragged_block_editor();
+        assert_eq!(ed.visual_block_selected_text().as_deref(), Some("cdef\nc\ncdefgh"));
+
+        ed.cut_visual_block();
+        assert_eq!(ed.lines(), vec!["ab ", "ab ", "ab ", "a"]);
+        assert_eq!(ed.cursor(), (0, 3));
+
+        assert!(ed.undo());
+        assert_eq!(ed.lines(), vec!["ab cdef", "ab c", "ab cdefgh", "a"]);
+        assert!(ed.redo());
+        assert_eq!(ed.lines(), vec!["ab ", "ab ", "ab ", "a"]);
+    }
+
+    #[test]
+    fn ragged_block_shades_past_the_block_column() {
+        let mut ed = ragged_block_editor();
+        let shaded = |ed: &Editor, row, col| {
+            ed.apply_selection_style(Style::default(), row, col, None, ed.visual_block_rect()) == ed.selection_style
+        };
+        assert!(shaded(&ed, 2, 8));
+        assert!(shaded(&ed, 1, 3));
+        assert!(!shaded(&ed, 1, 2));
+
+        // Without `$` the block stops at the cursor column
+        ed.set_block_to_eol(false);
+        assert!(!shaded(&ed, 2, 8));
+        ed.clear_visual_block_selection();
+        assert_eq!(ed.visual_block_rect(), None);
+    }
+
+    fn press(ed: &mut Editor, code: crossterm::event::KeyCode, modifiers: crossterm::event::KeyModifiers) {
+        ed.input(KeyEvent::new(code, modifiers));
+    }
+
+    #[test]
+    fn shift_arrows_select_and_plain_arrows_clear() {
+        use crossterm::event::{KeyCode, KeyModifiers};
+        let mut ed = editor_with("hello big world");
+        ed.set_cursor(0, 6);
+        press(&mut ed, KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT);
+        press(&mut ed, KeyCode::Right, KeyModifiers::SHIFT);
+        assert_eq!(ed.selected_text().as_deref(), Some("big w"));
+
+        press(&mut ed, KeyCode::Char('X'), KeyModifiers::SHIFT);
+        assert_eq!(ed.lines(), vec!["hello Xorld"]);
+        assert!(ed.undo());
+        assert!(ed.undo());
+        assert_eq!(ed.lines(), vec!["hello big world"]);
+
+        ed.set_cursor(0, 0);
+        press(&mut ed, KeyCode::End, KeyModifiers::SHIFT);
+        assert_eq!(ed.selected_text().as_deref(), Some("hello big world"));
+        press(&mut ed, KeyCode::Left, KeyModifiers::NONE);
+        assert!(!ed.has_selection());
+        press(&mut ed, KeyCode::Left, KeyModifiers::CONTROL);
+        assert_eq!(ed.cursor(), (0, 10));
+    }
+
+    #[test]
+    fn ctrl_backspace_and_delete_remove_words() {
+        use crossterm::event::{KeyCode, KeyModifiers};
+        let mut ed = editor_with("hello big world");
+        ed.set_cursor(0, 9);
+        press(&mut ed, KeyCode::Backspace, KeyModifiers::CONTROL);
+        assert_eq!(ed.lines(), vec!["hello  world"]);
+        press(&mut ed, KeyCode::Delete, KeyModifiers::CONTROL);
+        assert_eq!(ed.lines(), vec!["hello world"]);
+        assert_eq!(ed.cursor(), (0, 6));
+
+        // Deleting with a selection removes only the selection
+        press(&mut ed, KeyCode::Home, KeyModifiers::SHIFT);
+        press(&mut ed, KeyCode::Delete, KeyModifiers::CONTROL);
+        assert_eq!(ed.lines(), vec!["world"]);
+        assert!(ed.undo());
+        assert_eq!(ed.lines(), vec!["hello world"]);
+    }
+
+    /// Columns the cursor stops at moving with `motion` from `col`, until it
+    /// stops moving.
+    fn word_stops(ed: &mut Editor, col: usize, motion: CursorMove) -> Vec<usize> {
+        ed.set_cursor(0, col);
+        let mut stops = Vec::new();
+        loop {
+            let before = ed.cursor();
+            ed.move_cursor(motion);
+            if ed.cursor() == before {
+                return stops;
+            }
+            stops.push(ed.cursor().1);
+        }
+    }
+
+    #[test]
+    fn word_chars_decide_where_word_motions_stop() {
+        let mut ed = editor_with("foo-bar_baz.qux");
+
+        // Default: `_` joins words, `-` and `.` split them
+        assert_eq!(word_stops(&mut ed, 0, CursorMove::WordForward), [4, 12, 15]);
+        assert_eq!(word_stops(&mut ed, 15, CursorMove::WordBack), [12, 4, 0]);
+        assert_eq!(word_stops(&mut ed, 0, CursorMove::WordEndForward), [2, 3, 10, 11, 14]);
+
+        ed.set_word_chars("_-");
+        assert_eq!(word_stops(&mut ed, 0, CursorMove::WordForward), [12, 15]);
+        assert_eq!(word_stops(&mut ed, 15, CursorMove::WordBack), [12, 0]);
+        assert_eq!(word_stops(&mut ed, 0, CursorMove::WordEndForward), [10, 11, 14]);
+
+        // Without `_`, snake_case splits too
+        ed.set_word_chars("");
+        assert_eq!(word_stops(&mut ed, 0, CursorMove::WordForward), [4, 8, 12, 15]);
+        assert_eq!(word_stops(&mut ed, 15, CursorMove::WordBack), [12, 8, 4, 0]);
+    }
+
+    #[test]
+    fn narrowed_edits_widen_back_in_place_and_stay_undoable() {
+        let lines = ["# A", "a", "## B", "b", "# C", "c"];
+        let mut ed = Editor::new(lines.iter().map(|l| l.to_string()).collect());
+        ed.reset_change_baseline();
+        ed.set_cursor(0, 1);
+        ed.insert_str("!");
+        ed.set_cursor(3, 1);
+
+        assert!(ed.narrow(2..4));
+        assert!(!ed.narrow(0..1));
+        assert_eq!(ed.lines(), vec!["## B", "b"]);
+        assert_eq!(ed.narrowed_offset(), Some(2));
+        assert_eq!(ed.cursor(), (1, 1));
+        ed.insert_str("\nmore");
+        ed.refresh_change_markers();
+        assert_eq!(ed.change_markers().get(2), Some(LineChange::Added));
+        assert_eq!(ed.full_lines(), ["#! A", "a", "## B", "b", "more", "# C", "c"]);
+
+        assert!(ed.widen());
+        assert!(!ed.widen());
+        assert_eq!(ed.lines(), vec!["#! A", "a", "## B", "b", "more", "# C", "c"]);
+        assert_eq!(ed.cursor(), (4, 4));
+        assert!(ed.undo());
+        assert_eq!(ed.lines(), vec!["#! A", "a", "## B", "b", "# C", "c"]);
+        assert!(ed.undo());
+        assert_eq!(ed.lines(), lines);
+        assert!(ed.redo());
+        assert!(ed.redo());
+        assert_eq!(ed.lines(), vec!["#! A", "a", "## B", "b", "more", "# C", "c"]);
+    }
+
+    #[test]
+    fn folded_code_blocks_are_one_line_that_motions_step_over() {
+        let lines = ["---", "tags: x", "---", "intro", "```rust", "a", "b", "c", "```", "outro"];
+        let mut ed = Editor::new(lines.iter().map(|l| l.to_string()).collect());
+        ed.update_markdown_highlights();
+        ed.set_view_size(40, 10);
+
+        ed.set_cursor(6, 0);
+        assert!(ed.close_fold());
+        assert_eq!(ed.cursor(), (4, 0));
+        ed.set_cursor(1, 0);
+        assert!(ed.close_fold());
+        let rows = rendered_rows(&ed, 40, 4);
+        assert_eq!(
+            rows,
+            ["  1 --- frontmatter — 3 lines folded", "  4 intro", "  5 ``` rust — 5 lines folded", " 10 outro"]
+        );
+
+        // j from the summary lands below the fold, k comes back onto it
+        ed.set_cursor(4, 0);
+        ed.move_cursor(CursorMove::Down);
+        assert_eq!(ed.cursor().0, 9);
+        ed.move_cursor(CursorMove::Up);
+        assert_eq!(ed.cursor().0, 4);
+
+        // A line added above moves the fold along with its block
+        ed.set_cursor(3, 5);
+        ed.insert_newline();
+        assert_eq!(ed.folds.folded_at(5), Some(9));
+        assert_eq!(rendered_rows(&ed, 40, 5)[3], "  6 ``` rust — 5 lines folded");
+
+        // A jump into the fold, as to a search match, opens it
+        ed.set_cursor(7, 0);
+        assert_eq!(ed.folds.folded_at(5), None);
+        assert!(ed.toggle_fold());
+        assert_eq!(ed.cursor(), (5, 0));
+        assert!(ed.open_fold() && !ed.open_fold());
+    }
 }
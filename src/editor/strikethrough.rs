@@ -0,0 +1,55 @@
+//! GFM `~~strikethrough~~` delimiters, shared by the editor highlighters,
+//! conceal mode and the content view so they agree on what is struck out.
+
+/// Whether a `~~` delimiter starts at `i`: exactly two tildes, not part of a
+/// longer run.
+fn is_delimiter(chars: &[char], i: usize) -> bool {
+    chars.get(i) == Some(&'~')
+        && chars.get(i + 1) == Some(&'~')
+        && chars.get(i + 2) != Some(&'~')
+        && (i == 0 || chars[i - 1] != '~')
+}
+
+/// `~~text~~` starting at `start`: the column just past its closing tildes.
+/// A lone `~`, as in `~/notes`, never delimits, and the text can neither be
+/// empty nor start or end with whitespace.
+pub fn strikethrough_at(chars: &[char], start: usize) -> Option<usize> {
+    if !is_delimiter(chars, start) {
+        return None;
+    }
+    let text_start = start + 2;
+    if chars.get(text_start).is_none_or(|c| c.is_whitespace()) {
+        return None;
+    }
+    (text_start + 1..chars.len())
+        .find(|&j| is_delimiter(chars, j) && !chars[j - 1].is_whitespace())
+        .map(|j| j + 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn struck(line: &str, start: usize) -> Option<String> {
+        let chars: Vec<char> = line.chars().collect();
+        strikethrough_at(&chars, start).map(|end| chars[start..end].iter().collect())
+    }
+
+    #[test]
+    fn test_strikethrough_delimiters() {
+        assert_eq!(struck("~~done thing~~ next", 0).as_deref(), Some("~~done thing~~"));
+        assert_eq!(struck("a ~~ü~~", 2).as_deref(), Some("~~ü~~"));
+        // The first closing delimiter ends it
+        assert_eq!(struck("~~a~~ b ~~c~~", 0).as_deref(), Some("~~a~~"));
+    }
+
+    #[test]
+    fn test_single_tildes_and_odd_runs_are_not_delimiters() {
+        assert_eq!(struck("cd ~/notes and ~/work", 3), None);
+        assert_eq!(struck("~/a~~", 0), None);
+        assert_eq!(struck("~~~fence~~~", 0), None);
+        assert_eq!(struck("~~~~", 0), None);
+        assert_eq!(struck("~~ spaced ~~", 0), None);
+        assert_eq!(struck("~~unclosed", 0), None);
+    }
+}
@@ -0,0 +1,128 @@
+//! The last yanks and deletes, newest first: what `Ctrl-P` / `Ctrl-N` cycle a
+//! paste through and `:yanks` lists. Kept for the session only.
+
+use std::collections::VecDeque;
+
+/// Entries kept when `editor.yank_ring_size` isn't set
+pub const DEFAULT_SIZE: usize = 10;
+
+/// One yank or delete, and whether it was whole lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Yank {
+    pub text: String,
+    pub linewise: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct YankRing {
+    entries: VecDeque<Yank>,
+    size: usize,
+}
+
+impl Default for YankRing {
+    fn default() -> Self {
+        Self::new(DEFAULT_SIZE)
+    }
+}
+
+impl YankRing {
+    pub fn new(size: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            size,
+        }
+    }
+
+    pub fn set_size(&mut self, size: usize) {
+        self.size = size;
+        self.entries.truncate(size);
+    }
+
+    /// Put `text` first. Yanking something already in the ring moves it to
+    /// the front instead of keeping it twice.
+    pub fn push(&mut self, text: &str, linewise: bool) {
+        if text.is_empty() || self.size == 0 {
+            return;
+        }
+        if let Some(i) = self.position(text, linewise) {
+            self.entries.remove(i);
+        }
+        self.entries.push_front(Yank {
+            text: text.to_string(),
+            linewise,
+        });
+        self.entries.truncate(self.size);
+    }
+
+    pub fn position(&self, text: &str, linewise: bool) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|y| y.linewise == linewise && y.text == text)
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Yank> {
+        self.entries.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Yank> {
+        self.entries.iter()
+    }
+}
+
+/// `text` on one line for a list: line breaks shown as `⏎`, and cut to
+/// `width` characters with `…` when longer.
+pub fn preview(text: &str, width: usize) -> String {
+    let text = text.trim_end_matches('\n');
+    let long = text.chars().count() > width;
+    let mut out: String = text
+        .chars()
+        .take(if long { width.saturating_sub(1) } else { width })
+        .map(|c| match c {
+            '\n' => '⏎',
+            '\t' => ' ',
+            c => c,
+        })
+        .collect();
+    if long {
+        out.push('…');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_keeps_newest_first_without_repeats() {
+        let mut ring = YankRing::new(3);
+        ring.push("a", false);
+        ring.push("b\n", true);
+        ring.push("", false);
+        ring.push("c", false);
+        assert_eq!(ring.len(), 3);
+        assert_eq!(ring.get(0).unwrap().text, "c");
+
+        // Yanked again: moved to the front
+        ring.push("a", false);
+        let texts: Vec<&str> = ring.iter().map(|y| y.text.as_str()).collect();
+        assert_eq!(texts, ["a", "c", "b\n"]);
+
+        ring.push("d", false);
+        assert_eq!(ring.position("b\n", true), None);
+        ring.set_size(1);
+        assert_eq!(ring.len(), 1);
+
+        assert_eq!(preview("one\ntwo\n", 20), "one⏎two");
+        assert_eq!(preview("abcdef", 4), "abc…");
+        assert_eq!(preview("abcd", 4), "abcd");
+    }
+}
@@ -0,0 +1,170 @@
+//! Frame pacing for the event loop: draw at most `max_fps` times a second and
+//! drain every queued event between frames, so key repeat and mouse drags
+//! coalesce into one draw instead of queueing behind slow terminal writes.
+
+use std::time::{Duration, Instant};
+
+/// How long one batch of events may run when frames aren't capped.
+const UNCAPPED_BATCH_BUDGET: Duration = Duration::from_millis(16);
+
+pub struct FramePacer {
+    interval: Duration,
+    last_draw: Option<Instant>,
+}
+
+impl FramePacer {
+    /// `max_fps == 0` leaves frames uncapped.
+    pub fn new(max_fps: u32) -> Self {
+        let interval = if max_fps == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs(1) / max_fps
+        };
+        Self {
+            interval,
+            last_draw: None,
+        }
+    }
+
+    /// Whether enough time has passed since the last frame to draw another.
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.time_until_due(now).is_zero()
+    }
+
+    pub fn time_until_due(&self, now: Instant) -> Duration {
+        match self.last_draw {
+            Some(last) => (last + self.interval).saturating_duration_since(now),
+            None => Duration::ZERO,
+        }
+    }
+
+    pub fn drawn(&mut self, now: Instant) {
+        self.last_draw = Some(now);
+    }
+
+    /// Whether a batch of events started at `batch_start` may keep reading
+    /// queued events, or should stop so the next frame isn't starved.
+    pub fn batch_has_time(&self, batch_start: Instant, now: Instant) -> bool {
+        let budget = if self.interval.is_zero() {
+            UNCAPPED_BATCH_BUDGET
+        } else {
+            self.interval
+        };
+        now.saturating_duration_since(batch_start) < budget
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Storm {
+        draws: usize,
+        /// Time from the last event arriving to it being on screen.
+        lag: Duration,
+    }
+
+    /// Run `events` key presses arriving `spacing` apart through a model of the
+    /// event loop, where each draw takes `render`. Without a pacer the loop
+    /// reads at most 8 events and then draws, as it used to.
+    fn storm(events: u32, spacing: Duration, render: Duration, mut pacer: Option<FramePacer>) -> Storm {
+        let start = Instant::now();
+        let arrival = |i: u32| start + spacing * i;
+        let mut now = start;
+        let mut next = 0;
+        let mut draws = 0;
+        let mut dirty = false;
+
+        while next < events || dirty {
+            if next < events && !dirty && arrival(next) > now {
+                now = arrival(next);
+            }
+
+            let batch_start = now;
+            let mut batch = 0;
+            while next < events && arrival(next) <= now {
+                let keep_going = match &pacer {
+                    Some(p) => p.batch_has_time(batch_start, now),
+                    None => batch < 8,
+                };
+                if !keep_going {
+                    break;
+                }
+                next += 1;
+                batch += 1;
+                dirty = true;
+            }
+
+            match &mut pacer {
+                Some(p) if !p.is_due(now) => {
+                    let wait = p.time_until_due(now);
+                    now = match (next < events).then(|| arrival(next)) {
+                        Some(t) if t < now + wait => t,
+                        _ => now + wait,
+                    };
+                }
+                _ if dirty => {
+                    if let Some(p) = &mut pacer {
+                        p.drawn(now);
+                    }
+                    now += render;
+                    draws += 1;
+                    dirty = false;
+                }
+                _ => {}
+            }
+        }
+
+        Storm {
+            draws,
+            lag: now.saturating_duration_since(arrival(events - 1)),
+        }
+    }
+
+    #[test]
+    fn test_pacer_waits_for_interval() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new(50);
+        assert!(pacer.is_due(start));
+        pacer.drawn(start);
+        assert!(!pacer.is_due(start + Duration::from_millis(10)));
+        assert_eq!(pacer.time_until_due(start + Duration::from_millis(5)), Duration::from_millis(15));
+        assert!(pacer.is_due(start + Duration::from_millis(20)));
+
+        let uncapped = FramePacer::new(0);
+        assert!(uncapped.is_due(start));
+        assert!(uncapped.batch_has_time(start, start + Duration::from_millis(10)));
+        assert!(!uncapped.batch_has_time(start, start + Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn test_event_storm_on_slow_terminal() {
+        // A second of key repeat at 1ms per event on a terminal that takes
+        // 20ms per draw (SSH, conhost)
+        let spacing = Duration::from_millis(1);
+        let render = Duration::from_millis(20);
+
+        let batched = storm(1000, spacing, render, None);
+        let paced = storm(1000, spacing, render, Some(FramePacer::new(60)));
+
+        // Reading 8 events per 20ms draw falls behind: the last key press is
+        // drawn about 1.5s late. Draining the queue each frame keeps up.
+        assert!(batched.lag > Duration::from_millis(1000), "batched lag {:?}", batched.lag);
+        assert!(paced.lag <= Duration::from_millis(40), "paced lag {:?}", paced.lag);
+        assert!(paced.draws < batched.draws / 2, "{} vs {} draws", paced.draws, batched.draws);
+    }
+
+    #[test]
+    fn test_event_storm_on_fast_terminal() {
+        let spacing = Duration::from_millis(1);
+        let render = Duration::from_millis(2);
+
+        let batched = storm(1000, spacing, render, None);
+        let paced = storm(1000, spacing, render, Some(FramePacer::new(60)));
+
+        // Fast terminals kept up before, but drew hundreds of frames nobody sees
+        assert!(batched.draws > 300, "batched draws {}", batched.draws);
+        assert!(paced.draws <= 62, "paced draws {}", paced.draws);
+        assert!(paced.lag <= Duration::from_millis(20), "paced lag {:?}", paced.lag);
+    }
+}
@@ -1,15 +1,22 @@
 use std::io;
+use std::time::{Duration, Instant};
+
+use chrono::Timelike;
 
 use crossterm::cursor::SetCursorStyle;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind};
 use ratatui::{backend::CrosstermBackend, Terminal};
 
-use crate::app::{App, BlockInsertMode, BlockInsertState, ContextMenuItem, ContextMenuState, DeleteType, DialogState, SearchPickerState, Focus, Mode, SidebarItemKind, VimMode, WikiAutocompleteMode, WikiAutocompleteState};
+use crate::app::{App, LeaderAction, LinkCompletionState, ScreenLine, BlockInsertMode, BlockInsertState, ContextMenuItem, ContextMenuState, DeleteType, DialogState, PickerPurpose, SearchPickerState, Focus, Mode, SidebarItemKind, VimMode, WikiAutocompleteMode, WikiAutocompleteState, WikiLink, WordCompletionState, split_wiki_unescaped};
 use crate::clipboard::{self, ClipboardContent};
-use crate::editor::{CursorMove, CursorShape, Position};
+use crate::config::{Config, EscBehavior};
+use crate::editor::{CursorMove, CursorShape, LineEdit, Position};
 use crate::ui;
-use crate::vim::{FindState, PendingFind, PendingMacro, PendingMark, TextObject, TextObjectScope, VimMode as VimModeNew};
-use crate::vim::command::{parse_command, Command};
+use crate::vim::{ConfirmSubstitute, FindState, Motion, MotionKind, PendingFind, PendingMacro, PendingMark, MarkTarget, Region, RegionKind, TextObject, TextObjectScope, VimMode as VimModeNew};
+use crate::vim::command::{parse_command, split_range, Command, LineRange, TaskOp};
+use crate::vim::substitute::{apply_matches, find_matches, preserves_case, substitute};
+
+use super::frame::FramePacer;
 
 /// Emit a terminal control command to the controlling terminal.
 ///
@@ -56,6 +63,8 @@ fn update_cursor_style(app: &mut App) {
 
 pub fn run_app(terminal: &mut Terminal<CrosstermBackend<Box<dyn io::Write>>>, app: &mut App) -> io::Result<()> {
     let mut needs_render = true;
+    let mut pacer = FramePacer::new(app.config.max_fps);
+    let mut scan_progress = None;
 
     loop {
         let pending_before = app.pending_images.len();
@@ -70,6 +79,18 @@ pub fn run_app(terminal: &mut Terminal<CrosstermBackend<Box<dyn io::Write>>>, ap
             needs_render = true;
         }
 
+        if app.poll_change_markers() {
+            needs_render = true;
+        }
+
+        if app.poll_ipc() {
+            needs_render = true;
+        }
+
+        if app.poll_link_titles() {
+            needs_render = true;
+        }
+
         if app.pending_images.len() < pending_before
             || (highlighter_was_loading && !app.highlighter_loading)
             || (indexing_was_in_progress && !app.indexing_in_progress)
@@ -87,8 +108,63 @@ pub fn run_app(terminal: &mut Terminal<CrosstermBackend<Box<dyn io::Write>>>, ap
             needs_render = true;
         }
 
-        if needs_render {
+        if app.poll_pdf_export() {
+            needs_render = true;
+        }
+
+        // Redraw the scan's progress as it goes
+        let progress = app.scan_job.as_ref().map(|job| job.notes_read());
+        if progress != scan_progress {
+            scan_progress = progress;
+            needs_render = true;
+        }
+        if app.poll_notes_scan() {
+            needs_render = true;
+        }
+        if app.poll_saves() {
+            needs_render = true;
+        }
+        // One chunk of a large paste per turn, so keys and frames get a look in
+        if app.step_paste_job() {
+            needs_render = true;
+        }
+        // Each frame of a sliding jump, until it's there. One the content
+        // view wasn't drawn to finish still stops on time
+        if let Some(slide) = app.smooth_scroll {
+            if slide.is_over(app.clock.now()) {
+                app.smooth_scroll = None;
+            }
+            needs_render = true;
+        }
+
+        // Redraw each step of the fade after an external reload.
+        if app.tick_reload_highlight() {
+            needs_render = true;
+        }
+
+        // Put the node picked from the graph search back to normal.
+        if app.tick_graph_flash() {
+            needs_render = true;
+        }
+
+        // Drop a leader sequence that timed out waiting for its next key.
+        if app.tick_leader() {
+            needs_render = true;
+        }
+
+        // Lock encrypted notes again once their passphrase is forgotten.
+        if app.expire_passphrase() {
+            needs_render = true;
+        }
+
+        // Cap the frame rate; anything that changes before the next frame is
+        // due gets drawn with it
+        let now = Instant::now();
+        if needs_render && pacer.is_due(now) {
+            let _span = crate::logging::span("render");
             terminal.draw(|f| ui::render(f, app))?;
+            app.frame_stats.drawn(now.elapsed());
+            pacer.drawn(now);
             needs_render = false;
         }
 
@@ -98,21 +174,41 @@ pub fn run_app(terminal: &mut Terminal<CrosstermBackend<Box<dyn io::Write>>>, ap
             || app.is_content_search_in_progress()
             || app.indexing_in_progress
             || app.has_highlight_work()
+            || app.change_markers_due.is_some()
             // Keep ticking while a toast is visible so it can self-expire.
-            || app.toast.is_some();
+            || app.toast.is_some()
+            || app.reload_highlight.is_some()
+            || app.graph_view.flash.is_some()
+            || app.pdf_export.is_some()
+            // Scan progress and save results come in without an event
+            || app.scan_job.is_some()
+            || !app.save_jobs.is_empty()
+            || app.leader.is_pending()
+            // Commands can come in on the IPC socket at any time
+            || app.is_ipc_listening()
+            || app.is_fetching_link_titles()
+            || app.paste_job.is_some()
+            || app.smooth_scroll.is_some()
+            // A frame is waiting for the pacer
+            || needs_render;
 
         if has_background_work {
             // Use very short timeout for highlight work to be reactive
-            let timeout = if app.has_highlight_work() {
-                std::time::Duration::from_millis(1)
+            let mut timeout = if app.has_highlight_work() || app.paste_job.is_some() {
+                Duration::from_millis(1)
+            } else if app.smooth_scroll.is_some() {
+                crate::app::SMOOTH_SCROLL_FRAME
             } else if app.mouse_button_held {
-                std::time::Duration::from_millis(33)
+                Duration::from_millis(33)
             } else {
-                std::time::Duration::from_millis(100)
+                Duration::from_millis(100)
             };
+            if needs_render {
+                timeout = timeout.min(pacer.time_until_due(Instant::now()));
+            }
 
             if event::poll(timeout)? {
-                if process_events(terminal, app, &mut needs_render)? {
+                if process_events(terminal, app, &pacer, &mut needs_render)? {
                     return Ok(());
                 }
             } else {
@@ -121,48 +217,107 @@ pub fn run_app(terminal: &mut Terminal<CrosstermBackend<Box<dyn io::Write>>>, ap
                     needs_render = true;
                 }
             }
+        } else if let Some(timeout) = idle_wake_up(app) {
+            if event::poll(timeout)? {
+                if process_events(terminal, app, &pacer, &mut needs_render)? {
+                    return Ok(());
+                }
+            } else {
+                needs_render = true;
+            }
         } else {
             // idle block until event to avoid unnecessary cpu usage
-            if process_events(terminal, app, &mut needs_render)? {
+            if process_events(terminal, app, &pacer, &mut needs_render)? {
                 return Ok(());
             }
         }
     }
 }
 
-// Default event handling can't keep up with fast frame update
-// this one is okayish solution to batch event
+/// How long the idle loop may block before something on screen is due to
+/// change without an event, if anything is.
+fn idle_wake_up(app: &App) -> Option<Duration> {
+    // The turn of the minute, to redraw the status bar clock
+    let clock = app
+        .config
+        .shows_status_segment("clock")
+        .then(|| Duration::from_secs(60 - u64::from(chrono::Local::now().second())));
+    // The remembered passphrase running out, to lock unlocked notes
+    let passphrase = app
+        .passphrase_expires_at()
+        .map(|at| at.saturating_duration_since(Instant::now()));
+    clock.into_iter().chain(passphrase).min()
+}
+
+// Default event handling can't keep up with fast frame update, so read every
+// queued event (until the next frame is due) and draw once. Only events that
+// can change what is on screen ask for a redraw.
 fn process_events(
-    _terminal: &mut Terminal<CrosstermBackend<Box<dyn io::Write>>>,
+    terminal: &mut Terminal<CrosstermBackend<Box<dyn io::Write>>>,
     app: &mut App,
+    pacer: &FramePacer,
     needs_render: &mut bool,
 ) -> io::Result<bool> {
-    const MAX_EVENTS_PER_BATCH: u8 = 8;
-    let mut count = 0u8;
+    let batch_start = Instant::now();
+    let _span = crate::logging::span("events");
+    app.frame_stats.events_in_batch = 0;
 
     loop {
         let event = event::read()?;
-        count += 1;
-        *needs_render = true;
+        app.frame_stats.events_in_batch += 1;
 
         match event {
             Event::FocusGained => {
                 app.reload_on_focus();
                 app.needs_full_clear = true;
+                *needs_render = true;
             }
             Event::Key(key) if key.kind == KeyEventKind::Press => {
-                if handle_key_event(app, key)? {
+                log::trace!("key {:?} {:?}", key.code, key.modifiers);
+                *needs_render = true;
+                let before = app.view_selection();
+                let quit = handle_key_event(app, crate::terminal::normalize_key(key))?;
+                if app.guard_dirty_buffer(before, quit) {
                     return Ok(true);
                 }
+                if std::mem::take(&mut app.suspend_requested) {
+                    if let Some(capabilities) = crate::terminal::suspend(terminal)? {
+                        app.resume_after_suspend(capabilities);
+                    }
+                }
+                if std::mem::take(&mut app.edit_config_requested) {
+                    let (capabilities, edited) = crate::terminal::edit_externally(terminal, &Config::config_path())?;
+                    app.resume_after_suspend(capabilities);
+                    match edited {
+                        Ok(()) => app.retry_config(),
+                        Err(e) => app.show_error_toast(format!("Couldn't run the editor: {}", e)),
+                    }
+                }
+            }
+            Event::Mouse(mouse) => {
+                let hover_before = (app.mouse_hover_item, app.context_menu_state);
+                let before = app.view_selection();
+                handle_mouse_event(app, mouse);
+                app.guard_dirty_buffer(before, false);
+                // Bare pointer motion only matters when it moves a hover highlight
+                if mouse.kind != MouseEventKind::Moved
+                    || hover_before != (app.mouse_hover_item, app.context_menu_state)
+                {
+                    *needs_render = true;
+                }
+            }
+            Event::Paste(text) => {
+                handle_paste_event(app, text);
+                *needs_render = true;
             }
-            Event::Mouse(mouse) => handle_mouse_event(app, mouse),
-            Event::Paste(text) => handle_paste_event(app, text),
             Event::Resize(_, _) => {
+                *needs_render = true;
             }
+            // Key releases and repeats, focus lost: nothing to redraw
             _ => {}
         }
 
-        if count >= MAX_EVENTS_PER_BATCH || !event::poll(std::time::Duration::ZERO)? {
+        if !pacer.batch_has_time(batch_start, Instant::now()) || !event::poll(Duration::ZERO)? {
             break;
         }
     }
@@ -171,6 +326,10 @@ fn process_events(
 }
 
 fn handle_mouse_event(app: &mut App, mouse: crossterm::event::MouseEvent) {
+    // A click would move the cursor the rest of a large paste goes in at
+    if app.paste_job.is_some() {
+        return;
+    }
     let mouse_x = mouse.column;
     let mouse_y = mouse.row;
 
@@ -295,12 +454,17 @@ fn handle_mouse_event(app: &mut App, mouse: crossterm::event::MouseEvent) {
                     let inner_y = mouse_y.saturating_sub(app.sidebar_area.y + 1); // +1 for top border
                     let clicked_index = inner_y as usize;
 
-                    if clicked_index < app.sidebar_items.len() {
+                    let on_header = app
+                        .sidebar_items
+                        .get(clicked_index)
+                        .is_some_and(|item| matches!(item.kind, SidebarItemKind::Header));
+                    if clicked_index < app.sidebar_items.len() && !on_header {
                         app.selected_sidebar_index = clicked_index;
                         let item_info = app.sidebar_items.get(clicked_index).map(|item| {
                             match &item.kind {
                                 SidebarItemKind::Folder { path, .. } => Some((true, path.clone(), 0)),
                                 SidebarItemKind::Note { note_index } => Some((false, std::path::PathBuf::new(), *note_index)),
+                                SidebarItemKind::Header => None,
                             }
                         }).flatten();
 
@@ -308,6 +472,9 @@ fn handle_mouse_event(app: &mut App, mouse: crossterm::event::MouseEvent) {
                             if is_folder {
                                 app.focus = Focus::Sidebar;
                                 app.toggle_folder(path);
+                                app.show_selected_folder();
+                                app.update_content_items();
+                                app.update_outline();
                             } else {
                                 app.focus = Focus::Content;
                                 app.sync_selected_note_from_sidebar();
@@ -328,23 +495,24 @@ fn handle_mouse_event(app: &mut App, mouse: crossterm::event::MouseEvent) {
                         app.jump_to_outline();
                     }
                 } else if in_content_area {
+                    // Items can be indented, so link columns are measured from the item's own x
                     let clicked_item = app.content_item_rects.iter().find(|(_, rect)| {
                         mouse_y >= rect.y && mouse_y < rect.y + rect.height
-                    }).map(|(idx, _)| *idx);
+                    }).map(|(idx, rect)| (*idx, rect.x));
 
-                    if let Some(idx) = clicked_item {
+                    if let Some((idx, item_x)) = clicked_item {
                         if app.is_content_item_visible(idx) {
                             app.content_cursor = idx;
                             app.selected_link_index = 0;
                         }
 
-                        if app.is_click_on_task_checkbox(idx, mouse_x, app.content_area.x) {
+                        if app.is_click_on_task_checkbox(idx, mouse_x, item_x) {
                             app.toggle_task_at(idx);
                         }
-                        else if let Some(url) = app.find_clicked_link(idx, mouse_x, app.content_area.x) {
+                        else if let Some(url) = app.find_clicked_link(idx, mouse_x, item_x) {
                             app.open_link(&url);
                         }
-                        else if let Some(wiki_link) = app.find_clicked_wiki_link(idx, mouse_x, app.content_area.x) {
+                        else if let Some(wiki_link) = app.find_clicked_wiki_link(idx, mouse_x, item_x) {
                             if wiki_link.is_valid {
                                 app.navigate_to_wiki_link_with_heading(&wiki_link.target, wiki_link.heading.as_deref());
                             } else {
@@ -360,12 +528,7 @@ fn handle_mouse_event(app: &mut App, mouse: crossterm::event::MouseEvent) {
                                 app.resolve_image_path(path).map(|p| p.to_string_lossy().to_string())
                             };
                             if let Some(open_path) = open_path {
-                                #[cfg(target_os = "macos")]
-                                let _ = std::process::Command::new("open").arg(&open_path).spawn();
-                                #[cfg(target_os = "linux")]
-                                let _ = std::process::Command::new("xdg-open").arg(&open_path).spawn();
-                                #[cfg(target_os = "windows")]
-                                let _ = std::process::Command::new("cmd").args(["/c", "start", "", &open_path]).spawn();
+                                app.open_external(open_path);
                             }
                         }
                         else if app.item_is_details_at(idx) {
@@ -410,15 +573,51 @@ fn handle_mouse_event(app: &mut App, mouse: crossterm::event::MouseEvent) {
     }
 }
 
+/// Put pasted text in at the cursor, or hold it for the LargePaste dialog
+/// when it's past the configured limits.
+fn insert_paste(app: &mut App, text: &str) {
+    app.editor.remember_paste(text);
+    if app.hold_large_paste(text) {
+        return;
+    }
+
+    // Force full clear for multiline paste to prevent ghosting
+    if text.contains('\n') {
+        app.needs_full_clear = true;
+    }
+
+    // Insert the entire pasted text at once
+    let (row, col) = app.editor.cursor();
+    app.editor.insert_str(text);
+    app.fetch_pasted_link_title(text, row, col);
+}
+
 fn handle_paste_event(app: &mut App, text: String) {
-    // Only handle paste in Edit mode
-    if app.mode != Mode::Edit {
+    // Search prompts take the paste as typed text, its lines joined
+    if app.buffer_search.active || app.search_active {
+        paste_into_search(app, &text.lines().collect::<Vec<_>>().join(" "));
+        return;
+    }
+
+    // Only handle paste in Edit mode, and not into a large paste still going in
+    if app.mode != Mode::Edit || app.paste_job.is_some() {
+        return;
+    }
+
+    // A link target is one line, so only the first goes into the `[[` query
+    if matches!(app.wiki_autocomplete, WikiAutocompleteState::Open { .. }) {
+        paste_into_wiki_autocomplete(app, text.lines().next().unwrap_or(""));
         return;
     }
 
     // Close any open menus/autocomplete
     app.context_menu_state = ContextMenuState::None;
     app.wiki_autocomplete = WikiAutocompleteState::None;
+    app.close_word_completion();
+    app.close_link_completion();
+
+    // A URL pasted over a selection links it rather than replacing it
+    let linked = link_pasted_url(app, &text);
 
     // If in Normal or Visual mode, switch to Insert mode
     if app.vim_mode == VimMode::Normal || app.vim_mode == VimMode::Visual {
@@ -427,27 +626,23 @@ fn handle_paste_event(app: &mut App, text: String) {
         update_cursor_style(app);
     }
 
-    // Try to get html from clipboard and convert to Markdown
-    // falls back to plain text if html not available or conversion fails
-    let paste_text = match clipboard::get_content_as_markdown() {
-        Ok(ClipboardContent::Markdown(md)) => md,
-        Ok(ClipboardContent::PlainText(txt)) => txt,
-        Ok(ClipboardContent::Empty) => text.clone(),
-        Err(e) => {
-            // The terminal already handed us the pasted text, so fall back to it
-            // and surface the clipboard failure as a toast (never to stdout).
-            app.show_error_toast(format!("Clipboard: {}", e));
-            text.clone()
-        }
-    };
+    if !linked {
+        // Try to get html from clipboard and convert to Markdown
+        // falls back to plain text if html not available or conversion fails
+        let paste_text = match clipboard::get_content_as_markdown() {
+            Ok(ClipboardContent::Markdown(md)) => md,
+            Ok(ClipboardContent::PlainText(txt)) => txt,
+            Ok(ClipboardContent::Empty) => text.clone(),
+            Err(e) => {
+                // The terminal already handed us the pasted text, so fall back to it
+                // and surface the clipboard failure as a toast (never to stdout).
+                app.show_error_toast(format!("Clipboard: {}", e));
+                text.clone()
+            }
+        };
 
-    // Force full clear for multiline paste to prevent ghosting
-    if paste_text.contains('\n') {
-        app.needs_full_clear = true;
+        insert_paste(app, &paste_text);
     }
-
-    // Insert the entire pasted text at once
-    app.editor.insert_str(&paste_text);
     app.update_editor_highlights();
     app.update_editor_block();
 
@@ -539,17 +734,18 @@ fn handle_edit_mode_mouse(app: &mut App, mouse: crossterm::event::MouseEvent) {
                 app.editor.set_scroll_offset(app.editor_scroll_top);
             }
             constrain_cursor_to_viewport(app);
+            app.editor.pause_typewriter();
         }
 
         MouseEventKind::ScrollDown => {
-            let line_count = app.editor.line_count();
-            let max_scroll = line_count.saturating_sub(1);
+            let max_scroll = app.editor.max_scroll();
 
             if app.editor_scroll_top < max_scroll {
                 app.editor_scroll_top = (app.editor_scroll_top + 3).min(max_scroll);
                 app.editor.set_scroll_offset(app.editor_scroll_top);
             }
             constrain_cursor_to_viewport(app);
+            app.editor.pause_typewriter();
         }
 
         _ => {}
@@ -586,8 +782,7 @@ fn perform_auto_scroll(app: &mut App, direction: i8) {
         }
     } else {
         // Scroll down
-        let max_scroll = app.editor.line_count().saturating_sub(app.editor_view_height);
-        if app.editor_scroll_top < max_scroll {
+        if app.editor_scroll_top < app.editor.max_scroll() {
             app.editor_scroll_top += 1;
             app.editor.set_scroll_offset(app.editor_scroll_top);
             app.editor.move_cursor(CursorMove::Down);
@@ -676,6 +871,18 @@ fn get_context_menu_hover_index(mouse_x: u16, mouse_y: u16, menu_x: u16, menu_y:
     None
 }
 
+/// Wrap the characterwise visual selection as a markdown link when `text` is
+/// a lone URL and `paste_url_as_link` is on. Returns whether it did.
+fn link_pasted_url(app: &mut App, text: &str) -> bool {
+    if !app.config.editor.paste_url_as_link || app.vim_mode != VimMode::Visual {
+        return false;
+    }
+    match clipboard::pasted_url(text) {
+        Some(url) => app.editor.wrap_selection_in_link(url),
+        None => false,
+    }
+}
+
 fn execute_context_menu_action(app: &mut App, action: ContextMenuItem) {
     match action {
         ContextMenuItem::Copy => {
@@ -690,7 +897,35 @@ fn execute_context_menu_action(app: &mut App, action: ContextMenuItem) {
             update_cursor_style(app);
         }
         ContextMenuItem::Paste => {
-            app.editor.paste();
+            let text = app
+                .editor
+                .clipboard_text()
+                .map(str::to_string)
+                .or_else(clipboard::get_system_text);
+            match text {
+                Some(text) if link_pasted_url(app, &text) => {
+                    app.vim_mode = VimMode::Insert;
+                    update_cursor_style(app);
+                    app.update_editor_highlights();
+                }
+                Some(text) => {
+                    insert_paste(app, &text);
+                    app.update_editor_highlights();
+                }
+                None => {}
+            }
+        }
+        ContextMenuItem::CopyHtml => {
+            let region = visual_region(app);
+            app.editor.cancel_selection();
+            app.editor.clear_visual_line_selection();
+            app.editor.clear_visual_block_selection();
+            app.vim_mode = VimMode::Normal;
+            update_cursor_style(app);
+            app.visual_line_anchor = None;
+            app.visual_line_current = None;
+            app.visual_block_anchor = None;
+            app.copy_as_html(region);
         }
         ContextMenuItem::SelectAll => {
             app.editor.move_cursor(CursorMove::Top);
@@ -706,6 +941,22 @@ fn execute_context_menu_action(app: &mut App, action: ContextMenuItem) {
 
 /// Returns true if the app should quit
 fn handle_key_event(app: &mut App, key: crossterm::event::KeyEvent) -> io::Result<bool> {
+    // A key cuts a sliding jump short, straight to where it was going
+    app.smooth_scroll = None;
+
+    if key.code == KeyCode::F(12) {
+        app.debug_overlay = !app.debug_overlay;
+        return Ok(false);
+    }
+
+    // Keys would land in the middle of a large paste; Esc stops it
+    if app.paste_job.is_some() {
+        if key.code == KeyCode::Esc {
+            app.finish_paste_job();
+        }
+        return Ok(false);
+    }
+
     // Handle dialogs first
     match app.dialog {
         DialogState::Onboarding => {
@@ -751,14 +1002,28 @@ fn handle_key_event(app: &mut App, key: crossterm::event::KeyEvent) -> io::Resul
         DialogState::DirectoryNotFound => {
             return Ok(handle_directory_not_found_dialog(app, key));
         }
-        DialogState::UnsavedChanges => {
-            handle_unsaved_changes_dialog(app, key);
+        DialogState::ConfigError => {
+            return Ok(handle_config_error_dialog(app, key));
+        }
+        DialogState::ChangeNotesDir => {
+            handle_change_notes_dir_dialog(app, key);
+            return Ok(false);
+        }
+        DialogState::CreateNotesDirConfirm => {
+            handle_create_notes_dir_dialog(app, key);
             return Ok(false);
         }
+        DialogState::UnsavedChanges => {
+            return Ok(handle_unsaved_changes_dialog(app, key));
+        }
         DialogState::CreateWikiNote => {
             handle_create_wiki_note_dialog(app, key);
             return Ok(false);
         }
+        DialogState::ConfirmOpen => {
+            handle_confirm_open_dialog(app, key);
+            return Ok(false);
+        }
         DialogState::GraphView => {
             handle_graph_view_dialog(app, key);
             return Ok(false);
@@ -767,6 +1032,100 @@ fn handle_key_event(app: &mut App, key: crossterm::event::KeyEvent) -> io::Resul
             handle_theme_selector_dialog(app, key);
             return Ok(false);
         }
+        DialogState::YankList => {
+            handle_yank_list_dialog(app, key);
+            return Ok(false);
+        }
+        DialogState::MacroList => {
+            handle_macro_list_dialog(app, key);
+            return Ok(false);
+        }
+        DialogState::History => {
+            handle_history_dialog(app, key);
+            return Ok(false);
+        }
+        DialogState::DeleteMarkedConfirm => {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    app.delete_marked_items();
+                    app.dialog = DialogState::None;
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                    app.delete_marked.clear();
+                    app.dialog = DialogState::None;
+                }
+                _ => {}
+            }
+            return Ok(false);
+        }
+        DialogState::MovePicker => {
+            handle_move_picker_dialog(app, key);
+            return Ok(false);
+        }
+        DialogState::VaultPicker => {
+            handle_vault_picker_dialog(app, key);
+            return Ok(false);
+        }
+        DialogState::RefilePicker => {
+            handle_refile_picker_dialog(app, key);
+            return Ok(false);
+        }
+        DialogState::ProseReport => {
+            handle_prose_report_dialog(app, key);
+            return Ok(false);
+        }
+        DialogState::HeadingRenameConfirm => {
+            handle_heading_rename_dialog(app, key);
+        }
+        DialogState::MergeConfirm => {
+            handle_merge_dialog(app, key);
+            return Ok(false);
+        }
+        DialogState::LargePaste => {
+            handle_large_paste_dialog(app, key);
+            return Ok(false);
+        }
+        DialogState::ScanErrors => {
+            handle_scan_errors_dialog(app, key);
+            return Ok(false);
+        }
+        DialogState::GlobalSubstituteConfirm => {
+            handle_global_substitute_dialog(app, key);
+            return Ok(false);
+        }
+        DialogState::BulkRenameConfirm => {
+            handle_bulk_rename_dialog(app, key);
+            return Ok(false);
+        }
+        DialogState::ExternalChange => {
+            handle_external_change_dialog(app, key);
+            return Ok(false);
+        }
+        DialogState::SectionTasksConfirm => {
+            handle_section_tasks_dialog(app, key);
+            return Ok(false);
+        }
+        DialogState::Passphrase => {
+            handle_passphrase_dialog(app, key);
+            return Ok(false);
+        }
+        DialogState::Scaffold => {
+            handle_scaffold_dialog(app, key);
+            return Ok(false);
+        }
+        DialogState::NoteStats => {
+            if matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q')) {
+                app.note_stats = None;
+                app.dialog = DialogState::None;
+            }
+            return Ok(false);
+        }
+        DialogState::Snippets | DialogState::ConfigSources => {
+            if matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q')) {
+                app.dialog = DialogState::None;
+            }
+            return Ok(false);
+        }
         DialogState::None => {}
     }
 
@@ -793,6 +1152,16 @@ fn handle_key_event(app: &mut App, key: crossterm::event::KeyEvent) -> io::Resul
         return Ok(false);
     }
 
+    if app.title_edit.is_some() {
+        handle_title_edit_input(app, key);
+        return Ok(false);
+    }
+
+    // Esc stops a background scan of the notes folder before anything else
+    if app.mode == Mode::Normal && key.code == KeyCode::Esc && app.cancel_notes_scan() {
+        return Ok(false);
+    }
+
     // Handle mode-specific input
     match app.mode {
         Mode::Normal => {
@@ -813,12 +1182,39 @@ fn handle_onboarding_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
         KeyCode::Enter => {
             app.complete_onboarding();
         }
-        KeyCode::Char(c) => {
-            app.input_buffer.push(c);
+        KeyCode::Tab => app.complete_notes_dir_input(),
+        _ => edit_dialog_input(app, key),
+    }
+}
+
+fn handle_change_notes_dir_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    match key.code {
+        KeyCode::Enter => {
+            let input = app.input_buffer.to_string();
+            match app.change_notes_dir(&input) {
+                Ok(()) => app.input_buffer.clear(),
+                Err(e) => app.dialog_error = Some(e),
+            }
         }
-        KeyCode::Backspace => {
-            app.input_buffer.pop();
+        KeyCode::Tab => app.complete_notes_dir_input(),
+        KeyCode::Esc => {
+            app.input_buffer.clear();
+            app.dialog_error = None;
+            // Back to asking about the missing folder, which has to be answered
+            app.dialog = if app.config.notes_path().exists() {
+                DialogState::None
+            } else {
+                DialogState::DirectoryNotFound
+            };
         }
+        _ => edit_dialog_input(app, key),
+    }
+}
+
+fn handle_create_notes_dir_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => app.finish_create_notes_dir(true),
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => app.finish_create_notes_dir(false),
         _ => {}
     }
 }
@@ -831,10 +1227,11 @@ fn handle_create_note_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
                 app.dialog_error = Some("Note name cannot be empty".to_string());
                 return;
             }
-            app.create_note(&name);
-            app.input_buffer.clear();
-            app.dialog_error = None;
-            app.dialog = DialogState::None;
+            if app.create_note(&name) {
+                app.input_buffer.clear();
+                app.dialog_error = None;
+                app.dialog = DialogState::None;
+            }
         }
         KeyCode::Esc => {
             app.input_buffer.clear();
@@ -842,15 +1239,7 @@ fn handle_create_note_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
             app.dialog_error = None;
             app.dialog = DialogState::None;
         }
-        KeyCode::Char(c) => {
-            app.dialog_error = None;
-            app.input_buffer.push(c);
-        }
-        KeyCode::Backspace => {
-            app.dialog_error = None;
-            app.input_buffer.pop();
-        }
-        _ => {}
+        _ => edit_dialog_input(app, key),
     }
 }
 
@@ -874,15 +1263,7 @@ fn handle_create_folder_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
             app.target_folder = None;
             app.dialog = DialogState::None;
         }
-        KeyCode::Char(c) => {
-            app.dialog_error = None; // Clear error on new input
-            app.input_buffer.push(c);
-        }
-        KeyCode::Backspace => {
-            app.dialog_error = None; // Clear error on edit
-            app.input_buffer.pop();
-        }
-        _ => {}
+        _ => edit_dialog_input(app, key),
     }
 }
 
@@ -894,10 +1275,11 @@ fn handle_create_note_in_folder_dialog(app: &mut App, key: crossterm::event::Key
                 app.dialog_error = Some("Note name cannot be empty".to_string());
                 return;
             }
-            app.create_note(&name);
-            app.input_buffer.clear();
-            app.dialog_error = None;
-            app.dialog = DialogState::None;
+            if app.create_note(&name) {
+                app.input_buffer.clear();
+                app.dialog_error = None;
+                app.dialog = DialogState::None;
+            }
         }
         KeyCode::Esc => {
             app.input_buffer.clear();
@@ -906,15 +1288,7 @@ fn handle_create_note_in_folder_dialog(app: &mut App, key: crossterm::event::Key
             app.dialog = DialogState::None;
             app.load_notes_from_dir();
         }
-        KeyCode::Char(c) => {
-            app.dialog_error = None;
-            app.input_buffer.push(c);
-        }
-        KeyCode::Backspace => {
-            app.dialog_error = None;
-            app.input_buffer.pop();
-        }
-        _ => {}
+        _ => edit_dialog_input(app, key),
     }
 }
 
@@ -931,59 +1305,345 @@ fn handle_delete_confirm_dialog(app: &mut App, key: crossterm::event::KeyEvent)
     }
 }
 
+/// Folders holding many notes have to have their name typed instead of `y`.
 fn handle_delete_folder_confirm_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    let needs_typed_name = app.delete_folder_contents.is_some_and(|contents| contents.needs_typed_name());
+    if needs_typed_name {
+        match key.code {
+            KeyCode::Enter => {
+                if app.get_selected_folder_name().as_deref() == Some(app.input_buffer.trim()) {
+                    app.delete_current_folder();
+                    close_delete_folder_dialog(app);
+                } else {
+                    app.dialog_error = Some("Type the folder name exactly to delete it".to_string());
+                }
+            }
+            KeyCode::Esc => close_delete_folder_dialog(app),
+            _ => edit_dialog_input(app, key),
+        }
+        return;
+    }
     match key.code {
         KeyCode::Char('y') | KeyCode::Char('Y') => {
             app.delete_current_folder();
-            app.dialog = DialogState::None;
+            close_delete_folder_dialog(app);
         }
         KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
-            app.dialog = DialogState::None;
+            close_delete_folder_dialog(app);
         }
         _ => {}
     }
 }
 
-fn handle_unsaved_changes_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+/// Typing and cursor keys in a dialog's text field; an edit clears the
+/// dialog's error.
+fn edit_dialog_input(app: &mut App, key: crossterm::event::KeyEvent) {
+    if app.input_buffer.handle_key(key) == LineEdit::Changed {
+        app.dialog_error = None;
+    }
+}
+
+fn close_delete_folder_dialog(app: &mut App) {
+    app.delete_folder_contents = None;
+    app.input_buffer.clear();
+    app.dialog_error = None;
+    app.dialog = DialogState::None;
+}
+
+/// Returns true if the app should quit.
+fn handle_unsaved_changes_dialog(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
     match key.code {
         KeyCode::Char('y') | KeyCode::Char('Y') => {
             app.save_edit();
             app.vim_mode = VimMode::Normal;
             update_cursor_style(app);
             app.dialog = DialogState::None;
+            app.resume_after_unsaved_prompt()
         }
         KeyCode::Char('n') | KeyCode::Char('N') => {
-            app.cancel_edit();
+            if app.mode == Mode::Edit {
+                app.cancel_edit();
+            } else {
+                app.discard_dirty_buffer();
+            }
             app.vim_mode = VimMode::Normal;
             update_cursor_style(app);
             app.dialog = DialogState::None;
+            app.resume_after_unsaved_prompt()
         }
         KeyCode::Esc => {
+            app.after_unsaved_prompt = None;
             app.dialog = DialogState::None;
+            false
         }
-        _ => {}
+        _ => false,
     }
 }
 
-fn handle_create_wiki_note_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+fn handle_external_change_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
     match key.code {
-        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
-            if let Some(target) = app.pending_wiki_target.take() {
-                app.create_note_from_wiki_target(&target);
-            }
+        KeyCode::Char('r') | KeyCode::Char('R') | KeyCode::Enter => {
             app.dialog = DialogState::None;
+            app.reload_external_change();
+            update_cursor_style(app);
         }
-        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
-            app.pending_wiki_target = None;
+        KeyCode::Char('k') | KeyCode::Char('K') | KeyCode::Esc => {
             app.dialog = DialogState::None;
+            app.keep_external_change();
         }
         _ => {}
     }
 }
 
-fn handle_wiki_autocomplete(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
-    let is_open = matches!(app.wiki_autocomplete, WikiAutocompleteState::Open { .. });
-    if !is_open {
+fn handle_section_tasks_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+            app.dialog = DialogState::None;
+            app.apply_section_tasks();
+        }
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+            app.pending_section_tasks = None;
+            app.dialog = DialogState::None;
+        }
+        _ => {}
+    }
+}
+
+fn handle_passphrase_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    match key.code {
+        KeyCode::Enter => app.submit_passphrase(),
+        KeyCode::Esc => app.close_passphrase_prompt(),
+        _ => edit_dialog_input(app, key),
+    }
+}
+
+/// The outline is several lines, so Enter adds a line there and Ctrl-S
+/// submits it; the project name is submitted with Enter.
+fn handle_scaffold_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    let typing_outline = app.scaffold_prompt.as_ref().is_some_and(|prompt| prompt.entries.is_none());
+    match key.code {
+        KeyCode::Esc => app.close_scaffold_prompt(),
+        KeyCode::Char('s') if key.modifiers == KeyModifiers::CONTROL => app.submit_scaffold(),
+        KeyCode::Enter if typing_outline => app.input_buffer.insert('\n'),
+        KeyCode::Enter => app.submit_scaffold(),
+        KeyCode::Tab if typing_outline => app.input_buffer.insert_str("  "),
+        _ => edit_dialog_input(app, key),
+    }
+}
+
+fn handle_global_substitute_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+            app.dialog = DialogState::None;
+            app.run_global_substitute();
+        }
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+            app.pending_global_substitute = None;
+            app.dialog = DialogState::None;
+        }
+        _ => {}
+    }
+}
+
+fn handle_bulk_rename_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    let Some(bulk) = app.pending_bulk_rename.as_mut() else {
+        app.dialog = DialogState::None;
+        return;
+    };
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+            app.dialog = DialogState::None;
+            app.run_bulk_rename();
+        }
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+            app.pending_bulk_rename = None;
+            app.dialog = DialogState::None;
+        }
+        KeyCode::Char('j') | KeyCode::Down => {
+            bulk.scroll = (bulk.scroll + 1).min(bulk.renames.len().saturating_sub(1));
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            bulk.scroll = bulk.scroll.saturating_sub(1);
+        }
+        _ => {}
+    }
+}
+
+fn handle_heading_rename_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+            app.dialog = DialogState::None;
+            app.run_heading_rename();
+        }
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+            app.pending_heading_rename = None;
+            app.dialog = DialogState::None;
+        }
+        _ => {}
+    }
+}
+
+fn handle_merge_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+            app.dialog = DialogState::None;
+            app.run_merge();
+        }
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+            app.pending_merge = None;
+            app.dialog = DialogState::None;
+        }
+        _ => {}
+    }
+}
+
+fn handle_large_paste_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    match key.code {
+        KeyCode::Char('p') | KeyCode::Char('P') | KeyCode::Enter => app.start_large_paste(),
+        KeyCode::Char('n') | KeyCode::Char('N') => app.paste_as_new_note(),
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.large_paste = None;
+            app.dialog = DialogState::None;
+        }
+        _ => {}
+    }
+}
+
+fn handle_create_wiki_note_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+            if let Some(target) = app.pending_wiki_target.take() {
+                app.create_note_from_wiki_target(&target);
+            }
+            app.dialog = DialogState::None;
+        }
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+            app.pending_wiki_target = None;
+            app.dialog = DialogState::None;
+        }
+        _ => {}
+    }
+}
+
+/// Only `y` opens; Enter is left out so a stray key can't launch the target.
+fn handle_confirm_open_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') => app.finish_open_confirmation(true),
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => app.finish_open_confirmation(false),
+        _ => {}
+    }
+}
+
+/// Keys while the `Ctrl-N`/`Ctrl-P` popup is open. Enter and Esc accept the shown
+/// word; any other key accepts it and is then handled as usual.
+fn handle_word_completion(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
+    if matches!(app.word_completion, WordCompletionState::None) {
+        return false;
+    }
+
+    match key.code {
+        KeyCode::Char('n') if key.modifiers == KeyModifiers::CONTROL => {
+            app.cycle_word_completion(true);
+        }
+        KeyCode::Down => app.cycle_word_completion(true),
+        KeyCode::Char('p') if key.modifiers == KeyModifiers::CONTROL => {
+            app.cycle_word_completion(false);
+        }
+        KeyCode::Up => app.cycle_word_completion(false),
+        KeyCode::Enter | KeyCode::Esc => app.close_word_completion(),
+        _ => {
+            app.close_word_completion();
+            return false;
+        }
+    }
+    true
+}
+
+fn handle_link_completion(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
+    let (query, suggestions_len) = match &app.link_completion {
+        LinkCompletionState::Open { query, suggestions, .. } => (query.clone(), suggestions.len()),
+        LinkCompletionState::None => return false,
+    };
+
+    let step = |app: &mut App, forward: bool| {
+        if let LinkCompletionState::Open { ref mut selected_index, .. } = app.link_completion {
+            if suggestions_len > 0 {
+                *selected_index = if forward {
+                    (*selected_index + 1) % suggestions_len
+                } else {
+                    (*selected_index + suggestions_len - 1) % suggestions_len
+                };
+            }
+        }
+    };
+
+    match key.code {
+        KeyCode::Esc => app.close_link_completion(),
+        KeyCode::Enter | KeyCode::Tab if suggestions_len > 0 => {
+            app.accept_link_completion();
+            app.update_editor_highlights();
+        }
+        KeyCode::Down => step(app, true),
+        KeyCode::Up => step(app, false),
+        KeyCode::Char('n') if key.modifiers == KeyModifiers::CONTROL => step(app, true),
+        KeyCode::Char('p') if key.modifiers == KeyModifiers::CONTROL => step(app, false),
+        KeyCode::Backspace if !query.is_empty() => {
+            app.editor.delete_newline();
+            let mut new_query = query;
+            new_query.pop();
+            app.set_link_completion_query(new_query);
+        }
+        KeyCode::Char(c)
+            if c != ')' && (key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT) =>
+        {
+            app.editor.insert_char(c);
+            let mut new_query = query;
+            new_query.push(c);
+            app.set_link_completion_query(new_query);
+        }
+        _ => {
+            app.close_link_completion();
+            return false;
+        }
+    }
+    true
+}
+
+/// Types `text` into the open search prompt, refreshing its matches once.
+fn paste_into_search(app: &mut App, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    if app.buffer_search.active {
+        app.buffer_search.query.insert_str(text);
+        app.perform_buffer_search();
+        if !app.buffer_search.matches.is_empty() {
+            app.scroll_to_current_match();
+        }
+        update_editor_search_highlights(app);
+    } else {
+        app.search_query.insert_str(text);
+        app.update_filtered_indices();
+    }
+}
+
+/// Feeds `text` to the `[[` autocomplete as if typed, so a `#`, `|` or `]]`
+/// in it moves to headings, the alias or closes the link as usual. Whatever
+/// follows a closing `]]` is inserted as plain text.
+fn paste_into_wiki_autocomplete(app: &mut App, text: &str) {
+    for (at, c) in text.char_indices() {
+        if !matches!(app.wiki_autocomplete, WikiAutocompleteState::Open { .. }) {
+            app.editor.insert_str(&text[at..]);
+            break;
+        }
+        handle_wiki_autocomplete(app, crossterm::event::KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    }
+    app.update_editor_highlights();
+}
+
+fn handle_wiki_autocomplete(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
+    let is_open = matches!(app.wiki_autocomplete, WikiAutocompleteState::Open { .. });
+    if !is_open {
         return false;
     }
 
@@ -1072,6 +1732,9 @@ fn handle_wiki_autocomplete(app: &mut App, key: crossterm::event::KeyEvent) -> b
                         mode: WikiAutocompleteMode::Note,
                         target_note: None,
                     };
+                } else if let Some(why) = WikiLink::unlinkable(&suggestion.display_name) {
+                    app.vim.status_message = Some(format!("Can't link to {:?}: {}", suggestion.display_name, why));
+                    app.wiki_autocomplete = WikiAutocompleteState::None;
                 } else {
                     app.editor.insert_str(&suggestion.insert_text);
                     let already_closed = {
@@ -1141,10 +1804,7 @@ fn handle_wiki_autocomplete(app: &mut App, key: crossterm::event::KeyEvent) -> b
                     WikiAutocompleteMode::Alias => {
                         app.editor.delete_newline();
                         if let Some(ref target) = target_note {
-                            if target.contains('#') {
-                                let parts: Vec<&str> = target.splitn(2, '#').collect();
-                                let note_part = parts[0];
-                                let heading_part = parts.get(1).unwrap_or(&"");
+                            if let Some((note_part, heading_part)) = split_wiki_unescaped(target, '#') {
                                 let heading_suggestions = app.build_heading_suggestions(note_part, heading_part);
                                 app.wiki_autocomplete = WikiAutocompleteState::Open {
                                     trigger_pos: (0, 0),
@@ -1298,29 +1958,39 @@ fn handle_wiki_autocomplete(app: &mut App, key: crossterm::event::KeyEvent) -> b
 fn handle_rename_note_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
     match key.code {
         KeyCode::Enter => {
-            let new_name = app.input_buffer.clone();
-            app.rename_note(&new_name);
-            app.input_buffer.clear();
-            app.dialog = DialogState::None;
+            let new_name = app.input_buffer.to_string();
+            if app.rename_note(&new_name) {
+                app.input_buffer.clear();
+                app.dialog = DialogState::None;
+            }
         }
         KeyCode::Esc => {
             app.input_buffer.clear();
+            app.dialog_error = None;
             app.dialog = DialogState::None;
         }
-        KeyCode::Char(c) => {
-            app.input_buffer.push(c);
-        }
-        KeyCode::Backspace => {
-            app.input_buffer.pop();
+        _ => edit_dialog_input(app, key),
+    }
+}
+
+fn handle_title_edit_input(app: &mut App, key: crossterm::event::KeyEvent) {
+    match key.code {
+        KeyCode::Enter => app.apply_title_edit(),
+        KeyCode::Esc => app.title_edit = None,
+        _ => {
+            if let Some(edit) = app.title_edit.as_mut() {
+                if edit.input.handle_key(key) == LineEdit::Changed {
+                    edit.error = None;
+                }
+            }
         }
-        _ => {}
     }
 }
 
 fn handle_rename_folder_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
     match key.code {
         KeyCode::Enter => {
-            let new_name = app.input_buffer.clone();
+            let new_name = app.input_buffer.to_string();
             app.rename_folder(&new_name);
             if app.dialog_error.is_none() {
                 app.input_buffer.clear();
@@ -1332,21 +2002,14 @@ fn handle_rename_folder_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
             app.dialog_error = None;
             app.dialog = DialogState::None;
         }
-        KeyCode::Char(c) => {
-            app.dialog_error = None;
-            app.input_buffer.push(c);
-        }
-        KeyCode::Backspace => {
-            app.dialog_error = None;
-            app.input_buffer.pop();
-        }
-        _ => {}
+        _ => edit_dialog_input(app, key),
     }
 }
 
 fn handle_help_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
-    // Max scroll is approximately the right column content length (the longer one)
-    const MAX_HELP_LINES: usize = 90;
+    // Max scroll is approximately both columns' content length, as the
+    // narrow single-column layout stacks them
+    const MAX_HELP_LINES: usize = 160;
 
     match key.code {
         KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') | KeyCode::Char('?') => {
@@ -1365,10 +2028,16 @@ fn handle_help_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
         KeyCode::Char('u') if key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) => {
             app.help_scroll = app.help_scroll.saturating_sub(10);
         }
-        KeyCode::Char('g') => {
+        KeyCode::PageDown => {
+            app.help_scroll = app.help_scroll.saturating_add(20).min(MAX_HELP_LINES);
+        }
+        KeyCode::PageUp => {
+            app.help_scroll = app.help_scroll.saturating_sub(20);
+        }
+        KeyCode::Char('g') | KeyCode::Home => {
             app.help_scroll = 0;
         }
-        KeyCode::Char('G') => {
+        KeyCode::Char('G') | KeyCode::End => {
             app.help_scroll = MAX_HELP_LINES;
         }
         _ => {}
@@ -1547,6 +2216,11 @@ fn repel_nodes_from(app: &mut App, node_idx: usize) {
 }
 
 fn handle_graph_view_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    if app.graph_view.search.is_some() {
+        handle_graph_search_key(app, key);
+        return;
+    }
+
     if key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) {
         if let Some(node_idx) = app.graph_view.selected_node {
             if node_idx < app.graph_view.nodes.len() {
@@ -1578,10 +2252,50 @@ fn handle_graph_view_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
         }
     }
 
+    if app.graph_view.links_focused {
+        let links = app.graph_view.selected_links();
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                if app.graph_view.link_index + 1 < links.len() {
+                    app.graph_view.link_index += 1;
+                }
+                return;
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                app.graph_view.link_index = app.graph_view.link_index.saturating_sub(1);
+                return;
+            }
+            KeyCode::Enter => {
+                // Follow the link: the note it points at becomes the selected node
+                if let Some(link) = links.get(app.graph_view.link_index) {
+                    app.graph_view.selected_node = Some(link.node);
+                    app.graph_view.link_index = 0;
+                    center_on_selected_node(app);
+                }
+                return;
+            }
+            KeyCode::Tab | KeyCode::Esc => {
+                app.graph_view.links_focused = false;
+                return;
+            }
+            _ => {}
+        }
+    }
+
     match key.code {
         KeyCode::Esc | KeyCode::Char('q') => {
             app.dialog = DialogState::None;
         }
+        KeyCode::Tab if !app.graph_view.selected_links().is_empty() => {
+            app.graph_view.links_focused = true;
+            app.graph_view.link_index = 0;
+        }
+        KeyCode::Char('n') => {
+            app.graph_view.focus_neighborhood = !app.graph_view.focus_neighborhood;
+        }
+        KeyCode::Char('/') => {
+            app.open_graph_search();
+        }
         KeyCode::Char('h') | KeyCode::Left => {
             navigate_graph_node(app, GraphDirection::Left);
         }
@@ -1604,8 +2318,6 @@ fn handle_graph_view_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
                                 app.selected_sidebar_index = idx;
                                 app.selected_note = note_idx;
                                 app.push_navigation_history(note_idx);
-                                app.content_cursor = 0;
-                                app.content_scroll_offset = 0;
                                 app.update_content_items();
                                 app.update_outline();
                                 app.dialog = DialogState::None;
@@ -1657,6 +2369,7 @@ fn handle_graph_view_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
         KeyCode::Char('u') => {
             // Unselect current node
             app.graph_view.selected_node = None;
+            app.graph_view.links_focused = false;
         }
         _ => {}
     }
@@ -1718,6 +2431,44 @@ fn navigate_graph_node(app: &mut App, direction: GraphDirection) {
     }
 }
 
+/// Keys while the graph's `/` search is open: typing narrows the matches,
+/// Enter goes to the chosen one and Esc goes back to the graph as it was.
+fn handle_graph_search_key(app: &mut App, key: crossterm::event::KeyEvent) {
+    let Some(search) = app.graph_view.search.as_mut() else {
+        return;
+    };
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc => {
+            app.graph_view.search = None;
+        }
+        KeyCode::Enter => app.pick_graph_search(),
+        KeyCode::Down | KeyCode::Tab => {
+            search.selected = (search.selected + 1).min(search.matches.len().saturating_sub(1));
+        }
+        KeyCode::Char('n') if ctrl => {
+            search.selected = (search.selected + 1).min(search.matches.len().saturating_sub(1));
+        }
+        KeyCode::Up | KeyCode::BackTab => {
+            search.selected = search.selected.saturating_sub(1);
+        }
+        KeyCode::Char('p') if ctrl => {
+            search.selected = search.selected.saturating_sub(1);
+        }
+        KeyCode::Backspace => {
+            search.query.pop();
+            search.selected = 0;
+            app.update_graph_search();
+        }
+        KeyCode::Char(c) if !ctrl => {
+            search.query.push(c);
+            search.selected = 0;
+            app.update_graph_search();
+        }
+        _ => {}
+    }
+}
+
 fn center_on_selected_node(app: &mut App) {
     app.graph_view.needs_center = true;
 }
@@ -1730,6 +2481,7 @@ fn handle_graph_view_mouse(app: &mut App, mouse: crossterm::event::MouseEvent) {
         MouseEventKind::Down(MouseButton::Left) => {
             if let Some(idx) = find_node_at_position(app, mouse_x, mouse_y) {
                 app.graph_view.selected_node = Some(idx);
+                app.graph_view.link_index = 0;
                 app.graph_view.dragging_node = Some(idx);
                 app.graph_view.drag_start = Some((mouse_x, mouse_y));
                 app.graph_view.is_panning = false;
@@ -1787,6 +2539,11 @@ fn find_node_at_position(app: &App, mouse_x: u16, mouse_y: u16) -> Option<usize>
     let inner_x = 1u16;
     let inner_y = 1u16;
 
+    // Past the graph's width is the link strip, which nodes are clipped under
+    if mouse_x as f32 >= inner_x as f32 + app.graph_view.view_width {
+        return None;
+    }
+
     for (idx, node) in app.graph_view.nodes.iter().enumerate() {
         let screen_x = ((node.x - vx) * zoom + inner_x as f32) as i32;
         let screen_y = ((node.y - vy) * zoom + inner_y as f32) as i32;
@@ -1817,12 +2574,36 @@ fn handle_empty_directory_dialog(app: &mut App, key: crossterm::event::KeyEvent)
     }
 }
 
+fn handle_scan_errors_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    match key.code {
+        KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q') => {
+            app.dialog = DialogState::None;
+        }
+        KeyCode::Char('r') => app.retry_scan(),
+        KeyCode::Char('n') if app.notes.is_empty() => {
+            app.input_buffer.clear();
+            app.dialog = DialogState::CreateNote;
+        }
+        KeyCode::Down | KeyCode::Char('j') if app.scan_errors_scroll + 1 < app.scan_errors.len() => {
+            app.scan_errors_scroll += 1;
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.scan_errors_scroll = app.scan_errors_scroll.saturating_sub(1);
+        }
+        _ => {}
+    }
+}
+
 fn handle_directory_not_found_dialog(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
     match key.code {
         KeyCode::Char('c') | KeyCode::Char('C') => {
             app.create_notes_directory();
             false
         }
+        KeyCode::Char('p') | KeyCode::Char('P') => {
+            app.open_change_notes_dir();
+            false
+        }
         KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => {
             true
         }
@@ -1830,6 +2611,22 @@ fn handle_directory_not_found_dialog(app: &mut App, key: crossterm::event::KeyEv
     }
 }
 
+/// e edits config.toml in `$EDITOR`, c goes on with the defaults, q quits.
+fn handle_config_error_dialog(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('e') | KeyCode::Char('E') => {
+            app.edit_config_requested = true;
+            false
+        }
+        KeyCode::Char('c') | KeyCode::Char('C') => {
+            app.continue_with_default_config();
+            false
+        }
+        KeyCode::Char('q') | KeyCode::Char('Q') => true,
+        _ => false,
+    }
+}
+
 fn handle_welcome_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
     match key.code {
         KeyCode::Enter | KeyCode::Esc | KeyCode::Char(' ') => {
@@ -1847,7 +2644,10 @@ fn handle_search_picker_input(app: &mut App, key: crossterm::event::KeyEvent) {
         KeyCode::Enter => {
             app.select_search_picker_result();
         }
-        KeyCode::Left | KeyCode::Right => {
+        // Links are only inserted from the note list
+        KeyCode::Left | KeyCode::Right
+            if !matches!(app.search_picker, SearchPickerState::Open { purpose: PickerPurpose::InsertLink, .. }) =>
+        {
             app.toggle_search_picker_mode();
         }
         KeyCode::Up | KeyCode::BackTab => {
@@ -1868,13 +2668,14 @@ fn handle_search_picker_input(app: &mut App, key: crossterm::event::KeyEvent) {
         KeyCode::Char('p') if key.modifiers == KeyModifiers::CONTROL => {
             app.search_picker_select_prev();
         }
-        KeyCode::Backspace => {
-            app.search_picker_pop_char();
-        }
-        KeyCode::Char(c) => {
-            app.search_picker_push_char(c);
+        _ => {
+            let SearchPickerState::Open { query, .. } = &mut app.search_picker else {
+                return;
+            };
+            if query.handle_key(key) == LineEdit::Changed {
+                app.update_search_picker_results();
+            }
         }
-        _ => {}
     }
 }
 
@@ -1902,6 +2703,156 @@ fn handle_theme_selector_dialog(app: &mut App, key: crossterm::event::KeyEvent)
     }
 }
 
+fn handle_macro_list_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    if app.macro_list.renaming {
+        match key.code {
+            KeyCode::Char(c) if c.is_ascii_lowercase() => app.rename_selected_macro(c),
+            _ => app.macro_list.renaming = false,
+        }
+        return;
+    }
+
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.close_macro_list();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.macro_list_select_prev();
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.macro_list_select_next();
+        }
+        KeyCode::Char('d') => {
+            app.delete_selected_macro();
+        }
+        KeyCode::Char('r') => {
+            app.macro_list.renaming = true;
+        }
+        _ => {}
+    }
+}
+
+fn handle_yank_list_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => app.close_yank_list(),
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.yank_list.selected = app.yank_list.selected.saturating_sub(1);
+        }
+        KeyCode::Down | KeyCode::Char('j') if app.yank_list.selected + 1 < app.editor.yank_ring().len() => {
+            app.yank_list.selected += 1;
+        }
+        KeyCode::Enter => app.paste_selected_yank(),
+        _ => {}
+    }
+}
+
+fn handle_history_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    if let Some((_, scroll)) = app.history.viewing.as_mut() {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => app.history.viewing = None,
+            KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => *scroll += 1,
+            KeyCode::Char('r') => app.restore_selected_snapshot(),
+            _ => {}
+        }
+        return;
+    }
+
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.close_history();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.history_select_prev();
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.history_select_next();
+        }
+        KeyCode::Enter => {
+            app.view_selected_snapshot();
+        }
+        KeyCode::Char('r') => {
+            app.restore_selected_snapshot();
+        }
+        _ => {}
+    }
+}
+
+fn handle_prose_report_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.close_prose_report();
+        }
+        KeyCode::Enter => {
+            app.jump_to_prose_hint();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.prose_report_select_prev();
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.prose_report_select_next();
+        }
+        _ => {}
+    }
+}
+
+fn handle_move_picker_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.close_move_picker();
+        }
+        KeyCode::Enter => {
+            app.move_to_picked_folder();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.move_picker_select_prev();
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.move_picker_select_next();
+        }
+        _ => {}
+    }
+}
+
+fn handle_vault_picker_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.cancel_vault_selection();
+        }
+        KeyCode::Enter => {
+            app.confirm_vault_selection();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.vault_picker_select_prev();
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.vault_picker_select_next();
+        }
+        _ => {}
+    }
+}
+
+fn handle_refile_picker_dialog(app: &mut App, key: crossterm::event::KeyEvent) {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc => {
+            app.cancel_refile();
+        }
+        KeyCode::Enter => {
+            app.confirm_refile();
+        }
+        KeyCode::Up => app.refile_picker_select_prev(),
+        KeyCode::Char('k' | 'p') if ctrl => app.refile_picker_select_prev(),
+        KeyCode::Down => app.refile_picker_select_next(),
+        KeyCode::Char('j' | 'n') if ctrl => app.refile_picker_select_next(),
+        _ => {
+            if app.refile_picker.query.handle_key(key) == LineEdit::Changed {
+                app.refile_picker.selected = 0;
+            }
+        }
+    }
+}
+
 fn handle_search_input(app: &mut App, key: crossterm::event::KeyEvent) {
     let is_nav_down = key.code == KeyCode::Down
         || (key.code == KeyCode::Char('j') && key.modifiers == KeyModifiers::CONTROL)
@@ -1947,47 +2898,62 @@ fn handle_search_input(app: &mut App, key: crossterm::event::KeyEvent) {
         KeyCode::Enter => {
             app.search_active = false;
         }
-        KeyCode::Backspace => {
-            app.search_query.pop();
-            app.update_filtered_indices();
-        }
-        KeyCode::Char(c) => {
-            app.search_query.push(c);
-            app.update_filtered_indices();
+        _ => {
+            if app.search_query.handle_key(key) == LineEdit::Changed {
+                app.update_filtered_indices();
+            }
         }
-        _ => {}
     }
 }
 
 fn handle_buffer_search_input(app: &mut App, key: crossterm::event::KeyEvent) {
     match key.code {
         KeyCode::Esc => {
+            if !app.buffer_search.matches.is_empty() {
+                let pattern = app.buffer_search.query.to_string();
+                app.search_history.push(&pattern);
+            }
             app.end_buffer_search();
             if app.mode == Mode::Edit {
                 app.editor.clear_search_highlights();
             }
         }
-        KeyCode::Enter => {
-            if !app.buffer_search.matches.is_empty() {
-                app.buffer_search_next();
-                update_editor_search_highlights(app);
-            }
+        // An empty prompt searches again for the last pattern
+        KeyCode::Enter if app.buffer_search.query.is_empty() => {
+            app.recall_last_search();
+            update_editor_search_highlights(app);
         }
-        KeyCode::Backspace => {
-            app.buffer_search.query.pop();
-            app.perform_buffer_search();
-            if !app.buffer_search.matches.is_empty() {
-                app.scroll_to_current_match();
+        KeyCode::Enter if app.buffer_search_setting().is_some() => {
+            let setting = app.buffer_search_setting().unwrap_or_default();
+            app.end_buffer_search();
+            app.status_message = Some(app.set_option(&setting).unwrap_or_else(|err| err));
+        }
+        KeyCode::Enter => match app.buffer_search_line_jump() {
+            Some(Ok(jump)) => {
+                app.end_buffer_search();
+                app.jump_to_source_line(jump);
+            }
+            // Text that merely starts with `:` is still searched for
+            Some(Err(error)) if app.buffer_search.matches.is_empty() => app.status_message = Some(error),
+            _ => {
+                let pattern = app.buffer_search.query.to_string();
+                app.search_history.push(&pattern);
+                if !app.buffer_search.matches.is_empty() {
+                    app.buffer_search_next();
+                    update_editor_search_highlights(app);
+                }
             }
+        },
+        KeyCode::Char('r') if key.modifiers == KeyModifiers::CONTROL => {
+            app.recall_last_search();
             update_editor_search_highlights(app);
         }
-        KeyCode::Char(c) if key.modifiers == KeyModifiers::SHIFT => {
-            app.buffer_search.query.push(c);
-            app.perform_buffer_search();
-            if !app.buffer_search.matches.is_empty() {
-                app.scroll_to_current_match();
+        // Pressed again on the empty prompt it opened, Ctrl+F takes the word under the cursor
+        KeyCode::Char('f') if key.modifiers == KeyModifiers::CONTROL => {
+            if app.buffer_search.query.is_empty() {
+                app.start_buffer_search_for_word();
+                update_editor_search_highlights(app);
             }
-            update_editor_search_highlights(app);
         }
         KeyCode::Char('n') if key.modifiers == KeyModifiers::CONTROL => {
             if !app.buffer_search.matches.is_empty() {
@@ -2009,27 +2975,36 @@ fn handle_buffer_search_input(app: &mut App, key: crossterm::event::KeyEvent) {
             }
             update_editor_search_highlights(app);
         }
-        KeyCode::Char(c) => {
-            app.buffer_search.query.push(c);
-            app.perform_buffer_search();
-            if !app.buffer_search.matches.is_empty() {
-                app.scroll_to_current_match();
-            }
-            update_editor_search_highlights(app);
-        }
-        KeyCode::Down | KeyCode::Tab => {
+        KeyCode::Tab => {
             if !app.buffer_search.matches.is_empty() {
                 app.buffer_search_next();
                 update_editor_search_highlights(app);
             }
         }
-        KeyCode::Up | KeyCode::BackTab => {
+        KeyCode::BackTab => {
             if !app.buffer_search.matches.is_empty() {
                 app.buffer_search_prev();
                 update_editor_search_highlights(app);
             }
         }
-        _ => {}
+        KeyCode::Up => {
+            app.buffer_search_history_older();
+            update_editor_search_highlights(app);
+        }
+        KeyCode::Down => {
+            app.buffer_search_history_newer();
+            update_editor_search_highlights(app);
+        }
+        _ => {
+            if app.buffer_search.query.handle_key(key) == LineEdit::Changed {
+                app.search_history.stop_browsing();
+                app.perform_buffer_search();
+                if !app.buffer_search.matches.is_empty() {
+                    app.scroll_to_current_match();
+                }
+                update_editor_search_highlights(app);
+            }
+        }
     }
 }
 
@@ -2048,21 +3023,88 @@ fn update_editor_search_highlights(app: &mut App) {
     }
 }
 
+/// Keys while the content panel shows the note's source. It's read-only, so
+/// keys that act on rendered items do nothing; returns false for keys left to
+/// the usual view mode handling.
+fn handle_source_view_key(
+    app: &mut App,
+    key: crossterm::event::KeyEvent,
+    was_pending_g: bool,
+    count: Option<usize>,
+) -> bool {
+    let steps = count.unwrap_or(1) as isize;
+    let page = app.content_area.height.saturating_sub(2).max(1) as isize;
+    let half_page = (page / 2).max(1);
+    match key.code {
+        KeyCode::Char('s') if was_pending_g => app.toggle_source_view(),
+        KeyCode::Char('g') if was_pending_g => app.set_source_cursor(count.unwrap_or(1) - 1),
+        KeyCode::Char('G') => app.set_source_cursor(count.map_or(usize::MAX, |line| line - 1)),
+        KeyCode::Down | KeyCode::Char('j') if key.modifiers.is_empty() => app.move_source_cursor(steps),
+        KeyCode::Up | KeyCode::Char('k') if key.modifiers.is_empty() => app.move_source_cursor(-steps),
+        KeyCode::Char('d') if key.modifiers == KeyModifiers::CONTROL => app.move_source_cursor(half_page),
+        KeyCode::Char('u') if key.modifiers == KeyModifiers::CONTROL => app.move_source_cursor(-half_page),
+        KeyCode::PageDown => app.move_source_cursor(page),
+        KeyCode::PageUp => app.move_source_cursor(-page),
+        KeyCode::Home => app.set_source_cursor(0),
+        KeyCode::End => app.set_source_cursor(usize::MAX),
+        KeyCode::Esc => app.toggle_source_view(),
+        KeyCode::Enter
+        | KeyCode::Char(' ' | 'o' | 'C' | 'X' | 'u' | 'J' | 'K' | 'Y' | 'H' | 'M' | 'L' | '{' | '}' | '[' | ']') => {}
+        _ => return false,
+    }
+    true
+}
+
 /// Returns true if the app should quit
 fn handle_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
     let was_pending_g = app.pending_g;
     let was_pending_z = app.pending_z;
+    let count = app.pending_count.take();
     app.pending_g = false;
     app.pending_z = false;
     app.status_message = None;  // Clear old status message on new keystroke
 
+    if let Some(quit) = handle_leader_key(app, key) {
+        return quit;
+    }
+
+    // Counts for content motions (5j, 3}, 42G); a leading 0 is not a count
+    if let KeyCode::Char(c @ '0'..='9') = key.code {
+        if app.focus == Focus::Content && key.modifiers.is_empty() && (c != '0' || count.is_some()) {
+            let digit = c.to_digit(10).unwrap_or(0) as usize;
+            app.pending_count = Some(count.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+            return false;
+        }
+    }
+
+    if app.source_view.is_some()
+        && app.focus == Focus::Content
+        && handle_source_view_key(app, key, was_pending_g, count)
+    {
+        return false;
+    }
+
     match key.code {
         KeyCode::Char('q') => return true,
+        // Step through the quickfix list while its window is open
+        KeyCode::Char('n') if app.quickfix_open && key.modifiers == KeyModifiers::CONTROL => {
+            app.quickfix_step(true);
+        }
+        KeyCode::Char('p') if app.quickfix_open && key.modifiers == KeyModifiers::CONTROL => {
+            app.quickfix_step(false);
+        }
+        // The selected note or folder's path, or the open note's
+        KeyCode::Char('p') if was_pending_g && key.modifiers.is_empty() => app.copy_path(),
+        KeyCode::Char('o') if was_pending_g && key.modifiers.is_empty() => app.reveal_path(),
         KeyCode::Tab | KeyCode::Char('l') | KeyCode::Right if !app.zen_mode => app.toggle_focus(false),
         KeyCode::BackTab | KeyCode::Char('h') | KeyCode::Left if !app.zen_mode => app.toggle_focus(true),
         KeyCode::Char('e') => {
-            app.push_navigation_history(app.selected_note);
-            app.enter_edit_mode();
+            if app.folder_view_path().is_some() {
+                app.create_folder_index_note();
+            } else {
+                app.push_navigation_history(app.selected_note);
+                app.enter_edit_mode();
+            }
         }
         KeyCode::Char('n') if !app.zen_mode => {
             app.input_buffer.clear();
@@ -2087,56 +3129,75 @@ fn handle_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
             app.dialog = DialogState::CreateFolder;
         }
         KeyCode::Char('d') if !app.zen_mode && key.modifiers.is_empty() => {
-            if let Some(item) = app.sidebar_items.get(app.selected_sidebar_index) {
-                match &item.kind {
-                    SidebarItemKind::Note { .. } => {
-                        app.dialog = DialogState::DeleteConfirm;
-                    }
-                    SidebarItemKind::Folder { .. } => {
-                        app.dialog = DialogState::DeleteFolderConfirm;
-                    }
-                }
+            if app.focus == Focus::Sidebar && !app.sidebar_marks.is_empty() {
+                app.confirm_delete_marked();
+            } else {
+                app.confirm_delete_selected();
             }
         }
+        KeyCode::Char('m') if app.focus == Focus::Sidebar && key.modifiers.is_empty() => {
+            app.open_move_picker();
+        }
+        KeyCode::Char('V') if app.focus == Focus::Sidebar => {
+            app.mark_range_to_selected();
+        }
         KeyCode::Char('x') if !app.zen_mode && key.modifiers.is_empty() => {
             if app.focus == Focus::Sidebar {
                 app.cut_selected_item();
             }
         }
+        KeyCode::Char('C') if app.focus == Focus::Content => {
+            app.start_section_tasks(TaskOp::Complete);
+        }
+        KeyCode::Char('X') if app.focus == Focus::Content => {
+            app.start_section_tasks(TaskOp::Clear);
+        }
+        KeyCode::Char('u') if app.focus == Focus::Content && key.modifiers.is_empty() => {
+            app.undo_view_edit();
+        }
+        KeyCode::Char('Y') if app.focus == Focus::Content => {
+            app.open_link_picker();
+        }
         KeyCode::Char('p') if !app.zen_mode && key.modifiers.is_empty() => {
             if app.focus == Focus::Sidebar && app.cut_buffer.is_some() {
                 if let Err(e) = app.paste_cut_item() {
                     app.status_message = Some(format!("Move failed: {}", e));
                 }
+            } else if app.focus == Focus::Sidebar {
+                app.toggle_pin_selected();
             }
         }
+        KeyCode::Char('r') if app.focus == Focus::Content && app.folder_view_path().is_none() => {
+            app.start_title_edit();
+        }
         KeyCode::Char('r') if !app.zen_mode => {
             if let Some(item) = app.sidebar_items.get(app.selected_sidebar_index) {
                 match &item.kind {
                     SidebarItemKind::Note { note_index } => {
                         if let Some(note) = app.notes.get(*note_index) {
-                            app.input_buffer = note.title.clone();
+                            app.input_buffer.set(note.title.clone());
                             app.dialog_error = None;
                             app.dialog = DialogState::RenameNote;
                         }
                     }
                     SidebarItemKind::Folder { .. } => {
-                        app.input_buffer = item.display_name.clone();
+                        app.input_buffer.set(item.display_name.clone());
                         app.dialog_error = None;
                         app.dialog = DialogState::RenameFolder;
                     }
+                    SidebarItemKind::Header => {}
                 }
             }
         }
         KeyCode::Char('R') if key.modifiers == KeyModifiers::SHIFT | KeyModifiers::CONTROL => {
             app.reload_config();
-            app.needs_full_clear = true;
         }
         KeyCode::Char('R') => {
             if was_pending_z && app.focus == Focus::Content {
                 app.unfold_all_headings();
             } else {
                 app.reload_on_focus();
+                app.report_scan_errors();
                 app.needs_full_clear = true;
             }
         }
@@ -2154,10 +3215,12 @@ fn handle_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
                 Focus::Sidebar => app.next_sidebar_item(),
                 Focus::Outline => app.next_outline(),
                 Focus::Content => {
-                    if app.floating_cursor_mode {
-                        app.floating_move_down();
-                    } else {
-                        app.next_content_line();
+                    for _ in 0..count.unwrap_or(1) {
+                        if app.floating_cursor_mode {
+                            app.floating_move_down();
+                        } else {
+                            app.next_content_line();
+                        }
                     }
                     app.sync_outline_to_content();
                 }
@@ -2168,10 +3231,12 @@ fn handle_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
                 Focus::Sidebar => app.previous_sidebar_item(),
                 Focus::Outline => app.previous_outline(),
                 Focus::Content => {
-                    if app.floating_cursor_mode {
-                        app.floating_move_up();
-                    } else {
-                        app.previous_content_line();
+                    for _ in 0..count.unwrap_or(1) {
+                        if app.floating_cursor_mode {
+                            app.floating_move_up();
+                        } else {
+                            app.previous_content_line();
+                        }
                     }
                     app.sync_outline_to_content();
                 }
@@ -2180,7 +3245,9 @@ fn handle_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
         KeyCode::Enter => {
             match app.focus {
                 Focus::Content => {
-                        if app.current_item_link().is_some() {
+                        if app.is_folder_entry_at(app.content_cursor) {
+                            app.open_current_folder_entry();
+                        } else if app.current_item_link().is_some() {
                             app.open_current_link();
                         } else {
                             app.open_current_image();
@@ -2201,7 +3268,9 @@ fn handle_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
         }
         KeyCode::Char('o') => {
             if app.focus == Focus::Content {
-                if app.current_item_link().is_some() {
+                if app.is_folder_entry_at(app.content_cursor) {
+                    app.open_current_folder_entry();
+                } else if app.current_item_link().is_some() {
                     app.open_current_link();
                 } else {
                     app.open_current_image();
@@ -2219,11 +3288,37 @@ fn handle_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
                 app.activate_sidebar_search();
             }
         }
+        // `:42`, `:+5` or `:50%` in the search box jumps to that source line
+        KeyCode::Char(':') if app.focus == Focus::Content => {
+            app.start_buffer_search();
+            app.buffer_search.query.set(":");
+        }
+        KeyCode::Char('s') if was_pending_g && app.focus == Focus::Content => {
+            app.toggle_source_view();
+        }
+        KeyCode::Char('y') if was_pending_g && app.focus == Focus::Content => {
+            app.copy_as_html(None);
+        }
+        KeyCode::Char('r') if was_pending_g && app.focus == Focus::Content => {
+            app.start_refile();
+        }
+        KeyCode::Char('m') if was_pending_g && app.focus == Focus::Content => {
+            app.start_section_refile(false);
+        }
+        KeyCode::Char('c') if was_pending_g && app.focus == Focus::Content => {
+            app.start_section_refile(true);
+        }
+        KeyCode::Char('y') if app.focus == Focus::Content && key.modifiers.is_empty() => {
+            app.copy_link_target();
+        }
         KeyCode::Char('s') => {
             if app.focus == Focus::Sidebar {
                 app.cycle_sort_mode();
             }
         }
+        KeyCode::Char('f') if app.focus == Focus::Sidebar && key.modifiers.is_empty() => {
+            app.cycle_recent_filter();
+        }
         KeyCode::Char(' ') => {
             if app.focus == Focus::Content {
                 // task items: toggle if checkbox selected, otherwise follow link
@@ -2248,7 +3343,7 @@ fn handle_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
                         // No links in task, just toggle
                         app.toggle_current_task();
                     }
-                } else if let Some(crate::app::ContentItem::Details { .. }) = app.content_items.get(app.content_cursor) {
+                } else if app.item_is_details_at(app.content_cursor) {
                     app.toggle_current_details();
                 } else if app.is_heading_at(app.content_cursor) {
                     app.toggle_current_heading_fold();
@@ -2267,6 +3362,8 @@ fn handle_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
                         }
                     }
                 }
+            } else if app.focus == Focus::Sidebar {
+                app.toggle_mark_selected();
             }
         }
         KeyCode::Char(']') => {
@@ -2302,11 +3399,17 @@ fn handle_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
         KeyCode::Char('f') if key.modifiers == KeyModifiers::CONTROL => {
             app.start_buffer_search();
         }
+        KeyCode::Char('f') if key.modifiers == KeyModifiers::ALT => {
+            app.start_buffer_search_for_word();
+        }
         KeyCode::Char('g') if key.modifiers == KeyModifiers::CONTROL => {
             app.build_graph();
             app.dialog = DialogState::GraphView;
         }
         KeyCode::Char('z') if key.modifiers == KeyModifiers::CONTROL => {
+            app.suspend_requested = true;
+        }
+        KeyCode::Char('Z') => {
             app.toggle_zen_mode();
         }
         KeyCode::Char('m') if key.modifiers == KeyModifiers::CONTROL => {
@@ -2320,8 +3423,22 @@ fn handle_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
         KeyCode::Char('M') => {
             if was_pending_z && app.focus == Focus::Content {
                 app.fold_all_headings();
+            } else if app.focus == Focus::Content {
+                app.goto_content_screen_line(ScreenLine::Middle, 0);
+                app.sync_outline_to_content();
             }
         }
+        KeyCode::Char('H') | KeyCode::Char('L') if app.focus == Focus::Content => {
+            let line = if key.code == KeyCode::Char('H') { ScreenLine::Top } else { ScreenLine::Bottom };
+            app.goto_content_screen_line(line, count.unwrap_or(1) - 1);
+            app.sync_outline_to_content();
+        }
+        KeyCode::Char('}') | KeyCode::Char('{') if app.focus == Focus::Content => {
+            for _ in 0..count.unwrap_or(1) {
+                app.move_content_paragraph(key.code == KeyCode::Char('}'));
+            }
+            app.sync_outline_to_content();
+        }
         KeyCode::Char('a') => {
             if was_pending_z && app.focus == Focus::Content {
                 app.toggle_current_heading_fold();
@@ -2333,12 +3450,16 @@ fn handle_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
                     Focus::Sidebar => app.goto_first_sidebar_item(),
                     Focus::Outline => app.goto_first_outline(),
                     Focus::Content => {
-                        app.goto_first_content_line();
+                        match count {
+                            Some(line) => app.goto_content_source_line(line),
+                            None => app.goto_first_content_line(),
+                        }
                         app.sync_outline_to_content();
                     }
                 }
             } else {
                 app.pending_g = true;
+                app.pending_count = count;
             }
         }
         KeyCode::Char('G') => {
@@ -2346,27 +3467,160 @@ fn handle_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) -> bool {
                 Focus::Sidebar => app.goto_last_sidebar_item(),
                 Focus::Outline => app.goto_last_outline(),
                 Focus::Content => {
-                    app.goto_last_content_line();
+                    match count {
+                        Some(line) => app.goto_content_source_line(line),
+                        None => app.goto_last_content_line(),
+                    }
                     app.sync_outline_to_content();
                 }
             }
         }
-        KeyCode::Esc => {
-            if app.focus == Focus::Sidebar && app.cut_buffer.is_some() {
-                app.clear_cut_buffer();
+        KeyCode::Home => match app.focus {
+            Focus::Sidebar => app.goto_first_sidebar_item(),
+            Focus::Outline => app.goto_first_outline(),
+            Focus::Content => {
+                app.goto_first_content_line();
+                app.sync_outline_to_content();
+            }
+        },
+        KeyCode::End => match app.focus {
+            Focus::Sidebar => app.goto_last_sidebar_item(),
+            Focus::Outline => app.goto_last_outline(),
+            Focus::Content => {
+                app.goto_last_content_line();
+                app.sync_outline_to_content();
+            }
+        },
+        KeyCode::PageDown | KeyCode::PageUp => {
+            let forward = key.code == KeyCode::PageDown;
+            match app.focus {
+                Focus::Sidebar => app.page_sidebar(forward),
+                Focus::Outline => app.page_outline(forward),
+                Focus::Content => {
+                    app.page_content(forward);
+                    app.sync_outline_to_content();
+                }
             }
         }
+        KeyCode::Esc if app.focus == Focus::Sidebar => app.clear_marks_or_cut(),
         _ => {}
     }
     false
 }
 
+/// Leader sequences (`<leader>w`, `<leader>ff`, ...) in view and edit normal mode.
+/// Returns `None` if the key isn't part of a leader sequence, otherwise whether
+/// the app should quit.
+fn handle_leader_key(app: &mut App, key: crossterm::event::KeyEvent) -> Option<bool> {
+    let plain_char = match key.code {
+        KeyCode::Char(c) if key.modifiers.difference(KeyModifiers::SHIFT).is_empty() => Some(c),
+        _ => None,
+    };
+
+    if app.leader.is_pending() {
+        let action = match plain_char {
+            Some(c) => app.leader.feed(c, &app.leader_map, app.clock.now()),
+            None => {
+                app.leader.cancel();
+                None
+            }
+        };
+        return Some(action.is_some_and(|action| run_leader_action(app, action)));
+    }
+
+    // Space already toggles tasks and follows links in the content view, and
+    // marks items in the sidebar
+    let shadowed = app.mode == Mode::Normal && app.focus != Focus::Outline && plain_char == Some(' ');
+    if plain_char.is_some()
+        && plain_char == app.leader_key
+        && !shadowed
+        && app.vim.pending_operator.is_none()
+        && !app.leader_map.is_empty()
+    {
+        app.vim.reset_pending();
+        app.leader.start(app.clock.now());
+        return Some(false);
+    }
+    None
+}
+
+/// Run a `[leader]` action. Returns true if the app should quit.
+fn run_leader_action(app: &mut App, action: LeaderAction) -> bool {
+    if app.mode == Mode::Edit {
+        match action {
+            LeaderAction::Save => {
+                app.editor.cancel_selection();
+                app.save_edit();
+                app.vim_mode = VimMode::Normal;
+                update_cursor_style(app);
+            }
+            LeaderAction::Quit => {
+                if app.has_unsaved_changes() {
+                    app.dialog = DialogState::UnsavedChanges;
+                } else {
+                    app.cancel_edit();
+                }
+            }
+            LeaderAction::BufferSearch => app.start_buffer_search(),
+            LeaderAction::Help => app.dialog = DialogState::Help,
+            LeaderAction::Quickfix => app.toggle_quickfix(),
+            _ => {
+                app.vim.status_message = Some("Exit edit mode (Esc) to use this".to_string());
+            }
+        }
+        return false;
+    }
+
+    match action {
+        LeaderAction::Save => {}
+        LeaderAction::Quit => return true,
+        LeaderAction::FileFinder => app.open_search_picker(),
+        LeaderAction::ContentSearch => {
+            app.open_search_picker();
+            app.toggle_search_picker_mode();
+        }
+        LeaderAction::BufferSearch => app.start_buffer_search(),
+        LeaderAction::Graph => {
+            app.build_graph();
+            app.dialog = DialogState::GraphView;
+        }
+        LeaderAction::Help => app.dialog = DialogState::Help,
+        LeaderAction::ToggleSidebar => app.toggle_sidebar_collapsed(),
+        LeaderAction::ToggleOutline => app.toggle_outline_collapsed(),
+        LeaderAction::Zen => app.toggle_zen_mode(),
+        LeaderAction::ThemeSelector => app.open_theme_selector(),
+        LeaderAction::Journal => app.open_or_create_journal(),
+        LeaderAction::Quickfix => app.toggle_quickfix(),
+        LeaderAction::Scaffold => app.start_scaffold(None),
+        LeaderAction::Vault => app.open_vault_picker(),
+        LeaderAction::ReloadConfig => app.reload_config(),
+        LeaderAction::Frontmatter => app.toggle_frontmatter_hidden(),
+    }
+    false
+}
+
 fn handle_edit_mode(app: &mut App, key: crossterm::event::KeyEvent) {
+    if app.confirm_substitute.is_some() {
+        handle_confirm_substitute(app, key);
+        app.update_editor_block();
+        return;
+    }
+
     if handle_wiki_autocomplete(app, key) {
         app.request_highlight_update();
         return;
     }
 
+    if handle_link_completion(app, key) {
+        app.request_highlight_update();
+        return;
+    }
+
+    if handle_word_completion(app, key) {
+        app.request_highlight_update();
+        return;
+    }
+
     // Handle context menu keyboard navigation first
     if let ContextMenuState::Open { x, y, selected_index } = app.context_menu_state {
         let items = ContextMenuItem::all();
@@ -2465,6 +3719,21 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
         app.vim.macros.record_key(key);
     }
 
+    // Ctrl-P / Ctrl-N straight after p or P swap the pasted text for an older
+    // or newer yank; any other key keeps the paste as it is
+    if key.modifiers == KeyModifiers::CONTROL
+        && matches!(key.code, KeyCode::Char('p') | KeyCode::Char('n'))
+        && app.editor.cycle_paste(key.code == KeyCode::Char('p'))
+    {
+        if let Some(index) = app.editor.last_paste_index() {
+            app.vim.status_message = Some(format!("Yank {} of {}", index + 1, app.editor.yank_ring().len()));
+        }
+        app.vim.reset_pending();
+        app.update_editor_highlights();
+        return;
+    }
+    app.editor.forget_paste();
+
     // Handle pending find (f/F/t/T waiting for char)
     if let Some(pending) = app.vim.pending_find.take() {
         if let KeyCode::Char(c) = key.code {
@@ -2520,6 +3789,7 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
                     PendingMacro::Record => {
                         if app.vim.macros.is_recording() {
                             app.vim.macros.stop_recording();
+                            app.save_macros();
                         } else {
                             app.vim.macros.start_recording(c);
                         }
@@ -2555,20 +3825,24 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
         if let KeyCode::Char(c) = key.code {
             match pending {
                 PendingMark::Set => {
-                    let pos = app.editor.cursor();
-                    app.vim.marks.set(c, crate::editor::Position::new(pos.0, pos.1));
+                    let (row, col) = app.editor.cursor();
+                    let note = app.current_note().and_then(|n| n.file_path.clone());
+                    match note {
+                        Some(note) if c.is_ascii_uppercase() => {
+                            app.vim.marks.set_in_note(c, &note, Position::new(row, col));
+                            app.save_marks();
+                        }
+                        _ => app.vim.marks.set(c, Position::new(row, col)),
+                    }
                 }
                 PendingMark::GotoExact => {
-                    if let Some(pos) = app.vim.marks.get(c) {
-                        app.vim.marks.set_last_jump(crate::editor::Position::new(app.editor.cursor().0, app.editor.cursor().1));
-                        app.editor.move_cursor(CursorMove::GoToLine(pos.row + 1));
-                        for _ in 0..pos.col { app.editor.move_cursor(CursorMove::Forward); }
+                    if let Some(pos) = mark_jump_target(app, c) {
+                        app.editor.set_cursor(pos.row, pos.col);
                     }
                 }
                 PendingMark::GotoLine => {
-                    if let Some(pos) = app.vim.marks.get(c) {
-                        app.vim.marks.set_last_jump(crate::editor::Position::new(app.editor.cursor().0, app.editor.cursor().1));
-                        app.editor.move_cursor(CursorMove::GoToLine(pos.row + 1));
+                    if let Some(pos) = mark_jump_target(app, c) {
+                        app.editor.set_cursor(pos.row, 0);
                         app.editor.move_cursor(CursorMove::FirstNonBlank);
                     }
                 }
@@ -2584,7 +3858,7 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
         match key.code {
             KeyCode::Char('g') => {
                 // Handle operator + gg (linewise motion to start of file or specific line)
-                if let Some(op) = app.pending_operator.take() {
+                if let Some(op) = app.vim.pending_operator.take() {
                     let target_line = if let Some(count) = app.vim.count.take() {
                         count.saturating_sub(1)
                     } else {
@@ -2592,39 +3866,9 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
                     };
 
                     let (current_row, _) = app.editor.cursor();
-                    let (start_row, end_row) = if target_line <= current_row {
-                        (target_line, current_row)
-                    } else {
-                        (current_row, target_line)
-                    };
-
-                    app.editor.set_cursor(start_row, 0);
-                    app.editor.start_selection();
-                    app.editor.set_cursor(end_row, 0);
-                    app.editor.move_cursor(CursorMove::End);
-
-                    match op {
-                        'd' => {
-                            app.editor.cut();
-                            if start_row < app.editor.lines().len() {
-                                app.editor.set_cursor(start_row, 0);
-                            }
-                        }
-                        'c' => {
-                            app.editor.cut();
-                            app.vim_mode = VimMode::Insert;
-                            update_cursor_style(app);
-                        }
-                        'y' => {
-                            app.editor.copy();
-                            app.editor.cancel_selection();
-                            app.editor.set_cursor(current_row, 0);
-                        }
-                        _ => {
-                            app.editor.cancel_selection();
-                        }
-                    }
+                    operate_on_lines(app, op, target_line.min(current_row), target_line.max(current_row));
                 } else {
+                    record_jump(app);
                     if let Some(count) = app.vim.count.take() {
                         app.editor.move_cursor(CursorMove::GoToLine(count));
                     } else {
@@ -2640,12 +3884,51 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
                 let count = app.vim.get_count();
                 for _ in 0..count { app.editor.move_cursor(CursorMove::BigWordEndBackward); }
             }
+            KeyCode::Char('l') => {
+                let count = app.vim.get_count();
+                app.cycle_list_style(count);
+            }
             _ => {}
         }
         app.vim.reset_pending();
         return;
     }
 
+    if handle_leader_key(app, key).is_some() {
+        return;
+    }
+
+    // Handle pending bracket (]c, [c for change hunks)
+    if let Some(bracket) = app.vim.pending_bracket.take() {
+        if key.code == KeyCode::Char('c') {
+            let forward = bracket == ']';
+            let count = app.vim.get_count();
+            app.refresh_change_markers_now();
+            for _ in 0..count {
+                if !app.editor.goto_change_hunk(forward) {
+                    break;
+                }
+            }
+            if app.editor.change_markers().is_empty() {
+                app.vim.status_message = Some("No changes".to_string());
+            }
+        } else if key.code == KeyCode::Char('d') {
+            let forward = bracket == ']';
+            let count = app.vim.get_count();
+            app.refresh_diagnostics();
+            for _ in 0..count {
+                if !app.editor.goto_diagnostic(forward) {
+                    break;
+                }
+            }
+            if app.diagnostics.is_empty() {
+                app.vim.status_message = Some("No diagnostics".to_string());
+            }
+        }
+        app.vim.reset_pending();
+        return;
+    }
+
     // Handle pending z (zz, zt, zb for scrolling)
     if app.vim.pending_z {
         app.vim.pending_z = false;
@@ -2662,6 +3945,19 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
                 // zb - scroll cursor line to bottom
                 app.editor.scroll_cursor_to_bottom();
             }
+            KeyCode::Char(c @ ('c' | 'o' | 'a')) => {
+                // zc / zo / za - fold a code block or the frontmatter
+                let done = match c {
+                    'c' => app.editor.close_fold(),
+                    'o' => app.editor.open_fold(),
+                    _ => app.editor.toggle_fold(),
+                };
+                if !done {
+                    app.vim.status_message = Some(
+                        if c == 'o' { "No fold here" } else { "Not in a code block or frontmatter" }.to_string(),
+                    );
+                }
+            }
             _ => {}
         }
         app.vim.reset_pending();
@@ -2692,11 +3988,22 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
             return;
         }
 
+        KeyCode::Char(c @ ('a' | 'x')) if key.modifiers == KeyModifiers::CONTROL => {
+            let count = app.vim.get_count() as i64;
+            let delta = if c == 'a' { count } else { -count };
+            app.vim.reset_pending();
+            app.vim.pending_operator = None;
+            app.editor.cancel_selection();
+            if increment_at_cursor(app, delta) {
+                app.vim.last_change = Some(crate::vim::LastChange::Increment(delta));
+            }
+        }
+
         // Text object triggers (must come before mode changes)
-        KeyCode::Char('i') if app.pending_operator.is_some() => {
+        KeyCode::Char('i') if app.vim.pending_operator.is_some() => {
             app.vim.pending_text_object_scope = Some(TextObjectScope::Inner);
         }
-        KeyCode::Char('a') if app.pending_operator.is_some() => {
+        KeyCode::Char('a') if app.vim.pending_operator.is_some() => {
             app.vim.pending_text_object_scope = Some(TextObjectScope::Around);
         }
 
@@ -2786,6 +4093,7 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
         KeyCode::Char('q') if key.modifiers.is_empty() => {
             if app.vim.macros.is_recording() {
                 app.vim.macros.stop_recording();
+                app.save_macros();
             } else {
                 app.vim.pending_macro = Some(PendingMacro::Record);
             }
@@ -2836,49 +4144,17 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
         }
         KeyCode::Char('G') => {
             // Handle operator + G (linewise motion to end of file or specific line)
-            if let Some(op) = app.pending_operator.take() {
+            if let Some(op) = app.vim.pending_operator.take() {
                 let target_line = if let Some(count) = app.vim.count.take() {
                     count.saturating_sub(1) // Convert to 0-indexed
                 } else {
                     app.editor.lines().len().saturating_sub(1)
                 };
 
-                // Select from current line to target line (linewise)
                 let (current_row, _) = app.editor.cursor();
-                let (start_row, end_row) = if target_line >= current_row {
-                    (current_row, target_line)
-                } else {
-                    (target_line, current_row)
-                };
-
-                app.editor.set_cursor(start_row, 0);
-                app.editor.start_selection();
-                app.editor.set_cursor(end_row, 0);
-                app.editor.move_cursor(CursorMove::End);
-
-                match op {
-                    'd' => {
-                        app.editor.cut();
-                        // Delete from start line to end line (inclusive)
-                        if start_row < app.editor.lines().len() {
-                            app.editor.set_cursor(start_row, 0);
-                        }
-                    }
-                    'c' => {
-                        app.editor.cut();
-                        app.vim_mode = VimMode::Insert;
-                        update_cursor_style(app);
-                    }
-                    'y' => {
-                        app.editor.copy();
-                        app.editor.cancel_selection();
-                        app.editor.set_cursor(current_row, 0);
-                    }
-                    _ => {
-                        app.editor.cancel_selection();
-                    }
-                }
+                operate_on_lines(app, op, current_row.min(target_line), current_row.max(target_line));
             } else {
+                record_jump(app);
                 if let Some(count) = app.vim.count.take() {
                     app.editor.move_cursor(CursorMove::GoToLine(count));
                 } else {
@@ -2889,28 +4165,47 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
         }
 
         // Paragraph motions
-        KeyCode::Char('{') => execute_motion_n(app, CursorMove::ParagraphBack),
-        KeyCode::Char('}') => execute_motion_n(app, CursorMove::ParagraphForward),
+        KeyCode::Char('{') => {
+            record_jump(app);
+            execute_motion_n(app, CursorMove::ParagraphBack);
+        }
+        KeyCode::Char('}') => {
+            record_jump(app);
+            execute_motion_n(app, CursorMove::ParagraphForward);
+        }
 
         // Screen motions
         KeyCode::Char('H') => {
             app.vim.reset_pending();
+            record_jump(app);
             app.editor.move_cursor(CursorMove::ScreenTop);
         }
         KeyCode::Char('M') => {
             app.vim.reset_pending();
+            record_jump(app);
             app.editor.move_cursor(CursorMove::ScreenMiddle);
         }
         KeyCode::Char('L') => {
             app.vim.reset_pending();
+            record_jump(app);
             app.editor.move_cursor(CursorMove::ScreenBottom);
         }
 
+        KeyCode::Char('z') if key.modifiers == KeyModifiers::CONTROL => {
+            app.vim.reset_pending();
+            app.suspend_requested = true;
+        }
+
         // z commands (zz, zt, zb for scroll positioning)
         KeyCode::Char('z') => {
             app.vim.pending_z = true;
         }
 
+        // Bracket commands (]c, [c to jump between change hunks)
+        KeyCode::Char(c @ (']' | '[')) => {
+            app.vim.pending_bracket = Some(c);
+        }
+
         // Find char
         KeyCode::Char('f') if key.modifiers.is_empty() => {
             app.vim.pending_find = Some(PendingFind::new(true, false));
@@ -2942,6 +4237,7 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
         // Matching bracket
         KeyCode::Char('%') => {
             app.vim.reset_pending();
+            record_jump(app);
             app.editor.move_cursor(CursorMove::MatchingBracket);
         }
 
@@ -2958,12 +4254,17 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
             app.vim.reset_pending();
             app.start_buffer_search();
         }
+        KeyCode::Char('f') if key.modifiers == KeyModifiers::ALT => {
+            app.vim.reset_pending();
+            app.start_buffer_search_for_word();
+            update_editor_search_highlights(app);
+        }
 
         // Operators
         KeyCode::Char('d') => {
-            if app.pending_operator == Some('d') {
+            if app.vim.pending_operator == Some('d') {
                 // dd: delete line
-                app.pending_operator = None;
+                app.vim.pending_operator = None;
                 let count = app.vim.get_count();
                 for _ in 0..count {
                     app.editor.delete_current_line();
@@ -2971,13 +4272,13 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
                 app.vim.last_change = Some(crate::vim::LastChange::DeleteLine(count));
                 app.vim.reset_pending();
             } else {
-                app.pending_operator = Some('d');
+                app.vim.pending_operator = Some('d');
             }
         }
         KeyCode::Char('c') => {
-            if app.pending_operator == Some('c') {
+            if app.vim.pending_operator == Some('c') {
                 // cc: change line
-                app.pending_operator = None;
+                app.vim.pending_operator = None;
                 app.editor.move_cursor(CursorMove::Head);
                 app.editor.start_selection();
                 app.editor.move_cursor(CursorMove::End);
@@ -2986,13 +4287,13 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
                 update_cursor_style(app);
                 app.vim.reset_pending();
             } else {
-                app.pending_operator = Some('c');
+                app.vim.pending_operator = Some('c');
             }
         }
         KeyCode::Char('y') if key.modifiers.is_empty() => {
-            if app.pending_operator == Some('y') {
+            if app.vim.pending_operator == Some('y') {
                 // yy: yank line
-                app.pending_operator = None;
+                app.vim.pending_operator = None;
                 app.editor.move_cursor(CursorMove::Head);
                 app.editor.start_selection();
                 app.editor.move_cursor(CursorMove::End);
@@ -3000,13 +4301,13 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
                 app.editor.cancel_selection();
                 app.vim.reset_pending();
             } else {
-                app.pending_operator = Some('y');
+                app.vim.pending_operator = Some('y');
             }
         }
         KeyCode::Char('>') => {
-            if app.pending_operator == Some('>') {
+            if app.vim.pending_operator == Some('>') {
                 // >>: indent line
-                app.pending_operator = None;
+                app.vim.pending_operator = None;
                 let count = app.vim.get_count();
                 for _ in 0..count {
                     app.editor.move_cursor(CursorMove::Head);
@@ -3014,13 +4315,13 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
                 }
                 app.vim.reset_pending();
             } else {
-                app.pending_operator = Some('>');
+                app.vim.pending_operator = Some('>');
             }
         }
         KeyCode::Char('<') => {
-            if app.pending_operator == Some('<') {
+            if app.vim.pending_operator == Some('<') {
                 // <<: outdent line
-                app.pending_operator = None;
+                app.vim.pending_operator = None;
                 // Simplified: remove up to 4 spaces from start
                 let count = app.vim.get_count();
                 for _ in 0..count {
@@ -3036,7 +4337,7 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
                 }
                 app.vim.reset_pending();
             } else {
-                app.pending_operator = Some('<');
+                app.vim.pending_operator = Some('<');
             }
         }
 
@@ -3171,16 +4472,23 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
         }
         KeyCode::Esc => {
             app.vim.reset_pending();
-            app.pending_operator = None;
+            app.vim.pending_operator = None;
             app.editor.cancel_selection();
 
-            if app.has_unsaved_changes() {
-                app.dialog = DialogState::UnsavedChanges;
-            } else {
+            if !app.has_unsaved_changes() {
                 app.cancel_edit();
-                app.vim_mode = VimMode::Normal;
-                update_cursor_style(app);
+            } else {
+                match app.config.editor.esc_behavior {
+                    EscBehavior::Ask => {
+                        app.dialog = DialogState::UnsavedChanges;
+                        return;
+                    }
+                    EscBehavior::Keep => app.keep_edit(),
+                    EscBehavior::Discard => app.cancel_edit(),
+                }
             }
+            app.vim_mode = VimMode::Normal;
+            update_cursor_style(app);
         }
 
         // Search - enter search mode in status bar
@@ -3202,20 +4510,24 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
         }
         KeyCode::Char('n') => {
             app.vim.reset_pending();
-            if !app.buffer_search.matches.is_empty() {
+            if app.resume_last_search() {
+                record_jump(app);
                 match app.buffer_search.direction {
                     crate::app::SearchDirection::Forward => app.buffer_search_next(),
                     crate::app::SearchDirection::Backward => app.buffer_search_prev(),
                 }
+                update_editor_search_highlights(app);
             }
         }
         KeyCode::Char('N') => {
             app.vim.reset_pending();
-            if !app.buffer_search.matches.is_empty() {
+            if app.resume_last_search() {
+                record_jump(app);
                 match app.buffer_search.direction {
                     crate::app::SearchDirection::Forward => app.buffer_search_prev(),
                     crate::app::SearchDirection::Backward => app.buffer_search_next(),
                 }
+                update_editor_search_highlights(app);
             }
         }
         KeyCode::Char('*') => {
@@ -3230,7 +4542,7 @@ fn handle_vim_normal_mode(app: &mut App, key: crossterm::event::KeyEvent) {
 
         _ => {
             app.vim.reset_pending();
-            app.pending_operator = None;
+            app.vim.pending_operator = None;
         }
     }
 }
@@ -3278,6 +4590,14 @@ fn repeat_last_change(app: &mut App, change: crate::vim::LastChange) {
                 app.editor.cut();
             }
         }
+        LastChange::Increment(delta) => {
+            increment_at_cursor(app, delta);
+        }
+        LastChange::IncrementLines { lines, step, progressive } => {
+            let first = app.editor.cursor().0;
+            let last = (first + lines).min(app.editor.line_count()).saturating_sub(1);
+            increment_lines(app, first, last, |_| 0, step, progressive);
+        }
         // These require insert mode text replay - complex, skip for now
         LastChange::ChangeLine(_, _) |
         LastChange::YankLine(_) |
@@ -3290,6 +4610,71 @@ fn repeat_last_change(app: &mut App, change: crate::vim::LastChange) {
     }
 }
 
+/// Ctrl-A/Ctrl-X: adds `delta` to the number at or after the cursor and
+/// leaves the cursor on its last digit. False when there is no number.
+fn increment_at_cursor(app: &mut App, delta: i64) -> bool {
+    let (row, col) = app.editor.cursor();
+    let Some(line) = app.editor.lines().get(row).map(|l| l.to_string()) else {
+        return false;
+    };
+    let Some((new_line, last_digit)) = crate::vim::increment::increment(&line, col, delta) else {
+        return false;
+    };
+    app.editor.replace_lines(&[(row, new_line)]);
+    app.editor.set_cursor(row, last_digit);
+    true
+}
+
+/// Ctrl-A/Ctrl-X over lines `first..=last`: adds `step` to the first number
+/// at or after `start_col(row)` on each, or `n * step` to the nth number
+/// found when `progressive`, as one undo step. The cursor goes to the start
+/// of `first`. False when no line has a number.
+fn increment_lines(
+    app: &mut App,
+    first: usize,
+    last: usize,
+    start_col: impl Fn(usize) -> usize,
+    step: i64,
+    progressive: bool,
+) -> bool {
+    let lines = app.editor.lines();
+    let mut changes = Vec::new();
+    for (row, line) in lines.iter().enumerate().take(last + 1).skip(first) {
+        let n = changes.len() as i64 + 1;
+        let delta = if progressive { step.saturating_mul(n) } else { step };
+        if let Some((new_line, _)) = crate::vim::increment::increment(line, start_col(row), delta) {
+            changes.push((row, new_line));
+        }
+    }
+    if changes.is_empty() {
+        return false;
+    }
+    // Set first too, so undo comes back to it
+    app.editor.set_cursor(first, 0);
+    app.editor.replace_lines(&changes);
+    app.editor.set_cursor(first, 0);
+    true
+}
+
+/// Where a `` ` `` / `'` jump to `mark` lands, opening the mark's note first
+/// when an uppercase mark was set in another one.
+fn mark_jump_target(app: &mut App, mark: char) -> Option<Position> {
+    let note = app.current_note().and_then(|n| n.file_path.clone());
+    match app.vim.marks.target(mark, note.as_deref())? {
+        MarkTarget::Here(_) => {
+            let (row, col) = app.editor.cursor();
+            app.vim.marks.jump(mark, Position::new(row, col))
+        }
+        MarkTarget::Note(path, pos) => app.open_note_for_mark(&path).then_some(pos),
+    }
+}
+
+/// Remember the cursor as the `` ` `` / `'` mark before a jump motion.
+fn record_jump(app: &mut App) {
+    let (row, col) = app.editor.cursor();
+    app.vim.marks.set_last_jump(Position::new(row, col));
+}
+
 fn execute_motion_n(app: &mut App, movement: CursorMove) {
     let count = app.vim.get_count();
     app.vim.reset_pending();
@@ -3303,19 +4688,40 @@ fn execute_motion_or_operator(app: &mut App, movement: CursorMove) {
     use crate::vim::LastChange;
 
     let count = app.vim.get_count();
-    if let Some(op) = app.pending_operator.take() {
+    if let Some(op) = app.vim.pending_operator.take() {
         let start_pos = app.editor.cursor();
-        let start_row = start_pos.0;
+        let mut kind = Motion::from_cursor_move(movement).map_or(MotionKind::Exclusive, |m| m.kind());
 
         app.editor.cancel_selection();
         app.editor.start_selection();
 
-        let is_word_forward = matches!(movement, CursorMove::WordForward | CursorMove::BigWordForward);
-
-        if is_word_forward {
-            // For word forward motions with operators, we need special handling:
-            // 1. dw should delete to end of line if word motion would cross lines
-            // 2. cw should behave like ce (change to end of word, not including trailing space)
+        // Vim's `cw` on a word changes to its end like `ce`, keeping the
+        // space after it. Already at the end, the first count stays put.
+        let class_at = |col: usize| {
+            let big = matches!(movement, CursorMove::BigWordForward);
+            let line = app.editor.lines().get(start_pos.0).map(|l| l.to_string()).unwrap_or_default();
+            line.chars().nth(col).map(|c| match c {
+                c if c.is_whitespace() => 0,
+                c if big || app.editor.word_chars().is_word_char(c) => 1,
+                _ => 2,
+            })
+        };
+        let on_word = class_at(start_pos.1).is_some_and(|class| class != 0);
+        let change_word = op == 'c' && on_word && matches!(movement, CursorMove::WordForward | CursorMove::BigWordForward);
+
+        if change_word {
+            let at_word_end = class_at(start_pos.1 + 1) != class_at(start_pos.1);
+            let movement = match movement {
+                CursorMove::BigWordForward => CursorMove::BigWordEndForward,
+                _ => CursorMove::WordEndForward,
+            };
+            for _ in usize::from(at_word_end)..count {
+                app.editor.move_cursor(movement);
+            }
+            kind = MotionKind::Inclusive;
+        } else if matches!(movement, CursorMove::WordForward | CursorMove::BigWordForward) {
+            // The last word of a line ends at the line's end, so `dw` there
+            // leaves the line break alone
             for _ in 0..count {
                 let (row, _) = app.editor.cursor();
                 let line = app.editor.lines().get(row).map(|s| s.to_string());
@@ -3328,34 +4734,14 @@ fn execute_motion_or_operator(app: &mut App, movement: CursorMove) {
                     break;
                 }
             }
-
-            if op == 'c' {
-                let (end_row, end_col) = app.editor.cursor();
-                if end_row == start_row {
-                    if let Some(line) = app.editor.lines().get(end_row) {
-                        let chars: Vec<char> = line.chars().collect();
-                        let mut adjusted_col = end_col;
-                        while adjusted_col > start_pos.1 && adjusted_col > 0 {
-                            if let Some(&c) = chars.get(adjusted_col.saturating_sub(1)) {
-                                if c.is_whitespace() {
-                                    adjusted_col -= 1;
-                                } else {
-                                    break;
-                                }
-                            } else {
-                                break;
-                            }
-                        }
-                        if adjusted_col > start_pos.1 {
-                            app.editor.set_cursor(end_row, adjusted_col);
-                        }
-                    }
-                }
-            }
         } else {
             for _ in 0..count { app.editor.move_cursor(movement); }
         }
 
+        if kind == MotionKind::Inclusive {
+            app.editor.set_inclusive_selection(true);
+        }
+
         match op {
             'd' => {
                 app.editor.cut();
@@ -3379,7 +4765,14 @@ fn execute_motion_or_operator(app: &mut App, movement: CursorMove) {
                 update_cursor_style(app);
                 // Note: Change operations need insert text to be recorded on exit from insert mode
             }
-            'y' => { app.editor.copy(); app.editor.cancel_selection(); }
+            'y' => {
+                let start = app.editor.selection_range().map(|(start, _)| start);
+                app.editor.copy();
+                app.editor.cancel_selection();
+                if let Some(start) = start {
+                    app.editor.set_cursor(start.row, start.col);
+                }
+            }
             '>' => {
                 if let Some((start, _)) = app.editor.selection_range() {
                     app.editor.cancel_selection();
@@ -3410,6 +4803,35 @@ fn execute_motion_or_operator(app: &mut App, movement: CursorMove) {
     app.vim.reset_pending();
 }
 
+/// Applies `op` to whole lines, for linewise motions like `G` and `gg`.
+/// `c` keeps one empty line to type into, as `cc` does.
+fn operate_on_lines(app: &mut App, op: char, start_row: usize, end_row: usize) {
+    let end_row = end_row.min(app.editor.lines().len().saturating_sub(1));
+    match op {
+        'd' | 'y' => {
+            app.editor.cancel_selection();
+            app.editor.set_visual_line_selection(start_row, end_row);
+            if op == 'd' {
+                app.editor.cut_visual_lines();
+            } else {
+                app.editor.copy_visual_lines();
+                app.editor.set_cursor(start_row, 0);
+            }
+            app.editor.clear_visual_line_selection();
+        }
+        'c' => {
+            app.editor.set_cursor(start_row, 0);
+            app.editor.start_selection();
+            app.editor.set_cursor(end_row, 0);
+            app.editor.move_cursor(CursorMove::End);
+            app.editor.cut();
+            app.vim_mode = VimMode::Insert;
+            update_cursor_style(app);
+        }
+        _ => app.editor.cancel_selection(),
+    }
+}
+
 fn execute_find(app: &mut App, find: FindState) {
     let pos = app.editor.cursor();
     // Resolve the motion endpoint and line length in one scoped borrow so the
@@ -3426,7 +4848,7 @@ fn execute_find(app: &mut App, find: FindState) {
     };
 
     // Check for pending operator (d, c, y, etc.)
-    if let Some(op) = app.pending_operator.take() {
+    if let Some(op) = app.vim.pending_operator.take() {
         // f/t/F/T are inclusive motions, so the operated-on range includes the
         // motion endpoint. Build an explicit exclusive [start, end) selection
         // (and force exclusive mode so a stale inclusive flag can't shift it).
@@ -3469,8 +4891,8 @@ fn execute_text_object(app: &mut App, scope: TextObjectScope, obj: TextObject) {
     let lines: Vec<&str> = lines_owned.iter().map(|s| &**s).collect();
     let cursor_pos = crate::editor::Position::new(pos.0, pos.1);
 
-    if let Some((start, end)) = obj.find_bounds(scope, &lines, cursor_pos) {
-        if let Some(op) = app.pending_operator.take() {
+    if let Some((start, end)) = obj.find_bounds(scope, &lines, cursor_pos, app.editor.word_chars()) {
+        if let Some(op) = app.vim.pending_operator.take() {
             app.editor.set_cursor(start.row, start.col);
             app.editor.start_selection();
             app.editor.set_cursor(end.row, end.col);
@@ -3536,11 +4958,35 @@ fn handle_vim_insert_mode(app: &mut App, key: crossterm::event::KeyEvent) {
         app.vim.macros.record_key(key);
     }
 
+    // Ctrl-R <reg>: the key after Ctrl-R names the register to insert
+    if app.vim.pending_insert_register {
+        app.vim.pending_insert_register = false;
+        if let KeyCode::Char(reg) = key.code {
+            if let Some(text) = app.vim.register_text(reg, app.editor.clipboard_text()) {
+                app.editor.insert_str(&text);
+                app.update_editor_highlights();
+            }
+        }
+        return;
+    }
+
+    // Ctrl-V <char>: type the char as it is, so `;dt` then Ctrl-V Space
+    // keeps the trigger
+    if app.vim.pending_insert_literal {
+        app.vim.pending_insert_literal = false;
+        if let KeyCode::Char(c) = key.code {
+            app.editor.insert_char(c);
+            app.update_editor_highlights_incremental();
+            return;
+        }
+    }
+
     match key.code {
         KeyCode::Esc => {
             if let Some(state) = app.block_insert_state.take() {
                 apply_block_insert(app, state);
             }
+            app.editor.cancel_selection();
             app.vim_mode = VimMode::Normal;
             update_cursor_style(app);
             app.vim.mode = VimModeNew::Normal;
@@ -3560,6 +5006,49 @@ fn handle_vim_insert_mode(app: &mut App, key: crossterm::event::KeyEvent) {
             app.vim.mode = VimModeNew::Normal;
             app.start_buffer_search();
         }
+        KeyCode::Char('f') if key.modifiers == KeyModifiers::ALT => {
+            app.vim.mode = VimModeNew::Normal;
+            app.start_buffer_search_for_word();
+            update_editor_search_highlights(app);
+        }
+        KeyCode::Char('w') if key.modifiers == KeyModifiers::CONTROL => {
+            app.editor.delete_word_before_cursor();
+            app.update_editor_highlights_incremental();
+        }
+        KeyCode::Char('u') if key.modifiers == KeyModifiers::CONTROL => {
+            app.editor.delete_to_line_start();
+            app.update_editor_highlights_incremental();
+        }
+        KeyCode::Char('h') if key.modifiers == KeyModifiers::CONTROL => {
+            app.editor.delete_newline();
+            app.update_editor_highlights_incremental();
+        }
+        KeyCode::Char('r') if key.modifiers == KeyModifiers::CONTROL => {
+            app.vim.pending_insert_register = true;
+        }
+        KeyCode::Char('v') if key.modifiers == KeyModifiers::CONTROL => {
+            app.vim.pending_insert_literal = true;
+        }
+        // Copy and cut a Shift+arrow selection
+        KeyCode::Char('c') if key.modifiers == KeyModifiers::CONTROL && app.editor.has_selection() => {
+            app.editor.copy();
+            app.editor.cancel_selection();
+        }
+        KeyCode::Char('x') if key.modifiers == KeyModifiers::CONTROL && app.editor.has_selection() => {
+            app.editor.cut();
+            app.update_editor_highlights();
+        }
+        KeyCode::Char('l') if key.modifiers == KeyModifiers::CONTROL => {
+            app.open_link_picker();
+        }
+        KeyCode::Char('n') if key.modifiers == KeyModifiers::CONTROL => {
+            app.start_word_completion(true);
+            app.update_editor_highlights_incremental();
+        }
+        KeyCode::Char('p') if key.modifiers == KeyModifiers::CONTROL => {
+            app.start_word_completion(false);
+            app.update_editor_highlights_incremental();
+        }
         KeyCode::Char('[') => {
             app.editor.input(key);
 
@@ -3589,6 +5078,12 @@ fn handle_vim_insert_mode(app: &mut App, key: crossterm::event::KeyEvent) {
         }
         _ => {
             app.editor.input(key);
+            if key.code == KeyCode::Char('(') {
+                app.maybe_open_link_completion();
+            }
+            if key.code == KeyCode::Char(' ') && app.expand_snippet() {
+                return;
+            }
             if matches!(key.code, KeyCode::Char(_) | KeyCode::Backspace | KeyCode::Delete | KeyCode::Enter) {
                 app.update_editor_highlights_incremental();
 
@@ -3720,6 +5215,20 @@ fn handle_vim_visual_mode(app: &mut App, key: crossterm::event::KeyEvent) {
         }
     };
 
+    // `g` waits for a second `g`, or Ctrl-A/Ctrl-X
+    let pending_g = std::mem::take(&mut app.vim.pending_g);
+
+    // `$` makes the block ragged-right until the next sideways motion
+    if app.vim_mode == VimMode::VisualBlock {
+        match key.code {
+            KeyCode::Char('$') => app.editor.set_block_to_eol(true),
+            KeyCode::Char('h' | 'l' | 'w' | 'b' | '0') | KeyCode::Left | KeyCode::Right => {
+                app.editor.set_block_to_eol(false)
+            }
+            _ => {}
+        }
+    }
+
     match key.code {
         KeyCode::Esc => {
             app.editor.cancel_selection();
@@ -3828,6 +5337,9 @@ fn handle_vim_visual_mode(app: &mut App, key: crossterm::event::KeyEvent) {
                 update_block_selection(app);
             }
         }
+        KeyCode::Char('g') if !pending_g => {
+            app.vim.pending_g = true;
+        }
         KeyCode::Char('g') => {
             if app.vim_mode == VimMode::VisualLine {
                 reselect_lines_at(app, 0);
@@ -3836,6 +5348,36 @@ fn handle_vim_visual_mode(app: &mut App, key: crossterm::event::KeyEvent) {
                 update_block_selection(app);
             }
         }
+        KeyCode::Char(c @ ('a' | 'x')) if key.modifiers == KeyModifiers::CONTROL => {
+            // Ctrl-A/Ctrl-X on every selected line; after g, a sequence
+            if let Some(region) = visual_region(app) {
+                let step = if c == 'a' { 1 } else { -1 };
+                let (start, end) = (region.start, region.end);
+                let start_col = |row: usize| match region.kind {
+                    RegionKind::Lines => 0,
+                    RegionKind::Chars if row == start.row => start.col,
+                    RegionKind::Chars => 0,
+                    RegionKind::Block => start.col,
+                };
+                app.editor.cancel_selection();
+                app.editor.clear_visual_line_selection();
+                app.editor.clear_visual_block_selection();
+                if increment_lines(app, start.row, end.row, start_col, step, pending_g) {
+                    app.vim.last_change = Some(crate::vim::LastChange::IncrementLines {
+                        lines: end.row - start.row + 1,
+                        step,
+                        progressive: pending_g,
+                    });
+                }
+            }
+            app.vim_mode = VimMode::Normal;
+            update_cursor_style(app);
+            app.vim.mode = VimModeNew::Normal;
+            app.vim.reset_pending();
+            app.visual_line_anchor = None;
+            app.visual_line_current = None;
+            app.visual_block_anchor = None;
+        }
         KeyCode::Char('G') => {
             if app.vim_mode == VimMode::VisualLine {
                 let line_count = app.editor.lines().len();
@@ -3883,6 +5425,23 @@ fn handle_vim_visual_mode(app: &mut App, key: crossterm::event::KeyEvent) {
             app.visual_line_current = None;
             app.visual_block_anchor = None;
         }
+        KeyCode::Char(':') => {
+            // Remember the selection for `'<,'>` and open the command line with it
+            let region = visual_region(app);
+            app.editor.cancel_selection();
+            app.editor.clear_visual_line_selection();
+            app.editor.clear_visual_block_selection();
+            app.vim_mode = VimMode::Normal;
+            update_cursor_style(app);
+            app.visual_line_anchor = None;
+            app.visual_line_current = None;
+            app.visual_block_anchor = None;
+            app.vim.last_visual = region;
+            app.vim.enter_command_mode();
+            if region.is_some() {
+                app.vim.command_buffer.push_str("'<,'>");
+            }
+        }
         KeyCode::Char('s') if key.modifiers == KeyModifiers::CONTROL => {
             app.editor.cancel_selection();
             app.editor.clear_visual_line_selection();
@@ -3910,6 +5469,25 @@ fn handle_vim_visual_mode(app: &mut App, key: crossterm::event::KeyEvent) {
             app.visual_block_anchor = None;
             app.start_buffer_search();
         }
+        KeyCode::Char('c') if app.vim_mode == VimMode::VisualBlock => {
+            // Delete the block, then type the replacement on each of its rows
+            if let Some((start, end)) = app.editor.visual_block_rect() {
+                app.editor.cut_visual_block();
+                app.block_insert_state = Some(BlockInsertState {
+                    mode: BlockInsertMode::Insert,
+                    rows: (start.row, end.row),
+                    insert_col: start.col,
+                    active_row: start.row,
+                    start_col: start.col,
+                });
+                app.editor.clear_visual_block_selection();
+                app.visual_block_anchor = None;
+                app.editor.set_cursor(start.row, start.col);
+                app.vim_mode = VimMode::Insert;
+                update_cursor_style(app);
+                app.vim.mode = VimModeNew::Insert;
+            }
+        }
         KeyCode::Char('I') if app.vim_mode == VimMode::VisualBlock => {
             if let Some(anchor) = app.visual_block_anchor {
                 let (current_row, current_col) = app.editor.cursor();
@@ -3967,6 +5545,87 @@ fn handle_vim_visual_mode(app: &mut App, key: crossterm::event::KeyEvent) {
     }
 }
 
+/// Answer the `:s///c` prompt with `key`; Esc quits like `q`.
+fn handle_confirm_substitute(app: &mut App, key: crossterm::event::KeyEvent) {
+    let answer = match key.code {
+        KeyCode::Esc => 'q',
+        KeyCode::Char(c) if key.modifiers.is_empty() => c,
+        _ => return,
+    };
+    let finished = match app.confirm_substitute.as_mut() {
+        Some(prompt) => prompt.answer(answer),
+        None => return,
+    };
+    if !finished {
+        show_confirm_substitute_match(app);
+        return;
+    }
+
+    let Some(prompt) = app.confirm_substitute.take() else {
+        return;
+    };
+    app.editor.clear_search_highlights();
+    let result = {
+        let lines = app.editor.lines();
+        apply_matches(&lines, prompt.accepted(), prompt.pattern_len, &prompt.replacement, prompt.preserve_case)
+    };
+    if result.count > 0 {
+        app.editor.replace_lines(&result.changes);
+        app.update_editor_highlights();
+    }
+    app.vim.status_message = Some(format!(
+        "{} substitution{} on {} line{}",
+        result.count,
+        if result.count == 1 { "" } else { "s" },
+        result.changes.len(),
+        if result.changes.len() == 1 { "" } else { "s" },
+    ));
+}
+
+/// Move to the match the `:s///c` prompt is asking about and highlight it
+/// along with the matches still to come.
+fn show_confirm_substitute_match(app: &mut App) {
+    let Some(prompt) = &app.confirm_substitute else {
+        return;
+    };
+    let Some((row, col)) = prompt.current() else {
+        return;
+    };
+    let len = prompt.pattern_len;
+    let matches: Vec<(usize, usize, usize)> = prompt
+        .remaining()
+        .iter()
+        .map(|&(r, c)| (r, c, c + len))
+        .collect();
+    let message = format!("replace with {} (y/n/a/q/l)?", prompt.replacement);
+
+    let match_color = app.theme.search.match_highlight;
+    let current_color = app.theme.search.match_current;
+    app.editor.set_search_highlights(&matches, 0, match_color, current_color);
+    app.editor.set_cursor(row, col);
+    app.vim.status_message = Some(message);
+}
+
+/// The current visual selection as a substitute region.
+fn visual_region(app: &App) -> Option<Region> {
+    let row = app.editor.cursor().0;
+    match app.vim_mode {
+        VimMode::VisualLine => {
+            let anchor = app.visual_line_anchor?;
+            Some(Region::lines(anchor, app.visual_line_current.unwrap_or(row)))
+        }
+        VimMode::VisualBlock => {
+            let (start, end) = app.editor.visual_block_rect()?;
+            Some(Region { start, end, kind: RegionKind::Block })
+        }
+        VimMode::Visual => {
+            let (start, end) = app.editor.selection_range()?;
+            Some(Region { start, end, kind: RegionKind::Chars })
+        }
+        _ => None,
+    }
+}
+
 fn handle_vim_command_mode(app: &mut App, key: crossterm::event::KeyEvent) {
     match key.code {
         KeyCode::Esc => {
@@ -3982,8 +5641,9 @@ fn handle_vim_command_mode(app: &mut App, key: crossterm::event::KeyEvent) {
             app.vim.mode = VimModeNew::Normal;
             app.vim.reset_pending();
 
-            if let Some(command) = parse_command(&cmd) {
-                execute_vim_command(app, command);
+            let (range, rest) = split_range(&cmd);
+            if let Some(command) = parse_command(rest) {
+                execute_vim_command(app, command, range);
             }
         }
         KeyCode::Backspace => {
@@ -3994,6 +5654,7 @@ fn handle_vim_command_mode(app: &mut App, key: crossterm::event::KeyEvent) {
                 app.vim.reset_pending();
             }
         }
+        KeyCode::Tab => app.complete_cd_command(),
         KeyCode::Char(c) => {
             app.vim.command_buffer.push(c);
         }
@@ -4006,6 +5667,7 @@ fn handle_vim_search_mode(app: &mut App, key: crossterm::event::KeyEvent) {
 
     match key.code {
         KeyCode::Esc => {
+            app.search_history.stop_browsing();
             app.vim.search_buffer.clear();
             app.vim.mode = VimModeNew::Normal;
             app.vim.reset_pending();
@@ -4014,14 +5676,19 @@ fn handle_vim_search_mode(app: &mut App, key: crossterm::event::KeyEvent) {
             update_editor_search_highlights(app);
         }
         KeyCode::Enter => {
+            // `/` and Enter searches for the last pattern again
+            if app.vim.search_buffer.is_empty() {
+                app.vim.search_buffer = app.search_history.last().unwrap_or_default().to_string();
+            }
             if !app.vim.search_buffer.is_empty() {
+                app.search_history.push(&app.vim.search_buffer);
                 app.vim.search_pattern = Some(app.vim.search_buffer.clone());
                 app.vim.search_direction = if forward {
                     crate::vim::SearchDirection::Forward
                 } else {
                     crate::vim::SearchDirection::Backward
                 };
-                app.buffer_search.query = app.vim.search_buffer.clone();
+                app.buffer_search.query.set(app.vim.search_buffer.clone());
                 app.buffer_search.direction = if forward {
                     crate::app::SearchDirection::Forward
                 } else {
@@ -4031,6 +5698,7 @@ fn handle_vim_search_mode(app: &mut App, key: crossterm::event::KeyEvent) {
                 app.perform_buffer_search();
 
                 if !app.buffer_search.matches.is_empty() {
+                    record_jump(app);
                     if forward {
                         app.buffer_search_next();
                     } else {
@@ -4060,8 +5728,9 @@ fn handle_vim_search_mode(app: &mut App, key: crossterm::event::KeyEvent) {
                 app.buffer_search.matches.clear();
                 update_editor_search_highlights(app);
             } else {
+                app.search_history.stop_browsing();
                 app.vim.search_buffer.pop();
-                app.buffer_search.query = app.vim.search_buffer.clone();
+                app.buffer_search.query.set(app.vim.search_buffer.clone());
                 app.perform_buffer_search();
                 if !app.buffer_search.matches.is_empty() {
                     app.scroll_to_current_match();
@@ -4069,9 +5738,29 @@ fn handle_vim_search_mode(app: &mut App, key: crossterm::event::KeyEvent) {
                 update_editor_search_highlights(app);
             }
         }
+        KeyCode::Up | KeyCode::Down => {
+            let pattern = if key.code == KeyCode::Up {
+                app.search_history.older(&app.vim.search_buffer)
+            } else {
+                app.search_history.newer()
+            };
+            if let Some(pattern) = pattern.map(str::to_string) {
+                app.vim.search_buffer = pattern.clone();
+                app.set_buffer_search_query(&pattern);
+                update_editor_search_highlights(app);
+            }
+        }
+        KeyCode::Char('r') if key.modifiers == KeyModifiers::CONTROL => {
+            if let Some(pattern) = app.search_history.last().map(str::to_string) {
+                app.vim.search_buffer = pattern.clone();
+                app.set_buffer_search_query(&pattern);
+                update_editor_search_highlights(app);
+            }
+        }
         KeyCode::Char(c) => {
+            app.search_history.stop_browsing();
             app.vim.search_buffer.push(c);
-            app.buffer_search.query = app.vim.search_buffer.clone();
+            app.buffer_search.query.set(app.vim.search_buffer.clone());
             app.perform_buffer_search();
             if !app.buffer_search.matches.is_empty() {
                 app.scroll_to_current_match();
@@ -4091,6 +5780,7 @@ fn handle_vim_search_locked_mode(app: &mut App, key: crossterm::event::KeyEvent)
         }
         KeyCode::Char('n') => {
             if !app.buffer_search.matches.is_empty() {
+                record_jump(app);
                 match app.buffer_search.direction {
                     crate::app::SearchDirection::Forward => app.buffer_search_next(),
                     crate::app::SearchDirection::Backward => app.buffer_search_prev(),
@@ -4100,6 +5790,7 @@ fn handle_vim_search_locked_mode(app: &mut App, key: crossterm::event::KeyEvent)
         }
         KeyCode::Char('N') => {
             if !app.buffer_search.matches.is_empty() {
+                record_jump(app);
                 match app.buffer_search.direction {
                     crate::app::SearchDirection::Forward => app.buffer_search_prev(),
                     crate::app::SearchDirection::Backward => app.buffer_search_next(),
@@ -4136,7 +5827,12 @@ fn handle_vim_search_locked_mode(app: &mut App, key: crossterm::event::KeyEvent)
     }
 }
 
-fn execute_vim_command(app: &mut App, command: Command) {
+fn execute_vim_command(app: &mut App, command: Command, range: Option<LineRange>) {
+    if range.is_some() && !matches!(command, Command::Substitute { .. } | Command::CopyHtml | Command::Listify { .. }) {
+        app.vim.status_message = Some("No range allowed".to_string());
+        return;
+    }
+
     match command {
         Command::Write => {
             app.save_edit();
@@ -4155,11 +5851,135 @@ fn execute_vim_command(app: &mut App, command: Command) {
             // Force quit without saving
             app.cancel_edit();
         }
-        Command::GoToLine(line) => {
+        Command::Macros => {
+            app.open_macro_list();
+        }
+        Command::CloseOthers => app.close_other_folders(),
+        Command::Refile => app.start_refile(),
+        Command::MoveSection => app.start_section_refile(false),
+        Command::CopySection => app.start_section_refile(true),
+        Command::Prose => app.open_prose_report(),
+        Command::Narrow => app.narrow_to_section(),
+        Command::Widen => app.widen(),
+        Command::Cd(path) => match path {
+            Some(path) => {
+                if let Err(e) = app.change_notes_dir(&path) {
+                    app.vim.status_message = Some(e);
+                }
+            }
+            None => app.open_change_notes_dir(),
+        },
+        Command::Path => app.copy_path(),
+        Command::Reveal => app.reveal_path(),
+        Command::Errors => app.show_scan_errors(),
+        Command::Yanks => app.open_yank_list(),
+        Command::Toc => app.insert_toc(),
+        Command::Set(setting) => {
+            app.vim.status_message = Some(app.set_option(&setting).unwrap_or_else(|err| err));
+        }
+        Command::Merge(name) => {
+            if app.has_unsaved_changes() {
+                app.vim.status_message = Some("No write since last change".to_string());
+                return;
+            }
+            app.cancel_edit();
+            app.start_merge(name.as_deref());
+        }
+        Command::Vault(name) => {
+            if app.has_unsaved_changes() {
+                app.vim.status_message = Some("No write since last change".to_string());
+                return;
+            }
+            app.cancel_edit();
+            match name {
+                Some(name) => app.switch_vault_named(&name),
+                None => app.open_vault_picker(),
+            }
+        }
+        Command::Edit(path) => {
+            if app.has_unsaved_changes() {
+                app.vim.status_message = Some("No write since last change".to_string());
+                return;
+            }
+            app.cancel_edit();
+            match app.open_file(&path) {
+                Ok(()) => {
+                    app.push_navigation_history(app.selected_note);
+                    app.enter_edit_mode();
+                }
+                Err(e) => app.status_message = Some(e),
+            }
+        }
+        Command::QuickfixNext => app.quickfix_step(true),
+        Command::QuickfixPrev => app.quickfix_step(false),
+        Command::QuickfixOpen => app.quickfix_open = true,
+        Command::QuickfixClose => app.quickfix_open = false,
+        Command::CheckLinks => app.check_links(),
+        Command::Export(format) => app.export_note(format),
+        Command::GraphExport(format, path) => app.export_graph(format, path),
+        Command::CopyHtml => {
+            let region = match range {
+                None => None,
+                Some(LineRange::All) => Some(Region::lines(0, app.editor.lines().len().saturating_sub(1))),
+                Some(LineRange::Lines(start, end)) => Some(Region::lines(start.saturating_sub(1), end.saturating_sub(1))),
+                Some(LineRange::Visual) => match app.vim.last_visual {
+                    Some(region) => Some(region),
+                    None => {
+                        app.vim.status_message = Some("No visual selection".to_string());
+                        return;
+                    }
+                },
+            };
+            app.copy_as_html(region);
+        }
+        Command::Listify { style, nested } => {
+            let row = app.editor.cursor().0;
+            let (start, end) = match range {
+                None => (row, row),
+                Some(LineRange::All) => (0, app.editor.lines().len().saturating_sub(1)),
+                Some(LineRange::Lines(start, end)) => (start.saturating_sub(1), end.saturating_sub(1)),
+                Some(LineRange::Visual) => match app.vim.last_visual {
+                    Some(region) => (region.start.row, region.end.row),
+                    None => {
+                        app.vim.status_message = Some("No visual selection".to_string());
+                        return;
+                    }
+                },
+            };
+            app.listify(start.min(end), start.max(end), style, nested);
+        }
+        Command::Tasks(op) => app.start_section_tasks(op),
+        Command::Link => app.open_link_picker(),
+        Command::Encrypt => app.start_encrypt_note(),
+        Command::Decrypt => app.decrypt_note(),
+        Command::Stats => app.show_note_stats(),
+        Command::Snippets => app.dialog = DialogState::Snippets,
+        Command::Config => app.dialog = DialogState::ConfigSources,
+        Command::History => app.open_history(),
+        Command::Scaffold(template) => {
+            if app.has_unsaved_changes() {
+                app.vim.status_message = Some("No write since last change".to_string());
+                return;
+            }
+            app.start_scaffold(template.as_deref());
+        }
+        Command::RenameHeading(Some(title)) => app.rename_heading(&title),
+        Command::RenameHeading(None) => match app.heading_at_cursor() {
+            // Put the current title on the command line to be edited
+            Some(title) => {
+                app.vim.enter_command_mode();
+                app.vim.command_buffer = format!("rename-heading {}", title);
+            }
+            None => app.vim.status_message = Some("Not on a heading".to_string()),
+        },
+        Command::Promote => app.shift_heading_section(-1),
+        Command::Demote => app.shift_heading_section(1),
+        Command::GoToLine(jump) => {
             // Go to specific line (1-indexed in vim)
-            let target_line = line.saturating_sub(1);
             let total_lines = app.editor.lines().len();
+            let target_line = jump.target(app.editor.cursor().0 + 1, total_lines) - 1;
             if target_line < total_lines {
+                record_jump(app);
                 app.editor.move_cursor(CursorMove::Top);
                 for _ in 0..target_line {
                     app.editor.move_cursor(CursorMove::Down);
@@ -4167,46 +5987,572 @@ fn execute_vim_command(app: &mut App, command: Command) {
             }
         }
         Command::Substitute { pattern, replacement, flags } => {
-            // Simple substitute implementation
-            // First, collect all changes to make
-            let lines: Vec<String> = app.editor.lines().iter().map(|s| s.to_string()).collect();
-            let mut changes: Vec<(usize, String)> = Vec::new();
-
-            for (row, line) in lines.iter().enumerate() {
-                if line.contains(&pattern) {
-                    let new_line = if flags.global {
-                        line.replace(&pattern, &replacement)
-                    } else {
-                        line.replacen(&pattern, &replacement, 1)
-                    };
-                    if new_line != *line {
-                        changes.push((row, new_line));
-                        if !flags.global {
-                            break;
-                        }
+            let region = match range {
+                None => {
+                    let row = app.editor.cursor().0;
+                    Region::lines(row, row)
+                }
+                Some(LineRange::All) => {
+                    Region::lines(0, app.editor.lines().len().saturating_sub(1))
+                }
+                Some(LineRange::Lines(start, end)) => {
+                    Region::lines(start.saturating_sub(1), end.saturating_sub(1))
+                }
+                Some(LineRange::Visual) => match app.vim.last_visual {
+                    Some(region) => region,
+                    None => {
+                        app.vim.status_message = Some("No visual selection".to_string());
+                        return;
+                    }
+                },
+            };
+
+            // An empty pattern reuses the last search, as in vim
+            let pattern = if pattern.is_empty() {
+                match &app.vim.search_pattern {
+                    Some(last) => last.clone(),
+                    None => {
+                        app.vim.status_message = Some("No previous pattern".to_string());
+                        return;
                     }
                 }
-            }
+            } else {
+                pattern
+            };
 
-            // Apply changes in reverse order to preserve line numbers
-            for (row, new_line) in changes.into_iter().rev() {
-                // Go to the line
-                app.editor.move_cursor(CursorMove::Top);
-                for _ in 0..row {
-                    app.editor.move_cursor(CursorMove::Down);
+            if flags.report_only {
+                let matches = {
+                    let lines = app.editor.lines();
+                    find_matches(&lines, &region, &pattern, &flags)
+                };
+                if matches.is_empty() {
+                    app.vim.status_message = Some(format!("Pattern not found: {}", pattern));
+                    return;
                 }
-                app.editor.move_cursor(CursorMove::Head);
-                // Select entire line and delete it
-                app.editor.start_selection();
-                app.editor.move_cursor(CursorMove::End);
-                app.editor.cut();
-                // Insert the new line content
-                for c in new_line.chars() {
-                    app.editor.insert_char(c);
+                let mut rows: Vec<usize> = matches.iter().map(|&(row, _)| row).collect();
+                rows.dedup();
+                app.vim.status_message = Some(format!(
+                    "{} match{} on {} line{}",
+                    matches.len(),
+                    if matches.len() == 1 { "" } else { "es" },
+                    rows.len(),
+                    if rows.len() == 1 { "" } else { "s" },
+                ));
+                return;
+            }
+
+            if flags.confirm {
+                let matches = {
+                    let lines = app.editor.lines();
+                    find_matches(&lines, &region, &pattern, &flags)
+                };
+                if matches.is_empty() {
+                    app.vim.status_message = Some(format!("Pattern not found: {}", pattern));
+                    return;
                 }
+                record_jump(app);
+                let preserve_case = preserves_case(&flags, &pattern, &replacement);
+                app.confirm_substitute = Some(ConfirmSubstitute::new(
+                    matches,
+                    pattern.chars().count(),
+                    replacement,
+                    preserve_case,
+                ));
+                show_confirm_substitute_match(app);
+                return;
+            }
+
+            let result = {
+                let lines = app.editor.lines();
+                substitute(&lines, &region, &pattern, &replacement, &flags)
+            };
+            if result.count == 0 {
+                app.vim.status_message = Some(format!("Pattern not found: {}", pattern));
+                return;
             }
 
+            app.editor.replace_lines(&result.changes);
+            app.vim.status_message = Some(format!(
+                "{} substitution{} on {} line{}",
+                result.count,
+                if result.count == 1 { "" } else { "s" },
+                result.changes.len(),
+                if result.changes.len() == 1 { "" } else { "s" },
+            ));
             app.update_editor_highlights();
         }
+        Command::GlobalSubstitute { pattern, replacement, flags } => {
+            let pattern = if pattern.is_empty() {
+                match &app.vim.search_pattern {
+                    Some(last) => last.clone(),
+                    None => {
+                        app.vim.status_message = Some("No previous pattern".to_string());
+                        return;
+                    }
+                }
+            } else {
+                pattern
+            };
+            app.start_global_substitute(pattern, replacement, flags);
+        }
+        Command::BulkRename { pattern, replacement, flags } => {
+            if pattern.is_empty() {
+                app.vim.status_message = Some("Usage: :bulk-rename /pattern/replacement/".to_string());
+                return;
+            }
+            app.start_bulk_rename(pattern, replacement, flags);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_support::{test_app, TempDir};
+    use std::fs;
+
+    fn type_keys(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            handle_key_event(app, crossterm::event::KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)).unwrap();
+        }
+    }
+
+    fn autocomplete(app: &App) -> (String, WikiAutocompleteMode, Vec<String>) {
+        match &app.wiki_autocomplete {
+            WikiAutocompleteState::Open { query, mode, suggestions, .. } => (
+                query.clone(),
+                mode.clone(),
+                suggestions.iter().map(|s| s.display_name.clone()).collect(),
+            ),
+            WikiAutocompleteState::None => panic!("autocomplete closed"),
+        }
+    }
+
+    #[test]
+    fn paste_extends_the_wiki_autocomplete_query() {
+        let dir = TempDir::new("paste-wiki");
+        fs::write(dir.join("Inbox.md"), "").unwrap();
+        fs::write(dir.join("Meeting notes.md"), "# Agenda\n# Actions\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.select_note_by_path(&dir.join("Inbox.md"));
+        app.enter_edit_mode();
+        type_keys(&mut app, "i[[");
+
+        handle_paste_event(&mut app, "Meet\nnot this".to_string());
+        assert_eq!(autocomplete(&app), ("Meet".to_string(), WikiAutocompleteMode::Note, vec!["Meeting notes".to_string()]));
+        assert!(app.editor.lines()[0].starts_with("[[Meet"));
+
+        handle_paste_event(&mut app, "ing notes#Ag".to_string());
+        assert_eq!(autocomplete(&app), ("Ag".to_string(), WikiAutocompleteMode::Heading, vec!["Agenda".to_string()]));
+
+        handle_paste_event(&mut app, "enda]] for Monday".to_string());
+        assert_eq!(app.wiki_autocomplete, WikiAutocompleteState::None);
+        assert!(app.editor.lines()[0].starts_with("[[Meeting notes#Agenda]] for Monday"));
+    }
+
+    #[test]
+    fn paste_into_search_prompts_joins_lines() {
+        let dir = TempDir::new("paste-search");
+        fs::write(dir.join("Inbox.md"), "one two words\ntwo\nwords\n").unwrap();
+        fs::write(dir.join("Two words.md"), "").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.select_note_by_path(&dir.join("Inbox.md"));
+
+        app.start_buffer_search();
+        handle_paste_event(&mut app, "two\nwords".to_string());
+        assert_eq!(app.buffer_search.query.as_str(), "two words");
+        assert_eq!(app.buffer_search.matches.len(), 1);
+        assert_eq!(app.mode, Mode::Normal);
+        app.end_buffer_search();
+
+        app.activate_sidebar_search();
+        handle_paste_event(&mut app, "Two\r\nwords\n".to_string());
+        assert_eq!(app.search_query.as_str(), "Two words");
+        let visible: Vec<_> = app
+            .get_visible_sidebar_indices()
+            .into_iter()
+            .filter_map(|i| app.sidebar_items.get(i).map(|item| item.display_name.clone()))
+            .collect();
+        assert_eq!(visible, ["Two words"]);
+    }
+
+    #[test]
+    fn operators_follow_motion_inclusiveness() {
+        let dir = TempDir::new("operator-motions");
+        fs::write(dir.join("Inbox.md"), "").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.enter_edit_mode();
+
+        // Buffer, cursor, keys, then the buffer, cursor and register after
+        let cases = [
+            ("one two three", (0, 4), "dw", "one three", (0, 4), "two "),
+            ("one two three", (0, 8), "dw", "one two ", (0, 8), "three"),
+            ("one two\nthree", (0, 4), "dw", "one \nthree", (0, 4), "two"),
+            ("one two three", (0, 0), "2dw", "three", (0, 0), "one two "),
+            ("one two three", (0, 4), "de", "one  three", (0, 4), "two"),
+            ("one two three", (0, 4), "db", "two three", (0, 0), "one "),
+            ("one two three", (0, 4), "d$", "one ", (0, 4), "two three"),
+            ("one two three", (0, 12), "d$", "one two thre", (0, 12), "e"),
+            ("one two three", (0, 4), "d0", "two three", (0, 0), "one "),
+            ("one\ntwo\nthree", (1, 1), "dG", "one", (0, 0), "two\nthree\n"),
+            ("one two three", (0, 4), "cw", "one  three", (0, 4), "two"),
+            ("one two three", (0, 6), "cw", "one tw three", (0, 6), "o"),
+            ("one two three", (0, 3), "cw", "onetwo three", (0, 3), " "),
+            ("one two three", (0, 4), "ce", "one  three", (0, 4), "two"),
+            ("one two three", (0, 4), "cb", "two three", (0, 0), "one "),
+            ("one two three", (0, 4), "c$", "one ", (0, 4), "two three"),
+            ("one two three", (0, 4), "c0", "two three", (0, 0), "one "),
+            ("one\ntwo\nthree", (1, 1), "cG", "one\n", (1, 0), "two\nthree"),
+            ("one two three", (0, 4), "yw", "one two three", (0, 4), "two "),
+            ("one two three", (0, 4), "ye", "one two three", (0, 4), "two"),
+            ("one two three", (0, 4), "yb", "one two three", (0, 0), "one "),
+            ("one two three", (0, 4), "y$", "one two three", (0, 4), "two three"),
+            ("one two three", (0, 4), "y0", "one two three", (0, 0), "one "),
+            ("one\ntwo\nthree", (1, 1), "yG", "one\ntwo\nthree", (1, 0), "two\nthree\n"),
+        ];
+        for (text, (row, col), keys, expected, cursor, register) in cases {
+            app.editor = crate::editor::Editor::from_str(text);
+            app.vim_mode = VimMode::Normal;
+            app.editor.set_cursor(row, col);
+            type_keys(&mut app, keys);
+            let case = format!("{keys} at {row}:{col} in {text:?}");
+            assert_eq!(app.editor.lines().join("\n"), expected, "{case}");
+            assert_eq!(app.editor.cursor(), cursor, "{case}");
+            assert_eq!(app.editor.clipboard_text(), Some(register), "{case}");
+            let mode = if keys.starts_with('c') { VimMode::Insert } else { VimMode::Normal };
+            assert_eq!(app.vim_mode, mode, "{case}");
+        }
+    }
+
+    #[test]
+    fn ctrl_a_and_ctrl_x_change_numbers() {
+        let dir = TempDir::new("increment");
+        fs::write(dir.join("Inbox.md"), "").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.enter_edit_mode();
+        let ctrl = |app: &mut App, c: char| {
+            handle_key_event(app, crossterm::event::KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)).unwrap();
+        };
+
+        app.editor = crate::editor::Editor::from_str("Take 08 pills\n0. a\n0. b\n0. c");
+        app.vim_mode = VimMode::Normal;
+        type_keys(&mut app, "5");
+        ctrl(&mut app, 'a');
+        assert_eq!(app.editor.lines()[0], "Take 13 pills");
+        assert_eq!(app.editor.cursor(), (0, 6));
+        type_keys(&mut app, ".");
+        assert_eq!(app.editor.lines()[0], "Take 18 pills");
+        type_keys(&mut app, "0");
+        ctrl(&mut app, 'x');
+        type_keys(&mut app, "u");
+        assert_eq!(app.editor.lines()[0], "Take 18 pills");
+
+        // g Ctrl-A numbers the selected lines
+        app.editor.set_cursor(1, 0);
+        type_keys(&mut app, "Vjj");
+        type_keys(&mut app, "g");
+        ctrl(&mut app, 'a');
+        assert_eq!(app.vim_mode, VimMode::Normal);
+        assert_eq!(app.editor.lines()[1..], ["1. a", "2. b", "3. c"]);
+        type_keys(&mut app, "u");
+        assert_eq!(app.editor.lines()[1..], ["0. a", "0. b", "0. c"]);
+
+        // Plain Ctrl-X takes one from each, and `.` does it again
+        type_keys(&mut app, "Vj");
+        ctrl(&mut app, 'x');
+        type_keys(&mut app, ".");
+        assert_eq!(app.editor.lines()[1..], ["-2. a", "-2. b", "0. c"]);
+    }
+
+    #[test]
+    fn searches_share_a_history() {
+        let dir = TempDir::new("search-history");
+        fs::write(dir.join("Inbox.md"), "alpha beta\nbeta gamma\nalpha\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.enter_edit_mode();
+        let press = |app: &mut App, code: KeyCode, modifiers: KeyModifiers| {
+            handle_key_event(app, crossterm::event::KeyEvent::new(code, modifiers)).unwrap();
+        };
+        let query = |app: &App| app.buffer_search.query.to_string();
+
+        type_keys(&mut app, "/beta");
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        press(&mut app, KeyCode::Char('f'), KeyModifiers::CONTROL);
+        type_keys(&mut app, "gamma");
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        press(&mut app, KeyCode::Esc, KeyModifiers::NONE);
+
+        // Up and Down go through both searches and back to what was typed
+        press(&mut app, KeyCode::Char('f'), KeyModifiers::CONTROL);
+        type_keys(&mut app, "al");
+        press(&mut app, KeyCode::Up, KeyModifiers::NONE);
+        assert_eq!(query(&app), "gamma");
+        press(&mut app, KeyCode::Up, KeyModifiers::NONE);
+        assert_eq!(query(&app), "beta");
+        assert_eq!(app.buffer_search.matches.len(), 2);
+        press(&mut app, KeyCode::Down, KeyModifiers::NONE);
+        press(&mut app, KeyCode::Down, KeyModifiers::NONE);
+        assert_eq!(query(&app), "al");
+
+        // An empty Enter searches for the last pattern again
+        press(&mut app, KeyCode::Backspace, KeyModifiers::NONE);
+        press(&mut app, KeyCode::Backspace, KeyModifiers::NONE);
+        press(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(query(&app), "gamma");
+        press(&mut app, KeyCode::Esc, KeyModifiers::NONE);
+
+        // Ctrl+F twice searches for the word under the cursor
+        app.editor.set_cursor(2, 2);
+        press(&mut app, KeyCode::Char('f'), KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('f'), KeyModifiers::CONTROL);
+        assert_eq!(query(&app), "alpha");
+        press(&mut app, KeyCode::Esc, KeyModifiers::NONE);
+
+        // `n` in a note opened since still has the last pattern to go to
+        app.buffer_search.clear();
+        app.editor.set_cursor(1, 0);
+        type_keys(&mut app, "n");
+        assert_eq!(query(&app), "alpha");
+        assert_eq!(app.editor.cursor(), (2, 0));
+    }
+
+    #[test]
+    fn yanks_can_be_cycled_and_picked_for_the_session() {
+        let dir = TempDir::new("yank-ring");
+        fs::write(dir.join("Inbox.md"), "a\nb\nc\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.select_note_by_path(&dir.join("Inbox.md"));
+        app.enter_edit_mode();
+        let ctrl = |app: &mut App, c: char| {
+            handle_key_event(app, crossterm::event::KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)).unwrap();
+        };
+
+        type_keys(&mut app, "dddd");
+        type_keys(&mut app, "p");
+        assert_eq!(app.editor.lines(), ["c", "b"]);
+        ctrl(&mut app, 'p');
+        assert_eq!(app.editor.lines(), ["c", "a"]);
+        assert_eq!(app.vim.status_message.as_deref(), Some("Yank 2 of 2"));
+        ctrl(&mut app, 'n');
+        assert_eq!(app.editor.lines(), ["c", "b"]);
+
+        // Kept when the note is edited again
+        app.cancel_edit();
+        app.enter_edit_mode();
+        assert_eq!(app.editor.yank_ring().len(), 2);
+        app.editor.set_cursor(2, 0);
+        type_keys(&mut app, ":yanks");
+        handle_key_event(&mut app, crossterm::event::KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).unwrap();
+        assert_eq!(app.dialog, DialogState::YankList);
+        type_keys(&mut app, "j");
+        handle_key_event(&mut app, crossterm::event::KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).unwrap();
+        assert_eq!(app.dialog, DialogState::None);
+        assert_eq!(app.editor.lines(), ["a", "b", "c", "a"]);
+    }
+
+    #[test]
+    fn toc_is_inserted_then_redone_in_place_on_save() {
+        let dir = TempDir::new("toc");
+        fs::write(dir.join("Guide.md"), "# Guide\n\n## Install\n\n## Use\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.config.toc.auto_update = true;
+        app.config.display.outline_list_items = true;
+        app.select_note_by_path(&dir.join("Guide.md"));
+        app.enter_edit_mode();
+        let enter = |app: &mut App| {
+            handle_key_event(app, crossterm::event::KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).unwrap();
+        };
+
+        // The blank line under the title is taken over
+        app.editor.set_cursor(1, 0);
+        type_keys(&mut app, ":toc");
+        enter(&mut app);
+        assert_eq!(
+            app.editor.lines()[..6],
+            ["# Guide", "<!-- toc -->", "- [Guide](#guide)", "  - [Install](#install)", "  - [Use](#use)", "<!-- /toc -->"]
+        );
+
+        // Redone in place for a renamed heading, in one undo step
+        app.editor.set_cursor(8, 0);
+        type_keys(&mut app, "A Often");
+        handle_key_event(&mut app, crossterm::event::KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)).unwrap();
+        type_keys(&mut app, ":toc");
+        enter(&mut app);
+        assert_eq!(app.editor.lines()[4], "  - [Use Often](#use-often)");
+        assert!(app.editor.undo());
+        assert_eq!(app.editor.lines()[4], "  - [Use](#use)");
+        assert_eq!(app.editor.lines()[8], "## Use Often");
+
+        // and by the save, with auto_update on
+        type_keys(&mut app, ":w");
+        enter(&mut app);
+        let saved = fs::read_to_string(dir.join("Guide.md")).unwrap();
+        assert!(saved.contains("  - [Use Often](#use-often)\n<!-- /toc -->\n## Install"));
+
+        // The outline doesn't list the table itself
+        let titles: Vec<&str> = app.outline.iter().map(|item| item.title.as_str()).collect();
+        assert_eq!(titles, ["Guide", "Install", "Use Often"]);
+    }
+
+    #[test]
+    fn graph_search_goes_to_the_picked_node() {
+        let dir = TempDir::new("graph-search");
+        fs::write(dir.join("Alpha.md"), "[[Beta]]\n").unwrap();
+        fs::write(dir.join("Beta.md"), "").unwrap();
+        fs::write(dir.join("A very long project overview.md"), "").unwrap();
+        let (mut app, clock) = test_app(&dir);
+        app.select_note_by_path(&dir.join("Alpha.md"));
+        app.build_graph();
+        app.dialog = DialogState::GraphView;
+        app.graph_view.focus_neighborhood = false;
+        let key = |app: &mut App, code| handle_key_event(app, crossterm::event::KeyEvent::new(code, KeyModifiers::NONE)).unwrap();
+        let title = |app: &App, node: usize| app.graph_view.nodes[node].title.clone();
+        let alpha = app.graph_view.selected_node;
+
+        // Esc leaves the graph as it was
+        type_keys(&mut app, "/be");
+        assert_eq!(app.graph_view.search.as_ref().unwrap().matches.len(), 1);
+        key(&mut app, KeyCode::Esc);
+        assert!(app.graph_view.search.is_none());
+        assert_eq!(app.graph_view.selected_node, alpha);
+        assert_eq!(app.dialog, DialogState::GraphView);
+
+        // Matched by the whole title, though the node's label is cut short
+        type_keys(&mut app, "/overview");
+        key(&mut app, KeyCode::Enter);
+        let picked = app.graph_view.selected_node.unwrap();
+        assert_eq!(title(&app, picked), "A very long proje...");
+        assert!(app.graph_view.needs_center);
+        assert_eq!(app.graph_view.flash.map(|f| f.node), Some(picked));
+        assert!(!app.graph_view.focus_neighborhood);
+
+        // Best match first; the arrows choose among the rest
+        type_keys(&mut app, "/a");
+        let matches = app.graph_view.search.as_ref().unwrap().matches.clone();
+        assert_eq!(matches.len(), 3);
+        assert_eq!(title(&app, matches[0]), "Alpha");
+        key(&mut app, KeyCode::Down);
+        key(&mut app, KeyCode::Enter);
+        assert_eq!(app.graph_view.selected_node, Some(matches[1]));
+
+        clock.advance(std::time::Duration::from_secs(2));
+        assert!(app.tick_graph_flash());
+        assert!(app.graph_view.flash.is_none());
+    }
+
+    #[test]
+    fn leader_sequence_times_out_by_the_app_clock() {
+        let dir = TempDir::new("leader-timeout");
+        fs::write(dir.join("Note.md"), "text\n").unwrap();
+        let (mut app, clock) = test_app(&dir);
+        app.select_note_by_path(&dir.join("Note.md"));
+        app.leader_key = Some(',');
+
+        type_keys(&mut app, ",f");
+        assert_eq!(app.leader.keys(), Some("f"));
+        clock.advance(std::time::Duration::from_millis(900));
+        assert!(!app.tick_leader());
+        clock.advance(std::time::Duration::from_millis(200));
+        assert!(app.tick_leader());
+        assert!(!app.leader.is_pending());
+    }
+
+    #[test]
+    fn bulk_rename_renames_marked_notes_until_one_fails() {
+        let dir = TempDir::new("bulk-rename");
+        for name in ["draft one", "draft two", "draft three", "keep"] {
+            fs::write(dir.join(format!("{}.md", name)), "").unwrap();
+        }
+        fs::write(dir.join("index.md"), "[[draft one]], [[draft two#Plan]] and [[draft three|three]]\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        for name in ["draft one", "draft two", "draft three"] {
+            app.sidebar_marks.mark(&dir.join(format!("{}.md", name)));
+        }
+        app.select_note_by_path(&dir.join("keep.md"));
+        app.enter_edit_mode();
+        let key = |app: &mut App, code| handle_key_event(app, crossterm::event::KeyEvent::new(code, KeyModifiers::NONE)).unwrap();
+
+        type_keys(&mut app, ":bulk-rename /draft/final/");
+        key(&mut app, KeyCode::Enter);
+        assert_eq!(app.dialog, DialogState::BulkRenameConfirm);
+        assert_eq!(app.pending_bulk_rename.as_ref().unwrap().ready().count(), 3);
+        assert_eq!(app.pending_bulk_rename.as_ref().unwrap().links(), 3);
+
+        // The second file goes missing before it's confirmed
+        fs::remove_file(dir.join("draft two.md")).unwrap();
+        type_keys(&mut app, "y");
+        assert!(dir.join("final one.md").exists());
+        assert!(dir.join("draft three.md").exists() && !dir.join("final three.md").exists());
+        let status = app.vim.status_message.clone().unwrap();
+        assert!(status.starts_with("Renamed 1 of 3 notes, updated 1 link, stopped at 'draft two'"), "{}", status);
+        assert_eq!(
+            fs::read_to_string(dir.join("index.md")).unwrap(),
+            "[[final one]], [[draft two#Plan]] and [[draft three|three]]\n"
+        );
+        assert!(status.ends_with("Not renamed: draft two, draft three"), "{}", status);
+        assert!(app.sidebar_marks.is_empty());
+        assert_eq!(app.mode, Mode::Edit);
+        assert_eq!(app.current_note().unwrap().title, "keep");
+
+        // Without marks it goes by the sidebar filter
+        app.search_query.set("final");
+        app.update_filtered_indices();
+        type_keys(&mut app, ":bulk-rename /final/done/");
+        key(&mut app, KeyCode::Enter);
+        type_keys(&mut app, "y");
+        assert!(dir.join("done one.md").exists());
+        assert_eq!(app.vim.status_message.as_deref(), Some("Renamed 1 note, updated 1 link"));
+        assert!(fs::read_to_string(dir.join("index.md")).unwrap().starts_with("[[done one]], "));
+    }
+
+    /// With `reading.max_width` narrower than the content panel and the note
+    /// centered, a click lands on the link where it's drawn, not where it
+    /// would be at the panel's edge.
+    #[test]
+    fn clicks_follow_links_into_the_centered_reading_column() {
+        use crossterm::event::{MouseEvent, MouseEventKind};
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let dir = TempDir::new("reading-width");
+        fs::write(dir.join("Reading.md"), "[[Other]] is linked\n").unwrap();
+        fs::write(dir.join("Other.md"), "").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.select_note_by_path(&dir.join("Reading.md"));
+        app.focus = Focus::Content;
+        let key = |app: &mut App, code| handle_key_event(app, crossterm::event::KeyEvent::new(code, KeyModifiers::NONE)).unwrap();
+
+        // Set from view mode's prompt, with the side panels left open
+        type_keys(&mut app, "/:set reading.max_width=40");
+        key(&mut app, KeyCode::Enter);
+        type_keys(&mut app, "/:set reading.align=center");
+        key(&mut app, KeyCode::Enter);
+        assert_eq!(app.status_message.as_deref(), Some("reading.align=center"));
+
+        let mut terminal = Terminal::new(TestBackend::new(160, 20)).unwrap();
+        terminal.draw(|f| ui::render(f, &mut app)).unwrap();
+        let buffer = terminal.backend().buffer().clone();
+        let (panel, text) = (app.editor_area, app.content_area);
+        assert_eq!(text.width, 40);
+        assert_eq!(text.x - (panel.x + 1), (panel.width - 2 - 40) / 2);
+
+        let row = (text.y..text.bottom())
+            .find(|&y| (text.x..text.right()).map(|x| buffer[(x, y)].symbol()).collect::<String>().contains("is linked"))
+            .unwrap();
+        let link_x = (text.x..text.right()).find(|&x| buffer[(x, row)].symbol() == "O").unwrap();
+        let click = |app: &mut App, column| {
+            let down = MouseEventKind::Down(MouseButton::Left);
+            handle_mouse_event(app, MouseEvent { kind: down, column, row, modifiers: KeyModifiers::NONE });
+        };
+
+        // Where the link would start without the margin is blank now
+        click(&mut app, panel.x + 1 + (link_x - text.x));
+        assert_eq!(app.current_note().unwrap().title, "Reading");
+        click(&mut app, link_x + 2);
+        assert_eq!(app.current_note().unwrap().title, "Other");
+
+        assert_eq!(app.set_option("reading.align=right"), Err("reading.align is left or center".to_string()));
+        assert_eq!(app.set_option("reading.max_width=none"), Ok("reading.max_width=none".to_string()));
+        assert_eq!(app.config.reading.max_width, None);
     }
 }
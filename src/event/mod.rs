@@ -1,3 +1,4 @@
+mod frame;
 mod handler;
 
 pub use handler::run_app;
@@ -9,17 +9,26 @@ use std::panic;
 use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
 use std::thread::{self, JoinHandle};
 
-use crate::editor::{HighlightRange, HighlightType, WikiLinkRange};
+use crate::app::{callout_marker_start, indent_guides, parse_definition, WikiLink};
+use crate::config::CalloutTheme;
+use crate::editor::{strikethrough_at, HighlightRange, HighlightType, WikiLinkRange};
 
 #[derive(Debug, Clone)]
 pub struct HighlightColors {
     pub heading_colors: [Color; 6],
     pub code_color: Color,
+    pub code_block_color: Color,
     pub link_color: Color,
     pub blockquote_color: Color,
+    pub callout_colors: CalloutTheme,
     pub list_marker_color: Color,
+    /// Bullet colors by nesting depth, with indent guides on
+    pub list_marker_colors: Option<[Color; 3]>,
+    /// Columns of leading whitespace per nesting level
+    pub tab_width: usize,
     pub bold_color: Option<Color>,
     pub italic_color: Option<Color>,
+    pub strikethrough_modifier: Modifier,
     pub frontmatter_color: Color,
     pub details_color: Color,
     pub horizontal_rule_color: Color,
@@ -37,11 +46,16 @@ impl Default for HighlightColors {
                 Color::Gray,
             ],
             code_color: Color::Green,
+            code_block_color: Color::Green,
             link_color: Color::Cyan,
             blockquote_color: Color::Cyan,
+            callout_colors: CalloutTheme::default(),
             list_marker_color: Color::Yellow,
+            list_marker_colors: None,
+            tab_width: 4,
             bold_color: None,
             italic_color: None,
+            strikethrough_modifier: Modifier::CROSSED_OUT,
             frontmatter_color: Color::DarkGray,
             details_color: Color::Magenta,
             horizontal_rule_color: Color::DarkGray,
@@ -161,6 +175,12 @@ fn worker_thread_loop(receiver: Receiver<HighlightRequest>, sender: Sender<Highl
     }
 }
 
+/// Markdown highlights for `content`, computed on the calling thread, for
+/// read-only views that don't go through an editor.
+pub fn highlight_source(content: &str, colors: &HighlightColors) -> Vec<HighlightRange> {
+    compute_all_highlights(content, colors).0
+}
+
 fn compute_all_highlights(
     content: &str,
     colors: &HighlightColors,
@@ -196,7 +216,7 @@ fn compute_all_highlights(
                 row,
                 char_start,
                 char_start + bytecount_chars(trimmed),
-                Style::default().fg(colors.code_color),
+                Style::default().fg(colors.code_block_color),
                 HighlightType::CodeBlock,
             ));
             continue;
@@ -207,7 +227,7 @@ fn compute_all_highlights(
                 row,
                 0,
                 bytecount_chars(line),
-                Style::default().fg(colors.code_color),
+                Style::default().fg(colors.code_block_color),
                 HighlightType::CodeBlock,
             ));
             continue;
@@ -281,6 +301,18 @@ fn highlight_markdown_line(
         return;
     }
 
+    // `[ref]: url`, the definition of a reference link
+    if parse_definition(line).is_some() {
+        highlights.push(HighlightRange::new(
+            row,
+            0,
+            line_len,
+            Style::default().fg(colors.link_color).add_modifier(Modifier::ITALIC),
+            HighlightType::Link,
+        ));
+        return;
+    }
+
     let trimmed = line.trim_start();
     if !trimmed.is_empty() && trimmed.as_bytes()[0] == b'>' {
         let start = line.len() - trimmed.len();
@@ -292,6 +324,15 @@ fn highlight_markdown_line(
             Style::default().fg(colors.blockquote_color),
             HighlightType::Blockquote,
         ));
+        if let Some((kind, marker)) = callout_marker_start(line) {
+            highlights.push(HighlightRange::new(
+                row,
+                marker,
+                line_len,
+                Style::default().fg(kind.color(&colors.callout_colors)).add_modifier(Modifier::BOLD),
+                HighlightType::Blockquote,
+            ));
+        }
     }
 
     highlight_details_tags_fast(row, line, colors, highlights);
@@ -301,6 +342,7 @@ fn highlight_markdown_line(
     let highlight_start = highlights.len();
     highlight_bold_fast(row, &chars, colors, highlights, highlight_start);
     highlight_italic_fast(row, &chars, colors, highlights, highlight_start);
+    highlight_strikethrough_fast(row, &chars, colors, highlights, highlight_start);
 }
 
 #[inline]
@@ -406,6 +448,10 @@ fn highlight_list_marker_fast(
     let indent_chars = line.len() - trimmed.len();
     let indent_char_count = line[..indent_chars].chars().count();
     let first_byte = trimmed.as_bytes()[0];
+    let marker_color = match colors.list_marker_colors {
+        Some(by_depth) => by_depth[indent_guides(line, colors.tab_width, colors.tab_width).len() % 3],
+        None => colors.list_marker_color,
+    };
 
     if (first_byte == b'-' || first_byte == b'*' || first_byte == b'+')
         && trimmed.len() > 1
@@ -415,7 +461,7 @@ fn highlight_list_marker_fast(
             row,
             indent_char_count,
             indent_char_count + 1,
-            Style::default().fg(colors.list_marker_color),
+            Style::default().fg(marker_color),
             HighlightType::ListMarker,
         ));
 
@@ -445,7 +491,7 @@ fn highlight_list_marker_fast(
                     row,
                     indent_char_count,
                     indent_char_count + dot_pos + 1,
-                    Style::default().fg(colors.list_marker_color),
+                    Style::default().fg(marker_color),
                     HighlightType::ListMarker,
                 ));
             }
@@ -544,6 +590,30 @@ fn highlight_links_fast(
                     continue;
                 }
             }
+
+            // A reference link, `[text][ref]`
+            if j + 1 < len && chars[j + 1] == '[' {
+                let mut k = j + 2;
+                while k < len && chars[k] != ']' && chars[k] != '[' {
+                    k += 1;
+                }
+                if k < len && chars[k] == ']' {
+                    highlights.push(
+                        HighlightRange::new(
+                            row,
+                            i,
+                            k + 1,
+                            Style::default()
+                                .fg(colors.link_color)
+                                .add_modifier(Modifier::UNDERLINED),
+                            HighlightType::Link,
+                        )
+                        .with_priority(1),
+                    );
+                    i = k + 1;
+                    continue;
+                }
+            }
         }
         i += 1;
     }
@@ -654,6 +724,28 @@ fn highlight_italic_fast(
     }
 }
 
+fn highlight_strikethrough_fast(
+    row: usize,
+    chars: &[char],
+    colors: &HighlightColors,
+    highlights: &mut Vec<HighlightRange>,
+    check_from: usize,
+) {
+    let mut i = 0;
+    while i < chars.len() {
+        match strikethrough_at(chars, i) {
+            Some(end) => {
+                if !is_position_highlighted_fast(highlights, row, i, check_from) {
+                    let style = Style::default().add_modifier(colors.strikethrough_modifier);
+                    highlights.push(HighlightRange::new(row, i, end, style, HighlightType::Strikethrough));
+                }
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+}
+
 fn compute_all_wiki_links(content: &str, frontmatter_end: Option<usize>) -> Vec<WikiLinkRange> {
     let line_count = content.lines().count();
     let mut wiki_links = Vec::with_capacity(line_count / 4); 
@@ -699,29 +791,21 @@ fn compute_all_wiki_links(content: &str, frontmatter_end: Option<usize>) -> Vec<
 
             if let Some(start_pos) = remaining.find("[[") {
                 let abs_start = search_start + start_pos;
-                let after_brackets = &line[abs_start + 2..];
-
-                if let Some(end_pos) = after_brackets.find("]]") {
-                    let raw_content = &after_brackets[..end_pos];
-
-                    if !raw_content.is_empty()
-                        && !raw_content.as_bytes().contains(&b'[')
-                        && !raw_content.as_bytes().contains(&b']')
-                    {
-                        let start_col = line[..abs_start].chars().count();
-                        let end_col = start_col + 2 + raw_content.chars().count() + 2;
-
-                        wiki_links.push(WikiLinkRange {
-                            row,
-                            start_col,
-                            end_col,
-                            is_valid: false,
-                        });
-                    }
-
-                    search_start = abs_start + 2 + end_pos + 2;
-                    continue;
+                if let Some(link) = WikiLink::parse(&line[abs_start..]) {
+                    let start_col = line[..abs_start].chars().count();
+                    let end_col = start_col + line[abs_start..abs_start + link.len].chars().count();
+
+                    wiki_links.push(WikiLinkRange {
+                        row,
+                        start_col,
+                        end_col,
+                        is_valid: false,
+                    });
+                    search_start = abs_start + link.len;
+                } else {
+                    search_start = abs_start + 2;
                 }
+                continue;
             }
             break;
         }
@@ -776,6 +860,13 @@ mod tests {
         assert_eq!(links[0].start_col, 21);
     }
 
+    #[test]
+    fn test_wiki_links_with_escapes() {
+        let links = compute_all_wiki_links("[[C++ \\[advanced\\]]] [[a [b]]]", None);
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].end_col, 20);
+    }
+
     #[test]
     fn test_details_tags_highlighting() {
         let colors = HighlightColors::default();
@@ -898,6 +989,21 @@ mod tests {
             "[text] (url) with space should not be a link");
     }
 
+    #[test]
+    fn test_reference_links() {
+        let colors = HighlightColors::default();
+        let (highlights, _) = compute_all_highlights("See [docs][rust] and [Rust][].\n\n[rust]: https://rust-lang.org", &colors);
+        let links: Vec<(usize, usize, usize)> = highlights
+            .iter()
+            .filter(|h| h.highlight_type == HighlightType::Link)
+            .map(|h| (h.row, h.start_col, h.end_col))
+            .collect();
+        assert_eq!(links, [(0, 4, 16), (0, 21, 29), (2, 0, 29)]);
+        let definition = highlights.iter().find(|h| h.row == 2).unwrap();
+        assert!(definition.style.add_modifier.contains(Modifier::ITALIC),
+            "Definitions should look unlike the links using them");
+    }
+
     #[test]
     fn test_no_false_positive_inline_code() {
         let colors = HighlightColors::default();
@@ -948,6 +1054,25 @@ mod tests {
             "Number.text should not be list marker");
     }
 
+    #[test]
+    fn test_list_markers_colored_by_depth() {
+        let marker_colors = |colors: &HighlightColors| -> Vec<Option<Color>> {
+            let (highlights, _) = compute_all_highlights("- a\n  - b\n\t- c\n        1. d", colors);
+            highlights.iter().filter(|h| h.highlight_type == HighlightType::ListMarker).map(|h| h.style.fg).collect()
+        };
+        let colors = HighlightColors::default();
+        assert_eq!(marker_colors(&colors), [Some(Color::Yellow); 4]);
+
+        let colors = HighlightColors {
+            list_marker_colors: Some([Color::Red, Color::Green, Color::Blue]),
+            ..HighlightColors::default()
+        };
+        assert_eq!(
+            marker_colors(&colors),
+            [Some(Color::Red), Some(Color::Green), Some(Color::Green), Some(Color::Blue)]
+        );
+    }
+
     #[test]
     fn test_horizontal_rule_highlighting() {
         let colors = HighlightColors::default();
@@ -1125,4 +1250,16 @@ mod tests {
         assert_eq!(bq.unwrap().start_col, 0);
         assert_eq!(bq.unwrap().end_col, 1);
     }
+
+    #[test]
+    fn test_callout_marker() {
+        let colors = HighlightColors::default();
+
+        let (highlights, _) = compute_all_highlights("> [!caution] Hot\n> [x] not one", &colors);
+        let quotes: Vec<_> = highlights.iter().filter(|h| h.highlight_type == HighlightType::Blockquote).collect();
+        let marker = quotes.iter().find(|h| h.row == 0 && h.start_col == 2).expect("callout marker highlight");
+        assert_eq!(marker.end_col, 16);
+        assert_eq!(marker.style.fg, Some(colors.callout_colors.caution));
+        assert!(!quotes.iter().any(|h| h.row == 1 && h.start_col > 0));
+    }
 }
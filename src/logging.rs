@@ -0,0 +1,158 @@
+//! The diagnostics log, `ekphos.log` in the cache folder, turned on with
+//! `log.level`. Once it grows past `log.max_size_kb` it's moved to
+//! `ekphos.log.1`, replacing the one before, so at most two are kept.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use chrono::Local;
+use log::{LevelFilter, Log, Metadata, Record};
+
+use crate::config::{LogConfig, LogLevel};
+
+pub const LOG_FILE: &str = "ekphos.log";
+
+struct FileLogger {
+    level: LevelFilter,
+    path: PathBuf,
+    max_bytes: u64,
+    /// The open log and how many bytes are in it
+    file: Mutex<Option<(File, u64)>>,
+}
+
+impl FileLogger {
+    fn new(path: PathBuf, level: LevelFilter, max_bytes: u64) -> Self {
+        Self { level, path, max_bytes, file: Mutex::new(None) }
+    }
+
+    fn write_line(&self, line: &str) -> io::Result<()> {
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if file.is_none() {
+            let opened = OpenOptions::new().create(true).append(true).open(&self.path)?;
+            let len = opened.metadata()?.len();
+            *file = Some((opened, len));
+        }
+        let len = file.as_ref().map_or(0, |(_, len)| *len);
+        if len > 0 && len + line.len() as u64 > self.max_bytes {
+            *file = None;
+            fs::rename(&self.path, rotated(&self.path))?;
+            *file = Some((File::create(&self.path)?, 0));
+        }
+        if let Some((opened, len)) = file.as_mut() {
+            opened.write_all(line.as_bytes())?;
+            *len += line.len() as u64;
+        }
+        Ok(())
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "{} {:<5} {}: {}\n",
+            Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        let _ = self.write_line(&line);
+    }
+
+    fn flush(&self) {
+        if let Some((file, _)) = self.file.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Where the log is moved when it's full.
+fn rotated(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".1");
+    PathBuf::from(name)
+}
+
+fn level_filter(level: LogLevel) -> LevelFilter {
+    match level {
+        LogLevel::Off => LevelFilter::Off,
+        LogLevel::Error => LevelFilter::Error,
+        LogLevel::Warn => LevelFilter::Warn,
+        LogLevel::Info => LevelFilter::Info,
+        LogLevel::Debug => LevelFilter::Debug,
+        LogLevel::Trace => LevelFilter::Trace,
+    }
+}
+
+/// Start logging to [`LOG_FILE`] in `dir` as `config` asks. Nothing is
+/// opened or written with the level off.
+pub fn init(config: &LogConfig, dir: &Path) {
+    let level = level_filter(config.level);
+    if level == LevelFilter::Off || fs::create_dir_all(dir).is_err() {
+        return;
+    }
+    let logger = FileLogger::new(dir.join(LOG_FILE), level, config.max_size_kb.max(1) * 1024);
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(level);
+        log::info!("ekphos {} started", env!("CARGO_PKG_VERSION"));
+    }
+}
+
+/// Logs how long it lived at trace level when dropped, for timing a step
+/// of the main loop or a background job.
+pub struct Span {
+    name: &'static str,
+    start: Instant,
+}
+
+pub fn span(name: &'static str) -> Span {
+    Span { name, start: Instant::now() }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        log::trace!(target: "ekphos::span", "{} took {:.2?}", self.name, self.start.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_support::TempDir;
+    use log::Level;
+
+    fn record(logger: &FileLogger, level: Level, message: &str) {
+        logger.log(&Record::builder().level(level).target("test").args(format_args!("{}", message)).build());
+    }
+
+    #[test]
+    fn test_file_logger_filters_and_rotates() {
+        let dir = TempDir::new("log");
+        let path = dir.join(LOG_FILE);
+        let logger = FileLogger::new(path.clone(), LevelFilter::Info, 200);
+
+        record(&logger, Level::Debug, "too detailed");
+        assert!(!path.exists());
+
+        record(&logger, Level::Info, "first");
+        record(&logger, Level::Warn, "second");
+        let log = fs::read_to_string(&path).unwrap();
+        assert!(log.contains("INFO  test: first") && log.contains("WARN  test: second"));
+
+        record(&logger, Level::Error, &"x".repeat(150));
+        logger.flush();
+        let old = fs::read_to_string(rotated(&path)).unwrap();
+        assert!(old.contains("first") && !old.contains("xxx"));
+        let log = fs::read_to_string(&path).unwrap();
+        assert!(log.contains("xxx") && !log.contains("first"));
+    }
+}
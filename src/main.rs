@@ -7,7 +7,9 @@ mod graph;
 mod highlight;
 mod highlight_worker;
 mod journal;
+mod logging;
 mod search;
+mod terminal;
 mod ui;
 mod vim;
 
@@ -16,12 +18,6 @@ use std::fs;
 use std::io;
 use std::path::PathBuf;
 
-use crossterm::{
-    cursor::SetCursorStyle,
-    event::{DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste, EnableFocusChange, EnableMouseCapture},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
 use ratatui::{backend::CrosstermBackend, Terminal};
 
 use app::App;
@@ -146,6 +142,14 @@ fn print_help() {
     println!("    -d, --dir        Print notes directory path");
     println!("    --reset          Reset config and themes to defaults");
     println!("    --clean-cache    Clear the search index cache");
+    println!("    --debug          Start with the debug overlay shown (F12 toggles it)");
+    println!("    --export-graph <dot|json> [FILE]");
+    println!("                     Write the notes' link graph to FILE, or to stdout");
+    println!("    --ipc-send <JSON>");
+    println!("                     Send a command to the running ekphos (needs ipc = true)");
+    println!();
+    println!("ENVIRONMENT:");
+    println!("    {}      Keep config, themes and caches in this folder", config::HOME_ENV);
     println!();
     println!("EXAMPLES:");
     println!("    ekphos ~/notes           Open the ~/notes folder");
@@ -153,6 +157,46 @@ fn print_help() {
     println!("    ekphos .                 Open current directory as notes folder");
 }
 
+/// Print the running ekphos's answer to `command`, exiting with 1 if it
+/// couldn't be sent or didn't succeed.
+fn ipc_send(command: Option<&String>) {
+    let Some(command) = command else {
+        eprintln!("Usage: ekphos --ipc-send '{{\"command\": \"list-notes\"}}'");
+        std::process::exit(2);
+    };
+    let path = app::ipc_socket_path();
+    match app::ipc_send(&path, command) {
+        Ok(response) => {
+            println!("{}", response);
+            if !response.starts_with(r#"{"success":true"#) {
+                std::process::exit(1);
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to reach ekphos at {}: {}", path.display(), e);
+            eprintln!("Is it running with ipc = true in the config?");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn export_graph(args: &[String]) {
+    let Some(format) = args.first().and_then(|name| app::GraphFormat::parse(name)) else {
+        eprintln!("Usage: ekphos --export-graph <dot|json> [FILE]");
+        return;
+    };
+    let notes_path = config::Config::load().notes_path();
+    let notes = app::read_vault(&notes_path);
+    let graph = app::export_graph(&notes, &notes_path, format);
+    match args.get(1) {
+        Some(output) => match fs::write(output, graph) {
+            Ok(()) => println!("Wrote {}", output),
+            Err(e) => eprintln!("Failed to write {}: {}", output, e),
+        },
+        None => print!("{}", graph),
+    }
+}
+
 fn reset_config_and_themes() {
     let config_path = config::Config::config_path();
     let themes_dir = config::Config::themes_dir();
@@ -192,9 +236,7 @@ fn reset_config_and_themes() {
 }
 
 fn clean_cache() {
-    let cache_dir = dirs::cache_dir()
-        .unwrap_or_else(|| PathBuf::from(env::var("HOME").unwrap_or_default()).join(".cache"))
-        .join("ekphos");
+    let cache_dir = config::Config::cache_dir();
 
     println!("Cleaning ekphos search cache...");
     println!();
@@ -296,12 +338,14 @@ fn resolve_path(path_str: &str) -> Option<PathBuf> {
         env::current_dir().ok()?.join(path)
     };
 
-    absolute.canonicalize().ok().or(Some(absolute))
+    app::canonicalize_path(&absolute).ok().or(Some(absolute))
 }
 
 fn main() -> io::Result<()> {
     // Handle CLI arguments
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    let debug = args.iter().skip(1).any(|arg| arg == "--debug");
+    args.retain(|arg| arg != "--debug");
     let mut initial_path: Option<PathBuf> = None;
 
     if args.len() > 1 {
@@ -331,6 +375,14 @@ fn main() -> io::Result<()> {
                 clean_cache();
                 return Ok(());
             }
+            "--ipc-send" => {
+                ipc_send(args.get(2));
+                return Ok(());
+            }
+            "--export-graph" => {
+                export_graph(&args[2..]);
+                return Ok(());
+            }
             arg if arg.starts_with('-') => {
                 eprintln!("Unknown option: {}", arg);
                 eprintln!("Run 'ekphos --help' for usage information");
@@ -360,32 +412,32 @@ fn main() -> io::Result<()> {
 
     // Setup terminal. On Unix this also redirects stdout to /dev/null and draws
     // through a dup of the terminal, so stray library output can't corrupt the UI.
-    enable_raw_mode()?;
     let mut writer = terminal_writer();
-    execute!(writer, EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste, EnableFocusChange, SetCursorStyle::SteadyBlock)?;
+    let capabilities = terminal::enter(&mut writer)?;
+    terminal::install_panic_hook();
     let backend = CrosstermBackend::new(writer);
     let mut terminal = Terminal::new(backend)?;
 
     // Create app state
     let mut app = App::new_with_path(initial_path);
+    logging::init(&app.config.log, &config::Config::cache_dir());
+    app.set_capabilities(capabilities);
+    app.debug_overlay = debug;
 
     // Main loop
     let result = run_app(&mut terminal, &mut app);
 
+    let save_errors = app.finish_saves();
     app.save_last_opened_note_to_cache();
+    app.save_reading_positions();
+    app.save_search_history();
 
     // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        SetCursorStyle::DefaultUserShape,
-        LeaveAlternateScreen,
-        DisableMouseCapture,
-        DisableBracketedPaste,
-        DisableFocusChange
-    )?;
-    terminal.show_cursor()?;
+    terminal::restore(terminal.backend_mut())?;
 
+    for error in save_errors {
+        eprintln!("{error}");
+    }
     if let Err(err) = result {
         eprintln!("Error: {err:?}");
     }
@@ -4,6 +4,8 @@ use std::fs;
 use std::io::{BufReader, BufWriter};
 use serde::{Serialize, Deserialize};
 
+use crate::config::Config;
+
 const INDEX_VERSION: u32 = 2;
 
 #[derive(Serialize, Deserialize, Default, Clone)]
@@ -28,9 +30,12 @@ pub struct SearchIndex {
 
 /// Get cache directory for index
 pub fn get_index_path(notes_dir: &Path) -> PathBuf {
-    let cache_base = dirs::cache_dir()
-        .unwrap_or_else(|| PathBuf::from(std::env::var("HOME").unwrap_or_default()).join(".cache"));
+    vault_cache_dir(notes_dir).join("search_index.bin")
+}
 
+/// Cache folder of the notes directory, for what's kept per vault: the
+/// search index and the last opened note.
+pub fn vault_cache_dir(notes_dir: &Path) -> PathBuf {
     // Create 8-char hash of notes directory
     let hash = {
         use std::collections::hash_map::DefaultHasher;
@@ -40,7 +45,7 @@ pub fn get_index_path(notes_dir: &Path) -> PathBuf {
         format!("{:016x}", hasher.finish())[..8].to_string()
     };
 
-    cache_base.join("ekphos").join(hash).join("search_index.bin")
+    Config::cache_dir().join(hash)
 }
 
 /// Load index from disk
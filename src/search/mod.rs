@@ -1,3 +1,3 @@
 mod index;
 
-pub use index::{SearchIndex, get_index_path, load_index, save_index};
+pub use index::{SearchIndex, get_index_path, load_index, save_index, vault_cache_dir};
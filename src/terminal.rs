@@ -0,0 +1,327 @@
+//! Switching the terminal between the TUI and the shell: at start and exit,
+//! around a Ctrl+Z suspend, and from the panic hook so a crash doesn't
+//! leave the shell in raw mode. Past raw mode and the alternate screen,
+//! each feature is tried on its own and the TUI goes on without the ones a
+//! terminal refuses.
+
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crossterm::{
+    cursor::{SetCursorStyle, Show},
+    event::{
+        DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste, EnableFocusChange,
+        EnableMouseCapture, KeyCode, KeyEvent, KeyModifiers, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
+        PushKeyboardEnhancementFlags,
+    },
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{backend::CrosstermBackend, Terminal};
+
+use crate::app::Capabilities;
+
+/// The features [`enter`] turned on, for [`restore`] to turn off again.
+static ENABLED: AtomicU8 = AtomicU8::new(0);
+
+/// A terminal mode the TUI turns on at start and off at exit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    RawMode,
+    AlternateScreen,
+    BracketedPaste,
+    FocusChange,
+    CursorShape,
+    MouseCapture,
+    /// The kitty keyboard protocol, refused where it isn't supported
+    KeyboardEnhancement,
+}
+
+impl Feature {
+    /// In the order they're turned on.
+    const ALL: [Feature; 7] = [
+        Feature::RawMode,
+        Feature::AlternateScreen,
+        Feature::BracketedPaste,
+        Feature::FocusChange,
+        Feature::CursorShape,
+        Feature::MouseCapture,
+        Feature::KeyboardEnhancement,
+    ];
+
+    /// Whether the TUI can't run without it.
+    fn required(self) -> bool {
+        matches!(self, Feature::RawMode | Feature::AlternateScreen)
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Turns [`Feature`]s on and off: the terminal itself, or a fake in tests.
+pub trait FeatureBackend {
+    fn enable(&mut self, feature: Feature) -> io::Result<()>;
+    fn disable(&mut self, feature: Feature) -> io::Result<()>;
+}
+
+/// Crossterm's commands, written to the terminal.
+struct Crossterm<'a, W: Write>(&'a mut W);
+
+impl<W: Write> FeatureBackend for Crossterm<'_, W> {
+    fn enable(&mut self, feature: Feature) -> io::Result<()> {
+        let writer = &mut *self.0;
+        match feature {
+            Feature::RawMode => enable_raw_mode(),
+            Feature::AlternateScreen => execute!(writer, EnterAlternateScreen),
+            Feature::BracketedPaste => execute!(writer, EnableBracketedPaste),
+            Feature::FocusChange => execute!(writer, EnableFocusChange),
+            Feature::CursorShape => execute!(writer, SetCursorStyle::SteadyBlock),
+            Feature::MouseCapture => execute!(writer, EnableMouseCapture),
+            // Only disambiguating: with more, keys would need reading differently
+            Feature::KeyboardEnhancement if supports_keyboard_enhancement().unwrap_or(false) => {
+                execute!(writer, PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES))
+            }
+            Feature::KeyboardEnhancement => Err(io::Error::new(io::ErrorKind::Unsupported, "no kitty keyboard protocol")),
+        }
+    }
+
+    fn disable(&mut self, feature: Feature) -> io::Result<()> {
+        let writer = &mut *self.0;
+        match feature {
+            Feature::RawMode => disable_raw_mode(),
+            Feature::AlternateScreen => execute!(writer, LeaveAlternateScreen),
+            Feature::BracketedPaste => execute!(writer, DisableBracketedPaste),
+            Feature::FocusChange => execute!(writer, DisableFocusChange),
+            Feature::CursorShape => execute!(writer, SetCursorStyle::DefaultUserShape),
+            Feature::MouseCapture => execute!(writer, DisableMouseCapture),
+            Feature::KeyboardEnhancement => execute!(writer, PopKeyboardEnhancementFlags),
+        }
+    }
+}
+
+/// Which [`Feature`]s are on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TerminalFeatures {
+    enabled: u8,
+}
+
+impl TerminalFeatures {
+    /// Turn every feature on, going without the optional ones that fail. If
+    /// a required one fails, what was turned on is turned off again.
+    pub fn enable(backend: &mut impl FeatureBackend) -> io::Result<Self> {
+        let mut features = Self::default();
+        for feature in Feature::ALL {
+            match backend.enable(feature) {
+                Ok(()) => features.enabled |= feature.bit(),
+                Err(e) if feature.required() => {
+                    let _ = features.disable(backend);
+                    return Err(e);
+                }
+                Err(_) => {}
+            }
+        }
+        Ok(features)
+    }
+
+    /// Turn off what [`TerminalFeatures::enable`] turned on, last first.
+    /// Goes on past a failure, giving back the first.
+    pub fn disable(&self, backend: &mut impl FeatureBackend) -> io::Result<()> {
+        let mut result = Ok(());
+        for feature in Feature::ALL.into_iter().rev().filter(|&f| self.has(f)) {
+            if let Err(e) = backend.disable(feature) {
+                result = result.and(Err(e));
+            }
+        }
+        result
+    }
+
+    pub fn has(&self, feature: Feature) -> bool {
+        self.enabled & feature.bit() != 0
+    }
+
+    /// What the TUI can count on; image support is queried later.
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            keyboard_enhancement: self.has(Feature::KeyboardEnhancement),
+            mouse: self.has(Feature::MouseCapture),
+            bracketed_paste: self.has(Feature::BracketedPaste),
+            focus_events: self.has(Feature::FocusChange),
+            images: false,
+        }
+    }
+}
+
+/// Raw mode, the alternate screen, mouse capture and the rest the TUI needs.
+/// Fails only without raw mode or the alternate screen; otherwise returns
+/// what the terminal took of the rest.
+pub fn enter(writer: &mut impl Write) -> io::Result<Capabilities> {
+    let features = TerminalFeatures::enable(&mut Crossterm(writer))?;
+    ENABLED.store(features.enabled, Ordering::Relaxed);
+    Ok(features.capabilities())
+}
+
+/// Undo [`enter`], leaving the terminal as the shell had it. Only what was
+/// turned on is turned off.
+pub fn restore(writer: &mut impl Write) -> io::Result<()> {
+    let features = TerminalFeatures { enabled: ENABLED.swap(0, Ordering::Relaxed) };
+    let disabled = features.disable(&mut Crossterm(&mut *writer));
+    disabled.and(execute!(writer, Show))
+}
+
+/// A key as a terminal without the kitty keyboard protocol sends it. The
+/// protocol reports Ctrl+Shift+R as a lowercase `r` with Shift, where the
+/// key handlers expect the uppercase letter.
+pub fn normalize_key(mut key: KeyEvent) -> KeyEvent {
+    if let KeyCode::Char(c) = key.code {
+        if key.modifiers.contains(KeyModifiers::SHIFT) && c.is_ascii_lowercase() {
+            key.code = KeyCode::Char(c.to_ascii_uppercase());
+        }
+    }
+    key
+}
+
+/// Restore the terminal before a panic message is printed. stdout may point
+/// at `/dev/null` by then, so this goes through stderr.
+pub fn install_panic_hook() {
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = restore(&mut io::stderr());
+        hook(info);
+    }));
+}
+
+/// Stop the process as Ctrl+Z does in a shell, with the terminal handed back
+/// until `fg` continues it. Returns what the terminal supports on return, or
+/// `None` where there's no job control.
+#[cfg(unix)]
+pub fn suspend<W: Write>(terminal: &mut Terminal<CrosstermBackend<W>>) -> io::Result<Option<Capabilities>> {
+    restore(terminal.backend_mut())?;
+    // Stops here until SIGCONT
+    unsafe { libc::raise(libc::SIGTSTP) };
+    let capabilities = enter(terminal.backend_mut())?;
+    terminal.clear()?;
+    Ok(Some(capabilities))
+}
+
+#[cfg(not(unix))]
+pub fn suspend<W: Write>(_terminal: &mut Terminal<CrosstermBackend<W>>) -> io::Result<Option<Capabilities>> {
+    Ok(None)
+}
+
+/// Hand the terminal to `$VISUAL` or `$EDITOR` to edit `path`, taking it
+/// back once the editor exits. Returns what the terminal supports on
+/// return, and whether the editor could be run.
+pub fn edit_externally<W: Write>(
+    terminal: &mut Terminal<CrosstermBackend<W>>,
+    path: &Path,
+) -> io::Result<(Capabilities, io::Result<()>)> {
+    restore(terminal.backend_mut())?;
+    let command = editor_command(std::env::var("VISUAL").ok(), std::env::var("EDITOR").ok());
+    let ran = Command::new(&command[0])
+        .args(&command[1..])
+        .arg(path)
+        .status()
+        .map(|_| ());
+    let capabilities = enter(terminal.backend_mut())?;
+    terminal.clear()?;
+    Ok((capabilities, ran))
+}
+
+/// The editor program and its arguments, from `$VISUAL` over `$EDITOR`,
+/// falling back to the system's usual one.
+fn editor_command(visual: Option<String>, editor: Option<String>) -> Vec<String> {
+    let words: Vec<String> = [visual, editor]
+        .into_iter()
+        .flatten()
+        .map(|value| value.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+        .find(|words| !words.is_empty())
+        .unwrap_or_default();
+    if words.is_empty() {
+        vec![if cfg!(windows) { "notepad" } else { "vi" }.to_string()]
+    } else {
+        words
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Refuses the features in `refused`, recording what's turned on and off.
+    #[derive(Default)]
+    struct FakeBackend {
+        refused: Vec<Feature>,
+        calls: Vec<(bool, Feature)>,
+    }
+
+    impl FeatureBackend for FakeBackend {
+        fn enable(&mut self, feature: Feature) -> io::Result<()> {
+            self.calls.push((true, feature));
+            if self.refused.contains(&feature) {
+                return Err(io::Error::other("refused"));
+            }
+            Ok(())
+        }
+
+        fn disable(&mut self, feature: Feature) -> io::Result<()> {
+            self.calls.push((false, feature));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_editor_command_prefers_visual() {
+        assert_eq!(editor_command(Some("code -w".into()), Some("nano".into())), ["code", "-w"]);
+        assert_eq!(editor_command(Some(" ".into()), Some("nano".into())), ["nano"]);
+        assert_eq!(editor_command(None, None).len(), 1);
+    }
+
+    #[test]
+    fn test_optional_features_may_fail() {
+        let mut backend = FakeBackend {
+            refused: vec![Feature::MouseCapture, Feature::BracketedPaste],
+            ..FakeBackend::default()
+        };
+        let features = TerminalFeatures::enable(&mut backend).unwrap();
+        assert!(features.has(Feature::AlternateScreen) && features.has(Feature::KeyboardEnhancement));
+        assert!(!features.has(Feature::MouseCapture) && !features.has(Feature::BracketedPaste));
+        let capabilities = features.capabilities();
+        assert!(!capabilities.mouse && !capabilities.bracketed_paste && capabilities.focus_events);
+
+        backend.calls.clear();
+        features.disable(&mut backend).unwrap();
+        let disabled: Vec<Feature> = backend.calls.iter().map(|&(_, f)| f).collect();
+        assert_eq!(
+            disabled,
+            [
+                Feature::KeyboardEnhancement,
+                Feature::CursorShape,
+                Feature::FocusChange,
+                Feature::AlternateScreen,
+                Feature::RawMode,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_required_feature_failing_undoes_the_rest() {
+        let mut backend = FakeBackend { refused: vec![Feature::AlternateScreen], ..FakeBackend::default() };
+        assert!(TerminalFeatures::enable(&mut backend).is_err());
+        assert_eq!(
+            backend.calls,
+            [(true, Feature::RawMode), (true, Feature::AlternateScreen), (false, Feature::RawMode)]
+        );
+    }
+
+    #[test]
+    fn test_normalize_key() {
+        let ctrl_shift = KeyModifiers::CONTROL | KeyModifiers::SHIFT;
+        let key = normalize_key(KeyEvent::new(KeyCode::Char('r'), ctrl_shift));
+        assert_eq!((key.code, key.modifiers), (KeyCode::Char('R'), ctrl_shift));
+        let key = normalize_key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!(key.code, KeyCode::Char('r'));
+    }
+}
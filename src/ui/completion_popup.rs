@@ -0,0 +1,211 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::{App, WikiSuggestion};
+
+const POPUP_WIDTH: u16 = 45;
+const POPUP_MAX_VISIBLE_ITEMS: usize = 5;
+const POPUP_MAX_VISIBLE_LINES: usize = 8; // Max lines for items with folder hints
+
+/// Place a popup just below the editor cursor, or above it when it would run
+/// off the bottom of the screen. Shared by the editor's completion popups.
+pub fn popup_area_at_cursor(app: &App, area: Rect, width: u16, height: u16) -> Rect {
+    let (cursor_row, cursor_col) = app.editor.cursor();
+    let editor_area = app.editor_area;
+    let border_offset = if app.zen_mode { 0 } else { 1 };
+    let cursor_screen_y = editor_area.y + border_offset + (cursor_row.saturating_sub(app.editor_scroll_top)) as u16;
+    let cursor_screen_x = editor_area.x + border_offset + cursor_col as u16;
+
+    let popup_y = if cursor_screen_y + height < area.height {
+        cursor_screen_y + 1
+    } else {
+        cursor_screen_y.saturating_sub(height + 1)
+    };
+
+    let popup_x = cursor_screen_x.min(area.width.saturating_sub(width + 1));
+
+    Rect::new(popup_x, popup_y, width, height)
+}
+
+/// A suggestion list drawn at the editor cursor. Used by the wiki link and
+/// markdown link completions, which differ only in title and footer.
+pub struct CompletionPopup<'a> {
+    pub title: String,
+    pub suggestions: &'a [WikiSuggestion],
+    pub selected_index: usize,
+    /// Shown instead of the list, e.g. while typing a wiki link alias
+    pub placeholder: Option<&'a str>,
+    /// Overrides the default "n/total" / "No matches" footer
+    pub footer: Option<String>,
+}
+
+pub fn render_completion_popup(f: &mut Frame, app: &App, popup: CompletionPopup) {
+    let CompletionPopup {
+        title,
+        suggestions,
+        selected_index,
+        placeholder,
+        footer,
+    } = popup;
+    let theme = &app.theme;
+    let area = f.area();
+
+    let (scroll_offset, visible_count, total_lines) = if placeholder.is_some() {
+        (0, 1, 1)
+    } else if suggestions.is_empty() {
+        (0, 0, 0)
+    } else {
+        let mut lines_used = 0;
+        let mut first_visible = selected_index;
+
+        for i in (0..=selected_index).rev() {
+            let item_lines = if suggestions[i].folder_hint.is_some() { 2 } else { 1 };
+            if lines_used + item_lines > POPUP_MAX_VISIBLE_LINES {
+                break;
+            }
+            lines_used += item_lines;
+            first_visible = i;
+
+            if selected_index - first_visible + 1 >= POPUP_MAX_VISIBLE_ITEMS {
+                break;
+            }
+        }
+
+        let scroll_offset = first_visible;
+
+        let mut count = 0;
+        let mut lines = 0;
+        for suggestion in suggestions.iter().skip(scroll_offset) {
+            let item_lines = if suggestion.folder_hint.is_some() { 2 } else { 1 };
+            if lines + item_lines > POPUP_MAX_VISIBLE_LINES {
+                break;
+            }
+            if count >= POPUP_MAX_VISIBLE_ITEMS {
+                break;
+            }
+            lines += item_lines;
+            count += 1;
+        }
+
+        (scroll_offset, count.max(1), lines.max(1))
+    };
+
+    let popup_height = (total_lines as u16 + 2).min(POPUP_MAX_VISIBLE_LINES as u16 + 2);
+    let popup_width = POPUP_WIDTH.min(area.width.saturating_sub(2));
+    let popup_area = popup_area_at_cursor(app, area, popup_width, popup_height);
+
+    f.render_widget(Clear, popup_area);
+
+    let max_name_width = (popup_width as usize).saturating_sub(8);
+
+    let lines: Vec<Line> = if let Some(placeholder) = placeholder {
+        vec![Line::from(vec![
+            Span::raw(" "),
+            Span::styled(placeholder, Style::default().fg(theme.muted)),
+        ])]
+    } else {
+        let mut lines = Vec::new();
+        for (idx, suggestion) in suggestions.iter().enumerate().skip(scroll_offset).take(visible_count) {
+            let prefix = if suggestion.is_folder { "dir: " } else { "" };
+            let prefix_len = prefix.len();
+            let is_selected = idx == selected_index;
+
+            // Truncate display name if too long (use chars for Unicode safety)
+            let display_name = if suggestion.display_name.chars().count() > max_name_width {
+                let truncated: String = suggestion
+                    .display_name
+                    .chars()
+                    .take(max_name_width.saturating_sub(1))
+                    .collect();
+                format!("{}…", truncated)
+            } else {
+                suggestion.display_name.clone()
+            };
+
+            let style = if is_selected {
+                Style::default()
+                    .fg(theme.background)
+                    .bg(theme.primary)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.foreground)
+            };
+
+            let prefix_style = if is_selected {
+                style
+            } else {
+                Style::default().fg(theme.warning)
+            };
+
+            // Main line with title
+            if is_selected {
+                let content_width = (popup_width as usize).saturating_sub(2);
+                let used_width = 1 + prefix_len + display_name.chars().count();
+                let padding_right = " ".repeat(content_width.saturating_sub(used_width));
+                lines.push(Line::from(vec![
+                    Span::styled(" ".to_string(), style),
+                    Span::styled(prefix.to_string(), prefix_style),
+                    Span::styled(display_name, style),
+                    Span::styled(padding_right, style),
+                ]));
+            } else {
+                lines.push(Line::from(vec![
+                    Span::raw(" "),
+                    Span::styled(prefix.to_string(), prefix_style),
+                    Span::styled(display_name, style),
+                ]));
+            }
+
+            if let Some(ref folder) = suggestion.folder_hint {
+                let hint_style = if is_selected {
+                    Style::default()
+                        .fg(theme.muted)
+                        .bg(theme.primary)
+                } else {
+                    Style::default().fg(theme.muted)
+                };
+                let hint_text = if folder.chars().count() > max_name_width.saturating_sub(2) {
+                    let truncated: String = folder.chars().take(max_name_width.saturating_sub(3)).collect();
+                    format!("  {}…", truncated)
+                } else {
+                    format!("  {}", folder)
+                };
+                if is_selected {
+                    let content_width = (popup_width as usize).saturating_sub(2);
+                    let padding_right = " ".repeat(content_width.saturating_sub(hint_text.chars().count()));
+                    lines.push(Line::from(vec![
+                        Span::styled(hint_text, hint_style),
+                        Span::styled(padding_right, Style::default().bg(theme.primary)),
+                    ]));
+                } else {
+                    lines.push(Line::from(Span::styled(hint_text, hint_style)));
+                }
+            }
+        }
+        lines
+    };
+
+    let footer = footer.unwrap_or_else(|| {
+        if suggestions.is_empty() {
+            " No matches ".to_string()
+        } else {
+            format!(" {}/{} ", selected_index + 1, suggestions.len())
+        }
+    });
+
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .title(title)
+            .title_bottom(Line::from(footer).right_aligned())
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.info))
+            .style(Style::default().bg(theme.background_secondary)),
+    );
+
+    f.render_widget(widget, popup_area);
+}
@@ -1,6 +1,6 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph, Wrap},
     Frame,
@@ -8,10 +8,16 @@ use ratatui::{
 use ratatui_image::StatefulImage;
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use crate::app::{App, ContentItem, DialogState, Focus, ImageState, Mode};
-use crate::config::Theme;
+use crate::app::{fit_image, parse_list_marker, reference_link_at, render_fingerprint, App, CalloutKind, ContentItem, DialogState, Focus, ImageKey, ImageLoad, ImageSize, Mode, WikiLink, DEFINITION_INDENT};
+use crate::config::{HeadingStyle, ImageAlign, Theme};
+use crate::editor::strikethrough_at;
+use super::dialogs::input_spans;
+use super::inline_html;
+use super::source_view::render_source_view;
 
 const INLINE_THUMBNAIL_HEIGHT: u16 = 4;
+/// Cell size in pixels assumed when the terminal can't draw images
+const FALLBACK_FONT_SIZE: (u16, u16) = (8, 16);
 
 fn is_inside_inline_code(text: &str, position: usize) -> bool {
     let before = &text[..position];
@@ -114,9 +120,25 @@ pub fn render_content(f: &mut Frame, app: &mut App, area: Rect) {
         highlights
     };
 
+    let images: Vec<(usize, String, ImageSize)> = app
+        .content_items
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, item)| match item {
+            ContentItem::Image { path, size } => Some((idx, path.clone(), *size)),
+            _ => None,
+        })
+        .collect();
+    let image_pixels: Vec<_> = images
+        .into_iter()
+        .map(|(idx, path, size)| (idx, size, app.image_dimensions(&path)))
+        .collect();
+
     let is_focused = app.focus == Focus::Content && app.mode == Mode::Normal;
     // Skip rendering images when dialog is active to prevent terminal graphics artifacts
     let skip_images = app.dialog != DialogState::None || app.show_welcome;
+    // With previews off, images are only named on their placeholder line
+    let show_images = app.shows_inline_images();
     let theme = &app.theme;
 
     let border_style = if app.floating_cursor_mode {
@@ -127,7 +149,13 @@ pub fn render_content(f: &mut Frame, app: &mut App, area: Rect) {
         Style::default().fg(theme.border)
     };
 
-    let floating_indicator = if app.floating_cursor_mode { " [FLOAT] " } else { "" };
+    let floating_indicator = if app.floating_cursor_mode {
+        " [FLOAT] "
+    } else if app.source_view.is_some() {
+        " [SOURCE] "
+    } else {
+        ""
+    };
     let title = app
         .current_note()
         .map(|n| format!(" {}{} ", n.title, floating_indicator))
@@ -166,12 +194,48 @@ pub fn render_content(f: &mut Frame, app: &mut App, area: Rect) {
         inner
     };
     app.editor_area = if app.zen_mode { inner_area } else { area };
+    let inner_area = super::layout::reading_area(inner_area, app.config.reading.max_width, app.config.reading.align);
+
+    let inner_area = if app.folder_view_path().is_none() && (app.title_edit.is_some() || app.config.display.title_header) {
+        render_title_header(f, app, inner_area)
+    } else {
+        inner_area
+    };
+
+    if app.source_view.is_some() && app.folder_view_path().is_none() {
+        render_source_view(f, app, inner_area);
+        return;
+    }
 
     if app.content_items.is_empty() {
         return;
     }
 
     let cursor = app.content_cursor;
+    let heading_style = app.config.display.heading_style;
+    let heading_levels: Vec<Option<usize>> = app
+        .content_items
+        .iter()
+        .map(|item| match item {
+            ContentItem::TextLine(line) => heading_level_of(line),
+            _ => None,
+        })
+        .collect();
+    let item_indents = section_indents(&heading_levels, heading_style);
+    let numbers = if heading_style == HeadingStyle::Document && app.config.display.number_headings {
+        heading_numbers(&heading_levels)
+    } else {
+        vec![None; heading_levels.len()]
+    };
+    let heading_decors: Vec<HeadingDecor> = heading_levels
+        .iter()
+        .zip(numbers)
+        .map(|(level, number)| match level {
+            Some(level) => heading_decor(heading_style, *level, number),
+            None => HeadingDecor::default(),
+        })
+        .collect();
+
     let available_width = inner_area.width.saturating_sub(4) as usize;
     let max_item_height = inner_area.height.max(1);
 
@@ -213,44 +277,111 @@ pub fn render_content(f: &mut Frame, app: &mut App, area: Rect) {
 
         lines.min(max_item_height)
     };
+    // A text line's height, counting each `<br>` row when inline HTML is rendered
+    let render_inline_html = app.config.display.render_inline_html;
+    // The tab width nested lists are measured in, with `reading.indent_guides`
+    let indent_guides = app.config.reading.indent_guides.then_some(app.config.editor.tab_width as usize);
+    let calc_text_line_height = |line: &str, prefix_len: usize| -> u16 {
+        if render_inline_html && line.contains('<') {
+            inline_html::plain_rows(line)
+                .iter()
+                .map(|row| calc_wrapped_height(row, prefix_len))
+                .sum()
+        } else {
+            calc_wrapped_height(line, prefix_len)
+        }
+    };
 
+    // Columns and rows inside the border of each image on a line of its own,
+    // from the image's own size so scrolling counts the rows it really takes
+    let font_size = app.picker.as_ref().map_or(FALLBACK_FONT_SIZE, |picker| picker.font_size());
+    let mut image_boxes: std::collections::HashMap<usize, (u16, u16)> = std::collections::HashMap::new();
+    for (idx, size, pixels) in image_pixels {
+        let item_width = inner_area.width.saturating_sub(item_indents.get(idx).copied().unwrap_or(0));
+        let cells = fit_image(
+            pixels,
+            font_size,
+            size,
+            app.config.images.max_height_rows,
+            item_width.saturating_sub(2),
+            inner_area.height.saturating_sub(2),
+        );
+        image_boxes.insert(idx, cells);
+    }
+
+    // Everything an item is drawn from besides the theme and config, for the
+    // render cache. The items themselves stand behind the content generation,
+    // and only items that are measured or drawn get a fingerprint.
+    let frame_fingerprint = render_fingerprint(&[&app.content_generation, &show_images, &render_inline_html, &indent_guides]);
     let details_states = &app.details_open_states;
-    let get_item_height = |idx: usize, item: &ContentItem| -> u16 {
+    let fold_states = &app.heading_fold_states;
+    let content_items = &app.content_items;
+    let fingerprint = |idx: usize| {
+        let opened = match &content_items[idx] {
+            ContentItem::Details { id, .. } | ContentItem::Callout { id, .. } => details_states.get(id),
+            _ => None,
+        };
+        render_fingerprint(&[
+            &frame_fingerprint,
+            &heading_decors[idx],
+            &item_indents[idx],
+            &opened,
+            &fold_states.get(&idx),
+            &code_block_highlights.contains_key(&idx),
+        ])
+    };
+    let measure_item = |idx: usize, item: &ContentItem| -> u16 {
+        let section_indent = item_indents.get(idx).copied().unwrap_or(0);
+        let item_width = inner_area.width.saturating_sub(section_indent);
         match item {
             ContentItem::TextLine(line) => {
-                let base_height = calc_wrapped_height(line, 4);
+                let decor = &heading_decors[idx];
+                let decor_width = decor.number.as_ref().map_or(0, |n| n.len() + 1)
+                    + if decor.bar { 2 } else { 0 };
+                let prefix_width = parse_list_marker(line).map_or(4, |m| (2 + m.content_column()).max(4));
+                let base_height = calc_text_line_height(line, prefix_width + section_indent as usize + decor_width);
                 let inline_images = extract_inline_images(line);
-                if inline_images.is_empty() {
+                if inline_images.is_empty() || !show_images {
                     base_height
                 } else {
                     base_height + (inline_images.len() as u16 * INLINE_THUMBNAIL_HEIGHT)
                 }
             }
-            ContentItem::Image(_) => 8u16,
+            // The placeholder line, then the image in its border
+            ContentItem::Image { .. } if !show_images => 1,
+            ContentItem::Image { .. } => image_boxes.get(&idx).map_or(8, |&(_, rows)| rows + 2) + 1,
             ContentItem::CodeLine(line) => {
-                code_line_height(line, code_block_highlights.get(&idx), inner_area.width, theme)
+                code_line_height(line, code_block_highlights.get(&idx), item_width, theme)
                     .min(max_item_height)
             }
             ContentItem::CodeFence(_) => 1u16,
             ContentItem::TaskItem { text, indent, .. } => {
-                let base_height = calc_wrapped_height(text, 6 + *indent);
+                let base_height = calc_wrapped_height(text, 6 + *indent + section_indent as usize);
                 let inline_images = extract_inline_images(text);
-                if inline_images.is_empty() {
+                if inline_images.is_empty() || !show_images {
                     base_height
                 } else {
                     base_height + (inline_images.len() as u16 * INLINE_THUMBNAIL_HEIGHT)
                 }
             }
+            ContentItem::ListContinuation { text, indent } => {
+                calc_wrapped_height(text, 2 + *indent + section_indent as usize)
+            }
+            ContentItem::DefinitionTerm(text) => calc_wrapped_height(text, 2 + section_indent as usize),
+            ContentItem::Definition(text) => {
+                calc_wrapped_height(text, 2 + DEFINITION_INDENT + section_indent as usize)
+            }
             ContentItem::TableRow { cells, is_separator, column_widths, .. } => {
                 if *is_separator {
                     1u16
                 } else {
                     // Budget must match render_table_row exactly. render uses area.width
-                    // (= inner_area.width after chunk split), not `available_width`, which
-                    // carries a 4-char list-prefix margin that tables don't need.
+                    // (= item_width after chunk split and section indent), not
+                    // `available_width`, which carries a 4-char list-prefix margin that
+                    // tables don't need.
                     let n = column_widths.len();
                     let overhead = 3 + 3 * n;
-                    let budget = (inner_area.width as usize).saturating_sub(overhead);
+                    let budget = (item_width as usize).saturating_sub(overhead);
                     let capped = cap_column_widths(column_widths, budget);
                     let text_color = theme.content.text;
                     let row_lines = cells.iter().enumerate().map(|(i, cell)| {
@@ -260,7 +391,7 @@ pub fn render_content(f: &mut Frame, app: &mut App, area: Rect) {
                         // wraps independently and stacks vertically within the cell.
                         let mut total: usize = 0;
                         for logical in split_cell_by_br(&expanded) {
-                            let spans = parse_inline_formatting::<fn(&str) -> bool>(logical, theme, None, None);
+                            let spans = parse_inline_formatting::<fn(&str) -> bool>(logical, theme, Modifier::CROSSED_OUT, None, None);
                             total += distribute_spans_across_lines(spans, w, text_color).len();
                         }
                         total.max(1)
@@ -276,11 +407,60 @@ pub fn render_content(f: &mut Frame, app: &mut App, area: Rect) {
                     1u16
                 }
             }
+            ContentItem::Callout { lines, fold, id, .. } => {
+                let is_open = fold.is_none_or(|open| details_states.get(id).copied().unwrap_or(open));
+                if is_open {
+                    let prefix_width = 4 + section_indent as usize;
+                    1 + lines.iter().map(|line| calc_text_line_height(line, prefix_width)).sum::<u16>()
+                } else {
+                    1u16
+                }
+            }
             ContentItem::FrontmatterLine { .. } => 1u16,
             ContentItem::FrontmatterDelimiter { .. } => 1u16,
             ContentItem::TagBadges { .. } => 2u16, // 1 line padding + 1 line for tags
+            ContentItem::FolderEntry { .. } => 1u16,
         }
     };
+    let render_cache = &app.render_cache;
+    // Fingerprints of the items measured this frame, which the items drawn
+    // are among
+    let fingerprints = std::cell::RefCell::new(std::collections::HashMap::new());
+    let get_item_height = |idx: usize, item: &ContentItem| -> u16 {
+        let fingerprint = *fingerprints.borrow_mut().entry(idx).or_insert_with(|| fingerprint(idx));
+        // An image's box depends on the image, which may load after it's measured
+        if matches!(item, ContentItem::Image { .. }) {
+            return measure_item(idx, item);
+        }
+        let key = render_cache.key(fingerprint, inner_area.width, inner_area.height);
+        render_cache.height(idx, key, || measure_item(idx, item))
+    };
+
+    // In floating mode the scroll is kept between frames; when the width
+    // changes and the items rewrap, move it so the cursor keeps its screen row
+    if app.floating_cursor_mode && app.content_area.width > 0 && app.content_area.width != inner_area.width {
+        let cursor_row = app
+            .content_item_rects
+            .iter()
+            .find(|(idx, _)| *idx == cursor)
+            .map(|(_, rect)| rect.y.saturating_sub(app.content_area.y));
+        if let Some(row) = cursor_row {
+            let mut above = 0u16;
+            let mut top = cursor;
+            for i in (0..cursor).rev() {
+                if !app.is_content_item_visible(i) {
+                    continue;
+                }
+                let item_height = get_item_height(i, &app.content_items[i]);
+                if above + item_height > row {
+                    break;
+                }
+                above += item_height;
+                top = i;
+            }
+            app.content_scroll_offset = top + 1;
+        }
+    }
 
     let scroll_offset = if app.floating_cursor_mode {
         // FLOATING MODE: cursor moves freely, view only scrolls when cursor goes out of bounds
@@ -382,6 +562,13 @@ pub fn render_content(f: &mut Frame, app: &mut App, area: Rect) {
         }
     };
 
+    // A jump sliding into place draws from as far as it's got
+    let slide = app.smooth_scroll.and_then(|s| s.offset(scroll_offset, inner_area.height as usize, app.clock.now()));
+    if slide.is_none() {
+        app.smooth_scroll = None;
+    }
+    let scroll_offset = slide.unwrap_or(scroll_offset);
+
     let mut constraints: Vec<Constraint> = Vec::new();
     let mut visible_indices: Vec<usize> = Vec::new();
     let mut total_height = 0u16;
@@ -406,11 +593,20 @@ pub fn render_content(f: &mut Frame, app: &mut App, area: Rect) {
         return;
     }
 
-    let chunks = Layout::default()
+    let chunks: Vec<Rect> = Layout::default()
         .direction(Direction::Vertical)
         .constraints(constraints)
-        .split(inner_area);
+        .split(inner_area)
+        .iter()
+        .zip(&visible_indices)
+        .map(|(chunk, &item_idx)| {
+            // Section indent; click mapping reads x from these rects too
+            let indent = item_indents[item_idx].min(chunk.width);
+            Rect { x: chunk.x + indent, width: chunk.width - indent, ..*chunk }
+        })
+        .collect();
 
+    let fingerprints = fingerprints.into_inner();
     app.content_area = inner_area;
     app.content_item_rects.clear();
     for (chunk_idx, &item_idx) in visible_indices.iter().enumerate() {
@@ -426,8 +622,36 @@ pub fn render_content(f: &mut Frame, app: &mut App, area: Rect) {
         let is_cursor_line = item_idx == cursor && is_focused;
         let is_hovered = app.mouse_hover_item == Some(item_idx);
 
+        // Items drawn the same way in an earlier frame are copied from the
+        // cache. The cursor and hovered items show their links, and images
+        // are drawn by the terminal, so those are drawn afresh every time.
+        let area = chunks[chunk_idx];
+        let cache_key = fingerprints
+            .get(&item_idx)
+            .filter(|_| !is_cursor_line && !is_hovered && !draws_images(&app.content_items[item_idx], show_images))
+            .map(|fingerprint| {
+                // Whether its wiki links go anywhere can change with no change to the item
+                let links = render_fingerprint(&[fingerprint, &app.item_wiki_links_at(item_idx)]);
+                app.render_cache.key(links, area.width, area.height)
+            });
+        if let Some(key) = cache_key {
+            if app.render_cache.restore(item_idx, key, f.buffer_mut(), area) {
+                continue;
+            }
+        }
+
         // Clone the item data to avoid borrow conflicts
         let item_clone = app.content_items[item_idx].clone();
+        let line_ctx = LineRenderCtx {
+            theme: &app.theme,
+            strikethrough: app.config.display.strikethrough.modifier(),
+            render_inline_html,
+            indent_guides,
+            area: chunks[chunk_idx],
+            is_cursor: is_cursor_line,
+            has_link: false,
+            selected_link: 0,
+        };
 
         match item_clone {
             ContentItem::TextLine(ref line) => {
@@ -442,18 +666,25 @@ pub fn render_content(f: &mut Frame, app: &mut App, area: Rect) {
                 } else {
                     None
                 };
-                render_content_line(f, &app.theme, line, chunks[chunk_idx], is_cursor_line, has_link, selected_link, Some(wiki_validator), fold_state);
-                if !skip_images {
+                let ctx = LineRenderCtx { has_link, selected_link, ..line_ctx };
+                render_content_line(f, &ctx, line, Some(wiki_validator), fold_state, &heading_decors[item_idx]);
+                if !skip_images && show_images {
                     let inline_images = extract_inline_images(line);
                     if !inline_images.is_empty() {
-                        let text_height = calc_wrapped_height(line, 4);
+                        let text_height = calc_text_line_height(line, 4);
                         render_inline_thumbnails(f, app, &inline_images, chunks[chunk_idx], text_height);
                     }
                 }
             }
-            ContentItem::Image(path) => {
-                if !skip_images {
-                    render_inline_image_with_cursor(f, app, &path, chunks[chunk_idx], is_cursor_line, is_hovered);
+            ContentItem::Image { ref path, .. } => {
+                let area = chunks[chunk_idx];
+                let placeholder = Rect { height: area.height.min(1), ..area };
+                render_image_placeholder(f, app, path, placeholder, is_cursor_line, is_hovered, show_images);
+                if !skip_images && show_images {
+                    let cols = image_boxes.get(&item_idx).map_or(u16::MAX, |&(cols, _)| cols);
+                    let image_area = Rect { y: area.y + 1, height: area.height.saturating_sub(1), ..area };
+                    let line = app.content_item_source_lines.get(item_idx).copied().unwrap_or(item_idx);
+                    render_inline_image_with_cursor(f, app, (line, path.clone()), image_area, cols, is_cursor_line, is_hovered);
                 }
             }
             ContentItem::CodeLine(line) => {
@@ -467,8 +698,9 @@ pub fn render_content(f: &mut Frame, app: &mut App, area: Rect) {
                 let selected_link = if is_cursor_line { app.selected_link_index } else { 0 };
                 let has_links = !app.item_wiki_links_at(item_idx).is_empty() || !app.item_links_at(item_idx).is_empty();
                 let wiki_validator = |target: &str| app.wiki_link_exists(target);
-                render_task_item(f, &app.theme, text, checked, indent, chunks[chunk_idx], is_cursor_line, selected_link, has_links, Some(wiki_validator));
-                if !skip_images {
+                let ctx = LineRenderCtx { has_link: has_links, selected_link, ..line_ctx };
+                render_task_item(f, &ctx, text, checked, indent, Some(wiki_validator));
+                if !skip_images && show_images {
                     let inline_images = extract_inline_images(text);
                     if !inline_images.is_empty() {
                         let text_height = calc_wrapped_height(text, 6 + indent);
@@ -476,16 +708,25 @@ pub fn render_content(f: &mut Frame, app: &mut App, area: Rect) {
                     }
                 }
             }
+            ContentItem::ListContinuation { .. } | ContentItem::DefinitionTerm(_) | ContentItem::Definition(_) => {
+                render_item_text(f, app, item_idx, chunks[chunk_idx], is_cursor_line);
+            }
             ContentItem::TableRow { cells, is_separator, is_header, column_widths, alignments } => {
                 let has_link = !is_separator
                     && (is_cursor_line || is_hovered)
                     && !app.item_links_at(item_idx).is_empty();
-                render_table_row(f, &app.theme, &cells, is_separator, is_header, &column_widths, &alignments, chunks[chunk_idx], is_cursor_line, has_link);
+                let ctx = LineRenderCtx { has_link, ..line_ctx };
+                render_table_row(f, &ctx, &cells, is_separator, is_header, &column_widths, &alignments);
             }
             ContentItem::Details { summary, content_lines, id } => {
                 let is_open = app.details_open_states.get(&id).copied().unwrap_or(false);
                 render_details(f, &app.theme, &summary, &content_lines, is_open, chunks[chunk_idx], is_cursor_line);
             }
+            ContentItem::Callout { kind, ref title, ref lines, fold, .. } => {
+                let callout = Callout { kind, title, lines, foldable: fold.is_some(), is_open: app.is_details_open(item_idx) };
+                let strikethrough = app.config.display.strikethrough.modifier();
+                render_callout(f, &app.theme, strikethrough, &callout, chunks[chunk_idx], is_cursor_line);
+            }
             ContentItem::FrontmatterDelimiter { .. } => {
                 render_frontmatter_delimiter(f, &app.theme, chunks[chunk_idx], is_cursor_line);
             }
@@ -495,12 +736,111 @@ pub fn render_content(f: &mut Frame, app: &mut App, area: Rect) {
             ContentItem::TagBadges { ref tags, ref date } => {
                 render_tag_badges_inline(f, &app.theme, tags, date.as_deref(), chunks[chunk_idx], is_cursor_line);
             }
+            ContentItem::FolderEntry { ref name, ref detail, is_folder, .. } => {
+                render_folder_entry(f, &app.theme, name, detail, is_folder, chunks[chunk_idx], is_cursor_line);
+            }
+        }
+        if let Some(key) = cache_key {
+            app.render_cache.store(item_idx, key, f.buffer_mut(), area);
         }
     }
 
     if app.buffer_search.active && !app.buffer_search.matches.is_empty() {
         apply_content_search_highlights(f, app, &visible_indices, &chunks);
     }
+
+    apply_reload_highlight(f, app, &visible_indices, &chunks);
+}
+
+/// Whether drawing `item` puts an image on screen, which the terminal draws
+/// outside the cells the render cache can keep.
+fn draws_images(item: &ContentItem, show_images: bool) -> bool {
+    match item {
+        ContentItem::Image { .. } => true,
+        ContentItem::TextLine(text) | ContentItem::TaskItem { text, .. } => {
+            show_images && !extract_inline_images(text).is_empty()
+        }
+        _ => false,
+    }
+}
+
+/// Tint the lines that changed when the note was reloaded from disk, fading
+/// back to the normal background.
+/// The note's title over its content, or the title being typed after `r`
+/// with any rename error under it. Returns the area left for the content.
+fn render_title_header(f: &mut Frame, app: &App, area: Rect) -> Rect {
+    const HEIGHT: u16 = 2;
+    let Some(note) = app.current_note() else {
+        return area;
+    };
+    if area.height <= HEIGHT {
+        return area;
+    }
+    let theme = &app.theme;
+    let title_style = Style::default().fg(theme.primary).add_modifier(Modifier::BOLD);
+
+    let lines = match &app.title_edit {
+        Some(edit) => {
+            let title = Line::from(input_spans(
+                &edit.input,
+                edit.input.cursor(),
+                title_style,
+                Span::styled("█", Style::default().fg(theme.cursor)),
+            ));
+            let below = match &edit.error {
+                Some(error) => Span::styled(error.clone(), Style::default().fg(theme.error)),
+                None => Span::styled("Enter rename · Esc cancel", Style::default().fg(theme.muted)),
+            };
+            vec![title, Line::from(below)]
+        }
+        None => vec![Line::from(Span::styled(note.title.clone(), title_style)), Line::from("")],
+    };
+    let header = Rect { height: HEIGHT, ..area };
+    f.render_widget(Paragraph::new(lines), header);
+
+    Rect {
+        y: area.y + HEIGHT,
+        height: area.height - HEIGHT,
+        ..area
+    }
+}
+
+fn apply_reload_highlight(f: &mut Frame, app: &App, visible_indices: &[usize], chunks: &[Rect]) {
+    let Some(highlight) = app.reload_highlight.as_ref().filter(|h| h.note == app.selected_note) else {
+        return;
+    };
+    // Terminals without RGB theme colors get the selection color for the
+    // first half of the fade instead
+    let faded = highlight.faded(app.clock.now());
+    let tint = match mix_colors(app.theme.warning, app.theme.background, 0.3 * (1.0 - faded)) {
+        Some(color) => color,
+        None if faded < 0.5 => app.theme.selection,
+        None => return,
+    };
+
+    for (chunk_idx, &item_idx) in visible_indices.iter().enumerate() {
+        let Some(&area) = chunks.get(chunk_idx) else {
+            break;
+        };
+        let changed = app
+            .content_item_source_lines
+            .get(item_idx)
+            .is_some_and(|line| highlight.lines.contains(line));
+        if changed {
+            f.buffer_mut().set_style(area, Style::default().bg(tint));
+        }
+    }
+}
+
+/// `a` mixed into `b` by `amount` (0.0 to 1.0), if both are RGB colors.
+fn mix_colors(a: Color, b: Color, amount: f32) -> Option<Color> {
+    match (a, b) {
+        (Color::Rgb(ar, ag, ab), Color::Rgb(br, bg, bb)) => {
+            let mix = |a: u8, b: u8| (b as f32 + (a as f32 - b as f32) * amount).round() as u8;
+            Some(Color::Rgb(mix(ar, br), mix(ag, bg), mix(ab, bb)))
+        }
+        _ => None,
+    }
 }
 
 /// Visible width of a table cell after inline markdown shrinks
@@ -564,7 +904,7 @@ pub(crate) fn cap_column_widths(natural: &[usize], available: usize) -> Vec<usiz
 /// Use this downstream of `parse_inline_formatting` so the parser stays the
 /// single source of truth for what counts as a markdown construct:
 /// ```ignore
-/// let spans = parse_inline_formatting(cell, theme, None, None::<fn(&str) -> bool>);
+/// let spans = parse_inline_formatting(cell, theme, Modifier::CROSSED_OUT, None, None::<fn(&str) -> bool>);
 /// let lines = distribute_spans_across_lines(spans, width, theme.content.text);
 /// ```
 ///
@@ -994,52 +1334,14 @@ fn find_markdown_link(chars: &[char], start: usize) -> Option<(usize, usize)> {
     None
 }
 
-/// Calculate the adjusted column for a table cell
-/// Raw format: "| cell1 | cell2 |"
-/// Rendered:   "▶ │ cell1 │ cell2 │" with cells padded to column widths
-fn calc_table_adjusted_col(raw_col: usize, cells: &[String], column_widths: &[usize], alignments: &[crate::app::Alignment]) -> usize {
-    use crate::app::Alignment;
-    let mut rendered_pos = 3;
-    let mut raw_pos = 0;
-
-    for (cell_idx, cell) in cells.iter().enumerate() {
-        let col_width = column_widths.get(cell_idx).copied().unwrap_or(3);
-        if raw_pos == 0 {
-            raw_pos = 1;
-        }
-
-        let raw_cell_start = raw_pos;
-
-        let cell_char_len = cell.chars().count();
-        let cell_display_width = cell.width();
-        let raw_cell_end = raw_cell_start + cell_char_len + 3; // " content |"
-
-        if raw_col >= raw_cell_start && raw_col < raw_cell_end {
-            let char_offset_in_raw_cell = raw_col.saturating_sub(raw_cell_start + 1); // +1 for leading space
-            // Convert character offset to display width
-            let display_offset: usize = cell.chars()
-                .take(char_offset_in_raw_cell.min(cell_char_len))
-                .map(|c| c.width().unwrap_or(1))
-                .sum();
-            let pad = col_width.saturating_sub(cell_display_width);
-            let alignment = alignments.get(cell_idx).copied().unwrap_or(Alignment::Left);
-            let content_padding = match alignment {
-                Alignment::Left => 0,
-                Alignment::Right => pad,
-                Alignment::Center => pad / 2,
-            };
-            let rendered_content_start = rendered_pos + 1 + content_padding; // +1 for leading space
-
-            return rendered_content_start + display_offset;
-        }
-
-        raw_pos = raw_cell_end;
-        rendered_pos += col_width + 2 + 1;
-    }
-
-    3 + raw_col
-}
-
+/// Paint buffer-search matches onto the already-rendered content cells.
+///
+/// Matches are found in the raw note lines, but headings, list markers, table
+/// padding and inline markup all move text around once rendered, so raw columns
+/// can't be mapped to screen columns reliably. Instead the query is located again
+/// in the rendered cells of each visible item whose source line has matches. The
+/// n-th rendered hit on a source line stands in for the n-th raw match on that
+/// line when picking the current match.
 fn apply_content_search_highlights(
     f: &mut Frame,
     app: &App,
@@ -1047,133 +1349,72 @@ fn apply_content_search_highlights(
     chunks: &[Rect],
 ) {
     let theme = &app.theme;
-    let current_match_idx = app.buffer_search.current_match_index;
-    let lines: Vec<&str> = if app.mode == Mode::Edit {
-        app.editor.lines()
-    } else if let Some(note) = app.notes.get(app.selected_note) {
-        note.content.lines().collect()
-    } else {
+    let search = &app.buffer_search;
+    let case_sensitive = search.case_sensitive;
+    let query: Vec<char> = search
+        .query
+        .chars()
+        .map(|c| fold_search_char(c, case_sensitive))
+        .collect();
+    if query.is_empty() {
         return;
-    };
+    }
+
+    // (source row, ordinal of the current match among that row's matches)
+    let current = search.current_match().map(|cur| {
+        let ordinal = search.matches[..search.current_match_index]
+            .iter()
+            .filter(|m| m.row == cur.row)
+            .count();
+        (cur.row, ordinal)
+    });
+    let rows_with_matches: std::collections::HashSet<usize> =
+        search.matches.iter().map(|m| m.row).collect();
+    let mut seen_per_row: std::collections::HashMap<usize, usize> = std::collections::HashMap::new();
 
     for (chunk_idx, &item_idx) in visible_indices.iter().enumerate() {
-        if chunk_idx >= chunks.len() {
+        let Some(&area) = chunks.get(chunk_idx) else {
             break;
+        };
+        let Some(&source_line) = app.content_item_source_lines.get(item_idx) else {
+            continue;
+        };
+        if !rows_with_matches.contains(&source_line) {
+            continue;
         }
-
-        let source_line = app.content_item_source_lines.get(item_idx).copied().unwrap_or(usize::MAX);
-        if source_line == usize::MAX {
+        if let Some(ContentItem::TableRow { is_separator: true, .. }) = app.content_items.get(item_idx) {
             continue;
         }
 
-        let raw_line = lines.get(source_line).copied().unwrap_or("");
+        for y in area.y..area.y + area.height {
+            // Visible characters on this row with their x and display width;
+            // wide characters leave an empty continuation cell that is skipped.
+            let cells: Vec<(u16, u16, char)> = (area.x..area.x + area.width)
+                .filter_map(|x| {
+                    let symbol = f.buffer_mut().cell((x, y))?.symbol();
+                    let ch = symbol.chars().next()?;
+                    let width = (symbol.width() as u16).max(1);
+                    Some((x, width, fold_search_char(ch, case_sensitive)))
+                })
+                .collect();
+            let chars: Vec<char> = cells.iter().map(|&(_, _, c)| c).collect();
+
+            for start in find_char_matches(&chars, &query) {
+                let seen = seen_per_row.entry(source_line).or_insert(0);
+                let is_current = current == Some((source_line, *seen));
+                *seen += 1;
 
-        for (match_idx, m) in app.buffer_search.matches.iter().enumerate() {
-            if m.row == source_line {
-                let area = chunks[chunk_idx];
-                let is_current = match_idx == current_match_idx;
                 let highlight_color = if is_current {
                     theme.search.match_current
                 } else {
                     theme.search.match_highlight
                 };
-
-                // Calculate the rendered column position based on content type
-                // Use display width for CJK character support
-                let adjusted_col = match &app.content_items.get(item_idx) {
-                    Some(ContentItem::TableRow { cells, column_widths, alignments, is_separator, .. }) => {
-                        if *is_separator {
-                            continue;
-                        }
-                        calc_table_adjusted_col(m.start_col, cells, column_widths, alignments)
-                    }
-                    Some(ContentItem::TextLine(line)) => {
-                        let line = normalize_whitespace(line);
-                        let (rendered_prefix_len, raw_prefix_len, content_text) =
-                            if line.starts_with("###### ") {
-                                (2, 7, line[7..].to_string())
-                            } else if line.starts_with("##### ") {
-                                (2, 6, line[6..].to_string())
-                            } else if line.starts_with("#### ") {
-                                (4, 5, line[5..].to_string())
-                            } else if line.starts_with("### ") {
-                                (4, 4, line[4..].to_string())
-                            } else if line.starts_with("## ") {
-                                (4, 3, line[3..].to_string())
-                            } else if line.starts_with("# ") {
-                                (4, 2, line[2..].to_string())
-                            } else if line.starts_with("- ") {
-                                (4, 2, line[2..].to_string())
-                            } else if line.starts_with("* ") {
-                                (4, 2, line[2..].to_string())
-                            } else if line.starts_with("> ") {
-                                (4, 2, line[2..].to_string())
-                            } else {
-                                (2, 0, line.to_string())
-                            };
-
-                        if m.start_col < raw_prefix_len {
-                            continue;
-                        }
-                        let content_start_col = m.start_col - raw_prefix_len;
-                        let formatting_shrinkage = if !content_text.is_empty() {
-                            calc_formatting_shrinkage(&content_text, content_start_col)
-                        } else {
-                            0
-                        };
-                        // Calculate display width of content before the match
-                        let display_col = content_text.chars()
-                            .take(content_start_col.saturating_sub(formatting_shrinkage))
-                            .map(|c| c.width().unwrap_or(1))
-                            .sum::<usize>();
-                        rendered_prefix_len + display_col
-                    }
-                    Some(ContentItem::CodeLine(code)) => {
-                        // Calculate display width of code before the match
-                        let display_col: usize = code.chars()
-                            .take(m.start_col)
-                            .map(|c| c.width().unwrap_or(1))
-                            .sum();
-                        4 + display_col
-                    }
-                    Some(ContentItem::TaskItem { text, indent, .. }) => {
-                        let prefix = 6 + *indent;
-                        if m.start_col < prefix {
-                            continue;
-                        }
-                        let content_start_col = m.start_col - prefix;
-                        let formatting_shrinkage = calc_formatting_shrinkage(text, content_start_col);
-                        let display_col: usize = text.chars()
-                            .take(content_start_col.saturating_sub(formatting_shrinkage))
-                            .map(|c| c.width().unwrap_or(1))
-                            .sum();
-                        prefix + display_col
-                    }
-                    _ => {
-                        // Calculate display width of raw line before the match
-                        let display_col: usize = raw_line.chars()
-                            .take(m.start_col)
-                            .map(|c| c.width().unwrap_or(1))
-                            .sum();
-                        2 + display_col
-                    }
-                };
-
-                let start_x = area.x + adjusted_col as u16;
-                // Calculate display width of matched text
-                let match_display_width: usize = raw_line.chars()
-                    .skip(m.start_col)
-                    .take(m.end_col - m.start_col)
-                    .map(|c| c.width().unwrap_or(1))
-                    .sum();
-
-                for offset in 0..match_display_width {
-                    let x = start_x + offset as u16;
-                    if x < area.x + area.width {
-                        if let Some(cell) = f.buffer_mut().cell_mut((x, area.y)) {
-                            cell.set_bg(highlight_color);
-                            cell.set_fg(ratatui::style::Color::Black);
-                        }
+                let start_x = cells[start].0;
+                let (last_x, last_width, _) = cells[start + query.len() - 1];
+                for x in start_x..last_x + last_width {
+                    if let Some(cell) = f.buffer_mut().cell_mut((x, y)) {
+                        cell.set_bg(highlight_color);
+                        cell.set_fg(ratatui::style::Color::Black);
                     }
                 }
             }
@@ -1181,9 +1422,29 @@ fn apply_content_search_highlights(
     }
 }
 
+fn fold_search_char(c: char, case_sensitive: bool) -> char {
+    if case_sensitive {
+        c
+    } else {
+        c.to_lowercase().next().unwrap_or(c)
+    }
+}
+
+/// Start indices of every (possibly overlapping) occurrence of `query` in `chars`,
+/// matching how the buffer search itself counts hits.
+fn find_char_matches(chars: &[char], query: &[char]) -> Vec<usize> {
+    if query.is_empty() || query.len() > chars.len() {
+        return Vec::new();
+    }
+    (0..=chars.len() - query.len())
+        .filter(|&start| chars[start..start + query.len()] == *query)
+        .collect()
+}
+
 fn parse_inline_formatting<'a, F>(
     text: &'a str,
     theme: &Theme,
+    strikethrough: Modifier,
     selected_link: Option<usize>,
     wiki_link_validator: Option<F>,
 ) -> Vec<Span<'a>>
@@ -1252,7 +1513,7 @@ where
                 if let Some(end) = bold_end {
                     spans.push(Span::styled(
                         &text[bold_start..end],
-                        Style::default().fg(content_theme.text).add_modifier(Modifier::BOLD),
+                        Style::default().fg(content_theme.bold).add_modifier(Modifier::BOLD),
                     ));
                     current_start = end + 2;
                 } else {
@@ -1279,7 +1540,7 @@ where
                 if let Some(end) = italic_end {
                     spans.push(Span::styled(
                         &text[italic_start..end],
-                        Style::default().fg(content_theme.text).add_modifier(Modifier::ITALIC),
+                        Style::default().fg(content_theme.italic).add_modifier(Modifier::ITALIC),
                     ));
                     current_start = end + 1;
                 } else {
@@ -1312,7 +1573,7 @@ where
                 if let Some(end) = bold_end {
                     spans.push(Span::styled(
                         &text[bold_start..end],
-                        Style::default().fg(content_theme.text).add_modifier(Modifier::BOLD),
+                        Style::default().fg(content_theme.bold).add_modifier(Modifier::BOLD),
                     ));
                     current_start = end + 2;
                 } else {
@@ -1338,7 +1599,7 @@ where
                 if let Some(end) = italic_end {
                     spans.push(Span::styled(
                         &text[italic_start..end],
-                        Style::default().fg(content_theme.text).add_modifier(Modifier::ITALIC),
+                        Style::default().fg(content_theme.italic).add_modifier(Modifier::ITALIC),
                     ));
                     current_start = end + 1;
                 } else {
@@ -1349,34 +1610,19 @@ where
         }
 
         // Check for ~~strikethrough~~
-        if c == '~' {
-            if let Some(&(_, '~')) = chars.peek() {
+        if c == '~' && !text[..i].ends_with('~') {
+            let rest: Vec<char> = text[i..].chars().collect();
+            if let Some(end) = strikethrough_at(&rest, 0) {
                 if i > current_start {
                     spans.push(Span::styled(&text[current_start..i], Style::default().fg(content_theme.text)));
                 }
-                chars.next(); 
-                let strike_start = i + 2;
-                let mut strike_end = None;
-
-                while let Some((j, ch)) = chars.next() {
-                    if ch == '~' {
-                        if let Some(&(_, '~')) = chars.peek() {
-                            strike_end = Some(j);
-                            chars.next(); 
-                            break;
-                        }
-                    }
-                }
-
-                if let Some(end) = strike_end {
-                    spans.push(Span::styled(
-                        &text[strike_start..end],
-                        Style::default().fg(content_theme.text).add_modifier(Modifier::CROSSED_OUT),
-                    ));
-                    current_start = end + 2;
-                } else {
-                    current_start = i;
-                }
+                let end_byte = i + rest[..end].iter().map(|c| c.len_utf8()).sum::<usize>();
+                spans.push(Span::styled(
+                    &text[i + 2..end_byte - 2],
+                    Style::default().fg(content_theme.text).add_modifier(strikethrough),
+                ));
+                while chars.next_if(|&(j, _)| j < end_byte).is_some() {}
+                current_start = end_byte;
                 continue;
             }
         }
@@ -1487,61 +1733,60 @@ where
         // Check for [[wiki link]]
         if c == '[' {
             let remaining = &text[i..];
-            if remaining.starts_with("[[") {
-                if let Some(close_pos) = remaining[2..].find("]]") {
-                    let raw_content = &remaining[2..2 + close_pos];
-                    if !raw_content.is_empty() && !raw_content.contains('[') && !raw_content.contains(']') {
-                        if i > current_start {
-                            spans.push(Span::styled(&text[current_start..i], Style::default().fg(content_theme.text)));
-                        }
+            if let Some(link) = WikiLink::parse(remaining) {
+                if i > current_start {
+                    spans.push(Span::styled(&text[current_start..i], Style::default().fg(content_theme.text)));
+                }
 
-                        let (content, display_text) = if let Some(pipe_pos) = raw_content.find('|') {
-                            (&raw_content[..pipe_pos], Some(&raw_content[pipe_pos + 1..]))
-                        } else {
-                            (raw_content, None)
-                        };
-                        let target = if let Some(hash_pos) = content.find('#') {
-                            &content[..hash_pos]
-                        } else {
-                            content
-                        };
-                        let shown_text = display_text.unwrap_or(raw_content);
+                let is_selected = selected_link == Some(link_index);
+                let is_valid = wiki_link_validator
+                    .as_ref()
+                    .map(|f| f(&link.target))
+                    .unwrap_or(false);
 
-                        let is_selected = selected_link == Some(link_index);
-                        let is_valid = wiki_link_validator
-                            .as_ref()
-                            .map(|f| f(target))
-                            .unwrap_or(false);
+                let style = if is_selected {
+                    Style::default()
+                        .fg(theme.background)
+                        .bg(theme.warning)
+                        .add_modifier(Modifier::BOLD)
+                } else if is_valid {
+                    Style::default()
+                        .fg(content_theme.wiki_link)
+                        .add_modifier(Modifier::UNDERLINED)
+                } else {
+                    Style::default()
+                        .fg(content_theme.link_invalid)
+                        .add_modifier(Modifier::UNDERLINED)
+                };
 
-                        let style = if is_selected {
-                            Style::default()
-                                .fg(theme.background)
-                                .bg(theme.warning)
-                                .add_modifier(Modifier::BOLD)
-                        } else if is_valid {
-                            Style::default()
-                                .fg(content_theme.link)
-                                .add_modifier(Modifier::UNDERLINED)
-                        } else {
-                            Style::default()
-                                .fg(content_theme.link_invalid)
-                                .add_modifier(Modifier::UNDERLINED)
-                        };
+                spans.push(Span::styled(link.shown(), style));
+                link_index += 1;
 
-                        spans.push(Span::styled(shown_text.to_string(), style));
-                        link_index += 1;
+                // link.len is a byte count; advance the char iterator
+                // by byte position so multi-byte content doesn't over-skip.
+                let link_end = i + link.len;
+                while chars.peek().is_some_and(|&(j, _)| j < link_end) {
+                    chars.next();
+                }
+                current_start = link_end;
+                continue;
+            }
 
-                        let total_link_len = 2 + close_pos + 2; // [[target]]
-                        // total_link_len is a byte count; advance the char iterator
-                        // by byte position so multi-byte content doesn't over-skip.
-                        let link_end = i + total_link_len;
-                        while chars.peek().map_or(false, |&(j, _)| j < link_end) {
-                            chars.next();
-                        }
-                        current_start = link_end;
-                        continue;
-                    }
+            // Reference links with a definition were made inline links when
+            // the content was built, so any left have none
+            if let Some(reference) = reference_link_at(text, i) {
+                if i > current_start {
+                    spans.push(Span::styled(&text[current_start..i], Style::default().fg(content_theme.text)));
+                }
+                spans.push(Span::styled(
+                    reference.text,
+                    Style::default().fg(content_theme.link_invalid).add_modifier(Modifier::UNDERLINED),
+                ));
+                while chars.peek().is_some_and(|&(j, _)| j < reference.end) {
+                    chars.next();
                 }
+                current_start = reference.end;
+                continue;
             }
 
             if let Some(bracket_end) = remaining.find("](") {
@@ -1627,22 +1872,36 @@ fn wrap_line_for_cursor<'a>(
     }
     let mut prefix_spans: Vec<Span<'a>> = Vec::new();
     let mut content_spans: Vec<Span<'a>> = Vec::new();
-    let mut prefix_width = 0usize;
 
     for (i, span) in first_line_spans.into_iter().enumerate() {
         let span_text = span.content.to_string();
         let span_width = display_width(&span_text);
         if i == 0 {
             prefix_spans.push(span);
-            prefix_width += span_width;
         } else if i == 1 && span_width <= 3 && !span_text.chars().any(|c| c.is_alphanumeric()) {
             prefix_spans.push(span);
-            prefix_width += span_width;
         } else {
             content_spans.push(span);
         }
     }
 
+    wrap_with_hanging_indent(prefix_spans, content_spans, available_width)
+}
+
+/// Wrap `content_spans` after `prefix_spans`, indenting continuation rows by
+/// the prefix's width so wrapped text lines up under the first row's text.
+fn wrap_with_hanging_indent<'a>(
+    prefix_spans: Vec<Span<'a>>,
+    content_spans: Vec<Span<'a>>,
+    available_width: usize,
+) -> Vec<Line<'a>> {
+    if available_width == 0 {
+        let mut spans = prefix_spans;
+        spans.extend(content_spans);
+        return vec![Line::from(spans)];
+    }
+    let prefix_width: usize = prefix_spans.iter().map(|s| display_width(&s.content)).sum();
+
     let content_width: usize = content_spans.iter()
         .map(|s| display_width(&s.content))
         .sum();
@@ -1848,6 +2107,11 @@ fn wrap_line_for_cursor<'a>(
 }
 
 /// Normalize whitespace, replace tabs with spaces and handle special Unicode whitespace
+/// `width` columns of list indent, with a `│` at each of `guides`.
+fn guide_indent(width: usize, guides: &[usize]) -> String {
+    (0..width).map(|col| if guides.contains(&col) { '│' } else { ' ' }).collect()
+}
+
 fn normalize_whitespace(text: &str) -> String {
     let mut result = String::with_capacity(text.len());
     for c in text.chars() {
@@ -1891,19 +2155,108 @@ fn code_line_height(
     (wrap_line_for_cursor(spans, available_width, theme).len() as u16).max(1)
 }
 
-fn render_content_line<F>(
-    f: &mut Frame,
-    theme: &Theme,
-    line: &str,
+/// Level of a markdown heading line (`## Title` is 2).
+fn heading_level_of(line: &str) -> Option<usize> {
+    let hashes = line.chars().take_while(|&c| c == '#').count();
+    let is_heading = (1..=6).contains(&hashes) && line[hashes..].starts_with(' ');
+    is_heading.then_some(hashes)
+}
+
+/// Decoration for a heading line in the document heading style.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct HeadingDecor {
+    bar: bool,
+    underline: bool,
+    number: Option<String>,
+}
+
+/// Left indentation of a heading of `level` and of the section body below it.
+fn heading_indents(style: HeadingStyle, level: usize) -> (u16, u16) {
+    match style {
+        HeadingStyle::Plain => (0, 0),
+        HeadingStyle::Document => {
+            let heading = (level.min(4) as u16 - 1) * 2;
+            let body = level.min(3) as u16 * 2;
+            (heading, body)
+        }
+    }
+}
+
+fn heading_decor(style: HeadingStyle, level: usize, number: Option<String>) -> HeadingDecor {
+    match style {
+        HeadingStyle::Plain => HeadingDecor::default(),
+        HeadingStyle::Document => HeadingDecor {
+            bar: level == 1,
+            underline: level <= 2,
+            number,
+        },
+    }
+}
+
+/// Left indentation of every content item, given the heading level of each
+/// (`None` for anything that isn't a heading).
+fn section_indents(levels: &[Option<usize>], style: HeadingStyle) -> Vec<u16> {
+    let mut body = 0;
+    levels
+        .iter()
+        .map(|level| match level {
+            Some(level) => {
+                let (heading, section) = heading_indents(style, *level);
+                body = section;
+                heading
+            }
+            None => body,
+        })
+        .collect()
+}
+
+/// Outline numbers (`1`, `1.2`) for each heading, counted from the shallowest
+/// heading level used in the note.
+fn heading_numbers(levels: &[Option<usize>]) -> Vec<Option<String>> {
+    let top = levels.iter().flatten().min().copied().unwrap_or(1);
+    let mut counters = [0usize; 6];
+    levels
+        .iter()
+        .map(|level| {
+            let level = (*level)?;
+            counters[level - 1] += 1;
+            counters[level..].iter_mut().for_each(|c| *c = 0);
+            let parts: Vec<String> = counters[top - 1..level].iter().map(|c| c.to_string()).collect();
+            Some(parts.join("."))
+        })
+        .collect()
+}
+
+/// What a line of text is drawn with besides its own text: the display
+/// settings, where it goes and how the cursor and its links show on it.
+#[derive(Clone, Copy)]
+struct LineRenderCtx<'a> {
+    theme: &'a Theme,
+    strikethrough: Modifier,
+    render_inline_html: bool,
+    /// Tab width to draw indent guides at, when they're on
+    indent_guides: Option<usize>,
     area: Rect,
     is_cursor: bool,
+    /// Its links are shown, for the cursor or mouse on it
     has_link: bool,
     selected_link: usize,
+}
+
+fn render_content_line<F>(
+    f: &mut Frame,
+    ctx: &LineRenderCtx,
+    line: &str,
     wiki_link_validator: Option<F>,
     fold_state: Option<bool>,  // None = not foldable, Some(true) = folded, Some(false) = expanded
+    heading: &HeadingDecor,
 ) where
     F: Fn(&str) -> bool,
 {
+    let LineRenderCtx { theme, strikethrough, render_inline_html, indent_guides, area, is_cursor, has_link, selected_link } =
+        *ctx;
+    // Worked out before tabs are expanded, to count them at the tab width
+    let guides = indent_guides.map(|tab_width| crate::app::indent_guides(line, tab_width, 4));
     let line = &normalize_whitespace(line);
     let cursor_indicator = if is_cursor { "▶ " } else { "  " };
     let available_width = (area.width as usize).saturating_sub(1); // 1 char right padding
@@ -1917,6 +2270,10 @@ fn render_content_line<F>(
         }
     };
 
+    // Spans of the first row that continuation rows are indented past, when
+    // the wrap can't tell on its own
+    let mut hanging_spans = None;
+
     // Check headings from most specific (######) to least specific (#)
     let content_theme = &theme.content;
     let styled_line = if line.starts_with("###### ") {
@@ -1926,7 +2283,7 @@ fn render_content_line<F>(
             Span::styled(
                 line.trim_start_matches("###### "),
                 Style::default()
-                    .fg(content_theme.text)
+                    .fg(content_theme.heading6)
                     .add_modifier(Modifier::ITALIC),
             ),
         ])
@@ -1937,7 +2294,7 @@ fn render_content_line<F>(
             Span::styled(
                 line.trim_start_matches("##### "),
                 Style::default()
-                    .fg(content_theme.heading4)
+                    .fg(content_theme.heading5)
                     .add_modifier(Modifier::BOLD),
             ),
         ])
@@ -1996,7 +2353,7 @@ fn render_content_line<F>(
             Span::styled(cursor_indicator, Style::default().fg(theme.warning)),
             Span::styled("• ", Style::default().fg(content_theme.list_marker)),
         ];
-        spans.extend(parse_inline_formatting(line.trim_start_matches("- "), theme, selected, wiki_link_validator));
+        spans.extend(parse_inline_formatting(line.trim_start_matches("- "), theme, strikethrough, selected, wiki_link_validator));
         Line::from(spans)
     } else if line.starts_with("> ") {
         // Blockquote - with inline formatting support
@@ -2006,7 +2363,7 @@ fn render_content_line<F>(
             Span::styled(cursor_indicator, Style::default().fg(theme.warning)),
             Span::styled("┃ ", Style::default().fg(content_theme.blockquote)),
         ];
-        let formatted = parse_inline_formatting(content, theme, selected, wiki_link_validator);
+        let formatted = parse_inline_formatting(content, theme, strikethrough, selected, wiki_link_validator);
         for span in formatted {
             let mut style = span.style;
             if style.fg.is_none() || style.fg == Some(content_theme.text.into()) {
@@ -2020,7 +2377,7 @@ fn render_content_line<F>(
         let hr_width = available_width.saturating_sub(2);
         Line::from(vec![
             Span::styled(cursor_indicator, Style::default().fg(theme.warning)),
-            Span::styled("─".repeat(hr_width), Style::default().fg(theme.border)),
+            Span::styled("─".repeat(hr_width), Style::default().fg(content_theme.hr)),
         ])
     } else if line.starts_with("* ") {
         // Bullet list (asterisk variant)
@@ -2029,16 +2386,64 @@ fn render_content_line<F>(
             Span::styled(cursor_indicator, Style::default().fg(theme.warning)),
             Span::styled("• ", Style::default().fg(content_theme.list_marker)),
         ];
-        spans.extend(parse_inline_formatting(line.trim_start_matches("* "), theme, selected, wiki_link_validator));
+        spans.extend(parse_inline_formatting(line.trim_start_matches("* "), theme, strikethrough, selected, wiki_link_validator));
+        Line::from(spans)
+    } else if let Some(item) = parse_list_marker(line) {
+        // Indented and numbered items: the marker and its indent are the hanging indent
+        let selected = if is_cursor { Some(selected_link) } else { None };
+        let marker = if item.marker.len() == 1 && !item.marker.starts_with(|c: char| c.is_ascii_digit()) {
+            "•"
+        } else {
+            item.marker
+        };
+        let mut spans = vec![Span::styled(cursor_indicator, Style::default().fg(theme.warning))];
+        match &guides {
+            Some(guides) => spans.extend([
+                Span::styled(guide_indent(item.indent, guides), Style::default().fg(content_theme.indent_guide)),
+                Span::styled(
+                    format!("{} ", marker),
+                    Style::default().fg(content_theme.list_marker_at(guides.len())),
+                ),
+            ]),
+            None => spans.push(Span::styled(
+                format!("{}{} ", " ".repeat(item.indent), marker),
+                Style::default().fg(content_theme.list_marker),
+            )),
+        }
+        spans.extend(parse_inline_formatting(item.text, theme, strikethrough, selected, wiki_link_validator));
+        hanging_spans = Some(2);
         Line::from(spans)
     } else {
-        // Regular text lines (including numbered lists)
+        // Regular text lines
         let selected = if is_cursor { Some(selected_link) } else { None };
         let mut spans = vec![
             Span::styled(cursor_indicator, Style::default().fg(theme.warning)),
         ];
-        spans.extend(parse_inline_formatting(line, theme, selected, wiki_link_validator));
+        spans.extend(parse_inline_formatting(line, theme, strikethrough, selected, wiki_link_validator));
+        Line::from(spans)
+    };
+
+    // Document heading style: bar, number and underline go around the title span
+    let styled_line = if heading_level_of(line).is_some() && *heading != HeadingDecor::default() {
+        let mut spans = styled_line.spans;
+        if let Some(title) = spans.pop() {
+            let color = title.style.fg.unwrap_or(content_theme.text);
+            if heading.bar {
+                spans.push(Span::styled("▌ ", Style::default().fg(color)));
+            }
+            if let Some(number) = &heading.number {
+                spans.push(Span::styled(format!("{} ", number), title.style));
+            }
+            let title_style = if heading.underline {
+                title.style.add_modifier(Modifier::UNDERLINED)
+            } else {
+                title.style
+            };
+            spans.push(Span::styled(title.content, title_style));
+        }
         Line::from(spans)
+    } else {
+        styled_line
     };
 
     let final_line = if has_link {
@@ -2049,8 +2454,34 @@ fn render_content_line<F>(
         styled_line
     };
 
+    // `<br>` starts a row of its own, indented like a wrapped continuation
+    let mut rows = if render_inline_html {
+        let kbd = Style::default().add_modifier(Modifier::REVERSED);
+        let mark = Style::default().fg(Color::Black).bg(theme.search.match_highlight);
+        inline_html::apply(final_line.spans, kbd, mark).into_iter()
+    } else {
+        vec![final_line.spans].into_iter()
+    };
+    let first_row = rows.next().unwrap_or_default();
+    let indent_width: usize = first_row
+        .iter()
+        .take(hanging_spans.unwrap_or(1))
+        .map(|span| display_width(&span.content))
+        .sum();
+
     // Manually handle wrapping so continuation lines have same padding as first line
-    let wrapped_lines = wrap_line_for_cursor(final_line.spans, available_width, theme);
+    let mut wrapped_lines = match hanging_spans {
+        Some(n) => {
+            let mut prefix = first_row;
+            let content = prefix.split_off(n);
+            wrap_with_hanging_indent(prefix, content, available_width)
+        }
+        None => wrap_line_for_cursor(first_row, available_width, theme),
+    };
+    for row in rows {
+        let indent = vec![Span::raw(" ".repeat(indent_width))];
+        wrapped_lines.extend(wrap_with_hanging_indent(indent, row, available_width));
+    }
 
     let bg_style = if is_cursor {
         Style::default().bg(theme.selection)
@@ -2072,6 +2503,68 @@ fn render_content_line<F>(
     }
 }
 
+/// A list continuation line, definition term or definition: its text
+/// indented to the item's column, wrapping with a hanging indent to match.
+fn render_item_text(f: &mut Frame, app: &App, item_idx: usize, area: Rect, is_cursor: bool) {
+    let (text, indent, bold) = match &app.content_items[item_idx] {
+        ContentItem::ListContinuation { text, indent } => (text, *indent, false),
+        ContentItem::DefinitionTerm(text) => (text, 0, true),
+        ContentItem::Definition(text) => (text, DEFINITION_INDENT, false),
+        _ => return,
+    };
+    let theme = &app.theme;
+    let cursor_indicator = if is_cursor { "▶ " } else { "  " };
+    let selected = is_cursor.then_some(app.selected_link_index);
+    let wiki_validator = |target: &str| app.wiki_link_exists(target);
+    let text = normalize_whitespace(text);
+
+    let mut content = parse_inline_formatting(&text, theme, app.config.display.strikethrough.modifier(), selected, Some(wiki_validator));
+    if bold {
+        content = content
+            .into_iter()
+            .map(|span| {
+                let style = span.style.add_modifier(Modifier::BOLD);
+                Span::styled(span.content, style)
+            })
+            .collect();
+    }
+    let has_link = is_cursor && app.item_has_link_at(item_idx);
+    if has_link {
+        content.push(Span::styled(" Open ↗", Style::default().fg(theme.content.link)));
+    }
+    // Under a list item, guides run down to the column its text starts at
+    let is_continuation = matches!(app.content_items[item_idx], ContentItem::ListContinuation { .. });
+    let indent = if app.config.reading.indent_guides && is_continuation {
+        let tab_width = app.config.editor.tab_width as usize;
+        guide_indent(indent, &crate::app::indent_guides(&" ".repeat(indent), tab_width, 1))
+    } else {
+        " ".repeat(indent)
+    };
+    let prefix = vec![
+        Span::styled(cursor_indicator, Style::default().fg(theme.warning)),
+        Span::styled(indent, Style::default().fg(theme.content.indent_guide)),
+    ];
+    let available_width = (area.width as usize).saturating_sub(1); // 1 char right padding
+    let wrapped_lines = wrap_with_hanging_indent(prefix, content, available_width);
+
+    let bg_style = if is_cursor {
+        Style::default().bg(theme.selection)
+    } else {
+        Style::default()
+    };
+    for (i, wrapped_line) in wrapped_lines.into_iter().enumerate() {
+        let line_area = Rect {
+            x: area.x,
+            y: area.y.saturating_add(i as u16),
+            width: area.width,
+            height: 1,
+        };
+        if line_area.y < area.y + area.height {
+            f.render_widget(Paragraph::new(wrapped_line).style(bg_style), line_area);
+        }
+    }
+}
+
 fn render_code_line(
     f: &mut Frame,
     theme: &Theme,
@@ -2093,7 +2586,7 @@ fn render_code_line(
     if let Some(hl_spans) = highlighted_spans {
         spans.extend(hl_spans);
     } else {
-        spans.push(Span::styled(expanded_line, Style::default().fg(content_theme.code)));
+        spans.push(Span::styled(expanded_line, Style::default().fg(content_theme.code_block)));
     }
 
     let wrapped_lines = wrap_line_for_cursor(spans, available_width, theme);
@@ -2140,18 +2633,15 @@ fn render_code_fence(f: &mut Frame, theme: &Theme, _lang: &str, area: Rect, is_c
 
 fn render_task_item<F>(
     f: &mut Frame,
-    theme: &Theme,
+    ctx: &LineRenderCtx,
     text: &str,
     checked: bool,
     indent: usize,
-    area: Rect,
-    is_cursor: bool,
-    selected_link: usize,
-    has_links: bool,
     wiki_link_validator: Option<F>,
 ) where
     F: Fn(&str) -> bool,
 {
+    let LineRenderCtx { theme, strikethrough, indent_guides, area, is_cursor, has_link: has_links, selected_link, .. } = *ctx;
     let cursor_indicator = if is_cursor { "▶ " } else { "  " };
 
     let checkbox_selected = is_cursor && has_links && selected_link == 0;
@@ -2173,13 +2663,13 @@ fn render_task_item<F>(
     } else {
         None
     };
-    let mut text_spans = parse_inline_formatting(&expanded_text, theme, link_selected, wiki_link_validator);
+    let mut text_spans = parse_inline_formatting(&expanded_text, theme, strikethrough, link_selected, wiki_link_validator);
     if checked {
         text_spans = text_spans
             .into_iter()
             .map(|span| {
                 let mut style = span.style;
-                style = style.fg(theme.muted).add_modifier(Modifier::CROSSED_OUT);
+                style = style.fg(theme.muted).add_modifier(strikethrough);
                 Span::styled(span.content, style)
             })
             .collect();
@@ -2203,7 +2693,11 @@ fn render_task_item<F>(
         Span::styled(cursor_indicator, Style::default().fg(theme.warning)),
     ];
     if indent > 0 {
-        spans.push(Span::styled(" ".repeat(indent), Style::default()));
+        let indent = match indent_guides {
+            Some(tab_width) => guide_indent(indent, &crate::app::indent_guides(&" ".repeat(indent), tab_width, 1)),
+            None => " ".repeat(indent),
+        };
+        spans.push(Span::styled(indent, Style::default().fg(theme.content.indent_guide)));
     }
     spans.extend([
         Span::styled("[", bracket_style),
@@ -2211,9 +2705,9 @@ fn render_task_item<F>(
         Span::styled("]", bracket_style),
         Span::styled(" ", Style::default()),
     ]);
-    spans.extend(text_spans);
 
-    let wrapped_lines = wrap_line_for_cursor(spans, available_width, theme);
+    // Wrapped rows line up under the text, past the checkbox
+    let wrapped_lines = wrap_with_hanging_indent(spans, text_spans, available_width);
 
     let bg_style = if is_cursor {
         Style::default().bg(theme.selection)
@@ -2237,16 +2731,14 @@ fn render_task_item<F>(
 
 fn render_table_row(
     f: &mut Frame,
-    theme: &Theme,
+    ctx: &LineRenderCtx,
     cells: &[String],
     is_separator: bool,
     is_header: bool,
     natural_widths: &[usize],
     alignments: &[crate::app::Alignment],
-    area: Rect,
-    is_cursor: bool,
-    has_link: bool,
 ) {
+    let LineRenderCtx { theme, strikethrough, area, is_cursor, has_link, .. } = *ctx;
     let border_color = theme.border;
     let row_bg = if is_cursor {
         Style::default().bg(theme.selection)
@@ -2298,7 +2790,7 @@ fn render_table_row(
             let expanded = expand_tabs(c);
             let mut all_visual_lines: Vec<Vec<Span<'static>>> = Vec::new();
             for logical in split_cell_by_br(&expanded) {
-                let spans = parse_inline_formatting::<fn(&str) -> bool>(logical, theme, None, None);
+                let spans = parse_inline_formatting::<fn(&str) -> bool>(logical, theme, strikethrough, None, None);
                 all_visual_lines.extend(distribute_spans_across_lines(spans, w, text_color));
             }
             if all_visual_lines.is_empty() {
@@ -2383,9 +2875,34 @@ fn render_table_row(
     }
 }
 
-fn render_inline_image_with_cursor(f: &mut Frame, app: &mut App, path: &str, area: Rect, is_cursor: bool, is_hovered: bool) {
+/// The line above an image naming it, with its size and whether it's
+/// loading, failed or shown. It's all there is of the image with previews
+/// off, so it takes the "Open ↗" hint then.
+fn render_image_placeholder(f: &mut Frame, app: &mut App, path: &str, area: Rect, is_cursor: bool, is_hovered: bool, show_images: bool) {
+    let info = app.image_info(path);
+    let theme = &app.theme;
+    let text_color = match info.load {
+        ImageLoad::Failed(_) => theme.error,
+        _ => theme.muted,
+    };
+    let mut spans = vec![
+        Span::styled(if is_cursor { "▶ " } else { "  " }, Style::default().fg(theme.warning)),
+        Span::styled(info.describe(path, show_images), Style::default().fg(text_color).add_modifier(Modifier::ITALIC)),
+    ];
+    if !show_images && (is_cursor || is_hovered) {
+        spans.push(Span::styled("  Open ↗", Style::default().fg(theme.content.link)));
+    }
+    let style = if is_cursor { Style::default().bg(theme.selection) } else { Style::default() };
+    f.render_widget(Paragraph::new(Line::from(spans)).style(style), area);
+}
+
+/// Draw an image on a line of its own in a border `cols` wide inside, set
+/// left or centered in `area` per `images.align`.
+fn render_inline_image_with_cursor(f: &mut Frame, app: &mut App, key: ImageKey, area: Rect, cols: u16, is_cursor: bool, is_hovered: bool) {
+    let path = key.1.as_str();
     let is_remote = path.starts_with("http://") || path.starts_with("https://");
     let is_pending = is_remote && app.is_image_pending(path);
+    let failed = matches!(app.image_info(path).load, ImageLoad::Failed(_));
 
     let resolved_path = app.resolve_image_path(path);
     let resolved_path_str = resolved_path.as_ref()
@@ -2395,10 +2912,7 @@ fn render_inline_image_with_cursor(f: &mut Frame, app: &mut App, path: &str, are
     let is_cached = app.is_image_cached(&resolved_path_str);
 
     // Check if we need to load a new image
-    let need_load = match &app.current_image {
-        Some(state) => state.path != resolved_path_str,
-        None => true,
-    };
+    let need_load = !app.image_states.contains_key(&key);
 
     if need_load {
         // Load image from cache, disk, or trigger async fetch for remote
@@ -2422,10 +2936,7 @@ fn render_inline_image_with_cursor(f: &mut Frame, app: &mut App, path: &str, are
 
         if let (Some(img), Some(picker)) = (img, &mut app.picker) {
             let protocol = picker.new_resize_protocol(img);
-            app.current_image = Some(ImageState {
-                image: protocol,
-                path: resolved_path_str.clone(),
-            });
+            app.image_states.insert(key.clone(), protocol);
         }
     }
 
@@ -2455,7 +2966,13 @@ fn render_inline_image_with_cursor(f: &mut Frame, app: &mut App, path: &str, are
         .borders(Borders::ALL)
         .border_style(Style::default().fg(border_color));
 
-    let inner_area = block.inner(area);
+    let width = cols.saturating_add(2).min(area.width);
+    let x_offset = match app.config.images.align {
+        ImageAlign::Left => 0,
+        ImageAlign::Center => (area.width - width) / 2,
+    };
+    let image_area = Rect { x: area.x + x_offset, width, ..area };
+    let inner_area = block.inner(image_area);
 
     // Add background highlight when cursor is on image
     if is_cursor {
@@ -2463,20 +2980,25 @@ fn render_inline_image_with_cursor(f: &mut Frame, app: &mut App, path: &str, are
         f.render_widget(bg, area);
     }
 
-    f.render_widget(block, area);
+    f.render_widget(block, image_area);
 
-    if is_pending || (is_remote && !is_cached && app.current_image.as_ref().map(|s| s.path != resolved_path_str).unwrap_or(true)) {
+    if failed {
+        let failed = Paragraph::new("  [Image failed to load]")
+            .style(Style::default().fg(theme.error).add_modifier(Modifier::ITALIC));
+        f.render_widget(failed, inner_area);
+        return;
+    }
+
+    if is_pending || (is_remote && !is_cached && !app.image_states.contains_key(&key)) {
         let loading = Paragraph::new("  Loading remote image...")
             .style(Style::default().fg(theme.secondary).add_modifier(Modifier::ITALIC));
         f.render_widget(loading, inner_area);
         return;
     }
 
-    if let Some(state) = &mut app.current_image {
-        if state.path == resolved_path_str {
-            let image_widget = StatefulImage::new();
-            f.render_stateful_widget(image_widget, inner_area, &mut state.image);
-        }
+    if let Some(state) = app.image_states.get_mut(&key) {
+        let image_widget = StatefulImage::new();
+        f.render_stateful_widget(image_widget, inner_area, state);
     } else if !is_remote {
         let placeholder = Paragraph::new("  [Image not found]")
             .style(Style::default().fg(theme.error).add_modifier(Modifier::ITALIC));
@@ -2536,13 +3058,9 @@ fn render_inline_thumbnails(
             None
         };
         if let (Some(img), Some(picker)) = (img, &mut app.picker) {
-            let protocol = picker.new_resize_protocol(img);
-            let mut thumb_state = ImageState {
-                image: protocol,
-                path: resolved_path_str.clone(),
-            };
+            let mut protocol = picker.new_resize_protocol(img);
             let image_widget = StatefulImage::new();
-            f.render_stateful_widget(image_widget, thumb_area, &mut thumb_state.image);
+            f.render_stateful_widget(image_widget, thumb_area, &mut protocol);
         } else if is_pending {
             let loading = Paragraph::new("  ⏳ Loading...")
                 .style(Style::default().fg(secondary_color).add_modifier(Modifier::ITALIC));
@@ -2608,6 +3126,55 @@ fn render_details(
     f.render_widget(paragraph, area);
 }
 
+struct Callout<'a> {
+    kind: CalloutKind,
+    title: &'a str,
+    lines: &'a [String],
+    foldable: bool,
+    is_open: bool,
+}
+
+/// A `> [!NOTE]` callout: a bar in the type's color down the side, its icon
+/// and title on top, and the quoted lines under it unless it's folded.
+fn render_callout(f: &mut Frame, theme: &Theme, strikethrough: Modifier, callout: &Callout, area: Rect, is_cursor: bool) {
+    let color = callout.kind.color(&theme.callout);
+    let cursor_indicator = if is_cursor { "▶ " } else { "  " };
+    let toggle_indicator = match (callout.foldable, callout.is_open) {
+        (false, _) => "",
+        (true, true) => "▼ ",
+        (true, false) => "▶ ",
+    };
+
+    let mut lines = vec![Line::from(vec![
+        Span::styled(cursor_indicator, Style::default().fg(theme.warning)),
+        Span::styled("┃ ", Style::default().fg(color)),
+        Span::styled(toggle_indicator, Style::default().fg(color)),
+        Span::styled(
+            format!("{} {}", callout.kind.icon(), expand_tabs(callout.title)),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ),
+    ])];
+
+    let expanded: Vec<String> = callout.lines.iter().map(|text| expand_tabs(text)).collect();
+    if callout.is_open {
+        for text in &expanded {
+            let mut spans = vec![
+                Span::raw("  "),
+                Span::styled("┃ ", Style::default().fg(color)),
+            ];
+            spans.extend(parse_inline_formatting::<fn(&str) -> bool>(text, theme, strikethrough, None, None));
+            lines.push(Line::from(spans));
+        }
+    }
+
+    let style = if is_cursor {
+        Style::default().bg(theme.selection)
+    } else {
+        Style::default()
+    };
+    f.render_widget(Paragraph::new(lines).style(style).wrap(Wrap { trim: false }), area);
+}
+
 fn render_frontmatter_delimiter(
     f: &mut Frame,
     theme: &Theme,
@@ -2735,10 +3302,97 @@ fn render_frontmatter_line(
     f.render_widget(paragraph, area);
 }
 
+fn render_folder_entry(
+    f: &mut Frame,
+    theme: &Theme,
+    name: &str,
+    detail: &str,
+    is_folder: bool,
+    area: Rect,
+    is_cursor: bool,
+) {
+    let cursor_indicator = if is_cursor { "▶ " } else { "  " };
+    let (icon, name_style) = if is_folder {
+        ("▸ ", Style::default().fg(theme.sidebar.folder).add_modifier(Modifier::BOLD))
+    } else {
+        ("  ", Style::default().fg(theme.primary))
+    };
+
+    let mut spans = vec![
+        Span::styled(cursor_indicator, Style::default().fg(theme.warning)),
+        Span::styled(icon, name_style),
+        Span::styled(name, name_style),
+    ];
+    if !detail.is_empty() {
+        spans.push(Span::styled(format!("  {}", detail), Style::default().fg(theme.muted)));
+    }
+
+    let style = if is_cursor {
+        Style::default().bg(theme.selection)
+    } else {
+        Style::default()
+    };
+
+    let paragraph = Paragraph::new(Line::from(spans)).style(style);
+    f.render_widget(paragraph, area);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn heading_style_and_indent_per_level() {
+        let doc = HeadingStyle::Document;
+        assert_eq!(heading_indents(doc, 1), (0, 2));
+        assert_eq!(heading_indents(doc, 2), (2, 4));
+        assert_eq!(heading_indents(doc, 3), (4, 6));
+        assert_eq!(heading_indents(doc, 5), (6, 6));
+        assert_eq!(heading_indents(HeadingStyle::Plain, 2), (0, 0));
+
+        let h1 = heading_decor(doc, 1, None);
+        assert!(h1.bar && h1.underline);
+        let h2 = heading_decor(doc, 2, None);
+        assert!(!h2.bar && h2.underline);
+        assert_eq!(heading_decor(doc, 3, None), HeadingDecor::default());
+        assert_eq!(heading_decor(HeadingStyle::Plain, 1, Some("1".into())), HeadingDecor::default());
+    }
+
+    #[test]
+    fn section_indents_follow_enclosing_heading() {
+        // intro, # A, body, ## B, body, # C, body
+        let levels = [None, Some(1), None, Some(2), None, Some(1), None];
+        assert_eq!(
+            section_indents(&levels, HeadingStyle::Document),
+            vec![0, 0, 2, 2, 4, 0, 2]
+        );
+        assert_eq!(section_indents(&levels, HeadingStyle::Plain), vec![0; 7]);
+    }
+
+    #[test]
+    fn heading_numbers_count_from_top_level() {
+        let levels = [Some(2), None, Some(3), Some(3), Some(2), Some(3)];
+        let numbers = heading_numbers(&levels);
+        let numbers: Vec<Option<&str>> = numbers.iter().map(|n| n.as_deref()).collect();
+        assert_eq!(numbers, vec![Some("1"), None, Some("1.1"), Some("1.2"), Some("2"), Some("2.1")]);
+        assert_eq!(heading_level_of("### Title"), Some(3));
+        assert_eq!(heading_level_of("#hashtag"), None);
+    }
+
+    #[test]
+    fn find_char_matches_reports_overlapping_hits() {
+        let chars: Vec<char> = "aaa".chars().collect();
+        let query: Vec<char> = "aa".chars().collect();
+        assert_eq!(find_char_matches(&chars, &query), vec![0, 1]);
+        assert!(find_char_matches(&chars, &['b']).is_empty());
+    }
+
+    #[test]
+    fn fold_search_char_respects_case_sensitivity() {
+        assert_eq!(fold_search_char('A', false), 'a');
+        assert_eq!(fold_search_char('A', true), 'A');
+    }
+
     #[test]
     fn cell_visible_width_plain_text() {
         assert_eq!(cell_visible_width("Plain URL"), 9);
@@ -3043,4 +3697,82 @@ mod tests {
         assert!(joined.contains("函数结束"), "wrap lost the CJK head: {joined:?}");
         assert!(joined.contains("变成了悬垂指针"), "wrap lost the CJK tail: {joined:?}");
     }
+
+    #[test]
+    fn hanging_indent_lines_wrapped_rows_up_under_the_text() {
+        let prefix = vec![plain("  "), plain("12. ")];
+        let content = vec![plain("first second third fourth")];
+        let rows: Vec<String> = wrap_with_hanging_indent(prefix, content, 20)
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert_eq!(rows, vec!["  12. first second", "      third fourth"]);
+    }
+
+    /// Scrolling a note that isn't changing copies the items still on screen
+    /// from the render cache and draws only the ones scrolling in and the
+    /// cursor, and what's copied is what drawing would have given.
+    #[test]
+    fn scrolling_a_static_note_draws_only_new_items() {
+        use crate::app::test_support::{test_app, TempDir};
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let dir = TempDir::new("render-cache");
+        let mut note = String::from("# Notes\n\n| a | b |\n|---|---|\n| **one** | [[Elsewhere]] |\n\n");
+        for i in 0..60 {
+            note.push_str(&format!("Line {} with *some* `inline` formatting\n", i));
+        }
+        std::fs::write(dir.join("Long.md"), note).unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.select_note_by_path(&dir.join("Long.md"));
+        app.focus = Focus::Content;
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        let mut draw = |app: &mut App| {
+            terminal.draw(|f| render_content(f, app, f.area())).unwrap();
+            terminal.backend().buffer().clone()
+        };
+
+        app.content_cursor = 20;
+        draw(&mut app);
+        for cursor in 21..40 {
+            app.content_cursor = cursor;
+            let (hits, misses) = app.render_cache.stats();
+            let cached = draw(&mut app);
+            let (new_hits, new_misses) = app.render_cache.stats();
+            // The item scrolled in, the new cursor and the old one
+            assert!(new_misses - misses <= 3, "drew {} items at {}", new_misses - misses, cursor);
+            assert!(new_hits - hits >= 10);
+
+            app.render_cache.invalidate();
+            assert_eq!(draw(&mut app), cached);
+        }
+    }
+
+    /// A task checked off the cursor is drawn afresh, not copied as it was.
+    #[test]
+    fn checking_a_task_draws_it_again() {
+        use crate::app::test_support::{test_app, TempDir};
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let dir = TempDir::new("render-cache-task");
+        std::fs::write(dir.join("Tasks.md"), "# Tasks\n\n- [ ] one\n- [ ] two\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        app.select_note_by_path(&dir.join("Tasks.md"));
+        app.focus = Focus::Content;
+        app.content_cursor = 0;
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+        let mut draw = |app: &mut App| {
+            terminal.draw(|f| render_content(f, app, f.area())).unwrap();
+            terminal.backend().buffer().clone()
+        };
+
+        draw(&mut app);
+        let task = app.content_items.iter().position(|item| matches!(item, ContentItem::TaskItem { .. })).unwrap();
+        app.toggle_task_at(task);
+        let cached = draw(&mut app);
+        app.render_cache.invalidate();
+        assert_eq!(draw(&mut app), cached);
+    }
 }
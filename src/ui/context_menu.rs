@@ -8,7 +8,7 @@ use ratatui::{
 
 use crate::app::{App, ContextMenuItem, ContextMenuState};
 
-const MENU_WIDTH: u16 = 14;
+const MENU_WIDTH: u16 = 16;
 
 pub fn render_context_menu(f: &mut Frame, app: &App) {
     if let ContextMenuState::Open { x, y, selected_index } = app.context_menu_state {
@@ -41,11 +41,11 @@ pub fn render_context_menu(f: &mut Frame, app: &App) {
                 let label = item.label();
                 let style = if idx == selected_index {
                     Style::default()
-                        .fg(app.theme.background)
-                        .bg(app.theme.primary)
+                        .fg(app.theme.dialog.text)
+                        .bg(app.theme.selection)
                         .add_modifier(Modifier::BOLD)
                 } else {
-                    Style::default().fg(app.theme.foreground)
+                    Style::default().fg(app.theme.dialog.text)
                 };
                 Line::from(Span::styled(format!(" {} ", label), style))
             })
@@ -54,8 +54,8 @@ pub fn render_context_menu(f: &mut Frame, app: &App) {
         let menu = Paragraph::new(lines).block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(app.theme.border))
-                .style(Style::default().bg(app.theme.background_secondary)),
+                .border_style(Style::default().fg(app.theme.dialog.border))
+                .style(Style::default().bg(app.theme.dialog.background)),
         );
 
         f.render_widget(menu, menu_area);
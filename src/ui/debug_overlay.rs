@@ -0,0 +1,64 @@
+//! F12 overlay with what the last frame took and the state that decides
+//! where things are drawn, for diagnosing rendering glitches.
+
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::{App, Mode};
+
+/// Drawn last, over the top-right corner, so the layout under it is what it
+/// would be without it.
+pub fn render_debug_overlay(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let stats = &app.frame_stats;
+    let editor = &app.editor;
+    let (row, col) = editor.cursor();
+    let (wrapped_row, display_col) = editor.cursor_wrapped_position();
+
+    let mut rows = vec![
+        ("frame", format!("{:.2?} (#{})", stats.last_frame, stats.frames)),
+        ("events", format!("{} in last batch", stats.events_in_batch)),
+    ];
+    if app.mode == Mode::Edit {
+        rows.push(("cursor", format!("{}:{} logical", row + 1, col + 1)));
+        rows.push(("", format!("+{} wrapped, col {}", wrapped_row, display_col + 1)));
+        rows.push(("scroll", format!("{} rows, {} cols", editor.scroll_offset(), editor.h_scroll_offset())));
+        rows.push(("styles", format!("{} rows cached", editor.cached_row_styles())));
+        rows.push(("marks", format!("{} highlights", editor.highlight_count())));
+    } else {
+        rows.push(("cursor", format!("item {}", app.content_cursor + 1)));
+        rows.push(("scroll", format!("{} items", app.content_scroll_offset)));
+        let (hits, misses) = app.render_cache.stats();
+        rows.push(("items", format!("{} copied, {} drawn", hits, misses)));
+    }
+    rows.push(("content", format!("{} items parsed", app.content_items.len())));
+    rows.push(("images", format!("{} decoded", app.image_states.len())));
+
+    let lines: Vec<Line> = rows
+        .into_iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::styled(format!("{:<8}", label), Style::default().fg(theme.muted)),
+                Span::styled(value, Style::default().fg(theme.dialog.text)),
+            ])
+        })
+        .collect();
+
+    let area = f.area();
+    let width = (lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 2).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let overlay = Rect::new(area.x + area.width - width, area.y, width, height);
+
+    f.render_widget(Clear, overlay);
+    let block = Block::default()
+        .title(Span::styled(" Debug ", Style::default().fg(theme.dialog.title).add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.warning))
+        .style(Style::default().bg(theme.dialog.background));
+    f.render_widget(Paragraph::new(lines).block(block), overlay);
+}
@@ -1,13 +1,21 @@
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
     Frame,
 };
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
-use crate::app::App;
-use crate::config::Theme;
+use super::layout::{centered_rect, overflow_scroll};
+use crate::app::{App, PassphrasePurpose, RenameProblem, ARCHIVE_HEADING};
+use crate::config::{Config, Theme};
+use crate::editor::LineInput;
+use crate::vim::command::TaskOp;
+
+/// Narrowest help dialog, inside its borders, that keeps two columns.
+const HELP_TWO_COLUMN_MIN_WIDTH: u16 = 70;
 
 const TITLE_MAIN: &[&str] = &[
     "████████ ██   ██ ██████  ██   ██  ██████  ███████",
@@ -36,20 +44,39 @@ fn render_flat_title(theme: &Theme, dialog_width: u16) -> Vec<Line<'static>> {
     }).collect()
 }
 
+/// `text` with the cursor at char `cursor`: `end_cursor` after the text, or
+/// the cluster under it reversed.
+pub fn input_spans(text: &str, cursor: usize, style: Style, end_cursor: Span<'static>) -> Vec<Span<'static>> {
+    let Some((at, _)) = text.char_indices().nth(cursor) else {
+        return vec![Span::styled(text.to_string(), style), end_cursor];
+    };
+    let cluster = text[at..].graphemes(true).next().unwrap_or_default();
+    let after = at + cluster.len();
+    vec![
+        Span::styled(text[..at].to_string(), style),
+        Span::styled(cluster.to_string(), style.add_modifier(Modifier::REVERSED)),
+        Span::styled(text[after..].to_string(), style),
+    ]
+}
+
+/// A dialog's text field after `prompt`.
+fn input_line(prompt: Span<'static>, input: &LineInput, theme: &Theme) -> Line<'static> {
+    let mut spans = vec![prompt];
+    spans.extend(input_spans(
+        input,
+        input.cursor(),
+        Style::default().fg(theme.dialog.text),
+        Span::styled("█", Style::default().fg(theme.cursor)),
+    ));
+    Line::from(spans)
+}
+
 pub fn render_welcome_dialog(f: &mut Frame, theme: &Theme) {
     let area = f.area();
     let dialog_theme = &theme.dialog;
 
     // Calculate centered dialog area
-    let dialog_width = 60.min(area.width.saturating_sub(4));
-    let dialog_height = 24.min(area.height.saturating_sub(4));
-
-    let dialog_area = Rect {
-        x: (area.width.saturating_sub(dialog_width)) / 2,
-        y: (area.height.saturating_sub(dialog_height)) / 2,
-        width: dialog_width,
-        height: dialog_height,
-    };
+    let dialog_area = centered_rect(area, 60, 24);
 
     // Clear the area behind the dialog
     f.render_widget(Clear, dialog_area);
@@ -58,7 +85,7 @@ pub fn render_welcome_dialog(f: &mut Frame, theme: &Theme) {
     let mut welcome_text = vec![Line::from("")];
 
     // Add flat bitmap title with manual centering
-    welcome_text.extend(render_flat_title(theme, dialog_width));
+    welcome_text.extend(render_flat_title(theme, dialog_area.width));
 
     welcome_text.extend(vec![
         Line::from(""),
@@ -117,15 +144,7 @@ pub fn render_onboarding_dialog(f: &mut Frame, app: &App) {
     let theme = &app.theme;
 
     // Calculate centered dialog area
-    let dialog_width = 60.min(area.width.saturating_sub(4));
-    let dialog_height = 12.min(area.height.saturating_sub(4));
-
-    let dialog_area = Rect {
-        x: (area.width.saturating_sub(dialog_width)) / 2,
-        y: (area.height.saturating_sub(dialog_height)) / 2,
-        width: dialog_width,
-        height: dialog_height,
-    };
+    let dialog_area = centered_rect(area, 60, 12);
 
     // Clear the area behind the dialog
     f.render_widget(Clear, dialog_area);
@@ -134,33 +153,34 @@ pub fn render_onboarding_dialog(f: &mut Frame, app: &App) {
         Line::from(""),
         Line::from(Span::styled(
             "Welcome to Ekphos!",
-            Style::default().fg(theme.primary).add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.dialog.title).add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
         Line::from(Span::styled(
             "Where would you like to store your notes?",
-            Style::default().fg(theme.foreground),
+            Style::default().fg(theme.dialog.text),
         )),
         Line::from(""),
-        Line::from(vec![
-            Span::styled("> ", Style::default().fg(theme.warning)),
-            Span::styled(&app.input_buffer, Style::default().fg(theme.foreground)),
-            Span::styled("█", Style::default().fg(theme.cursor)),
-        ]),
+        input_line(Span::styled("> ", Style::default().fg(theme.warning)), &app.input_buffer, theme),
         Line::from(""),
-        Line::from(Span::styled(
-            "Press Enter to confirm",
-            Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
-        )),
+        match &app.dialog_error {
+            Some(error) => Line::from(Span::styled(error.as_str(), Style::default().fg(theme.dialog.danger))),
+            None => Line::from(Span::styled(
+                "Enter: Confirm  |  Tab: Complete folder",
+                Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+            )),
+        },
     ];
 
+    let scroll = overflow_scroll(content.len(), dialog_area);
     let dialog = Paragraph::new(content)
+        .scroll(scroll)
         .block(
             Block::default()
                 .title(" Setup ")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(theme.primary))
-                .style(Style::default().bg(theme.background)),
+                .border_style(Style::default().fg(theme.dialog.border))
+                .style(Style::default().bg(theme.dialog.background)),
         )
         .alignment(Alignment::Center);
 
@@ -177,15 +197,7 @@ pub fn render_create_note_dialog(f: &mut Frame, app: &App) {
     let dialog_height = if has_error { base_height + 2 } else { base_height };
 
     // Calculate centered dialog area
-    let dialog_width = 50.min(area.width.saturating_sub(4));
-    let dialog_height = dialog_height.min(area.height.saturating_sub(4));
-
-    let dialog_area = Rect {
-        x: (area.width.saturating_sub(dialog_width)) / 2,
-        y: (area.height.saturating_sub(dialog_height)) / 2,
-        width: dialog_width,
-        height: dialog_height,
-    };
+    let dialog_area = centered_rect(area, 50, dialog_height);
 
     // Clear the area behind the dialog
     f.render_widget(Clear, dialog_area);
@@ -194,7 +206,7 @@ pub fn render_create_note_dialog(f: &mut Frame, app: &App) {
         Line::from(""),
         Line::from(Span::styled(
             "Enter note name:",
-            Style::default().fg(theme.foreground),
+            Style::default().fg(theme.dialog.text),
         )),
     ];
 
@@ -209,18 +221,14 @@ pub fn render_create_note_dialog(f: &mut Frame, app: &App) {
     }
 
     content.push(Line::from(""));
-    content.push(Line::from(vec![
-        Span::styled("> ", Style::default().fg(theme.warning)),
-        Span::styled(&app.input_buffer, Style::default().fg(theme.foreground)),
-        Span::styled("█", Style::default().fg(theme.cursor)),
-    ]));
+    content.push(input_line(Span::styled("> ", Style::default().fg(theme.warning)), &app.input_buffer, theme));
 
     // Show error message if present
     if let Some(ref error) = app.dialog_error {
         content.push(Line::from(""));
         content.push(Line::from(Span::styled(
             error.as_str(),
-            Style::default().fg(theme.error),
+            Style::default().fg(theme.dialog.danger),
         )));
     }
 
@@ -230,15 +238,17 @@ pub fn render_create_note_dialog(f: &mut Frame, app: &App) {
         Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
     )));
 
-    let border_color = if has_error { theme.error } else { theme.success };
+    let border_color = if has_error { theme.dialog.danger } else { theme.dialog.confirm };
 
+    let scroll = overflow_scroll(content.len(), dialog_area);
     let dialog = Paragraph::new(content)
+        .scroll(scroll)
         .block(
             Block::default()
                 .title(" New Note ")
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(border_color))
-                .style(Style::default().bg(theme.background)),
+                .style(Style::default().bg(theme.dialog.background)),
         )
         .alignment(Alignment::Center);
 
@@ -250,34 +260,31 @@ pub fn render_delete_confirm_dialog(f: &mut Frame, app: &App) {
     let theme = &app.theme;
 
     // Calculate centered dialog area
-    let dialog_width = 50.min(area.width.saturating_sub(4));
-    let dialog_height = 9.min(area.height.saturating_sub(4));
-
-    let dialog_area = Rect {
-        x: (area.width.saturating_sub(dialog_width)) / 2,
-        y: (area.height.saturating_sub(dialog_height)) / 2,
-        width: dialog_width,
-        height: dialog_height,
-    };
+    let dialog_area = centered_rect(area, 50, 10);
 
     // Clear the area behind the dialog
     f.render_widget(Clear, dialog_area);
 
-    let note_name = app.current_note()
-        .map(|n| n.title.as_str())
-        .unwrap_or("this note");
+    let note = app.selected_sidebar_note();
+    let note_name = note.map(|n| n.title.as_str()).unwrap_or("this note");
+    let notes_path = app.config.notes_path();
+    let note_path = note
+        .and_then(|n| n.file_path.as_deref())
+        .map(|path| path.strip_prefix(&notes_path).unwrap_or(path).display().to_string())
+        .unwrap_or_default();
 
     let content = vec![
         Line::from(""),
         Line::from(Span::styled(
             "Delete note?",
-            Style::default().fg(theme.error).add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.dialog.danger).add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
         Line::from(Span::styled(
             note_name,
-            Style::default().fg(theme.foreground),
+            Style::default().fg(theme.dialog.text),
         )),
+        Line::from(Span::styled(note_path, Style::default().fg(theme.muted))),
         Line::from(""),
         Line::from(Span::styled(
             "y: Yes  |  n: No",
@@ -285,13 +292,15 @@ pub fn render_delete_confirm_dialog(f: &mut Frame, app: &App) {
         )),
     ];
 
+    let scroll = overflow_scroll(content.len(), dialog_area);
     let dialog = Paragraph::new(content)
+        .scroll(scroll)
         .block(
             Block::default()
                 .title(" Confirm Delete ")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(theme.error))
-                .style(Style::default().bg(theme.background)),
+                .border_style(Style::default().fg(theme.dialog.danger))
+                .style(Style::default().bg(theme.dialog.background)),
         )
         .alignment(Alignment::Center);
 
@@ -302,15 +311,7 @@ pub fn render_unsaved_changes_dialog(f: &mut Frame, app: &App) {
     let area = f.area();
     let theme = &app.theme;
 
-    let dialog_width = 50.min(area.width.saturating_sub(4));
-    let dialog_height = 10.min(area.height.saturating_sub(4));
-
-    let dialog_area = Rect {
-        x: (area.width.saturating_sub(dialog_width)) / 2,
-        y: (area.height.saturating_sub(dialog_height)) / 2,
-        width: dialog_width,
-        height: dialog_height,
-    };
+    let dialog_area = centered_rect(area, 50, 10);
 
     f.render_widget(Clear, dialog_area);
 
@@ -323,7 +324,7 @@ pub fn render_unsaved_changes_dialog(f: &mut Frame, app: &App) {
         Line::from(""),
         Line::from(Span::styled(
             "Do you want to save before exiting?",
-            Style::default().fg(theme.foreground),
+            Style::default().fg(theme.dialog.text),
         )),
         Line::from(""),
         Line::from(Span::styled(
@@ -332,154 +333,225 @@ pub fn render_unsaved_changes_dialog(f: &mut Frame, app: &App) {
         )),
     ];
 
+    let scroll = overflow_scroll(content.len(), dialog_area);
     let dialog = Paragraph::new(content)
+        .scroll(scroll)
         .block(
             Block::default()
                 .title(" Unsaved Changes ")
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(theme.warning))
-                .style(Style::default().bg(theme.background)),
+                .style(Style::default().bg(theme.dialog.background)),
         )
         .alignment(Alignment::Center);
 
     f.render_widget(dialog, dialog_area);
 }
 
-pub fn render_create_wiki_note_dialog(f: &mut Frame, app: &App) {
+pub fn render_external_change_dialog(f: &mut Frame, app: &App) {
     let area = f.area();
     let theme = &app.theme;
 
-    let dialog_width = 55.min(area.width.saturating_sub(4));
-    let dialog_height = 10.min(area.height.saturating_sub(4));
-
-    let dialog_area = Rect {
-        x: (area.width.saturating_sub(dialog_width)) / 2,
-        y: (area.height.saturating_sub(dialog_height)) / 2,
-        width: dialog_width,
-        height: dialog_height,
-    };
+    let dialog_area = centered_rect(area, 54, 11);
 
     f.render_widget(Clear, dialog_area);
 
-    let target = app.pending_wiki_target.as_deref().unwrap_or("note");
-
+    let title = app.current_note().map(|n| n.title.as_str()).unwrap_or("This note");
     let content = vec![
         Line::from(""),
         Line::from(Span::styled(
-            format!("Note '[[{}]]' doesn't exist.", target),
+            format!("{} changed on disk", title),
             Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
         Line::from(Span::styled(
-            "Would you like to create it?",
-            Style::default().fg(theme.foreground),
+            "Reload it, or keep the version on screen?",
+            Style::default().fg(theme.dialog.text),
+        )),
+        Line::from(Span::styled(
+            "Reloading drops unsaved edits; keeping overwrites",
+            Style::default().fg(theme.dialog.text),
+        )),
+        Line::from(Span::styled(
+            "the file on the next save.",
+            Style::default().fg(theme.dialog.text),
         )),
         Line::from(""),
         Line::from(Span::styled(
-            "y: Create  |  n: Cancel",
+            "r: Reload  |  k: Keep",
             Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
         )),
     ];
 
+    let scroll = overflow_scroll(content.len(), dialog_area);
     let dialog = Paragraph::new(content)
+        .scroll(scroll)
         .block(
             Block::default()
-                .title(" Create Note ")
+                .title(" Changed on Disk ")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(theme.info))
-                .style(Style::default().bg(theme.background)),
+                .border_style(Style::default().fg(theme.warning))
+                .style(Style::default().bg(theme.dialog.background)),
         )
         .alignment(Alignment::Center);
 
     f.render_widget(dialog, dialog_area);
 }
 
-pub fn render_delete_folder_confirm_dialog(f: &mut Frame, app: &App) {
+pub fn render_heading_rename_dialog(f: &mut Frame, app: &App) {
+    let Some(rename) = &app.pending_heading_rename else {
+        return;
+    };
     let area = f.area();
     let theme = &app.theme;
 
-    let dialog_width = 50.min(area.width.saturating_sub(4));
-    let dialog_height = 11.min(area.height.saturating_sub(4));
-
-    let dialog_area = Rect {
-        x: (area.width.saturating_sub(dialog_width)) / 2,
-        y: (area.height.saturating_sub(dialog_height)) / 2,
-        width: dialog_width,
-        height: dialog_height,
-    };
+    let dialog_area = centered_rect(area, 56, 11);
 
     f.render_widget(Clear, dialog_area);
 
-    let folder_name = app.get_selected_folder_name()
-        .unwrap_or_else(|| "this folder".to_string());
-
-    let content = vec![
+    let links = rename.preview.occurrences();
+    let files = rename.preview.files.len();
+    let mut content = vec![
         Line::from(""),
         Line::from(Span::styled(
-            "Delete folder and all contents?",
-            Style::default().fg(theme.error).add_modifier(Modifier::BOLD),
+            format!(
+                "Update {} link{} in {} other note{}?",
+                links,
+                if links == 1 { "" } else { "s" },
+                files,
+                if files == 1 { "" } else { "s" },
+            ),
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
         Line::from(Span::styled(
-            folder_name,
-            Style::default().fg(theme.foreground),
+            format!("#{} → #{}", rename.old_slug, rename.new_title),
+            Style::default().fg(theme.dialog.text),
+        )),
+        Line::from(Span::styled(
+            crate::app::note_names(&rename.preview.files.iter().map(|(path, _)| path.clone()).collect::<Vec<_>>()),
+            Style::default().fg(theme.muted),
         )),
+    ];
+    if !rename.preview.skipped.is_empty() {
+        content.push(Line::from(Span::styled(
+            format!("Skipping unreadable: {}", crate::app::note_names(&rename.preview.skipped)),
+            Style::default().fg(theme.muted),
+        )));
+    }
+    content.push(Line::from(""));
+    content.push(Line::from(Span::styled(
+        "y: Update  |  n: Only this note",
+        Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+    )));
+
+    let scroll = overflow_scroll(content.len(), dialog_area);
+    let dialog = Paragraph::new(content)
+        .scroll(scroll)
+        .block(
+            Block::default()
+                .title(" Rename Heading ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.warning))
+                .style(Style::default().bg(theme.dialog.background)),
+        )
+        .alignment(Alignment::Center);
+
+    f.render_widget(dialog, dialog_area);
+}
+
+pub fn render_merge_dialog(f: &mut Frame, app: &App) {
+    let Some(merge) = &app.pending_merge else {
+        return;
+    };
+    let (Some(source), Some(target)) = (app.notes.get(merge.source), app.notes.get(merge.target)) else {
+        return;
+    };
+    let area = f.area();
+    let theme = &app.theme;
+
+    let dialog_area = centered_rect(area, 56, 12);
+
+    f.render_widget(Clear, dialog_area);
+
+    let links = merge.preview.occurrences();
+    let files = merge.preview.files.len();
+    let mut content = vec![
         Line::from(""),
         Line::from(Span::styled(
-            "This will delete all notes inside!",
-            Style::default().fg(theme.warning),
+            format!("Merge \"{}\" into \"{}\"?", source.title, target.title),
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
         Line::from(Span::styled(
-            "y: Yes  |  n: No",
-            Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+            format!(
+                "{} link{} in {} note{} will point here, {} goes to .trash",
+                links,
+                if links == 1 { "" } else { "s" },
+                files,
+                if files == 1 { "" } else { "s" },
+                source.title,
+            ),
+            Style::default().fg(theme.dialog.text),
+        )),
+        Line::from(Span::styled(
+            crate::app::note_names(&merge.preview.files.iter().map(|(path, _)| path.clone()).collect::<Vec<_>>()),
+            Style::default().fg(theme.muted),
         )),
     ];
+    if !merge.preview.skipped.is_empty() {
+        content.push(Line::from(Span::styled(
+            format!("Skipping unreadable: {}", crate::app::note_names(&merge.preview.skipped)),
+            Style::default().fg(theme.muted),
+        )));
+    }
+    content.push(Line::from(""));
+    content.push(Line::from(Span::styled(
+        "y: Merge  |  n: Cancel",
+        Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+    )));
 
+    let scroll = overflow_scroll(content.len(), dialog_area);
     let dialog = Paragraph::new(content)
+        .scroll(scroll)
         .block(
             Block::default()
-                .title(" Confirm Delete Folder ")
+                .title(" Merge Notes ")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(theme.error))
-                .style(Style::default().bg(theme.background)),
+                .border_style(Style::default().fg(theme.warning))
+                .style(Style::default().bg(theme.dialog.background)),
         )
         .alignment(Alignment::Center);
 
     f.render_widget(dialog, dialog_area);
 }
 
-pub fn render_rename_note_dialog(f: &mut Frame, app: &App) {
+pub fn render_large_paste_dialog(f: &mut Frame, app: &App) {
+    let Some(text) = &app.large_paste else {
+        return;
+    };
     let area = f.area();
     let theme = &app.theme;
 
-    let dialog_width = 50.min(area.width.saturating_sub(4));
-    let dialog_height = 9.min(area.height.saturating_sub(4));
-
-    let dialog_area = Rect {
-        x: (area.width.saturating_sub(dialog_width)) / 2,
-        y: (area.height.saturating_sub(dialog_height)) / 2,
-        width: dialog_width,
-        height: dialog_height,
-    };
+    let dialog_area = centered_rect(area, 56, 9);
 
     f.render_widget(Clear, dialog_area);
 
+    let lines = text.lines().count();
     let content = vec![
         Line::from(""),
         Line::from(Span::styled(
-            "Enter new name:",
-            Style::default().fg(theme.foreground),
+            format!("Paste {} ({} line{})?", crate::app::format_size(text.len() as u64), lines, if lines == 1 { "" } else { "s" }),
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
-        Line::from(vec![
-            Span::styled("> ", Style::default().fg(theme.warning)),
-            Span::styled(&app.input_buffer, Style::default().fg(theme.foreground)),
-            Span::styled("█", Style::default().fg(theme.cursor)),
-        ]),
+        Line::from(Span::styled(
+            "It goes in a piece at a time; Esc stops it",
+            Style::default().fg(theme.dialog.text),
+        )),
         Line::from(""),
         Line::from(Span::styled(
-            "Enter: Rename  |  Esc: Cancel",
+            "p: Paste  |  n: Paste as new note  |  Esc: Cancel",
             Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
         )),
     ];
@@ -487,275 +559,1105 @@ pub fn render_rename_note_dialog(f: &mut Frame, app: &App) {
     let dialog = Paragraph::new(content)
         .block(
             Block::default()
-                .title(" Rename Note ")
+                .title(" Large Paste ")
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(theme.warning))
-                .style(Style::default().bg(theme.background)),
+                .style(Style::default().bg(theme.dialog.background)),
         )
         .alignment(Alignment::Center);
 
     f.render_widget(dialog, dialog_area);
 }
 
-pub fn render_rename_folder_dialog(f: &mut Frame, app: &App) {
+pub fn render_change_notes_dir_dialog(f: &mut Frame, app: &App) {
     let area = f.area();
     let theme = &app.theme;
-
     let has_error = app.dialog_error.is_some();
-    let dialog_height = if has_error { 11 } else { 9 };
-
-    let dialog_width = 50.min(area.width.saturating_sub(4));
-    let dialog_height = dialog_height.min(area.height.saturating_sub(4));
 
-    let dialog_area = Rect {
-        x: (area.width.saturating_sub(dialog_width)) / 2,
-        y: (area.height.saturating_sub(dialog_height)) / 2,
-        width: dialog_width,
-        height: dialog_height,
-    };
+    let dialog_area = centered_rect(area, 60, if has_error { 11 } else { 9 });
 
     f.render_widget(Clear, dialog_area);
 
     let mut content = vec![
         Line::from(""),
-        Line::from(Span::styled(
-            "Enter new folder name:",
-            Style::default().fg(theme.foreground),
-        )),
+        Line::from(Span::styled("Open notes folder:", Style::default().fg(theme.dialog.text))),
         Line::from(""),
-        Line::from(vec![
-            Span::styled("> ", Style::default().fg(theme.info)),
-            Span::styled(&app.input_buffer, Style::default().fg(theme.foreground)),
-            Span::styled("█", Style::default().fg(theme.cursor)),
-        ]),
+        input_line(Span::styled("> ", Style::default().fg(theme.warning)), &app.input_buffer, theme),
     ];
-
     if let Some(ref error) = app.dialog_error {
         content.push(Line::from(""));
-        content.push(Line::from(Span::styled(
-            error.as_str(),
-            Style::default().fg(theme.error),
-        )));
+        content.push(Line::from(Span::styled(error.as_str(), Style::default().fg(theme.dialog.danger))));
     }
-
     content.push(Line::from(""));
     content.push(Line::from(Span::styled(
-        "Enter: Rename  |  Esc: Cancel",
+        "Enter: Open  |  Tab: Complete  |  Esc: Cancel",
         Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
     )));
 
-    let border_color = if has_error { theme.error } else { theme.info };
-
+    let scroll = overflow_scroll(content.len(), dialog_area);
     let dialog = Paragraph::new(content)
+        .scroll(scroll)
         .block(
             Block::default()
-                .title(" Rename Folder ")
+                .title(" Notes Folder ")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(border_color))
-                .style(Style::default().bg(theme.background)),
+                .border_style(Style::default().fg(if has_error { theme.dialog.danger } else { theme.dialog.border }))
+                .style(Style::default().bg(theme.dialog.background)),
         )
         .alignment(Alignment::Center);
 
     f.render_widget(dialog, dialog_area);
 }
 
-pub fn render_create_folder_dialog(f: &mut Frame, app: &App) {
+pub fn render_create_notes_dir_dialog(f: &mut Frame, app: &App) {
+    let Some(path) = &app.pending_notes_dir else {
+        return;
+    };
     let area = f.area();
     let theme = &app.theme;
 
-    let has_error = app.dialog_error.is_some();
-    let has_context = app.target_folder.is_some();
-    let dialog_height = if has_error { 11 } else if has_context { 10 } else { 9 };
-
-    let dialog_width = 50.min(area.width.saturating_sub(4));
-    let dialog_height = dialog_height.min(area.height.saturating_sub(4));
-
-    let dialog_area = Rect {
-        x: (area.width.saturating_sub(dialog_width)) / 2,
-        y: (area.height.saturating_sub(dialog_height)) / 2,
-        width: dialog_width,
-        height: dialog_height,
-    };
+    let dialog_area = centered_rect(area, 60, 9);
 
     f.render_widget(Clear, dialog_area);
 
-    let mut content = vec![
+    let content = vec![
         Line::from(""),
         Line::from(Span::styled(
-            "Enter folder name:",
-            Style::default().fg(theme.foreground),
+            "This folder doesn't exist yet:",
+            Style::default().fg(theme.dialog.text),
+        )),
+        Line::from(Span::styled(path.display().to_string(), Style::default().fg(theme.warning))),
+        Line::from(""),
+        Line::from(Span::styled(
+            "y: Create and open it  |  n: Cancel",
+            Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
         )),
     ];
 
-    if let Some(ref folder_path) = app.target_folder {
-        if let Some(folder_name) = folder_path.file_name() {
-            content.push(Line::from(Span::styled(
-                format!("in {}/", folder_name.to_string_lossy()),
-                Style::default().fg(theme.info),
-            )));
-        }
-    }
-
-    content.push(Line::from(""));
-    content.push(Line::from(vec![
-        Span::styled("> ", Style::default().fg(theme.info)),
-        Span::styled(&app.input_buffer, Style::default().fg(theme.foreground)),
-        Span::styled("█", Style::default().fg(theme.cursor)),
-    ]));
-
-    if let Some(ref error) = app.dialog_error {
-        content.push(Line::from(""));
-        content.push(Line::from(Span::styled(
-            error.as_str(),
-            Style::default().fg(theme.error),
-        )));
-    }
-
-    content.push(Line::from(""));
-    content.push(Line::from(Span::styled(
-        "Enter: Create  |  Esc: Cancel",
-        Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
-    )));
-
-    let border_color = if has_error { theme.error } else { theme.info };
-
+    let scroll = overflow_scroll(content.len(), dialog_area);
     let dialog = Paragraph::new(content)
+        .scroll(scroll)
         .block(
             Block::default()
-                .title(" New Folder ")
+                .title(" Create Folder ")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(border_color))
-                .style(Style::default().bg(theme.background)),
+                .border_style(Style::default().fg(theme.warning))
+                .style(Style::default().bg(theme.dialog.background)),
         )
         .alignment(Alignment::Center);
 
     f.render_widget(dialog, dialog_area);
 }
 
-pub fn render_create_note_in_folder_dialog(f: &mut Frame, app: &App) {
+pub fn render_global_substitute_dialog(f: &mut Frame, app: &App) {
+    let Some(gsub) = &app.pending_global_substitute else {
+        return;
+    };
     let area = f.area();
     let theme = &app.theme;
 
-    let has_error = app.dialog_error.is_some();
-    let dialog_height = if has_error { 13 } else { 11 };
-
-    let dialog_width = 50.min(area.width.saturating_sub(4));
-    let dialog_height = dialog_height.min(area.height.saturating_sub(4));
-
-    let dialog_area = Rect {
-        x: (area.width.saturating_sub(dialog_width)) / 2,
-        y: (area.height.saturating_sub(dialog_height)) / 2,
-        width: dialog_width,
-        height: dialog_height,
-    };
+    let dialog_area = centered_rect(area, 56, 11);
 
     f.render_widget(Clear, dialog_area);
 
-    let folder_name = app.target_folder
-        .as_ref()
-        .and_then(|p| p.file_name())
-        .map(|n| n.to_string_lossy().to_string())
-        .unwrap_or_else(|| "folder".to_string());
-
+    let occurrences = gsub.preview.occurrences();
+    let files = gsub.preview.files.len();
     let mut content = vec![
         Line::from(""),
         Line::from(Span::styled(
-            "Folder created! Now create your first note:",
-            Style::default().fg(theme.success),
+            format!(
+                "Replace {} occurrence{} in {} note{}?",
+                occurrences,
+                if occurrences == 1 { "" } else { "s" },
+                files,
+                if files == 1 { "" } else { "s" },
+            ),
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
         )),
+        Line::from(""),
         Line::from(Span::styled(
-            format!("in {}/", folder_name),
-            Style::default().fg(theme.info),
+            format!("\"{}\" → \"{}\"", gsub.pattern, gsub.replacement),
+            Style::default().fg(theme.dialog.text),
         )),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("> ", Style::default().fg(theme.warning)),
-            Span::styled(&app.input_buffer, Style::default().fg(theme.foreground)),
-            Span::styled("█", Style::default().fg(theme.cursor)),
-        ]),
     ];
-
-    if let Some(ref error) = app.dialog_error {
-        content.push(Line::from(""));
+    if !gsub.preview.skipped.is_empty() {
         content.push(Line::from(Span::styled(
-            error.as_str(),
-            Style::default().fg(theme.error),
+            format!("Skipping unsaved: {}", crate::app::note_names(&gsub.preview.skipped)),
+            Style::default().fg(theme.muted),
         )));
     }
-
     content.push(Line::from(""));
     content.push(Line::from(Span::styled(
-        "Enter: Create  |  Esc: Cancel (removes folder)",
+        "y: Replace  |  n: Cancel",
         Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
     )));
 
-    let border_color = if has_error { theme.error } else { theme.success };
-
+    let scroll = overflow_scroll(content.len(), dialog_area);
     let dialog = Paragraph::new(content)
+        .scroll(scroll)
         .block(
             Block::default()
-                .title(" New Note in Folder ")
+                .title(" Replace in All Notes ")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(border_color))
-                .style(Style::default().bg(theme.background)),
+                .border_style(Style::default().fg(theme.warning))
+                .style(Style::default().bg(theme.dialog.background)),
         )
         .alignment(Alignment::Center);
 
     f.render_widget(dialog, dialog_area);
 }
 
-pub fn render_empty_directory_dialog(f: &mut Frame, app: &App) {
+pub fn render_bulk_rename_dialog(f: &mut Frame, app: &App) {
+    let Some(bulk) = &app.pending_bulk_rename else {
+        return;
+    };
     let area = f.area();
     let theme = &app.theme;
 
-    let dialog_width = 55.min(area.width.saturating_sub(4));
-    let dialog_height = 12.min(area.height.saturating_sub(4));
+    // Summary and key hint with a blank line around each, inside the border
+    let chrome = 7;
+    let dialog_area = centered_rect(area, 72, (bulk.renames.len() as u16 + chrome).min(22));
+    let rows = (dialog_area.height.saturating_sub(chrome) as usize).max(1);
+
+    f.render_widget(Clear, dialog_area);
+
+    let ready = bulk.ready().count();
+    let conflicts = bulk.conflicts();
+    let links = match bulk.links() {
+        0 => String::new(),
+        1 => " and 1 link to them".to_string(),
+        n => format!(" and {} links to them", n),
+    };
+    let mut summary = vec![Span::styled(
+        format!("Rename {} note{}{}?", ready, if ready == 1 { "" } else { "s" }, links),
+        Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+    )];
+    if conflicts > 0 {
+        summary.push(Span::styled(
+            format!("  {} conflict{} kept as {}", conflicts, if conflicts == 1 { "" } else { "s" }, if conflicts == 1 { "is" } else { "are" }),
+            Style::default().fg(theme.dialog.danger),
+        ));
+    }
+    let mut content = vec![Line::from(""), Line::from(summary).alignment(Alignment::Center), Line::from("")];
+
+    let scroll = bulk.scroll.min(bulk.renames.len().saturating_sub(rows));
+    let name_width = bulk.renames.iter().map(|r| r.old_name.width()).max().unwrap_or(0).min(30);
+    for rename in bulk.renames.iter().skip(scroll).take(rows) {
+        let (style, note) = match &rename.problem {
+            None => (Style::default().fg(theme.dialog.text), String::new()),
+            Some(RenameProblem::Unchanged) => (Style::default().fg(theme.muted), String::new()),
+            Some(problem) => (Style::default().fg(theme.dialog.danger), format!("  ({})", problem.describe())),
+        };
+        let padding = " ".repeat(name_width.saturating_sub(rename.old_name.width()));
+        content.push(Line::from(vec![
+            Span::styled(format!(" {}{} → ", rename.old_name, padding), Style::default().fg(theme.muted)),
+            Span::styled(rename.new_name.clone(), style),
+            Span::styled(note, style.add_modifier(Modifier::ITALIC)),
+        ]));
+    }
+
+    content.push(Line::from(""));
+    content.push(
+        Line::from(Span::styled(
+            "y: Rename  |  n: Cancel  |  j/k: Scroll",
+            Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+        ))
+        .alignment(Alignment::Center),
+    );
+
+    let dialog = Paragraph::new(content).block(
+        Block::default()
+            .title(format!(" Rename /{}/{}/ ", bulk.pattern, bulk.replacement))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.warning))
+            .style(Style::default().bg(theme.dialog.background)),
+    );
+
+    f.render_widget(dialog, dialog_area);
+}
 
-    let dialog_area = Rect {
-        x: (area.width.saturating_sub(dialog_width)) / 2,
-        y: (area.height.saturating_sub(dialog_height)) / 2,
-        width: dialog_width,
-        height: dialog_height,
+pub fn render_section_tasks_dialog(f: &mut Frame, app: &App) {
+    let Some(edit) = &app.pending_section_tasks else {
+        return;
     };
+    let area = f.area();
+    let theme = &app.theme;
+
+    let dialog_area = centered_rect(area, 56, 9);
 
     f.render_widget(Clear, dialog_area);
 
+    let section = match &edit.heading {
+        Some(heading) => format!("\"{}\"", heading),
+        None => "the top of the note".to_string(),
+    };
+    let tasks = format!("{} task{}", edit.affected, if edit.affected == 1 { "" } else { "s" });
+    let question = match (edit.op, edit.archived) {
+        (TaskOp::Complete, _) => format!("Check off {} in {}?", tasks, section),
+        (TaskOp::Clear, true) => format!("Move {} from {} to {}?", tasks, section, ARCHIVE_HEADING),
+        (TaskOp::Clear, false) => format!("Remove {} from {}?", tasks, section),
+    };
     let content = vec![
         Line::from(""),
         Line::from(Span::styled(
-            "Oops! This directory seems empty",
+            question,
             Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
         Line::from(Span::styled(
-            "No markdown notes found in:",
-            Style::default().fg(theme.foreground),
+            "y: Confirm  |  n: Cancel",
+            Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
         )),
-        Line::from(Span::styled(
-            &app.config.notes_dir,
+    ];
+
+    let scroll = overflow_scroll(content.len(), dialog_area);
+    let dialog = Paragraph::new(content)
+        .scroll(scroll)
+        .block(
+            Block::default()
+                .title(" Section Tasks ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.warning))
+                .style(Style::default().bg(theme.dialog.background)),
+        )
+        .alignment(Alignment::Center);
+
+    f.render_widget(dialog, dialog_area);
+}
+
+pub fn render_scaffold_dialog(f: &mut Frame, app: &App) {
+    let Some(prompt) = &app.scaffold_prompt else {
+        return;
+    };
+    let theme = &app.theme;
+    let input_style = Style::default().fg(theme.dialog.text);
+    let cursor = Span::styled("█", Style::default().fg(theme.cursor));
+
+    let mut content = vec![Line::from("")];
+    let hint = if prompt.entries.is_none() {
+        content.push(Line::from(Span::styled(
+            " One note per line; indent to nest, end folders with /",
             Style::default().fg(theme.muted),
-        )),
+        )));
+        content.push(Line::from(""));
+        // The cursor is on the line with as many line breaks before it
+        let before_cursor: String = app.input_buffer.chars().take(app.input_buffer.cursor()).collect();
+        let cursor_row = before_cursor.matches('\n').count();
+        let cursor_col = before_cursor.rsplit('\n').next().map_or(0, |line| line.chars().count());
+        for (i, line) in app.input_buffer.split('\n').enumerate() {
+            let mut spans = vec![Span::styled(" ", input_style)];
+            if i == cursor_row {
+                spans.extend(input_spans(line, cursor_col, input_style, cursor.clone()));
+            } else {
+                spans.push(Span::styled(line.to_string(), input_style));
+            }
+            content.push(Line::from(spans));
+        }
+        "Ctrl+S: Create  |  Tab: Indent  |  Esc: Cancel"
+    } else {
+        content.push(Line::from(Span::styled(
+            " Project name for {{project}}:",
+            Style::default().fg(theme.dialog.text),
+        )));
+        content.push(Line::from(""));
+        content.push(input_line(Span::styled(" > ", Style::default().fg(theme.warning)), &app.input_buffer, theme));
+        "Enter: Create  |  Esc: Cancel"
+    };
+
+    if let Some(ref error) = app.dialog_error {
+        content.push(Line::from(""));
+        content.push(Line::from(Span::styled(format!(" {}", error), Style::default().fg(theme.dialog.danger))));
+    }
+    content.push(Line::from(""));
+    content.push(Line::from(Span::styled(
+        format!(" {}", hint),
+        Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+    )));
+
+    let height = (content.len() as u16 + 2).clamp(9, 24);
+    let dialog_area = centered_rect(f.area(), 60, height);
+    f.render_widget(Clear, dialog_area);
+
+    let scroll = overflow_scroll(content.len(), dialog_area);
+    let dialog = Paragraph::new(content).scroll(scroll).block(
+        Block::default()
+            .title(format!(" Scaffold in {} ", prompt.folder.file_name().unwrap_or_default().to_string_lossy()))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.dialog.border))
+            .style(Style::default().bg(theme.dialog.background)),
+    );
+
+    f.render_widget(dialog, dialog_area);
+}
+
+pub fn render_note_stats_dialog(f: &mut Frame, app: &App) {
+    let Some(stats) = &app.note_stats else {
+        return;
+    };
+    let theme = &app.theme;
+    let dialog_area = centered_rect(f.area(), 46, 17);
+
+    f.render_widget(Clear, dialog_area);
+
+    let label_style = Style::default().fg(theme.muted);
+    let value_style = Style::default().fg(theme.dialog.text);
+    let row = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("  {:<14}", label), label_style),
+            Span::styled(value, value_style),
+        ])
+    };
+    let headings = stats
+        .headings
+        .iter()
+        .enumerate()
+        .filter(|(_, &count)| count > 0)
+        .map(|(level, count)| format!("H{} {}", level + 1, count))
+        .collect::<Vec<_>>()
+        .join("  ");
+
+    let content = vec![
+        Line::from(""),
+        row("Words", stats.words.to_string()),
+        row("Characters", stats.chars.to_string()),
+        row("Lines", stats.lines.to_string()),
+        row("Paragraphs", stats.paragraphs.to_string()),
+        row("Headings", if headings.is_empty() { "none".to_string() } else { headings }),
+        row("Links", stats.links.to_string()),
+        row("Wiki links", stats.wiki_links.to_string()),
+        row("Images", stats.images.to_string()),
+        row(
+            "Tasks",
+            format!("{} done, {} pending", stats.tasks_done, stats.tasks_pending),
+        ),
+        row("Reading time", format!("{} min", stats.reading_minutes())),
+        Line::from(""),
+        Line::from(Span::styled(
+            "  Esc: Close",
+            Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+        )),
+    ];
+
+    let scroll = overflow_scroll(content.len(), dialog_area);
+    let dialog = Paragraph::new(content).scroll(scroll).block(
+        Block::default()
+            .title(" Note Stats ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.dialog.border))
+            .style(Style::default().bg(theme.dialog.background)),
+    );
+
+    f.render_widget(dialog, dialog_area);
+}
+
+pub fn render_snippets_dialog(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let prefix = &app.config.editor.snippet_prefix;
+    let snippets = &app.config.snippets;
+    let dialog_area = centered_rect(f.area(), 60, snippets.len() as u16 + 6);
+
+    f.render_widget(Clear, dialog_area);
+
+    let trigger_width = snippets.keys().map(|name| prefix.chars().count() + name.chars().count()).max().unwrap_or(0);
+    let mut content = vec![Line::from("")];
+    if snippets.is_empty() {
+        content.push(Line::from(Span::styled("  No snippets in [snippets]", Style::default().fg(theme.muted))));
+    }
+    for (name, text) in snippets {
+        content.push(Line::from(vec![
+            Span::styled(
+                format!("  {:<width$}  ", format!("{}{}", prefix, name), width = trigger_width),
+                Style::default().fg(theme.info),
+            ),
+            // Show line breaks and tabs rather than breaking the row
+            Span::styled(text.replace('\n', "\\n").replace('\t', "\\t"), Style::default().fg(theme.dialog.text)),
+        ]));
+    }
+    content.push(Line::from(""));
+    content.push(Line::from(Span::styled(
+        "  Type a trigger and Space in insert mode. Esc: Close",
+        Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+    )));
+
+    let scroll = overflow_scroll(content.len(), dialog_area);
+    let dialog = Paragraph::new(content).scroll(scroll).block(
+        Block::default()
+            .title(" Snippets ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.dialog.border))
+            .style(Style::default().bg(theme.dialog.background)),
+    );
+
+    f.render_widget(dialog, dialog_area);
+}
+
+pub fn render_config_sources_dialog(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let notes_path = app.config.notes_path();
+    let mut content = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("  {}", Config::config_path().display()),
+            Style::default().fg(theme.info),
+        )),
+    ];
+    if app.folder_config.is_empty() {
+        content.push(Line::from(Span::styled(
+            "  No .ekphos.toml applies to this note",
+            Style::default().fg(theme.muted),
+        )));
+    }
+    for layer in &app.folder_config {
+        let path = layer.path.strip_prefix(&notes_path).unwrap_or(&layer.path);
+        content.push(Line::from(Span::styled(format!("  {}", path.display()), Style::default().fg(theme.info))));
+        match &layer.config {
+            Ok(folder) => {
+                let settings = folder.describe();
+                if settings.is_empty() {
+                    content.push(Line::from(Span::styled("    (sets nothing)", Style::default().fg(theme.muted))));
+                }
+                for setting in settings {
+                    content.push(Line::from(Span::styled(
+                        format!("    {}", setting),
+                        Style::default().fg(theme.dialog.text),
+                    )));
+                }
+            }
+            Err(e) => content.push(Line::from(Span::styled(
+                format!("    Not used: {}", e.lines().next().unwrap_or_default()),
+                Style::default().fg(theme.dialog.danger),
+            ))),
+        }
+    }
+    content.push(Line::from(""));
+    content.push(Line::from(Span::styled(
+        "  Nearer folders win. Esc: Close",
+        Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+    )));
+
+    let dialog_area = centered_rect(f.area(), 60, content.len() as u16 + 2);
+    f.render_widget(Clear, dialog_area);
+    let scroll = overflow_scroll(content.len(), dialog_area);
+    let dialog = Paragraph::new(content).scroll(scroll).block(
+        Block::default()
+            .title(" Config ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.dialog.border))
+            .style(Style::default().bg(theme.dialog.background)),
+    );
+
+    f.render_widget(dialog, dialog_area);
+}
+
+pub fn render_passphrase_dialog(f: &mut Frame, app: &App) {
+    let Some(prompt) = &app.passphrase_prompt else {
+        return;
+    };
+    let area = f.area();
+    let theme = &app.theme;
+
+    let has_error = app.dialog_error.is_some();
+    let dialog_area = centered_rect(area, 50, if has_error { 11 } else { 9 });
+
+    f.render_widget(Clear, dialog_area);
+
+    let title = app.notes.get(prompt.note).map(|n| n.title.as_str()).unwrap_or_default();
+    let (question, action) = match prompt.purpose {
+        PassphrasePurpose::Unlock { .. } => (format!("Passphrase for \"{}\":", title), "Unlock"),
+        PassphrasePurpose::Encrypt => (format!("New passphrase for \"{}\":", title), "Next"),
+        PassphrasePurpose::ConfirmEncrypt => ("Enter the passphrase again:".to_string(), "Encrypt"),
+    };
+    let mut content = vec![
+        Line::from(""),
+        Line::from(Span::styled(question, Style::default().fg(theme.dialog.text))),
+        Line::from(""),
+        Line::from(
+            [
+                vec![Span::styled("> ", Style::default().fg(theme.warning))],
+                input_spans(
+                    &"•".repeat(app.input_buffer.chars().count()),
+                    app.input_buffer.cursor(),
+                    Style::default().fg(theme.dialog.text),
+                    Span::styled("█", Style::default().fg(theme.cursor)),
+                ),
+            ]
+            .concat(),
+        ),
+    ];
+
+    if let Some(ref error) = app.dialog_error {
+        content.push(Line::from(""));
+        content.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(theme.dialog.danger),
+        )));
+    }
+
+    content.push(Line::from(""));
+    content.push(Line::from(Span::styled(
+        format!("Enter: {}  |  Esc: Cancel", action),
+        Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+    )));
+
+    let border_color = if has_error { theme.dialog.danger } else { theme.warning };
+
+    let scroll = overflow_scroll(content.len(), dialog_area);
+    let dialog = Paragraph::new(content)
+        .scroll(scroll)
+        .block(
+            Block::default()
+                .title(" 🔒 Encrypted Note ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border_color))
+                .style(Style::default().bg(theme.dialog.background)),
+        )
+        .alignment(Alignment::Center);
+
+    f.render_widget(dialog, dialog_area);
+}
+
+pub fn render_create_wiki_note_dialog(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let theme = &app.theme;
+
+    let dialog_area = centered_rect(area, 55, 10);
+
+    f.render_widget(Clear, dialog_area);
+
+    let target = app.pending_wiki_target.as_deref().unwrap_or("note");
+
+    let content = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("Note '[[{}]]' doesn't exist.", target),
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Would you like to create it?",
+            Style::default().fg(theme.dialog.text),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "y: Create  |  n: Cancel",
+            Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+        )),
+    ];
+
+    let scroll = overflow_scroll(content.len(), dialog_area);
+    let dialog = Paragraph::new(content)
+        .scroll(scroll)
+        .block(
+            Block::default()
+                .title(" Create Note ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.dialog.border))
+                .style(Style::default().bg(theme.dialog.background)),
+        )
+        .alignment(Alignment::Center);
+
+    f.render_widget(dialog, dialog_area);
+}
+
+pub fn render_confirm_open_dialog(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let theme = &app.theme;
+
+    let dialog_area = centered_rect(area, 60, 10);
+
+    f.render_widget(Clear, dialog_area);
+
+    let target = app.pending_open.target().unwrap_or("");
+
+    let content = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "Open this with the system opener?",
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(target, Style::default().fg(theme.dialog.text))),
+        Line::from(""),
+        Line::from(Span::styled(
+            "y: Open  |  n: Cancel",
+            Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+        )),
+    ];
+
+    let scroll = overflow_scroll(content.len(), dialog_area);
+    let dialog = Paragraph::new(content)
+        .scroll(scroll)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .title(" Open Link ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.warning))
+                .style(Style::default().bg(theme.dialog.background)),
+        )
+        .alignment(Alignment::Center);
+
+    f.render_widget(dialog, dialog_area);
+}
+
+pub fn render_delete_folder_confirm_dialog(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let theme = &app.theme;
+
+    let contents = app.delete_folder_contents.unwrap_or_default();
+    let needs_typed_name = contents.needs_typed_name();
+    let height = match (needs_typed_name, app.dialog_error.is_some()) {
+        (false, _) => 11,
+        (true, false) => 14,
+        (true, true) => 16,
+    };
+    let dialog_area = centered_rect(area, 50, height);
+
+    f.render_widget(Clear, dialog_area);
+
+    let folder_name = app.get_selected_folder_name()
+        .unwrap_or_else(|| "this folder".to_string());
+
+    let mut content = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "Delete folder and all contents?",
+            Style::default().fg(theme.dialog.danger).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            folder_name,
+            Style::default().fg(theme.dialog.text),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("This will delete {}!", contents.summary()),
+            Style::default().fg(theme.warning),
+        )),
+        Line::from(""),
+    ];
+    if needs_typed_name {
+        content.push(Line::from(Span::styled(
+            "Type the folder name to confirm:",
+            Style::default().fg(theme.dialog.text),
+        )));
+        content.push(Line::from(""));
+        content.push(input_line(Span::styled("> ", Style::default().fg(theme.warning)), &app.input_buffer, theme));
+        if let Some(ref error) = app.dialog_error {
+            content.push(Line::from(""));
+            content.push(Line::from(Span::styled(error.as_str(), Style::default().fg(theme.dialog.danger))));
+        }
+        content.push(Line::from(""));
+        content.push(Line::from(Span::styled(
+            "Enter: Delete  |  Esc: Cancel",
+            Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+        )));
+    } else {
+        content.push(Line::from(Span::styled(
+            "y: Yes  |  n: No",
+            Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+        )));
+    }
+
+    let scroll = overflow_scroll(content.len(), dialog_area);
+    let dialog = Paragraph::new(content)
+        .scroll(scroll)
+        .block(
+            Block::default()
+                .title(" Confirm Delete Folder ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.dialog.danger))
+                .style(Style::default().bg(theme.dialog.background)),
+        )
+        .alignment(Alignment::Center);
+
+    f.render_widget(dialog, dialog_area);
+}
+
+/// One confirmation for everything marked in the sidebar, each folder with
+/// what it holds.
+pub fn render_delete_marked_dialog(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let theme = &app.theme;
+    let items = &app.delete_marked;
+    let has_folders = items.iter().any(|(_, contents)| contents.is_some());
+    let height = items.len() as u16 + if has_folders { 10 } else { 8 };
+    let dialog_area = centered_rect(area, 60, height);
+
+    f.render_widget(Clear, dialog_area);
+
+    let notes_path = app.config.notes_path();
+    let mut content = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("Delete {} marked items?", items.len()),
+            Style::default().fg(theme.dialog.danger).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    for (path, contents) in items {
+        let relative = path.strip_prefix(&notes_path).unwrap_or(path).display().to_string();
+        content.push(Line::from(match contents {
+            Some(contents) => vec![
+                Span::styled(format!("{}/", relative), Style::default().fg(theme.dialog.text)),
+                Span::styled(format!("  {}", contents.summary()), Style::default().fg(theme.warning)),
+            ],
+            None => vec![Span::styled(relative, Style::default().fg(theme.dialog.text))],
+        }));
+    }
+    content.push(Line::from(""));
+    if has_folders {
+        content.push(Line::from(Span::styled(
+            "Folders are deleted with everything in them!",
+            Style::default().fg(theme.warning),
+        )));
+        content.push(Line::from(""));
+    }
+    content.push(Line::from(Span::styled(
+        "y: Yes  |  n: No",
+        Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+    )));
+
+    let scroll = overflow_scroll(content.len(), dialog_area);
+    let dialog = Paragraph::new(content)
+        .scroll(scroll)
+        .block(
+            Block::default()
+                .title(" Confirm Delete ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.dialog.danger))
+                .style(Style::default().bg(theme.dialog.background)),
+        )
+        .alignment(Alignment::Center);
+
+    f.render_widget(dialog, dialog_area);
+}
+
+pub fn render_rename_note_dialog(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let theme = &app.theme;
+
+    let has_error = app.dialog_error.is_some();
+    let dialog_height = if has_error { 11 } else { 9 };
+
+    let dialog_area = centered_rect(area, 50, dialog_height);
+
+    f.render_widget(Clear, dialog_area);
+
+    let mut content = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "Enter new name:",
+            Style::default().fg(theme.dialog.text),
+        )),
+        Line::from(""),
+        input_line(Span::styled("> ", Style::default().fg(theme.warning)), &app.input_buffer, theme),
+    ];
+
+    if let Some(ref error) = app.dialog_error {
+        content.push(Line::from(""));
+        content.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(theme.dialog.danger),
+        )));
+    }
+
+    content.push(Line::from(""));
+    content.push(Line::from(Span::styled(
+        "Enter: Rename  |  Esc: Cancel",
+        Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+    )));
+
+    let border_color = if has_error { theme.dialog.danger } else { theme.warning };
+
+    let scroll = overflow_scroll(content.len(), dialog_area);
+    let dialog = Paragraph::new(content)
+        .scroll(scroll)
+        .block(
+            Block::default()
+                .title(" Rename Note ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border_color))
+                .style(Style::default().bg(theme.dialog.background)),
+        )
+        .alignment(Alignment::Center);
+
+    f.render_widget(dialog, dialog_area);
+}
+
+pub fn render_rename_folder_dialog(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let theme = &app.theme;
+
+    let has_error = app.dialog_error.is_some();
+    let dialog_height = if has_error { 11 } else { 9 };
+
+    let dialog_area = centered_rect(area, 50, dialog_height);
+
+    f.render_widget(Clear, dialog_area);
+
+    let mut content = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "Enter new folder name:",
+            Style::default().fg(theme.dialog.text),
+        )),
+        Line::from(""),
+        input_line(Span::styled("> ", Style::default().fg(theme.info)), &app.input_buffer, theme),
+    ];
+
+    if let Some(ref error) = app.dialog_error {
+        content.push(Line::from(""));
+        content.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(theme.dialog.danger),
+        )));
+    }
+
+    content.push(Line::from(""));
+    content.push(Line::from(Span::styled(
+        "Enter: Rename  |  Esc: Cancel",
+        Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+    )));
+
+    let border_color = if has_error { theme.dialog.danger } else { theme.dialog.border };
+
+    let scroll = overflow_scroll(content.len(), dialog_area);
+    let dialog = Paragraph::new(content)
+        .scroll(scroll)
+        .block(
+            Block::default()
+                .title(" Rename Folder ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border_color))
+                .style(Style::default().bg(theme.dialog.background)),
+        )
+        .alignment(Alignment::Center);
+
+    f.render_widget(dialog, dialog_area);
+}
+
+pub fn render_create_folder_dialog(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let theme = &app.theme;
+
+    let has_error = app.dialog_error.is_some();
+    let has_context = app.target_folder.is_some();
+    let dialog_height = if has_error { 11 } else if has_context { 10 } else { 9 };
+
+    let dialog_area = centered_rect(area, 50, dialog_height);
+
+    f.render_widget(Clear, dialog_area);
+
+    let mut content = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "Enter folder name:",
+            Style::default().fg(theme.dialog.text),
+        )),
+    ];
+
+    if let Some(ref folder_path) = app.target_folder {
+        if let Some(folder_name) = folder_path.file_name() {
+            content.push(Line::from(Span::styled(
+                format!("in {}/", folder_name.to_string_lossy()),
+                Style::default().fg(theme.info),
+            )));
+        }
+    }
+
+    content.push(Line::from(""));
+    content.push(input_line(Span::styled("> ", Style::default().fg(theme.info)), &app.input_buffer, theme));
+
+    if let Some(ref error) = app.dialog_error {
+        content.push(Line::from(""));
+        content.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(theme.dialog.danger),
+        )));
+    }
+
+    content.push(Line::from(""));
+    content.push(Line::from(Span::styled(
+        "Enter: Create  |  Esc: Cancel",
+        Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+    )));
+
+    let border_color = if has_error { theme.dialog.danger } else { theme.dialog.border };
+
+    let scroll = overflow_scroll(content.len(), dialog_area);
+    let dialog = Paragraph::new(content)
+        .scroll(scroll)
+        .block(
+            Block::default()
+                .title(" New Folder ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border_color))
+                .style(Style::default().bg(theme.dialog.background)),
+        )
+        .alignment(Alignment::Center);
+
+    f.render_widget(dialog, dialog_area);
+}
+
+pub fn render_create_note_in_folder_dialog(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let theme = &app.theme;
+
+    let has_error = app.dialog_error.is_some();
+    let dialog_height = if has_error { 13 } else { 11 };
+
+    let dialog_area = centered_rect(area, 50, dialog_height);
+
+    f.render_widget(Clear, dialog_area);
+
+    let folder_name = app.target_folder
+        .as_ref()
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "folder".to_string());
+
+    let mut content = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "Folder created! Now create your first note:",
+            Style::default().fg(theme.dialog.confirm),
+        )),
+        Line::from(Span::styled(
+            format!("in {}/", folder_name),
+            Style::default().fg(theme.info),
+        )),
+        Line::from(""),
+        input_line(Span::styled("> ", Style::default().fg(theme.warning)), &app.input_buffer, theme),
+    ];
+
+    if let Some(ref error) = app.dialog_error {
+        content.push(Line::from(""));
+        content.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(theme.dialog.danger),
+        )));
+    }
+
+    content.push(Line::from(""));
+    content.push(Line::from(Span::styled(
+        "Enter: Create  |  Esc: Cancel (removes folder)",
+        Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+    )));
+
+    let border_color = if has_error { theme.dialog.danger } else { theme.dialog.confirm };
+
+    let scroll = overflow_scroll(content.len(), dialog_area);
+    let dialog = Paragraph::new(content)
+        .scroll(scroll)
+        .block(
+            Block::default()
+                .title(" New Note in Folder ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border_color))
+                .style(Style::default().bg(theme.dialog.background)),
+        )
+        .alignment(Alignment::Center);
+
+    f.render_widget(dialog, dialog_area);
+}
+
+pub fn render_empty_directory_dialog(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let theme = &app.theme;
+
+    let dialog_area = centered_rect(area, 55, 12);
+
+    f.render_widget(Clear, dialog_area);
+
+    let content = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "Oops! This directory seems empty",
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "No markdown notes found in:",
+            Style::default().fg(theme.dialog.text),
+        )),
+        Line::from(Span::styled(
+            &app.config.notes_dir,
+            Style::default().fg(theme.muted),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Press 'n' to create your first note!",
+            Style::default().fg(theme.dialog.confirm),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Press Enter or Esc to continue",
+            Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+        )),
+    ];
+
+    let scroll = overflow_scroll(content.len(), dialog_area);
+    let dialog = Paragraph::new(content)
+        .scroll(scroll)
+        .block(
+            Block::default()
+                .title(" Getting Started ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.warning))
+                .style(Style::default().bg(theme.dialog.background)),
+        )
+        .alignment(Alignment::Center);
+
+    f.render_widget(dialog, dialog_area);
+}
+
+pub fn render_scan_errors_dialog(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let theme = &app.theme;
+    let none_read = app.notes.is_empty();
+
+    let listed = app.scan_errors.len().min(10) as u16;
+    let dialog_area = centered_rect(area, 70, listed + 9);
+
+    f.render_widget(Clear, dialog_area);
+
+    let count = app.scan_errors.len();
+    let heading = if none_read {
+        "Notes were found, but none could be read".to_string()
+    } else {
+        format!("{} note{} couldn't be read", count, if count == 1 { "" } else { "s" })
+    };
+    let root = app.config.notes_path();
+    let mut content = vec![
         Line::from(""),
-        Line::from(Span::styled(
-            "Press 'n' to create your first note!",
-            Style::default().fg(theme.success),
-        )),
+        Line::from(Span::styled(heading, Style::default().fg(theme.warning).add_modifier(Modifier::BOLD))),
+        Line::from(Span::styled(app.config.notes_dir.as_str(), Style::default().fg(theme.muted))),
         Line::from(""),
-        Line::from(Span::styled(
-            "Press Enter or Esc to continue",
-            Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
-        )),
     ];
+    let scroll = app.scan_errors_scroll.min(count.saturating_sub(listed as usize));
+    for error in app.scan_errors.iter().skip(scroll).take(listed as usize) {
+        let path = error.path.strip_prefix(&root).unwrap_or(&error.path);
+        content.push(Line::from(vec![
+            Span::styled(format!(" {}", path.display()), Style::default().fg(theme.dialog.text)),
+            Span::styled(format!("  {}", error.message), Style::default().fg(theme.muted)),
+        ]));
+    }
+    content.push(Line::from(""));
+    content.push(Line::from(Span::styled(
+        if none_read { "r: Retry  |  n: New note  |  Esc: Close" } else { "j/k: Scroll  |  r: Retry  |  Esc: Close" },
+        Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+    )));
 
     let dialog = Paragraph::new(content)
         .block(
             Block::default()
-                .title(" Getting Started ")
+                .title(if none_read { " No Readable Notes " } else { " Unreadable Notes " })
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(theme.warning))
-                .style(Style::default().bg(theme.background)),
+                .style(Style::default().bg(theme.dialog.background)),
         )
-        .alignment(Alignment::Center);
+        .alignment(Alignment::Left);
 
     f.render_widget(dialog, dialog_area);
 }
@@ -766,15 +1668,7 @@ pub fn render_help_dialog(f: &mut Frame, app: &mut App) {
     let dialog_theme = &theme.dialog;
 
     // Calculate centered dialog area - wider for two columns
-    let dialog_width = 90.min(area.width.saturating_sub(4));
-    let dialog_height = area.height.saturating_sub(4).min(50);
-
-    let dialog_area = Rect {
-        x: (area.width.saturating_sub(dialog_width)) / 2,
-        y: (area.height.saturating_sub(dialog_height)) / 2,
-        width: dialog_width,
-        height: dialog_height,
-    };
+    let dialog_area = centered_rect(area, 90, 50);
 
     // Clear the area behind the dialog
     f.render_widget(Clear, dialog_area);
@@ -788,17 +1682,14 @@ pub fn render_help_dialog(f: &mut Frame, app: &mut App) {
     let inner_area = block.inner(dialog_area);
     f.render_widget(block, dialog_area);
 
-    let columns = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(inner_area);
-
     let key_style = Style::default().fg(theme.warning);
     let desc_style = Style::default().fg(dialog_theme.text);
     let header_style = Style::default().fg(dialog_theme.title).add_modifier(Modifier::BOLD);
     let subheader_style = Style::default().fg(theme.info).add_modifier(Modifier::BOLD);
+    // Chords the terminal can't send are shown as their leader sequence
+    let chord = |keys: &str| format!(" {:<10}", app.capabilities.key_label(keys, app.leader_key, &app.leader_map));
 
-    let left_content = vec![
+    let mut left_content = vec![
         Line::from(""),
         Line::from(Span::styled(" Global", header_style)),
         Line::from(vec![
@@ -835,7 +1726,11 @@ pub fn render_help_dialog(f: &mut Frame, app: &mut App) {
         ]),
         Line::from(vec![
             Span::styled(" Ctrl+f    ", key_style),
-            Span::styled("Find in buffer", desc_style),
+            Span::styled("Find in buffer (↑↓ history)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" Alt+f     ", key_style),
+            Span::styled("Find word under cursor", desc_style),
         ]),
         Line::from(vec![
             Span::styled(" Ctrl+k    ", key_style),
@@ -850,11 +1745,15 @@ pub fn render_help_dialog(f: &mut Frame, app: &mut App) {
             Span::styled("Open today's journal", desc_style),
         ]),
         Line::from(vec![
-            Span::styled(" Ctrl+z    ", key_style),
+            Span::styled(" Z         ", key_style),
             Span::styled("Toggle zen mode", desc_style),
         ]),
         Line::from(vec![
-            Span::styled(" Ctrl+m    ", key_style),
+            Span::styled(" Ctrl+z    ", key_style),
+            Span::styled("Suspend to the shell (fg to return)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(chord("Ctrl+m"), key_style),
             Span::styled("Toggle frontmatter", desc_style),
         ]),
         Line::from(vec![
@@ -862,7 +1761,7 @@ pub fn render_help_dialog(f: &mut Frame, app: &mut App) {
             Span::styled("Reload files from disk", desc_style),
         ]),
         Line::from(vec![
-            Span::styled(" Ctrl+Sh+R ", key_style),
+            Span::styled(chord("Ctrl+Sh+R"), key_style),
             Span::styled("Reload config/theme", desc_style),
         ]),
         Line::from(""),
@@ -885,16 +1784,32 @@ pub fn render_help_dialog(f: &mut Frame, app: &mut App) {
         ]),
         Line::from(vec![
             Span::styled(" d         ", key_style),
-            Span::styled("Delete", desc_style),
+            Span::styled("Delete (the marked items if any)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" m         ", key_style),
+            Span::styled("Move marked items (or this one) to a folder", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" Space     ", key_style),
+            Span::styled("Mark / unmark (V: mark range, Esc: clear)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" p         ", key_style),
+            Span::styled("Pin to top / Unpin (paste after x)", desc_style),
         ]),
         Line::from(vec![
             Span::styled(" e         ", key_style),
-            Span::styled("Edit note", desc_style),
+            Span::styled("Edit note / Create folder index", desc_style),
         ]),
         Line::from(vec![
             Span::styled(" /         ", key_style),
             Span::styled("Search notes", desc_style),
         ]),
+        Line::from(vec![
+            Span::styled(" f         ", key_style),
+            Span::styled("Show all / modified today / this week", desc_style),
+        ]),
         Line::from(""),
         Line::from(Span::styled(" Content View", header_style)),
         Line::from(vec![
@@ -913,10 +1828,74 @@ pub fn render_help_dialog(f: &mut Frame, app: &mut App) {
             Span::styled(" G         ", key_style),
             Span::styled("Go to end", desc_style),
         ]),
+        Line::from(vec![
+            Span::styled(" Home/End  ", key_style),
+            Span::styled("Go to beginning/end", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" PgUp/PgDn ", key_style),
+            Span::styled("Page up/down in any panel", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" 5j / 42G  ", key_style),
+            Span::styled("Count / Go to source line", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :42 :+5   ", key_style),
+            Span::styled("Go to line, relative or :50%", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" { / }     ", key_style),
+            Span::styled("Previous/Next paragraph", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" H/M/L     ", key_style),
+            Span::styled("Top/Middle/Bottom of screen", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" gs        ", key_style),
+            Span::styled("Toggle source view", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" gy        ", key_style),
+            Span::styled("Copy note as HTML", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" gr        ", key_style),
+            Span::styled("Refile list item to another note", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" gm / gc   ", key_style),
+            Span::styled("Move/Copy section to another note", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" gp / go   ", key_style),
+            Span::styled("Copy path/Show in file manager", desc_style),
+        ]),
         Line::from(vec![
             Span::styled(" Space     ", key_style),
             Span::styled("Toggle task/Open link", desc_style),
         ]),
+        Line::from(vec![
+            Span::styled(" C / X     ", key_style),
+            Span::styled("Check off/Clear section tasks", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" u         ", key_style),
+            Span::styled("Undo task edit", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" y         ", key_style),
+            Span::styled("Copy link URL or path", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" Y         ", key_style),
+            Span::styled("Copy a link to a note", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" r         ", key_style),
+            Span::styled("Rename note title", desc_style),
+        ]),
         Line::from(vec![
             Span::styled(" ]/[       ", key_style),
             Span::styled("Next/Previous link", desc_style),
@@ -933,12 +1912,36 @@ pub fn render_help_dialog(f: &mut Frame, app: &mut App) {
             Span::styled(" zR        ", key_style),
             Span::styled("Unfold all headings", desc_style),
         ]),
+    ];
+    if app.capabilities.mouse {
+        left_content.extend([
+            Line::from(""),
+            Line::from(Span::styled(" Mouse", header_style)),
+            Line::from(vec![
+                Span::styled(" Click     ", key_style),
+                Span::styled("Focus a panel, open, place the cursor", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled(" Drag      ", key_style),
+                Span::styled("Select text in the editor", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled(" Wheel     ", key_style),
+                Span::styled("Scroll the panel under the pointer", desc_style),
+            ]),
+            Line::from(vec![
+                Span::styled(" Right-click", key_style),
+                Span::styled("Editor menu", desc_style),
+            ]),
+        ]);
+    }
+    left_content.extend([
         Line::from(""),
         Line::from(Span::styled(
             " Press Esc or ? to close",
             Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
         )),
-    ];
+    ]);
 
     let right_content = vec![
         Line::from(""),
@@ -999,6 +2002,18 @@ pub fn render_help_dialog(f: &mut Frame, app: &mut App) {
             Span::styled(" %         ", key_style),
             Span::styled("Matching bracket", desc_style),
         ]),
+        Line::from(vec![
+            Span::styled(" ]c/[c     ", key_style),
+            Span::styled("Next/previous change", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" ]d/[d     ", key_style),
+            Span::styled("Next/previous markdown warning", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" Space     ", key_style),
+            Span::styled("Leader ([leader] config)", desc_style),
+        ]),
         Line::from(""),
         Line::from(Span::styled("  Find Character", subheader_style)),
         Line::from(vec![
@@ -1039,6 +2054,10 @@ pub fn render_help_dialog(f: &mut Frame, app: &mut App) {
             Span::styled(" D/C/Y     ", key_style),
             Span::styled("Operate to line end", desc_style),
         ]),
+        Line::from(vec![
+            Span::styled(" [N]gl     ", key_style),
+            Span::styled("Line(s) to the next kind of list", desc_style),
+        ]),
         Line::from(""),
         Line::from(Span::styled("  Text Objects (inner/around)", subheader_style)),
         Line::from(vec![
@@ -1107,6 +2126,14 @@ pub fn render_help_dialog(f: &mut Frame, app: &mut App) {
             Span::styled(" ~         ", key_style),
             Span::styled("Toggle case", desc_style),
         ]),
+        Line::from(vec![
+            Span::styled(" Ctrl+a/x  ", key_style),
+            Span::styled("Add to / subtract from number", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" g Ctrl+a  ", key_style),
+            Span::styled("Add 1, 2, 3… down a selection", desc_style),
+        ]),
         Line::from(""),
         Line::from(Span::styled("  Registers", subheader_style)),
         Line::from(vec![
@@ -1135,12 +2162,20 @@ pub fn render_help_dialog(f: &mut Frame, app: &mut App) {
             Span::styled(" @@        ", key_style),
             Span::styled("Repeat last macro", desc_style),
         ]),
+        Line::from(vec![
+            Span::styled(" :macros   ", key_style),
+            Span::styled("List, delete or move macros", desc_style),
+        ]),
         Line::from(""),
         Line::from(Span::styled("  Marks", subheader_style)),
         Line::from(vec![
             Span::styled(" m{a-z}    ", key_style),
             Span::styled("Set mark", desc_style),
         ]),
+        Line::from(vec![
+            Span::styled(" m{A-Z}    ", key_style),
+            Span::styled("Set global mark (any note)", desc_style),
+        ]),
         Line::from(vec![
             Span::styled(" '{a-z}    ", key_style),
             Span::styled("Jump to mark (line)", desc_style),
@@ -1149,6 +2184,10 @@ pub fn render_help_dialog(f: &mut Frame, app: &mut App) {
             Span::styled(" `{a-z}    ", key_style),
             Span::styled("Jump to mark (exact)", desc_style),
         ]),
+        Line::from(vec![
+            Span::styled(" `` / ''   ", key_style),
+            Span::styled("Back to position before jump", desc_style),
+        ]),
         Line::from(""),
         Line::from(Span::styled("  Scrolling", subheader_style)),
         Line::from(vec![
@@ -1174,6 +2213,56 @@ pub fn render_help_dialog(f: &mut Frame, app: &mut App) {
             Span::styled("Delete 2 lines", desc_style),
         ]),
         Line::from(""),
+        Line::from(Span::styled("  Insert Mode", subheader_style)),
+        Line::from(vec![
+            Span::styled(" Ctrl+w    ", key_style),
+            Span::styled("Delete word before cursor", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" Ctrl+u    ", key_style),
+            Span::styled("Delete to line start", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" Ctrl+h    ", key_style),
+            Span::styled("Backspace", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" Ctrl+r{r} ", key_style),
+            Span::styled("Insert register contents", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" ;dt Space ", key_style),
+            Span::styled("Expand a snippet (Ctrl+v Space: don't)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" Ctrl+n/p  ", key_style),
+            Span::styled("Complete word (next/prev)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" Ctrl+l    ", key_style),
+            Span::styled("Pick a note to link to", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" [[ / ](   ", key_style),
+            Span::styled("Complete wiki link / file link", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" Ctrl+←/→  ", key_style),
+            Span::styled("Move by word", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" Shift+arr ", key_style),
+            Span::styled("Select (with Ctrl: by word)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" Ctrl+c/x  ", key_style),
+            Span::styled("Copy/cut the selection", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" Ctrl+Bksp ", key_style),
+            Span::styled("Delete word before/after (Del)", desc_style),
+        ]),
+        Line::from(""),
         Line::from(Span::styled("  Save/Exit", subheader_style)),
         Line::from(vec![
             Span::styled(" Ctrl+s    ", key_style),
@@ -1187,9 +2276,189 @@ pub fn render_help_dialog(f: &mut Frame, app: &mut App) {
             Span::styled(" :w/:q/:wq ", key_style),
             Span::styled("Write/Quit/Both", desc_style),
         ]),
+        Line::from(vec![
+            Span::styled(" :e {file} ", key_style),
+            Span::styled("Edit any file, even outside notes", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :%s/a/b/g ", key_style),
+            Span::styled("Replace (:s line, v then : selection)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :%s/a/b/gc", key_style),
+            Span::styled("Replace, confirming each (y/n/a/q/l)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :%s/a/b/gp", key_style),
+            Span::styled("Replace, keeping each match's case", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :gsub/a/b/", key_style),
+            Span::styled("Replace in every note (n: count only)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :bulk-rename", key_style),
+            Span::styled(" Rename marked or filtered notes by /a/b/", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :cn/:cp   ", key_style),
+            Span::styled("Next/prev quickfix entry (Ctrl+N/P)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :copen    ", key_style),
+            Span::styled("Quickfix window (:cclose, Space c)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :checklinks", key_style),
+            Span::styled("List broken wiki links", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :export   ", key_style),
+            Span::styled("Note to html or pdf next to its file", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :copy html", key_style),
+            Span::styled("Note or range to the clipboard as HTML", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :graph    ", key_style),
+            Span::styled("export dot/json [path]: the link graph", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :link     ", key_style),
+            Span::styled("Pick a note and insert a link", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :tasks    ", key_style),
+            Span::styled("complete/clear the section's tasks", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :encrypt  ", key_style),
+            Span::styled("Passphrase-protect the note (:decrypt)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :stats    ", key_style),
+            Span::styled("Word counts and structure of the note", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :snippets ", key_style),
+            Span::styled("List the insert-mode snippets", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :scaffold ", key_style),
+            Span::styled("Create notes from an outline or template", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :rename-heading", key_style),
+            Span::styled(" Retitle a heading and links to it", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :promote  ", key_style),
+            Span::styled("Heading and subheadings a level up (:demote down)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :listify  ", key_style),
+            Span::styled("bullet/ordered/task/none; ! skips nested lines", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :config   ", key_style),
+            Span::styled("Config files the note's settings come from", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :history  ", key_style),
+            Span::styled("View or restore snapshots (snapshots.keep)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :vault    ", key_style),
+            Span::styled("Switch to another vault (Leader v)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :close-others", key_style),
+            Span::styled(" Collapse folders not leading to the note", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :refile   ", key_style),
+            Span::styled("Move list item under a note's heading (gr)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :movesection", key_style),
+            Span::styled(" Move heading's section to a note (gm)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :copysection", key_style),
+            Span::styled(" Copy heading's section to a note (gc)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :prose    ", key_style),
+            Span::styled("List weasel words, passive voice, long sentences", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :narrow   ", key_style),
+            Span::styled("Edit only the section under the heading", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :widen    ", key_style),
+            Span::styled("Back to the whole note after :narrow", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :merge    ", key_style),
+            Span::styled("Merge a duplicate note into this one", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :cd       ", key_style),
+            Span::styled("Open another notes folder (Tab completes)", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :path     ", key_style),
+            Span::styled("Copy the note's absolute path", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :reveal   ", key_style),
+            Span::styled("Show the note in the file manager", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :errors   ", key_style),
+            Span::styled("List notes that couldn't be read", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :yanks    ", key_style),
+            Span::styled("Pick a recent yank or delete to paste", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :toc      ", key_style),
+            Span::styled("Insert or update a table of contents", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" :set a=b  ", key_style),
+            Span::styled("reading.max_width, reading.align, editor.max_width", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" C-p/C-n   ", key_style),
+            Span::styled("After p: swap in an older / newer yank", desc_style),
+        ]),
+        Line::from(vec![
+            Span::styled(" zc/zo/za  ", key_style),
+            Span::styled("Fold / unfold a code block or frontmatter", desc_style),
+        ]),
         Line::from(""),
     ];
 
+    // Too narrow for two columns: one column with the right one below the left
+    let (left_content, right_content) = if inner_area.width < HELP_TWO_COLUMN_MIN_WIDTH {
+        (left_content.into_iter().chain(right_content).collect(), Vec::new())
+    } else {
+        (left_content, right_content)
+    };
+    let column_constraints = if right_content.is_empty() {
+        [Constraint::Percentage(100), Constraint::Length(0)]
+    } else {
+        [Constraint::Percentage(50), Constraint::Percentage(50)]
+    };
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(column_constraints)
+        .split(inner_area);
+
     // Calculate total lines and visible height
     let left_total = left_content.len();
     let right_total = right_content.len();
@@ -1224,15 +2493,7 @@ pub fn render_directory_not_found_dialog(f: &mut Frame, app: &App) {
     let area = f.area();
     let theme = &app.theme;
 
-    let dialog_width = 58.min(area.width.saturating_sub(4));
-    let dialog_height = 14.min(area.height.saturating_sub(4));
-
-    let dialog_area = Rect {
-        x: (area.width.saturating_sub(dialog_width)) / 2,
-        y: (area.height.saturating_sub(dialog_height)) / 2,
-        width: dialog_width,
-        height: dialog_height,
-    };
+    let dialog_area = centered_rect(area, 58, 14);
 
     f.render_widget(Clear, dialog_area);
 
@@ -1240,12 +2501,12 @@ pub fn render_directory_not_found_dialog(f: &mut Frame, app: &App) {
         Line::from(""),
         Line::from(Span::styled(
             "Directory Not Found",
-            Style::default().fg(theme.error).add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.dialog.danger).add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
         Line::from(Span::styled(
             "The configured notes directory does not exist:",
-            Style::default().fg(theme.foreground),
+            Style::default().fg(theme.dialog.text),
         )),
         Line::from(Span::styled(
             &app.config.notes_dir,
@@ -1254,14 +2515,16 @@ pub fn render_directory_not_found_dialog(f: &mut Frame, app: &App) {
         Line::from(""),
         Line::from(Span::styled(
             "Would you like to create it?",
-            Style::default().fg(theme.foreground),
+            Style::default().fg(theme.dialog.text),
         )),
         Line::from(""),
         Line::from(vec![
-            Span::styled("c", Style::default().fg(theme.success).add_modifier(Modifier::BOLD)),
-            Span::styled(" Create directory  ", Style::default().fg(theme.foreground)),
-            Span::styled("q", Style::default().fg(theme.error).add_modifier(Modifier::BOLD)),
-            Span::styled(" Quit and fix config", Style::default().fg(theme.foreground)),
+            Span::styled("c", Style::default().fg(theme.dialog.confirm).add_modifier(Modifier::BOLD)),
+            Span::styled(" Create directory  ", Style::default().fg(theme.dialog.text)),
+            Span::styled("p", Style::default().fg(theme.info).add_modifier(Modifier::BOLD)),
+            Span::styled(" Different path  ", Style::default().fg(theme.dialog.text)),
+            Span::styled("q", Style::default().fg(theme.dialog.danger).add_modifier(Modifier::BOLD)),
+            Span::styled(" Quit and fix config", Style::default().fg(theme.dialog.text)),
         ]),
         Line::from(""),
         Line::from(Span::styled(
@@ -1270,15 +2533,186 @@ pub fn render_directory_not_found_dialog(f: &mut Frame, app: &App) {
         )),
     ];
 
+    let scroll = overflow_scroll(content.len(), dialog_area);
     let dialog = Paragraph::new(content)
+        .scroll(scroll)
         .block(
             Block::default()
                 .title(" Error ")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(theme.error))
-                .style(Style::default().bg(theme.background)),
+                .border_style(Style::default().fg(theme.dialog.danger))
+                .style(Style::default().bg(theme.dialog.background)),
         )
         .alignment(Alignment::Center);
 
     f.render_widget(dialog, dialog_area);
 }
+
+/// config.toml didn't parse at startup: where, and what can be done about it.
+pub fn render_config_error_dialog(f: &mut Frame, app: &App) {
+    let Some(error) = &app.config_error else {
+        return;
+    };
+    let area = f.area();
+    let theme = &app.theme;
+
+    let dialog_area = centered_rect(area, 72, 17);
+
+    f.render_widget(Clear, dialog_area);
+
+    let mut content = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "The config file has errors",
+            Style::default().fg(theme.dialog.danger).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(error.path.display().to_string(), Style::default().fg(theme.muted))),
+        Line::from(""),
+    ];
+    if let Some((line, column)) = error.position {
+        content.push(Line::from(Span::styled(
+            format!("Line {}, column {}:", line, column),
+            Style::default().fg(theme.dialog.text),
+        )));
+    }
+    content.extend(
+        error
+            .message
+            .lines()
+            .map(|line| Line::from(Span::styled(line.to_string(), Style::default().fg(theme.warning)))),
+    );
+    if let (Some(snippet), Some((_, column))) = (&error.snippet, error.position) {
+        content.push(Line::from(""));
+        content.push(Line::from(Span::styled(format!(" {}", snippet), Style::default().fg(theme.dialog.text))));
+        content.push(Line::from(Span::styled(
+            format!(" {}^", " ".repeat(column - 1)),
+            Style::default().fg(theme.dialog.danger),
+        )));
+    }
+    content.push(Line::from(""));
+    content.push(Line::from(vec![
+        Span::styled("e", Style::default().fg(theme.dialog.confirm).add_modifier(Modifier::BOLD)),
+        Span::styled(" Edit in $EDITOR  ", Style::default().fg(theme.dialog.text)),
+        Span::styled("c", Style::default().fg(theme.info).add_modifier(Modifier::BOLD)),
+        Span::styled(" Continue with defaults  ", Style::default().fg(theme.dialog.text)),
+        Span::styled("q", Style::default().fg(theme.dialog.danger).add_modifier(Modifier::BOLD)),
+        Span::styled(" Quit", Style::default().fg(theme.dialog.text)),
+    ]));
+    content.push(Line::from(""));
+    content.push(Line::from(Span::styled(
+        "The file is left as it is; the defaults are for this session only",
+        Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+    )));
+
+    let scroll = overflow_scroll(content.len(), dialog_area);
+    let dialog = Paragraph::new(content)
+        .scroll(scroll)
+        .block(
+            Block::default()
+                .title(" Config Error ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.dialog.danger))
+                .style(Style::default().bg(theme.dialog.background)),
+        )
+        .alignment(Alignment::Left);
+
+    f.render_widget(dialog, dialog_area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_support::{test_app, TempDir};
+    use crate::app::ContextMenuState;
+    use ratatui::{backend::TestBackend, style::Color, Terminal};
+
+    type Render = fn(&mut Frame, &mut App);
+
+    /// Dialogs and the context menu draw with `theme.dialog` and the neutral
+    /// theme colors, so a theme's dialog section decides how they look.
+    #[test]
+    fn dialogs_only_draw_with_dialog_colors() {
+        let dir = TempDir::new("dialog-colors");
+        std::fs::write(dir.join("Note.md"), "# Note\n").unwrap();
+        let (mut app, _) = test_app(&dir);
+        // Colors no dialog should reach for, unlike anything in theme.dialog
+        let banned = [
+            Color::Rgb(1, 0, 0),
+            Color::Rgb(2, 0, 0),
+            Color::Rgb(3, 0, 0),
+            Color::Rgb(4, 0, 0),
+            Color::Rgb(5, 0, 0),
+            Color::Rgb(6, 0, 0),
+        ];
+        app.theme.background = banned[0];
+        app.theme.background_secondary = banned[1];
+        app.theme.foreground = banned[2];
+        app.theme.error = banned[3];
+        app.theme.success = banned[4];
+        app.theme.primary = banned[5];
+        app.context_menu_state = ContextMenuState::Open { x: 2, y: 2, selected_index: 0 };
+        app.config_error = Some(crate::config::ConfigError {
+            path: "config.toml".into(),
+            message: "invalid table header".to_string(),
+            position: Some((2, 8)),
+            snippet: Some("[editor".to_string()),
+        });
+        // One rename to go ahead and one held back
+        for name in ["Note two", "Taken"] {
+            std::fs::write(dir.join(format!("{}.md", name)), "").unwrap();
+        }
+        app.load_notes_from_dir();
+        app.sidebar_marks.mark(&dir.join("Note.md"));
+        app.sidebar_marks.mark(&dir.join("Note two.md"));
+        app.start_bulk_rename("Note".to_string(), "Taken".to_string(), Default::default());
+        assert_eq!(app.pending_bulk_rename.as_ref().map(|bulk| bulk.conflicts()), Some(1));
+
+        let renders: Vec<(&str, Render)> = vec![
+            ("welcome", |f, app| render_welcome_dialog(f, &app.theme)),
+            ("onboarding", |f, app| render_onboarding_dialog(f, app)),
+            ("create note", |f, app| render_create_note_dialog(f, app)),
+            ("delete confirm", |f, app| render_delete_confirm_dialog(f, app)),
+            ("unsaved changes", |f, app| render_unsaved_changes_dialog(f, app)),
+            ("external change", |f, app| render_external_change_dialog(f, app)),
+            ("heading rename", |f, app| render_heading_rename_dialog(f, app)),
+            ("merge", |f, app| render_merge_dialog(f, app)),
+            ("large paste", |f, app| render_large_paste_dialog(f, app)),
+            ("change notes dir", |f, app| render_change_notes_dir_dialog(f, app)),
+            ("create notes dir", |f, app| render_create_notes_dir_dialog(f, app)),
+            ("global substitute", |f, app| render_global_substitute_dialog(f, app)),
+            ("bulk rename", |f, app| render_bulk_rename_dialog(f, app)),
+            ("section tasks", |f, app| render_section_tasks_dialog(f, app)),
+            ("scaffold", |f, app| render_scaffold_dialog(f, app)),
+            ("note stats", |f, app| render_note_stats_dialog(f, app)),
+            ("snippets", |f, app| render_snippets_dialog(f, app)),
+            ("config sources", |f, app| render_config_sources_dialog(f, app)),
+            ("passphrase", |f, app| render_passphrase_dialog(f, app)),
+            ("create wiki note", |f, app| render_create_wiki_note_dialog(f, app)),
+            ("confirm open", |f, app| render_confirm_open_dialog(f, app)),
+            ("delete folder", |f, app| render_delete_folder_confirm_dialog(f, app)),
+            ("delete marked", |f, app| render_delete_marked_dialog(f, app)),
+            ("rename note", |f, app| render_rename_note_dialog(f, app)),
+            ("rename folder", |f, app| render_rename_folder_dialog(f, app)),
+            ("create folder", |f, app| render_create_folder_dialog(f, app)),
+            ("create note in folder", |f, app| render_create_note_in_folder_dialog(f, app)),
+            ("empty directory", |f, app| render_empty_directory_dialog(f, app)),
+            ("scan errors", |f, app| render_scan_errors_dialog(f, app)),
+            ("help", render_help_dialog),
+            ("directory not found", |f, app| render_directory_not_found_dialog(f, app)),
+            ("config error", |f, app| render_config_error_dialog(f, app)),
+            ("context menu", |f, app| super::super::context_menu::render_context_menu(f, app)),
+        ];
+        for (name, render) in renders {
+            let mut terminal = Terminal::new(TestBackend::new(120, 50)).unwrap();
+            terminal.draw(|f| render(f, &mut app)).unwrap();
+            for cell in terminal.backend().buffer().content() {
+                assert!(
+                    !banned.contains(&cell.fg) && !banned.contains(&cell.bg),
+                    "the {} dialog draws {:?} with a color outside theme.dialog",
+                    name,
+                    cell.symbol()
+                );
+            }
+        }
+    }
+}
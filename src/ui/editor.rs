@@ -6,6 +6,7 @@ use ratatui::{
     Frame,
 };
 
+use super::layout::text_margins;
 use crate::app::{App, BlockInsertMode, VimMode};
 
 pub fn render_editor(f: &mut Frame, app: &mut App, area: Rect) {
@@ -43,6 +44,12 @@ pub fn render_editor(f: &mut Frame, app: &mut App, area: Rect) {
 
     app.editor_area = editor_area;
 
+    // Keep the text to `editor.max_width`, widening the padding around it
+    let (left, right) = (app.config.editor.left_padding, app.config.editor.right_padding);
+    let text_width = (inner_width as u16).saturating_sub(left + right + app.editor.gutter_width());
+    let (extra_left, extra_right) = text_margins(text_width, app.config.editor.max_width, app.config.reading.align);
+    app.editor.set_padding(left + extra_left, right + extra_right);
+
     // Update editor view dimensions and scroll
     app.editor.set_view_size(inner_width, inner_height);
     app.update_editor_scroll(inner_height);
@@ -153,18 +160,27 @@ fn render_zen_status_line(f: &mut Frame, app: &App, area: Rect) {
         }
     };
 
-    let pending_str = match (&app.pending_delete, app.pending_operator) {
-        (Some(_), _) => " [DEL]",
-        (None, Some('d')) => " d-",
-        _ => "",
+    let pending_keys = app.vim.pending_keys();
+    let pending_str = if app.pending_delete.is_some() {
+        " [DEL]".to_string()
+    } else if pending_keys.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", pending_keys)
     };
+    let recording_str = app
+        .vim
+        .macros
+        .recording_register()
+        .map(|reg| format!("recording @{} ", reg))
+        .unwrap_or_default();
 
     let color = if is_command_mode {
         theme.info
     } else {
         match (&app.pending_delete, app.vim_mode) {
             (Some(_), _) => theme.error,
-            (None, VimMode::Normal) if app.pending_operator.is_some() => theme.warning,
+            (None, VimMode::Normal) if app.vim.pending_operator.is_some() => theme.warning,
             (None, VimMode::Normal) => theme.primary,
             (None, VimMode::Insert) => theme.success,
             (None, VimMode::Replace) => theme.warning,
@@ -184,7 +200,7 @@ fn render_zen_status_line(f: &mut Frame, app: &App, area: Rect) {
             (None, VimMode::Visual | VimMode::VisualLine | VimMode::VisualBlock) => {
                 "y: Yank, d: Delete, Esc: Cancel"
             }
-            (None, _) if app.pending_operator == Some('d') => "d: Line, w: Word→, b: Word←",
+            (None, _) if app.vim.pending_operator == Some('d') => "d: Line, w: Word→, b: Word←",
             _ => "Ctrl+S: Save, Esc: Exit",
         }
     };
@@ -199,6 +215,10 @@ fn render_zen_status_line(f: &mut Frame, app: &App, area: Rect) {
         ),
         Span::styled(pending_str, Style::default().fg(color)),
         Span::styled(" │ ", Style::default().fg(theme.border)),
+        Span::styled(
+            recording_str,
+            Style::default().fg(theme.error).add_modifier(Modifier::BOLD),
+        ),
         Span::styled(hint, Style::default().fg(theme.muted)),
     ]);
 
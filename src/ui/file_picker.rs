@@ -6,7 +6,8 @@ use ratatui::{
     Frame,
 };
 
-use crate::app::{App, SearchPickerMode, SearchPickerState};
+use super::dialogs::input_spans;
+use crate::app::{App, PickerPurpose, SearchPickerMode, SearchPickerState};
 
 const POPUP_MAX_WIDTH: u16 = 80;
 const POPUP_MAX_WIDTH_WITH_PREVIEW: u16 = 110;
@@ -20,6 +21,7 @@ const PREVIEW_LINES_AFTER: usize = 8;
 pub fn render_search_picker(f: &mut Frame, app: &mut App) {
     if let SearchPickerState::Open {
         mode,
+        purpose,
         query,
         file_results,
         content_results,
@@ -89,7 +91,12 @@ pub fn render_search_picker(f: &mut Frame, app: &mut App) {
 
         // Render the main popup border first
         let popup_block = Block::default()
-            .title(" Search (Ctrl+K) ")
+            .title(match purpose {
+                PickerPurpose::Open => " Search (Ctrl+K) ".to_string(),
+                PickerPurpose::InsertLink => " Insert Link (note|alias) ".to_string(),
+                PickerPurpose::Refile => format!(" {} to ", app.refile_picker.kind.verb()),
+                PickerPurpose::Merge => " Merge into this note ".to_string(),
+            })
             .title_bottom(Line::from(if results_len == 0 {
                 if *search_in_progress {
                     " ... ".to_string()
@@ -156,11 +163,14 @@ pub fn render_search_picker(f: &mut Frame, app: &mut App) {
                 Span::styled(placeholder, Style::default().fg(theme.muted)),
             ])
         } else {
-            Line::from(vec![
-                Span::raw(" "),
-                Span::styled(query.clone(), Style::default().fg(theme.foreground)),
+            let mut spans = vec![Span::raw(" ")];
+            spans.extend(input_spans(
+                query,
+                query.cursor(),
+                Style::default().fg(theme.foreground),
                 Span::styled("█", Style::default().fg(theme.primary)),
-            ])
+            ));
+            Line::from(spans)
         };
         header_lines.push(input_line);
 
@@ -4,9 +4,9 @@
 use ratatui::{
     buffer::Buffer,
     layout::{Alignment, Rect},
-    style::Style,
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
     Frame,
 };
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
@@ -19,6 +19,18 @@ const NODE_WIDTH: u16 = 3;
 const NODE_HEIGHT: u16 = 2;
 const LABEL_OFFSET: i32 = 1;  // Gap between node and label
 
+// Smallest inner area the graph is drawn in
+const MIN_GRAPH_WIDTH: u16 = 20;
+const MIN_GRAPH_HEIGHT: u16 = 6;
+
+// Side strip listing the selected node's links, shown when there's room
+const LINK_STRIP_WIDTH: u16 = 26;
+const LINK_STRIP_MIN_INNER_WIDTH: u16 = 60;
+
+// The `/` search box
+const SEARCH_WIDTH: u16 = 36;
+const SEARCH_MAX_RESULTS: usize = 8;
+
 pub fn render_graph_view(f: &mut Frame, app: &mut App) {
     let area = f.area();
     let theme = &app.theme;
@@ -33,6 +45,29 @@ pub fn render_graph_view(f: &mut Frame, app: &mut App) {
     let inner = block.inner(area);
     f.render_widget(block, area);
 
+    if inner.width < MIN_GRAPH_WIDTH || inner.height < MIN_GRAPH_HEIGHT {
+        let msg = Paragraph::new("Window too small for the graph (Esc: close)")
+            .style(Style::default().fg(theme.muted))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+        f.render_widget(msg, inner);
+        return;
+    }
+
+    // The strip takes the right edge, so the graph keeps its origin and
+    // mouse hit-testing only has to stop at the graph's width
+    let show_strip = app.graph_view.selected_node.is_some() && inner.width >= LINK_STRIP_MIN_INNER_WIDTH;
+    if !show_strip {
+        app.graph_view.links_focused = false;
+    }
+    let (inner, strip) = if show_strip {
+        let graph = Rect { width: inner.width - LINK_STRIP_WIDTH, ..inner };
+        let strip = Rect { x: graph.x + graph.width, width: LINK_STRIP_WIDTH, ..inner };
+        (graph, Some(strip))
+    } else {
+        (inner, None)
+    };
+
     app.graph_view.view_width = inner.width as f32;
     app.graph_view.view_height = inner.height as f32;
 
@@ -120,7 +155,13 @@ pub fn render_graph_view(f: &mut Frame, app: &mut App) {
     } else {
         std::collections::HashSet::new()
     };
-    let has_selection = app.graph_view.selected_node.is_some();
+    let has_selection = app.graph_view.selected_node.is_some() && app.graph_view.focus_neighborhood;
+    let project = |node: &crate::app::GraphNode| {
+        (
+            ((node.x - vx) * zoom + inner.x as f32) as i32,
+            ((node.y - vy) * zoom + inner.y as f32) as i32,
+        )
+    };
 
     // Layer 1: Draw dimmed edges first (not connected to selected node)
     for edge in &app.graph_view.edges {
@@ -136,26 +177,17 @@ pub fn render_graph_view(f: &mut Frame, app: &mut App) {
             continue; // Draw these later on top
         }
 
-        let from_node = &app.graph_view.nodes[edge.from];
-        let to_node = &app.graph_view.nodes[edge.to];
-
-        let from_screen_x = ((from_node.x - vx) * zoom + inner.x as f32) as i32;
-        let from_screen_y = ((from_node.y - vy) * zoom + inner.y as f32) as i32;
-        let to_screen_x = ((to_node.x - vx) * zoom + inner.x as f32) as i32;
-        let to_screen_y = ((to_node.y - vy) * zoom + inner.y as f32) as i32;
-        let from_center_x = from_screen_x + NODE_WIDTH as i32 / 2;
-        let from_center_y = from_screen_y + NODE_HEIGHT as i32 / 2;
-        let to_center_x = to_screen_x + NODE_WIDTH as i32 / 2;
-        let to_center_y = to_screen_y + NODE_HEIGHT as i32 / 2;
+        let from = project(&app.graph_view.nodes[edge.from]);
+        let to = project(&app.graph_view.nodes[edge.to]);
 
         // Very dimmed edge color when there's a selection (almost invisible for better tracing)
         let edge_color = if has_selection {
-            ratatui::style::Color::Rgb(40, 40, 40) // Very dark, almost invisible
+            theme.background_secondary // Barely off the background
         } else {
             theme.border
         };
 
-        draw_line(buf, from_center_x, from_center_y, to_center_x, to_center_y, edge_color, inner, false);
+        draw_edge(buf, from, to, edge.bidirectional, edge_color, inner, false);
     }
 
     // Layer 2: Draw dimmed nodes (not connected to selected)
@@ -165,8 +197,7 @@ pub fn render_graph_view(f: &mut Frame, app: &mut App) {
             continue; // Draw connected nodes later on top
         }
 
-        let screen_x = ((node.x - vx) * zoom + inner.x as f32) as i32;
-        let screen_y = ((node.y - vy) * zoom + inner.y as f32) as i32;
+        let (screen_x, screen_y) = project(node);
 
         if screen_x < (inner.x as i32 - NODE_WIDTH as i32)
             || screen_x >= (inner.x + inner.width) as i32
@@ -193,19 +224,10 @@ pub fn render_graph_view(f: &mut Frame, app: &mut App) {
             continue; // Already drawn
         }
 
-        let from_node = &app.graph_view.nodes[edge.from];
-        let to_node = &app.graph_view.nodes[edge.to];
+        let from = project(&app.graph_view.nodes[edge.from]);
+        let to = project(&app.graph_view.nodes[edge.to]);
 
-        let from_screen_x = ((from_node.x - vx) * zoom + inner.x as f32) as i32;
-        let from_screen_y = ((from_node.y - vy) * zoom + inner.y as f32) as i32;
-        let to_screen_x = ((to_node.x - vx) * zoom + inner.x as f32) as i32;
-        let to_screen_y = ((to_node.y - vy) * zoom + inner.y as f32) as i32;
-        let from_center_x = from_screen_x + NODE_WIDTH as i32 / 2;
-        let from_center_y = from_screen_y + NODE_HEIGHT as i32 / 2;
-        let to_center_x = to_screen_x + NODE_WIDTH as i32 / 2;
-        let to_center_y = to_screen_y + NODE_HEIGHT as i32 / 2;
-
-        draw_line(buf, from_center_x, from_center_y, to_center_x, to_center_y, theme.primary, inner, true);
+        draw_edge(buf, from, to, edge.bidirectional, theme.primary, inner, true);
     }
 
     // Layer 4: Draw connected and selected nodes on top
@@ -215,8 +237,7 @@ pub fn render_graph_view(f: &mut Frame, app: &mut App) {
             continue; // Already drawn
         }
 
-        let screen_x = ((node.x - vx) * zoom + inner.x as f32) as i32;
-        let screen_y = ((node.y - vy) * zoom + inner.y as f32) as i32;
+        let (screen_x, screen_y) = project(node);
 
         if screen_x < (inner.x as i32 - NODE_WIDTH as i32)
             || screen_x >= (inner.x + inner.width) as i32
@@ -232,47 +253,146 @@ pub fn render_graph_view(f: &mut Frame, app: &mut App) {
         render_node(buf, node, screen_x, screen_y, is_selected, false, node_show_label, theme, inner);
     }
 
+    // Layer 5: the node just picked from the search, drawn out in reverse
+    if let Some(node) = app.graph_view.flash.and_then(|flash| app.graph_view.nodes.get(flash.node)) {
+        let (screen_x, screen_y) = project(node);
+        let flash_style = Style::default().fg(theme.background).bg(theme.warning);
+        for y in screen_y..screen_y + NODE_HEIGHT as i32 {
+            for x in screen_x..screen_x + NODE_WIDTH as i32 {
+                if x >= 0 && y >= 0 && inner.contains((x as u16, y as u16).into()) {
+                    buf[(x as u16, y as u16)].set_style(flash_style);
+                }
+            }
+        }
+    }
+
+    if let Some(strip) = strip {
+        render_link_strip(f, app, strip);
+    }
+    render_search(f, app, inner);
     render_help_bar(f, app, area);
 }
 
-/// Draw a straight line between two points using Bresenham's algorithm
-fn draw_line(
+/// The `/` search box over the top left of the graph: the query, then the
+/// matching notes, best first, with the one Enter goes to highlighted.
+fn render_search(f: &mut Frame, app: &App, graph: Rect) {
+    let Some(search) = &app.graph_view.search else {
+        return;
+    };
+    let theme = &app.theme;
+    let visible = search.matches.len().clamp(1, SEARCH_MAX_RESULTS);
+    let area = Rect {
+        x: graph.x + 1,
+        y: graph.y,
+        width: SEARCH_WIDTH.min(graph.width.saturating_sub(2)),
+        height: (visible as u16 + 3).min(graph.height.saturating_sub(1)),
+    };
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(Span::styled(" Find node ", Style::default().fg(theme.dialog.title).add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border_focused))
+        .style(Style::default().bg(theme.dialog.background));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let mut lines = vec![Line::from(vec![
+        Span::styled("/", Style::default().fg(theme.warning)),
+        Span::styled(search.query.as_str(), Style::default().fg(theme.dialog.text)),
+        Span::styled("█", Style::default().fg(theme.cursor)),
+    ])];
+    if search.matches.is_empty() {
+        lines.push(Line::from(Span::styled(" No matches", Style::default().fg(theme.muted))));
+    }
+    let scroll = (search.selected + 1).saturating_sub(visible);
+    for (i, &node) in search.matches.iter().enumerate().skip(scroll).take(visible) {
+        let title = app
+            .graph_view
+            .nodes
+            .get(node)
+            .and_then(|n| app.notes.get(n.note_index))
+            .map_or("", |note| note.title.as_str());
+        let style = if i == search.selected {
+            Style::default().fg(theme.background).bg(theme.primary)
+        } else {
+            Style::default().fg(theme.dialog.text)
+        };
+        lines.push(Line::from(Span::styled(format!(" {}", title), style)));
+    }
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+/// Draw the edge between the nodes at screen positions `from` and `to`,
+/// center to center, with an arrowhead at the target end (both ends for a
+/// two-way link).
+fn draw_edge(
     buf: &mut Buffer,
-    x0: i32,
-    y0: i32,
-    x1: i32,
-    y1: i32,
+    from: (i32, i32),
+    to: (i32, i32),
+    bidirectional: bool,
     color: ratatui::style::Color,
     clip: Rect,
     force_overwrite: bool,
 ) {
+    let center = |(x, y): (i32, i32)| (x + NODE_WIDTH as i32 / 2, y + NODE_HEIGHT as i32 / 2);
+    let points = line_points(center(from), center(to));
+
+    // Always use dots for edges for consistent appearance
+    for &(x, y) in &points {
+        set_edge_cell(buf, x, y, '·', color, clip, force_overwrite);
+    }
+
+    let mut heads = vec![(arrow_cell(&points, to), center(from), center(to))];
+    if bidirectional {
+        let reversed: Vec<(i32, i32)> = points.iter().rev().copied().collect();
+        heads.push((arrow_cell(&reversed, from), center(to), center(from)));
+    }
+    for (cell, (x0, y0), (x1, y1)) in heads {
+        if let Some((x, y)) = cell {
+            set_edge_cell(buf, x, y, arrow_glyph(x1 - x0, y1 - y0), color, clip, true);
+        }
+    }
+}
+
+fn set_edge_cell(
+    buf: &mut Buffer,
+    x: i32,
+    y: i32,
+    ch: char,
+    color: ratatui::style::Color,
+    clip: Rect,
+    force_overwrite: bool,
+) {
+    if x < clip.x as i32
+        || x >= (clip.x + clip.width) as i32
+        || y < clip.y as i32
+        || y >= (clip.y + clip.height) as i32
+    {
+        return;
+    }
+    if let Some(cell) = buf.cell_mut((x as u16, y as u16)) {
+        let current = cell.symbol();
+        // For highlighted edges, overwrite more aggressively
+        if force_overwrite || current == " " || current == "·" {
+            cell.set_char(ch);
+            cell.set_fg(color);
+        }
+    }
+}
+
+/// Cells of a straight line between two points, using Bresenham's algorithm
+fn line_points((x0, y0): (i32, i32), (x1, y1): (i32, i32)) -> Vec<(i32, i32)> {
     let dx = (x1 - x0).abs();
     let dy = (y1 - y0).abs();
     let sx = if x0 < x1 { 1 } else { -1 };
     let sy = if y0 < y1 { 1 } else { -1 };
     let mut err = dx - dy;
-    let mut x = x0;
-    let mut y = y0;
-
-    // Always use dots for edges for consistent appearance
-    let line_char = '·';
+    let (mut x, mut y) = (x0, y0);
+    let mut points = Vec::with_capacity((dx.max(dy) + 1) as usize);
 
     loop {
-        if x >= clip.x as i32
-            && x < (clip.x + clip.width) as i32
-            && y >= clip.y as i32
-            && y < (clip.y + clip.height) as i32
-        {
-            if let Some(cell) = buf.cell_mut((x as u16, y as u16)) {
-                let current = cell.symbol();
-                // For highlighted edges, overwrite more aggressively
-                if force_overwrite || current == " " || current == "·" {
-                    cell.set_char(line_char);
-                    cell.set_fg(color);
-                }
-            }
-        }
-
+        points.push((x, y));
         if x == x1 && y == y1 {
             break;
         }
@@ -287,6 +407,73 @@ fn draw_line(
             y += sy;
         }
     }
+    points
+}
+
+/// Where the arrowhead goes on a line running into the node drawn at
+/// `target`: the last cell before the line enters the node's box.
+fn arrow_cell(points: &[(i32, i32)], (tx, ty): (i32, i32)) -> Option<(i32, i32)> {
+    let inside = |&(x, y): &(i32, i32)| {
+        x >= tx && x < tx + NODE_WIDTH as i32 && y >= ty && y < ty + NODE_HEIGHT as i32
+    };
+    let entry = points.iter().position(inside)?;
+    entry.checked_sub(1).map(|i| points[i])
+}
+
+/// Arrow pointing along `(dx, dy)` in screen cells, which are about twice as
+/// tall as they are wide.
+fn arrow_glyph(dx: i32, dy: i32) -> char {
+    const ARROWS: [char; 8] = ['→', '↘', '↓', '↙', '←', '↖', '↑', '↗'];
+    let angle = (dy as f32 * 2.0).atan2(dx as f32);
+    let sector = (angle / std::f32::consts::FRAC_PI_4).round() as i32;
+    ARROWS[sector.rem_euclid(8) as usize]
+}
+
+/// The selected node's outgoing and incoming links, with the one Enter
+/// follows highlighted while the strip has focus.
+fn render_link_strip(f: &mut Frame, app: &mut App, area: Rect) {
+    let links = app.graph_view.selected_links();
+    if app.graph_view.link_index >= links.len() {
+        app.graph_view.link_index = links.len().saturating_sub(1);
+    }
+    let theme = &app.theme;
+
+    let block = Block::default()
+        .borders(Borders::LEFT)
+        .border_style(Style::default().fg(theme.dialog.border));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let header_style = Style::default().fg(theme.muted).add_modifier(Modifier::BOLD);
+    let mut lines = Vec::new();
+    let mut selected_line = 0;
+    for (i, link) in links.iter().enumerate() {
+        if i == 0 || links[i - 1].outgoing != link.outgoing {
+            if i > 0 {
+                lines.push(Line::from(""));
+            }
+            let header = if link.outgoing { " Links to" } else { " Linked from" };
+            lines.push(Line::from(Span::styled(header, header_style)));
+        }
+        let title = app.graph_view.nodes.get(link.node).map(|n| n.title.as_str()).unwrap_or("");
+        let arrow = if link.outgoing { "→" } else { "←" };
+        let style = if app.graph_view.links_focused && i == app.graph_view.link_index {
+            selected_line = lines.len();
+            Style::default().fg(theme.background).bg(theme.primary)
+        } else {
+            Style::default().fg(theme.dialog.text)
+        };
+        lines.push(Line::from(Span::styled(format!(" {} {}", arrow, title), style)));
+    }
+    if links.is_empty() {
+        lines.push(Line::from(Span::styled(" No links", Style::default().fg(theme.muted))));
+    }
+
+    // Keep the highlighted link in view, above the help bar's row
+    let visible = inner.height.saturating_sub(1) as usize;
+    let scroll = (selected_line + 1).saturating_sub(visible) as u16;
+    let list_area = Rect { height: visible as u16, ..inner };
+    f.render_widget(Paragraph::new(lines).scroll((scroll, 0)), list_area);
 }
 
 fn render_node(
@@ -305,8 +492,7 @@ fn render_node(
         (theme.primary, theme.primary)
     } else if is_dimmed {
         // Dimmed but still visible (not as dark as edges)
-        let dim_color = ratatui::style::Color::Rgb(70, 70, 70);
-        (dim_color, dim_color)
+        (theme.muted, theme.muted)
     } else {
         (theme.foreground, theme.dialog.text)
     };
@@ -379,24 +565,60 @@ fn render_node(
 fn render_help_bar(f: &mut Frame, app: &App, area: Rect) {
     let theme = &app.theme;
 
-    let hint = Line::from(vec![
-        Span::styled("hjkl", Style::default().fg(theme.warning)),
-        Span::styled(": select  ", Style::default().fg(theme.muted)),
-        Span::styled("u", Style::default().fg(theme.warning)),
-        Span::styled(": unselect  ", Style::default().fg(theme.muted)),
-        Span::styled("HJKL", Style::default().fg(theme.warning)),
-        Span::styled(": pan  ", Style::default().fg(theme.muted)),
-        Span::styled("+/-", Style::default().fg(theme.warning)),
-        Span::styled(": zoom  ", Style::default().fg(theme.muted)),
-        Span::styled("f", Style::default().fg(theme.warning)),
-        Span::styled(": fit  ", Style::default().fg(theme.muted)),
-        Span::styled("Enter", Style::default().fg(theme.warning)),
-        Span::styled(": open  ", Style::default().fg(theme.muted)),
-        Span::styled("Esc", Style::default().fg(theme.warning)),
-        Span::styled(": close", Style::default().fg(theme.muted)),
+    let key_style = Style::default().fg(theme.warning);
+    let desc_style = Style::default().fg(theme.muted);
+    let hint_area = Rect::new(area.x + 2, area.y + area.height.saturating_sub(2), area.width.saturating_sub(4), 1);
+    if app.graph_view.search.is_some() {
+        let hint = Line::from(vec![
+            Span::styled("↑/↓", key_style),
+            Span::styled(": choose  ", desc_style),
+            Span::styled("Enter", key_style),
+            Span::styled(": go to  ", desc_style),
+            Span::styled("Esc", key_style),
+            Span::styled(": cancel", desc_style),
+        ]);
+        f.render_widget(Paragraph::new(hint), hint_area);
+        return;
+    }
+
+    let full_hint = Line::from(vec![
+        Span::styled("hjkl", key_style),
+        Span::styled(": select  ", desc_style),
+        Span::styled("u", key_style),
+        Span::styled(": unselect  ", desc_style),
+        Span::styled("HJKL", key_style),
+        Span::styled(": pan  ", desc_style),
+        Span::styled("+/-", key_style),
+        Span::styled(": zoom  ", desc_style),
+        Span::styled("f", key_style),
+        Span::styled(": fit  ", desc_style),
+        Span::styled("n", key_style),
+        Span::styled(": focus  ", desc_style),
+        Span::styled("/", key_style),
+        Span::styled(": find  ", desc_style),
+        Span::styled("Tab", key_style),
+        Span::styled(": links  ", desc_style),
+        Span::styled("Enter", key_style),
+        Span::styled(": open  ", desc_style),
+        Span::styled("Esc", key_style),
+        Span::styled(": close", desc_style),
     ]);
 
-    let hint_area = Rect::new(area.x + 2, area.y + area.height - 2, area.width.saturating_sub(4), 1);
+    // Keep the keys that matter most when the full hint doesn't fit
+    let hint = if full_hint.width() <= hint_area.width as usize {
+        full_hint
+    } else {
+        Line::from(vec![
+            Span::styled("hjkl", key_style),
+            Span::styled(": select  ", desc_style),
+            Span::styled("/", key_style),
+            Span::styled(": find  ", desc_style),
+            Span::styled("Enter", key_style),
+            Span::styled(": open  ", desc_style),
+            Span::styled("Esc", key_style),
+            Span::styled(": close", desc_style),
+        ])
+    };
     f.render_widget(Paragraph::new(hint), hint_area);
 }
 
@@ -421,3 +643,28 @@ fn graph_bounds(nodes: &[crate::app::GraphNode]) -> (f32, f32, f32, f32) {
     (min_x, min_y, max_x, max_y)
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arrow_glyph() {
+        assert_eq!(arrow_glyph(10, 0), '→');
+        assert_eq!(arrow_glyph(-10, 1), '←');
+        assert_eq!(arrow_glyph(0, 5), '↓');
+        assert_eq!(arrow_glyph(1, -5), '↑');
+        // A cell is twice as tall as wide: 4 across, 2 down is diagonal
+        assert_eq!(arrow_glyph(4, 2), '↘');
+        assert_eq!(arrow_glyph(-4, -2), '↖');
+    }
+
+    #[test]
+    fn test_arrow_cell_stops_outside_the_target() {
+        // From (1, 1) to the center of a node drawn at (10, 0)
+        let points = line_points((1, 1), (11, 1));
+        assert_eq!(arrow_cell(&points, (10, 0)), Some((9, 1)));
+        // Overlapping nodes leave no room for an arrow
+        assert_eq!(arrow_cell(&line_points((1, 1), (2, 1)), (0, 0)), None);
+    }
+}
@@ -0,0 +1,147 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::{format_size, App};
+
+const MAX_VISIBLE: usize = 12;
+const WIDTH: u16 = 44;
+
+/// Centered `:history` list: when each snapshot of the note was taken and
+/// its size, or the text of the one being viewed.
+pub fn render_history(f: &mut Frame, app: &mut App) {
+    if app.history.viewing.is_some() {
+        render_snapshot_text(f, app);
+        return;
+    }
+    let len = app.history.entries.len();
+    if len == 0 {
+        return;
+    }
+
+    let selected = app.history.selected.min(len - 1);
+    let visible = len.min(MAX_VISIBLE);
+
+    let mut scroll = app.history.scroll_offset;
+    if selected < scroll {
+        scroll = selected;
+    } else if selected >= scroll + visible {
+        scroll = selected + 1 - visible;
+    }
+    app.history.scroll_offset = scroll;
+
+    let theme = &app.theme;
+    let area = f.area();
+    let popup_width = WIDTH.min(area.width.saturating_sub(4));
+    let popup_height = (visible as u16 + 4).min(area.height.saturating_sub(4));
+
+    let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+    let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(Line::from(Span::styled(
+            " History ",
+            Style::default()
+                .fg(theme.dialog.title)
+                .add_modifier(Modifier::BOLD),
+        )))
+        .title_bottom(
+            Line::from(Span::styled(" enter view · r restore · esc close ", Style::default().fg(theme.muted)))
+                .right_aligned(),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.dialog.border))
+        .style(Style::default().bg(theme.dialog.background));
+
+    f.render_widget(block, popup_area);
+
+    let mut lines: Vec<Line> = Vec::with_capacity(visible + 2);
+    lines.push(Line::from(""));
+
+    for (row, snapshot) in app.history.entries.iter().enumerate().skip(scroll).take(visible) {
+        let is_sel = row == selected;
+        let marker = if is_sel { "▶ " } else { "  " };
+        let line_style = if is_sel {
+            Style::default().bg(theme.selection)
+        } else {
+            Style::default()
+        };
+
+        lines.push(
+            Line::from(vec![
+                Span::styled(marker, Style::default().fg(theme.dialog.title)),
+                Span::styled(
+                    snapshot.taken.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    Style::default().fg(theme.dialog.text),
+                ),
+                Span::raw("  "),
+                Span::styled(format_size(snapshot.bytes), Style::default().fg(theme.muted)),
+            ])
+            .style(line_style),
+        );
+    }
+
+    lines.push(Line::from(""));
+
+    let inner = Rect::new(
+        popup_area.x + 1,
+        popup_area.y + 1,
+        popup_area.width.saturating_sub(2),
+        popup_area.height.saturating_sub(2),
+    );
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+/// The viewed snapshot, read-only, scrolled with j/k.
+fn render_snapshot_text(f: &mut Frame, app: &mut App) {
+    let area = f.area();
+    let popup_area = Rect::new(
+        area.width / 10,
+        area.height / 10,
+        area.width - area.width / 5,
+        area.height - area.height / 5,
+    );
+    let visible = popup_area.height.saturating_sub(2) as usize;
+    let taken = app
+        .history
+        .entries
+        .get(app.history.selected)
+        .map(|s| s.taken.format(" %Y-%m-%d %H:%M:%S ").to_string())
+        .unwrap_or_default();
+    let Some((text, scroll)) = app.history.viewing.as_mut() else {
+        return;
+    };
+    *scroll = (*scroll).min(text.lines().count().saturating_sub(visible));
+
+    let theme = &app.theme;
+    f.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .title(Line::from(Span::styled(
+            taken,
+            Style::default()
+                .fg(theme.dialog.title)
+                .add_modifier(Modifier::BOLD),
+        )))
+        .title_bottom(
+            Line::from(Span::styled(" j/k scroll · r restore · esc back ", Style::default().fg(theme.muted)))
+                .right_aligned(),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.dialog.border))
+        .style(Style::default().bg(theme.dialog.background));
+
+    let lines: Vec<Line> = text
+        .lines()
+        .skip(*scroll)
+        .take(visible)
+        .map(|line| Line::from(Span::styled(line.to_string(), Style::default().fg(theme.dialog.text))))
+        .collect();
+    f.render_widget(Paragraph::new(lines).block(block), popup_area);
+}
@@ -0,0 +1,210 @@
+//! Light inline HTML in the content view, with `[display] render_inline_html`:
+//! `<kbd>` and `<mark>` are styled, `<br>` breaks the line, and any other
+//! tag is dropped, leaving the text inside it. Something like `a < b` that
+//! isn't a tag stays as it is.
+
+use ratatui::{style::Style, text::Span};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tag {
+    Kbd,
+    Mark,
+    Other,
+}
+
+/// A line of text cut at its tags.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Piece<'a> {
+    Text(&'a str),
+    Open(Tag),
+    Close(Tag),
+    Break,
+}
+
+/// `text` as text and tags, in order.
+pub fn pieces(text: &str) -> Vec<Piece<'_>> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut at = 0;
+    while let Some(offset) = text[at..].find('<') {
+        let lt = at + offset;
+        match tag_at(text, lt) {
+            Some((piece, end)) => {
+                if lt > start {
+                    pieces.push(Piece::Text(&text[start..lt]));
+                }
+                pieces.push(piece);
+                start = end;
+                at = end;
+            }
+            None => at = lt + 1,
+        }
+    }
+    if start < text.len() {
+        pieces.push(Piece::Text(&text[start..]));
+    }
+    pieces
+}
+
+/// The tag starting at byte `at`, and the byte just past its `>`.
+fn tag_at(text: &str, at: usize) -> Option<(Piece<'_>, usize)> {
+    let rest = &text[at + 1..];
+    let (closing, rest) = match rest.strip_prefix('/') {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+    if !rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let name_len = rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '-').unwrap_or(rest.len());
+    let (name, after) = rest.split_at(name_len);
+    if !after.starts_with(|c: char| c == '>' || c == '/' || c.is_whitespace()) {
+        return None;
+    }
+    let close = after.find(['<', '>']).filter(|&i| after[i..].starts_with('>'))?;
+    if closing && !after[..close].trim().is_empty() {
+        return None;
+    }
+    let end = text.len() - after.len() + close + 1;
+
+    let piece = match name.to_ascii_lowercase().as_str() {
+        "br" => Piece::Break,
+        "kbd" if closing => Piece::Close(Tag::Kbd),
+        "kbd" => Piece::Open(Tag::Kbd),
+        "mark" if closing => Piece::Close(Tag::Mark),
+        "mark" => Piece::Open(Tag::Mark),
+        _ if closing => Piece::Close(Tag::Other),
+        _ => Piece::Open(Tag::Other),
+    };
+    Some((piece, end))
+}
+
+/// Rendered `spans` with their tags applied, one row per `<br>`. Keys get a
+/// space either side inside the `kbd` style so they read as a box. Spans
+/// with a background of their own, like inline code and the selected link,
+/// are left as they are.
+pub fn apply<'a>(spans: Vec<Span<'a>>, kbd: Style, mark: Style) -> Vec<Vec<Span<'a>>> {
+    let mut rows = vec![Vec::new()];
+    let (mut in_kbd, mut in_mark) = (false, false);
+    for span in spans {
+        let row = rows.last_mut().expect("rows is never empty");
+        if span.style.bg.is_some() || (!span.content.contains('<') && !in_kbd && !in_mark) {
+            row.push(span);
+            continue;
+        }
+        for piece in pieces(&span.content) {
+            match piece {
+                Piece::Text(text) => {
+                    let mut style = span.style;
+                    if in_mark {
+                        style = style.patch(mark);
+                    }
+                    if in_kbd {
+                        style = style.patch(kbd);
+                    }
+                    rows.last_mut().expect("rows is never empty").push(Span::styled(text.to_string(), style));
+                }
+                Piece::Open(Tag::Kbd) | Piece::Close(Tag::Kbd) => {
+                    in_kbd = piece == Piece::Open(Tag::Kbd);
+                    rows.last_mut().expect("rows is never empty").push(Span::styled(" ", span.style.patch(kbd)));
+                }
+                Piece::Open(Tag::Mark) => in_mark = true,
+                Piece::Close(Tag::Mark) => in_mark = false,
+                Piece::Open(Tag::Other) | Piece::Close(Tag::Other) => {}
+                Piece::Break => rows.push(Vec::new()),
+            }
+        }
+    }
+    rows
+}
+
+/// The text of each row `line` renders as, for working out how tall it is.
+/// Inline code keeps its tags, as it does when rendered.
+pub fn plain_rows(line: &str) -> Vec<String> {
+    let mut rows = vec![String::new()];
+    for (i, segment) in line.split('`').enumerate() {
+        let row = rows.last_mut().expect("rows is never empty");
+        if i > 0 {
+            row.push('`');
+        }
+        if i % 2 == 1 {
+            row.push_str(segment);
+            continue;
+        }
+        for piece in pieces(segment) {
+            match piece {
+                Piece::Text(text) => rows.last_mut().expect("rows is never empty").push_str(text),
+                Piece::Open(Tag::Kbd) | Piece::Close(Tag::Kbd) => {
+                    rows.last_mut().expect("rows is never empty").push(' ')
+                }
+                Piece::Break => rows.push(String::new()),
+                _ => {}
+            }
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Color, Modifier};
+
+    fn kbd() -> Style {
+        Style::default().add_modifier(Modifier::REVERSED)
+    }
+
+    fn mark() -> Style {
+        Style::default().fg(Color::Black).bg(Color::Yellow)
+    }
+
+    fn text_of(row: &[Span]) -> String {
+        row.iter().map(|span| span.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_kbd() {
+        let rows = apply(vec![Span::raw("Press <kbd>Ctrl</kbd>+<KBD>S</KBD>.")], kbd(), mark());
+        assert_eq!(rows.len(), 1);
+        assert_eq!(text_of(&rows[0]), "Press  Ctrl + S .");
+        let ctrl = rows[0].iter().find(|span| span.content == "Ctrl").unwrap();
+        assert!(ctrl.style.add_modifier.contains(Modifier::REVERSED));
+        assert!(!rows[0][0].style.add_modifier.contains(Modifier::REVERSED));
+    }
+
+    #[test]
+    fn test_mark_across_spans() {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let spans = vec![Span::raw("a <mark>big "), Span::styled("deal", bold), Span::raw("</mark> here")];
+        let rows = apply(spans, kbd(), mark());
+        assert_eq!(text_of(&rows[0]), "a big deal here");
+        let deal = rows[0].iter().find(|span| span.content == "deal").unwrap();
+        assert_eq!(deal.style.bg, Some(Color::Yellow));
+        assert!(deal.style.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(rows[0].last().unwrap().style.bg, None);
+    }
+
+    #[test]
+    fn test_br_breaks_rows() {
+        let rows = apply(vec![Span::raw("one<br>two<br/>three<BR />")], kbd(), mark());
+        let texts: Vec<String> = rows.iter().map(|row| text_of(row)).collect();
+        assert_eq!(texts, ["one", "two", "three", ""]);
+        assert_eq!(plain_rows("one<br>two <kbd>K</kbd>"), ["one", "two  K "]);
+    }
+
+    #[test]
+    fn test_unknown_tags_strip_to_text() {
+        let rows = apply(
+            vec![Span::raw("<span class=\"x\">kept</span> <sub>2</sub> a < b, <3 and <not a tag")],
+            kbd(),
+            mark(),
+        );
+        assert_eq!(text_of(&rows[0]), "kept 2 a < b, <3 and <not a tag");
+
+        // Inline code keeps its tags
+        let code = Span::styled("<b>x</b>", Style::default().bg(Color::DarkGray));
+        let rows = apply(vec![Span::raw("<i>see</i> "), code], kbd(), mark());
+        assert_eq!(text_of(&rows[0]), "see <b>x</b>");
+        assert_eq!(plain_rows("<i>see</i> `<b>x</b>`"), ["see `<b>x</b>`"]);
+    }
+}
@@ -0,0 +1,200 @@
+//! Layout rules that keep the UI usable on narrow terminals: side panels that
+//! step aside below `display.hide_outline_below`/`hide_sidebar_below`, and
+//! dialogs clamped to the screen.
+
+use std::rc::Rc;
+
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+
+use crate::config::{DisplayConfig, ReadingAlign};
+
+/// Width of a collapsed side panel.
+const COLLAPSED_WIDTH: u16 = 5;
+
+/// Narrowest the content column gets while side panels are shown.
+const MIN_CONTENT_WIDTH: u16 = 20;
+
+/// How a side panel is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SidePanel {
+    Hidden,
+    Collapsed,
+    Open,
+}
+
+impl SidePanel {
+    fn constraint(self) -> Constraint {
+        match self {
+            SidePanel::Hidden => Constraint::Length(0),
+            SidePanel::Collapsed => Constraint::Length(COLLAPSED_WIDTH),
+            SidePanel::Open => Constraint::Percentage(20),
+        }
+    }
+}
+
+/// Whether a terminal `width` columns wide is too narrow for the sidebar and
+/// the outline, in that order.
+pub fn narrow_hidden(width: u16, display: &DisplayConfig) -> (bool, bool) {
+    (width < display.hide_sidebar_below, width < display.hide_outline_below)
+}
+
+/// Sidebar, content and outline columns of `area`.
+pub fn panels(area: Rect, sidebar: SidePanel, outline: SidePanel) -> Rc<[Rect]> {
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            sidebar.constraint(),
+            Constraint::Min(MIN_CONTENT_WIDTH),
+            outline.constraint(),
+        ])
+        .split(area)
+}
+
+/// A `width` × `height` box centered in `area`, shrunk to leave a two-cell
+/// margin when `area` is too small for it.
+pub fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width.saturating_sub(4));
+    let height = height.min(area.height.saturating_sub(4));
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// Blank columns left and right of text `width` columns wide kept to
+/// `max_width`, by `align`.
+pub fn text_margins(width: u16, max_width: Option<u16>, align: ReadingAlign) -> (u16, u16) {
+    let spare = max_width.map_or(0, |max| width.saturating_sub(max.max(1)));
+    match align {
+        ReadingAlign::Left => (0, spare),
+        ReadingAlign::Center => (spare / 2, spare - spare / 2),
+    }
+}
+
+/// The part of `area` a note is drawn in under `reading.max_width`.
+pub fn reading_area(area: Rect, max_width: Option<u16>, align: ReadingAlign) -> Rect {
+    let (left, right) = text_margins(area.width, max_width, align);
+    Rect { x: area.x + left, width: area.width - left - right, ..area }
+}
+
+/// Scroll for `lines` of text in a bordered `area` that keeps the last line
+/// in view, so a clamped dialog loses its top rather than its key hints.
+pub fn overflow_scroll(lines: usize, area: Rect) -> (u16, u16) {
+    let visible = area.height.saturating_sub(2) as usize;
+    (lines.saturating_sub(visible) as u16, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Alignment;
+    use ratatui::text::Line;
+    use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+    use ratatui::Terminal;
+
+    /// The three columns as they'd be laid out at `width` × `height` with
+    /// both side panels open, drawn as titled boxes.
+    fn draw_panels(width: u16, height: u16) -> Buffer {
+        let display = DisplayConfig::default();
+        let (hide_sidebar, hide_outline) = narrow_hidden(width, &display);
+        let side = |hidden: bool| if hidden { SidePanel::Hidden } else { SidePanel::Open };
+
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|f| {
+                let chunks = panels(f.area(), side(hide_sidebar), side(hide_outline));
+                for (chunk, title) in chunks.iter().zip(["S", "Content", "O"]) {
+                    if chunk.width > 0 {
+                        f.render_widget(Block::default().title(title).borders(Borders::ALL), *chunk);
+                    }
+                }
+            })
+            .unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    #[test]
+    fn test_reading_area_keeps_to_max_width() {
+        let panel = Rect::new(10, 1, 100, 30);
+        assert_eq!(reading_area(panel, None, ReadingAlign::Center), panel);
+        assert_eq!(reading_area(panel, Some(90), ReadingAlign::Left), Rect::new(10, 1, 90, 30));
+        assert_eq!(reading_area(panel, Some(89), ReadingAlign::Center), Rect::new(15, 1, 89, 30));
+        // Narrower than the limit, as when a side panel opens
+        assert_eq!(reading_area(Rect::new(0, 0, 60, 5), Some(90), ReadingAlign::Center), Rect::new(0, 0, 60, 5));
+        assert_eq!(text_margins(100, Some(90), ReadingAlign::Center), (5, 5));
+    }
+
+    #[test]
+    fn test_40x15_shows_only_content() {
+        let buffer = draw_panels(40, 15);
+        let mut expected = vec![format!("┌Content{}┐", "─".repeat(31))];
+        expected.extend((0..13).map(|_| format!("│{}│", " ".repeat(38))));
+        expected.push(format!("└{}┘", "─".repeat(38)));
+        assert_eq!(buffer, Buffer::with_lines(expected));
+    }
+
+    #[test]
+    fn test_60x20_hides_outline_and_keeps_sidebar() {
+        let buffer = draw_panels(60, 20);
+        // 20% of 60 columns for the sidebar, the rest for content
+        let mut expected = vec![format!("┌S{}┐┌Content{}┐", "─".repeat(9), "─".repeat(39))];
+        expected.extend((0..18).map(|_| format!("│{}││{}│", " ".repeat(10), " ".repeat(46))));
+        expected.push(format!("└{}┘└{}┘", "─".repeat(10), "─".repeat(46)));
+        assert_eq!(buffer, Buffer::with_lines(expected));
+    }
+
+    #[test]
+    fn test_dialog_clamped_at_40x15() {
+        let mut terminal = Terminal::new(TestBackend::new(40, 15)).unwrap();
+        terminal
+            .draw(|f| {
+                let area = centered_rect(f.area(), 50, 14);
+                let lines: Vec<Line> = [
+                    "", "Replace in 6 notes?", "", "a", "b", "c", "d", "e", "f", "", "y: Yes  |  n: No",
+                ]
+                .into_iter()
+                .map(Line::from)
+                .collect();
+                let scroll = overflow_scroll(lines.len(), area);
+                f.render_widget(Clear, area);
+                let dialog = Paragraph::new(lines)
+                    .scroll(scroll)
+                    .block(Block::default().title("Replace").borders(Borders::ALL))
+                    .alignment(Alignment::Center);
+                f.render_widget(dialog, area);
+            })
+            .unwrap();
+
+        // Nine inner rows: the top two lines scroll away, the hint stays
+        let expected = [
+            "                                        ",
+            "                                        ",
+            "  ┌Replace───────────────────────────┐  ",
+            "  │                                  │  ",
+            "  │                 a                │  ",
+            "  │                 b                │  ",
+            "  │                 c                │  ",
+            "  │                 d                │  ",
+            "  │                 e                │  ",
+            "  │                 f                │  ",
+            "  │                                  │  ",
+            "  │         y: Yes  |  n: No         │  ",
+            "  └──────────────────────────────────┘  ",
+            "                                        ",
+            "                                        ",
+        ];
+        assert_eq!(terminal.backend().buffer(), &Buffer::with_lines(expected));
+    }
+
+    #[test]
+    fn test_centered_rect_fits_tiny_areas() {
+        let area = Rect::new(0, 0, 40, 15);
+        assert_eq!(centered_rect(area, 30, 9), Rect::new(5, 3, 30, 9));
+        assert_eq!(centered_rect(area, 90, 50), Rect::new(2, 2, 36, 11));
+        assert_eq!(centered_rect(Rect::new(0, 0, 3, 3), 10, 10), Rect::new(1, 1, 0, 0));
+    }
+}
@@ -0,0 +1,31 @@
+use ratatui::Frame;
+
+use super::completion_popup::{render_completion_popup, CompletionPopup};
+use crate::app::{App, LinkCompletionState};
+
+pub fn render_link_completion(f: &mut Frame, app: &App) {
+    if let LinkCompletionState::Open {
+        query,
+        suggestions,
+        selected_index,
+        ..
+    } = &app.link_completion
+    {
+        let title = if query.is_empty() {
+            " Link ".to_string()
+        } else {
+            format!(" ]({} ", query)
+        };
+        render_completion_popup(
+            f,
+            app,
+            CompletionPopup {
+                title,
+                suggestions,
+                selected_index: *selected_index,
+                placeholder: None,
+                footer: None,
+            },
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::App;
+use crate::vim::key_notation::format_keys;
+
+const MAX_VISIBLE: usize = 12;
+const MIN_WIDTH: u16 = 40;
+const MAX_WIDTH: u16 = 80;
+
+/// Centered `:macros` list: one row per register with its keys in vim notation.
+pub fn render_macro_list(f: &mut Frame, app: &mut App) {
+    let registers = app.vim.macros.registers();
+    let len = registers.len();
+    if len == 0 {
+        return;
+    }
+
+    let selected = app.macro_list.selected.min(len - 1);
+    let visible = len.min(MAX_VISIBLE);
+
+    let mut scroll = app.macro_list.scroll_offset;
+    if selected < scroll {
+        scroll = selected;
+    } else if selected >= scroll + visible {
+        scroll = selected + 1 - visible;
+    }
+    app.macro_list.scroll_offset = scroll;
+
+    let theme = &app.theme;
+    let area = f.area();
+
+    let rows: Vec<(char, String)> = registers
+        .iter()
+        .map(|&r| {
+            let keys = app
+                .vim
+                .macros
+                .get_macro(r)
+                .and_then(|keys| format_keys(keys))
+                .unwrap_or_else(|| "(unsupported keys)".to_string());
+            (r, keys)
+        })
+        .collect();
+
+    let longest = rows.iter().map(|(_, k)| k.chars().count()).max().unwrap_or(0) as u16;
+    // marker(2) + register(1) + gap(2) + keys + inner padding(2)
+    let popup_width = (longest + 7)
+        .clamp(MIN_WIDTH, MAX_WIDTH)
+        .min(area.width.saturating_sub(4));
+    let popup_height = (visible as u16 + 4).min(area.height.saturating_sub(4));
+
+    let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+    let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+    f.render_widget(Clear, popup_area);
+
+    let hint = if app.macro_list.renaming {
+        " move to register a-z · esc cancel "
+    } else {
+        " d delete · r move · esc close "
+    };
+    let block = Block::default()
+        .title(Line::from(Span::styled(
+            " Macros ",
+            Style::default()
+                .fg(theme.dialog.title)
+                .add_modifier(Modifier::BOLD),
+        )))
+        .title_bottom(Line::from(Span::styled(hint, Style::default().fg(theme.muted))).right_aligned())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.dialog.border))
+        .style(Style::default().bg(theme.dialog.background));
+
+    f.render_widget(block, popup_area);
+
+    let keys_width = (popup_width as usize).saturating_sub(7);
+    let mut lines: Vec<Line> = Vec::with_capacity(visible + 2);
+    lines.push(Line::from(""));
+
+    for (row, (register, keys)) in rows.iter().enumerate().skip(scroll).take(visible) {
+        let is_sel = row == selected;
+        let marker = if is_sel { "▶ " } else { "  " };
+        let keys: String = if keys.chars().count() > keys_width {
+            let mut truncated: String = keys.chars().take(keys_width.saturating_sub(1)).collect();
+            truncated.push('…');
+            truncated
+        } else {
+            keys.clone()
+        };
+        let line_style = if is_sel {
+            Style::default().bg(theme.selection)
+        } else {
+            Style::default()
+        };
+
+        lines.push(
+            Line::from(vec![
+                Span::styled(marker, Style::default().fg(theme.dialog.title)),
+                Span::styled(
+                    register.to_string(),
+                    Style::default().fg(theme.info).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("  "),
+                Span::styled(keys, Style::default().fg(theme.dialog.text)),
+            ])
+            .style(line_style),
+        );
+    }
+
+    lines.push(Line::from(""));
+
+    let inner = Rect::new(
+        popup_area.x + 1,
+        popup_area.y + 1,
+        popup_area.width.saturating_sub(2),
+        popup_area.height.saturating_sub(2),
+    );
+    f.render_widget(Paragraph::new(lines), inner);
+}
@@ -1,16 +1,31 @@
+mod completion_popup;
 mod content;
 mod context_menu;
+mod debug_overlay;
 mod dialogs;
 mod editor;
 mod file_picker;
 mod graph_view;
+mod history;
+mod inline_html;
+mod layout;
+mod link_completion;
+mod macro_list;
+mod move_picker;
 mod outline;
+mod prose_report;
+mod quickfix;
+mod refile_picker;
 mod search_dialog;
 mod sidebar;
+mod source_view;
 mod status_bar;
 mod theme_picker;
 mod toast;
+mod vault_picker;
 mod wiki_autocomplete;
+mod word_completion;
+mod yank_list;
 
 use ratatui::{
     layout::{Constraint, Direction, Layout},
@@ -19,16 +34,23 @@ use ratatui::{
     Frame,
 };
 
-use crate::app::{App, ContextMenuState, DialogState, SearchPickerState, Mode, WikiAutocompleteState};
+use layout::SidePanel;
+
+use crate::app::{App, ContextMenuState, DialogState, LinkCompletionState, SearchPickerState, Mode, WikiAutocompleteState, WordCompletionState};
 
 pub use content::render_content;
 pub(crate) use content::{cell_visible_width, detect_bare_url_len};
 pub use dialogs::{
-    render_create_folder_dialog, render_create_note_dialog, render_create_note_in_folder_dialog,
-    render_create_wiki_note_dialog, render_delete_confirm_dialog, render_delete_folder_confirm_dialog,
-    render_directory_not_found_dialog, render_empty_directory_dialog, render_help_dialog,
-    render_onboarding_dialog, render_rename_folder_dialog, render_rename_note_dialog,
-    render_unsaved_changes_dialog, render_welcome_dialog,
+    render_change_notes_dir_dialog, render_create_folder_dialog, render_create_note_dialog,
+    render_create_note_in_folder_dialog, render_create_notes_dir_dialog,
+    render_config_error_dialog, render_config_sources_dialog, render_confirm_open_dialog, render_create_wiki_note_dialog, render_delete_confirm_dialog, render_delete_folder_confirm_dialog, render_delete_marked_dialog,
+    render_directory_not_found_dialog, render_empty_directory_dialog, render_external_change_dialog,
+    render_global_substitute_dialog,
+    render_bulk_rename_dialog,
+    render_heading_rename_dialog, render_large_paste_dialog, render_merge_dialog,
+    render_help_dialog, render_note_stats_dialog, render_onboarding_dialog, render_passphrase_dialog, render_rename_folder_dialog,
+    render_rename_note_dialog, render_scaffold_dialog, render_scan_errors_dialog, render_snippets_dialog,
+    render_section_tasks_dialog, render_unsaved_changes_dialog, render_welcome_dialog,
 };
 pub use editor::render_editor;
 pub use outline::render_outline;
@@ -50,39 +72,46 @@ pub fn render(f: &mut Frame, app: &mut App) {
         ])
         .split(f.area());
 
-    let sidebar_constraint = if app.zen_mode {
-        Constraint::Length(0)
-    } else if app.sidebar_collapsed {
-        Constraint::Length(5)
-    } else {
-        Constraint::Percentage(20)
-    };
+    let (sidebar_narrow, outline_narrow) = layout::narrow_hidden(f.area().width, &app.config.display);
+    app.update_narrow_panels(sidebar_narrow, outline_narrow);
 
-    let outline_constraint = if app.zen_mode {
-        Constraint::Length(0)
-    } else if app.outline_collapsed {
-        Constraint::Length(5)
-    } else {
-        Constraint::Percentage(20)
+    let side_panel = |hidden: bool, collapsed: bool| {
+        if app.zen_mode || hidden {
+            SidePanel::Hidden
+        } else if collapsed {
+            SidePanel::Collapsed
+        } else {
+            SidePanel::Open
+        }
     };
+    let sidebar = side_panel(app.narrow.sidebar_hidden(), app.sidebar_collapsed);
+    let outline = side_panel(app.narrow.outline_hidden(), app.outline_collapsed);
 
     // Create main layout with left sidebar, content, and right outline
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            sidebar_constraint,     
-            Constraint::Min(20),    
-            outline_constraint,    
-        ])
-        .split(vertical_chunks[0]);
+    let chunks = layout::panels(vertical_chunks[0], sidebar, outline);
 
     // Render left sidebar (notes list)
     render_sidebar(f, app, chunks[0]);
 
+    // The quickfix window, when open, takes the bottom of the content column
+    let content_area = if app.quickfix_open {
+        let split = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(3),
+                Constraint::Length(quickfix::quickfix_height(app)),
+            ])
+            .split(chunks[1]);
+        quickfix::render_quickfix(f, app, split[1]);
+        split[0]
+    } else {
+        chunks[1]
+    };
+
     // Render content (either view or edit mode)
     match app.mode {
-        Mode::Normal => render_content(f, app, chunks[1]),
-        Mode::Edit => render_editor(f, app, chunks[1]),
+        Mode::Normal => render_content(f, app, content_area),
+        Mode::Edit => render_editor(f, app, content_area),
     }
 
     // Render right sidebar (outline)
@@ -99,15 +128,40 @@ pub fn render(f: &mut Frame, app: &mut App) {
         DialogState::CreateNoteInFolder => render_create_note_in_folder_dialog(f, app),
         DialogState::DeleteConfirm => render_delete_confirm_dialog(f, app),
         DialogState::DeleteFolderConfirm => render_delete_folder_confirm_dialog(f, app),
+        DialogState::DeleteMarkedConfirm => render_delete_marked_dialog(f, app),
         DialogState::RenameNote => render_rename_note_dialog(f, app),
         DialogState::RenameFolder => render_rename_folder_dialog(f, app),
         DialogState::Help => render_help_dialog(f, app),
         DialogState::EmptyDirectory => render_empty_directory_dialog(f, app),
         DialogState::DirectoryNotFound => render_directory_not_found_dialog(f, app),
+        DialogState::ConfigError => render_config_error_dialog(f, app),
+        DialogState::ChangeNotesDir => render_change_notes_dir_dialog(f, app),
+        DialogState::CreateNotesDirConfirm => render_create_notes_dir_dialog(f, app),
         DialogState::UnsavedChanges => render_unsaved_changes_dialog(f, app),
+        DialogState::GlobalSubstituteConfirm => render_global_substitute_dialog(f, app),
+        DialogState::BulkRenameConfirm => render_bulk_rename_dialog(f, app),
+        DialogState::HeadingRenameConfirm => render_heading_rename_dialog(f, app),
+        DialogState::MergeConfirm => render_merge_dialog(f, app),
+        DialogState::LargePaste => render_large_paste_dialog(f, app),
+        DialogState::ScanErrors => render_scan_errors_dialog(f, app),
+        DialogState::ExternalChange => render_external_change_dialog(f, app),
+        DialogState::SectionTasksConfirm => render_section_tasks_dialog(f, app),
+        DialogState::Passphrase => render_passphrase_dialog(f, app),
+        DialogState::NoteStats => render_note_stats_dialog(f, app),
+        DialogState::Snippets => render_snippets_dialog(f, app),
+        DialogState::ConfigSources => render_config_sources_dialog(f, app),
+        DialogState::Scaffold => render_scaffold_dialog(f, app),
         DialogState::CreateWikiNote => render_create_wiki_note_dialog(f, app),
+        DialogState::ConfirmOpen => render_confirm_open_dialog(f, app),
         DialogState::GraphView => graph_view::render_graph_view(f, app),
         DialogState::ThemeSelector => theme_picker::render_theme_picker(f, app),
+        DialogState::MacroList => macro_list::render_macro_list(f, app),
+        DialogState::YankList => yank_list::render_yank_list(f, app),
+        DialogState::History => history::render_history(f, app),
+        DialogState::MovePicker => move_picker::render_move_picker(f, app),
+        DialogState::VaultPicker => vault_picker::render_vault_picker(f, app),
+        DialogState::RefilePicker => refile_picker::render_refile_picker(f, app),
+        DialogState::ProseReport => prose_report::render_prose_report(f, app),
         DialogState::None => {
             // Render welcome dialog on top if active
             if app.show_welcome {
@@ -125,6 +179,14 @@ pub fn render(f: &mut Frame, app: &mut App) {
         wiki_autocomplete::render_wiki_autocomplete(f, app);
     }
 
+    if app.mode == Mode::Edit && !matches!(app.link_completion, LinkCompletionState::None) {
+        link_completion::render_link_completion(f, app);
+    }
+
+    if app.mode == Mode::Edit && !matches!(app.word_completion, WordCompletionState::None) {
+        word_completion::render_word_completion(f, app);
+    }
+
     if app.buffer_search.active {
         search_dialog::render_search_dialog(f, app, app.editor_area);
     }
@@ -135,4 +197,8 @@ pub fn render(f: &mut Frame, app: &mut App) {
 
     // Toast notifications float above everything else.
     toast::render_toast(f, app);
+
+    if app.debug_overlay {
+        debug_overlay::render_debug_overlay(f, app);
+    }
 }
@@ -0,0 +1,121 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::App;
+
+const MAX_VISIBLE: usize = 12;
+const MIN_WIDTH: u16 = 40;
+const MAX_WIDTH: u16 = 80;
+
+/// Centered list of the folders `m` can move sidebar items to, relative to
+/// the notes folder, which is shown as `/`.
+pub fn render_move_picker(f: &mut Frame, app: &mut App) {
+    let len = app.move_picker.folders.len();
+    if len == 0 {
+        return;
+    }
+
+    let selected = app.move_picker.selected.min(len - 1);
+    let visible = len.min(MAX_VISIBLE);
+
+    let mut scroll = app.move_picker.scroll_offset;
+    if selected < scroll {
+        scroll = selected;
+    } else if selected >= scroll + visible {
+        scroll = selected + 1 - visible;
+    }
+    app.move_picker.scroll_offset = scroll;
+
+    let theme = &app.theme;
+    let area = f.area();
+    let notes_path = app.config.notes_path();
+    let names: Vec<String> = app
+        .move_picker
+        .folders
+        .iter()
+        .map(|folder| match folder.strip_prefix(&notes_path) {
+            Ok(relative) if relative.as_os_str().is_empty() => "/".to_string(),
+            Ok(relative) => format!("{}/", relative.display()),
+            Err(_) => folder.display().to_string(),
+        })
+        .collect();
+
+    let longest = names.iter().map(|n| n.chars().count()).max().unwrap_or(0) as u16;
+    // marker(2) + name + inner padding(2)
+    let popup_width = (longest + 4)
+        .clamp(MIN_WIDTH, MAX_WIDTH)
+        .min(area.width.saturating_sub(4));
+    let popup_height = (visible as u16 + 4).min(area.height.saturating_sub(4));
+
+    let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+    let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+    f.render_widget(Clear, popup_area);
+
+    let title = match app.move_picker.items.len() {
+        1 => " Move to ".to_string(),
+        n => format!(" Move {} items to ", n),
+    };
+    let block = Block::default()
+        .title(Line::from(Span::styled(
+            title,
+            Style::default()
+                .fg(theme.dialog.title)
+                .add_modifier(Modifier::BOLD),
+        )))
+        .title_bottom(
+            Line::from(Span::styled(" enter move · esc cancel ", Style::default().fg(theme.muted))).right_aligned(),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.dialog.border))
+        .style(Style::default().bg(theme.dialog.background));
+
+    f.render_widget(block, popup_area);
+
+    let name_width = (popup_width as usize).saturating_sub(4);
+    let mut lines: Vec<Line> = Vec::with_capacity(visible + 2);
+    lines.push(Line::from(""));
+
+    for (row, name) in names.iter().enumerate().skip(scroll).take(visible) {
+        let is_sel = row == selected;
+        let marker = if is_sel { "▶ " } else { "  " };
+        // Keep the end of a long path, where the folder's own name is
+        let count = name.chars().count();
+        let name: String = if count > name_width {
+            let mut truncated = String::from("…");
+            truncated.extend(name.chars().skip(count + 1 - name_width));
+            truncated
+        } else {
+            name.clone()
+        };
+        let line_style = if is_sel {
+            Style::default().bg(theme.selection)
+        } else {
+            Style::default()
+        };
+
+        lines.push(
+            Line::from(vec![
+                Span::styled(marker, Style::default().fg(theme.dialog.title)),
+                Span::styled(name, Style::default().fg(theme.dialog.text)),
+            ])
+            .style(line_style),
+        );
+    }
+
+    lines.push(Line::from(""));
+
+    let inner = Rect::new(
+        popup_area.x + 1,
+        popup_area.y + 1,
+        popup_area.width.saturating_sub(2),
+        popup_area.height.saturating_sub(2),
+    );
+    f.render_widget(Paragraph::new(lines), inner);
+}
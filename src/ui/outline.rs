@@ -6,15 +6,27 @@ use ratatui::{
     Frame,
 };
 
-use crate::app::{App, Focus, Mode};
+use crate::app::{App, Focus, Mode, OutlineItem, OutlineKind};
+use crate::config::Theme;
 
 fn expand_tabs(text: &str) -> String {
     text.replace('\t', "    ")
 }
 
+fn item_style(item: &OutlineItem, theme: &Theme) -> Style {
+    let outline_theme = &theme.outline;
+    match (item.kind, item.level) {
+        (OutlineKind::Heading, 1) => Style::default().fg(outline_theme.heading1).add_modifier(Modifier::BOLD),
+        (OutlineKind::Heading, 2) => Style::default().fg(outline_theme.heading2),
+        (OutlineKind::Heading, 3) => Style::default().fg(outline_theme.heading3),
+        (OutlineKind::Heading, _) | (OutlineKind::ListItem, _) => Style::default().fg(outline_theme.heading4),
+        (OutlineKind::CodeBlock, _) => Style::default().fg(theme.muted),
+        (OutlineKind::BoldLine, _) => Style::default().fg(outline_theme.heading4).add_modifier(Modifier::BOLD),
+    }
+}
+
 pub fn render_outline(f: &mut Frame, app: &mut App, area: Rect) {
     let theme = &app.theme;
-    let outline_theme = &theme.outline;
     if app.outline_collapsed {
         render_collapsed_outline(f, app, area);
         return;
@@ -25,21 +37,17 @@ pub fn render_outline(f: &mut Frame, app: &mut App, area: Rect) {
         .iter()
         .map(|item| {
             let indent = "  ".repeat(item.level.saturating_sub(1));
-            let prefix = match item.level {
-                1 => "# ",
-                2 => "## ",
-                3 => "### ",
-                _ => "",
-            };
-            let style = match item.level {
-                1 => Style::default().fg(outline_theme.heading1).add_modifier(Modifier::BOLD),
-                2 => Style::default().fg(outline_theme.heading2),
-                3 => Style::default().fg(outline_theme.heading3),
-                _ => Style::default().fg(outline_theme.heading4),
+            let prefix = match (item.kind, item.level) {
+                (OutlineKind::Heading, 1) => "# ",
+                (OutlineKind::Heading, 2) => "## ",
+                (OutlineKind::Heading, 3) => "### ",
+                (OutlineKind::Heading, _) | (OutlineKind::BoldLine, _) => "",
+                (OutlineKind::ListItem, _) => "• ",
+                (OutlineKind::CodeBlock, _) => "` ",
             };
             ListItem::new(Line::from(Span::styled(
                 format!("{}{}{}", indent, prefix, expand_tabs(&item.title)),
-                style,
+                item_style(item, theme),
             )))
         })
         .collect();
@@ -75,7 +83,6 @@ pub fn render_outline(f: &mut Frame, app: &mut App, area: Rect) {
 
 fn render_collapsed_outline(f: &mut Frame, app: &mut App, area: Rect) {
     let theme = &app.theme;
-    let outline_theme = &theme.outline;
     let in_edit_mode = app.mode == Mode::Edit;
 
     let items: Vec<ListItem> = app
@@ -86,19 +93,16 @@ fn render_collapsed_outline(f: &mut Frame, app: &mut App, area: Rect) {
             // Hide selection indicator in edit mode
             let is_selected = !in_edit_mode && app.outline_state.selected() == Some(idx);
 
-            let symbol = match item.level {
-                1 => "◆",  // H1
-                2 => "■",  // H2
-                3 => "▸",  // H3
-                _ => "›",  // H4+
-            };
-
-            let style = match item.level {
-                1 => Style::default().fg(outline_theme.heading1).add_modifier(Modifier::BOLD),
-                2 => Style::default().fg(outline_theme.heading2),
-                3 => Style::default().fg(outline_theme.heading3),
-                _ => Style::default().fg(outline_theme.heading4),
+            let symbol = match (item.kind, item.level) {
+                (OutlineKind::Heading, 1) => "◆",  // H1
+                (OutlineKind::Heading, 2) => "■",  // H2
+                (OutlineKind::Heading, 3) => "▸",  // H3
+                (OutlineKind::Heading, _) => "›",  // H4+
+                (OutlineKind::ListItem, _) => "•",
+                (OutlineKind::CodeBlock, _) => "`",
+                (OutlineKind::BoldLine, _) => "▪",
             };
+            let style = item_style(item, theme);
 
             let display = if is_selected {
                 format!("▶{}", symbol)
@@ -0,0 +1,114 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::App;
+
+const MAX_VISIBLE: usize = 12;
+const MIN_WIDTH: u16 = 44;
+const MAX_WIDTH: u16 = 90;
+
+/// Centered `:prose` list: where each prose hint is in the buffer and what
+/// it says.
+pub fn render_prose_report(f: &mut Frame, app: &mut App) {
+    let len = app.prose_report.hints.len();
+    if len == 0 {
+        return;
+    }
+
+    let selected = app.prose_report.selected.min(len - 1);
+    let visible = len.min(MAX_VISIBLE);
+
+    let mut scroll = app.prose_report.scroll_offset;
+    if selected < scroll {
+        scroll = selected;
+    } else if selected >= scroll + visible {
+        scroll = selected + 1 - visible;
+    }
+    app.prose_report.scroll_offset = scroll;
+
+    let theme = &app.theme;
+    let area = f.area();
+    let positions: Vec<String> = app
+        .prose_report
+        .hints
+        .iter()
+        .map(|hint| format!("{}:{}", hint.row + 1, hint.start_col + 1))
+        .collect();
+    let position_width = positions.iter().map(String::len).max().unwrap_or(0);
+    let longest = app.prose_report.hints.iter().map(|h| h.message.chars().count()).max().unwrap_or(0);
+    // marker(2) + position + gap(2) + message + padding and borders
+    let popup_width = ((longest + position_width) as u16 + 8)
+        .clamp(MIN_WIDTH, MAX_WIDTH)
+        .min(area.width.saturating_sub(4));
+    let popup_height = (visible as u16 + 4).min(area.height.saturating_sub(4));
+
+    let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+    let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(Line::from(Span::styled(
+            format!(" Prose ({}) ", len),
+            Style::default()
+                .fg(theme.dialog.title)
+                .add_modifier(Modifier::BOLD),
+        )))
+        .title_bottom(
+            Line::from(Span::styled(" enter jump · esc close ", Style::default().fg(theme.muted))).right_aligned(),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.dialog.border))
+        .style(Style::default().bg(theme.dialog.background));
+
+    f.render_widget(block, popup_area);
+
+    let message_width = (popup_width as usize).saturating_sub(position_width + 6);
+    let mut lines: Vec<Line> = Vec::with_capacity(visible + 2);
+    lines.push(Line::from(""));
+
+    for (row, hint) in app.prose_report.hints.iter().enumerate().skip(scroll).take(visible) {
+        let is_sel = row == selected;
+        let marker = if is_sel { "▶ " } else { "  " };
+        let line_style = if is_sel {
+            Style::default().bg(theme.selection)
+        } else {
+            Style::default()
+        };
+        let message: String = if hint.message.chars().count() > message_width {
+            let mut truncated: String = hint.message.chars().take(message_width.saturating_sub(1)).collect();
+            truncated.push('…');
+            truncated
+        } else {
+            hint.message.clone()
+        };
+
+        lines.push(
+            Line::from(vec![
+                Span::styled(marker, Style::default().fg(theme.dialog.title)),
+                Span::styled(
+                    format!("{:>width$}  ", positions[row], width = position_width),
+                    Style::default().fg(theme.muted),
+                ),
+                Span::styled(message, Style::default().fg(theme.dialog.text)),
+            ])
+            .style(line_style),
+        );
+    }
+
+    lines.push(Line::from(""));
+
+    let inner = Rect::new(
+        popup_area.x + 1,
+        popup_area.y + 1,
+        popup_area.width.saturating_sub(2),
+        popup_area.height.saturating_sub(2),
+    );
+    f.render_widget(Paragraph::new(lines), inner);
+}
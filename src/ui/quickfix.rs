@@ -0,0 +1,65 @@
+//! Quickfix window: the quickfix list below the content, opened with `:copen`.
+
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::app::App;
+
+/// Most entries shown at once; the window scrolls to keep the current one in view.
+const MAX_VISIBLE_ENTRIES: usize = 8;
+
+/// Rows the quickfix window takes, borders included.
+pub fn quickfix_height(app: &App) -> u16 {
+    app.quickfix.entries.len().clamp(1, MAX_VISIBLE_ENTRIES) as u16 + 2
+}
+
+pub fn render_quickfix(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let list = &app.quickfix;
+    let visible = area.height.saturating_sub(2) as usize;
+    let current = list.current.unwrap_or(0);
+    let scroll = (current + 1).saturating_sub(visible);
+
+    let lines: Vec<Line> = if list.is_empty() {
+        vec![Line::from(Span::styled(
+            " No quickfix list",
+            Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+        ))]
+    } else {
+        list.entries
+            .iter()
+            .enumerate()
+            .skip(scroll)
+            .take(visible)
+            .map(|(i, entry)| {
+                let is_current = list.current == Some(i);
+                let location = format!(" {}:{}:{} ", i + 1, entry.line + 1, entry.col + 1);
+                let (location_style, text_style) = if is_current {
+                    (
+                        Style::default().fg(theme.primary).bg(theme.selection).add_modifier(Modifier::BOLD),
+                        Style::default().fg(theme.foreground).bg(theme.selection),
+                    )
+                } else {
+                    (Style::default().fg(theme.muted), Style::default().fg(theme.foreground))
+                };
+                Line::from(vec![
+                    Span::styled(location, location_style),
+                    Span::styled(entry.text.clone(), text_style),
+                ])
+            })
+            .collect()
+    };
+
+    let title = format!(" Quickfix: {} ({}) ", list.title, list.entries.len());
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border))
+        .style(Style::default().bg(theme.background));
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
@@ -0,0 +1,129 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::dialogs::input_spans;
+use crate::app::App;
+
+const MAX_VISIBLE: usize = 12;
+const MIN_WIDTH: u16 = 40;
+const MAX_WIDTH: u16 = 80;
+
+/// Centered list of where in the picked note a refiled item can go, narrowed
+/// by the query typed above it.
+pub fn render_refile_picker(f: &mut Frame, app: &mut App) {
+    let choices = app.refile_picker.choices();
+    let verb = app.refile_picker.kind.verb();
+    let len = choices.len();
+    let selected = app.refile_picker.selected.min(len.saturating_sub(1));
+    let visible = len.clamp(1, MAX_VISIBLE);
+
+    let mut scroll = app.refile_picker.scroll_offset;
+    if selected < scroll {
+        scroll = selected;
+    } else if selected >= scroll + visible {
+        scroll = selected + 1 - visible;
+    }
+    app.refile_picker.scroll_offset = scroll;
+
+    let theme = &app.theme;
+    let area = f.area();
+    let picker = &app.refile_picker;
+    let title = app.notes.get(picker.target).map_or("", |n| n.title.as_str());
+
+    let longest = choices.iter().map(|(_, label)| label.chars().count()).max().unwrap_or(0);
+    // marker(2) + label + inner padding(2)
+    let popup_width = (longest.max(title.chars().count() + 12) as u16 + 4)
+        .clamp(MIN_WIDTH, MAX_WIDTH)
+        .min(area.width.saturating_sub(4));
+    // query + separator + list + padding and borders
+    let popup_height = (visible as u16 + 5).min(area.height.saturating_sub(4));
+
+    let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+    let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(Line::from(Span::styled(
+            format!(" {} to {} ", verb, title),
+            Style::default()
+                .fg(theme.dialog.title)
+                .add_modifier(Modifier::BOLD),
+        )))
+        .title_bottom(
+            Line::from(Span::styled(
+                format!(" enter {} · esc cancel ", verb.to_lowercase()),
+                Style::default().fg(theme.muted),
+            ))
+            .right_aligned(),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.dialog.border))
+        .style(Style::default().bg(theme.dialog.background));
+
+    f.render_widget(block, popup_area);
+
+    let inner_width = popup_width.saturating_sub(2) as usize;
+    let mut lines: Vec<Line> = Vec::with_capacity(visible + 3);
+    lines.push(if picker.query.is_empty() {
+        Line::from(vec![
+            Span::raw(" "),
+            Span::styled("Filter headings, or name a new one...", Style::default().fg(theme.muted)),
+        ])
+    } else {
+        let mut spans = vec![Span::raw(" ")];
+        spans.extend(input_spans(
+            &picker.query,
+            picker.query.cursor(),
+            Style::default().fg(theme.dialog.text),
+            Span::styled("█", Style::default().fg(theme.primary)),
+        ));
+        Line::from(spans)
+    });
+    lines.push(Line::from(Span::styled("─".repeat(inner_width), Style::default().fg(theme.muted))));
+
+    for (row, (_, label)) in choices.iter().enumerate().skip(scroll).take(visible) {
+        let is_sel = row == selected;
+        let marker = if is_sel { "▶ " } else { "  " };
+        let label: String = if label.chars().count() > inner_width.saturating_sub(2) {
+            let mut truncated: String = label.chars().take(inner_width.saturating_sub(3)).collect();
+            truncated.push('…');
+            truncated
+        } else {
+            label.clone()
+        };
+        let line_style = if is_sel {
+            Style::default().bg(theme.selection)
+        } else {
+            Style::default()
+        };
+        // The first choice is the note's end rather than one of its headings
+        let label_style = if row == 0 {
+            Style::default().fg(theme.muted)
+        } else {
+            Style::default().fg(theme.dialog.text)
+        };
+
+        lines.push(
+            Line::from(vec![
+                Span::styled(marker, Style::default().fg(theme.dialog.title)),
+                Span::styled(label, label_style),
+            ])
+            .style(line_style),
+        );
+    }
+
+    let inner = Rect::new(
+        popup_area.x + 1,
+        popup_area.y + 1,
+        popup_area.width.saturating_sub(2),
+        popup_area.height.saturating_sub(2),
+    );
+    f.render_widget(Paragraph::new(lines), inner);
+}
@@ -6,6 +6,7 @@ use ratatui::{
     Frame,
 };
 
+use super::dialogs::input_spans;
 use crate::app::App;
 
 const DIALOG_WIDTH: u16 = 35;
@@ -49,25 +50,28 @@ pub fn render_search_dialog(f: &mut Frame, app: &App, content_area: Rect) {
     let available_width = (dialog_width as usize).saturating_sub(4 + count_text.len() + 2);
 
     let query_char_count = query.chars().count();
-    let display_query = if query_char_count > available_width {
+    let (display_query, display_cursor) = if query_char_count > available_width {
         let skip_count = query_char_count.saturating_sub(available_width);
         let truncated: String = query.chars().skip(skip_count).collect();
-        format!("...{}", truncated)
+        (format!("...{}", truncated), (query.cursor() + 3).saturating_sub(skip_count).max(3))
     } else {
-        query.clone()
+        (query.to_string(), query.cursor())
     };
 
-    let input_line = Line::from(vec![
-        Span::styled(" ", Style::default()),
-        Span::styled(&display_query, Style::default().fg(theme.search.input)),
+    let mut input_line = vec![Span::styled(" ", Style::default())];
+    input_line.extend(input_spans(
+        &display_query,
+        display_cursor,
+        Style::default().fg(theme.search.input),
         Span::styled(cursor, Style::default().fg(theme.primary).add_modifier(Modifier::SLOW_BLINK)),
-        Span::styled(" ", Style::default()),
-    ]);
+    ));
+    input_line.push(Span::styled(" ", Style::default()));
+    let input_line = Line::from(input_line);
 
     let hint_text = if count_text.is_empty() {
-        " ↑↓/Tab: nav, Esc: close ".to_string()
+        " ↑↓: history, Tab: nav, Esc: close ".to_string()
     } else {
-        format!(" {} ↑↓ ", count_text)
+        format!(" {} Tab ", count_text)
     };
 
     let border_color = if match_count > 0 {
@@ -6,7 +6,8 @@ use ratatui::{
     Frame,
 };
 
-use crate::app::{App, CutItem, Focus, Mode, SidebarItemKind};
+use super::dialogs::input_spans;
+use crate::app::{App, CutItem, Focus, Mode, NoteLock, RecentFilter, SidebarItemKind};
 
 pub fn render_sidebar(f: &mut Frame, app: &mut App, area: Rect) {
     let theme = &app.theme;
@@ -43,11 +44,14 @@ pub fn render_sidebar(f: &mut Frame, app: &mut App, area: Rect) {
             .border_style(Style::default().fg(border_color))
             .title(" Search ");
 
-        let search_text = Paragraph::new(Line::from(vec![
-            Span::styled("/", Style::default().fg(theme.foreground)),
-            Span::styled(&app.search_query, Style::default().fg(theme.foreground)),
+        let mut spans = vec![Span::styled("/", Style::default().fg(theme.foreground))];
+        spans.extend(input_spans(
+            &app.search_query,
+            app.search_query.cursor(),
+            Style::default().fg(theme.foreground),
             Span::styled("_", Style::default().fg(border_color)),
-        ]))
+        ));
+        let search_text = Paragraph::new(Line::from(spans))
         .block(search_block);
 
         f.render_widget(search_text, search_area);
@@ -89,7 +93,11 @@ pub fn render_sidebar(f: &mut Frame, app: &mut App, area: Rect) {
                     (icon, style)
                 }
                 SidebarItemKind::Note { note_index } => {
-                    let icon = "  ";
+                    let icon = match app.notes.get(*note_index).map(|n| &n.lock) {
+                        Some(NoteLock::Locked) => "🔒",
+                        Some(NoteLock::Unlocked(_)) => "🔓",
+                        _ => "  ",
+                    };
                     let is_match = is_searching && app.search_matched_notes.contains(note_index);
                     let style = if is_selected {
                         Style::default()
@@ -104,13 +112,29 @@ pub fn render_sidebar(f: &mut Frame, app: &mut App, area: Rect) {
                     };
                     (icon, style)
                 }
+                SidebarItemKind::Header => {
+                    let style = Style::default().fg(theme.info).add_modifier(Modifier::BOLD);
+                    (" ", style)
+                }
             };
 
             if is_cut {
                 style = style.add_modifier(Modifier::DIM | Modifier::ITALIC);
             }
+            let mark = if app.is_marked_sidebar_item(item) {
+                style = style.fg(theme.info);
+                "● "
+            } else {
+                ""
+            };
 
-            let display = format!("{}{}{}", indent, icon, item.display_name);
+            // Edits kept in the buffer and not yet saved
+            let dirty = match item.kind {
+                SidebarItemKind::Note { note_index } if app.dirty_note == Some(note_index) => " [+]",
+                _ => "",
+            };
+            let pin = if app.is_pinned_sidebar_item(item) { " 📌" } else { "" };
+            let display = format!("{}{}{}{}{}{}", indent, mark, icon, item.display_name, pin, dirty);
             ListItem::new(Line::from(Span::styled(display, style)))
         })
         .collect();
@@ -121,16 +145,29 @@ pub fn render_sidebar(f: &mut Frame, app: &mut App, area: Rect) {
         Style::default().fg(theme.border)
     };
 
+    // Shown so a filter left on isn't mistaken for missing notes
+    let filter = match app.recent_filter {
+        RecentFilter::All => String::new(),
+        recent => format!("[{}] ", recent.label()),
+    };
     let title = if is_searching {
         let match_count = app.search_matched_notes.len();
         let total_count = app.notes.len();
-        format!(" Found {}/{} ", match_count, total_count)
+        format!(" Found {}/{} {}", match_count, total_count, filter)
     } else {
         let note_count = app.sidebar_items
             .iter()
             .filter(|item| matches!(item.kind, SidebarItemKind::Note { .. }))
             .count();
-        format!(" Notes ({}) [{}] ", note_count, app.sort_mode.label())
+        format!(" Notes ({}) [{}] {}", note_count, app.sort_mode.label(), filter)
+    };
+    let title = match app.sidebar_marks.len() {
+        0 => title,
+        marked => format!("{}· {} marked ", title, marked),
+    };
+    let title = match app.scan_errors.len() {
+        0 => title,
+        skipped => format!("{}· {} unreadable ", title, skipped),
     };
 
     let sidebar = List::new(items)
@@ -0,0 +1,169 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+use unicode_width::UnicodeWidthChar;
+
+use crate::app::App;
+use crate::editor::HighlightRange;
+use crate::highlight_worker::highlight_source;
+
+const TAB_WIDTH: usize = 4;
+
+/// The note's source with line numbers, highlighted like the editor. Search
+/// matches are drawn on their source columns.
+pub fn render_source_view(f: &mut Frame, app: &mut App, area: Rect) {
+    let Some(source) = app.displayed_source() else {
+        return;
+    };
+    let lines: Vec<&str> = source.lines().collect();
+    let Some(view) = app.source_view.as_mut() else {
+        return;
+    };
+    if view.note != app.selected_note {
+        *view = crate::app::SourceView { note: app.selected_note, ..Default::default() };
+    }
+    view.cursor = view.cursor.min(lines.len().saturating_sub(1));
+
+    let gutter_width = lines.len().max(1).to_string().len() + 1;
+    let text_width = (area.width as usize).saturating_sub(gutter_width + 1).max(1);
+    let height = area.height as usize;
+    let row_height = |row: usize| wrap_line(lines.get(row).copied().unwrap_or(""), text_width).len();
+
+    // Keep the cursor line on screen, scrolling by whole source lines
+    view.scroll = view.scroll.min(view.cursor);
+    while view.scroll < view.cursor && (view.scroll..=view.cursor).map(row_height).sum::<usize>() > height {
+        view.scroll += 1;
+    }
+    let view = *view;
+
+    let theme = &app.theme;
+    let mut highlights: Vec<Vec<HighlightRange>> = vec![Vec::new(); lines.len()];
+    for highlight in highlight_source(&source, &app.get_highlight_colors()) {
+        if let Some(row) = highlights.get_mut(highlight.row) {
+            row.push(highlight);
+        }
+    }
+    let search = &app.buffer_search;
+
+    let mut out: Vec<Line> = Vec::with_capacity(height);
+    for (row, line) in lines.iter().enumerate().skip(view.scroll) {
+        if out.len() >= height {
+            break;
+        }
+        let is_cursor = row == view.cursor;
+        let mut styles: Vec<Style> = (0..line.chars().count())
+            .map(|col| style_at(&highlights[row], row, col).unwrap_or(Style::default().fg(theme.foreground)))
+            .collect();
+        for (idx, m) in search.matches.iter().enumerate().filter(|(_, m)| m.row == row) {
+            let bg = if idx == search.current_match_index {
+                theme.search.match_current
+            } else {
+                theme.search.match_highlight
+            };
+            for style in styles.iter_mut().take(m.end_col).skip(m.start_col) {
+                *style = style.bg(bg).fg(Color::Black);
+            }
+        }
+
+        let number_style = if is_cursor {
+            Style::default().fg(theme.warning)
+        } else {
+            Style::default().fg(theme.muted)
+        };
+        let line_style = if is_cursor {
+            Style::default().bg(theme.selection)
+        } else {
+            Style::default()
+        };
+        for (i, (start, end)) in wrap_line(line, text_width).into_iter().enumerate() {
+            let number = if i == 0 { format!("{:>w$} ", row + 1, w = gutter_width - 1) } else { " ".repeat(gutter_width) };
+            let mut spans = vec![Span::styled(number, number_style)];
+            spans.extend(styled_spans(line, start, end, &styles));
+            out.push(Line::from(spans).style(line_style));
+            if out.len() >= height {
+                break;
+            }
+        }
+    }
+
+    f.render_widget(Paragraph::new(out), area);
+}
+
+/// The style the editor would give `col`: the highest-priority highlight,
+/// the first one on a tie.
+fn style_at(highlights: &[HighlightRange], row: usize, col: usize) -> Option<Style> {
+    let mut best: Option<&HighlightRange> = None;
+    for highlight in highlights.iter().filter(|h| h.contains(row, col)) {
+        if best.is_none_or(|b| highlight.priority > b.priority) {
+            best = Some(highlight);
+        }
+    }
+    best.map(|h| h.style)
+}
+
+fn char_width(c: char) -> usize {
+    if c == '\t' {
+        TAB_WIDTH
+    } else {
+        c.width().unwrap_or(0)
+    }
+}
+
+/// Char ranges of `line` that fit `width` display columns each, breaking
+/// anywhere so every source column stays where it is.
+fn wrap_line(line: &str, width: usize) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let (mut start, mut used) = (0, 0);
+    for (i, c) in line.chars().enumerate() {
+        let w = char_width(c);
+        if used + w > width && i > start {
+            ranges.push((start, i));
+            start = i;
+            used = 0;
+        }
+        used += w;
+    }
+    ranges.push((start, line.chars().count()));
+    ranges
+}
+
+/// Chars `start..end` of `line` as spans, one per run of equal styles.
+fn styled_spans(line: &str, start: usize, end: usize, styles: &[Style]) -> Vec<Span<'static>> {
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut text = String::new();
+    let mut current: Option<Style> = None;
+    for (col, c) in line.chars().enumerate().take(end).skip(start) {
+        let style = styles[col];
+        if current.is_some_and(|s| s != style) {
+            spans.push(Span::styled(std::mem::take(&mut text), current.unwrap_or_default()));
+        }
+        current = Some(style);
+        if c == '\t' {
+            text.push_str(&" ".repeat(TAB_WIDTH));
+        } else {
+            text.push(c);
+        }
+    }
+    if !text.is_empty() {
+        spans.push(Span::styled(text, current.unwrap_or_default()));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_line_keeps_every_column() {
+        assert_eq!(wrap_line("", 4), [(0, 0)]);
+        assert_eq!(wrap_line("abcdefghij", 4), [(0, 4), (4, 8), (8, 10)]);
+        // Wide chars and tabs count their display width
+        assert_eq!(wrap_line("日本語", 4), [(0, 2), (2, 3)]);
+        assert_eq!(wrap_line("a\tb", 4), [(0, 1), (1, 2), (2, 3)]);
+    }
+}
@@ -6,363 +6,562 @@ use ratatui::{
     Frame,
 };
 
-use crate::app::{App, BlockInsertMode, Focus, Mode};
+use crate::app::{App, BlockInsertMode, Focus, Mode, Note};
 use crate::vim::VimMode as VimModeNew;
 
+/// Draws one status bar segment; an empty result leaves the segment out.
+type Segment = fn(&StatusContext) -> Vec<Span<'static>>;
+
+/// The segment named `name` in `status_left`/`status_right`. Every name in
+/// [`crate::config::STATUS_SEGMENTS`] has one.
+fn segment(name: &str) -> Option<Segment> {
+    let segment: Segment = match name {
+        "brand" => brand,
+        "vault" => vault,
+        "mode" => mode,
+        "pending" => pending,
+        "note_title" => note_title,
+        "dirty" => dirty,
+        "message" => message,
+        "recording" => recording,
+        "indexing" => indexing,
+        "zen" => zen,
+        "search_count" => search_count,
+        "word_count" => word_count,
+        "cursor_pos" => cursor_pos,
+        "clock" => clock,
+        "help" => help,
+        _ => return None,
+    };
+    Some(segment)
+}
+
+/// Left segments drawn straight after the one before, without a separator.
+const ATTACHED: &[&str] = &["dirty", "message"];
+
+/// Right segments that stay when the bar is too narrow; the rest are dropped,
+/// nearest the middle first.
+const KEPT_WHEN_NARROW: &[&str] = &["recording", "indexing", "zen", "help"];
+
+/// What the segments draw from, worked out once per frame.
+struct StatusContext<'a> {
+    app: &'a App,
+    /// None when a folder is listed, as it has no note of its own
+    viewed_note: Option<&'a Note>,
+    /// Command line, search input or vim message, with whether it's a warning.
+    /// Shown in place of the note title.
+    command_input: Option<(String, bool)>,
+    /// Widest the note title may be; None while it fits.
+    title_width: Option<usize>,
+}
+
 pub fn render_status_bar(f: &mut Frame, app: &App, area: Rect) {
     const ZEN_MAX_WIDTH: u16 = 95;
 
     let theme = &app.theme;
 
-    // Calculate stats - count only actual words, not markdown syntax
-    let word_count = if let Some(note) = app.current_note() {
-        note.content
-            .split_whitespace()
-            .filter(|word| {
-                word.chars().any(|c| c.is_alphanumeric())
-            })
-            .count()
+    // A listed folder has no note of its own
+    let viewed_note = if app.folder_view_path().is_some() {
+        None
     } else {
-        0
+        app.current_note()
     };
-
-    // Calculate percentage
-    let percentage = if app.content_items.is_empty() {
-        0
-    } else {
-        ((app.content_cursor + 1) * 100) / app.content_items.len()
+    let mut ctx = StatusContext {
+        app,
+        viewed_note,
+        command_input: command_input(app),
+        title_width: None,
     };
 
-    let note_path = if app.zen_mode {
-        // In zen mode, just show the note title
-        app.current_note()
-            .map(|n| n.title.clone())
-            .unwrap_or_else(|| "—".to_string())
+    let mut left_names: Vec<&str> = app.config.status_left.iter().map(String::as_str).collect();
+    // The command line has to show up somewhere
+    if ctx.command_input.is_some() && !left_names.contains(&"note_title") {
+        left_names.push("note_title");
+    }
+    // So does the vault, once there are several, even with a status bar
+    // configured before vaults were
+    if !app.config.vaults.is_empty() && !app.config.shows_status_segment("vault") {
+        let at = left_names.iter().position(|name| *name == "brand").map_or(0, |i| i + 1);
+        left_names.insert(at, "vault");
+    }
+    let mut left = render_segments(&ctx, &left_names);
+    let mut right = render_segments(&ctx, &app.config.status_right);
+
+    let content_width = if app.zen_mode {
+        (area.width as usize).min(ZEN_MAX_WIDTH as usize)
     } else {
-        app.current_note()
-            .and_then(|n| n.file_path.as_ref())
-            .map(|p| {
-                let path_str = p.to_string_lossy().to_string();
-                if let Some(home) = dirs::home_dir() {
-                    let home_str = home.to_string_lossy().to_string();
-                    if path_str.starts_with(&home_str) {
-                        return path_str.replacen(&home_str, "~", 1);
-                    }
-                }
-                path_str
-            })
-            .unwrap_or_else(|| "—".to_string())
+        area.width as usize
     };
 
-    // Get mode indicator and command info for edit mode
-    let (mode_text, pending_info, command_input, normal_status) = match app.mode {
-        Mode::Normal => {
-            let mode = match app.focus {
-                Focus::Sidebar => "sidebar",
-                Focus::Content => "content",
-                Focus::Outline => "outline",
-            };
-            let status = app.status_message.clone();
-            (mode.to_string(), String::new(), None, status)
+    // Too narrow: drop right-side segments, then shorten the title
+    while left_width(&left) + right_width(&right) > content_width {
+        let Some(i) = right.iter().position(|(name, _)| !KEPT_WHEN_NARROW.contains(name)) else {
+            break;
+        };
+        right.remove(i);
+    }
+    let overflow = (left_width(&left) + right_width(&right)).saturating_sub(content_width);
+    if overflow > 0 {
+        if let Some(title) = left.iter_mut().find(|(name, _)| *name == "note_title") {
+            // The title's leading space stays
+            let width = spans_width(&title.1).saturating_sub(1);
+            ctx.title_width = Some(width.saturating_sub(overflow));
+            title.1 = note_title(&ctx);
         }
-        Mode::Edit => {
-            // Get detailed vim mode info
-            let vim = &app.vim;
-            let mode_name = match &vim.mode {
-                VimModeNew::Search { .. } => "search".to_string(),
-                VimModeNew::SearchLocked { .. } => "search locked".to_string(),
-                VimModeNew::Command => "command".to_string(),
-                VimModeNew::OperatorPending { .. } => "normal".to_string(),
-                _ => {
-                    if let Some(ref block_state) = app.block_insert_state {
-                        match block_state.mode {
-                            BlockInsertMode::Insert => "v-blk insert".to_string(),
-                            BlockInsertMode::Append => "v-blk append".to_string(),
-                        }
-                    } else {
-                        match app.vim_mode {
-                            crate::app::VimMode::Normal => "normal".to_string(),
-                            crate::app::VimMode::Insert => "insert".to_string(),
-                            crate::app::VimMode::Replace => "replace".to_string(),
-                            crate::app::VimMode::Visual => "visual".to_string(),
-                            crate::app::VimMode::VisualLine => "v-line".to_string(),
-                            crate::app::VimMode::VisualBlock => "v-block".to_string(),
-                        }
-                    }
-                }
-            };
+    }
 
-            // Build pending info string
-            let mut pending_parts = Vec::new();
+    let statusbar = &theme.statusbar;
+    let bg_style = if app.config.transparent_bg {
+        Style::default()
+    } else {
+        Style::default().bg(statusbar.background)
+    };
 
-            // Recording indicator
-            if vim.macros.is_recording() {
-                pending_parts.push("recording".to_string());
-            }
+    let mut spans = Vec::new();
+    if app.zen_mode {
+        let left_margin = (area.width as usize).saturating_sub(content_width) / 2;
+        if left_margin > 0 {
+            spans.push(Span::styled(" ".repeat(left_margin), bg_style));
+        }
+    }
 
-            // Count prefix
-            if let Some(count) = vim.count {
-                pending_parts.push(format!("{}", count));
-            }
+    let middle_padding = content_width.saturating_sub(left_width(&left) + right_width(&right));
+    spans.extend(join_left(left, Style::default().fg(statusbar.separator)));
+    spans.push(Span::styled(" ".repeat(middle_padding), bg_style));
+    spans.extend(join_right(right));
 
-            // Operator pending
-            if let VimModeNew::OperatorPending { operator, count } = &vim.mode {
-                if let Some(c) = count {
-                    pending_parts.push(format!("{}", c));
-                }
-                pending_parts.push(format!("{}", operator.char()));
-            }
+    let current_width = spans_width(&spans);
+    let right_margin = (area.width as usize).saturating_sub(current_width);
+    if right_margin > 0 {
+        spans.push(Span::styled(" ".repeat(right_margin), bg_style));
+    }
 
-            // Pending g (for gg)
-            if vim.pending_g {
-                pending_parts.push("g".to_string());
-            }
+    let status_line = Line::from(spans);
+    let status_bar = Paragraph::new(status_line)
+        .style(bg_style);
 
-            // Pending z (for zz, zt, zb)
-            if vim.pending_z {
-                pending_parts.push("z".to_string());
-            }
+    f.render_widget(status_bar, area);
+}
 
-            // Pending find (f, F, t, T)
-            if vim.pending_find.is_some() {
-                pending_parts.push("f/t".to_string());
-            }
+/// The named segments that have something to show, in order. Unknown names
+/// are skipped here; config validation reports them.
+fn render_segments<'n, S: AsRef<str>>(ctx: &StatusContext, names: &'n [S]) -> Vec<(&'n str, Vec<Span<'static>>)> {
+    names
+        .iter()
+        .map(AsRef::as_ref)
+        .filter_map(|name| segment(name).map(|draw| (name, draw(ctx))))
+        .filter(|(_, spans)| !spans.is_empty())
+        .collect()
+}
 
-            // Awaiting replace char
-            if vim.awaiting_replace {
-                pending_parts.push("r".to_string());
-            }
+fn spans_width(spans: &[Span]) -> usize {
+    spans.iter().map(|s| s.content.chars().count()).sum()
+}
 
-            // Pending text object scope (i/a)
-            if let Some(scope) = &vim.pending_text_object_scope {
-                let ch = match scope {
-                    crate::vim::TextObjectScope::Inner => 'i',
-                    crate::vim::TextObjectScope::Around => 'a',
-                };
-                pending_parts.push(format!("{}", ch));
-            }
+/// Left segments separated by `›`, with [`ATTACHED`] ones joined on directly.
+fn join_left(segments: Vec<(&str, Vec<Span<'static>>)>, separator: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    for (i, (name, segment)) in segments.into_iter().enumerate() {
+        if i > 0 && !ATTACHED.contains(&name) {
+            spans.push(Span::styled("›", separator));
+        }
+        spans.extend(segment);
+    }
+    spans
+}
 
-            // Pending mark
-            if let Some(mark) = &vim.pending_mark {
-                let ch = match mark {
-                    crate::vim::PendingMark::Set => 'm',
-                    crate::vim::PendingMark::GotoExact => '`',
-                    crate::vim::PendingMark::GotoLine => '\'',
-                };
-                pending_parts.push(format!("{}", ch));
-            }
+fn left_width(segments: &[(&str, Vec<Span<'static>>)]) -> usize {
+    let separators = segments
+        .iter()
+        .skip(1)
+        .filter(|(name, _)| !ATTACHED.contains(name))
+        .count();
+    segments.iter().map(|(_, s)| spans_width(s)).sum::<usize>() + separators
+}
 
-            // Pending macro
-            if let Some(mac) = &vim.pending_macro {
-                let ch = match mac {
-                    crate::vim::PendingMacro::Record => 'q',
-                    crate::vim::PendingMacro::Play => '@',
-                };
-                pending_parts.push(format!("{}", ch));
-            }
+/// Right segments two spaces apart, ending a space from the edge.
+fn join_right(segments: Vec<(&str, Vec<Span<'static>>)>) -> Vec<Span<'static>> {
+    let count = segments.len();
+    let mut spans = Vec::new();
+    for (i, (_, segment)) in segments.into_iter().enumerate() {
+        spans.extend(segment);
+        spans.push(Span::raw(if i + 1 < count { "  " } else { " " }));
+    }
+    spans
+}
 
-            // Selected register
-            if let Some(reg) = vim.registers.get_selected() {
-                pending_parts.push(format!("\"{}", reg));
-            }
+fn right_width(segments: &[(&str, Vec<Span<'static>>)]) -> usize {
+    segments.iter().map(|(_, s)| spans_width(s) + 2).sum::<usize>().saturating_sub(1)
+}
 
-            let pending = pending_parts.join("");
-
-            // Command mode, search mode input, or status message
-            let cmd_input = if matches!(vim.mode, VimModeNew::Command) {
-                Some((format!(":{}", vim.command_buffer), false))
-            } else if let VimModeNew::Search { forward } = vim.mode {
-                let prefix = if forward { "/" } else { "?" };
-                Some((format!("{}{}", prefix, vim.search_buffer), false))
-            } else if let VimModeNew::SearchLocked { forward } = vim.mode {
-                let prefix = if forward { "/" } else { "?" };
-                let match_info = if app.buffer_search.matches.is_empty() {
-                    String::new()
-                } else {
-                    format!(" [{}/{}]", app.buffer_search.current_match_index + 1, app.buffer_search.matches.len())
-                };
-                Some((format!("{}{}{}", prefix, vim.search_buffer, match_info), false))
-            } else if let Some(ref msg) = vim.status_message {
-                Some((msg.clone(), true))
-            } else {
-                None
-            };
-
-            (mode_name, pending, cmd_input, None)
-        }
-    };
+/// `text` cut to `width` characters, keeping its end (the file name of a
+/// path) behind an ellipsis.
+fn ellipsize(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    if len <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let tail: String = text.chars().skip(len - (width - 1)).collect();
+    format!("…{}", tail)
+}
 
-    let statusbar = &theme.statusbar;
-    let transparent_bg = app.config.transparent_bg;
+/// Command mode, search input, or a vim status message, in edit mode.
+fn command_input(app: &App) -> Option<(String, bool)> {
+    if app.mode != Mode::Edit {
+        return None;
+    }
+    let vim = &app.vim;
+    if matches!(vim.mode, VimModeNew::Command) {
+        Some((format!(":{}", vim.command_buffer), false))
+    } else if let VimModeNew::Search { forward } = vim.mode {
+        let prefix = if forward { "/" } else { "?" };
+        Some((format!("{}{}", prefix, vim.search_buffer), false))
+    } else if let VimModeNew::SearchLocked { forward } = vim.mode {
+        let prefix = if forward { "/" } else { "?" };
+        let match_info = if app.buffer_search.matches.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}/{}]", app.buffer_search.current_match_index + 1, app.buffer_search.matches.len())
+        };
+        Some((format!("{}{}{}", prefix, vim.search_buffer, match_info), false))
+    } else {
+        vim.status_message
+            .as_ref()
+            .map(|msg| (msg.clone(), true))
+            .or_else(|| app.diagnostic_at_cursor().map(|d| (d.message.clone(), true)))
+    }
+}
 
-    let brand = Span::styled(
+fn brand(ctx: &StatusContext) -> Vec<Span<'static>> {
+    vec![Span::styled(
         " ekphos ",
         Style::default()
-            .fg(statusbar.brand)
+            .fg(ctx.app.theme.statusbar.brand)
             .add_modifier(Modifier::BOLD),
-    );
+    )]
+}
 
-    let separator1 = Span::styled(
-        "›",
-        Style::default().fg(statusbar.separator),
-    );
+/// The name of the `[[vaults]]` entry in use.
+fn vault(ctx: &StatusContext) -> Vec<Span<'static>> {
+    let app = ctx.app;
+    let Some(vault) = app.config.active_vault() else {
+        return vec![];
+    };
+    vec![Span::styled(
+        format!(" {} ", vault.name),
+        Style::default().fg(app.theme.statusbar.brand),
+    )]
+}
 
-    let mode = Span::styled(
-        format!(" {} ", mode_text),
-        Style::default().fg(statusbar.mode),
-    );
-
-    // Pending info (operators, count, etc.)
-    let pending = if !pending_info.is_empty() {
-        vec![
-            Span::styled(
-                "›",
-                Style::default().fg(statusbar.separator),
-            ),
-            Span::styled(
-                format!(" {} ", pending_info),
-                Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
-            ),
-        ]
-    } else {
-        vec![]
+fn mode(ctx: &StatusContext) -> Vec<Span<'static>> {
+    let app = ctx.app;
+    let mode_text = match app.mode {
+        Mode::Normal => match app.focus {
+            Focus::Sidebar => "sidebar",
+            Focus::Content => "content",
+            Focus::Outline => "outline",
+        },
+        Mode::Edit => match &app.vim.mode {
+            VimModeNew::Search { .. } => "search",
+            VimModeNew::SearchLocked { .. } => "search locked",
+            VimModeNew::Command => "command",
+            VimModeNew::OperatorPending { .. } => "normal",
+            _ => {
+                if let Some(ref block_state) = app.block_insert_state {
+                    match block_state.mode {
+                        BlockInsertMode::Insert => "v-blk insert",
+                        BlockInsertMode::Append => "v-blk append",
+                    }
+                } else {
+                    match app.vim_mode {
+                        crate::app::VimMode::Normal => "normal",
+                        crate::app::VimMode::Insert => "insert",
+                        crate::app::VimMode::Replace => "replace",
+                        crate::app::VimMode::Visual => "visual",
+                        crate::app::VimMode::VisualLine => "v-line",
+                        crate::app::VimMode::VisualBlock => "v-block",
+                    }
+                }
+            }
+        },
     };
+    let mut spans = vec![Span::styled(
+        format!(" {} ", mode_text),
+        Style::default().fg(app.theme.statusbar.mode),
+    )];
+    if let Some(heading) = app.narrowed_heading.as_ref().filter(|_| app.mode == Mode::Edit) {
+        spans.push(Span::styled(
+            format!("NARROWED: {} ", heading),
+            Style::default().fg(app.theme.warning),
+        ));
+    }
+    spans
+}
 
-    let separator2 = Span::styled(
-        "›",
-        Style::default().fg(statusbar.separator),
-    );
-
-    // Command input or file path (with optional status message for Normal mode)
-    let (path_or_command, status_span) = if let Some((cmd, is_warning)) = command_input {
-        let color = if is_warning { theme.warning } else { theme.primary };
-        (Span::styled(
-            format!(" {}", cmd),
-            Style::default().fg(color).add_modifier(Modifier::BOLD),
-        ), None)
+/// Leader sequence, count, operator and other partially typed keys.
+fn pending(ctx: &StatusContext) -> Vec<Span<'static>> {
+    let app = ctx.app;
+    let mut parts = Vec::new();
+    if let Some(keys) = app.leader.keys() {
+        parts.push(format!("<leader>{}", keys));
+    }
+    if app.mode == Mode::Edit {
+        let keys = app.vim.pending_keys();
+        if !keys.is_empty() {
+            parts.push(keys);
+        }
+    }
+    if parts.is_empty() {
+        return vec![];
+    }
+    vec![Span::styled(
+        format!(" {} ", parts.join(" ")),
+        Style::default().fg(app.theme.warning).add_modifier(Modifier::BOLD),
+    )]
+}
+
+/// The note's path (its title in zen mode), or the command line while one is
+/// being typed.
+fn note_title(ctx: &StatusContext) -> Vec<Span<'static>> {
+    let app = ctx.app;
+    let theme = &app.theme;
+    let (text, style) = if let Some((cmd, is_warning)) = &ctx.command_input {
+        let color = if *is_warning { theme.warning } else { theme.primary };
+        (cmd.clone(), Style::default().fg(color).add_modifier(Modifier::BOLD))
     } else {
-        let path = Span::styled(
-            format!(" {}", note_path),
-            Style::default().fg(statusbar.foreground),
-        );
-        let status = normal_status.map(|msg| {
-            vec![
-                Span::styled(" › ", Style::default().fg(statusbar.separator)),
-                Span::styled(msg, Style::default().fg(theme.warning).add_modifier(Modifier::BOLD)),
-            ]
-        });
-        (path, status)
+        let title = if app.zen_mode {
+            // In zen mode, just show the note title
+            ctx.viewed_note.map(|n| n.title.clone())
+        } else {
+            ctx.viewed_note
+                .and_then(|n| n.file_path.as_deref())
+                .or(app.folder_view_path())
+                .map(|p| {
+                    let path_str = p.to_string_lossy().to_string();
+                    if let Some(home) = dirs::home_dir() {
+                        let home_str = home.to_string_lossy().to_string();
+                        if path_str.starts_with(&home_str) {
+                            return path_str.replacen(&home_str, "~", 1);
+                        }
+                    }
+                    path_str
+                })
+        };
+        (title.unwrap_or_else(|| "—".to_string()), Style::default().fg(theme.statusbar.foreground))
     };
+    let text = match ctx.title_width {
+        Some(width) => ellipsize(&text, width),
+        None => text,
+    };
+    vec![Span::styled(format!(" {}", text), style)]
+}
 
-    // Right side content
-    // Recording indicator
-    let recording_indicator = if app.mode == Mode::Edit && app.vim.macros.is_recording() {
-        vec![
-            Span::styled(
-                "● REC  ",
-                Style::default().fg(theme.error).add_modifier(Modifier::BOLD),
-            ),
-        ]
-    } else {
-        vec![]
+fn dirty(ctx: &StatusContext) -> Vec<Span<'static>> {
+    let app = ctx.app;
+    // A background save isn't done until it's written
+    let unsaved = app.has_dirty_buffer() || app.is_saving_current_note();
+    if ctx.command_input.is_some() || ctx.viewed_note.is_none() || !unsaved {
+        return vec![];
+    }
+    vec![Span::styled(" [+]", Style::default().fg(app.theme.statusbar.foreground))]
+}
+
+/// The view mode status message; edit mode shows its messages on the command
+/// line instead.
+fn message(ctx: &StatusContext) -> Vec<Span<'static>> {
+    let app = ctx.app;
+    if app.mode != Mode::Normal {
+        return vec![];
+    }
+    // With nothing else to say, point out how to bring back a panel hidden by
+    // a narrow terminal
+    let status = app.notes_scan_status().or_else(|| app.status_message.clone()).or_else(|| {
+        app.narrow
+            .sidebar_hidden()
+            .then(|| "Narrow window: Ctrl+B shows the sidebar".to_string())
+    });
+    let Some(msg) = status else {
+        return vec![];
     };
+    vec![
+        Span::styled(" › ", Style::default().fg(app.theme.statusbar.separator)),
+        Span::styled(msg, Style::default().fg(app.theme.warning).add_modifier(Modifier::BOLD)),
+    ]
+}
 
-    let indexing_indicator = if app.indexing_in_progress {
-        use std::sync::atomic::Ordering;
-        let current = app.index_progress.load(Ordering::Relaxed);
-        let total = app.index_total.load(Ordering::Relaxed);
-        let progress_text = if total > 0 {
-            format!("indexing ({}/{})  ", current, total)
-        } else {
-            "indexing  ".to_string()
-        };
-        vec![
-            Span::styled(
-                progress_text,
-                Style::default().fg(theme.muted),
-            ),
-        ]
-    } else {
-        vec![]
+fn recording(ctx: &StatusContext) -> Vec<Span<'static>> {
+    let app = ctx.app;
+    let Some(reg) = app.vim.macros.recording_register().filter(|_| app.mode == Mode::Edit) else {
+        return vec![];
     };
+    vec![Span::styled(
+        format!("● REC @{}", reg),
+        Style::default().fg(app.theme.error).add_modifier(Modifier::BOLD),
+    )]
+}
+
+/// Progress of the background search index build.
+fn indexing(ctx: &StatusContext) -> Vec<Span<'static>> {
+    use std::sync::atomic::Ordering;
 
-    let zen_indicator = if app.zen_mode {
-        vec![
-            Span::styled(
-                "zen  ",
-                Style::default().fg(theme.info).add_modifier(Modifier::BOLD),
-            ),
-        ]
+    let app = ctx.app;
+    if !app.indexing_in_progress {
+        return vec![];
+    }
+    let current = app.index_progress.load(Ordering::Relaxed);
+    let total = app.index_total.load(Ordering::Relaxed);
+    let progress_text = if total > 0 {
+        format!("indexing ({}/{})", current, total)
     } else {
-        vec![]
+        "indexing".to_string()
     };
+    vec![Span::styled(progress_text, Style::default().fg(app.theme.muted))]
+}
 
-    let stats = Span::styled(
-        format!("{} words", word_count),
-        Style::default().fg(statusbar.mode),
-    );
-
-    let position = Span::styled(
-        format!("  {}%", percentage),
-        Style::default().fg(statusbar.mode),
-    );
-
-    let help = Span::styled(
-        "  ? help ",
-        Style::default().fg(statusbar.mode),
-    );
-
-    // Build layout
-    let mut left_content = vec![brand, separator1, mode];
-    left_content.extend(pending);
-    left_content.push(separator2);
-    left_content.push(path_or_command);
-    if let Some(status_spans) = status_span {
-        left_content.extend(status_spans);
+fn zen(ctx: &StatusContext) -> Vec<Span<'static>> {
+    let app = ctx.app;
+    if !app.zen_mode {
+        return vec![];
     }
+    vec![Span::styled(
+        "zen",
+        Style::default().fg(app.theme.info).add_modifier(Modifier::BOLD),
+    )]
+}
 
-    let mut right_content = recording_indicator;
-    right_content.extend(indexing_indicator);
-    right_content.extend(zen_indicator);
-    right_content.extend(vec![stats, position, help]);
-
-    let content_width = if app.zen_mode {
-        (area.width as usize).min(ZEN_MAX_WIDTH as usize)
+/// Which match of the buffer search the cursor is on in edit mode, or how
+/// many notes the sidebar search matched.
+fn search_count(ctx: &StatusContext) -> Vec<Span<'static>> {
+    let app = ctx.app;
+    let text = if app.mode == Mode::Edit {
+        let search = &app.buffer_search;
+        if search.matches.is_empty() {
+            return vec![];
+        }
+        format!("match {}/{}", search.current_match_index + 1, search.matches.len())
+    } else if app.search_active && !app.search_query.is_empty() {
+        let count = app.search_matched_notes.len();
+        format!("{} {}", count, if count == 1 { "note" } else { "notes" })
     } else {
-        area.width as usize
+        return vec![];
     };
+    vec![Span::styled(text, Style::default().fg(app.theme.statusbar.mode))]
+}
 
-    let left_width: usize = left_content.iter().map(|s| s.content.chars().count()).sum();
-    let right_width: usize = right_content.iter().map(|s| s.content.chars().count()).sum();
-    let middle_padding = content_width.saturating_sub(left_width + right_width);
-    let mut spans = Vec::new();
+/// Words in the viewed note, counting only words, not markdown syntax.
+fn word_count(ctx: &StatusContext) -> Vec<Span<'static>> {
+    let count = ctx.viewed_note.map_or(0, |note| {
+        note.content
+            .split_whitespace()
+            .filter(|word| {
+                word.chars().any(|c| c.is_alphanumeric())
+            })
+            .count()
+    });
+    vec![Span::styled(
+        format!("{} words", count),
+        Style::default().fg(ctx.app.theme.statusbar.mode),
+    )]
+}
 
-    let bg_style = if transparent_bg {
-        Style::default()
+/// Cursor line and how far through the note the view is.
+fn cursor_pos(ctx: &StatusContext) -> Vec<Span<'static>> {
+    let app = ctx.app;
+    let (cursor_line, cursor_col, scroll) = if app.mode == Mode::Edit {
+        let (row, col) = app.editor.cursor();
+        let label = scroll_label(app.editor.scroll_offset(), app.editor_view_height, app.editor.line_count());
+        (app.editor.narrowed_offset().unwrap_or(0) + row + 1, col + 1, label)
     } else {
-        Style::default().bg(statusbar.background)
+        let line = app.content_item_source_lines.get(app.content_cursor).map_or(1, |line| line + 1);
+        let rects = &app.content_item_rects;
+        let label = match (rects.first(), rects.last()) {
+            (Some(&(first, _)), Some(&(last, _))) => scroll_label(first, last + 1 - first, app.content_items.len()),
+            _ => scroll_label(0, 0, app.content_items.len()),
+        };
+        (line, 1, label)
     };
+    vec![Span::styled(
+        format!("L{}:{}  {}", cursor_line, cursor_col, scroll),
+        Style::default().fg(app.theme.statusbar.mode),
+    )]
+}
 
-    if app.zen_mode {
-        let left_margin = (area.width as usize).saturating_sub(content_width) / 2;
-        if left_margin > 0 {
-            spans.push(Span::styled(" ".repeat(left_margin), bg_style));
-        }
+fn clock(ctx: &StatusContext) -> Vec<Span<'static>> {
+    vec![Span::styled(
+        chrono::Local::now().format("%H:%M").to_string(),
+        Style::default().fg(ctx.app.theme.statusbar.mode),
+    )]
+}
+
+fn help(ctx: &StatusContext) -> Vec<Span<'static>> {
+    vec![Span::styled("? help", Style::default().fg(ctx.app.theme.statusbar.mode))]
+}
+
+/// Vim-style position of a view showing `visible` of `total` lines from line
+/// `top`: `All` when everything fits, `Top`/`Bot` at either end, otherwise the
+/// share of the lines that are above the view.
+fn scroll_label(top: usize, visible: usize, total: usize) -> String {
+    let above = top.min(total);
+    let below = total.saturating_sub(top + visible);
+    match (above, below) {
+        (0, 0) => "All".to_string(),
+        (0, _) => "Top".to_string(),
+        (_, 0) => "Bot".to_string(),
+        _ => format!("{}%", above * 100 / (above + below)),
     }
+}
 
-    spans.extend(left_content);
-    spans.push(Span::styled(" ".repeat(middle_padding), bg_style));
-    spans.extend(right_content);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scroll_label_at_the_extremes() {
+        // Empty note, and one shorter than the view
+        assert_eq!(scroll_label(0, 0, 0), "All");
+        assert_eq!(scroll_label(0, 30, 12), "All");
+        assert_eq!(scroll_label(0, 30, 100), "Top");
+        assert_eq!(scroll_label(70, 30, 100), "Bot");
+        // A view scrolled past the end still counts as the bottom
+        assert_eq!(scroll_label(90, 30, 100), "Bot");
+    }
 
-    let current_width = spans.iter().map(|s| s.content.chars().count()).sum::<usize>();
-    let right_margin = (area.width as usize).saturating_sub(current_width);
-    if right_margin > 0 {
-        spans.push(Span::styled(" ".repeat(right_margin), bg_style));
+    #[test]
+    fn test_scroll_label_percentage() {
+        assert_eq!(scroll_label(35, 30, 100), "50%");
+        assert_eq!(scroll_label(1, 30, 100), "1%");
+        assert_eq!(scroll_label(69, 30, 100), "98%");
     }
 
-    let status_line = Line::from(spans);
-    let status_bar = Paragraph::new(status_line)
-        .style(bg_style);
+    #[test]
+    fn test_every_segment_name_has_a_segment() {
+        for name in crate::config::STATUS_SEGMENTS {
+            assert!(segment(name).is_some(), "{}", name);
+        }
+        assert!(segment("title").is_none());
+    }
 
-    f.render_widget(status_bar, area);
+    #[test]
+    fn test_ellipsize_keeps_the_end() {
+        assert_eq!(ellipsize("~/notes/plan.md", 20), "~/notes/plan.md");
+        assert_eq!(ellipsize("~/notes/plan.md", 8), "…plan.md");
+        assert_eq!(ellipsize("abc", 1), "…");
+        assert_eq!(ellipsize("abc", 0), "");
+    }
+
+    #[test]
+    fn test_layout_widths_match_joined_spans() {
+        let left = vec![
+            ("brand", vec![Span::raw(" ekphos ")]),
+            ("note_title", vec![Span::raw(" a.md")]),
+            ("dirty", vec![Span::raw(" [+]")]),
+        ];
+        let right = vec![("word_count", vec![Span::raw("3 words")]), ("help", vec![Span::raw("? help")])];
+        assert_eq!(left_width(&left), spans_width(&join_left(left.clone(), Style::default())));
+        assert_eq!(right_width(&right), spans_width(&join_right(right.clone())));
+        assert_eq!(right_width(&[]), 0);
+    }
 }
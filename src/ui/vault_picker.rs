@@ -0,0 +1,114 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::App;
+
+const MAX_VISIBLE: usize = 12;
+const MIN_WIDTH: u16 = 40;
+const MAX_WIDTH: u16 = 80;
+
+/// Centered list of the `[[vaults]]` entries, the one in use marked with `●`.
+pub fn render_vault_picker(f: &mut Frame, app: &mut App) {
+    let len = app.config.vaults.len();
+    if len == 0 {
+        return;
+    }
+
+    let selected = app.vault_picker.selected.min(len - 1);
+    let visible = len.min(MAX_VISIBLE);
+
+    let mut scroll = app.vault_picker.scroll_offset;
+    if selected < scroll {
+        scroll = selected;
+    } else if selected >= scroll + visible {
+        scroll = selected + 1 - visible;
+    }
+    app.vault_picker.scroll_offset = scroll;
+
+    let theme = &app.theme;
+    let area = f.area();
+    let vaults = &app.config.vaults;
+    // Nothing is open yet at startup
+    let active = if app.vault_picker.at_startup { None } else { app.config.active_vault() };
+
+    let name_width = vaults.iter().map(|v| v.name.chars().count()).max().unwrap_or(0);
+    let longest = vaults.iter().map(|v| v.path.chars().count()).max().unwrap_or(0) + name_width;
+    // marker(2) + active(2) + name + gap(2) + path + inner padding(2)
+    let popup_width = (longest as u16 + 8)
+        .clamp(MIN_WIDTH, MAX_WIDTH)
+        .min(area.width.saturating_sub(4));
+    let popup_height = (visible as u16 + 4).min(area.height.saturating_sub(4));
+
+    let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+    let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(Line::from(Span::styled(
+            " Vaults ",
+            Style::default()
+                .fg(theme.dialog.title)
+                .add_modifier(Modifier::BOLD),
+        )))
+        .title_bottom(
+            Line::from(Span::styled(" enter open · esc close ", Style::default().fg(theme.muted))).right_aligned(),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.dialog.border))
+        .style(Style::default().bg(theme.dialog.background));
+
+    f.render_widget(block, popup_area);
+
+    let path_width = (popup_width as usize).saturating_sub(8 + name_width);
+    let mut lines: Vec<Line> = Vec::with_capacity(visible + 2);
+    lines.push(Line::from(""));
+
+    for (row, vault) in vaults.iter().enumerate().skip(scroll).take(visible) {
+        let is_sel = row == selected;
+        let marker = if is_sel { "▶ " } else { "  " };
+        let in_use = if Some(vault) == active { "● " } else { "  " };
+        let path: String = if vault.path.chars().count() > path_width {
+            let mut truncated: String = vault.path.chars().take(path_width.saturating_sub(1)).collect();
+            truncated.push('…');
+            truncated
+        } else {
+            vault.path.clone()
+        };
+        let line_style = if is_sel {
+            Style::default().bg(theme.selection)
+        } else {
+            Style::default()
+        };
+
+        lines.push(
+            Line::from(vec![
+                Span::styled(marker, Style::default().fg(theme.dialog.title)),
+                Span::styled(in_use, Style::default().fg(theme.info)),
+                Span::styled(
+                    format!("{:width$}", vault.name, width = name_width),
+                    Style::default().fg(theme.dialog.text).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("  "),
+                Span::styled(path, Style::default().fg(theme.muted)),
+            ])
+            .style(line_style),
+        );
+    }
+
+    lines.push(Line::from(""));
+
+    let inner = Rect::new(
+        popup_area.x + 1,
+        popup_area.y + 1,
+        popup_area.width.saturating_sub(2),
+        popup_area.height.saturating_sub(2),
+    );
+    f.render_widget(Paragraph::new(lines), inner);
+}
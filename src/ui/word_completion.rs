@@ -0,0 +1,81 @@
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::completion_popup::popup_area_at_cursor;
+use crate::app::{App, WordCompletionState};
+
+const POPUP_MAX_WIDTH: u16 = 40;
+const POPUP_MAX_VISIBLE_ITEMS: usize = 8;
+
+pub fn render_word_completion(f: &mut Frame, app: &App) {
+    if let WordCompletionState::Open {
+        candidates,
+        selected,
+        ..
+    } = &app.word_completion
+    {
+        let theme = &app.theme;
+        let area = f.area();
+
+        let visible_count = candidates.len().min(POPUP_MAX_VISIBLE_ITEMS);
+        let scroll_offset = match selected {
+            Some(i) if *i >= visible_count => i + 1 - visible_count,
+            _ => 0,
+        };
+
+        let longest = candidates
+            .iter()
+            .map(|c| c.chars().count())
+            .max()
+            .unwrap_or(0) as u16;
+        let popup_width = (longest + 4)
+            .clamp(12, POPUP_MAX_WIDTH)
+            .min(area.width.saturating_sub(2));
+        let popup_height = visible_count as u16 + 2;
+        let popup_area = popup_area_at_cursor(app, area, popup_width, popup_height);
+
+        f.render_widget(Clear, popup_area);
+
+        let content_width = (popup_width as usize).saturating_sub(2);
+        let lines: Vec<Line> = candidates
+            .iter()
+            .enumerate()
+            .skip(scroll_offset)
+            .take(visible_count)
+            .map(|(idx, candidate)| {
+                let text: String = format!(" {}", candidate).chars().take(content_width).collect();
+                if *selected == Some(idx) {
+                    let padding = " ".repeat(content_width.saturating_sub(text.chars().count()));
+                    Line::from(Span::styled(
+                        format!("{}{}", text, padding),
+                        Style::default()
+                            .fg(theme.background)
+                            .bg(theme.primary)
+                            .add_modifier(Modifier::BOLD),
+                    ))
+                } else {
+                    Line::from(Span::styled(text, Style::default().fg(theme.foreground)))
+                }
+            })
+            .collect();
+
+        let hint = match selected {
+            Some(i) => format!(" {}/{} ", i + 1, candidates.len()),
+            None => format!(" -/{} ", candidates.len()),
+        };
+
+        let popup = Paragraph::new(lines).block(
+            Block::default()
+                .title_bottom(Line::from(hint).right_aligned())
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.info))
+                .style(Style::default().bg(theme.background_secondary)),
+        );
+
+        f.render_widget(popup, popup_area);
+    }
+}
@@ -0,0 +1,105 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::App;
+use crate::editor::yank_preview;
+
+const MAX_VISIBLE: usize = 12;
+const MIN_WIDTH: u16 = 40;
+const MAX_WIDTH: u16 = 80;
+
+/// Centered `:yanks` list: one row per yank ring entry, newest first, with
+/// whether it was whole lines and as much of its text as fits.
+pub fn render_yank_list(f: &mut Frame, app: &mut App) {
+    let len = app.editor.yank_ring().len();
+    if len == 0 {
+        return;
+    }
+
+    let selected = app.yank_list.selected.min(len - 1);
+    let visible = len.min(MAX_VISIBLE);
+
+    let mut scroll = app.yank_list.scroll_offset;
+    if selected < scroll {
+        scroll = selected;
+    } else if selected >= scroll + visible {
+        scroll = selected + 1 - visible;
+    }
+    app.yank_list.scroll_offset = scroll;
+
+    let theme = &app.theme;
+    let area = f.area();
+    let ring = app.editor.yank_ring();
+
+    let longest = ring.iter().map(|y| y.text.chars().count()).max().unwrap_or(0);
+    // marker(2) + kind(5) + gap(2) + text + inner padding(2)
+    let popup_width = (longest.min(MAX_WIDTH as usize) as u16 + 11)
+        .clamp(MIN_WIDTH, MAX_WIDTH)
+        .min(area.width.saturating_sub(4));
+    let popup_height = (visible as u16 + 4).min(area.height.saturating_sub(4));
+
+    let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+    let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(Line::from(Span::styled(
+            " Yanks ",
+            Style::default()
+                .fg(theme.dialog.title)
+                .add_modifier(Modifier::BOLD),
+        )))
+        .title_bottom(
+            Line::from(Span::styled(" enter paste · esc close ", Style::default().fg(theme.muted)))
+                .right_aligned(),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.dialog.border))
+        .style(Style::default().bg(theme.dialog.background));
+
+    f.render_widget(block, popup_area);
+
+    let text_width = (popup_width as usize).saturating_sub(11);
+    let mut lines: Vec<Line> = Vec::with_capacity(visible + 2);
+    lines.push(Line::from(""));
+
+    for (row, yank) in ring.iter().enumerate().skip(scroll).take(visible) {
+        let is_sel = row == selected;
+        let marker = if is_sel { "▶ " } else { "  " };
+        let line_style = if is_sel {
+            Style::default().bg(theme.selection)
+        } else {
+            Style::default()
+        };
+
+        lines.push(
+            Line::from(vec![
+                Span::styled(marker, Style::default().fg(theme.dialog.title)),
+                Span::styled(
+                    if yank.linewise { "lines" } else { "chars" },
+                    Style::default().fg(theme.info),
+                ),
+                Span::raw("  "),
+                Span::styled(yank_preview(&yank.text, text_width), Style::default().fg(theme.dialog.text)),
+            ])
+            .style(line_style),
+        );
+    }
+
+    lines.push(Line::from(""));
+
+    let inner = Rect::new(
+        popup_area.x + 1,
+        popup_area.y + 1,
+        popup_area.width.saturating_sub(2),
+        popup_area.height.saturating_sub(2),
+    );
+    f.render_widget(Paragraph::new(lines), inner);
+}
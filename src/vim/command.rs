@@ -1,4 +1,8 @@
-//! Vim command mode (:w, :q, :%s)
+//! Vim command mode (:w, :q, :e, :%s, :'<,'>s, :cnext)
+
+use std::path::PathBuf;
+
+use crate::app::{GraphFormat, ListStyle};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Command {
@@ -6,12 +10,124 @@ pub enum Command {
     Quit,
     WriteQuit,
     ForceQuit,
-    GoToLine(usize),
+    /// `:macros`: list, delete and rename recorded macros
+    Macros,
+    /// `:e {file}`: edit a file, which may be outside the notes directory
+    Edit(PathBuf),
+    /// `:cnext`/`:cprev`: step through the quickfix list
+    QuickfixNext,
+    QuickfixPrev,
+    /// `:copen`/`:cclose`: show or hide the quickfix window
+    QuickfixOpen,
+    QuickfixClose,
+    /// `:checklinks`: list broken wiki links in the quickfix list
+    CheckLinks,
+    /// `:link`: pick a note and insert a wiki link to it
+    Link,
+    /// `:export html`/`:export pdf`: write the note out next to itself
+    Export(ExportFormat),
+    /// `:graph export dot|json [path]`: write the vault's link graph out
+    GraphExport(GraphFormat, Option<PathBuf>),
+    /// `:copy html`: the note, or a range of it, on the clipboard as HTML
+    CopyHtml,
+    /// `:tasks complete`/`:tasks clear`: bulk task edits on the cursor's section
+    Tasks(TaskOp),
+    /// `:encrypt`/`:decrypt`: store the note passphrase-encrypted, or plain again
+    Encrypt,
+    Decrypt,
+    /// `:stats`: word counts and structure of the note
+    Stats,
+    /// `:snippets`: list the snippets insert mode expands
+    Snippets,
+    /// `:config`: show the config files the current note's settings come from
+    Config,
+    /// `:history`: list the note's snapshots to view or restore one
+    History,
+    /// `:scaffold [template]`: create notes from a template folder, or from
+    /// an outline typed into a dialog
+    Scaffold(Option<String>),
+    /// `:rename-heading [title]`: retitle the heading on the cursor line and
+    /// the links to it; without a title the command line is filled in to edit
+    RenameHeading(Option<String>),
+    /// `:listify bullet|ordered|task|none`: make lines that kind of list; with
+    /// `!`, lines nested under others are left alone
+    Listify { style: ListStyle, nested: bool },
+    /// `:promote`/`:demote`: a heading and its subheadings one level up or down
+    Promote,
+    Demote,
+    /// `:vault [name]`: switch to another `[[vaults]]` entry, picking it from
+    /// a list without a name
+    Vault(Option<String>),
+    /// `:close-others`: collapse the sidebar folders not leading to the
+    /// selected note
+    CloseOthers,
+    /// `:refile`: move the list item on the cursor line, with what's nested
+    /// under it, under a heading of a picked note
+    Refile,
+    /// `:movesection`: move the cursor's heading and its section to a
+    /// picked note
+    MoveSection,
+    /// `:copysection`: copy the cursor's heading and its section to a
+    /// picked note
+    CopySection,
+    /// `:prose`: list the prose lint's hints for the buffer
+    Prose,
+    /// `:narrow`: edit only the section under the cursor's heading
+    Narrow,
+    /// `:widen`: back to the whole note after `:narrow`
+    Widen,
+    /// `:merge [[note]]`: merge a note into the current one and point the
+    /// links to it here; without a note it's picked from the note picker
+    Merge(Option<String>),
+    /// `:cd [path]`: open another notes folder; without a path it's typed
+    /// into a dialog
+    Cd(Option<String>),
+    /// `:path`: copy the note's absolute path
+    Path,
+    /// `:reveal`: show the note in the system file manager
+    Reveal,
+    /// `:errors`: list the notes the last scan couldn't read
+    Errors,
+    /// `:yanks`: pick a recent yank or delete to paste
+    Yanks,
+    /// `:toc`: put a table of contents at the cursor, or redo the note's one
+    Toc,
+    /// `:set reading.max_width=90`: change a setting until the next restart
+    Set(String),
+    /// `:42`, `:+5`, `:-5` or `:50%`
+    GoToLine(LineJump),
     Substitute {
         pattern: String,
         replacement: String,
         flags: SubstituteFlags,
     },
+    /// `:gsub /pat/rep/flags`: substitute in every note of the vault
+    GlobalSubstitute {
+        pattern: String,
+        replacement: String,
+        flags: SubstituteFlags,
+    },
+    /// `:bulk-rename /pat/rep/flags`: substitute in the names of the notes
+    /// marked in the sidebar, or else the ones its filter matches
+    BulkRename {
+        pattern: String,
+        replacement: String,
+        flags: SubstituteFlags,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Html,
+    Pdf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskOp {
+    /// Check off every open task
+    Complete,
+    /// Take out every checked task, along with the lines nested under it
+    Clear,
 }
 
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -19,6 +135,11 @@ pub struct SubstituteFlags {
     pub global: bool,
     pub case_insensitive: bool,
     pub confirm: bool,
+    /// `n`: only report how many matches there are
+    pub report_only: bool,
+    /// `p`: a lowercase pattern matches any case, and a lowercase replacement
+    /// takes on each match's capitalization
+    pub preserve_case: bool,
 }
 
 impl SubstituteFlags {
@@ -29,6 +150,8 @@ impl SubstituteFlags {
                 'g' => flags.global = true,
                 'i' | 'I' => flags.case_insensitive = true,
                 'c' => flags.confirm = true,
+                'n' => flags.report_only = true,
+                'p' => flags.preserve_case = true,
                 _ => {}
             }
         }
@@ -36,6 +159,93 @@ impl SubstituteFlags {
     }
 }
 
+/// Where a `:42`-style jump goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineJump {
+    /// `:42`: 1-based
+    Line(usize),
+    /// `:+5` / `:-5`: lines below or above the current one
+    Relative(isize),
+    /// `:50%`: that far through the note
+    Percent(usize),
+}
+
+impl LineJump {
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        // Digits only, as `parse` would take a sign too
+        let number = |s: &str| -> Option<usize> {
+            s.bytes().all(|b| b.is_ascii_digit()).then(|| s.parse().ok()).flatten()
+        };
+        if let Some(percent) = input.strip_suffix('%') {
+            number(percent).map(Self::Percent)
+        } else if let Some(lines) = input.strip_prefix('+') {
+            number(lines).map(|n| Self::Relative(n as isize))
+        } else if let Some(lines) = input.strip_prefix('-') {
+            number(lines).map(|n| Self::Relative(-(n as isize)))
+        } else {
+            number(input).map(Self::Line)
+        }
+    }
+
+    /// The 1-based line this lands on from line `current` of `total`, kept
+    /// inside the note.
+    pub fn target(self, current: usize, total: usize) -> usize {
+        let line = match self {
+            Self::Line(line) => line,
+            Self::Relative(delta) => current.saturating_add_signed(delta),
+            Self::Percent(percent) => (total * percent.min(100)).div_ceil(100),
+        };
+        line.clamp(1, total.max(1))
+    }
+}
+
+/// Line range typed before a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineRange {
+    /// `%`: every line
+    All,
+    /// `'<,'>`: the last visual selection
+    Visual,
+    /// `N,M` or `N`: 1-based, inclusive
+    Lines(usize, usize),
+}
+
+/// Split a leading range off `input`, returning the range and the command after it.
+/// A bare number or percentage is left alone so `:42` and `:50%` still jump
+/// to a line.
+pub fn split_range(input: &str) -> (Option<LineRange>, &str) {
+    let input = input.trim();
+
+    if let Some(rest) = input.strip_prefix("'<,'>") {
+        return (Some(LineRange::Visual), rest);
+    }
+    if let Some(rest) = input.strip_prefix('%') {
+        return (Some(LineRange::All), rest);
+    }
+
+    let (start, rest) = split_number(input);
+    let Some(start) = start.filter(|_| rest != "%") else {
+        return (None, input);
+    };
+    let (end, rest) = match rest.strip_prefix(',') {
+        Some(after_comma) => match split_number(after_comma) {
+            (Some(end), rest) => (end, rest),
+            (None, _) => return (None, input),
+        },
+        None => (start, rest),
+    };
+    if rest.is_empty() {
+        return (None, input);
+    }
+    (Some(LineRange::Lines(start, end)), rest)
+}
+
+fn split_number(input: &str) -> (Option<usize>, &str) {
+    let digits = input.len() - input.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    (input[..digits].parse().ok(), &input[digits..])
+}
+
 pub fn parse_command(input: &str) -> Option<Command> {
     let input = input.trim();
 
@@ -43,6 +253,24 @@ pub fn parse_command(input: &str) -> Option<Command> {
         return None;
     }
 
+    if let Some(rest) = input.strip_prefix("bulk-rename") {
+        return match parse_substitute(rest.trim_start())? {
+            Command::Substitute { pattern, replacement, flags } => {
+                Some(Command::BulkRename { pattern, replacement, flags })
+            }
+            _ => None,
+        };
+    }
+
+    if let Some(rest) = input.strip_prefix("gsub") {
+        return match parse_substitute(rest.trim_start())? {
+            Command::Substitute { pattern, replacement, flags } => {
+                Some(Command::GlobalSubstitute { pattern, replacement, flags })
+            }
+            _ => None,
+        };
+    }
+
     if let Some(rest) = input.strip_prefix("%s") {
         return parse_substitute(rest);
     }
@@ -57,11 +285,100 @@ pub fn parse_command(input: &str) -> Option<Command> {
         "q" => return Some(Command::Quit),
         "wq" | "x" => return Some(Command::WriteQuit),
         "q!" => return Some(Command::ForceQuit),
+        "macros" => return Some(Command::Macros),
+        "cn" | "cnext" => return Some(Command::QuickfixNext),
+        "cp" | "cprev" | "cprevious" => return Some(Command::QuickfixPrev),
+        "copen" => return Some(Command::QuickfixOpen),
+        "ccl" | "cclose" => return Some(Command::QuickfixClose),
+        "checklinks" => return Some(Command::CheckLinks),
+        "link" => return Some(Command::Link),
+        "encrypt" => return Some(Command::Encrypt),
+        "decrypt" => return Some(Command::Decrypt),
+        "stats" => return Some(Command::Stats),
+        "snippets" => return Some(Command::Snippets),
+        "config" => return Some(Command::Config),
+        "history" => return Some(Command::History),
+        "scaffold" => return Some(Command::Scaffold(None)),
+        "rename-heading" => return Some(Command::RenameHeading(None)),
+        "promote" => return Some(Command::Promote),
+        "demote" => return Some(Command::Demote),
+        "vault" => return Some(Command::Vault(None)),
+        "close-others" => return Some(Command::CloseOthers),
+        "refile" => return Some(Command::Refile),
+        "movesection" => return Some(Command::MoveSection),
+        "copysection" => return Some(Command::CopySection),
+        "prose" => return Some(Command::Prose),
+        "narrow" => return Some(Command::Narrow),
+        "widen" => return Some(Command::Widen),
+        "merge" => return Some(Command::Merge(None)),
+        "cd" => return Some(Command::Cd(None)),
+        "path" => return Some(Command::Path),
+        "reveal" => return Some(Command::Reveal),
+        "errors" => return Some(Command::Errors),
+        "yanks" => return Some(Command::Yanks),
+        "toc" => return Some(Command::Toc),
         _ => {}
     }
 
-    if let Ok(line) = input.parse::<usize>() {
-        return Some(Command::GoToLine(line));
+    if let Some((name, file)) = input.split_once(char::is_whitespace) {
+        if name == "e" || name == "edit" {
+            return Some(Command::Edit(PathBuf::from(file.trim())));
+        }
+        if name == "export" {
+            return match file.trim() {
+                "html" => Some(Command::Export(ExportFormat::Html)),
+                "pdf" => Some(Command::Export(ExportFormat::Pdf)),
+                _ => None,
+            };
+        }
+        if name == "graph" {
+            let (sub, rest) = file.trim().split_once(char::is_whitespace)?;
+            let rest = rest.trim();
+            let (format, path) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            let path = path.trim();
+            return match sub {
+                "export" => Some(Command::GraphExport(
+                    GraphFormat::parse(format)?,
+                    (!path.is_empty()).then(|| PathBuf::from(path)),
+                )),
+                _ => None,
+            };
+        }
+        if name == "copy" && file.trim() == "html" {
+            return Some(Command::CopyHtml);
+        }
+        if name == "scaffold" {
+            return Some(Command::Scaffold(Some(file.trim().to_string())));
+        }
+        if name == "vault" {
+            return Some(Command::Vault(Some(file.trim().to_string())));
+        }
+        if name == "cd" {
+            return Some(Command::Cd(Some(file.trim().to_string())));
+        }
+        if name == "merge" {
+            return Some(Command::Merge(Some(file.trim().to_string())));
+        }
+        if name == "rename-heading" {
+            return Some(Command::RenameHeading(Some(file.trim().to_string())));
+        }
+        if name == "set" {
+            return Some(Command::Set(file.trim().to_string()));
+        }
+        if name == "listify" || name == "listify!" {
+            return Some(Command::Listify { style: ListStyle::from_name(file.trim())?, nested: name == "listify" });
+        }
+        if name == "tasks" {
+            return match file.trim() {
+                "complete" => Some(Command::Tasks(TaskOp::Complete)),
+                "clear" => Some(Command::Tasks(TaskOp::Clear)),
+                _ => None,
+            };
+        }
+    }
+
+    if let Some(jump) = LineJump::parse(input) {
+        return Some(Command::GoToLine(jump));
     }
 
     None
@@ -106,13 +423,146 @@ mod tests {
         assert_eq!(parse_command("wq"), Some(Command::WriteQuit));
         assert_eq!(parse_command("x"), Some(Command::WriteQuit));
         assert_eq!(parse_command("q!"), Some(Command::ForceQuit));
+        assert_eq!(parse_command("macros"), Some(Command::Macros));
+        assert_eq!(parse_command("vault"), Some(Command::Vault(None)));
+        assert_eq!(parse_command("close-others"), Some(Command::CloseOthers));
+        assert_eq!(parse_command("history"), Some(Command::History));
+        assert_eq!(parse_command("refile"), Some(Command::Refile));
+        assert_eq!(parse_command("movesection"), Some(Command::MoveSection));
+        assert_eq!(parse_command("copysection"), Some(Command::CopySection));
+        assert_eq!(parse_command("prose"), Some(Command::Prose));
+        assert_eq!(parse_command("narrow"), Some(Command::Narrow));
+        assert_eq!(parse_command("widen"), Some(Command::Widen));
+        assert_eq!(parse_command("merge"), Some(Command::Merge(None)));
+        assert_eq!(parse_command("cd"), Some(Command::Cd(None)));
+        assert_eq!(parse_command("cd ~/notes"), Some(Command::Cd(Some("~/notes".to_string()))));
+        assert_eq!(parse_command("path"), Some(Command::Path));
+        assert_eq!(parse_command("reveal"), Some(Command::Reveal));
+        assert_eq!(parse_command("errors"), Some(Command::Errors));
+        assert_eq!(parse_command("yanks"), Some(Command::Yanks));
+        assert_eq!(parse_command("toc"), Some(Command::Toc));
+        assert_eq!(parse_command("merge [[Meeting notes]]"), Some(Command::Merge(Some("[[Meeting notes]]".to_string()))));
+        assert_eq!(parse_command("vault  my notes "), Some(Command::Vault(Some("my notes".to_string()))));
+    }
+
+    #[test]
+    fn test_parse_edit() {
+        assert_eq!(
+            parse_command("e /tmp/outside.md"),
+            Some(Command::Edit(PathBuf::from("/tmp/outside.md")))
+        );
+        assert_eq!(
+            parse_command("edit  ~/My Notes/todo.md"),
+            Some(Command::Edit(PathBuf::from("~/My Notes/todo.md")))
+        );
+        assert_eq!(parse_command("e"), None);
+    }
+
+    #[test]
+    fn test_parse_export() {
+        assert_eq!(parse_command("export pdf"), Some(Command::Export(ExportFormat::Pdf)));
+        assert_eq!(parse_command("export  html"), Some(Command::Export(ExportFormat::Html)));
+        assert_eq!(parse_command("export docx"), None);
+        assert_eq!(parse_command("export"), None);
+        assert_eq!(parse_command("copy html"), Some(Command::CopyHtml));
+        assert_eq!(parse_command("graph export dot"), Some(Command::GraphExport(GraphFormat::Dot, None)));
+        assert_eq!(
+            parse_command("graph export json ~/links graph.json"),
+            Some(Command::GraphExport(GraphFormat::Json, Some(PathBuf::from("~/links graph.json"))))
+        );
+        assert_eq!(parse_command("graph export svg"), None);
+        assert_eq!(parse_command("copy"), None);
+    }
+
+    #[test]
+    fn test_parse_link() {
+        assert_eq!(parse_command("link"), Some(Command::Link));
+        assert_eq!(parse_command("links"), None);
+    }
+
+    #[test]
+    fn test_parse_encrypt() {
+        assert_eq!(parse_command("encrypt"), Some(Command::Encrypt));
+        assert_eq!(parse_command("decrypt"), Some(Command::Decrypt));
+        assert_eq!(parse_command("stats"), Some(Command::Stats));
+        assert_eq!(parse_command("config"), Some(Command::Config));
+        assert_eq!(
+            parse_command("listify task"),
+            Some(Command::Listify { style: ListStyle::Task, nested: true })
+        );
+        assert_eq!(
+            parse_command("listify! none"),
+            Some(Command::Listify { style: ListStyle::Plain, nested: false })
+        );
+        assert_eq!(parse_command("listify numbers"), None);
+        assert_eq!(parse_command("scaffold"), Some(Command::Scaffold(None)));
+        assert_eq!(parse_command("scaffold research"), Some(Command::Scaffold(Some("research".to_string()))));
+        assert_eq!(parse_command("rename-heading"), Some(Command::RenameHeading(None)));
+        assert_eq!(parse_command("set reading.align=center"), Some(Command::Set("reading.align=center".to_string())));
+        assert_eq!(
+            parse_command("rename-heading Next steps "),
+            Some(Command::RenameHeading(Some("Next steps".to_string())))
+        );
+        assert_eq!(parse_command("demote"), Some(Command::Demote));
+        assert_eq!(parse_command("encrypt now"), None);
+    }
+
+    #[test]
+    fn test_parse_tasks() {
+        assert_eq!(parse_command("tasks complete"), Some(Command::Tasks(TaskOp::Complete)));
+        assert_eq!(parse_command("tasks clear"), Some(Command::Tasks(TaskOp::Clear)));
+        assert_eq!(parse_command("tasks"), None);
+    }
+
+    #[test]
+    fn test_parse_quickfix_commands() {
+        assert_eq!(parse_command("cn"), Some(Command::QuickfixNext));
+        assert_eq!(parse_command("cprevious"), Some(Command::QuickfixPrev));
+        assert_eq!(parse_command("copen"), Some(Command::QuickfixOpen));
+        assert_eq!(parse_command("ccl"), Some(Command::QuickfixClose));
+        assert_eq!(parse_command("checklinks"), Some(Command::CheckLinks));
+    }
+
+    #[test]
+    fn test_parse_global_substitute() {
+        let cmd = parse_command("gsub /old/new/gn");
+        assert!(matches!(
+            cmd,
+            Some(Command::GlobalSubstitute { pattern, replacement, flags })
+            if pattern == "old" && replacement == "new" && flags.global && flags.report_only
+        ));
+        assert_eq!(parse_command("gsub"), None);
+        assert_eq!(
+            parse_command("bulk-rename /draft/final/"),
+            Some(Command::BulkRename {
+                pattern: "draft".to_string(),
+                replacement: "final".to_string(),
+                flags: SubstituteFlags::default(),
+            })
+        );
     }
 
     #[test]
     fn test_parse_line_number() {
-        assert_eq!(parse_command("42"), Some(Command::GoToLine(42)));
-        assert_eq!(parse_command("1"), Some(Command::GoToLine(1)));
-        assert_eq!(parse_command("999"), Some(Command::GoToLine(999)));
+        assert_eq!(parse_command("42"), Some(Command::GoToLine(LineJump::Line(42))));
+        assert_eq!(parse_command("1"), Some(Command::GoToLine(LineJump::Line(1))));
+        assert_eq!(parse_command("999"), Some(Command::GoToLine(LineJump::Line(999))));
+        assert_eq!(parse_command("+5"), Some(Command::GoToLine(LineJump::Relative(5))));
+        assert_eq!(parse_command("-5"), Some(Command::GoToLine(LineJump::Relative(-5))));
+        assert_eq!(parse_command("50%"), Some(Command::GoToLine(LineJump::Percent(50))));
+        assert_eq!(split_range("50%"), (None, "50%"));
+        assert_eq!(parse_command("+"), None);
+        assert_eq!(parse_command("5%%"), None);
+    }
+
+    #[test]
+    fn test_line_jump_target() {
+        assert_eq!(LineJump::Line(999).target(3, 40), 40);
+        assert_eq!(LineJump::Relative(5).target(3, 40), 8);
+        assert_eq!(LineJump::Relative(-5).target(3, 40), 1);
+        assert_eq!(LineJump::Percent(50).target(1, 41), 21);
+        assert_eq!(LineJump::Percent(0).target(9, 40), 1);
+        assert_eq!(LineJump::Percent(300).target(9, 40), 40);
     }
 
     #[test]
@@ -181,11 +631,24 @@ mod tests {
         assert_eq!(parse_command("xyz"), None);
     }
 
+    #[test]
+    fn test_split_range() {
+        assert_eq!(split_range("'<,'>s/a/b/"), (Some(LineRange::Visual), "s/a/b/"));
+        assert_eq!(split_range("%s/a/b/"), (Some(LineRange::All), "s/a/b/"));
+        assert_eq!(split_range("3,7s/a/b/"), (Some(LineRange::Lines(3, 7)), "s/a/b/"));
+        assert_eq!(split_range("5s/a/b/"), (Some(LineRange::Lines(5, 5)), "s/a/b/"));
+        assert_eq!(split_range("s/a/b/"), (None, "s/a/b/"));
+        assert_eq!(split_range("42"), (None, "42"));
+        assert_eq!(split_range("3,s/a/b/"), (None, "3,s/a/b/"));
+    }
+
     #[test]
     fn test_substitute_flags_parse() {
         let flags = SubstituteFlags::parse("gic");
         assert!(flags.global);
         assert!(flags.case_insensitive);
         assert!(flags.confirm);
+        assert!(!flags.preserve_case);
+        assert!(SubstituteFlags::parse("gp").preserve_case);
     }
 }
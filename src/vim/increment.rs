@@ -0,0 +1,68 @@
+//! `Ctrl-A` and `Ctrl-X`: adding to the decimal number under or after the
+//! cursor. A `-` right before the digits makes the number negative unless it
+//! follows a letter or digit, so `item-3` is item three. A number written
+//! with leading zeros keeps its width. Dates get no special handling: each
+//! part of `2024-05-01` is a number of its own.
+
+/// Char columns of the number at or after `col` in `line`, its `-` included.
+fn number_span(chars: &[char], col: usize) -> Option<(usize, usize)> {
+    let mut start = if chars.get(col) == Some(&'-') && chars.get(col + 1).is_some_and(char::is_ascii_digit) {
+        col + 1
+    } else {
+        let mut start = (col..chars.len()).find(|&i| chars[i].is_ascii_digit())?;
+        while start > 0 && chars[start - 1].is_ascii_digit() {
+            start -= 1;
+        }
+        start
+    };
+    let end = (start..chars.len()).find(|&i| !chars[i].is_ascii_digit()).unwrap_or(chars.len());
+    if start > 0 && chars[start - 1] == '-' && (start < 2 || !chars[start - 2].is_alphanumeric()) {
+        start -= 1;
+    }
+    Some((start, end))
+}
+
+/// `line` with `delta` added to the number at or after char column `col`,
+/// and the column of the new number's last digit. `None` when there is no
+/// number or the result doesn't fit.
+pub fn increment(line: &str, col: usize, delta: i64) -> Option<(String, usize)> {
+    let chars: Vec<char> = line.chars().collect();
+    let (start, end) = number_span(&chars, col)?;
+    let number: String = chars[start..end].iter().collect();
+    let digits = number.trim_start_matches('-');
+    let value: i64 = number.parse().ok()?;
+    let new_value = value.checked_add(delta)?;
+
+    let width = if digits.len() > 1 && digits.starts_with('0') { digits.len() } else { 0 };
+    let sign = if new_value < 0 { "-" } else { "" };
+    let new_number = format!("{sign}{:0width$}", new_value.unsigned_abs());
+
+    let mut new_line: String = chars[..start].iter().collect();
+    new_line.push_str(&new_number);
+    new_line.extend(&chars[end..]);
+    Some((new_line, start + new_number.chars().count() - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_increment() {
+        assert_eq!(increment("x = 9;", 0, 1), Some(("x = 10;".to_string(), 5)));
+        assert_eq!(increment("step 007", 2, 5), Some(("step 012".to_string(), 7)));
+        assert_eq!(increment("0100", 3, -1), Some(("0099".to_string(), 3)));
+        assert_eq!(increment("at -3 deg", 0, 5), Some(("at 2 deg".to_string(), 3)));
+        assert_eq!(increment("1 - 1", 4, -2), Some(("1 - -1".to_string(), 5)));
+        // Cursor in the middle of a number, or on its sign
+        assert_eq!(increment("a 123 4", 3, 1), Some(("a 124 4".to_string(), 4)));
+        assert_eq!(increment("-5", 0, 1), Some(("-4".to_string(), 1)));
+        // A dash after a word is punctuation
+        assert_eq!(increment("item-3", 0, 1), Some(("item-4".to_string(), 5)));
+        assert_eq!(increment("2024-05-01", 5, 1), Some(("2024-06-01".to_string(), 6)));
+
+        assert_eq!(increment("no digits", 0, 1), None);
+        assert_eq!(increment("5 then", 2, 1), None);
+        assert_eq!(increment("9223372036854775807", 0, 1), None);
+    }
+}
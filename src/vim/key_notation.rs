@@ -0,0 +1,170 @@
+//! Vim-style key notation (`dd<C-r>x<Esc>`) for saving and showing macros.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+const NAMED_KEYS: &[(&str, KeyCode)] = &[
+    ("CR", KeyCode::Enter),
+    ("Esc", KeyCode::Esc),
+    ("BS", KeyCode::Backspace),
+    ("Tab", KeyCode::Tab),
+    ("S-Tab", KeyCode::BackTab),
+    ("Del", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Space", KeyCode::Char(' ')),
+    ("lt", KeyCode::Char('<')),
+];
+
+/// Format one key, or `None` for keys the notation can't express.
+pub fn format_key(key: &KeyEvent) -> Option<String> {
+    let mut prefix = String::new();
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        prefix.push_str("C-");
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        prefix.push_str("A-");
+    }
+    // Shift is already part of an uppercase char
+    let shifted = key.modifiers.contains(KeyModifiers::SHIFT);
+    if shifted && !matches!(key.code, KeyCode::Char(_) | KeyCode::BackTab) {
+        prefix.push_str("S-");
+    }
+
+    let name = match key.code {
+        KeyCode::Char(c) if prefix.is_empty() && c != '<' && c != ' ' => return Some(c.to_string()),
+        KeyCode::Char(c) if !prefix.is_empty() && c != '<' && c != ' ' => c.to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        code => NAMED_KEYS.iter().find(|(_, k)| *k == code)?.0.to_string(),
+    };
+    Some(format!("<{}{}>", prefix, name))
+}
+
+/// Format a key sequence, or `None` if any key can't be expressed.
+pub fn format_keys(keys: &[KeyEvent]) -> Option<String> {
+    keys.iter().map(format_key).collect()
+}
+
+/// Parse a key sequence written by [`format_keys`] (or by hand).
+pub fn parse_keys(s: &str) -> Result<Vec<KeyEvent>, String> {
+    let mut keys = Vec::new();
+    let mut rest = s;
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            if let Some(end) = rest.find('>') {
+                keys.push(parse_bracketed(&rest[1..end])?);
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        keys.push(char_key(c, KeyModifiers::NONE));
+        rest = &rest[c.len_utf8()..];
+    }
+    Ok(keys)
+}
+
+fn parse_bracketed(name: &str) -> Result<KeyEvent, String> {
+    if let Some(&(_, code)) = NAMED_KEYS.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+        let modifiers = if code == KeyCode::BackTab {
+            KeyModifiers::SHIFT
+        } else {
+            KeyModifiers::NONE
+        };
+        return Ok(KeyEvent::new(code, modifiers));
+    }
+
+    let mut modifiers = KeyModifiers::NONE;
+    let mut base = name;
+    while base.len() > 2 && base.as_bytes()[1] == b'-' {
+        modifiers |= match base.as_bytes()[0].to_ascii_uppercase() {
+            b'C' => KeyModifiers::CONTROL,
+            b'A' | b'M' => KeyModifiers::ALT,
+            b'S' => KeyModifiers::SHIFT,
+            _ => return Err(format!("unknown modifier in <{}>", name)),
+        };
+        base = &base[2..];
+    }
+
+    let mut chars = base.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => return Ok(char_key(c, modifiers)),
+        _ if base.len() > 1 && base.starts_with(['F', 'f']) => base[1..]
+            .parse()
+            .map(KeyCode::F)
+            .map_err(|_| format!("unknown key <{}>", name))?,
+        _ => NAMED_KEYS
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(base))
+            .map(|&(_, code)| code)
+            .ok_or_else(|| format!("unknown key <{}>", name))?,
+    };
+    Ok(KeyEvent::new(code, modifiers))
+}
+
+fn char_key(c: char, modifiers: KeyModifiers) -> KeyEvent {
+    let modifiers = if c.is_uppercase() {
+        modifiers | KeyModifiers::SHIFT
+    } else {
+        modifiers
+    };
+    KeyEvent::new(KeyCode::Char(c), modifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_format_keys() {
+        let keys = [
+            key(KeyCode::Char('d'), KeyModifiers::NONE),
+            key(KeyCode::Char('A'), KeyModifiers::SHIFT),
+            key(KeyCode::Char('r'), KeyModifiers::CONTROL),
+            key(KeyCode::Char('<'), KeyModifiers::NONE),
+            key(KeyCode::Esc, KeyModifiers::NONE),
+            key(KeyCode::F(5), KeyModifiers::NONE),
+        ];
+        assert_eq!(format_keys(&keys).unwrap(), "dA<C-r><lt><Esc><F5>");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let keys = [
+            key(KeyCode::Char('x'), KeyModifiers::NONE),
+            key(KeyCode::Char('J'), KeyModifiers::SHIFT),
+            key(KeyCode::Char(' '), KeyModifiers::NONE),
+            key(KeyCode::Enter, KeyModifiers::NONE),
+            key(KeyCode::BackTab, KeyModifiers::SHIFT),
+            key(KeyCode::Up, KeyModifiers::SHIFT),
+            key(KeyCode::Char('w'), KeyModifiers::CONTROL | KeyModifiers::ALT),
+        ];
+        let text = format_keys(&keys).unwrap();
+        assert_eq!(parse_keys(&text).unwrap(), keys);
+    }
+
+    #[test]
+    fn test_parse_hand_written() {
+        let keys = parse_keys("0<c-v>j<esc>").unwrap();
+        assert_eq!(keys[1], key(KeyCode::Char('v'), KeyModifiers::CONTROL));
+        assert_eq!(keys[3], key(KeyCode::Esc, KeyModifiers::NONE));
+        // An unclosed `<` is a literal key
+        assert_eq!(parse_keys("a<").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        assert!(parse_keys("<Nope>").is_err());
+        assert!(parse_keys("<X-a>").is_err());
+        assert_eq!(format_key(&key(KeyCode::CapsLock, KeyModifiers::NONE)), None);
+    }
+}
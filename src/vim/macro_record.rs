@@ -1,7 +1,29 @@
 //! Vim macro recording and playback (q, @)
 
 use crossterm::event::KeyEvent;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use super::key_notation::{format_keys, parse_keys};
+
+/// Version written to the macros file. Files with a newer version are left
+/// untouched rather than being overwritten with an older format.
+const MACRO_FILE_VERSION: u32 = 1;
+
+/// On-disk form of the named macros, e.g. `a = "0dw<Esc>j"`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MacroFile {
+    #[serde(default = "default_macro_file_version")]
+    version: u32,
+    #[serde(default)]
+    macros: BTreeMap<String, String>,
+}
+
+fn default_macro_file_version() -> u32 {
+    MACRO_FILE_VERSION
+}
 
 #[allow(dead_code)]
 #[derive(Debug, Clone, Default)]
@@ -10,6 +32,8 @@ pub struct MacroState {
     recording: Option<char>,
     current_keys: Vec<KeyEvent>,
     last_played: Option<char>,
+    /// Set when the macros file couldn't be understood, so saving won't clobber it.
+    save_disabled: bool,
 }
 
 impl MacroState {
@@ -57,6 +81,109 @@ impl MacroState {
     pub fn set_last_played(&mut self, register: char) {
         self.last_played = Some(register);
     }
+
+    /// Registers holding a macro, in order.
+    pub fn registers(&self) -> Vec<char> {
+        let mut registers: Vec<char> = self.macros.keys().copied().collect();
+        registers.sort_unstable();
+        registers
+    }
+
+    pub fn delete(&mut self, register: char) -> bool {
+        self.macros.remove(&register).is_some()
+    }
+
+    /// Move a macro to another register, replacing whatever was there.
+    pub fn rename(&mut self, from: char, to: char) -> bool {
+        if from == to {
+            return self.macros.contains_key(&from);
+        }
+        match self.macros.remove(&from) {
+            Some(keys) => {
+                self.macros.insert(to, keys);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Load named macros from `path`, replacing the current ones. A missing
+    /// file is not an error; macros that fail to parse are skipped and
+    /// reported in the returned warnings.
+    pub fn load(&mut self, path: &Path) -> Vec<String> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+            Err(e) => {
+                self.save_disabled = true;
+                return vec![format!("Failed to read macros: {}", e)];
+            }
+        };
+        self.load_str(&content)
+    }
+
+    fn load_str(&mut self, content: &str) -> Vec<String> {
+        let file: MacroFile = match toml::from_str(content) {
+            Ok(file) => file,
+            Err(e) => {
+                self.save_disabled = true;
+                return vec![format!("Failed to parse macros: {}", e)];
+            }
+        };
+        if file.version > MACRO_FILE_VERSION {
+            self.save_disabled = true;
+            return vec![format!(
+                "Macros file version {} is newer than supported ({})",
+                file.version, MACRO_FILE_VERSION
+            )];
+        }
+
+        let mut warnings = Vec::new();
+        self.macros.clear();
+        for (name, keys) in &file.macros {
+            let mut chars = name.chars();
+            let register = match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_lowercase() => c,
+                _ => {
+                    warnings.push(format!("Skipped macro '{}': not a register a-z", name));
+                    continue;
+                }
+            };
+            match parse_keys(keys) {
+                Ok(keys) if !keys.is_empty() => {
+                    self.macros.insert(register, keys);
+                }
+                Ok(_) => {}
+                Err(e) => warnings.push(format!("Skipped macro '{}': {}", register, e)),
+            }
+        }
+        warnings
+    }
+
+    /// Write the named macros to `path`. Macros containing keys the notation
+    /// can't express are left out.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if self.save_disabled {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_file_string())
+    }
+
+    fn to_file_string(&self) -> String {
+        let macros = self
+            .macros
+            .iter()
+            .filter_map(|(register, keys)| Some((register.to_string(), format_keys(keys)?)))
+            .collect();
+        let file = MacroFile {
+            version: MACRO_FILE_VERSION,
+            macros,
+        };
+        toml::to_string_pretty(&file).unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
@@ -324,6 +451,57 @@ mod tests {
         assert_eq!(recorded.len(), 4);
     }
 
+    // ==================== Persistence Tests ====================
+
+    #[test]
+    fn test_file_round_trip() {
+        let mut state = MacroState::new();
+        state.start_recording('a');
+        state.record_key(make_key(KeyCode::Char('0')));
+        state.record_key(make_key_with_mod(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        state.record_key(make_key(KeyCode::Esc));
+        state.stop_recording();
+
+        let content = state.to_file_string();
+        assert!(content.contains("version = 1"));
+        assert!(content.contains("a = \"0<C-r><Esc>\""));
+
+        let mut loaded = MacroState::new();
+        assert!(loaded.load_str(&content).is_empty());
+        assert_eq!(loaded.get_macro('a'), state.get_macro('a'));
+    }
+
+    #[test]
+    fn test_load_skips_bad_macros_with_warning() {
+        let mut state = MacroState::new();
+        let warnings = state.load_str("version = 1\n[macros]\na = \"dd\"\nb = \"<Bogus>\"\nAB = \"x\"\n");
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(state.registers(), vec!['a']);
+    }
+
+    #[test]
+    fn test_newer_version_is_not_loaded() {
+        let mut state = MacroState::new();
+        let warnings = state.load_str("version = 99\n[macros]\na = \"dd\"\n");
+        assert_eq!(warnings.len(), 1);
+        assert!(state.get_macro('a').is_none());
+        assert!(state.save_disabled);
+    }
+
+    #[test]
+    fn test_delete_and_rename() {
+        let mut state = MacroState::new();
+        state.start_recording('a');
+        state.record_key(make_key(KeyCode::Char('x')));
+        state.stop_recording();
+
+        assert!(state.rename('a', 'q'));
+        assert!(state.get_macro('a').is_none());
+        assert_eq!(state.registers(), vec!['q']);
+        assert!(state.delete('q'));
+        assert!(!state.delete('q'));
+    }
+
     // ==================== Start Recording Clears Buffer Tests ====================
 
     #[test]
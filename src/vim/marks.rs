@@ -1,12 +1,41 @@
 //! Vim marks (m, `, ')
 
 use crate::editor::Position;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// On-disk form of the global marks, e.g. `[marks.A] note = "..." row = 3 col = 0`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MarkFile {
+    #[serde(default)]
+    marks: BTreeMap<String, GlobalMark>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GlobalMark {
+    note: PathBuf,
+    row: usize,
+    col: usize,
+}
+
+/// Where a jump to a mark lands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarkTarget {
+    /// A position in the note being edited
+    Here(Position),
+    /// A position in another note, which has to be opened first
+    Note(PathBuf, Position),
+}
 
 #[allow(dead_code)]
 #[derive(Debug, Clone, Default)]
 pub struct MarkMap {
     marks: HashMap<char, Position>,
+    /// Notes the uppercase marks were set in. Lowercase marks stay local to
+    /// the buffer and never get an entry here.
+    mark_notes: HashMap<char, PathBuf>,
     last_jump: Option<Position>,
     last_change: Option<Position>,
     last_insert: Option<Position>,
@@ -33,10 +62,85 @@ impl MarkMap {
         }
     }
 
+    /// Set `mark` at `pos` in `note`. Uppercase marks remember the note so they
+    /// can be jumped to from anywhere; other marks behave like [`Self::set`].
+    pub fn set_in_note(&mut self, mark: char, note: &Path, pos: Position) {
+        self.set(mark, pos);
+        if mark.is_ascii_uppercase() {
+            self.mark_notes.insert(mark, note.to_path_buf());
+        }
+    }
+
+    /// Resolve `mark` for a jump made while editing `current_note`.
+    pub fn target(&self, mark: char, current_note: Option<&Path>) -> Option<MarkTarget> {
+        let pos = self.get(mark)?;
+        match self.mark_notes.get(&mark) {
+            Some(note) if current_note != Some(note.as_path()) => {
+                Some(MarkTarget::Note(note.clone(), pos))
+            }
+            _ => Some(MarkTarget::Here(pos)),
+        }
+    }
+
+    /// Load global marks from `path`. A missing or unreadable file leaves
+    /// the marks empty.
+    pub fn load_global(&mut self, path: &Path) {
+        let Ok(content) = fs::read_to_string(path) else {
+            return;
+        };
+        let Ok(file) = toml::from_str::<MarkFile>(&content) else {
+            return;
+        };
+        for (name, mark) in file.marks {
+            let mut chars = name.chars();
+            if let (Some(c), None) = (chars.next(), chars.next()) {
+                if c.is_ascii_uppercase() {
+                    self.set_in_note(c, &mark.note, Position::new(mark.row, mark.col));
+                }
+            }
+        }
+    }
+
+    /// Write the uppercase marks that belong to a note to `path`.
+    pub fn save_global(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_file_string())
+    }
+
+    fn to_file_string(&self) -> String {
+        let marks = self
+            .mark_notes
+            .iter()
+            .filter_map(|(&c, note)| {
+                let pos = self.marks.get(&c)?;
+                Some((
+                    c.to_string(),
+                    GlobalMark {
+                        note: note.clone(),
+                        row: pos.row,
+                        col: pos.col,
+                    },
+                ))
+            })
+            .collect();
+        toml::to_string_pretty(&MarkFile { marks }).unwrap_or_default()
+    }
+
     pub fn set_last_jump(&mut self, pos: Position) {
         self.last_jump = Some(pos);
     }
 
+    /// Position of `mark` for a jump made from `from`, which becomes the new
+    /// last-jump position. Jumping to `` ` `` or `'` therefore toggles between
+    /// the two most recent locations.
+    pub fn jump(&mut self, mark: char, from: Position) -> Option<Position> {
+        let target = self.get(mark)?;
+        self.last_jump = Some(from);
+        Some(target)
+    }
+
     #[allow(dead_code)]
     pub fn set_last_change(&mut self, pos: Position) {
         self.last_change = Some(pos);
@@ -50,6 +154,7 @@ impl MarkMap {
     #[allow(dead_code)]
     pub fn delete(&mut self, mark: char) {
         self.marks.remove(&mark);
+        self.mark_notes.remove(&mark);
     }
 
     #[allow(dead_code)]
@@ -220,6 +325,88 @@ mod tests {
         assert_eq!(marks.get('\''), Some(Position::new(2, 2)));
     }
 
+    #[test]
+    fn test_backtick_jump_toggles_between_positions() {
+        let mut marks = MarkMap::new();
+        marks.set_last_jump(Position::new(2, 0));
+        assert_eq!(marks.jump('`', Position::new(9, 4)), Some(Position::new(2, 0)));
+        assert_eq!(marks.jump('`', Position::new(2, 0)), Some(Position::new(9, 4)));
+        assert_eq!(MarkMap::new().jump('`', Position::new(0, 0)), None);
+    }
+
+    #[test]
+    fn test_backtick_returns_to_line_before_g() {
+        use crate::editor::{CursorMove, Editor};
+
+        let mut editor = Editor::new((0..10).map(|i| format!("line {}", i)).collect());
+        let mut marks = MarkMap::new();
+        editor.set_cursor(3, 2);
+
+        // `G` records the jump before moving
+        let (row, col) = editor.cursor();
+        marks.set_last_jump(Position::new(row, col));
+        editor.move_cursor(CursorMove::Bottom);
+        assert_eq!(editor.cursor().0, 9);
+
+        // ``
+        let (row, col) = editor.cursor();
+        let target = marks.jump('`', Position::new(row, col)).unwrap();
+        editor.set_cursor(target.row, target.col);
+        assert_eq!(editor.cursor(), (3, 2));
+    }
+
+    // ==================== Global Mark Tests ====================
+
+    #[test]
+    fn test_uppercase_mark_jumps_back_to_its_note() {
+        let mut marks = MarkMap::new();
+        let first = Path::new("/vault/first.md");
+        let second = Path::new("/vault/second.md");
+        marks.set_in_note('A', first, Position::new(7, 3));
+
+        // After switching notes, the mark points back at the original one
+        assert_eq!(
+            marks.target('A', Some(second)),
+            Some(MarkTarget::Note(first.to_path_buf(), Position::new(7, 3)))
+        );
+        assert_eq!(
+            marks.target('A', Some(first)),
+            Some(MarkTarget::Here(Position::new(7, 3)))
+        );
+    }
+
+    #[test]
+    fn test_lowercase_mark_stays_in_buffer() {
+        let mut marks = MarkMap::new();
+        marks.set_in_note('a', Path::new("/vault/first.md"), Position::new(2, 0));
+        assert_eq!(
+            marks.target('a', Some(Path::new("/vault/second.md"))),
+            Some(MarkTarget::Here(Position::new(2, 0)))
+        );
+        assert!(!marks.to_file_string().contains("first.md"));
+    }
+
+    #[test]
+    fn test_global_marks_round_trip() {
+        let mut marks = MarkMap::new();
+        marks.set_in_note('B', Path::new("/vault/note.md"), Position::new(4, 1));
+        marks.set('c', Position::new(1, 1));
+
+        let dir = std::env::temp_dir().join(format!("ekphos-marks-{}", std::process::id()));
+        let path = dir.join("marks.toml");
+        marks.save_global(&path).unwrap();
+
+        let mut loaded = MarkMap::new();
+        loaded.load_global(&path);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            loaded.target('B', None),
+            Some(MarkTarget::Note(PathBuf::from("/vault/note.md"), Position::new(4, 1)))
+        );
+        assert_eq!(loaded.get('c'), None);
+    }
+
     // ==================== Delete Mark Tests ====================
 
     #[test]
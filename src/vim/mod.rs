@@ -9,25 +9,30 @@
 //! - Character find (f, F, t, T) with repeat (;, ,)
 //! - Macros (q to record, @ to play)
 //! - Marks (m to set, ` or ' to jump)
-//! - Command mode (:w, :q, :wq, :%s/pat/rep/g)
+//! - Number increment (Ctrl-A, Ctrl-X, g Ctrl-A over a selection)
+//! - Command mode (:w, :q, :wq, :%s/pat/rep/g, :'<,'>s over a selection)
 
 pub mod command;
 pub mod find;
+pub mod increment;
+pub mod key_notation;
 pub mod macro_record;
 pub mod marks;
 pub mod mode;
 pub mod motion;
 pub mod operator;
 pub mod register;
+pub mod substitute;
 pub mod text_object;
 
 pub use find::{FindState, PendingFind};
 pub use macro_record::MacroState;
-pub use marks::MarkMap;
+pub use marks::{MarkMap, MarkTarget};
 pub use mode::VimMode;
-pub use motion::Motion;
+pub use motion::{Motion, MotionKind};
 pub use operator::Operator;
 pub use register::RegisterMap;
+pub use substitute::{ConfirmSubstitute, Region, RegionKind};
 pub use text_object::{TextObject, TextObjectScope};
 
 use crate::editor::Position;
@@ -82,6 +87,11 @@ pub enum LastChange {
     DeleteWordBackward(usize),
     /// Change word: cw with count and inserted text
     ChangeWord(usize, String),
+    /// Add to the number at or after the cursor: Ctrl-A/Ctrl-X with count
+    Increment(i64),
+    /// Add to the first number of each of `lines` lines from the cursor, the
+    /// nth one `n * step` when `progressive` (g Ctrl-A in visual mode)
+    IncrementLines { lines: usize, step: i64, progressive: bool },
 }
 
 #[allow(dead_code)]
@@ -119,6 +129,8 @@ impl Default for RecordedCommand {
 pub struct VimState {
     pub mode: VimMode,
     pub count: Option<usize>,
+    /// Operator (`d`, `c`, `y`, `>`, `<`) typed in normal mode, waiting for its motion.
+    pub pending_operator: Option<char>,
     pub registers: RegisterMap,
     pub macros: MacroState,
     pub marks: MarkMap,
@@ -127,18 +139,27 @@ pub struct VimState {
     pub last_find: Option<FindState>,
     pub pending_find: Option<PendingFind>,
     pub command_buffer: String,
+    /// Selection captured when `:` was pressed in visual mode, used by `'<,'>`.
+    pub last_visual: Option<Region>,
     pub search_buffer: String,
     pub status_message: Option<String>,
     pub last_command: Option<RecordedCommand>,
     pub recording_command: Option<RecordedCommand>,
     pub pending_g: bool,
     pub pending_z: bool,
+    /// `]` or `[` was pressed; the next key picks the bracket motion (`c` for change hunks).
+    pub pending_bracket: Option<char>,
     pub awaiting_replace: bool,
     pub pending_text_object_scope: Option<TextObjectScope>,
     pub insert_start_pos: Option<Position>,
     pub pending_mark: Option<PendingMark>,
     pub pending_macro: Option<PendingMacro>,
     pub pending_register: bool,
+    /// Insert-mode `Ctrl-R` was pressed; the next key names the register to insert.
+    pub pending_insert_register: bool,
+    /// Insert-mode `Ctrl-V` was pressed; the next key is typed as it is,
+    /// without expanding a snippet.
+    pub pending_insert_literal: bool,
     pub last_change: Option<LastChange>,
     pub insert_buffer: String,
 }
@@ -154,6 +175,7 @@ impl VimState {
         Self {
             mode: VimMode::Normal,
             count: None,
+            pending_operator: None,
             registers: RegisterMap::new(),
             macros: MacroState::new(),
             marks: MarkMap::new(),
@@ -162,18 +184,22 @@ impl VimState {
             last_find: None,
             pending_find: None,
             command_buffer: String::new(),
+            last_visual: None,
             search_buffer: String::new(),
             status_message: None,
             last_command: None,
             recording_command: None,
             pending_g: false,
             pending_z: false,
+            pending_bracket: None,
             awaiting_replace: false,
             pending_text_object_scope: None,
             insert_start_pos: None,
             pending_mark: None,
             pending_macro: None,
             pending_register: false,
+            pending_insert_register: false,
+            pending_insert_literal: false,
             last_change: None,
             insert_buffer: String::new(),
         }
@@ -183,12 +209,15 @@ impl VimState {
         self.count = None;
         self.pending_g = false;
         self.pending_z = false;
+        self.pending_bracket = None;
         self.pending_find = None;
         self.awaiting_replace = false;
         self.pending_text_object_scope = None;
         self.pending_mark = None;
         self.pending_macro = None;
         self.pending_register = false;
+        self.pending_insert_register = false;
+        self.pending_insert_literal = false;
         self.registers.clear_selection();
 
         if matches!(self.mode, VimMode::OperatorPending { .. }) {
@@ -264,6 +293,22 @@ impl VimState {
         self.recording_command = None;
     }
 
+    /// Contents of `reg` for insert-mode `Ctrl-R`. Yanks and deletes land in
+    /// the editor's own clipboard, so that is passed in as the unnamed register;
+    /// `+`/`*` read the system clipboard.
+    pub fn register_text(&self, reg: char, unnamed: Option<&str>) -> Option<String> {
+        let text = match reg {
+            '"' => unnamed
+                .map(str::to_string)
+                .or_else(|| self.registers.get('"').map(|r| r.text.clone())),
+            '+' | '*' => crate::clipboard::get_system_text(),
+            _ => self.registers.get(reg).map(|r| r.text.clone()),
+        };
+        text.filter(|t| !t.is_empty())
+    }
+
+    /// Mode line for edit mode: the recording register, the mode and any
+    /// keys still waiting to be completed, e.g. `recording @q NORMAL 2 d-`.
     #[allow(dead_code)]
     pub fn status_display(&self) -> String {
         let mut parts = Vec::new();
@@ -274,10 +319,26 @@ impl VimState {
 
         parts.push(self.mode.display_name().to_string());
 
+        let pending = self.pending_keys();
+        if !pending.is_empty() {
+            parts.push(pending);
+        }
+
+        parts.join(" ")
+    }
+
+    /// Count, operator and other partially typed keys, plus the selected register.
+    pub fn pending_keys(&self) -> String {
+        let mut parts = Vec::new();
+
         if let Some(count) = self.count {
             parts.push(format!("{}", count));
         }
 
+        if let Some(op) = self.pending_operator {
+            parts.push(format!("{}-", op));
+        }
+
         if let VimMode::OperatorPending { operator, count } = &self.mode {
             if let Some(c) = count {
                 parts.push(format!("{}", c));
@@ -287,8 +348,11 @@ impl VimState {
 
         if self.pending_g { parts.push("g-".to_string()); }
         if self.pending_z { parts.push("z-".to_string()); }
+        if let Some(bracket) = self.pending_bracket { parts.push(format!("{}-", bracket)); }
         if self.pending_find.is_some() { parts.push("f-".to_string()); }
         if self.awaiting_replace { parts.push("r-".to_string()); }
+        if self.pending_insert_register { parts.push("^R-".to_string()); }
+        if self.pending_insert_literal { parts.push("^V-".to_string()); }
 
         if let Some(pending) = &self.pending_mark {
             parts.push(match pending {
@@ -480,6 +544,51 @@ mod tests {
         assert!(state.status_display().contains("recording @a"));
     }
 
+    #[test]
+    fn test_status_display_count_and_operator() {
+        let mut state = VimState::new();
+        state.count = Some(2);
+        state.pending_operator = Some('d');
+        assert_eq!(state.status_display(), "NORMAL 2 d-");
+        assert_eq!(state.pending_keys(), "2 d-");
+    }
+
+    #[test]
+    fn test_status_display_recording_with_register() {
+        let mut state = VimState::new();
+        state.macros.start_recording('q');
+        state.registers.select('a');
+        assert_eq!(state.status_display(), "recording @q NORMAL \"a");
+        // Recording is shown on its own, not as a pending key
+        assert_eq!(state.pending_keys(), "\"a");
+    }
+
+    #[test]
+    fn test_reset_pending_clears_insert_register() {
+        let mut state = VimState::new();
+        state.pending_insert_register = true;
+        state.pending_insert_literal = true;
+        state.reset_pending();
+        assert!(!state.pending_insert_register);
+        assert!(!state.pending_insert_literal);
+    }
+
+    #[test]
+    fn test_register_text_unnamed_uses_editor_clipboard() {
+        let state = VimState::new();
+        assert_eq!(state.register_text('"', Some("yanked")).as_deref(), Some("yanked"));
+        assert_eq!(state.register_text('"', None), None);
+    }
+
+    #[test]
+    fn test_register_text_named() {
+        let mut state = VimState::new();
+        state.registers.select('a');
+        state.registers.yank("alpha".to_string(), false);
+        assert_eq!(state.register_text('a', Some("other")).as_deref(), Some("alpha"));
+        assert_eq!(state.register_text('b', Some("other")), None);
+    }
+
     #[test]
     fn test_macros_initialized() {
         let state = VimState::new();
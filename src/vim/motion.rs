@@ -1,6 +1,18 @@
 //! Vim motions
 
-use crate::editor::Position;
+use crate::editor::{CursorMove, Position};
+
+/// How much of the text between the cursor and a motion's end an operator
+/// works on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MotionKind {
+    /// Up to the end, not including it, like `w` and `b`
+    Exclusive,
+    /// Including the character at the end, like `e` and `$`
+    Inclusive,
+    /// Whole lines, like `j` and `G`
+    Linewise,
+}
 
 #[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq)]
@@ -41,7 +53,47 @@ pub enum Motion {
 }
 
 impl Motion {
-    #[allow(dead_code)]
+    /// The motion a cursor movement makes, for the ones operators take.
+    pub fn from_cursor_move(movement: CursorMove) -> Option<Self> {
+        Some(match movement {
+            CursorMove::Back => Self::Left,
+            CursorMove::Forward => Self::Right,
+            CursorMove::Up => Self::Up,
+            CursorMove::Down => Self::Down,
+            CursorMove::WordForward => Self::WordForward,
+            CursorMove::WordBack => Self::WordBackward,
+            CursorMove::WordEndForward => Self::WordEndForward,
+            CursorMove::WordEndBackward => Self::WordEndBackward,
+            CursorMove::BigWordForward => Self::BigWordForward,
+            CursorMove::BigWordBack => Self::BigWordBackward,
+            CursorMove::BigWordEndForward => Self::BigWordEndForward,
+            CursorMove::BigWordEndBackward => Self::BigWordEndBackward,
+            CursorMove::Head => Self::LineStart,
+            CursorMove::FirstNonBlank => Self::FirstNonBlank,
+            CursorMove::End => Self::LineEnd,
+            CursorMove::Top => Self::DocumentStart,
+            CursorMove::Bottom => Self::DocumentEnd,
+            CursorMove::GoToLine(line) => Self::GoToLine(line),
+            CursorMove::ParagraphForward => Self::ParagraphForward,
+            CursorMove::ParagraphBack => Self::ParagraphBackward,
+            CursorMove::ScreenTop => Self::ScreenTop,
+            CursorMove::ScreenMiddle => Self::ScreenMiddle,
+            CursorMove::ScreenBottom => Self::ScreenBottom,
+            CursorMove::MatchingBracket => Self::MatchingBracket,
+            _ => return None,
+        })
+    }
+
+    pub fn kind(&self) -> MotionKind {
+        if self.is_linewise() {
+            MotionKind::Linewise
+        } else if self.is_exclusive() {
+            MotionKind::Exclusive
+        } else {
+            MotionKind::Inclusive
+        }
+    }
+
     pub fn is_linewise(&self) -> bool {
         matches!(
             self,
@@ -58,7 +110,6 @@ impl Motion {
         )
     }
 
-    #[allow(dead_code)]
     pub fn is_exclusive(&self) -> bool {
         matches!(
             self,
@@ -68,6 +119,10 @@ impl Motion {
                 | Motion::BigWordForward
                 | Motion::WordBackward
                 | Motion::BigWordBackward
+                | Motion::LineStart
+                | Motion::FirstNonBlank
+                | Motion::SearchNext
+                | Motion::SearchPrev
         )
     }
 }
@@ -741,6 +796,18 @@ mod tests {
         assert!(!Motion::WordEndForward.is_exclusive());
         assert!(!Motion::Up.is_exclusive());
         assert!(!Motion::Down.is_exclusive());
+        assert!(Motion::LineStart.is_exclusive());
+    }
+
+    #[test]
+    fn test_motion_kind() {
+        let kind = |movement| Motion::from_cursor_move(movement).map(|m| m.kind());
+        assert_eq!(kind(CursorMove::WordForward), Some(MotionKind::Exclusive));
+        assert_eq!(kind(CursorMove::WordEndForward), Some(MotionKind::Inclusive));
+        assert_eq!(kind(CursorMove::End), Some(MotionKind::Inclusive));
+        assert_eq!(kind(CursorMove::Head), Some(MotionKind::Exclusive));
+        assert_eq!(kind(CursorMove::Bottom), Some(MotionKind::Linewise));
+        assert_eq!(kind(CursorMove::PageDown), None);
     }
 
     // ==================== is_word_char Tests ====================
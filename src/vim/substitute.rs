@@ -0,0 +1,436 @@
+//! `:s/pat/rep/` substitution over a region of the buffer.
+
+use super::command::SubstituteFlags;
+use crate::editor::Position;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionKind {
+    /// Whole lines (`:%s`, `:N,Ms`, `V` selections)
+    Lines,
+    /// From `start` to `end` inclusive (`v` selections)
+    Chars,
+    /// The same columns on every row (`Ctrl-V` selections)
+    Block,
+}
+
+/// Part of the buffer a substitution applies to. Rows and columns are
+/// inclusive, matching how visual selections are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub start: Position,
+    pub end: Position,
+    pub kind: RegionKind,
+}
+
+impl Region {
+    pub fn lines(start_row: usize, end_row: usize) -> Self {
+        Self {
+            start: Position::new(start_row.min(end_row), 0),
+            end: Position::new(start_row.max(end_row), 0),
+            kind: RegionKind::Lines,
+        }
+    }
+
+    /// The text of the region in `lines`, one line per row.
+    pub fn text(&self, lines: &[&str]) -> String {
+        let last = self.end.row.min(lines.len().saturating_sub(1));
+        (self.start.row..=last)
+            .filter_map(|row| {
+                let chars: Vec<char> = lines.get(row)?.chars().collect();
+                let (lo, hi) = self.columns(row, chars.len());
+                Some(chars[lo..hi].iter().collect::<String>())
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Char column bounds (`lo..hi`) of the region on `row`.
+    fn columns(&self, row: usize, line_len: usize) -> (usize, usize) {
+        match self.kind {
+            RegionKind::Lines => (0, line_len),
+            RegionKind::Block => {
+                let lo = self.start.col.min(self.end.col);
+                let hi = self.start.col.max(self.end.col) + 1;
+                (lo.min(line_len), hi.min(line_len))
+            }
+            RegionKind::Chars => {
+                let lo = if row == self.start.row { self.start.col } else { 0 };
+                let hi = if row == self.end.row { self.end.col + 1 } else { line_len };
+                (lo.min(line_len), hi.min(line_len))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Substitution {
+    /// Rewritten lines, as `(row, new_line)` in row order.
+    pub changes: Vec<(usize, String)>,
+    /// Total number of replacements made.
+    pub count: usize,
+}
+
+/// Replace literal `pattern` with `replacement` inside `region`. Only matches
+/// lying entirely within the region are replaced; without the `g` flag only the
+/// first one on each row is.
+pub fn substitute(
+    lines: &[&str],
+    region: &Region,
+    pattern: &str,
+    replacement: &str,
+    flags: &SubstituteFlags,
+) -> Substitution {
+    let matches = find_matches(lines, region, pattern, flags);
+    let preserve = preserves_case(flags, pattern, replacement);
+    apply_matches(lines, &matches, pattern.chars().count(), replacement, preserve)
+}
+
+/// Whether the `p` flag applies: it only does when both sides are written in
+/// lowercase, so a deliberately cased pattern or replacement is left alone.
+pub fn preserves_case(flags: &SubstituteFlags, pattern: &str, replacement: &str) -> bool {
+    let lowercase = |s: &str| !s.chars().any(char::is_uppercase);
+    flags.preserve_case && lowercase(pattern) && lowercase(replacement)
+}
+
+/// `replacement` in the capitalization of `matched`: all caps, a leading
+/// capital, or as written.
+pub fn match_case(matched: &str, replacement: &str) -> String {
+    let letters: Vec<char> = matched.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.len() > 1 && letters.iter().all(|c| c.is_uppercase()) {
+        return replacement.to_uppercase();
+    }
+    if letters.first().is_some_and(|c| c.is_uppercase()) {
+        let mut chars = replacement.chars();
+        if let Some(first) = chars.next() {
+            return first.to_uppercase().chain(chars).collect();
+        }
+    }
+    replacement.to_string()
+}
+
+/// Run the substitution over every line of `text`, returning the new text and
+/// the number of replacements. Line endings are kept as they were.
+pub fn substitute_text(
+    text: &str,
+    pattern: &str,
+    replacement: &str,
+    flags: &SubstituteFlags,
+) -> (String, usize) {
+    let mut lines: Vec<&str> = text.split('\n').collect();
+    let result = substitute(&lines, &Region::lines(0, lines.len() - 1), pattern, replacement, flags);
+    for (row, new_line) in &result.changes {
+        lines[*row] = new_line;
+    }
+    (lines.join("\n"), result.count)
+}
+
+/// Char positions `(row, col)` of the matches [`substitute`] would replace, in
+/// buffer order.
+pub fn find_matches(
+    lines: &[&str],
+    region: &Region,
+    pattern: &str,
+    flags: &SubstituteFlags,
+) -> Vec<(usize, usize)> {
+    let mut matches = Vec::new();
+    // `p` with a lowercase pattern finds every casing of it
+    let ignore_case = flags.case_insensitive || (flags.preserve_case && !pattern.chars().any(char::is_uppercase));
+    let pattern: Vec<char> = pattern.chars().collect();
+    if pattern.is_empty() || lines.is_empty() {
+        return matches;
+    }
+
+    let last_row = region.end.row.min(lines.len() - 1);
+    for (row, line) in lines.iter().enumerate().take(last_row + 1).skip(region.start.row) {
+        let chars: Vec<char> = line.chars().collect();
+        let (lo, hi) = region.columns(row, chars.len());
+
+        let mut found = false;
+        let mut col = lo;
+        while col + pattern.len() <= hi {
+            if (flags.global || !found) && matches_at(&chars, col, &pattern, ignore_case) {
+                matches.push((row, col));
+                found = true;
+                col += pattern.len();
+            } else {
+                col += 1;
+            }
+        }
+    }
+
+    matches
+}
+
+/// Replace the `pattern_len` chars at each of `matches` (from [`find_matches`])
+/// with `replacement`, cased like each match when `preserve_case` is set.
+pub fn apply_matches(
+    lines: &[&str],
+    matches: &[(usize, usize)],
+    pattern_len: usize,
+    replacement: &str,
+    preserve_case: bool,
+) -> Substitution {
+    let mut result = Substitution::default();
+    let mut rest = matches;
+    while let Some(&(row, _)) = rest.first() {
+        let on_row = rest.iter().take_while(|(r, _)| *r == row).count();
+        let (row_matches, next) = rest.split_at(on_row);
+        rest = next;
+
+        let Some(line) = lines.get(row) else {
+            continue;
+        };
+        let chars: Vec<char> = line.chars().collect();
+        let mut new_line = String::with_capacity(line.len());
+        let mut col = 0;
+        for &(_, start) in row_matches {
+            new_line.extend(&chars[col..start]);
+            if preserve_case {
+                let matched: String = chars[start..start + pattern_len].iter().collect();
+                new_line.push_str(&match_case(&matched, replacement));
+            } else {
+                new_line.push_str(replacement);
+            }
+            col = start + pattern_len;
+        }
+        new_line.extend(&chars[col..]);
+
+        result.count += row_matches.len();
+        result.changes.push((row, new_line));
+    }
+    result
+}
+
+/// A `:s///c` substitution waiting for an answer at each match.
+#[derive(Debug, Clone)]
+pub struct ConfirmSubstitute {
+    pub matches: Vec<(usize, usize)>,
+    pub pattern_len: usize,
+    pub replacement: String,
+    /// Case each replacement like its match (the `p` flag)
+    pub preserve_case: bool,
+    current: usize,
+    accepted: Vec<(usize, usize)>,
+    done: bool,
+}
+
+impl ConfirmSubstitute {
+    pub fn new(matches: Vec<(usize, usize)>, pattern_len: usize, replacement: String, preserve_case: bool) -> Self {
+        Self {
+            matches,
+            pattern_len,
+            replacement,
+            preserve_case,
+            current: 0,
+            accepted: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// The match being asked about, or `None` once finished.
+    pub fn current(&self) -> Option<(usize, usize)> {
+        if self.done {
+            None
+        } else {
+            self.matches.get(self.current).copied()
+        }
+    }
+
+    /// Matches not answered yet, starting with the current one.
+    pub fn remaining(&self) -> &[(usize, usize)] {
+        if self.done {
+            &[]
+        } else {
+            &self.matches[self.current.min(self.matches.len())..]
+        }
+    }
+
+    /// Matches to replace so far, in buffer order.
+    pub fn accepted(&self) -> &[(usize, usize)] {
+        &self.accepted
+    }
+
+    /// Apply one answer: `y` replaces, `n` skips, `a` replaces this and every
+    /// remaining match, `l` replaces this one and stops, `q` stops. Other keys
+    /// are ignored. Returns true once there is nothing left to ask.
+    pub fn answer(&mut self, key: char) -> bool {
+        let Some(current) = self.current() else {
+            return true;
+        };
+        match key {
+            'y' => {
+                self.accepted.push(current);
+                self.current += 1;
+            }
+            'n' => self.current += 1,
+            'a' => {
+                self.accepted.extend_from_slice(&self.matches[self.current..]);
+                self.done = true;
+            }
+            'l' => {
+                self.accepted.push(current);
+                self.done = true;
+            }
+            'q' => self.done = true,
+            _ => {}
+        }
+        self.current().is_none()
+    }
+}
+
+fn matches_at(chars: &[char], col: usize, pattern: &[char], ignore_case: bool) -> bool {
+    chars[col..col + pattern.len()]
+        .iter()
+        .zip(pattern)
+        .all(|(&a, &b)| a == b || (ignore_case && a.to_lowercase().eq(b.to_lowercase())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(s: &str) -> SubstituteFlags {
+        SubstituteFlags::parse(s)
+    }
+
+    #[test]
+    fn test_substitute_line_range_only() {
+        let lines = ["foo", "foo foo", "foo"];
+        let result = substitute(&lines, &Region::lines(1, 1), "foo", "bar", &flags("g"));
+        assert_eq!(result.changes, vec![(1, "bar bar".to_string())]);
+        assert_eq!(result.count, 2);
+    }
+
+    #[test]
+    fn test_region_text() {
+        let lines = ["# Plan", "first line", "second line"];
+        assert_eq!(Region::lines(1, 2).text(&lines), "first line\nsecond line");
+        let chars = Region { start: Position::new(0, 2), end: Position::new(1, 4), kind: RegionKind::Chars };
+        assert_eq!(chars.text(&lines), "Plan\nfirst");
+    }
+
+    #[test]
+    fn test_substitute_first_match_without_global() {
+        let lines = ["foo foo", "foo"];
+        let result = substitute(&lines, &Region::lines(0, 1), "foo", "x", &flags(""));
+        assert_eq!(
+            result.changes,
+            vec![(0, "x foo".to_string()), (1, "x".to_string())]
+        );
+        assert_eq!(result.count, 2);
+    }
+
+    #[test]
+    fn test_substitute_characterwise_region() {
+        let lines = ["aa aa", "aa aa"];
+        let region = Region {
+            start: Position::new(0, 3),
+            end: Position::new(1, 1),
+            kind: RegionKind::Chars,
+        };
+        let result = substitute(&lines, &region, "aa", "b", &flags("g"));
+        assert_eq!(
+            result.changes,
+            vec![(0, "aa b".to_string()), (1, "b aa".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_substitute_block_region_skips_partial_matches() {
+        let lines = ["abcabc", "abcabc"];
+        let region = Region {
+            start: Position::new(0, 2),
+            end: Position::new(1, 5),
+            kind: RegionKind::Block,
+        };
+        let result = substitute(&lines, &region, "abc", "X", &flags("g"));
+        assert_eq!(
+            result.changes,
+            vec![(0, "abcX".to_string()), (1, "abcX".to_string())]
+        );
+    }
+
+    /// Feed `answers` to a prompt over `matches`, returning the accepted matches
+    /// and whether the prompt finished.
+    fn confirm(matches: &[(usize, usize)], answers: &str) -> (Vec<(usize, usize)>, bool) {
+        let mut prompt = ConfirmSubstitute::new(matches.to_vec(), 3, "bar".to_string(), false);
+        let mut finished = false;
+        for key in answers.chars() {
+            finished = prompt.answer(key);
+        }
+        (prompt.accepted().to_vec(), finished)
+    }
+
+    #[test]
+    fn test_confirm_answers() {
+        let matches = [(0, 0), (0, 4), (1, 0), (2, 0), (2, 4)];
+        assert_eq!(confirm(&matches, "yna"), (vec![(0, 0), (1, 0), (2, 0), (2, 4)], true));
+        assert_eq!(confirm(&matches, "nyq"), (vec![(0, 4)], true));
+        assert_eq!(confirm(&matches, "nnl"), (vec![(1, 0)], true));
+        // Unknown keys are ignored; the prompt is still open after two answers
+        assert_eq!(confirm(&matches, "xyn"), (vec![(0, 0)], false));
+        assert_eq!(confirm(&matches, "yyyyy"), (matches.to_vec(), true));
+    }
+
+    #[test]
+    fn test_confirm_applies_only_accepted_matches() {
+        let lines = ["foo foo", "foo", "x foo"];
+        let matches = find_matches(&lines, &Region::lines(0, 2), "foo", &flags("gc"));
+        assert_eq!(matches, vec![(0, 0), (0, 4), (1, 0), (2, 2)]);
+
+        let (accepted, _) = confirm(&matches, "nyna");
+        let result = apply_matches(&lines, &accepted, 3, "bar", false);
+        assert_eq!(
+            result.changes,
+            vec![(0, "foo bar".to_string()), (2, "x bar".to_string())]
+        );
+        assert_eq!(result.count, 2);
+    }
+
+    #[test]
+    fn test_substitute_text_keeps_line_endings() {
+        let (text, count) = substitute_text("foo\nbar foo\n", "foo", "x", &flags("g"));
+        assert_eq!(text, "x\nbar x\n");
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_substitute_case_insensitive() {
+        let lines = ["Foo FOO"];
+        let result = substitute(&lines, &Region::lines(0, 0), "foo", "x", &flags("gi"));
+        assert_eq!(result.changes, vec![(0, "x x".to_string())]);
+
+        let result = substitute(&lines, &Region::lines(0, 0), "foo", "x", &flags("g"));
+        assert!(result.changes.is_empty());
+    }
+
+    #[test]
+    fn test_match_case_patterns() {
+        assert_eq!(match_case("color", "colour"), "colour");
+        assert_eq!(match_case("Color", "colour"), "Colour");
+        assert_eq!(match_case("COLOR", "colour"), "COLOUR");
+        // A lone capital is a leading capital, not all caps
+        assert_eq!(match_case("A", "the"), "The");
+        assert_eq!(match_case("cOLOR", "colour"), "colour");
+    }
+
+    #[test]
+    fn test_preserve_case_substitution() {
+        let lines = ["color Color COLOR", "colorful"];
+        let result = substitute(&lines, &Region::lines(0, 1), "color", "colour", &flags("gp"));
+        assert_eq!(
+            result.changes,
+            vec![(0, "colour Colour COLOUR".to_string()), (1, "colourful".to_string())]
+        );
+
+        // Opt-in: without `p` only the exact match changes, as written
+        let result = substitute(&lines, &Region::lines(0, 0), "color", "colour", &flags("g"));
+        assert_eq!(result.changes, vec![(0, "colour Color COLOR".to_string())]);
+
+        // A cased replacement is put in as written
+        assert!(!preserves_case(&flags("p"), "Color", "colour"));
+        assert!(!preserves_case(&flags("p"), "color", "Colour"));
+        let result = substitute(&lines, &Region::lines(0, 0), "color", "Hue", &flags("gp"));
+        assert_eq!(result.changes, vec![(0, "Hue Hue Hue".to_string())]);
+    }
+}
@@ -1,6 +1,6 @@
 //! Vim text objects (iw, aw, i", a(, etc.)
 
-use crate::editor::Position;
+use crate::editor::{Position, WordChars};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TextObject {
@@ -65,10 +65,11 @@ impl TextObject {
         scope: TextObjectScope,
         lines: &[&str],
         pos: Position,
+        word_chars: &WordChars,
     ) -> Option<(Position, Position)> {
         match self {
-            TextObject::Word => find_word_bounds(lines, pos, scope, false),
-            TextObject::BigWord => find_word_bounds(lines, pos, scope, true),
+            TextObject::Word => find_word_bounds(lines, pos, scope, Some(word_chars)),
+            TextObject::BigWord => find_word_bounds(lines, pos, scope, None),
             TextObject::Paragraph => find_paragraph_bounds(lines, pos, scope),
             TextObject::SingleQuote | TextObject::DoubleQuote | TextObject::BackQuote => {
                 let (open, close) = self.delimiters()?;
@@ -82,15 +83,11 @@ impl TextObject {
     }
 }
 
-fn is_word_char(c: char) -> bool {
-    c.is_alphanumeric() || c == '_'
-}
-
 fn find_word_bounds(
     lines: &[&str],
     pos: Position,
     scope: TextObjectScope,
-    big_word: bool,
+    word_chars: Option<&WordChars>,
 ) -> Option<(Position, Position)> {
     let line = lines.get(pos.row)?;
     let chars: Vec<char> = line.chars().collect();
@@ -102,16 +99,14 @@ fn find_word_bounds(
     let col = pos.col.min(chars.len().saturating_sub(1));
     let current_char = chars.get(col)?;
 
-    let is_word = if big_word {
-        !current_char.is_whitespace()
-    } else {
-        is_word_char(*current_char)
-    };
+    // `None` is a WORD: anything but whitespace
+    let is_word_char = |c: char| word_chars.map_or(!c.is_whitespace(), |words| words.is_word_char(c));
+    let is_word = is_word_char(*current_char);
 
     let mut start = col;
     while start > 0 {
         let c = chars[start - 1];
-        let c_is_word = if big_word { !c.is_whitespace() } else { is_word_char(c) };
+        let c_is_word = is_word_char(c);
         if c_is_word != is_word {
             break;
         }
@@ -121,7 +116,7 @@ fn find_word_bounds(
     let mut end = col;
     while end < chars.len() {
         let c = chars[end];
-        let c_is_word = if big_word { !c.is_whitespace() } else { is_word_char(c) };
+        let c_is_word = is_word_char(c);
         if c_is_word != is_word {
             break;
         }
@@ -449,70 +444,81 @@ mod tests {
     #[test]
     fn test_find_word_bounds_inner() {
         let lines = vec!["hello world"];
-        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 2));
+        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 2), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 0), Position::new(0, 5))));
     }
 
+    #[test]
+    fn test_find_word_bounds_with_word_chars() {
+        let lines = vec!["foo-bar_baz.qux"];
+        let inner = |extra: &str| {
+            TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 5), &WordChars::new(extra))
+        };
+        assert_eq!(inner("_"), Some((Position::new(0, 4), Position::new(0, 11))));
+        assert_eq!(inner("_-"), Some((Position::new(0, 0), Position::new(0, 11))));
+        assert_eq!(inner(""), Some((Position::new(0, 4), Position::new(0, 7))));
+    }
+
     #[test]
     fn test_find_word_bounds_around() {
         let lines = vec!["hello world"];
-        let bounds = TextObject::Word.find_bounds(TextObjectScope::Around, &lines, Position::new(0, 2));
+        let bounds = TextObject::Word.find_bounds(TextObjectScope::Around, &lines, Position::new(0, 2), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 0), Position::new(0, 6))));
     }
 
     #[test]
     fn test_find_word_bounds_at_start() {
         let lines = vec!["hello world"];
-        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0));
+        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 0), Position::new(0, 5))));
     }
 
     #[test]
     fn test_find_word_bounds_at_end() {
         let lines = vec!["hello world"];
-        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 10));
+        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 10), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 6), Position::new(0, 11))));
     }
 
     #[test]
     fn test_find_word_bounds_single_word() {
         let lines = vec!["hello"];
-        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 2));
+        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 2), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 0), Position::new(0, 5))));
     }
 
     #[test]
     fn test_find_word_bounds_with_underscore() {
         let lines = vec!["hello_world test"];
-        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 5));
+        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 5), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 0), Position::new(0, 11))));
     }
 
     #[test]
     fn test_find_word_bounds_punctuation() {
         let lines = vec!["foo.bar"];
-        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 3));
+        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 3), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 3), Position::new(0, 4))));
     }
 
     #[test]
     fn test_find_word_bounds_empty_line() {
         let lines = vec![""];
-        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0));
+        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 0), Position::new(0, 0))));
     }
 
     #[test]
     fn test_find_word_bounds_around_trailing_space() {
         let lines = vec!["hello world"];
-        let bounds = TextObject::Word.find_bounds(TextObjectScope::Around, &lines, Position::new(0, 8));
+        let bounds = TextObject::Word.find_bounds(TextObjectScope::Around, &lines, Position::new(0, 8), &WordChars::default());
         assert!(bounds.is_some());
     }
 
     #[test]
     fn test_find_word_bounds_around_leading_space() {
         let lines = vec!["hello world"];
-        let bounds = TextObject::Word.find_bounds(TextObjectScope::Around, &lines, Position::new(0, 10));
+        let bounds = TextObject::Word.find_bounds(TextObjectScope::Around, &lines, Position::new(0, 10), &WordChars::default());
         assert!(bounds.is_some());
     }
 
@@ -521,21 +527,21 @@ mod tests {
     #[test]
     fn test_find_big_word_bounds_inner() {
         let lines = vec!["foo.bar baz"];
-        let bounds = TextObject::BigWord.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 2));
+        let bounds = TextObject::BigWord.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 2), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 0), Position::new(0, 7))));
     }
 
     #[test]
     fn test_find_big_word_bounds_around() {
         let lines = vec!["foo.bar baz"];
-        let bounds = TextObject::BigWord.find_bounds(TextObjectScope::Around, &lines, Position::new(0, 2));
+        let bounds = TextObject::BigWord.find_bounds(TextObjectScope::Around, &lines, Position::new(0, 2), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 0), Position::new(0, 8))));
     }
 
     #[test]
     fn test_find_big_word_bounds_complex() {
         let lines = vec!["http://example.com next"];
-        let bounds = TextObject::BigWord.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 5));
+        let bounds = TextObject::BigWord.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 5), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 0), Position::new(0, 18))));
     }
 
@@ -544,56 +550,56 @@ mod tests {
     #[test]
     fn test_find_quote_bounds_inner() {
         let lines = vec!["say \"hello\" there"];
-        let bounds = TextObject::DoubleQuote.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 6));
+        let bounds = TextObject::DoubleQuote.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 6), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 5), Position::new(0, 10))));
     }
 
     #[test]
     fn test_find_quote_bounds_around() {
         let lines = vec!["say \"hello\" there"];
-        let bounds = TextObject::DoubleQuote.find_bounds(TextObjectScope::Around, &lines, Position::new(0, 6));
+        let bounds = TextObject::DoubleQuote.find_bounds(TextObjectScope::Around, &lines, Position::new(0, 6), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 4), Position::new(0, 11))));
     }
 
     #[test]
     fn test_find_quote_bounds_single_quote() {
         let lines = vec!["say 'hello' there"];
-        let bounds = TextObject::SingleQuote.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 6));
+        let bounds = TextObject::SingleQuote.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 6), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 5), Position::new(0, 10))));
     }
 
     #[test]
     fn test_find_quote_bounds_backtick() {
         let lines = vec!["say `hello` there"];
-        let bounds = TextObject::BackQuote.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 6));
+        let bounds = TextObject::BackQuote.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 6), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 5), Position::new(0, 10))));
     }
 
     #[test]
     fn test_find_quote_bounds_empty_quotes() {
         let lines = vec!["say \"\" there"];
-        let bounds = TextObject::DoubleQuote.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 5));
+        let bounds = TextObject::DoubleQuote.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 5), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 5), Position::new(0, 5))));
     }
 
     #[test]
     fn test_find_quote_bounds_at_quote_char() {
         let lines = vec!["say \"hello\" there"];
-        let bounds = TextObject::DoubleQuote.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 4));
+        let bounds = TextObject::DoubleQuote.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 4), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 5), Position::new(0, 10))));
     }
 
     #[test]
     fn test_find_quote_bounds_no_quotes() {
         let lines = vec!["say hello there"];
-        let bounds = TextObject::DoubleQuote.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 6));
+        let bounds = TextObject::DoubleQuote.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 6), &WordChars::default());
         assert_eq!(bounds, None);
     }
 
     #[test]
     fn test_find_quote_bounds_unmatched_quote() {
         let lines = vec!["say \"hello there"];
-        let bounds = TextObject::DoubleQuote.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 6));
+        let bounds = TextObject::DoubleQuote.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 6), &WordChars::default());
         assert_eq!(bounds, None);
     }
 
@@ -602,154 +608,154 @@ mod tests {
     #[test]
     fn test_find_bracket_bounds_inner() {
         let lines = vec!["(hello)"];
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 3));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 3), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 1), Position::new(0, 6))));
     }
 
     #[test]
     fn test_find_bracket_bounds_around() {
         let lines = vec!["(hello)"];
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Around, &lines, Position::new(0, 3));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Around, &lines, Position::new(0, 3), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 0), Position::new(0, 7))));
     }
 
     #[test]
     fn test_find_bracket_bounds_square() {
         let lines = vec!["[hello]"];
-        let bounds = TextObject::Brackets.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 3));
+        let bounds = TextObject::Brackets.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 3), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 1), Position::new(0, 6))));
     }
 
     #[test]
     fn test_find_bracket_bounds_curly() {
         let lines = vec!["{hello}"];
-        let bounds = TextObject::Braces.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 3));
+        let bounds = TextObject::Braces.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 3), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 1), Position::new(0, 6))));
     }
 
     #[test]
     fn test_find_bracket_bounds_angle() {
         let lines = vec!["<hello>"];
-        let bounds = TextObject::AngleBrackets.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 3));
+        let bounds = TextObject::AngleBrackets.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 3), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 1), Position::new(0, 6))));
     }
 
     #[test]
     fn test_find_bracket_bounds_nested() {
         let lines = vec!["((inner))"];
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 3));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 3), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 2), Position::new(0, 7))));
     }
 
     #[test]
     fn test_find_bracket_bounds_deeply_nested() {
         let lines = vec!["(((deep)))"];
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 4));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 4), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 3), Position::new(0, 7))));
     }
 
     #[test]
     fn test_find_bracket_bounds_multiline() {
         let lines = vec!["(", "  hello", ")"];
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(1, 3));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(1, 3), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 1), Position::new(2, 0))));
     }
 
     #[test]
     fn test_find_bracket_bounds_multiline_around() {
         let lines = vec!["(", "  hello", ")"];
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Around, &lines, Position::new(1, 3));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Around, &lines, Position::new(1, 3), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 0), Position::new(2, 1))));
     }
 
     #[test]
     fn test_find_bracket_bounds_empty() {
         let lines = vec!["()"];
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 1), Position::new(0, 1))));
     }
 
     #[test]
     fn test_find_bracket_bounds_at_open_bracket() {
         let lines = vec!["(hello)"];
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 1), Position::new(0, 6))));
     }
 
     #[test]
     fn test_find_bracket_bounds_unmatched() {
         let lines = vec!["(hello"];
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 3));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 3), &WordChars::default());
         assert_eq!(bounds, None);
     }
 
     #[test]
     fn test_find_bracket_bounds_no_brackets() {
         let lines = vec!["hello"];
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 2));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 2), &WordChars::default());
         assert_eq!(bounds, None);
     }
 
     #[test]
     fn test_find_bracket_bounds_seek_forward_parentheses() {
         let lines = vec!["foo (bar)"];
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 5), Position::new(0, 8))));
     }
 
     #[test]
     fn test_find_bracket_bounds_seek_forward_braces() {
         let lines = vec!["foo {bar}"];
-        let bounds = TextObject::Braces.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0));
+        let bounds = TextObject::Braces.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 5), Position::new(0, 8))));
     }
 
     #[test]
     fn test_find_bracket_bounds_seek_forward_brackets() {
         let lines = vec!["foo [bar]"];
-        let bounds = TextObject::Brackets.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0));
+        let bounds = TextObject::Brackets.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 5), Position::new(0, 8))));
     }
 
     #[test]
     fn test_find_bracket_bounds_seek_forward_angle() {
         let lines = vec!["foo <bar>"];
-        let bounds = TextObject::AngleBrackets.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0));
+        let bounds = TextObject::AngleBrackets.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 5), Position::new(0, 8))));
     }
 
     #[test]
     fn test_find_bracket_bounds_seek_forward_around() {
         let lines = vec!["foo (bar)"];
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Around, &lines, Position::new(0, 0));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Around, &lines, Position::new(0, 0), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 4), Position::new(0, 9))));
     }
 
     #[test]
     fn test_find_bracket_bounds_seek_mid_line() {
         let lines = vec!["foo bar (baz)"];
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 2));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 2), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 9), Position::new(0, 12))));
     }
 
     #[test]
     fn test_find_bracket_bounds_cursor_after_closed_pair() {
         let lines = vec!["(foo) (bar)"];
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 5));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 5), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 7), Position::new(0, 10))));
     }
 
     #[test]
     fn test_find_bracket_bounds_cursor_after_only_pair() {
         let lines = vec!["(foo) bar"];
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 6));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 6), &WordChars::default());
         assert_eq!(bounds, None);
     }
 
     #[test]
     fn test_find_bracket_bounds_complex_code() {
         let lines = vec!["fn test(a: (i32, i32)) {"];
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 12));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 12), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(0, 12), Position::new(0, 20))));
     }
 
@@ -763,7 +769,7 @@ mod tests {
             "abc(...)",                        // line 2: should still work!
         ];
         // Cursor at start of line 2, should seek forward to find abc(...)
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(2, 0));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(2, 0), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(2, 4), Position::new(2, 7))));
     }
 
@@ -776,7 +782,7 @@ mod tests {
             "abc(foo)",                        // line 1: complete pair
         ];
         // Cursor inside abc(foo), should find that pair, not the unmatched one
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(1, 5));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(1, 5), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(1, 4), Position::new(1, 7))));
     }
 
@@ -789,7 +795,7 @@ mod tests {
             "",                                // line 2
             "valid(content)",                  // line 3
         ];
-        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(3, 7));
+        let bounds = TextObject::Parentheses.find_bounds(TextObjectScope::Inner, &lines, Position::new(3, 7), &WordChars::default());
         assert_eq!(bounds, Some((Position::new(3, 6), Position::new(3, 13))));
     }
 
@@ -798,7 +804,7 @@ mod tests {
     #[test]
     fn test_find_paragraph_bounds_inner() {
         let lines = vec!["line1", "line2", "", "line3"];
-        let bounds = TextObject::Paragraph.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0));
+        let bounds = TextObject::Paragraph.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0), &WordChars::default());
         assert!(bounds.is_some());
         let (start, end) = bounds.unwrap();
         assert_eq!(start.row, 0);
@@ -808,28 +814,28 @@ mod tests {
     #[test]
     fn test_find_paragraph_bounds_around() {
         let lines = vec!["line1", "line2", "", "line3"];
-        let bounds = TextObject::Paragraph.find_bounds(TextObjectScope::Around, &lines, Position::new(0, 0));
+        let bounds = TextObject::Paragraph.find_bounds(TextObjectScope::Around, &lines, Position::new(0, 0), &WordChars::default());
         assert!(bounds.is_some());
     }
 
     #[test]
     fn test_find_paragraph_bounds_single_line() {
         let lines = vec!["only line"];
-        let bounds = TextObject::Paragraph.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0));
+        let bounds = TextObject::Paragraph.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0), &WordChars::default());
         assert!(bounds.is_some());
     }
 
     #[test]
     fn test_find_paragraph_bounds_at_empty_line() {
         let lines = vec!["line1", "", "line2"];
-        let bounds = TextObject::Paragraph.find_bounds(TextObjectScope::Inner, &lines, Position::new(1, 0));
+        let bounds = TextObject::Paragraph.find_bounds(TextObjectScope::Inner, &lines, Position::new(1, 0), &WordChars::default());
         assert!(bounds.is_some());
     }
 
     #[test]
     fn test_find_paragraph_bounds_multiple_paragraphs() {
         let lines = vec!["para1", "", "para2", "para2cont", "", "para3"];
-        let bounds = TextObject::Paragraph.find_bounds(TextObjectScope::Inner, &lines, Position::new(2, 0));
+        let bounds = TextObject::Paragraph.find_bounds(TextObjectScope::Inner, &lines, Position::new(2, 0), &WordChars::default());
         assert!(bounds.is_some());
     }
 
@@ -838,33 +844,37 @@ mod tests {
     #[test]
     fn test_find_bounds_empty_lines() {
         let lines: Vec<&str> = vec![];
-        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0));
+        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 0), &WordChars::default());
         assert_eq!(bounds, None);
     }
 
     #[test]
     fn test_find_bounds_row_out_of_bounds() {
         let lines = vec!["hello"];
-        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(10, 0));
+        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(10, 0), &WordChars::default());
         assert_eq!(bounds, None);
     }
 
     #[test]
     fn test_find_bounds_col_beyond_line() {
         let lines = vec!["hello"];
-        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 100));
+        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 100), &WordChars::default());
         assert!(bounds.is_some());
     }
 
     #[test]
     fn test_find_bounds_whitespace_only() {
         let lines = vec!["   "];
-        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 1));
+        let bounds = TextObject::Word.find_bounds(TextObjectScope::Inner, &lines, Position::new(0, 1), &WordChars::default());
         assert!(bounds.is_some());
     }
 
     // ==================== is_word_char Tests ====================
 
+    fn is_word_char(c: char) -> bool {
+        WordChars::default().is_word_char(c)
+    }
+
     #[test]
     fn test_is_word_char_letters() {
         assert!(is_word_char('a'));